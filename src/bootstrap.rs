@@ -0,0 +1,297 @@
+//! Quick setup of an instance, device, queues and swapchain with reasonable defaults.
+//!
+//! `quick_init` is intended for samples and prototypes. It selects the first physical device (preferring discrete GPUs) on which
+//! `QueueFamilyPlan` finds graphics and, if a surface is given, present queue families, creates a device with one queue from
+//! each planned family and negotiates a swapchain using `SurfaceNegotiator`.
+//!
+//! When no queue family supports both graphics and presentation, a separate present queue is created. The swapchain images are then
+//! shared concurrently by both families unless `exclusive_swapchain_images` is set. `BootstrapContext::present_handoff` creates the
+//! `PresentHandoff` that takes care of the semaphore handoff and ownership transfer between the queues in either case.
+//!
+//! Since a surface requires an instance, windowed applications first create the instance using `create_instance`
+//! with the surface extensions of their platform and then pass the surface to `quick_init`:
+//!
+//! ```no_run
+//! # use vulkayes_core::prelude::*;
+//! # use vulkayes_core::bootstrap::{self, BootstrapOptions};
+//! # fn f(create_surface: impl FnOnce(Vrc<Instance>) -> Surface) -> Result<(), bootstrap::BootstrapError> {
+//! let surface_extensions = [ash::extensions::khr::Surface::name()];
+//! let instance = bootstrap::create_instance(&BootstrapOptions { instance_extensions: &surface_extensions, ..Default::default() })?;
+//! let surface = create_surface(instance);
+//!
+//! let context = bootstrap::quick_init(Some(surface), Default::default())?;
+//! let swapchain = context.swapchain.unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{ffi::CStr, fmt, num::NonZeroU32};
+
+use ash::{extensions::ext::DebugUtils, vk};
+use thiserror::Error;
+
+use crate::{
+	device::error::DeviceError,
+	instance::{
+		debug::DebugCallback,
+		error::{InstanceError, PhysicalDeviceEnumerationError}
+	},
+	prelude::{Device, DeviceBuilder, Entry, HostMemoryAllocator, Instance, PhysicalDevice, SharingMode, Surface, Swapchain, Vrc},
+	queue::{
+		error::QueueFamilyPlanError,
+		plan::{PlannedQueues, QueueFamilyPlan},
+		sharing_mode::SharingModeError
+	},
+	surface::{
+		error::SurfaceQueryError,
+		negotiate::{SurfaceNegotiator, VsyncPreference}
+	},
+	swapchain::{
+		error::{PresentHandoffError, SwapchainError},
+		handoff::PresentHandoff,
+		SwapchainData
+	}
+};
+#[cfg(feature = "profiles")]
+use crate::device::profile::DeviceProfile;
+
+#[derive(Error, Debug)]
+pub enum BootstrapError {
+	#[error("Could not load Vulkan entry")]
+	Loading(#[from] ash::LoadingError),
+	#[error("Could not create instance")]
+	Instance(#[from] InstanceError),
+	#[error("Could not enumerate physical devices")]
+	PhysicalDeviceEnumeration(#[from] PhysicalDeviceEnumerationError),
+	#[error("No physical device with the required queue families was found")]
+	NoSuitableDevice,
+	#[error("Could not create device")]
+	Device(#[from] DeviceError),
+	#[error("Could not select queues")]
+	QueueFamilyPlan(#[from] QueueFamilyPlanError),
+	#[error("Could not query surface parameters")]
+	SurfaceQuery(#[from] SurfaceQueryError),
+	#[error("Surface supports no formats or the window size is zero")]
+	SurfaceUnsupported,
+	#[error("Could not create swapchain sharing mode")]
+	SharingMode(#[from] SharingModeError),
+	#[error("Could not create swapchain")]
+	Swapchain(#[from] SwapchainError)
+}
+
+/// Options of `create_instance` and `quick_init`.
+pub struct BootstrapOptions<'a> {
+	/// Additional instance extensions, such as the surface extensions of the platform.
+	pub instance_extensions: &'a [&'a CStr],
+	/// Additional device extensions. `VK_KHR_swapchain` is enabled automatically when a surface is given.
+	pub device_extensions: &'a [&'a CStr],
+	/// Enables `VK_EXT_debug_utils` and registers the default debug callback.
+	pub debug: bool,
+	/// Profile the selected physical device must support, its extensions and features are enabled on the device.
+	#[cfg(feature = "profiles")]
+	pub profile: Option<&'a DeviceProfile<'a>>,
+
+	/// Preferred surface formats in order of preference, see `SurfaceNegotiator::choose_surface_format`.
+	pub format_preferences: &'a [vk::SurfaceFormatKHR],
+	pub vsync_preference: VsyncPreference,
+	/// Size of the window, used when the surface does not dictate its extent.
+	pub window_size: [NonZeroU32; 2],
+	/// Desired number of swapchain images.
+	pub image_count: NonZeroU32,
+	pub image_usage: vk::ImageUsageFlags,
+	/// Creates the swapchain images owned by the graphics family even when presentation uses another family.
+	///
+	/// Otherwise the images are shared concurrently between both families. Exclusive images must be transferred to the present family
+	/// before presentation, see `PresentHandoff`.
+	pub exclusive_swapchain_images: bool
+}
+impl Default for BootstrapOptions<'_> {
+	fn default() -> Self {
+		BootstrapOptions {
+			instance_extensions: &[],
+			device_extensions: &[],
+			debug: cfg!(debug_assertions),
+			#[cfg(feature = "profiles")]
+			profile: None,
+			format_preferences: &[vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_SRGB, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR }],
+			vsync_preference: VsyncPreference::Vsync,
+			window_size: [NonZeroU32::new(800).unwrap(), NonZeroU32::new(600).unwrap()],
+			image_count: NonZeroU32::new(3).unwrap(),
+			image_usage: vk::ImageUsageFlags::COLOR_ATTACHMENT,
+			exclusive_swapchain_images: false
+		}
+	}
+}
+impl fmt::Debug for BootstrapOptions<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut d = f.debug_struct("BootstrapOptions");
+		d.field("instance_extensions", &self.instance_extensions)
+			.field("device_extensions", &self.device_extensions)
+			.field("debug", &self.debug);
+		#[cfg(feature = "profiles")]
+		d.field("profile", &self.profile);
+		d.field("format_preferences", &self.format_preferences)
+			.field("vsync_preference", &self.vsync_preference)
+			.field("window_size", &self.window_size)
+			.field("image_count", &self.image_count)
+			.field("image_usage", &self.image_usage)
+			.field(
+				"exclusive_swapchain_images",
+				&self.exclusive_swapchain_images
+			)
+			.finish()
+	}
+}
+
+/// Objects created by `quick_init`.
+#[derive(Debug)]
+pub struct BootstrapContext {
+	pub instance: Vrc<Instance>,
+	pub device: Vrc<Device>,
+	pub queues: PlannedQueues,
+	/// Swapchain and its images, `None` if no surface was given.
+	pub swapchain: Option<SwapchainData>
+}
+impl BootstrapContext {
+	/// Creates a `PresentHandoff` from the graphics queue to the present queue for the swapchain, `None` if no surface was given.
+	///
+	/// `render_layout` is the layout the swapchain images are in when rendering finishes.
+	pub fn present_handoff(&self, render_layout: vk::ImageLayout) -> Result<Option<PresentHandoff>, PresentHandoffError> {
+		let swapchain = match self.swapchain {
+			None => return Ok(None),
+			Some(ref swapchain) => swapchain
+		};
+
+		let handoff = PresentHandoff::new(
+			swapchain,
+			self.queues.graphics.clone(),
+			self.queues.present_queue().clone(),
+			render_layout,
+			HostMemoryAllocator::Unspecified()
+		)?;
+
+		Ok(Some(handoff))
+	}
+}
+
+/// Loads the Vulkan entry and creates an instance with the highest supported api version, see `Instance::minimal`.
+///
+/// Only `instance_extensions` and `debug` of `options` are used.
+pub fn create_instance(options: &BootstrapOptions) -> Result<Vrc<Instance>, BootstrapError> {
+	let entry = Entry::new()?;
+
+	let mut extensions = options.instance_extensions.to_vec();
+	let debug_callback = if options.debug {
+		extensions.push(DebugUtils::name());
+		DebugCallback::Default()
+	} else {
+		DebugCallback::None()
+	};
+
+	let instance = Instance::minimal(entry, extensions, debug_callback)?;
+
+	Ok(instance)
+}
+
+/// Creates a device, queues and, if `window_surface` is given, a swapchain, see the module documentation.
+///
+/// The instance of `window_surface` is used if given, otherwise a new instance is created using `create_instance`.
+pub fn quick_init(window_surface: Option<Surface>, options: BootstrapOptions) -> Result<BootstrapContext, BootstrapError> {
+	let instance = match window_surface {
+		Some(ref surface) => surface.instance().clone(),
+		None => create_instance(&options)?
+	};
+
+	let (physical_device, plan) = select_physical_device(&instance, window_surface.as_ref(), &options)?;
+
+	let mut device_extensions = options.device_extensions.to_vec();
+	if window_surface.is_some() {
+		device_extensions.push(ash::extensions::khr::Swapchain::name());
+	}
+	#[allow(unused_mut)]
+	let mut builder = DeviceBuilder::new(physical_device.clone())
+		.queues(plan.queue_create_infos())
+		.extensions(device_extensions);
+	#[cfg(feature = "profiles")]
+	{
+		if let Some(profile) = options.profile {
+			builder = builder.profile(profile);
+		}
+	}
+	let device_data = builder.build()?;
+	let queues = plan.queues(&device_data)?;
+
+	let swapchain = match window_surface {
+		None => None,
+		Some(surface) => {
+			let negotiator = SurfaceNegotiator::query(&surface, &physical_device)?;
+
+			let mut families = vec![plan.graphics()];
+			if let Some(present) = plan.present().filter(|&present| present != plan.graphics()) {
+				families.push(present);
+			}
+
+			let create_info = negotiator
+				.swapchain_create_info(
+					options.format_preferences,
+					options.vsync_preference,
+					options.window_size,
+					options.image_count,
+					options.image_usage,
+					SharingMode::new(families)?
+				)
+				.ok_or(BootstrapError::SurfaceUnsupported)?;
+
+			Some(Swapchain::new(
+				device_data.device.clone(),
+				surface,
+				create_info,
+				HostMemoryAllocator::Unspecified()
+			)?)
+		}
+	};
+
+	Ok(BootstrapContext { instance, device: device_data.device, queues, swapchain })
+}
+
+/// Returns the first physical device with a valid queue family plan and supporting the profile of `options`, preferring discrete GPUs.
+#[allow(unused_variables)]
+fn select_physical_device(
+	instance: &Vrc<Instance>,
+	surface: Option<&Surface>,
+	options: &BootstrapOptions
+) -> Result<(PhysicalDevice, QueueFamilyPlan), BootstrapError> {
+	let mut candidates: Vec<(PhysicalDevice, QueueFamilyPlan)> = instance
+		.physical_devices()?
+		.filter_map(|physical_device| {
+			#[cfg(feature = "profiles")]
+			{
+				if let Some(profile) = options.profile {
+					if let Err(err) = profile.check(&physical_device) {
+						log::debug!(
+							"Physical device {} does not support profile {}: {}",
+							physical_device.properties().device_name,
+							profile.name,
+							err
+						);
+						return None
+					}
+				}
+			}
+
+			let plan = QueueFamilyPlan::new(&physical_device, surface).ok()?;
+
+			Some((physical_device, plan))
+		})
+		.collect();
+	// Stable sort keeps the enumeration order among devices of the same type
+	candidates.sort_by_key(|(physical_device, _)| physical_device.properties().device_type != vk::PhysicalDeviceType::DISCRETE_GPU);
+
+	let selected = candidates.into_iter().next().ok_or(BootstrapError::NoSuitableDevice)?;
+	log::debug!(
+		"Selected physical device {}",
+		selected.0.properties().device_name
+	);
+
+	Ok(selected)
+}
@@ -10,7 +10,6 @@ use crate::{
 };
 
 pub mod recording;
-// pub mod clear;
 // pub mod control;
 // pub mod render_pass;
 // pub mod bind;
@@ -20,6 +19,7 @@ pub struct CommandBuffer {
 	command_buffer: Vutex<vk::CommandBuffer>
 }
 impl CommandBuffer {
+	#[track_caller]
 	pub fn new<const BUFFERS: usize>(pool: Vrc<CommandPool>, secondary: bool) -> Result<[Vrc<Self>; BUFFERS], CommandBufferError> {
 		let raw = pool.allocate_command_buffers::<BUFFERS>(secondary)?;
 
@@ -70,7 +70,8 @@ impl CommandBuffer {
 	///
 	/// ### Panic
 	///
-	/// This function will panic if the pool or the buffer vutex cannot be locked.
+	/// This function will panic if the pool or the buffer vutex cannot be locked, see `CommandBufferRecordingLockCommon::new`.
+	#[track_caller]
 	pub fn begin_recording(
 		&self,
 		info: recording::CommandBufferBeginInfo
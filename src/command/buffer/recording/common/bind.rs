@@ -1,6 +1,9 @@
 use ash::vk;
 
-use crate::prelude::{Buffer, GraphicsPipeline, ComputePipeline, HasHandle, PipelineLayout, PushConstantsTrait, SafeHandle, Transparent};
+use crate::{
+	command::error::{BindBuffersError, BindDescriptorSetsError},
+	prelude::{Buffer, ComputePipeline, DescriptorSet, GraphicsPipeline, HasHandle, PipelineLayout, PushConstantsTrait}
+};
 
 impl<'a> super::CommandBufferRecordingLockCommon<'a> {
 	pub fn bind_graphics_pipeline(&self, pipeline: &GraphicsPipeline) {
@@ -33,14 +36,52 @@ impl<'a> super::CommandBufferRecordingLockCommon<'a> {
 		}
 	}
 
+	#[cfg(feature = "ray_tracing")]
+	pub fn bind_ray_tracing_pipeline(&self, pipeline: &crate::raytracing::pipeline::RayTracingPipeline) {
+		log_trace_common!(
+			"Binding ray tracing pipeline:",
+			crate::util::fmt::format_handle(self.handle()),
+			pipeline
+		);
+		unsafe {
+			self.device().cmd_bind_pipeline(
+				self.handle(),
+				vk::PipelineBindPoint::RAY_TRACING_KHR,
+				pipeline.handle()
+			)
+		}
+	}
+
+	/// Binds `descriptor_sets` starting at `first_set`.
+	///
+	/// Under runtime validations, the layouts of `descriptor_sets` are checked to be compatible with the set layouts of `layout`.
 	pub fn bind_descriptor_sets<'d>(
 		&self,
 		bind_point: vk::PipelineBindPoint,
 		layout: &PipelineLayout,
 		first_set: u32,
-		descriptor_sets: impl AsRef<[SafeHandle<'d, vk::DescriptorSet>]>,
+		descriptor_sets: impl AsRef<[&'d DescriptorSet]>,
 		dynamic_offsets: impl AsRef<[u32]>
-	) {
+	) -> Result<(), BindDescriptorSetsError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			for (set, descriptor_set) in (first_set ..).zip(descriptor_sets.as_ref().iter()) {
+				let set_layout = layout
+					.set_layouts()
+					.get(set as usize)
+					.ok_or(BindDescriptorSetsError::SetIndexOutOfRange(set))?;
+
+				if !set_layout.is_compatible_with(descriptor_set.layout()) {
+					return Err(BindDescriptorSetsError::IncompatibleSetLayout(set))
+				}
+			}
+		}
+
+		let descriptor_sets_raw = collect_iter_faster!(
+			descriptor_sets.as_ref().iter().map(|s| s.handle()),
+			4
+		);
+
 		log_trace_common!(
 			"Binding descriptor sets:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -56,10 +97,12 @@ impl<'a> super::CommandBufferRecordingLockCommon<'a> {
 				bind_point,
 				layout.handle(),
 				first_set,
-				Transparent::transmute_slice(descriptor_sets.as_ref()),
+				descriptor_sets_raw.as_slice(),
 				dynamic_offsets.as_ref()
 			)
 		}
+
+		Ok(())
 	}
 
 	pub fn push_constants<P: PushConstantsTrait>(&self, layout: &PipelineLayout, value: &P) {
@@ -84,30 +127,58 @@ impl<'a> super::CommandBufferRecordingLockCommon<'a> {
 		}
 	}
 
-	pub fn bind_vertex_buffers<'b>(
-		&self,
-		first_binding: u32,
-		buffers: impl AsRef<[SafeHandle<'b, vk::Buffer>]>,
-		offsets: impl AsRef<[vk::DeviceSize]>
-	) {
+	/// Binds `buffers` with their offsets as vertex buffers starting at `first_binding`.
+	///
+	/// Under runtime validations, the buffers are checked to be from the same device as the command buffer,
+	/// to have the `VERTEX_BUFFER` usage and for the offsets to be in bounds.
+	pub fn bind_vertex_buffers(&self, first_binding: u32, buffers: &[(&Buffer, vk::DeviceSize)]) -> Result<(), BindBuffersError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			for (index, &(buffer, offset)) in buffers.iter().enumerate() {
+				self.validate_bound_buffer(index, buffer, offset, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+			}
+		}
+
+		let buffers_raw = collect_iter_faster!(buffers.iter().map(|(buffer, _)| buffer.handle()), 4);
+		let offsets = collect_iter_faster!(buffers.iter().map(|&(_, offset)| offset), 4);
+
 		log_trace_common!(
 			"Binding vertex buffers:",
 			crate::util::fmt::format_handle(self.handle()),
 			first_binding,
-			buffers.as_ref(),
-			offsets.as_ref()
+			buffers
 		);
 		unsafe {
 			self.device().cmd_bind_vertex_buffers(
 				self.handle(),
 				first_binding,
-				Transparent::transmute_slice(buffers.as_ref()),
-				offsets.as_ref()
+				buffers_raw.as_slice(),
+				offsets.as_slice()
 			)
 		}
+
+		Ok(())
 	}
 
-	pub fn bind_index_buffer(&self, buffer: &Buffer, offset: vk::DeviceSize, index_type: vk::IndexType) {
+	/// Binds `buffer` as the index buffer.
+	///
+	/// Under runtime validations, the buffer is checked to be from the same device as the command buffer,
+	/// to have the `INDEX_BUFFER` usage and for `offset` to be in bounds and a multiple of the size of `index_type`.
+	pub fn bind_index_buffer(&self, buffer: &Buffer, offset: vk::DeviceSize, index_type: vk::IndexType) -> Result<(), BindBuffersError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			self.validate_bound_buffer(0, buffer, offset, vk::BufferUsageFlags::INDEX_BUFFER)?;
+
+			let index_size = match index_type {
+				vk::IndexType::UINT32 => 4,
+				vk::IndexType::UINT16 => 2,
+				_ => 1
+			};
+			if offset % index_size != 0 {
+				return Err(BindBuffersError::IndexOffsetNotAligned)
+			}
+		}
+
 		log_trace_common!(
 			"Binding index buffer:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -123,5 +194,22 @@ impl<'a> super::CommandBufferRecordingLockCommon<'a> {
 				index_type
 			)
 		}
+
+		Ok(())
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn validate_bound_buffer(&self, index: usize, buffer: &Buffer, offset: vk::DeviceSize, usage: vk::BufferUsageFlags) -> Result<(), BindBuffersError> {
+		if buffer.device() != self.device() {
+			return Err(BindBuffersError::DeviceMismatch(index))
+		}
+		if !buffer.usage().contains(usage) {
+			return Err(BindBuffersError::UsageMissing(index, usage))
+		}
+		if offset >= buffer.size().get() {
+			return Err(BindBuffersError::OffsetOutOfBounds(index))
+		}
+
+		Ok(())
 	}
 }
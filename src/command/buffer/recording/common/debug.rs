@@ -0,0 +1,58 @@
+use std::{ffi::CStr, ops::Deref};
+
+use ash::vk;
+
+/// Debug label commands.
+///
+/// These commands do nothing if the `VK_EXT_debug_utils` loader is not available on the instance, see `Instance::debug_utils`.
+impl<'a> super::CommandBufferRecordingLockCommon<'a> {
+	/// Opens a debug label region with `name` and `color`.
+	///
+	/// Must be closed by `end_debug_label` in the same command buffer.
+	pub fn begin_debug_label(&self, name: &CStr, color: [f32; 4]) {
+		if let Some(loader) = self.device().instance().debug_utils() {
+			let label = vk::DebugUtilsLabelEXT::builder().label_name(name).color(color);
+
+			log_trace_common!(
+				"Beginning debug label:",
+				crate::util::fmt::format_handle(self.handle()),
+				label.deref()
+			);
+			unsafe { loader.cmd_begin_debug_utils_label(self.handle(), &label) }
+		}
+	}
+
+	/// Closes the debug label region opened by the last `begin_debug_label`.
+	pub fn end_debug_label(&self) {
+		if let Some(loader) = self.device().instance().debug_utils() {
+			log_trace_common!(
+				"Ending debug label:",
+				crate::util::fmt::format_handle(self.handle())
+			);
+			unsafe { loader.cmd_end_debug_utils_label(self.handle()) }
+		}
+	}
+
+	/// Inserts a single debug label with `name` and `color`.
+	pub fn insert_debug_label(&self, name: &CStr, color: [f32; 4]) {
+		if let Some(loader) = self.device().instance().debug_utils() {
+			let label = vk::DebugUtilsLabelEXT::builder().label_name(name).color(color);
+
+			log_trace_common!(
+				"Inserting debug label:",
+				crate::util::fmt::format_handle(self.handle()),
+				label.deref()
+			);
+			unsafe { loader.cmd_insert_debug_utils_label(self.handle(), &label) }
+		}
+	}
+
+	/// Opens a debug label region with `name` and `color` and records it in `capture` if a capture is in progress.
+	///
+	/// Must be closed by `end_debug_label` in the same command buffer.
+	#[cfg(feature = "renderdoc")]
+	pub fn begin_capture_region(&self, capture: &crate::instance::capture::CaptureControl, name: &CStr, color: [f32; 4]) {
+		capture.record_region(name);
+		self.begin_debug_label(name, color);
+	}
+}
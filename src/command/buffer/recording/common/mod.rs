@@ -3,7 +3,10 @@ use ash::vk;
 use crate::prelude::{CommandBuffer, Device, HasSynchronizedHandle, Vrc, VutexGuard};
 
 pub mod bind;
+pub mod debug;
 pub mod set;
+#[cfg(feature = "shader_object")]
+pub mod shader_object;
 
 /// Wrapper around `VutexGuard` and `CommandBuffer` reference that provides safe command recording functions.
 #[derive(Debug)]
@@ -16,14 +19,21 @@ pub struct CommandBufferRecordingLockCommon<'a> {
 impl<'a> CommandBufferRecordingLockCommon<'a> {
 	/// ### Panic
 	///
-	/// This function will panic if the pool or the buffer vutex cannot be locked.
+	/// * This function will panic if the pool or the buffer vutex cannot be locked.
+	/// * In debug builds, this function will panic if the pool is already locked by another recording on the current thread.
+	#[track_caller]
 	pub fn new(command_buffer: &'a CommandBuffer) -> Self {
-		let pool_lock = command_buffer.pool().lock_handle();
+		let pool_lock = command_buffer.pool().lock_for_recording();
 		let lock = command_buffer.lock_handle();
 
 		CommandBufferRecordingLockCommon { pool_lock, lock, buffer: command_buffer }
 	}
 
+	/// Returns the command buffer being recorded.
+	pub const fn command_buffer(&self) -> &'a CommandBuffer {
+		self.buffer
+	}
+
 	pub(super) fn handle(&self) -> vk::CommandBuffer {
 		*self.lock
 	}
@@ -36,3 +46,8 @@ impl<'a> CommandBufferRecordingLockCommon<'a> {
 		self.buffer.pool().device()
 	}
 }
+impl<'a> Drop for CommandBufferRecordingLockCommon<'a> {
+	fn drop(&mut self) {
+		self.buffer.pool().recording_finished();
+	}
+}
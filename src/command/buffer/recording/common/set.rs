@@ -16,4 +16,138 @@ impl<'a> super::CommandBufferRecordingLockCommon<'a> {
 			)
 		}
 	}
+
+	pub fn set_scissors(&self, first_scissor: u32, scissors: impl AsRef<[vk::Rect2D]>) {
+		log_trace_common!(
+			"Setting scissors:",
+			crate::util::fmt::format_handle(self.handle()),
+			first_scissor,
+			scissors.as_ref()
+		);
+		unsafe {
+			self.device().cmd_set_scissor(
+				self.handle(),
+				first_scissor,
+				scissors.as_ref()
+			)
+		}
+	}
+
+	pub fn set_line_width(&self, line_width: f32) {
+		log_trace_common!(
+			"Setting line width:",
+			crate::util::fmt::format_handle(self.handle()),
+			line_width
+		);
+		unsafe { self.device().cmd_set_line_width(self.handle(), line_width) }
+	}
+
+	pub fn set_depth_bias(&self, constant_factor: f32, clamp: f32, slope_factor: f32) {
+		log_trace_common!(
+			"Setting depth bias:",
+			crate::util::fmt::format_handle(self.handle()),
+			constant_factor,
+			clamp,
+			slope_factor
+		);
+		unsafe {
+			self.device().cmd_set_depth_bias(
+				self.handle(),
+				constant_factor,
+				clamp,
+				slope_factor
+			)
+		}
+	}
+
+	pub fn set_blend_constants(&self, blend_constants: [f32; 4]) {
+		log_trace_common!(
+			"Setting blend constants:",
+			crate::util::fmt::format_handle(self.handle()),
+			blend_constants
+		);
+		unsafe {
+			self.device()
+				.cmd_set_blend_constants(self.handle(), &blend_constants)
+		}
+	}
+
+	pub fn set_depth_bounds(&self, min_depth_bounds: f32, max_depth_bounds: f32) {
+		log_trace_common!(
+			"Setting depth bounds:",
+			crate::util::fmt::format_handle(self.handle()),
+			min_depth_bounds,
+			max_depth_bounds
+		);
+		unsafe {
+			self.device().cmd_set_depth_bounds(
+				self.handle(),
+				min_depth_bounds,
+				max_depth_bounds
+			)
+		}
+	}
+
+	pub fn set_stencil_compare_mask(&self, face_mask: vk::StencilFaceFlags, compare_mask: u32) {
+		log_trace_common!(
+			"Setting stencil compare mask:",
+			crate::util::fmt::format_handle(self.handle()),
+			face_mask,
+			compare_mask
+		);
+		unsafe {
+			self.device().cmd_set_stencil_compare_mask(
+				self.handle(),
+				face_mask,
+				compare_mask
+			)
+		}
+	}
+
+	pub fn set_stencil_write_mask(&self, face_mask: vk::StencilFaceFlags, write_mask: u32) {
+		log_trace_common!(
+			"Setting stencil write mask:",
+			crate::util::fmt::format_handle(self.handle()),
+			face_mask,
+			write_mask
+		);
+		unsafe {
+			self.device().cmd_set_stencil_write_mask(
+				self.handle(),
+				face_mask,
+				write_mask
+			)
+		}
+	}
+
+	pub fn set_stencil_reference(&self, face_mask: vk::StencilFaceFlags, reference: u32) {
+		log_trace_common!(
+			"Setting stencil reference:",
+			crate::util::fmt::format_handle(self.handle()),
+			face_mask,
+			reference
+		);
+		unsafe {
+			self.device().cmd_set_stencil_reference(
+				self.handle(),
+				face_mask,
+				reference
+			)
+		}
+	}
+
+	/// Sets the device mask for subsequent commands, see the `instance::device_group` module.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn set_device_mask(&self, device_mask: u32) {
+		log_trace_common!(
+			"Setting device mask:",
+			crate::util::fmt::format_handle(self.handle()),
+			device_mask
+		);
+		unsafe { self.device().cmd_set_device_mask(self.handle(), device_mask) }
+	}
 }
@@ -0,0 +1,288 @@
+use ash::vk;
+
+use crate::{
+	prelude::HasHandle,
+	shader::object::{ColorBlendEquationEXT, ShaderObject, ShaderObjectFn}
+};
+
+/// Shader object commands.
+///
+/// `VK_EXT_shader_object` must be enabled on the device, see the `shader::object` module.
+impl<'a> super::CommandBufferRecordingLockCommon<'a> {
+	fn shader_object_fn(&self) -> &ShaderObjectFn {
+		self.device().ext().shader_object()
+	}
+
+	/// Binds `shaders` to their stages. `None` unbinds the shader of the stage.
+	pub fn bind_shader_objects(&self, shaders: &[(vk::ShaderStageFlags, Option<&ShaderObject>)]) {
+		debug_assert!(shaders
+			.iter()
+			.all(|(stage, shader)| shader.map(|s| s.stage() == *stage && s.device() == self.device()).unwrap_or(true)));
+
+		let stages: Vec<_> = shaders.iter().map(|(stage, _)| *stage).collect();
+		let handles: Vec<_> = shaders.iter().map(|(_, shader)| shader.map(|s| s.handle()).unwrap_or_default()).collect();
+
+		log_trace_common!(
+			"Binding shader objects:",
+			crate::util::fmt::format_handle(self.handle()),
+			stages,
+			handles.iter().map(|h| crate::util::fmt::format_handle(*h)).collect::<Vec<_>>()
+		);
+		unsafe {
+			(self.shader_object_fn().cmd_bind_shaders)(
+				self.handle(),
+				stages.len() as u32,
+				stages.as_ptr(),
+				handles.as_ptr()
+			)
+		}
+	}
+
+	/// Sets viewports and their count.
+	pub fn set_viewports_with_count(&self, viewports: impl AsRef<[vk::Viewport]>) {
+		let viewports = viewports.as_ref();
+		log_trace_common!(
+			"Setting viewports with count:",
+			crate::util::fmt::format_handle(self.handle()),
+			viewports
+		);
+		unsafe {
+			(self.shader_object_fn().cmd_set_viewport_with_count)(
+				self.handle(),
+				viewports.len() as u32,
+				viewports.as_ptr()
+			)
+		}
+	}
+
+	/// Sets scissors and their count.
+	pub fn set_scissors_with_count(&self, scissors: impl AsRef<[vk::Rect2D]>) {
+		let scissors = scissors.as_ref();
+		log_trace_common!(
+			"Setting scissors with count:",
+			crate::util::fmt::format_handle(self.handle()),
+			scissors
+		);
+		unsafe {
+			(self.shader_object_fn().cmd_set_scissor_with_count)(
+				self.handle(),
+				scissors.len() as u32,
+				scissors.as_ptr()
+			)
+		}
+	}
+
+	pub fn set_rasterizer_discard_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_rasterizer_discard_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_primitive_topology(&self, topology: vk::PrimitiveTopology) {
+		unsafe { (self.shader_object_fn().cmd_set_primitive_topology)(self.handle(), topology) }
+	}
+
+	pub fn set_primitive_restart_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_primitive_restart_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	/// Sets vertex input bindings and attributes, which replace the vertex input state of a pipeline.
+	pub fn set_vertex_input(
+		&self,
+		bindings: &[vk::VertexInputBindingDescription2EXT],
+		attributes: &[vk::VertexInputAttributeDescription2EXT]
+	) {
+		log_trace_common!(
+			"Setting vertex input:",
+			crate::util::fmt::format_handle(self.handle()),
+			bindings,
+			attributes
+		);
+		unsafe {
+			(self.shader_object_fn().cmd_set_vertex_input)(
+				self.handle(),
+				bindings.len() as u32,
+				bindings.as_ptr(),
+				attributes.len() as u32,
+				attributes.as_ptr()
+			)
+		}
+	}
+
+	pub fn set_polygon_mode(&self, polygon_mode: vk::PolygonMode) {
+		unsafe { (self.shader_object_fn().cmd_set_polygon_mode)(self.handle(), polygon_mode) }
+	}
+
+	pub fn set_cull_mode(&self, cull_mode: vk::CullModeFlags) {
+		unsafe { (self.shader_object_fn().cmd_set_cull_mode)(self.handle(), cull_mode) }
+	}
+
+	pub fn set_front_face(&self, front_face: vk::FrontFace) {
+		unsafe { (self.shader_object_fn().cmd_set_front_face)(self.handle(), front_face) }
+	}
+
+	pub fn set_depth_bias_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_depth_bias_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_rasterization_samples(&self, samples: vk::SampleCountFlags) {
+		unsafe { (self.shader_object_fn().cmd_set_rasterization_samples)(self.handle(), samples) }
+	}
+
+	/// `sample_mask` must contain `ceil(samples / 32)` elements.
+	pub fn set_sample_mask(&self, samples: vk::SampleCountFlags, sample_mask: &[vk::SampleMask]) {
+		debug_assert!(sample_mask.len() * 32 >= samples.as_raw() as usize);
+
+		unsafe { (self.shader_object_fn().cmd_set_sample_mask)(self.handle(), samples, sample_mask.as_ptr()) }
+	}
+
+	pub fn set_alpha_to_coverage_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_alpha_to_coverage_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_depth_test_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_depth_test_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_depth_write_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_depth_write_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_depth_compare_op(&self, compare_op: vk::CompareOp) {
+		unsafe { (self.shader_object_fn().cmd_set_depth_compare_op)(self.handle(), compare_op) }
+	}
+
+	pub fn set_depth_bounds_test_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_depth_bounds_test_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_stencil_test_enable(&self, enable: bool) {
+		unsafe { (self.shader_object_fn().cmd_set_stencil_test_enable)(self.handle(), enable as vk::Bool32) }
+	}
+
+	pub fn set_stencil_op(
+		&self,
+		face_mask: vk::StencilFaceFlags,
+		fail_op: vk::StencilOp,
+		pass_op: vk::StencilOp,
+		depth_fail_op: vk::StencilOp,
+		compare_op: vk::CompareOp
+	) {
+		unsafe {
+			(self.shader_object_fn().cmd_set_stencil_op)(
+				self.handle(),
+				face_mask,
+				fail_op,
+				pass_op,
+				depth_fail_op,
+				compare_op
+			)
+		}
+	}
+
+	/// Enables or disables blending for color attachments starting at `first_attachment`.
+	pub fn set_color_blend_enable(&self, first_attachment: u32, enables: &[bool]) {
+		let enables: Vec<vk::Bool32> = enables.iter().map(|&enable| enable as vk::Bool32).collect();
+
+		unsafe {
+			(self.shader_object_fn().cmd_set_color_blend_enable)(
+				self.handle(),
+				first_attachment,
+				enables.len() as u32,
+				enables.as_ptr()
+			)
+		}
+	}
+
+	/// Sets blend equations for color attachments starting at `first_attachment`.
+	pub fn set_color_blend_equation(&self, first_attachment: u32, equations: &[ColorBlendEquationEXT]) {
+		unsafe {
+			(self.shader_object_fn().cmd_set_color_blend_equation)(
+				self.handle(),
+				first_attachment,
+				equations.len() as u32,
+				equations.as_ptr()
+			)
+		}
+	}
+
+	/// Sets write masks for color attachments starting at `first_attachment`.
+	pub fn set_color_write_mask(&self, first_attachment: u32, masks: &[vk::ColorComponentFlags]) {
+		unsafe {
+			(self.shader_object_fn().cmd_set_color_write_mask)(
+				self.handle(),
+				first_attachment,
+				masks.len() as u32,
+				masks.as_ptr()
+			)
+		}
+	}
+
+	/// Sets all dynamic state required to draw with shader objects to defaults equivalent to a simple pipeline.
+	///
+	/// The defaults are a single viewport and scissor covering `render_area`, triangle lists without primitive restart,
+	/// filled polygons without culling, single sampling, no depth or stencil tests and no blending with all components written
+	/// for `color_attachment_count` attachments. Vertex input is empty. Individual states can be overridden afterwards.
+	pub fn set_shader_object_defaults(&self, render_area: vk::Rect2D, color_attachment_count: u32) {
+		log_trace_common!(
+			"Setting shader object defaults:",
+			crate::util::fmt::format_handle(self.handle()),
+			render_area,
+			color_attachment_count
+		);
+
+		self.set_viewports_with_count([vk::Viewport {
+			x: render_area.offset.x as f32,
+			y: render_area.offset.y as f32,
+			width: render_area.extent.width as f32,
+			height: render_area.extent.height as f32,
+			min_depth: 0.0,
+			max_depth: 1.0
+		}]);
+		self.set_scissors_with_count([render_area]);
+		self.set_rasterizer_discard_enable(false);
+
+		self.set_primitive_topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+		self.set_primitive_restart_enable(false);
+		self.set_vertex_input(&[], &[]);
+
+		self.set_polygon_mode(vk::PolygonMode::FILL);
+		self.set_cull_mode(vk::CullModeFlags::NONE);
+		self.set_front_face(vk::FrontFace::COUNTER_CLOCKWISE);
+		self.set_depth_bias_enable(false);
+		self.set_rasterization_samples(vk::SampleCountFlags::TYPE_1);
+		self.set_sample_mask(vk::SampleCountFlags::TYPE_1, &[!0]);
+		self.set_alpha_to_coverage_enable(false);
+
+		self.set_depth_test_enable(false);
+		self.set_depth_write_enable(false);
+		self.set_depth_compare_op(vk::CompareOp::LESS_OR_EQUAL);
+		self.set_depth_bounds_test_enable(false);
+		self.set_stencil_test_enable(false);
+		self.set_stencil_op(
+			vk::StencilFaceFlags::FRONT_AND_BACK,
+			vk::StencilOp::KEEP,
+			vk::StencilOp::KEEP,
+			vk::StencilOp::KEEP,
+			vk::CompareOp::ALWAYS
+		);
+
+		if color_attachment_count > 0 {
+			let count = color_attachment_count as usize;
+			self.set_color_blend_enable(0, &vec![false; count]);
+			self.set_color_blend_equation(
+				0,
+				&vec![
+					ColorBlendEquationEXT {
+						src_color_blend_factor: vk::BlendFactor::ONE,
+						dst_color_blend_factor: vk::BlendFactor::ZERO,
+						color_blend_op: vk::BlendOp::ADD,
+						src_alpha_blend_factor: vk::BlendFactor::ONE,
+						dst_alpha_blend_factor: vk::BlendFactor::ZERO,
+						alpha_blend_op: vk::BlendOp::ADD
+					};
+					count
+				]
+			);
+			self.set_color_write_mask(0, &vec![vk::ColorComponentFlags::RGBA; count]);
+		}
+	}
+}
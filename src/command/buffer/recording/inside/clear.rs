@@ -0,0 +1,126 @@
+use std::{fmt, num::NonZeroU32};
+
+use ash::vk;
+
+use crate::{command::error::ClearError, prelude::Transparent};
+
+/// Attachment of the current subpass cleared by `clear_attachments`.
+#[derive(Clone, Copy)]
+pub enum ClearAttachment {
+	/// Color attachment at index `attachment` into the color attachments of the current subpass.
+	Color { attachment: u32, value: vk::ClearColorValue },
+	/// Depth/stencil attachment of the current subpass, only the aspects in `aspect_mask` are cleared.
+	DepthStencil { aspect_mask: vk::ImageAspectFlags, value: vk::ClearDepthStencilValue }
+}
+impl From<ClearAttachment> for vk::ClearAttachment {
+	fn from(value: ClearAttachment) -> Self {
+		match value {
+			ClearAttachment::Color { attachment, value } => vk::ClearAttachment {
+				aspect_mask: vk::ImageAspectFlags::COLOR,
+				color_attachment: attachment,
+				clear_value: vk::ClearValue { color: value }
+			},
+			ClearAttachment::DepthStencil { aspect_mask, value } => vk::ClearAttachment {
+				aspect_mask,
+				color_attachment: vk::ATTACHMENT_UNUSED,
+				clear_value: vk::ClearValue { depth_stencil: value }
+			}
+		}
+	}
+}
+impl fmt::Debug for ClearAttachment {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ClearAttachment::Color { attachment, value } => f
+				.debug_struct("Color")
+				.field("attachment", attachment)
+				.field("value", &unsafe { value.uint32 })
+				.finish(),
+			ClearAttachment::DepthStencil { aspect_mask, value } => f
+				.debug_struct("DepthStencil")
+				.field("aspect_mask", aspect_mask)
+				.field("value", value)
+				.finish()
+		}
+	}
+}
+
+vk_builder_wrap! {
+	/// Region of the attachments cleared by `clear_attachments`.
+	pub struct ClearRect {
+		builder: vk::ClearRectBuilder<'static> => vk::ClearRect
+	}
+	impl {
+		pub fn new(
+			rect: vk::Rect2D,
+			base_array_layer: u32,
+			layer_count: NonZeroU32
+		) -> Self {
+			ClearRect {
+				builder: vk::ClearRect::builder()
+					.rect(rect)
+					.base_array_layer(base_array_layer)
+					.layer_count(layer_count.get())
+			}
+		}
+	}
+}
+
+/// Checks that depth/stencil clears only clear depth and stencil aspects and that no rect is empty.
+///
+/// The attachment formats are not known while recording, so the aspects cannot be checked against them.
+#[cfg(feature = "runtime_implicit_validations")]
+fn validate_clear_attachments(attachments: &[ClearAttachment], rects: &[ClearRect]) -> Result<(), ClearError> {
+	for (index, attachment) in attachments.iter().enumerate() {
+		if let ClearAttachment::DepthStencil { aspect_mask, .. } = *attachment {
+			if aspect_mask.is_empty() || !(vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL).contains(aspect_mask) {
+				return Err(ClearError::AttachmentAspect { index, aspect_mask })
+			}
+		}
+	}
+
+	for (index, rect) in rects.iter().enumerate() {
+		if rect.rect.extent.width == 0 || rect.rect.extent.height == 0 {
+			return Err(ClearError::EmptyRect(index))
+		}
+	}
+
+	Ok(())
+}
+
+impl<'a> super::super::CommandBufferRecordingLockInsideRenderPass<'a> {
+	/// Clears `rects` of `attachments` of the current subpass.
+	///
+	/// Under runtime validations, depth/stencil clears are checked to only have the `DEPTH` and `STENCIL` aspects
+	/// and `rects` to have non-zero extent.
+	pub fn clear_attachments(&self, attachments: impl AsRef<[ClearAttachment]>, rects: impl AsRef<[ClearRect]>) -> Result<(), ClearError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			validate_clear_attachments(attachments.as_ref(), rects.as_ref())?;
+		}
+
+		log_trace_common!(
+			"Clear attachments:",
+			crate::util::fmt::format_handle(self.handle()),
+			attachments.as_ref(),
+			rects.as_ref()
+		);
+
+		let attachments_raw = collect_iter_faster!(
+			attachments
+				.as_ref()
+				.iter()
+				.map(|&attachment| vk::ClearAttachment::from(attachment)),
+			4
+		);
+		unsafe {
+			self.device().cmd_clear_attachments(
+				self.handle(),
+				attachments_raw.as_slice(),
+				Transparent::transmute_slice_twice(rects.as_ref())
+			)
+		}
+
+		Ok(())
+	}
+}
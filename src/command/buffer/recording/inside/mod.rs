@@ -1,5 +1,19 @@
+use ash::vk;
+
+use crate::prelude::{Buffer, HasHandle};
+
+pub mod clear;
+
 impl<'a> super::CommandBufferRecordingLockInsideRenderPass<'a> {
+	/// Under runtime validations, zero `vertex_count` or `instance_count` is logged together with the caller.
+	#[track_caller]
 	pub fn draw(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		super::warn_zero_counts(
+			"Draw",
+			&[("vertex count", vertex_count), ("instance count", instance_count)]
+		);
+
 		log_trace_common!(
 			"Drawing:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -18,4 +32,90 @@ impl<'a> super::CommandBufferRecordingLockInsideRenderPass<'a> {
 			);
 		}
 	}
+
+	/// Under runtime validations, zero `index_count` or `instance_count` is logged together with the caller.
+	#[track_caller]
+	pub fn draw_indexed(&self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		super::warn_zero_counts(
+			"Draw indexed",
+			&[("index count", index_count), ("instance count", instance_count)]
+		);
+
+		log_trace_common!(
+			"Drawing indexed:",
+			crate::util::fmt::format_handle(self.handle()),
+			index_count,
+			instance_count,
+			first_index,
+			vertex_offset,
+			first_instance
+		);
+		unsafe {
+			self.device().cmd_draw_indexed(
+				self.handle(),
+				index_count,
+				instance_count,
+				first_index,
+				vertex_offset,
+				first_instance
+			);
+		}
+	}
+
+	pub fn draw_indexed_indirect(&self, buffer: &Buffer, offset: vk::DeviceSize, draw_count: u32, stride: u32) {
+		log_trace_common!(
+			"Drawing indexed indirect:",
+			crate::util::fmt::format_handle(self.handle()),
+			buffer,
+			offset,
+			draw_count,
+			stride
+		);
+		unsafe {
+			self.device().cmd_draw_indexed_indirect(
+				self.handle(),
+				buffer.handle(),
+				offset,
+				draw_count,
+				stride
+			);
+		}
+	}
+
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	#[cfg(feature = "vulkan1_2")]
+	pub fn draw_indexed_indirect_count(
+		&self,
+		buffer: &Buffer,
+		offset: vk::DeviceSize,
+		count_buffer: &Buffer,
+		count_buffer_offset: vk::DeviceSize,
+		max_draw_count: u32,
+		stride: u32
+	) {
+		log_trace_common!(
+			"Drawing indexed indirect count:",
+			crate::util::fmt::format_handle(self.handle()),
+			buffer,
+			offset,
+			count_buffer,
+			count_buffer_offset,
+			max_draw_count,
+			stride
+		);
+		unsafe {
+			self.device().cmd_draw_indexed_indirect_count(
+				self.handle(),
+				buffer.handle(),
+				offset,
+				count_buffer.handle(),
+				count_buffer_offset,
+				max_draw_count,
+				stride
+			);
+		}
+	}
 }
@@ -8,6 +8,7 @@ use crate::prelude::{Framebuffer, HasHandle, RenderPass};
 pub mod common;
 pub mod inside;
 pub mod outside;
+pub mod rendering;
 
 pub use common::CommandBufferRecordingLockCommon;
 
@@ -38,6 +39,47 @@ impl From<CommandBufferBeginInfo> for vk::CommandBufferBeginInfoBuilder<'static>
 	}
 }
 
+/// Logs a warning naming the caller if any of `counts` is zero, which makes the recorded command a no-op.
+#[cfg(feature = "runtime_implicit_validations")]
+#[track_caller]
+fn warn_zero_counts(command: &str, counts: &[(&str, u32)]) {
+	for (name, count) in counts.iter() {
+		if *count == 0 {
+			log::warn!(
+				"{} recorded at {} has zero {} and does nothing",
+				command,
+				std::panic::Location::caller(),
+				name
+			);
+		}
+	}
+}
+
+/// Checks that a dispatch starting at `base` with `group_count` fits within `maxComputeWorkGroupCount`.
+///
+/// ### Panic
+///
+/// Panics naming the caller if the limit is exceeded.
+#[cfg(feature = "runtime_implicit_validations")]
+#[track_caller]
+fn validate_dispatch(command: &str, limits: &vk::PhysicalDeviceLimits, base: [u32; 3], group_count: [u32; 3]) {
+	warn_zero_counts(
+		command,
+		&[("group count x", group_count[0]), ("group count y", group_count[1]), ("group count z", group_count[2])]
+	);
+
+	for axis in 0 .. 3 {
+		assert!(
+			base[axis] as u64 + group_count[axis] as u64 <= limits.max_compute_work_group_count[axis] as u64,
+			"{} base {:?} and group count {:?} exceed maxComputeWorkGroupCount {:?}",
+			command,
+			base,
+			group_count,
+			limits.max_compute_work_group_count
+		);
+	}
+}
+
 /// Wrapper around `VutexGuard` and `CommandBuffer` reference that provides safe command recording functions.
 ///
 /// TODO: This struct is under construction
@@ -104,7 +146,55 @@ impl<'a> CommandBufferRecordingLockOutsideRenderPass<'a> {
 				.cmd_begin_render_pass(self.handle(), &create_info, contents);
 		}
 
-		CommandBufferRecordingLockInsideRenderPass(self)
+		CommandBufferRecordingLockInsideRenderPass(self, false)
+	}
+
+	/// Begins a render pass on the physical devices selected by `device_mask`, see the `instance::device_group` module.
+	///
+	/// `device_render_areas` are the render areas for each physical device of the group and override `render_area` if not empty.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn begin_render_pass_device_group(
+		self,
+		render_pass: &RenderPass,
+		framebuffer: &Framebuffer,
+		render_area: vk::Rect2D,
+		clear_values: impl AsRef<[vk::ClearValue]>,
+		contents_inline: bool,
+		device_mask: u32,
+		device_render_areas: &[vk::Rect2D]
+	) -> CommandBufferRecordingLockInsideRenderPass<'a> {
+		let mut device_group_info = vk::DeviceGroupRenderPassBeginInfo::builder()
+			.device_mask(device_mask)
+			.device_render_areas(device_render_areas);
+		let create_info = vk::RenderPassBeginInfo::builder()
+			.render_pass(render_pass.handle())
+			.framebuffer(framebuffer.handle())
+			.render_area(render_area)
+			.clear_values(clear_values.as_ref())
+			.push_next(&mut device_group_info);
+
+		let contents = if contents_inline { vk::SubpassContents::INLINE } else { vk::SubpassContents::SECONDARY_COMMAND_BUFFERS };
+
+		log_trace_common!(
+			"Recording BeginRenderPass:",
+			crate::util::fmt::format_handle(self.handle()),
+			render_pass,
+			framebuffer,
+			render_area,
+			contents,
+			device_mask,
+			device_render_areas
+		);
+		unsafe {
+			self.device()
+				.cmd_begin_render_pass(self.handle(), &create_info, contents);
+		}
+
+		CommandBufferRecordingLockInsideRenderPass(self, false)
 	}
 
 	/// Ends the recording.
@@ -149,7 +239,13 @@ impl Drop for CommandBufferRecordingLockOutsideRenderPass<'_> {
 ///
 /// This structure will panic on `drop` if the inner `CommandBufferRecordingLockOutsideRenderPass` panics on drop.
 /// It is recommended to call `end_render_pass` and retrieve the inner lock instead.
-pub struct CommandBufferRecordingLockInsideRenderPass<'a>(CommandBufferRecordingLockOutsideRenderPass<'a>);
+///
+/// This lock is used both inside a render pass instance and inside dynamic rendering, see `begin_rendering`.
+pub struct CommandBufferRecordingLockInsideRenderPass<'a>(
+	CommandBufferRecordingLockOutsideRenderPass<'a>,
+	// Whether this is dynamic rendering instead of a render pass instance
+	bool
+);
 impl<'a> Deref for CommandBufferRecordingLockInsideRenderPass<'a> {
 	type Target = CommandBufferRecordingLockCommon<'a>;
 
@@ -158,7 +254,12 @@ impl<'a> Deref for CommandBufferRecordingLockInsideRenderPass<'a> {
 	}
 }
 impl<'a> CommandBufferRecordingLockInsideRenderPass<'a> {
+	/// ### Panic
+	///
+	/// This function will panic inside dynamic rendering.
 	pub fn next_subpass(&self, contents_inline: bool) {
+		assert!(!self.1, "next_subpass cannot be called inside dynamic rendering");
+
 		let contents = if contents_inline { vk::SubpassContents::INLINE } else { vk::SubpassContents::SECONDARY_COMMAND_BUFFERS };
 
 		log_trace_common!(
@@ -173,11 +274,32 @@ impl<'a> CommandBufferRecordingLockInsideRenderPass<'a> {
 	///
 	/// Must only be called once.
 	unsafe fn end_render_pass_mut(&mut self) {
-		log_trace_common!(
-			"Recording EndRenderPass:",
-			crate::util::fmt::format_handle(self.handle())
-		);
-		self.device().cmd_end_render_pass(self.handle());
+		if self.1 {
+			log_trace_common!(
+				"Recording EndRendering:",
+				crate::util::fmt::format_handle(self.handle())
+			);
+			self.device()
+				.ext()
+				.dynamic_rendering()
+				.cmd_end_rendering(self.handle());
+		} else {
+			log_trace_common!(
+				"Recording EndRenderPass:",
+				crate::util::fmt::format_handle(self.handle())
+			);
+			self.device().cmd_end_render_pass(self.handle());
+		}
+	}
+
+	/// Returns whether this lock records inside dynamic rendering instead of a render pass instance.
+	pub const fn is_dynamic_rendering(&self) -> bool {
+		self.1
+	}
+
+	/// Same as `end_render_pass`, provided for symmetry with `begin_rendering`.
+	pub fn end_rendering(self) -> CommandBufferRecordingLockOutsideRenderPass<'a> {
+		self.end_render_pass()
 	}
 
 	/// Consumes this struct, ends the render pass and returns the `CommandBufferRecordingLockOutsideRenderPass`.
@@ -2,7 +2,10 @@ use std::num::NonZeroU64;
 
 use ash::vk;
 
-use crate::prelude::{Buffer, HasHandle, Image, ImageLayoutFinal, ImageSubresourceRange, Queue, Transparent};
+use crate::{
+	command::error::{BarrierAccessError, BarrierIndex},
+	prelude::{Buffer, HasHandle, Image, ImageLayoutFinal, ImageSubresourceRange, Queue, Transparent}
+};
 
 // salmon
 vk_builder_wrap! {
@@ -78,6 +81,9 @@ vk_builder_wrap! {
 		builder: vk::ImageMemoryBarrierBuilder<'a> => vk::ImageMemoryBarrier
 	}
 	impl ['a] {
+		/// ### Panic
+		///
+		/// With `runtime_implicit_validations` this function panics if `image` cannot be in `old_layout` or `new_layout`, see `Image::validate_layout`.
 		pub fn new(
 			image: &'a Image,
 			subresource_range: ImageSubresourceRange,
@@ -92,6 +98,12 @@ vk_builder_wrap! {
 			debug_assert!(
 				subresource_range.array_layers_base + subresource_range.array_layers.get() <= image.size().array_layers().get()
 			);
+			#[cfg(feature = "runtime_implicit_validations")]
+			{
+				if let Err(err) = image.validate_layout(old_layout).and_then(|_| image.validate_layout(new_layout.into())) {
+					panic!("{}", err);
+				}
+			}
 
 			ImageMemoryBarrier {
 				builder: vk::ImageMemoryBarrier::builder()
@@ -134,7 +146,118 @@ vk_builder_wrap! {
 	}
 }
 
+const SHADER_STAGES: vk::PipelineStageFlags = vk::PipelineStageFlags::from_raw(
+	vk::PipelineStageFlags::VERTEX_SHADER.as_raw()
+		| vk::PipelineStageFlags::TESSELLATION_CONTROL_SHADER.as_raw()
+		| vk::PipelineStageFlags::TESSELLATION_EVALUATION_SHADER.as_raw()
+		| vk::PipelineStageFlags::GEOMETRY_SHADER.as_raw()
+		| vk::PipelineStageFlags::FRAGMENT_SHADER.as_raw()
+		| vk::PipelineStageFlags::COMPUTE_SHADER.as_raw()
+		| vk::PipelineStageFlags::RAY_TRACING_SHADER_KHR.as_raw()
+		| vk::PipelineStageFlags::TASK_SHADER_NV.as_raw()
+		| vk::PipelineStageFlags::MESH_SHADER_NV.as_raw()
+);
+
+const GRAPHICS_STAGES: vk::PipelineStageFlags = vk::PipelineStageFlags::from_raw(
+	vk::PipelineStageFlags::DRAW_INDIRECT.as_raw()
+		| vk::PipelineStageFlags::VERTEX_INPUT.as_raw()
+		| vk::PipelineStageFlags::VERTEX_SHADER.as_raw()
+		| vk::PipelineStageFlags::TESSELLATION_CONTROL_SHADER.as_raw()
+		| vk::PipelineStageFlags::TESSELLATION_EVALUATION_SHADER.as_raw()
+		| vk::PipelineStageFlags::GEOMETRY_SHADER.as_raw()
+		| vk::PipelineStageFlags::FRAGMENT_SHADER.as_raw()
+		| vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS.as_raw()
+		| vk::PipelineStageFlags::LATE_FRAGMENT_TESTS.as_raw()
+		| vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT.as_raw()
+		| vk::PipelineStageFlags::TASK_SHADER_NV.as_raw()
+		| vk::PipelineStageFlags::MESH_SHADER_NV.as_raw()
+);
+
+/// Returns the stages that support a single `access` flag, or `None` if all stages support it.
+///
+/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/html/vkspec.html#synchronization-access-types-supported>.
+pub fn access_supported_stages(access: vk::AccessFlags) -> Option<vk::PipelineStageFlags> {
+	let stages = match access {
+		vk::AccessFlags::INDIRECT_COMMAND_READ => vk::PipelineStageFlags::DRAW_INDIRECT | vk::PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_KHR,
+		vk::AccessFlags::INDEX_READ | vk::AccessFlags::VERTEX_ATTRIBUTE_READ => vk::PipelineStageFlags::VERTEX_INPUT,
+		vk::AccessFlags::UNIFORM_READ | vk::AccessFlags::SHADER_WRITE => SHADER_STAGES,
+		vk::AccessFlags::SHADER_READ => SHADER_STAGES | vk::PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_KHR,
+		vk::AccessFlags::INPUT_ATTACHMENT_READ => vk::PipelineStageFlags::FRAGMENT_SHADER,
+		vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE => vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+		vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE => {
+			vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS
+		}
+		vk::AccessFlags::TRANSFER_READ | vk::AccessFlags::TRANSFER_WRITE => {
+			vk::PipelineStageFlags::TRANSFER | vk::PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_KHR
+		}
+		vk::AccessFlags::HOST_READ | vk::AccessFlags::HOST_WRITE => vk::PipelineStageFlags::HOST,
+		vk::AccessFlags::ACCELERATION_STRUCTURE_READ_KHR => SHADER_STAGES | vk::PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_KHR,
+		vk::AccessFlags::ACCELERATION_STRUCTURE_WRITE_KHR => vk::PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_KHR,
+		_ => return None
+	};
+
+	Some(stages)
+}
+
+/// Returns the first flag of `access` that is not supported by any of `stages`.
+fn unsupported_access(access: vk::AccessFlags, stages: vk::PipelineStageFlags) -> Option<vk::AccessFlags> {
+	if stages.contains(vk::PipelineStageFlags::ALL_COMMANDS) {
+		return None
+	}
+	let stages = if stages.contains(vk::PipelineStageFlags::ALL_GRAPHICS) { stages | GRAPHICS_STAGES } else { stages };
+
+	(0 .. 32)
+		.map(|bit| vk::AccessFlags::from_raw(1 << bit))
+		.filter(|&flag| access.contains(flag))
+		.find(|&flag| access_supported_stages(flag).map(|supported| !stages.intersects(supported)).unwrap_or(false))
+}
+
+/// Checks that the access masks of all barriers are supported by `source_stages` and `destination_stages`.
+///
+/// For example, `COLOR_ATTACHMENT_WRITE` is only supported by the `COLOR_ATTACHMENT_OUTPUT` stage.
+/// The error identifies the first offending barrier and access flag.
+pub fn validate_barrier_access<'b, 'i>(
+	source_stages: vk::PipelineStageFlags,
+	destination_stages: vk::PipelineStageFlags,
+	memory_barriers: &[MemoryBarrier],
+	buffer_memory_barriers: &[BufferMemoryBarrier<'b>],
+	image_memory_barriers: &[ImageMemoryBarrier<'i>]
+) -> Result<(), BarrierAccessError> {
+	let accesses = memory_barriers
+		.iter()
+		.enumerate()
+		.map(|(index, b)| (BarrierIndex::Memory(index), b.src_access_mask, b.dst_access_mask))
+		.chain(
+			buffer_memory_barriers
+				.iter()
+				.enumerate()
+				.map(|(index, b)| (BarrierIndex::Buffer(index), b.src_access_mask, b.dst_access_mask))
+		)
+		.chain(
+			image_memory_barriers
+				.iter()
+				.enumerate()
+				.map(|(index, b)| (BarrierIndex::Image(index), b.src_access_mask, b.dst_access_mask))
+		);
+
+	for (barrier, source_access, destination_access) in accesses {
+		if let Some(access) = unsupported_access(source_access, source_stages) {
+			return Err(BarrierAccessError::SourceAccessUnsupported { barrier, access, stages: source_stages })
+		}
+		if let Some(access) = unsupported_access(destination_access, destination_stages) {
+			return Err(BarrierAccessError::DestinationAccessUnsupported { barrier, access, stages: destination_stages })
+		}
+	}
+
+	Ok(())
+}
+
 impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// ### Panic
+	///
+	/// With `runtime_implicit_validations` this function panics if the access masks of the barriers are not supported
+	/// by the stages, see `validate_barrier_access`. Use `try_pipeline_barrier` to handle the error instead.
+	#[cfg_attr(feature = "runtime_implicit_validations", track_caller)]
 	pub fn pipeline_barrier<'b, 'i>(
 		&self,
 		source_stages: vk::PipelineStageFlags,
@@ -143,14 +266,75 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 		buffer_memory_barriers: impl AsRef<[BufferMemoryBarrier<'b>]>,
 		image_memory_barriers: impl AsRef<[ImageMemoryBarrier<'i>]>
 	) {
-		log_trace_common!(
-			"Pipeline barrier:",
-			crate::util::fmt::format_handle(self.handle()),
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if let Err(err) = validate_barrier_access(
+				source_stages,
+				destination_stages,
+				memory_barriers.as_ref(),
+				buffer_memory_barriers.as_ref(),
+				image_memory_barriers.as_ref()
+			) {
+				panic!("{}", err);
+			}
+		}
+
+		self.record_pipeline_barrier(
 			source_stages,
 			destination_stages,
 			memory_barriers.as_ref(),
 			buffer_memory_barriers.as_ref(),
 			image_memory_barriers.as_ref()
+		)
+	}
+
+	/// Same as `pipeline_barrier`, but returns an error instead of panicking.
+	///
+	/// The barriers are always validated using `validate_barrier_access`, regardless of `runtime_implicit_validations`.
+	/// Nothing is recorded if the validation fails.
+	pub fn try_pipeline_barrier<'b, 'i>(
+		&self,
+		source_stages: vk::PipelineStageFlags,
+		destination_stages: vk::PipelineStageFlags,
+		memory_barriers: impl AsRef<[MemoryBarrier]>,
+		buffer_memory_barriers: impl AsRef<[BufferMemoryBarrier<'b>]>,
+		image_memory_barriers: impl AsRef<[ImageMemoryBarrier<'i>]>
+	) -> Result<(), BarrierAccessError> {
+		validate_barrier_access(
+			source_stages,
+			destination_stages,
+			memory_barriers.as_ref(),
+			buffer_memory_barriers.as_ref(),
+			image_memory_barriers.as_ref()
+		)?;
+
+		self.record_pipeline_barrier(
+			source_stages,
+			destination_stages,
+			memory_barriers.as_ref(),
+			buffer_memory_barriers.as_ref(),
+			image_memory_barriers.as_ref()
+		);
+
+		Ok(())
+	}
+
+	fn record_pipeline_barrier(
+		&self,
+		source_stages: vk::PipelineStageFlags,
+		destination_stages: vk::PipelineStageFlags,
+		memory_barriers: &[MemoryBarrier],
+		buffer_memory_barriers: &[BufferMemoryBarrier],
+		image_memory_barriers: &[ImageMemoryBarrier]
+	) {
+		log_trace_common!(
+			"Pipeline barrier:",
+			crate::util::fmt::format_handle(self.handle()),
+			source_stages,
+			destination_stages,
+			memory_barriers,
+			buffer_memory_barriers,
+			image_memory_barriers
 		);
 		unsafe {
 			self.device().cmd_pipeline_barrier(
@@ -158,10 +342,57 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 				source_stages,
 				destination_stages,
 				vk::DependencyFlags::empty(),
-				Transparent::transmute_slice_twice(memory_barriers.as_ref()),
-				Transparent::transmute_slice_twice(buffer_memory_barriers.as_ref()),
-				Transparent::transmute_slice_twice(image_memory_barriers.as_ref())
+				Transparent::transmute_slice_twice(memory_barriers),
+				Transparent::transmute_slice_twice(buffer_memory_barriers),
+				Transparent::transmute_slice_twice(image_memory_barriers)
 			)
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{validate_barrier_access, MemoryBarrier};
+	use crate::command::error::{BarrierAccessError, BarrierIndex};
+
+	#[test]
+	fn barrier_access_compatibility() {
+		let barriers = [
+			MemoryBarrier::new(
+				vk::AccessFlags::TRANSFER_WRITE,
+				vk::AccessFlags::HOST_READ
+			),
+			MemoryBarrier::new(
+				vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+				vk::AccessFlags::SHADER_READ
+			)
+		];
+
+		assert_eq!(
+			validate_barrier_access(
+				vk::PipelineStageFlags::TRANSFER | vk::PipelineStageFlags::ALL_GRAPHICS,
+				vk::PipelineStageFlags::HOST | vk::PipelineStageFlags::FRAGMENT_SHADER,
+				&barriers,
+				&[],
+				&[]
+			),
+			Ok(())
+		);
+		assert_eq!(
+			validate_barrier_access(
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::ALL_COMMANDS,
+				&barriers,
+				&[],
+				&[]
+			),
+			Err(BarrierAccessError::SourceAccessUnsupported {
+				barrier: BarrierIndex::Memory(1),
+				access: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+				stages: vk::PipelineStageFlags::TRANSFER
+			})
+		);
+	}
+}
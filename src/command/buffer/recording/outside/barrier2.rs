@@ -0,0 +1,232 @@
+//! Pipeline barriers using `VK_KHR_synchronization2`.
+//!
+//! Each barrier carries its own stage masks. When synchronization2 is not enabled on the device,
+//! `pipeline_barrier2` records a legacy pipeline barrier instead, see `sync::sync2`.
+
+use std::num::NonZeroU64;
+
+use ash::vk;
+
+use super::barrier::{BufferMemoryBarrier, ImageMemoryBarrier, MemoryBarrier};
+use crate::{
+	prelude::{Buffer, HasHandle, Image, ImageLayoutFinal, ImageSubresourceRange, Transparent},
+	sync::sync2::{legacy_access, legacy_stages}
+};
+
+vk_builder_wrap! {
+	pub struct MemoryBarrier2 {
+		builder: vk::MemoryBarrier2Builder<'static> => vk::MemoryBarrier2
+	}
+	impl {
+		pub fn new(
+			source_stages: vk::PipelineStageFlags2,
+			source_access: vk::AccessFlags2,
+			destination_stages: vk::PipelineStageFlags2,
+			destination_access: vk::AccessFlags2
+		) -> Self {
+			MemoryBarrier2 {
+				builder: vk::MemoryBarrier2::builder()
+					.src_stage_mask(source_stages)
+					.src_access_mask(source_access)
+					.dst_stage_mask(destination_stages)
+					.dst_access_mask(destination_access)
+			}
+		}
+	}
+}
+
+vk_builder_wrap! {
+	pub struct BufferMemoryBarrier2 ['a] {
+		builder: vk::BufferMemoryBarrier2Builder<'a> => vk::BufferMemoryBarrier2
+	}
+	impl ['a] {
+		pub fn new(
+			buffer: &'a Buffer,
+			offset: u64,
+			size: NonZeroU64,
+			source_stages: vk::PipelineStageFlags2,
+			source_access: vk::AccessFlags2,
+			destination_stages: vk::PipelineStageFlags2,
+			destination_access: vk::AccessFlags2
+		) -> Self {
+			debug_assert!(
+				offset + size.get() <= buffer.size().get()
+			);
+
+			BufferMemoryBarrier2 {
+				builder: vk::BufferMemoryBarrier2::builder()
+					.buffer(buffer.handle())
+					.offset(offset)
+					.size(size.get())
+					.src_stage_mask(source_stages)
+					.src_access_mask(source_access)
+					.dst_stage_mask(destination_stages)
+					.dst_access_mask(destination_access)
+					.src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+					.dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+			}
+		}
+	}
+}
+
+vk_builder_wrap! {
+	pub struct ImageMemoryBarrier2 ['a] {
+		builder: vk::ImageMemoryBarrier2Builder<'a> => vk::ImageMemoryBarrier2
+	}
+	impl ['a] {
+		/// ### Panic
+		///
+		/// With `runtime_implicit_validations` this function panics if `image` cannot be in `old_layout` or `new_layout`, see `Image::validate_layout`.
+		pub fn new(
+			image: &'a Image,
+			subresource_range: ImageSubresourceRange,
+			old_layout: vk::ImageLayout,
+			new_layout: ImageLayoutFinal,
+			source_stages: vk::PipelineStageFlags2,
+			source_access: vk::AccessFlags2,
+			destination_stages: vk::PipelineStageFlags2,
+			destination_access: vk::AccessFlags2
+		) -> Self {
+			debug_assert!(
+				subresource_range.mipmap_levels_base + subresource_range.mipmap_levels.get() <= image.size().mipmap_levels().get()
+			);
+			debug_assert!(
+				subresource_range.array_layers_base + subresource_range.array_layers.get() <= image.size().array_layers().get()
+			);
+			#[cfg(feature = "runtime_implicit_validations")]
+			{
+				if let Err(err) = image.validate_layout(old_layout).and_then(|_| image.validate_layout(new_layout.into())) {
+					panic!("{}", err);
+				}
+			}
+
+			ImageMemoryBarrier2 {
+				builder: vk::ImageMemoryBarrier2::builder()
+					.image(image.handle())
+					.subresource_range(
+						vk::ImageSubresourceRangeBuilder::from(
+							subresource_range
+						).build()
+					)
+					.old_layout(old_layout)
+					.new_layout(new_layout.into())
+					.src_stage_mask(source_stages)
+					.src_access_mask(source_access)
+					.dst_stage_mask(destination_stages)
+					.dst_access_mask(destination_access)
+					.src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+					.dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+			}
+		}
+	}
+}
+
+impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Records a synchronization2 pipeline barrier.
+	///
+	/// Falls back to `pipeline_barrier` with the union of all stage masks when synchronization2 is not enabled on the device.
+	pub fn pipeline_barrier2<'b, 'i>(
+		&self,
+		memory_barriers: impl AsRef<[MemoryBarrier2]>,
+		buffer_memory_barriers: impl AsRef<[BufferMemoryBarrier2<'b>]>,
+		image_memory_barriers: impl AsRef<[ImageMemoryBarrier2<'i>]>
+	) {
+		let memory_barriers: &[vk::MemoryBarrier2] = Transparent::transmute_slice_twice(memory_barriers.as_ref());
+		let buffer_memory_barriers: &[vk::BufferMemoryBarrier2] = Transparent::transmute_slice_twice(buffer_memory_barriers.as_ref());
+		let image_memory_barriers: &[vk::ImageMemoryBarrier2] = Transparent::transmute_slice_twice(image_memory_barriers.as_ref());
+
+		if self.device().synchronization2_enabled() {
+			let dependency_info = vk::DependencyInfo::builder()
+				.memory_barriers(memory_barriers)
+				.buffer_memory_barriers(buffer_memory_barriers)
+				.image_memory_barriers(image_memory_barriers);
+
+			log_trace_common!(
+				"Pipeline barrier2:",
+				crate::util::fmt::format_handle(self.handle()),
+				memory_barriers,
+				buffer_memory_barriers,
+				image_memory_barriers
+			);
+			unsafe {
+				self.device()
+					.ext()
+					.synchronization2()
+					.cmd_pipeline_barrier2(self.handle(), &dependency_info);
+			}
+
+			return
+		}
+
+		let mut source_stages = vk::PipelineStageFlags2::NONE;
+		let mut destination_stages = vk::PipelineStageFlags2::NONE;
+		let mut add_stages = |source: vk::PipelineStageFlags2, destination: vk::PipelineStageFlags2| {
+			source_stages |= source;
+			destination_stages |= destination;
+		};
+
+		let legacy_memory = collect_iter_faster!(
+			memory_barriers
+				.iter()
+				.map(|b| {
+					add_stages(b.src_stage_mask, b.dst_stage_mask);
+					MemoryBarrier::new(
+						legacy_access(b.src_access_mask),
+						legacy_access(b.dst_access_mask)
+					)
+				}),
+			8
+		);
+		let legacy_buffer = collect_iter_faster!(
+			buffer_memory_barriers
+				.iter()
+				.map(|b| {
+					add_stages(b.src_stage_mask, b.dst_stage_mask);
+					// SAFETY: the fields are copied from a valid synchronization2 barrier
+					unsafe {
+						BufferMemoryBarrier::from_raw(
+							vk::BufferMemoryBarrier::builder()
+								.buffer(b.buffer)
+								.offset(b.offset)
+								.size(b.size)
+								.src_access_mask(legacy_access(b.src_access_mask))
+								.dst_access_mask(legacy_access(b.dst_access_mask))
+								.src_queue_family_index(b.src_queue_family_index)
+								.dst_queue_family_index(b.dst_queue_family_index)
+						)
+					}
+				}),
+			8
+		);
+		let legacy_image = collect_iter_faster!(
+			image_memory_barriers
+				.iter()
+				.map(|b| {
+					add_stages(b.src_stage_mask, b.dst_stage_mask);
+					// SAFETY: the fields are copied from a valid synchronization2 barrier
+					unsafe {
+						ImageMemoryBarrier::from_raw(
+							vk::ImageMemoryBarrier::builder()
+								.image(b.image)
+								.subresource_range(b.subresource_range)
+								.old_layout(b.old_layout)
+								.new_layout(b.new_layout)
+								.src_access_mask(legacy_access(b.src_access_mask))
+								.dst_access_mask(legacy_access(b.dst_access_mask))
+								.src_queue_family_index(b.src_queue_family_index)
+								.dst_queue_family_index(b.dst_queue_family_index)
+						)
+					}
+				}),
+			8
+		);
+
+		self.pipeline_barrier(
+			legacy_stages(source_stages, vk::PipelineStageFlags::TOP_OF_PIPE),
+			legacy_stages(destination_stages, vk::PipelineStageFlags::BOTTOM_OF_PIPE),
+			legacy_memory,
+			legacy_buffer,
+			legacy_image
+		)
+	}
+}
@@ -0,0 +1,440 @@
+//! Batching of pipeline barriers into as few `vkCmdPipelineBarrier` calls as possible.
+//!
+//! A `BarrierBatch` collects barriers, each with its own stage masks, and records them in a single pipeline barrier
+//! with the union of all stage masks when flushed. Widening the stage masks only adds synchronization, but it can serialize
+//! more work than strictly needed when the masks of the merged barriers differ a lot.
+//!
+//! Barriers within one pipeline barrier are not ordered against each other, so barriers that depend on each other cannot be merged.
+//! A barrier that depends on a pending one flushes the pending barriers before it is added. A barrier depends on a pending one if:
+//! * both are buffer barriers of overlapping ranges of the same buffer that differ in access masks or queue families,
+//! * both are image barriers of overlapping subresources of the same image that are not identical, for example two layout transitions,
+//! * one is a global memory barrier whose access masks chain with the access masks of the other.
+//!
+//! When flushing, global memory barriers are merged into one, buffer barriers with the same buffer, access masks
+//! and queue families whose ranges overlap or touch are merged, and duplicate image barriers are removed.
+
+use ash::vk;
+
+use super::barrier::{BufferMemoryBarrier, ImageMemoryBarrier, MemoryBarrier};
+use crate::prelude::Transparent;
+
+/// Collects pipeline barriers and records them merged, see the module documentation.
+///
+/// The batch borrows the recording lock mutably, other commands must be recorded through `recording`, which flushes the batch first.
+/// Pending barriers are flushed on drop.
+pub struct BarrierBatch<'r, 'a, 'b> {
+	recording: &'r mut super::super::CommandBufferRecordingLockOutsideRenderPass<'a>,
+
+	source_stages: vk::PipelineStageFlags,
+	destination_stages: vk::PipelineStageFlags,
+	memory_access: Option<(vk::AccessFlags, vk::AccessFlags)>,
+	buffer_barriers: Vec<BufferMemoryBarrier<'b>>,
+	image_barriers: Vec<ImageMemoryBarrier<'b>>,
+
+	// Whether a barrier was recorded by this batch without any command recorded after it
+	barrier_recorded: bool
+}
+impl<'r, 'a, 'b> BarrierBatch<'r, 'a, 'b> {
+	fn new(recording: &'r mut super::super::CommandBufferRecordingLockOutsideRenderPass<'a>) -> Self {
+		BarrierBatch {
+			recording,
+
+			source_stages: vk::PipelineStageFlags::empty(),
+			destination_stages: vk::PipelineStageFlags::empty(),
+			memory_access: None,
+			buffer_barriers: Vec::new(),
+			image_barriers: Vec::new(),
+
+			barrier_recorded: false
+		}
+	}
+
+	fn add_stages(&mut self, source_stages: vk::PipelineStageFlags, destination_stages: vk::PipelineStageFlags) {
+		self.source_stages |= source_stages;
+		self.destination_stages |= destination_stages;
+	}
+
+	/// Flushes pending barriers because the next barrier depends on them.
+	fn flush_dependency(&mut self) {
+		self.flush();
+		// The barriers could not have been merged
+		self.barrier_recorded = false;
+	}
+
+	/// Adds a global memory barrier.
+	///
+	/// Flushes pending buffer and image barriers first if the barrier depends on them, see the module documentation.
+	pub fn memory(
+		&mut self,
+		source_stages: vk::PipelineStageFlags,
+		destination_stages: vk::PipelineStageFlags,
+		source_access: vk::AccessFlags,
+		destination_access: vk::AccessFlags
+	) -> &mut Self {
+		let depends = self.buffer_barriers.iter().any(|pending| accesses_chain(pending.dst_access_mask, pending.src_access_mask, source_access, destination_access))
+			|| self.image_barriers.iter().any(|pending| accesses_chain(pending.dst_access_mask, pending.src_access_mask, source_access, destination_access));
+		if depends {
+			self.flush_dependency();
+		}
+		self.add_stages(source_stages, destination_stages);
+
+		let (source, destination) = self.memory_access.get_or_insert((vk::AccessFlags::empty(), vk::AccessFlags::empty()));
+		*source |= source_access;
+		*destination |= destination_access;
+
+		self
+	}
+
+	/// Returns whether a barrier with `source_access` and `destination_access` depends on the pending global memory barrier.
+	fn depends_on_memory(&self, source_access: vk::AccessFlags, destination_access: vk::AccessFlags) -> bool {
+		match self.memory_access {
+			None => false,
+			Some((pending_source, pending_destination)) => accesses_chain(pending_destination, pending_source, source_access, destination_access)
+		}
+	}
+
+	/// Adds a buffer memory barrier.
+	///
+	/// Flushes pending barriers first if the barrier depends on them, see the module documentation.
+	pub fn buffer(
+		&mut self,
+		source_stages: vk::PipelineStageFlags,
+		destination_stages: vk::PipelineStageFlags,
+		barrier: BufferMemoryBarrier<'b>
+	) -> &mut Self {
+		if self.depends_on_memory(barrier.src_access_mask, barrier.dst_access_mask)
+			|| self.buffer_barriers.iter().any(|pending| buffer_barriers_depend(pending, &barrier))
+		{
+			self.flush_dependency();
+		}
+		self.add_stages(source_stages, destination_stages);
+		self.buffer_barriers.push(barrier);
+
+		self
+	}
+
+	/// Adds an image memory barrier.
+	///
+	/// Flushes pending barriers first if the barrier depends on them, see the module documentation.
+	pub fn image(
+		&mut self,
+		source_stages: vk::PipelineStageFlags,
+		destination_stages: vk::PipelineStageFlags,
+		barrier: ImageMemoryBarrier<'b>
+	) -> &mut Self {
+		if self.depends_on_memory(barrier.src_access_mask, barrier.dst_access_mask)
+			|| self.image_barriers.iter().any(|pending| image_barriers_depend(pending, &barrier))
+		{
+			self.flush_dependency();
+		}
+		self.add_stages(source_stages, destination_stages);
+		self.image_barriers.push(barrier);
+
+		self
+	}
+
+	/// Returns whether there are no pending barriers.
+	pub fn is_empty(&self) -> bool {
+		self.memory_access.is_none() && self.buffer_barriers.is_empty() && self.image_barriers.is_empty()
+	}
+
+	/// Records all pending barriers in one pipeline barrier.
+	///
+	/// Logs a warning when this batch already recorded a barrier and no command was recorded since,
+	/// because the two barriers could have been merged.
+	pub fn flush(&mut self) {
+		if self.is_empty() {
+			return
+		}
+
+		if self.barrier_recorded {
+			log::warn!(
+				"Back-to-back pipeline barriers in {:?} could be merged into one",
+				crate::util::fmt::format_handle(self.recording.handle())
+			);
+		}
+
+		let memory_barriers = collect_iter_faster!(
+			self.memory_access
+				.take()
+				.map(|(source, destination)| MemoryBarrier::new(source, destination))
+				.into_iter(),
+			1
+		);
+		let buffer_barriers = merge_buffer_barriers(std::mem::take(&mut self.buffer_barriers));
+		let image_barriers = dedup_image_barriers(std::mem::take(&mut self.image_barriers));
+
+		self.recording.pipeline_barrier(
+			self.source_stages,
+			self.destination_stages,
+			memory_barriers,
+			buffer_barriers,
+			image_barriers
+		);
+
+		self.source_stages = vk::PipelineStageFlags::empty();
+		self.destination_stages = vk::PipelineStageFlags::empty();
+		self.barrier_recorded = true;
+	}
+
+	/// Flushes pending barriers and returns the recording lock to record other commands.
+	pub fn recording(&mut self) -> &super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+		self.flush();
+		self.barrier_recorded = false;
+
+		self.recording
+	}
+}
+impl Drop for BarrierBatch<'_, '_, '_> {
+	fn drop(&mut self) {
+		self.flush();
+	}
+}
+impl std::fmt::Debug for BarrierBatch<'_, '_, '_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("BarrierBatch")
+			.field("recording", &self.recording)
+			.field("source_stages", &self.source_stages)
+			.field("destination_stages", &self.destination_stages)
+			.field("memory_access", &self.memory_access)
+			.field("buffer_barriers", &self.buffer_barriers)
+			.field("image_barriers", &self.image_barriers)
+			.field("barrier_recorded", &self.barrier_recorded)
+			.finish()
+	}
+}
+
+/// Returns whether the access masks of two barriers chain, that is whether either barrier makes memory available
+/// or visible to accesses that the other one synchronizes.
+fn accesses_chain(
+	first_destination: vk::AccessFlags,
+	first_source: vk::AccessFlags,
+	second_source: vk::AccessFlags,
+	second_destination: vk::AccessFlags
+) -> bool {
+	first_destination.intersects(second_source) || second_destination.intersects(first_source)
+}
+
+/// Returns whether buffer barrier `next` depends on `pending`.
+fn buffer_barriers_depend(pending: &vk::BufferMemoryBarrier, next: &vk::BufferMemoryBarrier) -> bool {
+	if pending.buffer != next.buffer {
+		return false
+	}
+
+	let same_key = pending.src_access_mask == next.src_access_mask
+		&& pending.dst_access_mask == next.dst_access_mask
+		&& pending.src_queue_family_index == next.src_queue_family_index
+		&& pending.dst_queue_family_index == next.dst_queue_family_index;
+	if same_key {
+		return false
+	}
+
+	let range_end = |b: &vk::BufferMemoryBarrier| {
+		if b.size == vk::WHOLE_SIZE {
+			vk::DeviceSize::MAX
+		} else {
+			b.offset + b.size
+		}
+	};
+
+	pending.offset < range_end(next) && next.offset < range_end(pending)
+}
+
+/// Returns whether image barrier `next` depends on `pending`.
+fn image_barriers_depend(pending: &vk::ImageMemoryBarrier, next: &vk::ImageMemoryBarrier) -> bool {
+	if pending.image != next.image {
+		return false
+	}
+
+	if image_barrier_key(pending) == image_barrier_key(next) {
+		return false
+	}
+
+	let ranges_overlap = |base_a: u32, count_a: u32, base_b: u32, count_b: u32| {
+		// `REMAINING_*` counts are `u32::MAX`
+		let end_a = base_a.saturating_add(count_a);
+		let end_b = base_b.saturating_add(count_b);
+
+		base_a < end_b && base_b < end_a
+	};
+	let (a, b) = (&pending.subresource_range, &next.subresource_range);
+
+	a.aspect_mask.intersects(b.aspect_mask)
+		&& ranges_overlap(a.base_mip_level, a.level_count, b.base_mip_level, b.level_count)
+		&& ranges_overlap(a.base_array_layer, a.layer_count, b.base_array_layer, b.layer_count)
+}
+
+fn merge_buffer_barriers(barriers: Vec<BufferMemoryBarrier>) -> Vec<BufferMemoryBarrier> {
+	let mut raw: Vec<vk::BufferMemoryBarrier> = Transparent::transmute_slice_twice(barriers.as_slice()).to_vec();
+	raw.sort_by_key(|b| {
+		(
+			b.buffer,
+			b.src_access_mask,
+			b.dst_access_mask,
+			b.src_queue_family_index,
+			b.dst_queue_family_index,
+			b.offset
+		)
+	});
+
+	let range_end = |b: &vk::BufferMemoryBarrier| {
+		if b.size == vk::WHOLE_SIZE {
+			vk::WHOLE_SIZE
+		} else {
+			b.offset + b.size
+		}
+	};
+
+	let mut merged: Vec<vk::BufferMemoryBarrier> = Vec::with_capacity(raw.len());
+	for barrier in raw {
+		if let Some(last) = merged.last_mut() {
+			let same_key = last.buffer == barrier.buffer
+				&& last.src_access_mask == barrier.src_access_mask
+				&& last.dst_access_mask == barrier.dst_access_mask
+				&& last.src_queue_family_index == barrier.src_queue_family_index
+				&& last.dst_queue_family_index == barrier.dst_queue_family_index;
+
+			let last_end = range_end(last);
+			if same_key && barrier.offset <= last_end {
+				let end = last_end.max(range_end(&barrier));
+				last.size = if end == vk::WHOLE_SIZE { vk::WHOLE_SIZE } else { end - last.offset };
+
+				continue
+			}
+		}
+
+		merged.push(barrier);
+	}
+
+	merged
+		.into_iter()
+		.map(|b| {
+			// SAFETY: the barriers are merged from valid barriers of the same buffer
+			unsafe {
+				BufferMemoryBarrier::from_raw(
+					vk::BufferMemoryBarrier::builder()
+						.buffer(b.buffer)
+						.offset(b.offset)
+						.size(b.size)
+						.src_access_mask(b.src_access_mask)
+						.dst_access_mask(b.dst_access_mask)
+						.src_queue_family_index(b.src_queue_family_index)
+						.dst_queue_family_index(b.dst_queue_family_index)
+				)
+			}
+		})
+		.collect()
+}
+
+/// Image, layouts, access masks, queue families and subresource range of an image barrier.
+type ImageBarrierKey = (vk::Image, vk::ImageLayout, vk::ImageLayout, vk::AccessFlags, vk::AccessFlags, u32, u32, (vk::ImageAspectFlags, u32, u32, u32, u32));
+
+/// Returns all members of an image barrier that make it distinct.
+fn image_barrier_key(b: &vk::ImageMemoryBarrier) -> ImageBarrierKey {
+	(
+		b.image,
+		b.old_layout,
+		b.new_layout,
+		b.src_access_mask,
+		b.dst_access_mask,
+		b.src_queue_family_index,
+		b.dst_queue_family_index,
+		(
+			b.subresource_range.aspect_mask,
+			b.subresource_range.base_mip_level,
+			b.subresource_range.level_count,
+			b.subresource_range.base_array_layer,
+			b.subresource_range.layer_count
+		)
+	)
+}
+
+fn dedup_image_barriers(mut barriers: Vec<ImageMemoryBarrier>) -> Vec<ImageMemoryBarrier> {
+	let mut index = 0;
+	while index < barriers.len() {
+		let current = image_barrier_key(&barriers[index]);
+		if barriers[.. index].iter().any(|b| image_barrier_key(b) == current) {
+			barriers.remove(index);
+		} else {
+			index += 1;
+		}
+	}
+
+	barriers
+}
+
+impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Starts a batch of barriers that are merged into as few pipeline barriers as possible.
+	pub fn barrier_batch<'b>(&mut self) -> BarrierBatch<'_, 'a, 'b> {
+		BarrierBatch::new(self)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk::{self, Handle};
+
+	use super::{buffer_barriers_depend, image_barriers_depend, merge_buffer_barriers, BufferMemoryBarrier};
+
+	fn barrier(buffer: u64, offset: u64, size: u64, destination_access: vk::AccessFlags) -> BufferMemoryBarrier<'static> {
+		unsafe {
+			BufferMemoryBarrier::from_raw(
+				vk::BufferMemoryBarrier::builder()
+					.buffer(vk::Buffer::from_raw(buffer))
+					.offset(offset)
+					.size(size)
+					.src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+					.dst_access_mask(destination_access)
+			)
+		}
+	}
+
+	#[test]
+	fn buffer_barrier_merging() {
+		let merged = merge_buffer_barriers(vec![
+			barrier(1, 64, 64, vk::AccessFlags::SHADER_READ),
+			barrier(1, 0, 64, vk::AccessFlags::SHADER_READ),
+			barrier(1, 128, vk::WHOLE_SIZE, vk::AccessFlags::SHADER_READ),
+			barrier(1, 0, 16, vk::AccessFlags::UNIFORM_READ),
+			barrier(2, 0, 16, vk::AccessFlags::SHADER_READ)
+		]);
+
+		let ranges: Vec<_> = merged.iter().map(|b| (b.buffer.as_raw(), b.offset, b.size)).collect();
+		assert_eq!(
+			ranges,
+			[(1, 0, 16), (1, 0, vk::WHOLE_SIZE), (2, 0, 16)]
+		);
+	}
+
+	fn transition(base_mip_level: u32, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout) -> vk::ImageMemoryBarrier {
+		vk::ImageMemoryBarrier::builder()
+			.image(vk::Image::from_raw(1))
+			.old_layout(old_layout)
+			.new_layout(new_layout)
+			.subresource_range(vk::ImageSubresourceRange {
+				aspect_mask: vk::ImageAspectFlags::COLOR,
+				base_mip_level,
+				level_count: 1,
+				base_array_layer: 0,
+				layer_count: vk::REMAINING_ARRAY_LAYERS
+			})
+			.build()
+	}
+
+	#[test]
+	fn overlapping_barriers_depend() {
+		let to_transfer = transition(0, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+		let to_sampled = transition(0, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+		let other_level = transition(1, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+		assert!(image_barriers_depend(&to_transfer, &to_sampled));
+		assert!(!image_barriers_depend(&to_transfer, &other_level));
+		assert!(!image_barriers_depend(&to_transfer, &to_transfer));
+
+		let write = barrier(1, 0, 64, vk::AccessFlags::SHADER_READ);
+		let chained = barrier(1, 32, vk::WHOLE_SIZE, vk::AccessFlags::UNIFORM_READ);
+		let disjoint = barrier(1, 64, 64, vk::AccessFlags::UNIFORM_READ);
+		assert!(buffer_barriers_depend(&write, &chained));
+		assert!(!buffer_barriers_depend(&write, &disjoint));
+		assert!(!buffer_barriers_depend(&write, &barrier(1, 16, 16, vk::AccessFlags::SHADER_READ)));
+	}
+}
@@ -0,0 +1,131 @@
+use ash::vk;
+
+#[cfg(feature = "runtime_implicit_validations")]
+use crate::resource::image::params::format_aspects;
+use crate::{
+	command::error::ClearError,
+	prelude::{HasHandle, Image, ImageLayoutClearColorImage, ImageLayoutClearDepthStencilImage, ImageSubresourceRange}
+};
+
+/// Checks that `image` can be cleared in `layout` and that the aspect masks of `ranges` are non-empty and contained
+/// in both the format of `image` and `aspects`.
+#[cfg(feature = "runtime_implicit_validations")]
+fn validate_clear_image(
+	image: &Image,
+	layout: vk::ImageLayout,
+	ranges: &[ImageSubresourceRange],
+	aspects: vk::ImageAspectFlags
+) -> Result<(), ClearError> {
+	image.validate_usage(vk::ImageUsageFlags::TRANSFER_DST)?;
+	image.validate_layout(layout)?;
+
+	let allowed = format_aspects(image.format()) & aspects;
+	for (index, range) in ranges.iter().enumerate() {
+		if range.aspect_mask.is_empty() || !allowed.contains(range.aspect_mask) {
+			return Err(ClearError::RangeAspect { index, aspect_mask: range.aspect_mask, format: image.format() })
+		}
+	}
+
+	Ok(())
+}
+
+impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Clears `ranges` of a color `image` to `value`.
+	///
+	/// Under runtime validations, `image` is checked to have the `TRANSFER_DST` usage, to be allowed in `layout`,
+	/// and `image` must have a color format and `ranges` only the `COLOR` aspect.
+	pub fn clear_color_image(
+		&self,
+		image: &Image,
+		layout: ImageLayoutClearColorImage,
+		value: vk::ClearColorValue,
+		ranges: impl AsRef<[ImageSubresourceRange]>
+	) -> Result<(), ClearError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			validate_clear_image(
+				image,
+				layout.into(),
+				ranges.as_ref(),
+				vk::ImageAspectFlags::COLOR
+			)?;
+		}
+
+		log_trace_common!(
+			"Clear color image:",
+			crate::util::fmt::format_handle(self.handle()),
+			image,
+			layout,
+			ranges.as_ref()
+		);
+
+		let ranges_raw = collect_iter_faster!(
+			ranges
+				.as_ref()
+				.iter()
+				.map(|&range| vk::ImageSubresourceRangeBuilder::from(range).build()),
+			4
+		);
+		unsafe {
+			self.device().cmd_clear_color_image(
+				self.handle(),
+				image.handle(),
+				layout.into(),
+				&value,
+				ranges_raw.as_slice()
+			)
+		}
+
+		Ok(())
+	}
+
+	/// Clears `ranges` of a depth and/or stencil `image` to `value`.
+	///
+	/// Under runtime validations, `image` is checked to have the `TRANSFER_DST` usage, to be allowed in `layout`,
+	/// and the aspect masks of `ranges` must be contained in the depth and stencil aspects of the image format.
+	pub fn clear_depth_stencil_image(
+		&self,
+		image: &Image,
+		layout: ImageLayoutClearDepthStencilImage,
+		value: vk::ClearDepthStencilValue,
+		ranges: impl AsRef<[ImageSubresourceRange]>
+	) -> Result<(), ClearError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			validate_clear_image(
+				image,
+				layout.into(),
+				ranges.as_ref(),
+				vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+			)?;
+		}
+
+		log_trace_common!(
+			"Clear depth stencil image:",
+			crate::util::fmt::format_handle(self.handle()),
+			image,
+			layout,
+			value,
+			ranges.as_ref()
+		);
+
+		let ranges_raw = collect_iter_faster!(
+			ranges
+				.as_ref()
+				.iter()
+				.map(|&range| vk::ImageSubresourceRangeBuilder::from(range).build()),
+			4
+		);
+		unsafe {
+			self.device().cmd_clear_depth_stencil_image(
+				self.handle(),
+				image.handle(),
+				layout.into(),
+				&value,
+				ranges_raw.as_slice()
+			)
+		}
+
+		Ok(())
+	}
+}
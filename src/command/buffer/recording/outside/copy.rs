@@ -83,6 +83,45 @@ vk_builder_wrap! {
 	}
 }
 
+vk_builder_wrap! {
+	pub struct ImageBlit {
+		builder: vk::ImageBlitBuilder<'static> => vk::ImageBlit
+	}
+	impl {
+		/// Region blitted from `source_offsets[0] .. source_offsets[1]` to `destination_offsets[0] .. destination_offsets[1]`.
+		///
+		/// The image is flipped along an axis if the offsets of the source and the destination are in opposite order along it.
+		pub fn new(
+			source_subresource: ImageSubresourceLayers,
+			source_offsets: [vk::Offset3D; 2],
+			destination_subresource: ImageSubresourceLayers,
+			destination_offsets: [vk::Offset3D; 2]
+		) -> Self {
+			let builder = vk::ImageBlit::builder()
+				.src_subresource(source_subresource.transmute().transmute())
+				.src_offsets(source_offsets)
+				.dst_subresource(destination_subresource.transmute().transmute())
+				.dst_offsets(destination_offsets)
+			;
+
+			ImageBlit {
+				builder
+			}
+		}
+	}
+}
+
+/// ### Panic
+///
+/// This function panics if `image` was not created with `usage` or cannot be in `layout`.
+#[cfg(feature = "runtime_implicit_validations")]
+#[track_caller]
+fn validate_transfer_image(image: &Image, layout: vk::ImageLayout, usage: vk::ImageUsageFlags) {
+	if let Err(err) = image.validate_usage(usage).and_then(|_| image.validate_layout(layout)) {
+		panic!("{}", err);
+	}
+}
+
 impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 	pub fn copy_buffer_to_buffer(
 		&self,
@@ -108,6 +147,11 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 		}
 	}
 	
+	/// ### Panic
+	///
+	/// With `runtime_implicit_validations` this function panics if `destination` was not created with `TRANSFER_DST` usage
+	/// or cannot be in `destination_layout`, see `Image::validate_layout`.
+	#[cfg_attr(feature = "runtime_implicit_validations", track_caller)]
 	pub fn copy_buffer_to_image(
 		&self,
 		source: &Buffer,
@@ -115,6 +159,13 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 		destination_layout: ImageLayoutDestination,
 		regions: impl AsRef<[BufferImageCopy]>
 	) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		validate_transfer_image(
+			destination,
+			destination_layout.into(),
+			vk::ImageUsageFlags::TRANSFER_DST
+		);
+
 		log_trace_common!(
 			"Copy buffer to image:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -135,6 +186,11 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 		}
 	}
 
+	/// ### Panic
+	///
+	/// With `runtime_implicit_validations` this function panics if `source` was not created with `TRANSFER_SRC` usage
+	/// or cannot be in `source_layout`, see `Image::validate_layout`.
+	#[cfg_attr(feature = "runtime_implicit_validations", track_caller)]
 	pub fn copy_image_to_buffer(
 		&self,
 		source: &Image,
@@ -142,6 +198,13 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 		destination: &Buffer,
 		regions: impl AsRef<[BufferImageCopy]>
 	) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		validate_transfer_image(
+			source,
+			source_layout,
+			vk::ImageUsageFlags::TRANSFER_SRC
+		);
+
 		log_trace_common!(
 			"Copy image to buffer:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -161,4 +224,60 @@ impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 			)
 		}
 	}
+
+	/// Blits `regions` of `source` to `destination`, converting formats and scaling using `filter`.
+	///
+	/// Swapchain images can be used as the destination if they were created with `TRANSFER_DST` usage.
+	///
+	/// ### Panic
+	///
+	/// With `runtime_implicit_validations` this function panics if `source` was not created with `TRANSFER_SRC` usage,
+	/// `destination` was not created with `TRANSFER_DST` usage or either image cannot be in its layout, see `Image::validate_layout`.
+	#[cfg_attr(feature = "runtime_implicit_validations", track_caller)]
+	pub fn blit_image(
+		&self,
+		source: &Image,
+		source_layout: ImageLayoutSource,
+		destination: &Image,
+		destination_layout: ImageLayoutDestination,
+		regions: impl AsRef<[ImageBlit]>,
+		filter: vk::Filter
+	) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			validate_transfer_image(
+				source,
+				source_layout,
+				vk::ImageUsageFlags::TRANSFER_SRC
+			);
+			validate_transfer_image(
+				destination,
+				destination_layout.into(),
+				vk::ImageUsageFlags::TRANSFER_DST
+			);
+		}
+
+		log_trace_common!(
+			"Blit image:",
+			crate::util::fmt::format_handle(self.handle()),
+			source,
+			source_layout,
+			destination,
+			destination_layout,
+			regions.as_ref(),
+			filter
+		);
+
+		unsafe {
+			self.device().cmd_blit_image(
+				self.handle(),
+				source.handle(),
+				source_layout,
+				destination.handle(),
+				destination_layout.into(),
+				Transparent::transmute_slice_twice(regions.as_ref()),
+				filter
+			)
+		}
+	}
 }
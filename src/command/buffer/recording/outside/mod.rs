@@ -1,8 +1,65 @@
+use std::num::NonZeroU64;
+
+use ash::vk;
+
+use crate::{
+	command::error::UpdateBufferError,
+	prelude::{Buffer, HasHandle}
+};
+
 pub mod barrier;
+pub mod batch;
+pub mod clear;
+#[cfg(feature = "sync2")]
+pub mod barrier2;
 pub mod copy;
+#[cfg(feature = "ray_tracing")]
+pub mod ray_tracing;
+pub mod transition;
+
+/// Maximum size of data passed to `update_buffer`.
+pub const MAX_UPDATE_BUFFER_SIZE: vk::DeviceSize = 65536;
+
+/// Checks the usage, alignment and bounds of a `fill_buffer` or `update_buffer` range.
+///
+/// `size` of `vk::WHOLE_SIZE` covers the rest of the buffer after `offset`.
+#[cfg(feature = "runtime_implicit_validations")]
+fn validate_buffer_range(buffer: &Buffer, offset: vk::DeviceSize, size: vk::DeviceSize) -> Result<(), UpdateBufferError> {
+	if !buffer.usage().contains(vk::BufferUsageFlags::TRANSFER_DST) {
+		return Err(UpdateBufferError::UsageMissing)
+	}
+	if offset % 4 != 0 {
+		return Err(UpdateBufferError::OffsetNotAligned(offset))
+	}
+	if size == vk::WHOLE_SIZE {
+		if offset >= buffer.size().get() {
+			return Err(UpdateBufferError::OutOfBounds { offset, size })
+		}
+
+		return Ok(())
+	}
+	if size == 0 || size % 4 != 0 {
+		return Err(UpdateBufferError::SizeNotAligned(size))
+	}
+	if offset.checked_add(size).map(|end| end > buffer.size().get()).unwrap_or(true) {
+		return Err(UpdateBufferError::OutOfBounds { offset, size })
+	}
+
+	Ok(())
+}
 
 impl<'a> super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Under runtime validations, zero group counts are logged and group counts above `maxComputeWorkGroupCount` panic.
+	#[track_caller]
 	pub fn dispatch(&self, group_count: [u32; 3]) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		super::validate_dispatch(
+			"Dispatch",
+			&self.device().physical_properties().limits,
+			[0; 3],
+			group_count
+		);
+
 		log_trace_common!(
 			"Dispatch:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -19,7 +76,17 @@ impl<'a> super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 		}
 	}
 
+	/// Under runtime validations, zero group counts are logged and `base + group_count` above `maxComputeWorkGroupCount` panics.
+	#[track_caller]
 	pub fn dispatch_base(&self, base: [u32; 3], group_count: [u32; 3]) {
+		#[cfg(feature = "runtime_implicit_validations")]
+		super::validate_dispatch(
+			"Dispatch base",
+			&self.device().physical_properties().limits,
+			base,
+			group_count
+		);
+
 		log_trace_common!(
 			"Dispatch base:",
 			crate::util::fmt::format_handle(self.handle()),
@@ -35,4 +102,120 @@ impl<'a> super::CommandBufferRecordingLockOutsideRenderPass<'a> {
 			)
 		}
 	}
+
+	/// Fills `size` bytes of `buffer` starting at `offset` with repeated `data`.
+	///
+	/// `offset` and `size` must be multiples of 4. `size` can be `vk::WHOLE_SIZE` to fill the rest of the buffer,
+	/// rounded down to a multiple of 4.
+	///
+	/// Under runtime validations, `buffer` is checked to have the `TRANSFER_DST` usage, `offset` and `size` to be aligned
+	/// and the range to be in bounds of `buffer`.
+	pub fn fill_buffer(&self, buffer: &Buffer, offset: vk::DeviceSize, size: NonZeroU64, data: u32) -> Result<(), UpdateBufferError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			validate_buffer_range(buffer, offset, size.get())?;
+		}
+
+		log_trace_common!(
+			"Fill buffer:",
+			crate::util::fmt::format_handle(self.handle()),
+			buffer,
+			offset,
+			size,
+			data
+		);
+
+		unsafe {
+			self.device().cmd_fill_buffer(
+				self.handle(),
+				buffer.handle(),
+				offset,
+				size.get(),
+				data
+			)
+		}
+
+		Ok(())
+	}
+
+	/// Writes `data` into `buffer` at `offset` inline in the command buffer.
+	///
+	/// Intended for small updates, `data` can be at most `MAX_UPDATE_BUFFER_SIZE` bytes and its length must be a multiple of 4.
+	/// `offset` must be a multiple of 4 as well.
+	///
+	/// Under runtime validations, `buffer` is checked to have the `TRANSFER_DST` usage, `offset` and the length of `data`
+	/// to be aligned, `data` to be non-empty and at most `MAX_UPDATE_BUFFER_SIZE` bytes and the range to be in bounds of `buffer`.
+	pub fn update_buffer(&self, buffer: &Buffer, offset: vk::DeviceSize, data: &[u8]) -> Result<(), UpdateBufferError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			let size = data.len() as vk::DeviceSize;
+			if size > MAX_UPDATE_BUFFER_SIZE {
+				return Err(UpdateBufferError::UpdateTooLarge(size))
+			}
+			validate_buffer_range(buffer, offset, size)?;
+		}
+
+		log_trace_common!(
+			"Update buffer:",
+			crate::util::fmt::format_handle(self.handle()),
+			buffer,
+			offset,
+			data.len()
+		);
+
+		unsafe {
+			self.device().cmd_update_buffer(
+				self.handle(),
+				buffer.handle(),
+				offset,
+				data
+			)
+		}
+
+		Ok(())
+	}
+
+	/// Writes `marker` into `buffer` at `offset` once all previous commands finish `stage`.
+	///
+	/// Uses `VK_AMD_buffer_marker` when it is enabled on the device. Otherwise the marker is written by `fill_buffer`,
+	/// which does not wait for `stage` and is only ordered with previous commands by the implicit submission order.
+	///
+	/// `offset` must be a multiple of 4 and `buffer` must be created with `TRANSFER_DST` usage.
+	/// Under runtime validations, this is checked along with the bounds of the marker.
+	pub fn write_buffer_marker(&self, stage: vk::PipelineStageFlags, buffer: &Buffer, offset: vk::DeviceSize, marker: u32) -> Result<(), UpdateBufferError> {
+		if !self.device().is_extension_enabled(vk::AmdBufferMarkerFn::name()) {
+			return self.fill_buffer(
+				buffer,
+				offset,
+				NonZeroU64::new(4).unwrap(),
+				marker
+			)
+		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			validate_buffer_range(buffer, offset, 4)?;
+		}
+
+		log_trace_common!(
+			"Write buffer marker:",
+			crate::util::fmt::format_handle(self.handle()),
+			stage,
+			buffer,
+			offset,
+			marker
+		);
+
+		unsafe {
+			(self.device().ext().buffer_marker().cmd_write_buffer_marker_amd)(
+				self.handle(),
+				stage,
+				buffer.handle(),
+				offset,
+				marker
+			)
+		}
+
+		Ok(())
+	}
 }
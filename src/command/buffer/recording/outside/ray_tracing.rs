@@ -0,0 +1,98 @@
+use ash::vk;
+
+use crate::{
+	prelude::HasHandle,
+	raytracing::{
+		acceleration::{AccelerationStructure, AccelerationStructureBuild, ScratchBuffer},
+		sbt::ShaderBindingTable
+	}
+};
+
+impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Records a build of `destination` described by `build` using `scratch` memory.
+	///
+	/// If `update_source` is set, the build updates it into `destination` instead, which requires the `ALLOW_UPDATE` build flag.
+	///
+	/// ### Panic
+	///
+	/// Under runtime validations, this function will panic if the type of `destination` does not match `build`, `scratch` is too small
+	/// or any of the geometry buffers was not created with `SHADER_DEVICE_ADDRESS` usage.
+	pub fn build_acceleration_structure(
+		&self,
+		build: &AccelerationStructureBuild,
+		update_source: Option<&AccelerationStructure>,
+		destination: &AccelerationStructure,
+		scratch: &ScratchBuffer
+	) {
+		let (geometries, ranges) = build.raw_geometries().expect("geometry buffers must be created with SHADER_DEVICE_ADDRESS usage");
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			assert_eq!(
+				destination.ty(),
+				build.ty,
+				"acceleration structure type must match the build"
+			);
+			let sizes = build.build_sizes(self.device()).expect("geometry buffers were validated above");
+			let required_scratch_size = if update_source.is_some() { sizes.update_scratch_size } else { sizes.build_scratch_size };
+			assert!(
+				scratch.size().get() >= required_scratch_size,
+				"scratch buffer of size {} is too small for the build requiring {}",
+				scratch.size(),
+				required_scratch_size
+			);
+		}
+
+		let build_info = vk::AccelerationStructureBuildGeometryInfoKHR::builder()
+			.ty(build.ty)
+			.flags(build.flags)
+			.mode(if update_source.is_some() {
+				vk::BuildAccelerationStructureModeKHR::UPDATE
+			} else {
+				vk::BuildAccelerationStructureModeKHR::BUILD
+			})
+			.src_acceleration_structure(update_source.map(|source| source.handle()).unwrap_or_default())
+			.dst_acceleration_structure(destination.handle())
+			.geometries(&geometries)
+			.scratch_data(vk::DeviceOrHostAddressKHR { device_address: scratch.address() })
+			.build();
+
+		log_trace_common!(
+			"Building acceleration structure:",
+			crate::util::fmt::format_handle(self.handle()),
+			build,
+			update_source,
+			destination,
+			scratch
+		);
+		unsafe {
+			self.device().ext().acceleration_structure().cmd_build_acceleration_structures(
+				self.handle(),
+				&[build_info],
+				&[&ranges]
+			)
+		}
+	}
+
+	/// Traces rays of the bound ray tracing pipeline using the shader groups in `table`.
+	pub fn trace_rays(&self, table: &ShaderBindingTable, size: [u32; 3]) {
+		log_trace_common!(
+			"Tracing rays:",
+			crate::util::fmt::format_handle(self.handle()),
+			table,
+			size
+		);
+		unsafe {
+			self.device().ext().ray_tracing_pipeline().cmd_trace_rays(
+				self.handle(),
+				table.raygen_region(),
+				table.miss_region(),
+				table.hit_region(),
+				table.callable_region(),
+				size[0],
+				size[1],
+				size[2]
+			)
+		}
+	}
+}
@@ -0,0 +1,174 @@
+//! Image layout transitions with stage and access masks derived from the layouts.
+
+use ash::vk;
+
+use super::barrier::ImageMemoryBarrier;
+use crate::{
+	prelude::{Image, ImageLayoutFinal, ImageSubresourceRange},
+	resource::image::layout::layout_access_scope
+};
+
+/// Description of an image layout transition.
+///
+/// By default the transition covers the whole image with all aspects of its format and the stage and access masks
+/// are derived from the old and new layouts using `layout_access_scope`. Both can be overridden.
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutTransition<'a> {
+	image: &'a Image,
+	subresource_range: ImageSubresourceRange,
+	old_layout: vk::ImageLayout,
+	new_layout: ImageLayoutFinal,
+
+	source_stages: vk::PipelineStageFlags,
+	source_access: vk::AccessFlags,
+	destination_stages: vk::PipelineStageFlags,
+	destination_access: vk::AccessFlags
+}
+impl<'a> LayoutTransition<'a> {
+	/// Transitions the whole `image` from `old_layout` to `new_layout`.
+	///
+	/// Use `vk::ImageLayout::UNDEFINED` as `old_layout` to discard the contents of the image.
+	pub fn new(image: &'a Image, old_layout: vk::ImageLayout, new_layout: ImageLayoutFinal) -> Self {
+		let (source_stages, source_access) = layout_access_scope(old_layout, true);
+		let (destination_stages, destination_access) = layout_access_scope(new_layout.into(), false);
+
+		LayoutTransition {
+			image,
+			subresource_range: image.whole_subresource_range(),
+			old_layout,
+			new_layout,
+
+			source_stages,
+			source_access,
+			destination_stages,
+			destination_access
+		}
+	}
+
+	/// Transitions `subresource_range` of `image` to `new_layout` from the layout recorded in its layout tracker.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the subresources in `subresource_range` are not all in the same tracked layout.
+	pub fn tracked(image: &'a Image, subresource_range: ImageSubresourceRange, new_layout: ImageLayoutFinal) -> Self {
+		let old_layout = image
+			.layout_tracker()
+			.layout(subresource_range)
+			.expect("subresources must be in the same tracked layout");
+
+		Self::new(image, old_layout, new_layout).subresource_range(subresource_range)
+	}
+
+	/// Restricts the transition to `subresource_range`.
+	pub const fn subresource_range(mut self, subresource_range: ImageSubresourceRange) -> Self {
+		self.subresource_range = subresource_range;
+		self
+	}
+
+	/// Overrides the stages and accesses that must complete before the transition.
+	pub const fn source(mut self, stages: vk::PipelineStageFlags, access: vk::AccessFlags) -> Self {
+		self.source_stages = stages;
+		self.source_access = access;
+		self
+	}
+
+	/// Overrides the stages and accesses that wait for the transition.
+	pub const fn destination(mut self, stages: vk::PipelineStageFlags, access: vk::AccessFlags) -> Self {
+		self.destination_stages = stages;
+		self.destination_access = access;
+		self
+	}
+
+	pub const fn source_stages(&self) -> vk::PipelineStageFlags {
+		self.source_stages
+	}
+
+	pub const fn destination_stages(&self) -> vk::PipelineStageFlags {
+		self.destination_stages
+	}
+
+	/// Creates the image memory barrier performing this transition.
+	pub fn barrier(&self) -> ImageMemoryBarrier<'a> {
+		ImageMemoryBarrier::new(
+			self.image,
+			self.subresource_range,
+			self.old_layout,
+			self.new_layout,
+			self.source_access,
+			self.destination_access
+		)
+	}
+}
+
+impl<'a> super::super::CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Records a pipeline barrier transitioning the whole `image` from `old_layout` to `new_layout`.
+	///
+	/// See `LayoutTransition::new`.
+	pub fn transition_layout(&self, image: &Image, old_layout: vk::ImageLayout, new_layout: ImageLayoutFinal) {
+		self.transition_layouts([LayoutTransition::new(
+			image, old_layout, new_layout
+		)])
+	}
+
+	/// Records a pipeline barrier transitioning the whole `image` from its tracked layout to `new_layout`.
+	///
+	/// See `LayoutTransition::tracked`.
+	pub fn transition_layout_tracked(&self, image: &Image, new_layout: ImageLayoutFinal) {
+		self.transition_layouts([LayoutTransition::tracked(
+			image,
+			image.whole_subresource_range(),
+			new_layout
+		)])
+	}
+
+	/// Records a pipeline barrier transitioning the whole swapchain `image` from its tracked layout to `PRESENT_SRC_KHR`.
+	///
+	/// The barrier waits for all commands and does not block any, the presentation engine waits for the semaphore signalled by the submission instead.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `image` is not a swapchain image or if its subresources are not all in the same tracked layout.
+	#[track_caller]
+	pub fn transition_to_present(&self, image: &Image) {
+		assert!(image.is_swapchain_image(), "only swapchain images can be presented");
+
+		self.transition_layouts([LayoutTransition::tracked(
+			image,
+			image.whole_subresource_range(),
+			ImageLayoutFinal::PRESENT_SRC_KHR
+		)])
+	}
+
+	/// Records a single pipeline barrier performing all `transitions`.
+	///
+	/// The barrier waits for the union of all source stages and blocks the union of all destination stages.
+	/// The layout trackers of the images are updated to the new layouts.
+	pub fn transition_layouts<'t>(&self, transitions: impl AsRef<[LayoutTransition<'t>]>) {
+		let transitions = transitions.as_ref();
+		if transitions.is_empty() {
+			return
+		}
+
+		let source_stages = transitions.iter().fold(vk::PipelineStageFlags::empty(), |acc, t| acc | t.source_stages);
+		let destination_stages = transitions.iter().fold(vk::PipelineStageFlags::empty(), |acc, t| acc | t.destination_stages);
+		let barriers = collect_iter_faster!(
+			transitions.iter().map(LayoutTransition::barrier),
+			8
+		);
+
+		self.pipeline_barrier(
+			source_stages,
+			destination_stages,
+			[],
+			[],
+			barriers
+		);
+
+		for transition in transitions {
+			transition
+				.image
+				.layout_tracker()
+				.set_layout(transition.subresource_range, transition.new_layout.into());
+		}
+	}
+}
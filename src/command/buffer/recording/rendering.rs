@@ -0,0 +1,106 @@
+//! Dynamic rendering using `VK_KHR_dynamic_rendering`.
+//!
+//! Allows rendering into image views without creating a `RenderPass` and a `Framebuffer`.
+//! The extension and the `dynamicRendering` feature must be enabled on the device.
+
+use ash::vk;
+
+use super::{CommandBufferRecordingLockInsideRenderPass, CommandBufferRecordingLockOutsideRenderPass};
+use crate::{
+	prelude::{HasHandle, ImageView, Transparent},
+	resource::image::layout::ImageLayoutAttachment
+};
+
+vk_builder_wrap! {
+	/// Transparent wrapper over `vk::RenderingAttachmentInfoBuilder`.
+	pub struct RenderingAttachmentInfo ['a] {
+		builder: vk::RenderingAttachmentInfoBuilder<'a> => vk::RenderingAttachmentInfo
+	}
+	impl ['a] {
+		/// Renders into `image_view` in `layout`.
+		///
+		/// `clear_value` is only used when `load` is `CLEAR`.
+		pub fn new(
+			image_view: &ImageView,
+			layout: ImageLayoutAttachment,
+			load: vk::AttachmentLoadOp,
+			store: vk::AttachmentStoreOp,
+			clear_value: vk::ClearValue
+		) -> Self {
+			RenderingAttachmentInfo {
+				builder: vk::RenderingAttachmentInfo::builder()
+					.image_view(image_view.handle())
+					.image_layout(layout.into())
+					.load_op(load)
+					.store_op(store)
+					.clear_value(clear_value)
+			}
+		}
+
+		/// Resolves the multisampled attachment into `image_view` in `layout` using `mode` at the end of rendering.
+		pub fn resolve(
+			self,
+			mode: vk::ResolveModeFlags,
+			image_view: &ImageView,
+			layout: ImageLayoutAttachment
+		) -> Self {
+			RenderingAttachmentInfo {
+				builder: self.builder
+					.resolve_mode(mode)
+					.resolve_image_view(image_view.handle())
+					.resolve_image_layout(layout.into())
+			}
+		}
+	}
+}
+
+impl<'a> CommandBufferRecordingLockOutsideRenderPass<'a> {
+	/// Begins dynamic rendering into `color_attachments` and optional `depth_attachment` and `stencil_attachment`.
+	///
+	/// The returned lock records commands inside the rendering scope and `end_rendering` (or `end_render_pass`) ends it.
+	/// Subpasses do not exist in dynamic rendering, so `next_subpass` must not be called on the returned lock.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.3-extensions/man/html/vkCmdBeginRendering.html>.
+	pub fn begin_rendering(
+		self,
+		render_area: vk::Rect2D,
+		layer_count: u32,
+		color_attachments: &[RenderingAttachmentInfo],
+		depth_attachment: Option<&RenderingAttachmentInfo>,
+		stencil_attachment: Option<&RenderingAttachmentInfo>,
+		flags: vk::RenderingFlags
+	) -> CommandBufferRecordingLockInsideRenderPass<'a> {
+		let mut rendering_info = vk::RenderingInfo::builder()
+			.flags(flags)
+			.render_area(render_area)
+			.layer_count(layer_count)
+			.color_attachments(Transparent::transmute_slice_twice(
+				color_attachments
+			));
+		if let Some(depth) = depth_attachment {
+			rendering_info = rendering_info.depth_attachment(depth);
+		}
+		if let Some(stencil) = stencil_attachment {
+			rendering_info = rendering_info.stencil_attachment(stencil);
+		}
+
+		log_trace_common!(
+			"Recording BeginRendering:",
+			crate::util::fmt::format_handle(self.handle()),
+			render_area,
+			layer_count,
+			color_attachments,
+			depth_attachment,
+			stencil_attachment,
+			flags
+		);
+		unsafe {
+			self.device()
+				.ext()
+				.dynamic_rendering()
+				.cmd_begin_rendering(self.handle(), &rendering_info);
+		}
+
+		CommandBufferRecordingLockInsideRenderPass(self, true)
+	}
+}
@@ -1,3 +1,5 @@
+use thiserror::Error;
+
 vk_result_error! {
 	#[derive(Debug)]
 	pub enum CommandPoolError {
@@ -5,6 +7,10 @@ vk_result_error! {
 			ERROR_OUT_OF_HOST_MEMORY,
 			ERROR_OUT_OF_DEVICE_MEMORY
 		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Queue must come from the same device as the thread local pools")]
+		QueueDeviceMismatch,
 	}
 }
 
@@ -17,3 +23,111 @@ vk_result_error! {
 		}
 	}
 }
+
+#[derive(Error, Debug)]
+pub enum BindDescriptorSetsError {
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Set index {0} is out of range of the pipeline layout")]
+	SetIndexOutOfRange(u32),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Layout of the descriptor set at index {0} is not compatible with the pipeline layout")]
+	IncompatibleSetLayout(u32)
+}
+
+#[derive(Error, Debug)]
+pub enum BindBuffersError {
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Buffer at index {0} is not from the same device as the command buffer")]
+	DeviceMismatch(usize),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Buffer at index {0} was not created with the {1:?} usage")]
+	UsageMissing(usize, ash::vk::BufferUsageFlags),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Offset of the buffer at index {0} is not less than its size")]
+	OffsetOutOfBounds(usize),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Index buffer offset must be a multiple of the index type size")]
+	IndexOffsetNotAligned
+}
+
+/// Identifies a barrier passed to `pipeline_barrier` by its kind and index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarrierIndex {
+	Memory(usize),
+	Buffer(usize),
+	Image(usize)
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BarrierAccessError {
+	#[error("Source access {access:?} of {barrier:?} is not supported by the source stages {stages:?}")]
+	SourceAccessUnsupported { barrier: BarrierIndex, access: ash::vk::AccessFlags, stages: ash::vk::PipelineStageFlags },
+
+	#[error("Destination access {access:?} of {barrier:?} is not supported by the destination stages {stages:?}")]
+	DestinationAccessUnsupported { barrier: BarrierIndex, access: ash::vk::AccessFlags, stages: ash::vk::PipelineStageFlags }
+}
+
+/// Validation errors of `fill_buffer` and `update_buffer`.
+#[derive(Error, Debug)]
+pub enum UpdateBufferError {
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Buffer was not created with the TRANSFER_DST usage")]
+	UsageMissing,
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Offset {0} must be a multiple of 4")]
+	OffsetNotAligned(ash::vk::DeviceSize),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Size {0} must be a non-zero multiple of 4")]
+	SizeNotAligned(ash::vk::DeviceSize),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Update size {0} is larger than 65536 bytes")]
+	UpdateTooLarge(ash::vk::DeviceSize),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Range of size {size} at offset {offset} is out of bounds of the buffer")]
+	OutOfBounds { offset: ash::vk::DeviceSize, size: ash::vk::DeviceSize }
+}
+
+#[derive(Error, Debug)]
+pub enum ClearError {
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Image cannot be cleared")]
+	ImageUse(#[from] crate::resource::image::error::ImageUseError),
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Aspect mask {aspect_mask:?} of range {index} is not valid for clearing an image of format {format:?}")]
+	RangeAspect { index: usize, aspect_mask: ash::vk::ImageAspectFlags, format: ash::vk::Format },
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Aspect mask {aspect_mask:?} of attachment clear {index} must be a non-empty combination of DEPTH and STENCIL")]
+	AttachmentAspect { index: usize, aspect_mask: ash::vk::ImageAspectFlags },
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Clear rect {0} has zero extent")]
+	EmptyRect(usize)
+}
+
+#[derive(Error, Debug)]
+pub enum OwnershipTransferError {
+	#[error("Source and destination queue families must differ")]
+	SameQueueFamily,
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Queues and the resource must be from the same device")]
+	DeviceMismatch,
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Command buffer must be allocated from queue family {expected}, but its pool is from queue family {actual}")]
+	QueueFamilyMismatch { expected: u32, actual: u32 },
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Image cannot be used in the transfer")]
+	ImageUse(#[from] crate::resource::image::error::ImageUseError)
+}
@@ -1,3 +1,4 @@
 pub mod buffer;
 pub mod error;
+pub mod ownership;
 pub mod pool;
@@ -0,0 +1,253 @@
+//! Queue family ownership transfers of exclusive resources.
+//!
+//! A resource created with exclusive sharing mode must be released by a queue of the family that owns it and then acquired
+//! by a queue of the family that uses it next. Both barriers must specify the same queue families, resource range and,
+//! for images, the same layouts. `OwnershipTransfer` stores these parameters once and records both halves from them.
+//!
+//! The acquire must not execute before the release finished, usually by having the release submission signal a semaphore
+//! that the acquire submission waits on.
+
+use std::{fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use super::error::OwnershipTransferError;
+use crate::prelude::{
+	Buffer,
+	BufferMemoryBarrier,
+	CommandBufferRecordingLockOutsideRenderPass,
+	HasHandle,
+	Image,
+	ImageLayoutFinal,
+	ImageMemoryBarrier,
+	ImageSubresourceRange,
+	MemoryBarrier,
+	Queue
+};
+
+#[derive(Clone, Copy)]
+enum OwnershipResource<'a> {
+	Buffer { buffer: &'a Buffer, offset: vk::DeviceSize, size: NonZeroU64 },
+	Image { image: &'a Image, subresource_range: ImageSubresourceRange, old_layout: vk::ImageLayout, new_layout: ImageLayoutFinal }
+}
+
+/// Matching release and acquire barriers of a queue family ownership transfer, see the module documentation.
+#[derive(Clone, Copy)]
+pub struct OwnershipTransfer<'a> {
+	resource: OwnershipResource<'a>,
+	source_queue_family_index: u32,
+	destination_queue_family_index: u32
+}
+impl<'a> OwnershipTransfer<'a> {
+	/// Transfers the `offset` and `size` range of `buffer` from the family of `source_queue` to the family of `destination_queue`.
+	pub fn buffer(
+		buffer: &'a Buffer,
+		offset: vk::DeviceSize,
+		size: NonZeroU64,
+		source_queue: &Queue,
+		destination_queue: &Queue
+	) -> Result<Self, OwnershipTransferError> {
+		debug_assert!(offset + size.get() <= buffer.size().get());
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if buffer.device() != source_queue.device() || buffer.device() != destination_queue.device() {
+				return Err(OwnershipTransferError::DeviceMismatch)
+			}
+		}
+
+		Self::new(
+			OwnershipResource::Buffer { buffer, offset, size },
+			source_queue,
+			destination_queue
+		)
+	}
+
+	/// Transfers `subresource_range` of `image` from the family of `source_queue` to the family of `destination_queue`,
+	/// transitioning it from `old_layout` to `new_layout`.
+	///
+	/// Swapchain images with exclusive sharing mode presented from a different queue family than the one that rendered them
+	/// are transferred to the present queue family using `PRESENT_SRC_KHR` as `new_layout`.
+	pub fn image(
+		image: &'a Image,
+		subresource_range: ImageSubresourceRange,
+		old_layout: vk::ImageLayout,
+		new_layout: ImageLayoutFinal,
+		source_queue: &Queue,
+		destination_queue: &Queue
+	) -> Result<Self, OwnershipTransferError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if image.device() != source_queue.device() || image.device() != destination_queue.device() {
+				return Err(OwnershipTransferError::DeviceMismatch)
+			}
+			image.validate_layout(old_layout)?;
+			image.validate_layout(new_layout.into())?;
+		}
+
+		Self::new(
+			OwnershipResource::Image { image, subresource_range, old_layout, new_layout },
+			source_queue,
+			destination_queue
+		)
+	}
+
+	fn new(resource: OwnershipResource<'a>, source_queue: &Queue, destination_queue: &Queue) -> Result<Self, OwnershipTransferError> {
+		if source_queue.queue_family_index() == destination_queue.queue_family_index() {
+			return Err(OwnershipTransferError::SameQueueFamily)
+		}
+
+		Ok(OwnershipTransfer {
+			resource,
+			source_queue_family_index: source_queue.queue_family_index(),
+			destination_queue_family_index: destination_queue.queue_family_index()
+		})
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn validate_recording(recording: &CommandBufferRecordingLockOutsideRenderPass, expected: u32) -> Result<(), OwnershipTransferError> {
+		let actual = recording.command_buffer().pool().queue_family_index();
+		if actual != expected {
+			return Err(OwnershipTransferError::QueueFamilyMismatch { expected, actual })
+		}
+
+		Ok(())
+	}
+
+	fn record(
+		&self,
+		recording: &CommandBufferRecordingLockOutsideRenderPass,
+		source_stages: vk::PipelineStageFlags,
+		destination_stages: vk::PipelineStageFlags,
+		source_access: vk::AccessFlags,
+		destination_access: vk::AccessFlags
+	) {
+		match self.resource {
+			OwnershipResource::Buffer { buffer, offset, size } => {
+				let barrier = unsafe {
+					BufferMemoryBarrier::from_raw(
+						vk::BufferMemoryBarrier::builder()
+							.buffer(buffer.handle())
+							.offset(offset)
+							.size(size.get())
+							.src_access_mask(source_access)
+							.dst_access_mask(destination_access)
+							.src_queue_family_index(self.source_queue_family_index)
+							.dst_queue_family_index(self.destination_queue_family_index)
+					)
+				};
+
+				recording.pipeline_barrier(
+					source_stages,
+					destination_stages,
+					[] as [MemoryBarrier; 0],
+					[barrier],
+					[] as [ImageMemoryBarrier; 0]
+				);
+			}
+			OwnershipResource::Image { image, subresource_range, old_layout, new_layout } => {
+				let barrier = unsafe {
+					ImageMemoryBarrier::from_raw(
+						vk::ImageMemoryBarrier::builder()
+							.image(image.handle())
+							.subresource_range(vk::ImageSubresourceRangeBuilder::from(subresource_range).build())
+							.old_layout(old_layout)
+							.new_layout(new_layout.into())
+							.src_access_mask(source_access)
+							.dst_access_mask(destination_access)
+							.src_queue_family_index(self.source_queue_family_index)
+							.dst_queue_family_index(self.destination_queue_family_index)
+					)
+				};
+
+				recording.pipeline_barrier(
+					source_stages,
+					destination_stages,
+					[] as [MemoryBarrier; 0],
+					[] as [BufferMemoryBarrier; 0],
+					[barrier]
+				);
+			}
+		}
+	}
+
+	/// Records the release half of the transfer into `recording`, which must be submitted to a queue of the source family.
+	///
+	/// `source_stages` and `source_access` describe the last use of the resource on the source queue.
+	pub fn record_release(
+		&self,
+		recording: &CommandBufferRecordingLockOutsideRenderPass,
+		source_stages: vk::PipelineStageFlags,
+		source_access: vk::AccessFlags
+	) -> Result<(), OwnershipTransferError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		Self::validate_recording(recording, self.source_queue_family_index)?;
+
+		self.record(
+			recording,
+			source_stages,
+			vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+			source_access,
+			vk::AccessFlags::empty()
+		);
+
+		Ok(())
+	}
+
+	/// Records the acquire half of the transfer into `recording`, which must be submitted to a queue of the destination family.
+	///
+	/// `destination_stages` and `destination_access` describe the first use of the resource on the destination queue.
+	pub fn record_acquire(
+		&self,
+		recording: &CommandBufferRecordingLockOutsideRenderPass,
+		destination_stages: vk::PipelineStageFlags,
+		destination_access: vk::AccessFlags
+	) -> Result<(), OwnershipTransferError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		Self::validate_recording(recording, self.destination_queue_family_index)?;
+
+		self.record(
+			recording,
+			vk::PipelineStageFlags::TOP_OF_PIPE,
+			destination_stages,
+			vk::AccessFlags::empty(),
+			destination_access
+		);
+
+		Ok(())
+	}
+
+	pub const fn source_queue_family_index(&self) -> u32 {
+		self.source_queue_family_index
+	}
+
+	pub const fn destination_queue_family_index(&self) -> u32 {
+		self.destination_queue_family_index
+	}
+}
+impl fmt::Debug for OwnershipTransfer<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut d = f.debug_struct("OwnershipTransfer");
+		match self.resource {
+			OwnershipResource::Buffer { buffer, offset, size } => {
+				d.field("buffer", buffer).field("offset", &offset).field("size", &size);
+			}
+			OwnershipResource::Image { image, subresource_range, old_layout, new_layout } => {
+				d.field("image", image)
+					.field("subresource_range", &subresource_range)
+					.field("old_layout", &old_layout)
+					.field("new_layout", &new_layout);
+			}
+		}
+
+		d.field(
+			"source_queue_family_index",
+			&self.source_queue_family_index
+		)
+		.field(
+			"destination_queue_family_index",
+			&self.destination_queue_family_index
+		)
+		.finish()
+	}
+}
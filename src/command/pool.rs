@@ -1,9 +1,26 @@
-use std::{fmt, ops::Deref, num::NonZeroU32};
+use std::{fmt, num::NonZeroU32, ops::Deref, thread::ThreadId};
 
 use ash::vk;
 
 use super::error::{CommandBufferError, CommandPoolError};
-use crate::{device::Device, memory::host::HostMemoryAllocator, prelude::Vrc, queue::Queue, util::sync::Vutex};
+use crate::{
+	device::Device,
+	memory::host::HostMemoryAllocator,
+	prelude::Vrc,
+	queue::Queue,
+	util::{
+		hash::VHashMap,
+		sync::{Vutex, VutexGuard}
+	}
+};
+
+/// Thread and call site of the recording currently holding the pool lock.
+///
+/// Recording locks the pool for its whole duration, so locking the pool again from the same thread,
+/// for example by dropping a command buffer of the same pool, would deadlock. In debug builds this is detected
+/// and turned into a panic naming both call sites.
+#[cfg(debug_assertions)]
+type RecordingOwner = Option<(std::thread::ThreadId, &'static std::panic::Location<'static>)>;
 
 /// Internally synchronized command pool.
 pub struct CommandPool {
@@ -11,6 +28,8 @@ pub struct CommandPool {
 	queue_family_index: u32,
 
 	pool: Vutex<vk::CommandPool>,
+	#[cfg(debug_assertions)]
+	recording_owner: Vutex<RecordingOwner>,
 
 	host_memory_allocator: HostMemoryAllocator
 }
@@ -51,11 +70,14 @@ impl CommandPool {
 			host_memory_allocator.as_ref()
 		)?;
 
+		queue.device().child_registry().register(pool);
 		Ok(Vrc::new(Self {
 			device: queue.device().clone(),
 			queue_family_index: queue.queue_family_index(),
 
 			pool: Vutex::new(pool),
+			#[cfg(debug_assertions)]
+			recording_owner: Vutex::new(None),
 			host_memory_allocator
 		}))
 	}
@@ -65,10 +87,11 @@ impl CommandPool {
 	/// * This function will panic if the pool `Vutex` is poisoned.
 	/// * This function will panic under Vulkan 1.0.
 	#[cfg(feature = "Vulkan1_1")]
+	#[track_caller]
 	pub fn trim(&self) {
 		use ash::version::DeviceV1_1;
 
-		let lock = self.pool.lock().expect("vutex poisoned");
+		let lock = self.lock_pool();
 
 		unsafe {
 			self.device
@@ -79,8 +102,9 @@ impl CommandPool {
 	/// ### Panic
 	///
 	/// This function will panic if the pool `Vutex` is poisoned.
+	#[track_caller]
 	pub fn reset(&self, return_resources: bool) -> Result<(), CommandPoolError> {
-		let lock = self.pool.lock().expect("vutex poisoned");
+		let lock = self.lock_pool();
 
 		let flags = if return_resources { vk::CommandPoolResetFlags::RELEASE_RESOURCES } else { vk::CommandPoolResetFlags::empty() };
 
@@ -96,6 +120,7 @@ impl CommandPool {
 	/// ### Panic
 	///
 	/// This function will panic if the pool `Vutex` is poisoned.
+	#[track_caller]
 	pub fn allocate_command_buffers<const BUFFERS: usize>(
 		&self,
 		secondary: bool,
@@ -128,13 +153,14 @@ impl CommandPool {
 	/// ### Panic
 	///
 	/// This function will panic if the pool `Vutex` is poisoned.
+	#[track_caller]
 	pub unsafe fn allocate_command_buffers_into(
 		&self,
 		level: vk::CommandBufferLevel,
 		count: NonZeroU32,
 		out: *mut vk::CommandBuffer,
 	) -> Result<(), CommandBufferError> {
-		let lock = self.pool.lock().expect("vutex poisoned");
+		let lock = self.lock_pool();
 
 		let alloc_info = vk::CommandBufferAllocateInfo::builder()
 			.command_pool(*lock)
@@ -165,8 +191,9 @@ impl CommandPool {
 	/// ### Panic
 	///
 	/// This function will panic if the pool `Vutex` is poisoned.
+	#[track_caller]
 	pub unsafe fn free_command_buffers(&self, buffers: impl AsRef<[vk::CommandBuffer]>) {
-		let lock = self.pool.lock().expect("vutex poisoned");
+		let lock = self.lock_pool();
 
 		log_trace_common!(
 			"Freeing command buffers:",
@@ -178,6 +205,60 @@ impl CommandPool {
 		self.device.free_command_buffers(*lock, buffers.as_ref())
 	}
 
+	/// Locks the pool for the duration of a recording started at the caller.
+	///
+	/// In debug builds, the recording is remembered until `recording_finished` is called, see `RecordingOwner`.
+	///
+	/// ### Panic
+	///
+	/// * This function will panic if the pool `Vutex` is poisoned.
+	/// * In debug builds, this function will panic if the pool is already locked by a recording on the current thread.
+	#[track_caller]
+	pub(crate) fn lock_for_recording(&self) -> VutexGuard<'_, vk::CommandPool> {
+		let lock = self.lock_pool();
+
+		#[cfg(debug_assertions)]
+		{
+			*self.recording_owner.lock().expect("vutex poisoned") = Some((
+				std::thread::current().id(),
+				std::panic::Location::caller()
+			));
+		}
+
+		lock
+	}
+
+	/// Forgets the recording remembered by `lock_for_recording`. Must be called before the pool lock is released.
+	pub(crate) fn recording_finished(&self) {
+		#[cfg(debug_assertions)]
+		{
+			*self.recording_owner.lock().expect("vutex poisoned") = None;
+		}
+	}
+
+	/// ### Panic
+	///
+	/// * This function will panic if the pool `Vutex` is poisoned.
+	/// * In debug builds, this function will panic if the pool is already locked by a recording on the current thread.
+	#[track_caller]
+	fn lock_pool(&self) -> VutexGuard<'_, vk::CommandPool> {
+		#[cfg(debug_assertions)]
+		{
+			let owner = *self.recording_owner.lock().expect("vutex poisoned");
+			if let Some((thread, location)) = owner {
+				if thread == std::thread::current().id() {
+					panic!(
+						"Command pool is locked by a recording started at {} on this thread, locking it again at {} would deadlock. The recording must be finished first.",
+						location,
+						std::panic::Location::caller()
+					);
+				}
+			}
+		}
+
+		self.pool.lock().expect("vutex poisoned")
+	}
+
 	pub const fn queue_family_index(&self) -> u32 {
 		self.queue_family_index
 	}
@@ -193,8 +274,9 @@ impl_common_handle_traits! {
 }
 impl Drop for CommandPool {
 	fn drop(&mut self) {
-		let lock = self.pool.lock().expect("vutex poisoned");
+		let lock = self.lock_pool();
 		log_trace_common!("Dropping", self, lock);
+		self.device.child_registry().unregister(*lock);
 
 		unsafe {
 			self.device.destroy_command_pool(
@@ -220,3 +302,113 @@ impl fmt::Debug for CommandPool {
 			.finish()
 	}
 }
+
+/// Command pools for recording from multiple threads, one for each thread and queue family.
+///
+/// Recording locks the command pool, so threads recording into buffers from a shared pool serialize.
+/// `pool` instead returns a pool owned by the calling thread, which is then passed to `CommandBuffer::new`:
+/// ```ignore
+/// let [command_buffer] = CommandBuffer::new(pools.pool(&queue)?, false)?;
+/// ```
+pub struct ThreadLocalPools {
+	device: Vrc<Device>,
+	flags: vk::CommandPoolCreateFlags,
+	pools: Vutex<VHashMap<(ThreadId, u32), Vrc<CommandPool>>>,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl ThreadLocalPools {
+	/// Creates new empty pools, each pool is created with `flags` on first use.
+	pub fn new(device: Vrc<Device>, flags: vk::CommandPoolCreateFlags, host_memory_allocator: HostMemoryAllocator) -> Self {
+		ThreadLocalPools { device, flags, pools: Vutex::new(VHashMap::default()), host_memory_allocator }
+	}
+
+	/// Returns the pool of the current thread for the queue family of `queue`, creating it if it does not exist.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn pool(&self, queue: &Queue) -> Result<Vrc<CommandPool>, CommandPoolError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if queue.device() != &self.device {
+				return Err(CommandPoolError::QueueDeviceMismatch)
+			}
+		}
+
+		let key = (
+			std::thread::current().id(),
+			queue.queue_family_index()
+		);
+		let mut pools = self.pools.lock().expect("vutex poisoned");
+		if let Some(pool) = pools.get(&key) {
+			return Ok(pool.clone())
+		}
+
+		let pool = CommandPool::new(
+			queue,
+			self.flags,
+			self.host_memory_allocator
+		)?;
+		pools.insert(key, pool.clone());
+
+		Ok(pool)
+	}
+
+	/// Resets all pools of all threads, see `CommandPool::reset`.
+	///
+	/// This is intended to be called at frame boundaries. No command buffer allocated from the pools may be
+	/// recording or pending execution.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if any `Vutex` is poisoned.
+	pub fn reset_all(&self, return_resources: bool) -> Result<(), CommandPoolError> {
+		let pools = self.pools.lock().expect("vutex poisoned");
+		for pool in pools.values() {
+			pool.reset(return_resources)?;
+		}
+
+		Ok(())
+	}
+
+	/// Releases the pools of `thread`, for example after it exits.
+	///
+	/// The pools are destroyed once all command buffers allocated from them are dropped.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn release_thread(&self, thread: ThreadId) {
+		self.pools
+			.lock()
+			.expect("vutex poisoned")
+			.retain(|&(pool_thread, _), _| pool_thread != thread);
+	}
+
+	/// Returns the number of pools across all threads and queue families.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn pool_count(&self) -> usize {
+		self.pools.lock().expect("vutex poisoned").len()
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl fmt::Debug for ThreadLocalPools {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ThreadLocalPools")
+			.field("device", &self.device)
+			.field("flags", &self.flags)
+			.field("pools", &self.pools)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
@@ -0,0 +1,172 @@
+//! Markers written around labeled regions of command buffers to find the failing pass after a device loss.
+//!
+//! Each region gets a unique marker. When the region is entered, its marker is written into the first slot of the
+//! breadcrumb buffer and when it is left, into the second slot. After `ERROR_DEVICE_LOST`, `Breadcrumbs::report` reads
+//! the buffer and returns the labels of the last started and the last finished region. A started region that did not finish
+//! is the most likely culprit.
+//!
+//! Markers are written using `write_buffer_marker`, which is only precise with `VK_AMD_buffer_marker` enabled on the device.
+
+use std::{fmt, num::NonZeroU32};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+	memory::device::{MapError, MappingAccessResult},
+	prelude::{Buffer, CommandBufferRecordingLockOutsideRenderPass, Vrc},
+	util::sync::Vutex
+};
+
+/// Size of the breadcrumb buffer in bytes.
+pub const BREADCRUMBS_SIZE: u64 = 8;
+
+#[derive(Error, Debug)]
+pub enum BreadcrumbsError {
+	#[error("Buffer has no bound memory")]
+	NoMemory,
+	#[error("Could not map buffer memory")]
+	Map(#[from] MapError),
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Buffer must be at least BREADCRUMBS_SIZE bytes large")]
+	BufferTooSmall,
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Buffer must be created with TRANSFER_DST usage")]
+	UsageMissing
+}
+
+/// Marker of a region started by `Breadcrumbs::begin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[must_use = "regions must be ended"]
+pub struct BreadcrumbRegion(NonZeroU32);
+
+/// Labels of the last regions reached by the device.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BreadcrumbReport {
+	pub last_started: Option<String>,
+	pub last_finished: Option<String>
+}
+impl fmt::Display for BreadcrumbReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"last started: {}, last finished: {}",
+			self.last_started.as_deref().unwrap_or("<none>"),
+			self.last_finished.as_deref().unwrap_or("<none>")
+		)
+	}
+}
+
+/// Writes markers around labeled regions of command buffers into a host visible buffer.
+pub struct Breadcrumbs {
+	buffer: Vrc<Buffer>,
+	labels: Vutex<Vec<String>>
+}
+impl Breadcrumbs {
+	/// Creates new breadcrumbs writing into `buffer`.
+	///
+	/// `buffer` must be at least `BREADCRUMBS_SIZE` bytes large, must be created with `TRANSFER_DST` usage
+	/// and its memory must be host visible and host coherent. The memory stays mapped so that it can be read after a device loss.
+	pub fn new(buffer: Vrc<Buffer>) -> Result<Self, BreadcrumbsError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if buffer.size().get() < BREADCRUMBS_SIZE {
+				return Err(BreadcrumbsError::BufferTooSmall)
+			}
+			if !buffer.usage().contains(vk::BufferUsageFlags::TRANSFER_DST) {
+				return Err(BreadcrumbsError::UsageMissing)
+			}
+		}
+
+		let breadcrumbs = Breadcrumbs { buffer, labels: Vutex::new(Vec::new()) };
+		breadcrumbs.clear_markers()?;
+
+		Ok(breadcrumbs)
+	}
+
+	fn clear_markers(&self) -> Result<(), BreadcrumbsError> {
+		let memory = self.buffer.memory().ok_or(BreadcrumbsError::NoMemory)?;
+		memory.map_memory_with(|mut access| {
+			access.bytes_mut()[.. BREADCRUMBS_SIZE as usize].fill(0);
+			MappingAccessResult::Continue
+		})?;
+
+		Ok(())
+	}
+
+	/// Records the start of a region labeled `label`.
+	///
+	/// The marker is written once all previous commands finish, so the region is reported as started
+	/// only after everything before it completed.
+	pub fn begin(&self, recording: &CommandBufferRecordingLockOutsideRenderPass, label: impl Into<String>) -> BreadcrumbRegion {
+		let region = {
+			let mut labels = self.labels.lock().expect("vutex poisoned");
+			labels.push(label.into());
+
+			BreadcrumbRegion(NonZeroU32::new(labels.len() as u32).unwrap())
+		};
+
+		recording.write_buffer_marker(
+			vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+			&self.buffer,
+			0,
+			region.0.get()
+		)
+		.expect("breadcrumbs buffer is validated in `new`");
+
+		region
+	}
+
+	/// Records the end of `region`.
+	pub fn end(&self, recording: &CommandBufferRecordingLockOutsideRenderPass, region: BreadcrumbRegion) {
+		recording.write_buffer_marker(
+			vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+			&self.buffer,
+			4,
+			region.0.get()
+		)
+		.expect("breadcrumbs buffer is validated in `new`");
+	}
+
+	/// Reads the markers last written by the device.
+	pub fn report(&self) -> Result<BreadcrumbReport, BreadcrumbsError> {
+		let memory = self.buffer.memory().ok_or(BreadcrumbsError::NoMemory)?;
+
+		let mut markers = [0u32; 2];
+		memory.map_memory_with(|mut access| {
+			let bytes = access.bytes_mut();
+			for (marker, chunk) in markers.iter_mut().zip(bytes.chunks_exact(4)) {
+				*marker = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+			}
+
+			MappingAccessResult::Continue
+		})?;
+
+		let labels = self.labels.lock().expect("vutex poisoned");
+		let label = |marker: u32| marker.checked_sub(1).and_then(|index| labels.get(index as usize)).cloned();
+
+		Ok(BreadcrumbReport { last_started: label(markers[0]), last_finished: label(markers[1]) })
+	}
+
+	/// Forgets all labels and clears the markers.
+	///
+	/// The labels are kept until reset, so this should be called periodically, for example once per frame.
+	/// The device must not be executing any command buffer with markers from these breadcrumbs.
+	pub fn reset(&self) -> Result<(), BreadcrumbsError> {
+		self.labels.lock().expect("vutex poisoned").clear();
+
+		self.clear_markers()
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+}
+impl fmt::Debug for Breadcrumbs {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Breadcrumbs")
+			.field("buffer", &self.buffer)
+			.field("labels", &self.labels)
+			.finish()
+	}
+}
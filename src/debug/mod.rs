@@ -0,0 +1,3 @@
+//! Utilities for debugging GPU crashes.
+
+pub mod breadcrumbs;
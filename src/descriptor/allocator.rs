@@ -0,0 +1,165 @@
+//! Growable descriptor set allocator backed by a list of descriptor pools.
+
+use std::{fmt, num::NonZeroU32};
+
+use ash::vk;
+
+use super::error::{DescriptorAllocatorError, DescriptorSetError};
+use crate::prelude::{DescriptorPool, DescriptorPoolSize, DescriptorSet, DescriptorSetLayout, Device, HostMemoryAllocator, Vrc, Vutex};
+
+#[derive(Debug, Default)]
+struct DescriptorAllocatorPools {
+	/// Pool that is currently being allocated from.
+	current: Option<Vrc<DescriptorPool>>,
+	/// Pools that have run out of memory.
+	exhausted: Vec<Vrc<DescriptorPool>>,
+	/// Pools that were reset and can be reused.
+	recycled: Vec<Vrc<DescriptorPool>>
+}
+
+/// Descriptor set allocator that manages a growable list of descriptor pools.
+///
+/// When the current pool runs out of memory, it is moved to the exhausted list and a new pool is taken
+/// from the recycled list or created. All pools are created with the same sizes.
+///
+/// Descriptor sets are not freed individually when they are dropped, their memory is reclaimed by `recycle`
+/// once no descriptor set allocated from a pool is alive.
+pub struct DescriptorAllocator {
+	device: Vrc<Device>,
+
+	max_sets_per_pool: NonZeroU32,
+	pool_sizes: Vec<DescriptorPoolSize>,
+
+	pools: Vutex<DescriptorAllocatorPools>,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl DescriptorAllocator {
+	pub fn new(
+		device: Vrc<Device>,
+		max_sets_per_pool: NonZeroU32,
+		pool_sizes: impl IntoIterator<Item = DescriptorPoolSize>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Self {
+		DescriptorAllocator {
+			device,
+			max_sets_per_pool,
+			pool_sizes: pool_sizes.into_iter().collect(),
+			pools: Vutex::new(Default::default()),
+			host_memory_allocator
+		}
+	}
+
+	fn create_pool(&self) -> Result<Vrc<DescriptorPool>, DescriptorAllocatorError> {
+		DescriptorPool::new(
+			self.device.clone(),
+			vk::DescriptorPoolCreateFlags::empty(),
+			self.max_sets_per_pool,
+			self.pool_sizes.iter().copied(),
+			None,
+			self.host_memory_allocator.clone()
+		)
+		.map_err(Into::into)
+	}
+
+	/// Allocates a new descriptor set with given `layout`.
+	///
+	/// If the current pool is exhausted, a recycled pool is used or a new pool is created.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the pools `Vutex` is poisoned.
+	pub fn allocate(&self, layout: Vrc<DescriptorSetLayout>) -> Result<Vrc<DescriptorSet>, DescriptorAllocatorError> {
+		let mut pools = self.pools.lock().expect("vutex poisoned");
+
+		if let Some(current) = pools.current.as_ref() {
+			match DescriptorSet::new(current.clone(), layout.clone()) {
+				Ok(set) => return Ok(set),
+				Err(DescriptorSetError::ERROR_OUT_OF_POOL_MEMORY) | Err(DescriptorSetError::ERROR_FRAGMENTED_POOL) => {
+					log::trace!("Descriptor pool exhausted, switching to a new one");
+
+					let exhausted = pools.current.take().unwrap();
+					pools.exhausted.push(exhausted);
+				}
+				Err(err) => return Err(err.into())
+			}
+		}
+
+		let pool = match pools.recycled.pop() {
+			Some(pool) => pool,
+			None => self.create_pool()?
+		};
+		pools.current = Some(pool.clone());
+
+		DescriptorSet::new(pool, layout).map_err(Into::into)
+	}
+
+	/// Resets and recycles all pools that have no descriptor sets alive.
+	///
+	/// The current pool is reset in place, exhausted pools are moved to the recycled list.
+	///
+	/// Returns the number of reset pools.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the pools `Vutex` is poisoned.
+	pub fn recycle(&self) -> usize {
+		let mut pools = self.pools.lock().expect("vutex poisoned");
+		let pools = &mut *pools;
+
+		let mut recycled = 0;
+		if let Some(current) = pools.current.as_ref() {
+			if Vrc::strong_count(current) == 1 {
+				// Safe because there are no descriptor sets allocated from the pool alive
+				unsafe { current.reset() };
+				recycled += 1;
+			}
+		}
+
+		let mut index = 0;
+		while index < pools.exhausted.len() {
+			// Each alive descriptor set holds a reference to its pool
+			if Vrc::strong_count(&pools.exhausted[index]) == 1 {
+				let pool = pools.exhausted.swap_remove(index);
+				// Safe because there are no descriptor sets allocated from the pool alive
+				unsafe { pool.reset() };
+
+				pools.recycled.push(pool);
+				recycled += 1;
+			} else {
+				index += 1;
+			}
+		}
+
+		recycled
+	}
+
+	/// Returns the number of pools currently owned by this allocator.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the pools `Vutex` is poisoned.
+	pub fn pool_count(&self) -> usize {
+		let pools = self.pools.lock().expect("vutex poisoned");
+
+		pools.current.iter().count() + pools.exhausted.len() + pools.recycled.len()
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl fmt::Debug for DescriptorAllocator {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DescriptorAllocator")
+			.field("device", &self.device)
+			.field("max_sets_per_pool", &self.max_sets_per_pool)
+			.field("pool_sizes", &self.pool_sizes)
+			.field("pools", &self.pools)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
@@ -28,6 +28,7 @@ vk_result_error! {
 			ERROR_OUT_OF_HOST_MEMORY,
 			ERROR_OUT_OF_DEVICE_MEMORY,
 			ERROR_FRAGMENTATION_EXT,
+			ERROR_FRAGMENTED_POOL,
 			ERROR_OUT_OF_POOL_MEMORY
 		}
 
@@ -41,6 +42,14 @@ vk_result_error! {
 	}
 }
 
+#[derive(Error, Debug)]
+pub enum DescriptorAllocatorError {
+	#[error("Could not create a new descriptor pool")]
+	Pool(#[from] DescriptorPoolError),
+
+	#[error("Could not allocate descriptor set")]
+	Set(#[from] DescriptorSetError)
+}
 
 vk_result_error! {
 	#[derive(Debug)]
@@ -77,3 +86,13 @@ pub enum DescriptorSetWriteError {
 	#[error("Descriptor count must not be zero")]
 	ZeroCount
 }
+
+#[derive(Error, Debug)]
+pub enum InputAttachmentError {
+	#[error("Input attachment {input_index} references attachment {attachment} but the framebuffer has {attachment_count} attachments")]
+	AttachmentOutOfRange { input_index: usize, attachment: u32, attachment_count: usize },
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Framebuffer attachment cannot be used as an input attachment")]
+	ImageUse(#[from] crate::resource::image::error::ImageUseError)
+}
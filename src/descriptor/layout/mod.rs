@@ -7,9 +7,71 @@ use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Vrc};
 
 pub mod params;
 
+/// Definition of one binding of a descriptor set layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DescriptorSetLayoutBindingDefinition {
+	pub binding: u32,
+	pub descriptor_type: vk::DescriptorType,
+	pub descriptor_count: u32,
+	pub stage_flags: vk::ShaderStageFlags,
+	pub immutable_samplers: Vec<vk::Sampler>
+}
+
+/// Definition of a descriptor set layout.
+///
+/// Two descriptor set layouts are compatible if their definitions are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DescriptorSetLayoutDefinition {
+	pub flags: vk::DescriptorSetLayoutCreateFlags,
+	/// Sorted by binding number.
+	pub bindings: Vec<DescriptorSetLayoutBindingDefinition>
+}
+impl DescriptorSetLayoutDefinition {
+	/// ### Safety
+	///
+	/// All pointers in `create_info` must be valid.
+	pub unsafe fn from_create_info(create_info: &vk::DescriptorSetLayoutCreateInfo) -> Self {
+		let raw_bindings: &[vk::DescriptorSetLayoutBinding] = if create_info.binding_count == 0 {
+			&[]
+		} else {
+			std::slice::from_raw_parts(
+				create_info.p_bindings,
+				create_info.binding_count as usize
+			)
+		};
+
+		let mut bindings: Vec<_> = raw_bindings
+			.iter()
+			.map(|b| {
+				let immutable_samplers = if b.p_immutable_samplers.is_null() {
+					Vec::new()
+				} else {
+					std::slice::from_raw_parts(
+						b.p_immutable_samplers,
+						b.descriptor_count as usize
+					)
+					.to_vec()
+				};
+
+				DescriptorSetLayoutBindingDefinition {
+					binding: b.binding,
+					descriptor_type: b.descriptor_type,
+					descriptor_count: b.descriptor_count,
+					stage_flags: b.stage_flags,
+					immutable_samplers
+				}
+			})
+			.collect();
+		bindings.sort_by_key(|b| b.binding);
+
+		DescriptorSetLayoutDefinition { flags: create_info.flags, bindings }
+	}
+}
+
 pub struct DescriptorSetLayout {
 	device: Vrc<Device>,
 	layout: vk::DescriptorSetLayout,
+	definition: DescriptorSetLayoutDefinition,
 
 	host_memory_allocator: HostMemoryAllocator
 }
@@ -41,6 +103,45 @@ impl DescriptorSetLayout {
 		}
 	}
 
+	/// Creates a new descriptor set layout from `definition`, for example one generated by shader reflection.
+	///
+	/// ### Safety
+	///
+	/// `definition` must be valid as per <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/VkDescriptorSetLayoutCreateInfo.html>.
+	pub unsafe fn from_definition(
+		device: Vrc<Device>,
+		definition: &DescriptorSetLayoutDefinition,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, DescriptorSetLayoutError> {
+		let bindings: Vec<_> = definition
+			.bindings
+			.iter()
+			.map(|b| {
+				let builder = vk::DescriptorSetLayoutBinding::builder()
+					.binding(b.binding)
+					.descriptor_type(b.descriptor_type)
+					.descriptor_count(b.descriptor_count)
+					.stage_flags(b.stage_flags);
+
+				if b.immutable_samplers.is_empty() {
+					builder.build()
+				} else {
+					builder.immutable_samplers(&b.immutable_samplers).build()
+				}
+			})
+			.collect();
+
+		let create_info = vk::DescriptorSetLayoutCreateInfo::builder()
+			.flags(definition.flags)
+			.bindings(bindings.as_slice());
+
+		Self::from_create_info(
+			device,
+			create_info,
+			host_memory_allocator
+		)
+	}
+
 	/// ### Safety
 	///
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkCreateDescriptorSetLayout.html>.
@@ -60,10 +161,13 @@ impl DescriptorSetLayout {
 			create_info.deref(),
 			host_memory_allocator.as_ref()
 		)?;
+		let definition = DescriptorSetLayoutDefinition::from_create_info(create_info.deref());
 
+		device.child_registry().register(layout);
 		Ok(Vrc::new(DescriptorSetLayout {
 			device,
 			layout,
+			definition,
 			host_memory_allocator
 		}))
 	}
@@ -71,6 +175,15 @@ impl DescriptorSetLayout {
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
+
+	pub const fn definition(&self) -> &DescriptorSetLayoutDefinition {
+		&self.definition
+	}
+
+	/// Returns whether this layout is the same as `other` or has the same definition.
+	pub fn is_compatible_with(&self, other: &DescriptorSetLayout) -> bool {
+		self.layout == other.layout || self.definition == other.definition
+	}
 }
 impl_common_handle_traits! {
 	impl HasHandle<vk::DescriptorSetLayout>, Deref, Borrow, Eq, Hash, Ord for DescriptorSetLayout {
@@ -80,6 +193,7 @@ impl_common_handle_traits! {
 impl Drop for DescriptorSetLayout {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.layout);
 
 		unsafe {
 			self.device.destroy_descriptor_set_layout(
@@ -94,6 +208,7 @@ impl fmt::Debug for DescriptorSetLayout {
 		f.debug_struct("DescriptorSetLayout")
 			.field("device", &self.device)
 			.field("layout", &self.safe_handle())
+			.field("definition", &self.definition)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
@@ -101,3 +216,68 @@ impl fmt::Debug for DescriptorSetLayout {
 			.finish()
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::DescriptorSetLayoutDefinition;
+
+	fn definition(bindings: &[(u32, vk::DescriptorType, vk::ShaderStageFlags)]) -> DescriptorSetLayoutDefinition {
+		let raw_bindings: Vec<_> = bindings
+			.iter()
+			.map(|&(binding, descriptor_type, stage_flags)| {
+				vk::DescriptorSetLayoutBinding::builder()
+					.binding(binding)
+					.descriptor_type(descriptor_type)
+					.descriptor_count(1)
+					.stage_flags(stage_flags)
+					.build()
+			})
+			.collect();
+		let create_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(&raw_bindings);
+
+		unsafe { DescriptorSetLayoutDefinition::from_create_info(&create_info) }
+	}
+
+	#[test]
+	fn definition_bindings_are_sorted() {
+		let definition = definition(&[
+			(2, vk::DescriptorType::STORAGE_BUFFER, vk::ShaderStageFlags::COMPUTE),
+			(0, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::COMPUTE),
+			(1, vk::DescriptorType::SAMPLED_IMAGE, vk::ShaderStageFlags::COMPUTE)
+		]);
+
+		let bindings: Vec<_> = definition.bindings.iter().map(|b| b.binding).collect();
+		assert_eq!(bindings, [0, 1, 2]);
+		assert_eq!(
+			definition.bindings[0].descriptor_type,
+			vk::DescriptorType::UNIFORM_BUFFER
+		);
+	}
+
+	#[test]
+	fn definition_equality() {
+		let a = definition(&[
+			(0, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::VERTEX),
+			(1, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::ShaderStageFlags::FRAGMENT)
+		]);
+		let b = definition(&[
+			(1, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::ShaderStageFlags::FRAGMENT),
+			(0, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::VERTEX)
+		]);
+		assert_eq!(a, b);
+
+		let different_stage = definition(&[
+			(0, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::VERTEX),
+			(1, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::ShaderStageFlags::ALL_GRAPHICS)
+		]);
+		assert_ne!(a, different_stage);
+
+		let different_type = definition(&[
+			(0, vk::DescriptorType::STORAGE_BUFFER, vk::ShaderStageFlags::VERTEX),
+			(1, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::ShaderStageFlags::FRAGMENT)
+		]);
+		assert_ne!(a, different_type);
+	}
+}
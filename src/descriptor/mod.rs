@@ -1,3 +1,4 @@
+pub mod allocator;
 pub mod error;
 pub mod layout;
 pub mod pool;
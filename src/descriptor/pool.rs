@@ -22,6 +22,7 @@ impl From<DescriptorPoolSize> for vk::DescriptorPoolSize {
 pub struct DescriptorPool {
 	device: Vrc<Device>,
 	pool: Vutex<vk::DescriptorPool>,
+	flags: vk::DescriptorPoolCreateFlags,
 
 	host_memory_allocator: HostMemoryAllocator
 }
@@ -82,9 +83,11 @@ impl DescriptorPool {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(pool);
 		Ok(Vrc::new(Self {
 			device,
 			pool: Vutex::new(pool),
+			flags: create_info.flags,
 			host_memory_allocator
 		}))
 	}
@@ -184,6 +187,10 @@ impl DescriptorPool {
 	/// ### Safety
 	///
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkResetDescriptorPool.html>.
+	///
+	/// All descriptor sets allocated from this pool are freed, so no `DescriptorSet` allocated from it may be used afterwards.
+	/// Dropping such a `DescriptorSet` is allowed, because dropping never frees its handle, but its handle must not be
+	/// passed to `free_descriptor_sets`.
 	pub unsafe fn reset(&self) {
 		let lock = self.pool.lock().expect("vutex poisoned");
 
@@ -204,6 +211,15 @@ impl DescriptorPool {
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
+
+	pub const fn flags(&self) -> vk::DescriptorPoolCreateFlags {
+		self.flags
+	}
+
+	/// Returns whether descriptor sets allocated from this pool can be individually freed.
+	pub fn can_free_descriptor_sets(&self) -> bool {
+		self.flags.contains(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
+	}
 }
 impl_common_handle_traits! {
 	impl HasSynchronizedHandle<vk::DescriptorPool>, Deref, Borrow, Eq, Hash, Ord for DescriptorPool {
@@ -214,6 +230,7 @@ impl Drop for DescriptorPool {
 	fn drop(&mut self) {
 		let lock = self.pool.lock().expect("vutex poisoned");
 		log_trace_common!("Dropping", self, lock);
+		self.device.child_registry().unregister(*lock);
 
 		unsafe {
 			self.device.destroy_descriptor_pool(
@@ -228,6 +245,7 @@ impl fmt::Debug for DescriptorPool {
 		f.debug_struct("DescriptorPool")
 			.field("device", &self.device)
 			.field("pool", &self.pool)
+			.field("flags", &self.flags)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
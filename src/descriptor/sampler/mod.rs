@@ -46,6 +46,7 @@ impl Sampler {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(sampler);
 		Ok(Vrc::new(Sampler {
 			device,
 			sampler,
@@ -65,6 +66,7 @@ impl_common_handle_traits! {
 impl Drop for Sampler {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.sampler);
 
 		unsafe {
 			self.device.destroy_sampler(
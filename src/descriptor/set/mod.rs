@@ -68,7 +68,7 @@ impl Drop for DescriptorSet {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
 
-		// TODO: Not all descriptor sets are free-able
-		// unsafe { self.pool.free_command_buffers([*lock]) }
+		// The set is not freed here because it may still be used by pending command buffers
+		// or may have already been freed by `DescriptorPool::reset`. It is freed together with its pool instead.
 	}
 }
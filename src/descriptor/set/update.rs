@@ -2,8 +2,11 @@ use std::{num::NonZeroU64, ops::DerefMut};
 
 use ash::vk;
 
-use super::super::error::{DescriptorImageInfoError, DescriptorInlineUniformBlockInfoError, DescriptorSetWriteError};
-use crate::prelude::{Buffer, HasHandle, ImageView, SafeHandle, Sampler, Transparent};
+use super::super::error::{DescriptorImageInfoError, DescriptorInlineUniformBlockInfoError, DescriptorSetWriteError, InputAttachmentError};
+use crate::{
+	prelude::{Buffer, Framebuffer, HasHandle, ImageView, SafeHandle, Sampler, Transparent},
+	render_pass::params::AttachmentReference
+};
 
 vk_builder_wrap! {
 	/// Transparent wrapper struct over `DescriptorImageInfoBuilder`.
@@ -219,3 +222,115 @@ vk_builder_wrap! {
 		}
 	}
 }
+
+/// Image infos of the input attachments of one subpass, bound to the views of a framebuffer.
+///
+/// Input attachment `i` is written to binding `first_binding + i`, which matches shaders declaring
+/// `layout(input_attachment_index = i, set = ..., binding = first_binding + i)`.
+#[derive(Debug)]
+pub struct InputAttachmentInfos<'a> {
+	/// Indexed by input attachment index, `None` for unused input attachments.
+	infos: Vec<Option<[DescriptorImageInfo<'a>; 1]>>
+}
+impl<'a> InputAttachmentInfos<'a> {
+	/// Pairs the views of `framebuffer` referenced by `input_attachments` of a subpass with the layouts of the references.
+	///
+	/// The subpass description holders generated by `render_pass_description!` provide this through `SubpassDescriptionHolder::input_attachment_infos`.
+	pub fn new(input_attachments: &[AttachmentReference], framebuffer: &'a Framebuffer) -> Result<Self, InputAttachmentError> {
+		let views = framebuffer.attachments();
+
+		let infos = input_attachments
+			.iter()
+			.enumerate()
+			.map(|(input_index, reference)| {
+				if reference.attachment == vk::ATTACHMENT_UNUSED {
+					return Ok(None)
+				}
+
+				let view = views.get(reference.attachment as usize).ok_or(
+					InputAttachmentError::AttachmentOutOfRange {
+						input_index,
+						attachment: reference.attachment,
+						attachment_count: views.len()
+					}
+				)?;
+				#[cfg(feature = "runtime_implicit_validations")]
+				view.image().validate_usage(vk::ImageUsageFlags::INPUT_ATTACHMENT)?;
+
+				Ok(Some([DescriptorImageInfo::without_sampler(view, reference.layout)]))
+			})
+			.collect::<Result<Vec<_>, InputAttachmentError>>()?;
+
+		Ok(InputAttachmentInfos { infos })
+	}
+
+	/// Returns `INPUT_ATTACHMENT` writes of the used input attachments into `descriptor_set`.
+	///
+	/// Input attachment `i` is written to binding `first_binding + i` at array element 0. Unused input attachments
+	/// produce no write, but still take up their binding so that the following input attachments keep theirs.
+	pub fn writes<'s>(
+		&'s self,
+		descriptor_set: SafeHandle<'s, vk::DescriptorSet>,
+		first_binding: u32
+	) -> Result<Vec<DescriptorSetWrite<'s>>, DescriptorSetWriteError> {
+		self.infos
+			.iter()
+			.enumerate()
+			.filter_map(|(input_index, info)| info.as_ref().map(|info| (input_index, info)))
+			.map(|(input_index, info)| {
+				DescriptorSetWrite::new(
+					descriptor_set,
+					first_binding + input_index as u32,
+					0,
+					DescriptorSetWriteData::Image(DescriptorTypeImage::INPUT_ATTACHMENT, info)
+				)
+			})
+			.collect()
+	}
+
+	/// Returns the number of input attachments, including unused ones.
+	pub fn len(&self) -> usize {
+		self.infos.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.infos.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk::{self, Handle};
+
+	use super::{DescriptorImageInfo, InputAttachmentInfos};
+	use crate::prelude::SafeHandle;
+
+	fn info(view: u64) -> [DescriptorImageInfo<'static>; 1] {
+		[DescriptorImageInfo {
+			builder: vk::DescriptorImageInfo::builder()
+				.image_view(vk::ImageView::from_raw(view))
+				.image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+		}]
+	}
+
+	#[test]
+	fn input_attachment_writes_bindings() {
+		let infos = InputAttachmentInfos { infos: vec![Some(info(1)), None, Some(info(3))] };
+		let set = vk::DescriptorSet::from_raw(7);
+
+		let writes = infos.writes(unsafe { SafeHandle::from_raw(set) }, 2).unwrap();
+
+		assert_eq!(writes.len(), 2);
+		for (write, (binding, view)) in writes.iter().zip([(2, 1), (4, 3)]) {
+			assert_eq!(write.dst_set, set);
+			assert_eq!(write.dst_binding, binding);
+			assert_eq!(write.dst_array_element, 0);
+			assert_eq!(write.descriptor_count, 1);
+			assert_eq!(write.descriptor_type, vk::DescriptorType::INPUT_ATTACHMENT);
+			assert_eq!(
+				unsafe { (*write.p_image_info).image_view },
+				vk::ImageView::from_raw(view)
+			);
+		}
+	}
+}
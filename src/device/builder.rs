@@ -0,0 +1,207 @@
+use std::{ffi::CStr, fmt, os::raw::c_char};
+
+use ash::vk;
+
+use super::{error, features::DeviceFeatures, Device, DeviceData, QueueCreateInfo};
+use crate::{memory::host::HostMemoryAllocator, physical_device::PhysicalDevice};
+
+/// Builder for `Device` that supports Vulkan 1.1 and 1.2 feature structures.
+///
+/// Supported features are queried when the builder is created and can be inspected using `supported_features`.
+///
+/// Example:
+/// ```no_run
+/// # use vulkayes_core::prelude::*;
+/// # use vulkayes_core::device::builder::DeviceBuilder;
+/// # fn f(physical_device: PhysicalDevice) -> Result<(), vulkayes_core::device::error::DeviceError> {
+/// let builder = DeviceBuilder::new(physical_device).queue(0, [1.0]);
+/// let timeline_semaphore = builder.supported_features().vulkan12.timeline_semaphore;
+///
+/// let device_data = builder
+/// 	.enable_features(|features| features.vulkan12.timeline_semaphore = timeline_semaphore)
+/// 	.build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DeviceBuilder<'a> {
+	physical_device: PhysicalDevice,
+	supported_features: DeviceFeatures,
+
+	queues: Vec<QueueCreateInfo<Vec<f32>>>,
+	layers: Vec<&'a CStr>,
+	extensions: Vec<&'a CStr>,
+	features: DeviceFeatures,
+	host_memory_allocator: HostMemoryAllocator
+}
+impl<'a> DeviceBuilder<'a> {
+	pub fn new(physical_device: PhysicalDevice) -> Self {
+		let supported_features = DeviceFeatures::query(&physical_device);
+
+		DeviceBuilder {
+			physical_device,
+			supported_features,
+			queues: Vec::new(),
+			layers: Vec::new(),
+			extensions: Vec::new(),
+			features: DeviceFeatures::empty(),
+			host_memory_allocator: HostMemoryAllocator::Unspecified()
+		}
+	}
+
+	/// Requests `priorities.len()` queues from the queue family `queue_family_index`.
+	pub fn queue(mut self, queue_family_index: u32, priorities: impl AsRef<[f32]>) -> Self {
+		self.queues.push(QueueCreateInfo {
+			queue_family_index,
+			queue_priorities: priorities.as_ref().to_vec()
+		});
+		self
+	}
+
+	/// Requests queues for each of `queues`.
+	pub fn queues<P: AsRef<[f32]>>(mut self, queues: impl IntoIterator<Item = QueueCreateInfo<P>>) -> Self {
+		for info in queues {
+			self = self.queue(info.queue_family_index, info.queue_priorities);
+		}
+		self
+	}
+
+	pub fn layers(mut self, layers: impl IntoIterator<Item = &'a CStr>) -> Self {
+		self.layers.extend(layers);
+		self
+	}
+
+	pub fn extensions(mut self, extensions: impl IntoIterator<Item = &'a CStr>) -> Self {
+		self.extensions.extend(extensions);
+		self
+	}
+
+	/// Replaces the requested features.
+	pub fn features(mut self, features: impl Into<DeviceFeatures>) -> Self {
+		self.features = features.into();
+		self
+	}
+
+	/// Modifies the requested features in place.
+	pub fn enable_features(mut self, f: impl FnOnce(&mut DeviceFeatures)) -> Self {
+		f(&mut self.features);
+		self
+	}
+
+	/// Enables the extensions and features required by `profile`.
+	///
+	/// The profile is not checked against the physical device, see `DeviceProfile::check`.
+	#[cfg(feature = "profiles")]
+	pub fn profile(mut self, profile: &super::profile::DeviceProfile<'a>) -> Self {
+		self.extensions.extend(profile.extensions);
+		self.features.enable_all_of(&profile.features);
+		self
+	}
+
+	pub fn host_memory_allocator(mut self, host_memory_allocator: HostMemoryAllocator) -> Self {
+		self.host_memory_allocator = host_memory_allocator;
+		self
+	}
+
+	pub const fn physical_device(&self) -> &PhysicalDevice {
+		&self.physical_device
+	}
+
+	/// Features supported by the physical device.
+	pub const fn supported_features(&self) -> &DeviceFeatures {
+		&self.supported_features
+	}
+
+	/// Features requested so far.
+	pub const fn requested_features(&self) -> &DeviceFeatures {
+		&self.features
+	}
+
+	/// Creates the device.
+	///
+	/// When any Vulkan 1.1 or 1.2 feature is requested, the features are passed using `vk::PhysicalDeviceFeatures2`
+	/// in the `p_next` chain, otherwise `p_enabled_features` is used.
+	pub fn build(self) -> Result<DeviceData, error::DeviceError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if self.queues.len() == 0 {
+				return Err(error::DeviceError::QueuesEmpty)
+			}
+			if self.queues.iter().any(|c| c.queue_priorities.len() == 0) {
+				return Err(error::DeviceError::QueuePrioritiesEmpty)
+			}
+			if !self.features.is_subset_of(&self.supported_features) {
+				return Err(error::DeviceError::FeaturesNotSupported)
+			}
+		}
+
+		let queue_create_infos: Vec<_> = self
+			.queues
+			.iter()
+			.map(|q| {
+				vk::DeviceQueueCreateInfo::builder()
+					.queue_family_index(q.queue_family_index)
+					.queue_priorities(&q.queue_priorities)
+					.build()
+			})
+			.collect();
+
+		log::debug!(
+			"Device builder create info {:#?} {:#?} {:#?} {:#?}",
+			self.queues,
+			self.layers,
+			self.extensions,
+			self.features
+		);
+
+		let ptr_layers: Vec<*const c_char> = self.layers.iter().map(|l| l.as_ptr()).collect();
+		let ptr_extensions: Vec<*const c_char> = self.extensions.iter().map(|e| e.as_ptr()).collect();
+		#[allow(unused_mut)]
+		let mut features = self.features;
+
+		let create_info = vk::DeviceCreateInfo::builder()
+			.queue_create_infos(&queue_create_infos)
+			.enabled_layer_names(&ptr_layers)
+			.enabled_extension_names(&ptr_extensions);
+
+		#[cfg(feature = "vulkan1_2")]
+		{
+			if features.has_extended() {
+				let mut features2 = vk::PhysicalDeviceFeatures2::builder()
+					.features(features.core)
+					.push_next(&mut features.vulkan11)
+					.push_next(&mut features.vulkan12);
+
+				return unsafe {
+					Device::from_create_info(
+						self.physical_device,
+						create_info.push_next(&mut features2),
+						self.host_memory_allocator
+					)
+				}
+			}
+		}
+
+		unsafe {
+			Device::from_create_info(
+				self.physical_device,
+				create_info.enabled_features(&features.core),
+				self.host_memory_allocator
+			)
+		}
+	}
+}
+impl fmt::Debug for DeviceBuilder<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DeviceBuilder")
+			.field("physical_device", &self.physical_device)
+			.field("queues", &self.queues)
+			.field("layers", &self.layers)
+			.field("extensions", &self.extensions)
+			.field("features", &self.features)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
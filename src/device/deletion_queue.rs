@@ -0,0 +1,171 @@
+//! Deferred destruction of resources still in use by the device.
+//!
+//! Dropping the last reference to a buffer, image or any other object destroys it immediately, which is undefined behavior
+//! while the device still executes commands using it. `DeletionQueue` keeps such references alive until the fence
+//! of the last submission using them is signaled.
+//!
+//! For resources that are released every frame, `TransientResources::defer_drop` ties the lifetime to the frame fence instead.
+
+use std::{
+	collections::VecDeque,
+	fmt,
+	time::{Duration, Instant}
+};
+
+use super::Device;
+use crate::{
+	prelude::{Fence, Vrc, Vutex},
+	sync::fence::error::{FenceError, FenceStatusError},
+	util::WaitTimeout
+};
+
+/// How long dropping a `DeletionQueue` waits for the pending fences, in nanoseconds.
+pub const DROP_TIMEOUT: u64 = 5_000_000_000;
+
+/// Owned resource kept alive by a deletion queue, usually a `Vrc` of a device object.
+pub type DeferredResource = Box<VSendSync![dyn std::any::Any]>;
+
+struct DeferredGroup {
+	fence: Vrc<Fence>,
+	resources: Vec<DeferredResource>
+}
+impl fmt::Debug for DeferredGroup {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DeferredGroup")
+			.field("fence", &self.fence)
+			.field("resources", &self.resources.len())
+			.finish()
+	}
+}
+
+/// Queue of resources dropped once their associated fence is signaled.
+///
+/// Resources are reclaimed by `collect`, which should be called periodically, for example once per frame.
+/// Dropping the queue waits up to `DROP_TIMEOUT` for all fences to be signaled. Resources whose fences are not signaled
+/// by then, for example because they were never submitted, are leaked instead of being destroyed while possibly in use.
+pub struct DeletionQueue {
+	device: Vrc<Device>,
+	groups: Vutex<VecDeque<DeferredGroup>>
+}
+impl DeletionQueue {
+	pub fn new(device: Vrc<Device>) -> Self {
+		DeletionQueue { device, groups: Vutex::new(VecDeque::new()) }
+	}
+
+	/// Keeps `resource` alive until `fence` is signaled.
+	///
+	/// `fence` must be signaled by the last submission using `resource` and must not be reset before the resource is collected,
+	/// otherwise the resource is kept alive until the fence is signaled again.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn defer_drop(&self, resource: DeferredResource, fence: &Vrc<Fence>) {
+		let mut groups = self.groups.lock().expect("vutex poisoned");
+
+		match groups.back_mut() {
+			Some(group) if Vrc::ptr_eq(&group.fence, fence) => group.resources.push(resource),
+			_ => groups.push_back(DeferredGroup { fence: fence.clone(), resources: vec![resource] })
+		}
+	}
+
+	/// Drops all resources whose fence is signaled and returns how many were dropped.
+	///
+	/// Does not block on any fence.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn collect(&self) -> Result<usize, FenceStatusError> {
+		let mut groups = self.groups.lock().expect("vutex poisoned");
+
+		let mut dropped = 0;
+		let mut index = 0;
+		while index < groups.len() {
+			if groups[index].fence.status()? {
+				// Order does not matter, the fences may be signaled out of order
+				let group = groups.swap_remove_back(index).unwrap();
+				dropped += group.resources.len();
+			} else {
+				index += 1;
+			}
+		}
+
+		Ok(dropped)
+	}
+
+	/// Waits for all fences and drops all resources.
+	///
+	/// `timeout` applies to the whole flush. Returns `false` if it expires before all fences are signaled,
+	/// in which case the resources of the remaining fences stay in the queue.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn flush(&self, timeout: WaitTimeout) -> Result<bool, FenceError> {
+		let mut groups = self.groups.lock().expect("vutex poisoned");
+
+		let deadline = match timeout {
+			WaitTimeout::Timeout(nanos) => Some(Instant::now() + Duration::from_nanos(nanos)),
+			WaitTimeout::None | WaitTimeout::Forever => None
+		};
+		while let Some(group) = groups.front() {
+			let remaining = match deadline {
+				Some(deadline) => WaitTimeout::Timeout(deadline.saturating_duration_since(Instant::now()).as_nanos() as u64),
+				None => timeout
+			};
+			if !group.fence.wait(remaining)? {
+				return Ok(false)
+			}
+			groups.pop_front();
+		}
+
+		Ok(true)
+	}
+
+	/// Returns the number of resources waiting to be dropped.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn len(&self) -> usize {
+		self.groups.lock().expect("vutex poisoned").iter().map(|group| group.resources.len()).sum()
+	}
+
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn is_empty(&self) -> bool {
+		self.groups.lock().expect("vutex poisoned").is_empty()
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl Drop for DeletionQueue {
+	fn drop(&mut self) {
+		match self.flush(WaitTimeout::Timeout(DROP_TIMEOUT)) {
+			Ok(true) => (),
+			Ok(false) => {
+				let groups = std::mem::take(&mut *self.groups.lock().expect("vutex poisoned"));
+				log::error!(
+					"Fences of {} deferred resources were not signaled in time, leaking them",
+					groups.iter().map(|group| group.resources.len()).sum::<usize>()
+				);
+				for group in groups {
+					std::mem::forget(group.resources);
+				}
+			}
+			Err(err) => log::error!("Could not wait for deferred resources: {}", err)
+		}
+	}
+}
+impl fmt::Debug for DeletionQueue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DeletionQueue")
+			.field("device", &self.device)
+			.field("groups", &self.groups)
+			.finish()
+	}
+}
@@ -20,7 +20,11 @@ vk_result_error! {
 
 		#[cfg(feature = "runtime_implicit_validations")]
 		#[error("Queue create info `queue_priorities` array must contain at least one element")]
-		QueuePrioritiesEmpty
+		QueuePrioritiesEmpty,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Requested features are not supported by the physical device")]
+		FeaturesNotSupported
 	}
 }
 
@@ -34,3 +38,16 @@ vk_result_error! {
 		}
 	}
 }
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum SetObjectNameError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY
+		}
+
+		#[error("Object name could not be converted into CStr")]
+		NulError(#[from] std::ffi::NulError)
+	}
+}
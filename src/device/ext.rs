@@ -0,0 +1,211 @@
+//! Lazily-initialized device extension loaders.
+
+use std::{fmt, ops::Deref};
+
+use ash::{
+	extensions::{ext, khr},
+	vk
+};
+
+use super::Device;
+use crate::util::sync::VOnce;
+
+/// Storage of device extension loaders, shared by all objects created from the device.
+#[derive(Default)]
+pub(super) struct DeviceExtensionTable {
+	swapchain: VOnce<khr::Swapchain>,
+	dynamic_rendering: VOnce<khr::DynamicRendering>,
+	present_wait: VOnce<khr::PresentWait>,
+	external_memory_fd: VOnce<khr::ExternalMemoryFd>,
+	external_semaphore_fd: VOnce<khr::ExternalSemaphoreFd>,
+	synchronization2: VOnce<khr::Synchronization2>,
+	buffer_marker: VOnce<vk::AmdBufferMarkerFn>,
+	full_screen_exclusive: VOnce<ext::FullScreenExclusive>,
+	#[cfg(feature = "shader_object")]
+	shader_object: VOnce<crate::shader::object::ShaderObjectFn>,
+	#[cfg(feature = "ray_tracing")]
+	acceleration_structure: VOnce<khr::AccelerationStructure>,
+	#[cfg(feature = "ray_tracing")]
+	ray_tracing_pipeline: VOnce<khr::RayTracingPipeline>
+}
+impl fmt::Debug for DeviceExtensionTable {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut d = f.debug_struct("DeviceExtensionTable");
+		d.field("swapchain", &self.swapchain.get().is_some())
+			.field(
+				"dynamic_rendering",
+				&self.dynamic_rendering.get().is_some()
+			)
+			.field("present_wait", &self.present_wait.get().is_some())
+			.field(
+				"external_memory_fd",
+				&self.external_memory_fd.get().is_some()
+			)
+			.field(
+				"external_semaphore_fd",
+				&self.external_semaphore_fd.get().is_some()
+			)
+			.field(
+				"synchronization2",
+				&self.synchronization2.get().is_some()
+			)
+			.field("buffer_marker", &self.buffer_marker.get().is_some())
+			.field(
+				"full_screen_exclusive",
+				&self.full_screen_exclusive.get().is_some()
+			);
+		#[cfg(feature = "shader_object")]
+		d.field("shader_object", &self.shader_object.get().is_some());
+		#[cfg(feature = "ray_tracing")]
+		d.field(
+			"acceleration_structure",
+			&self.acceleration_structure.get().is_some()
+		)
+		.field(
+			"ray_tracing_pipeline",
+			&self.ray_tracing_pipeline.get().is_some()
+		);
+
+		d.finish()
+	}
+}
+
+/// Accessor of device extension loaders, see `Device::ext`.
+///
+/// Loaders are created on first access and then shared. The corresponding extension must be enabled on the device,
+/// otherwise calling the loader functions will panic.
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceExt<'a> {
+	device: &'a Device
+}
+impl<'a> DeviceExt<'a> {
+	pub(super) const fn new(device: &'a Device) -> Self {
+		DeviceExt { device }
+	}
+
+	/// `VK_KHR_swapchain` loader.
+	pub fn swapchain(self) -> &'a khr::Swapchain {
+		let device = self.device;
+
+		device
+			.extensions
+			.swapchain
+			.get_or_init(|| khr::Swapchain::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_KHR_dynamic_rendering` loader.
+	pub fn dynamic_rendering(self) -> &'a khr::DynamicRendering {
+		let device = self.device;
+
+		device
+			.extensions
+			.dynamic_rendering
+			.get_or_init(|| khr::DynamicRendering::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_KHR_present_wait` loader.
+	pub fn present_wait(self) -> &'a khr::PresentWait {
+		let device = self.device;
+
+		device
+			.extensions
+			.present_wait
+			.get_or_init(|| khr::PresentWait::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_KHR_external_memory_fd` loader.
+	pub fn external_memory_fd(self) -> &'a khr::ExternalMemoryFd {
+		let device = self.device;
+
+		device
+			.extensions
+			.external_memory_fd
+			.get_or_init(|| khr::ExternalMemoryFd::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_KHR_external_semaphore_fd` loader.
+	pub fn external_semaphore_fd(self) -> &'a khr::ExternalSemaphoreFd {
+		let device = self.device;
+
+		device
+			.extensions
+			.external_semaphore_fd
+			.get_or_init(|| khr::ExternalSemaphoreFd::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_KHR_synchronization2` loader.
+	pub fn synchronization2(self) -> &'a khr::Synchronization2 {
+		let device = self.device;
+
+		device
+			.extensions
+			.synchronization2
+			.get_or_init(|| khr::Synchronization2::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_AMD_buffer_marker` function table.
+	///
+	/// ash does not provide a loader for this extension, so the raw function table is returned instead.
+	pub fn buffer_marker(self) -> &'a vk::AmdBufferMarkerFn {
+		let device = self.device;
+
+		device.extensions.buffer_marker.get_or_init(|| {
+			let instance = device.instance();
+			vk::AmdBufferMarkerFn::load(|name| unsafe {
+				std::mem::transmute(instance.get_device_proc_addr(device.handle(), name.as_ptr()))
+			})
+		})
+	}
+
+	/// `VK_EXT_full_screen_exclusive` loader.
+	pub fn full_screen_exclusive(self) -> &'a ext::FullScreenExclusive {
+		let device = self.device;
+
+		device
+			.extensions
+			.full_screen_exclusive
+			.get_or_init(|| ext::FullScreenExclusive::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_EXT_shader_object` function table.
+	///
+	/// ash does not provide definitions for this extension, see the `shader::object` module.
+	#[cfg(feature = "shader_object")]
+	pub fn shader_object(self) -> &'a crate::shader::object::ShaderObjectFn {
+		let device = self.device;
+
+		device.extensions.shader_object.get_or_init(|| {
+			let instance = device.instance();
+			crate::shader::object::ShaderObjectFn::load(|name| unsafe {
+				std::mem::transmute(instance.get_device_proc_addr(device.handle(), name.as_ptr()))
+			})
+		})
+	}
+
+	/// `VK_KHR_acceleration_structure` loader.
+	#[cfg(feature = "ray_tracing")]
+	pub fn acceleration_structure(self) -> &'a khr::AccelerationStructure {
+		let device = self.device;
+
+		device
+			.extensions
+			.acceleration_structure
+			.get_or_init(|| khr::AccelerationStructure::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_KHR_ray_tracing_pipeline` loader.
+	#[cfg(feature = "ray_tracing")]
+	pub fn ray_tracing_pipeline(self) -> &'a khr::RayTracingPipeline {
+		let device = self.device;
+
+		device
+			.extensions
+			.ray_tracing_pipeline
+			.get_or_init(|| khr::RayTracingPipeline::new(device.instance().deref().deref(), device.deref()))
+	}
+
+	/// `VK_EXT_debug_utils` loader of the parent instance.
+	pub fn debug_utils(self) -> &'a ext::DebugUtils {
+		self.device.instance().ext().debug_utils()
+	}
+}
@@ -0,0 +1,285 @@
+//! Device features including the Vulkan 1.1 and 1.2 feature structures.
+
+use std::fmt;
+
+use ash::vk;
+
+use crate::physical_device::PhysicalDevice;
+
+/// Set of device features.
+///
+/// Groups `vk::PhysicalDeviceFeatures` with `vk::PhysicalDeviceVulkan11Features` and `vk::PhysicalDeviceVulkan12Features`
+/// (which contain descriptor indexing, timeline semaphores, buffer device address, etc.).
+///
+/// The `s_type` and `p_next` members of the contained structures are ignored and overwritten when chaining.
+#[derive(Clone, Copy)]
+pub struct DeviceFeatures {
+	pub core: vk::PhysicalDeviceFeatures,
+	#[cfg(feature = "vulkan1_2")]
+	pub vulkan11: vk::PhysicalDeviceVulkan11Features,
+	#[cfg(feature = "vulkan1_2")]
+	pub vulkan12: vk::PhysicalDeviceVulkan12Features
+}
+// `p_next` pointers are never dereferenced through this type.
+unsafe impl Send for DeviceFeatures {}
+unsafe impl Sync for DeviceFeatures {}
+impl DeviceFeatures {
+	/// Returns a set with no features enabled.
+	pub fn empty() -> Self {
+		DeviceFeatures {
+			core: Default::default(),
+			#[cfg(feature = "vulkan1_2")]
+			vulkan11: Default::default(),
+			#[cfg(feature = "vulkan1_2")]
+			vulkan12: Default::default()
+		}
+	}
+
+	/// Queries features supported by `physical_device`.
+	///
+	/// Vulkan 1.1 and 1.2 feature structures are only queried when the physical device supports Vulkan 1.2.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceFeatures2.html>.
+	pub fn query(physical_device: &PhysicalDevice) -> Self {
+		#[allow(unused_mut)]
+		let mut features = DeviceFeatures::empty();
+
+		#[cfg(feature = "vulkan1_2")]
+		{
+			if physical_device.properties().api_version.0 >= vk::API_VERSION_1_2 {
+				let mut vulkan11 = vk::PhysicalDeviceVulkan11Features::default();
+				let mut vulkan12 = vk::PhysicalDeviceVulkan12Features::default();
+				let mut features2 = vk::PhysicalDeviceFeatures2::builder()
+					.push_next(&mut vulkan11)
+					.push_next(&mut vulkan12);
+
+				unsafe {
+					physical_device
+						.instance()
+						.get_physical_device_features2(**physical_device, &mut features2);
+				}
+				features.core = features2.features;
+				features.vulkan11 = vulkan11;
+				features.vulkan12 = vulkan12;
+				features.clear_chain();
+
+				return features
+			}
+		}
+
+		features.core = physical_device.features();
+		features
+	}
+
+	/// Parses enabled features from `create_info`.
+	///
+	/// Reads `p_enabled_features` and `vk::PhysicalDeviceFeatures2`, `vk::PhysicalDeviceVulkan11Features` and
+	/// `vk::PhysicalDeviceVulkan12Features` structures from the `p_next` chain. Other structures are ignored.
+	///
+	/// ### Safety
+	///
+	/// `create_info` must be a valid `vk::DeviceCreateInfo`, including its `p_next` chain.
+	pub unsafe fn from_create_info(create_info: &vk::DeviceCreateInfo) -> Self {
+		let mut features = DeviceFeatures::empty();
+
+		if !create_info.p_enabled_features.is_null() {
+			features.core = *create_info.p_enabled_features;
+		}
+
+		let mut next = create_info.p_next as *const vk::BaseInStructure;
+		while let Some(structure) = next.as_ref() {
+			match structure.s_type {
+				vk::StructureType::PHYSICAL_DEVICE_FEATURES_2 => {
+					features.core = (*(next as *const vk::PhysicalDeviceFeatures2)).features;
+				}
+				#[cfg(feature = "vulkan1_2")]
+				vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_1_FEATURES => {
+					features.vulkan11 = *(next as *const vk::PhysicalDeviceVulkan11Features);
+				}
+				#[cfg(feature = "vulkan1_2")]
+				vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_2_FEATURES => {
+					features.vulkan12 = *(next as *const vk::PhysicalDeviceVulkan12Features);
+				}
+				_ => ()
+			}
+			next = structure.p_next;
+		}
+		features.clear_chain();
+
+		features
+	}
+
+	/// Returns whether every feature enabled in `self` is also enabled in `other`.
+	pub fn is_subset_of(&self, other: &Self) -> bool {
+		#[allow(unused_mut)]
+		let mut result = is_subset(
+			Self::bools(&self.core, &self.core.robust_buffer_access, &self.core.inherited_queries),
+			Self::bools(&other.core, &other.core.robust_buffer_access, &other.core.inherited_queries)
+		);
+
+		#[cfg(feature = "vulkan1_2")]
+		{
+			let vulkan11 = is_subset(
+				Self::bools(&self.vulkan11, &self.vulkan11.storage_buffer16_bit_access, &self.vulkan11.shader_draw_parameters),
+				Self::bools(&other.vulkan11, &other.vulkan11.storage_buffer16_bit_access, &other.vulkan11.shader_draw_parameters)
+			);
+			let vulkan12 = is_subset(
+				Self::bools(&self.vulkan12, &self.vulkan12.sampler_mirror_clamp_to_edge, &self.vulkan12.subgroup_broadcast_dynamic_id),
+				Self::bools(&other.vulkan12, &other.vulkan12.sampler_mirror_clamp_to_edge, &other.vulkan12.subgroup_broadcast_dynamic_id)
+			);
+
+			result = result && vulkan11 && vulkan12;
+		}
+
+		result
+	}
+
+	/// Enables every feature that is enabled in `other`.
+	pub fn enable_all_of(&mut self, other: &Self) {
+		enable_from(
+			&mut self.core,
+			&other.core,
+			Self::bools(&other.core, &other.core.robust_buffer_access, &other.core.inherited_queries)
+		);
+
+		#[cfg(feature = "vulkan1_2")]
+		{
+			enable_from(
+				&mut self.vulkan11,
+				&other.vulkan11,
+				Self::bools(&other.vulkan11, &other.vulkan11.storage_buffer16_bit_access, &other.vulkan11.shader_draw_parameters)
+			);
+			enable_from(
+				&mut self.vulkan12,
+				&other.vulkan12,
+				Self::bools(&other.vulkan12, &other.vulkan12.sampler_mirror_clamp_to_edge, &other.vulkan12.subgroup_broadcast_dynamic_id)
+			);
+		}
+	}
+
+	/// Returns whether no feature is enabled.
+	pub fn is_empty(&self) -> bool {
+		self.is_subset_of(&DeviceFeatures::empty())
+	}
+
+	/// Returns whether any feature of the Vulkan 1.1 or 1.2 feature structures is enabled.
+	#[cfg(feature = "vulkan1_2")]
+	pub fn has_extended(&self) -> bool {
+		let mut extended = DeviceFeatures::empty();
+		extended.vulkan11 = self.vulkan11;
+		extended.vulkan12 = self.vulkan12;
+
+		!extended.is_empty()
+	}
+
+	fn clear_chain(&mut self) {
+		#[cfg(feature = "vulkan1_2")]
+		{
+			self.vulkan11.s_type = vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_1_FEATURES;
+			self.vulkan11.p_next = std::ptr::null_mut();
+			self.vulkan12.s_type = vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_2_FEATURES;
+			self.vulkan12.p_next = std::ptr::null_mut();
+		}
+	}
+
+	/// Views the `vk::Bool32` members of `value` from `first` to `last` (inclusive) as a slice.
+	///
+	/// `first` and `last` must be members of `value`.
+	fn bools<'a, T>(value: &'a T, first: &'a vk::Bool32, last: &'a vk::Bool32) -> &'a [vk::Bool32] {
+		let base = value as *const T as usize;
+		let start = first as *const vk::Bool32 as usize - base;
+		let len = (last as *const vk::Bool32 as usize - base - start) / std::mem::size_of::<vk::Bool32>() + 1;
+
+		unsafe {
+			let ptr = (value as *const T as *const u8).add(start) as *const vk::Bool32;
+			std::slice::from_raw_parts(ptr, len)
+		}
+	}
+}
+impl Default for DeviceFeatures {
+	fn default() -> Self {
+		DeviceFeatures::empty()
+	}
+}
+impl From<vk::PhysicalDeviceFeatures> for DeviceFeatures {
+	fn from(core: vk::PhysicalDeviceFeatures) -> Self {
+		DeviceFeatures { core, ..DeviceFeatures::empty() }
+	}
+}
+impl fmt::Debug for DeviceFeatures {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut d = f.debug_struct("DeviceFeatures");
+		d.field("core", &self.core);
+		#[cfg(feature = "vulkan1_2")]
+		{
+			d.field("vulkan11", &self.vulkan11);
+			d.field("vulkan12", &self.vulkan12);
+		}
+
+		d.finish()
+	}
+}
+
+fn is_subset(left: &[vk::Bool32], right: &[vk::Bool32]) -> bool {
+	left.iter()
+		.zip(right.iter())
+		.all(|(&l, &r)| l == vk::FALSE || r != vk::FALSE)
+}
+
+/// Enables the members of `target` at the same offsets as the enabled members of `source`, which is a slice of `base`.
+fn enable_from<T>(target: &mut T, base: &T, source: &[vk::Bool32]) {
+	let start = source.as_ptr() as usize - base as *const T as usize;
+
+	unsafe {
+		let target = (target as *mut T as *mut u8).add(start) as *mut vk::Bool32;
+		for (index, &value) in source.iter().enumerate() {
+			if value != vk::FALSE {
+				*target.add(index) = vk::TRUE;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::DeviceFeatures;
+
+	#[test]
+	fn subset_of_supported() {
+		let mut supported = DeviceFeatures::empty();
+		supported.core.inherited_queries = vk::TRUE;
+		supported.core.robust_buffer_access = vk::TRUE;
+
+		let mut requested = DeviceFeatures::empty();
+		assert!(requested.is_empty());
+		assert!(requested.is_subset_of(&supported));
+
+		requested.core.inherited_queries = vk::TRUE;
+		assert!(!requested.is_empty());
+		assert!(requested.is_subset_of(&supported));
+
+		requested.core.geometry_shader = vk::TRUE;
+		assert!(!requested.is_subset_of(&supported));
+
+		supported.enable_all_of(&requested);
+		assert!(requested.is_subset_of(&supported));
+		assert_eq!(supported.core.robust_buffer_access, vk::TRUE);
+	}
+
+	#[test]
+	#[cfg(feature = "vulkan1_2")]
+	fn subset_of_extended() {
+		let mut supported = DeviceFeatures::empty();
+		supported.vulkan12.subgroup_broadcast_dynamic_id = vk::TRUE;
+
+		let mut requested = DeviceFeatures::empty();
+		requested.vulkan12.subgroup_broadcast_dynamic_id = vk::TRUE;
+		assert!(requested.has_extended());
+		assert!(requested.is_subset_of(&supported));
+
+		requested.vulkan11.shader_draw_parameters = vk::TRUE;
+		assert!(!requested.is_subset_of(&supported));
+	}
+}
@@ -1,18 +1,34 @@
 //! A device represents an instance of connection to a physical device.
 
-use std::{ffi::CStr, fmt::Debug, ops::Deref, os::raw::c_char};
+use std::{
+	ffi::{CStr, CString},
+	fmt::Debug,
+	num::NonZeroU32,
+	ops::Deref,
+	os::raw::c_char
+};
 
 use ash::vk::{self, DeviceCreateInfo, DeviceQueueCreateInfo};
 
 use crate::{
 	instance::Instance,
-	memory::host::HostMemoryAllocator,
+	memory::{
+		host::HostMemoryAllocator,
+		stats::{MemoryStatsRegistry, MemoryUsageReport}
+	},
 	physical_device::{enumerate::PhysicalDeviceProperties, PhysicalDevice},
 	prelude::Vrc,
-	queue::Queue
+	queue::Queue,
+	util::{destruction::ChildRegistry, handle::HasHandle}
 };
 
+pub mod builder;
+pub mod deletion_queue;
 pub mod error;
+pub mod ext;
+pub mod features;
+#[cfg(feature = "profiles")]
+pub mod profile;
 
 #[derive(Debug, Clone, Copy)]
 pub struct QueueCreateInfo<P: AsRef<[f32]>> {
@@ -33,8 +49,19 @@ pub struct Device {
 
 	physical_device: PhysicalDevice,
 	physical_properties: PhysicalDeviceProperties,
+	enabled_features: features::DeviceFeatures,
+	enabled_extensions: Vec<CString>,
+	device_group_size: NonZeroU32,
+	#[cfg(feature = "sync2")]
+	synchronization2: bool,
+	extensions: ext::DeviceExtensionTable,
+
+	host_memory_allocator: HostMemoryAllocator,
 
-	host_memory_allocator: HostMemoryAllocator
+	children: ChildRegistry,
+	memory_stats: MemoryStatsRegistry,
+	#[cfg(feature = "shader_reflection")]
+	shader_reflections: crate::shader::reflect::ShaderReflectionRegistry
 }
 impl Device {
 	pub fn new<'a, P: AsRef<[f32]> + Debug>(
@@ -45,7 +72,55 @@ impl Device {
 		features: vk::PhysicalDeviceFeatures,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<DeviceData, error::DeviceError> {
-		let queues = queues.as_ref();
+		Self::create(
+			physical_device,
+			&[],
+			queues.as_ref(),
+			layers,
+			extensions,
+			features,
+			host_memory_allocator
+		)
+	}
+
+	/// Creates a new device using all physical devices of `group`, see the `instance::device_group` module.
+	///
+	/// Queue families and features of the first physical device of the group are used.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn from_device_group<'a, P: AsRef<[f32]> + Debug>(
+		group: &crate::instance::device_group::PhysicalDeviceGroup,
+		queues: impl AsRef<[QueueCreateInfo<P>]>,
+		layers: impl IntoIterator<Item = &'a CStr> + std::fmt::Debug,
+		extensions: impl IntoIterator<Item = &'a CStr> + std::fmt::Debug,
+		features: vk::PhysicalDeviceFeatures,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<DeviceData, error::DeviceError> {
+		let physical_devices: Vec<_> = group.physical_devices().iter().map(|physical_device| **physical_device).collect();
+
+		Self::create(
+			group.physical_devices()[0].clone(),
+			&physical_devices,
+			queues.as_ref(),
+			layers,
+			extensions,
+			features,
+			host_memory_allocator
+		)
+	}
+
+	fn create<'a, P: AsRef<[f32]> + Debug>(
+		physical_device: PhysicalDevice,
+		device_group: &[vk::PhysicalDevice],
+		queues: &[QueueCreateInfo<P>],
+		layers: impl IntoIterator<Item = &'a CStr> + std::fmt::Debug,
+		extensions: impl IntoIterator<Item = &'a CStr> + std::fmt::Debug,
+		features: vk::PhysicalDeviceFeatures,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<DeviceData, error::DeviceError> {
 
 		#[cfg(feature = "runtime_implicit_validations")]
 		{
@@ -81,11 +156,15 @@ impl Device {
 
 		let ptr_layers: Vec<*const c_char> = layers.into_iter().map(CStr::as_ptr).collect();
 		let ptr_extensions: Vec<*const c_char> = extensions.into_iter().map(CStr::as_ptr).collect();
-		let create_info = vk::DeviceCreateInfo::builder()
+		let mut device_group_info = vk::DeviceGroupDeviceCreateInfo::builder().physical_devices(device_group);
+		let mut create_info = vk::DeviceCreateInfo::builder()
 			.queue_create_infos(&queue_create_infos)
 			.enabled_layer_names(ptr_layers.as_slice())
 			.enabled_extension_names(ptr_extensions.as_slice())
 			.enabled_features(&features);
+		if !device_group.is_empty() {
+			create_info = create_info.push_next(&mut device_group_info);
+		}
 
 		unsafe {
 			Device::from_create_info(
@@ -112,6 +191,14 @@ impl Device {
 			create_info.deref(),
 			host_memory_allocator
 		);
+		let enabled_features = features::DeviceFeatures::from_create_info(&create_info);
+		let device_group_size = device_group_size(&create_info);
+		let enabled_extensions: Vec<CString> = (0 .. create_info.enabled_extension_count as usize)
+			.map(|index| CStr::from_ptr(*create_info.pp_enabled_extension_names.add(index)).to_owned())
+			.collect();
+		#[cfg(feature = "sync2")]
+		let synchronization2 = enabled_extensions.iter().any(|name| name.as_c_str() == ash::extensions::khr::Synchronization2::name())
+			&& crate::sync::sync2::synchronization2_feature_enabled(&create_info);
 		let device = physical_device.instance().create_device(
 			*physical_device,
 			&create_info,
@@ -122,9 +209,20 @@ impl Device {
 			device_handle: device.handle(),
 			device,
 			physical_properties: physical_device.properties(),
+			enabled_features,
+			enabled_extensions,
+			device_group_size,
+			#[cfg(feature = "sync2")]
+			synchronization2,
+			extensions: Default::default(),
 			physical_device,
-			host_memory_allocator
+			host_memory_allocator,
+			children: ChildRegistry::new(),
+			memory_stats: MemoryStatsRegistry::new(),
+			#[cfg(feature = "shader_reflection")]
+			shader_reflections: crate::shader::reflect::ShaderReflectionRegistry::new()
 		});
+		device.instance().child_registry().register(device.device_handle);
 		let queues = device.get_created_queues(create_info);
 
 		Ok(DeviceData { device, queues })
@@ -154,6 +252,34 @@ impl Device {
 		unsafe { self.device.device_wait_idle().map_err(Into::into) }
 	}
 
+	/// Assigns a debug `name` to `object`.
+	///
+	/// Returns `false` without doing anything if the `VK_EXT_debug_utils` loader is not available on the instance,
+	/// see `Instance::debug_utils`.
+	pub fn set_object_name<T: vk::Handle + Copy>(&self, object: &impl HasHandle<T>, name: &str) -> Result<bool, error::SetObjectNameError> {
+		let loader = match self.instance().debug_utils() {
+			None => return Ok(false),
+			Some(loader) => loader
+		};
+
+		let name = CString::new(name)?;
+		let name_info = vk::DebugUtilsObjectNameInfoEXT::builder()
+			.object_type(T::TYPE)
+			.object_handle(object.handle().as_raw())
+			.object_name(&name);
+
+		log_trace_common!(
+			"Setting object name:",
+			self,
+			name_info.deref()
+		);
+		unsafe {
+			loader.debug_utils_set_object_name(self.device_handle, &name_info)?;
+		}
+
+		Ok(true)
+	}
+
 	pub const fn physical_device(&self) -> &PhysicalDevice {
 		&self.physical_device
 	}
@@ -163,10 +289,111 @@ impl Device {
 		&self.physical_properties
 	}
 
+	/// Returns lazily-initialized extension loaders shared by objects created from this device.
+	pub const fn ext(&self) -> ext::DeviceExt<'_> {
+		ext::DeviceExt::new(self)
+	}
+
+	/// Features enabled on this device.
+	///
+	/// Parsed from the create info, including Vulkan 1.1 and 1.2 feature structures in the `p_next` chain.
+	pub const fn enabled_features(&self) -> &features::DeviceFeatures {
+		&self.enabled_features
+	}
+
+	/// Returns whether extension `name` was enabled when creating this device.
+	pub fn is_extension_enabled(&self, name: &CStr) -> bool {
+		self.enabled_extensions.iter().any(|extension| extension.as_c_str() == name)
+	}
+
+	/// Returns the number of physical devices this device was created from, greater than one for device groups.
+	pub const fn device_group_size(&self) -> NonZeroU32 {
+		self.device_group_size
+	}
+
+	/// Returns the device mask selecting all physical devices of this device, see the `instance::device_group` module.
+	pub const fn all_devices_mask(&self) -> u32 {
+		crate::instance::device_group::device_mask_all(self.device_group_size.get())
+	}
+
+	/// Returns whether `VK_KHR_synchronization2` and its `synchronization2` feature are enabled on this device.
+	///
+	/// When they are not, the synchronization2 commands fall back to the legacy ones.
+	#[cfg(feature = "sync2")]
+	pub const fn synchronization2_enabled(&self) -> bool {
+		self.synchronization2
+	}
+
 	pub const fn instance(&self) -> &Vrc<Instance> {
 		self.physical_device.instance()
 	}
+
+	/// Registry of objects created from this device.
+	///
+	/// Only tracks anything when the `debug_destruction_order` feature is enabled.
+	pub const fn child_registry(&self) -> &ChildRegistry {
+		&self.children
+	}
+
+	/// Logs a warning listing the objects created from this device that are still alive and returns their number.
+	///
+	/// Intended to be called at teardown after dropping all objects created from this device, see the `util::destruction` module.
+	/// Always returns `0` unless the `debug_destruction_order` feature is enabled.
+	pub fn report_live_children(&self) -> usize {
+		self.children.report_live(crate::util::fmt::format_handle(self.device_handle))
+	}
+
+	/// Registry of live device memory allocations used by `memory_usage_report`.
+	pub const fn memory_stats(&self) -> &MemoryStatsRegistry {
+		&self.memory_stats
+	}
+
+	/// Returns heap budgets, usage and the `largest_count` largest allocations of this device.
+	///
+	/// Budgets and usage are only available when `VK_EXT_memory_budget` is enabled and the `vulkan1_1` feature is on,
+	/// otherwise only the allocations recorded in `memory_stats` are reported.
+	pub fn memory_usage_report(&self, largest_count: usize) -> MemoryUsageReport {
+		#[cfg(feature = "vulkan1_1")]
+		{
+			if self.is_extension_enabled(vk::ExtMemoryBudgetFn::name()) {
+				let (memory_properties, budget) = self.physical_device.memory_budget_properties();
+				return self.memory_stats.report(&memory_properties, Some(&budget), largest_count)
+			}
+		}
+
+		self.memory_stats.report(
+			&self.physical_device.memory_properties(),
+			None,
+			largest_count
+		)
+	}
+
+	/// Reflections of live shader modules created from this device.
+	#[cfg(feature = "shader_reflection")]
+	pub const fn shader_reflections(&self) -> &crate::shader::reflect::ShaderReflectionRegistry {
+		&self.shader_reflections
+	}
 }
+/// Reads the number of physical devices from a `vk::DeviceGroupDeviceCreateInfo` in the `p_next` chain of `create_info`.
+///
+/// ### Safety
+///
+/// `create_info` must be a valid `vk::DeviceCreateInfo`, including its `p_next` chain.
+unsafe fn device_group_size(create_info: &DeviceCreateInfo) -> NonZeroU32 {
+	let mut next = create_info.p_next as *const vk::BaseInStructure;
+	while let Some(structure) = next.as_ref() {
+		if structure.s_type == vk::StructureType::DEVICE_GROUP_DEVICE_CREATE_INFO {
+			let info = &*(next as *const vk::DeviceGroupDeviceCreateInfo);
+			if let Some(count) = NonZeroU32::new(info.physical_device_count) {
+				return count
+			}
+		}
+		next = structure.p_next;
+	}
+
+	NonZeroU32::new(1).unwrap()
+}
+
 impl_common_handle_traits! {
 	impl HasHandle<vk::Device>, Borrow, Eq, Hash, Ord for Device {
 		target = { device_handle }
@@ -184,6 +411,8 @@ impl Drop for Device {
 		log_trace_common!(info; "Dropping", self);
 
 		let _ = self.wait_idle();
+		self.children.report_surviving(crate::util::fmt::format_handle(self.device_handle));
+		self.instance().child_registry().unregister(self.device_handle);
 		unsafe {
 			self.device
 				.destroy_device(self.host_memory_allocator.as_ref());
@@ -0,0 +1,225 @@
+//! Device selection and creation driven by Vulkan profiles.
+//!
+//! A `DeviceProfile` describes a baseline of capabilities: the minimum api version, required extensions, required features
+//! and limits. Instead of checking each capability by hand, an application targeting the baseline checks physical devices
+//! using `DeviceProfile::check` and creates the device using `DeviceBuilder::profile`, which enables everything the profile requires.
+//!
+//! Profiles are defined in code, profile JSON files are not parsed. `DeviceProfile::roadmap_2022` describes the `VP_KHR_roadmap_2022` profile.
+
+use std::{ffi::CStr, fmt};
+
+use ash::vk;
+use thiserror::Error;
+
+use super::features::DeviceFeatures;
+use crate::{
+	instance::{error::PhysicalDeviceEnumerationError, Instance},
+	physical_device::{enumerate::EnumerateError, PhysicalDevice},
+	prelude::Vrc,
+	util::fmt::VkVersion
+};
+
+#[derive(Error, Debug)]
+pub enum ProfileError {
+	#[error("Profile requires api version {required} but the device supports {supported}")]
+	ApiVersion { required: VkVersion, supported: VkVersion },
+	#[error("Could not enumerate device extensions")]
+	Enumerate(#[from] EnumerateError),
+	#[error("Profile requires extensions {0:?} which are not supported")]
+	ExtensionsMissing(Vec<String>),
+	#[error("Profile requires features which are not supported")]
+	FeaturesMissing,
+	#[error("Profile requires limits {0:?} which are not met")]
+	LimitsNotMet(Vec<&'static str>)
+}
+
+/// Requirement on one member of `vk::PhysicalDeviceLimits`.
+#[derive(Debug, Clone, Copy)]
+pub enum ProfileLimit {
+	/// The limit named by the first member must be at least the last member.
+	Min(&'static str, fn(&vk::PhysicalDeviceLimits) -> u64, u64),
+	/// The limit named by the first member must be at most the last member.
+	Max(&'static str, fn(&vk::PhysicalDeviceLimits) -> u64, u64)
+}
+impl ProfileLimit {
+	pub const fn name(&self) -> &'static str {
+		match self {
+			ProfileLimit::Min(name, _, _) | ProfileLimit::Max(name, _, _) => name
+		}
+	}
+
+	/// Returns whether `limits` satisfy this requirement.
+	pub fn is_met(&self, limits: &vk::PhysicalDeviceLimits) -> bool {
+		match *self {
+			ProfileLimit::Min(_, get, min) => get(limits) >= min,
+			ProfileLimit::Max(_, get, max) => get(limits) <= max
+		}
+	}
+}
+
+/// Baseline of device capabilities, see the module documentation.
+#[derive(Clone)]
+pub struct DeviceProfile<'a> {
+	pub name: &'a str,
+	pub api_version: VkVersion,
+	pub extensions: &'a [&'a CStr],
+	pub features: DeviceFeatures,
+	pub limits: &'a [ProfileLimit]
+}
+impl DeviceProfile<'static> {
+	/// Returns the `VP_KHR_roadmap_2022` profile.
+	///
+	/// The profile requires Vulkan 1.3, whose mandatory features cover the Vulkan 1.3 feature structure requirements of the profile.
+	/// Only the integer limits of the profile are checked.
+	pub fn roadmap_2022() -> Self {
+		let mut features = DeviceFeatures::empty();
+
+		let core = &mut features.core;
+		core.full_draw_index_uint32 = vk::TRUE;
+		core.image_cube_array = vk::TRUE;
+		core.independent_blend = vk::TRUE;
+		core.sample_rate_shading = vk::TRUE;
+		core.draw_indirect_first_instance = vk::TRUE;
+		core.depth_clamp = vk::TRUE;
+		core.depth_bias_clamp = vk::TRUE;
+		core.sampler_anisotropy = vk::TRUE;
+		core.occlusion_query_precise = vk::TRUE;
+		core.fragment_stores_and_atomics = vk::TRUE;
+		core.shader_storage_image_extended_formats = vk::TRUE;
+		core.shader_uniform_buffer_array_dynamic_indexing = vk::TRUE;
+		core.shader_sampled_image_array_dynamic_indexing = vk::TRUE;
+		core.shader_storage_buffer_array_dynamic_indexing = vk::TRUE;
+		core.shader_storage_image_array_dynamic_indexing = vk::TRUE;
+
+		features.vulkan11.sampler_ycbcr_conversion = vk::TRUE;
+
+		let vulkan12 = &mut features.vulkan12;
+		vulkan12.sampler_mirror_clamp_to_edge = vk::TRUE;
+		vulkan12.descriptor_indexing = vk::TRUE;
+		vulkan12.shader_uniform_texel_buffer_array_dynamic_indexing = vk::TRUE;
+		vulkan12.shader_storage_texel_buffer_array_dynamic_indexing = vk::TRUE;
+		vulkan12.shader_uniform_buffer_array_non_uniform_indexing = vk::TRUE;
+		vulkan12.shader_sampled_image_array_non_uniform_indexing = vk::TRUE;
+		vulkan12.shader_storage_buffer_array_non_uniform_indexing = vk::TRUE;
+		vulkan12.shader_storage_image_array_non_uniform_indexing = vk::TRUE;
+		vulkan12.shader_uniform_texel_buffer_array_non_uniform_indexing = vk::TRUE;
+		vulkan12.shader_storage_texel_buffer_array_non_uniform_indexing = vk::TRUE;
+		vulkan12.descriptor_binding_sampled_image_update_after_bind = vk::TRUE;
+		vulkan12.descriptor_binding_storage_image_update_after_bind = vk::TRUE;
+		vulkan12.descriptor_binding_storage_buffer_update_after_bind = vk::TRUE;
+		vulkan12.descriptor_binding_uniform_texel_buffer_update_after_bind = vk::TRUE;
+		vulkan12.descriptor_binding_storage_texel_buffer_update_after_bind = vk::TRUE;
+		vulkan12.descriptor_binding_update_unused_while_pending = vk::TRUE;
+		vulkan12.descriptor_binding_partially_bound = vk::TRUE;
+		vulkan12.descriptor_binding_variable_descriptor_count = vk::TRUE;
+		vulkan12.runtime_descriptor_array = vk::TRUE;
+		vulkan12.scalar_block_layout = vk::TRUE;
+
+		DeviceProfile { name: "VP_KHR_roadmap_2022", api_version: VkVersion(vk::API_VERSION_1_3), extensions: &[], features, limits: ROADMAP_2022_LIMITS }
+	}
+}
+impl<'a> DeviceProfile<'a> {
+	/// Checks whether `physical_device` supports this profile.
+	///
+	/// The api version, extensions, features and limits are checked in this order and the first unmet category is returned.
+	pub fn check(&self, physical_device: &PhysicalDevice) -> Result<(), ProfileError> {
+		let properties = physical_device.properties();
+		if properties.api_version.0 < self.api_version.0 {
+			return Err(ProfileError::ApiVersion { required: self.api_version, supported: properties.api_version })
+		}
+
+		let supported_extensions: Vec<_> = physical_device.extensions_properties()?.collect();
+		let missing_extensions: Vec<String> = self
+			.extensions
+			.iter()
+			.filter(|required| {
+				!supported_extensions
+					.iter()
+					.any(|supported| supported.extension_name.as_bytes() == required.to_bytes())
+			})
+			.map(|required| required.to_string_lossy().into_owned())
+			.collect();
+		if !missing_extensions.is_empty() {
+			return Err(ProfileError::ExtensionsMissing(missing_extensions))
+		}
+
+		if !self.features.is_subset_of(&DeviceFeatures::query(physical_device)) {
+			return Err(ProfileError::FeaturesMissing)
+		}
+
+		let unmet_limits: Vec<&'static str> = self
+			.limits
+			.iter()
+			.filter(|limit| !limit.is_met(&properties.limits))
+			.map(ProfileLimit::name)
+			.collect();
+		if !unmet_limits.is_empty() {
+			return Err(ProfileError::LimitsNotMet(unmet_limits))
+		}
+
+		Ok(())
+	}
+
+	/// Returns physical devices of `instance` that support this profile, in enumeration order.
+	///
+	/// Devices that do not support the profile are logged at debug level together with the reason.
+	pub fn supported_devices(&self, instance: &Vrc<Instance>) -> Result<Vec<PhysicalDevice>, PhysicalDeviceEnumerationError> {
+		let devices = instance
+			.physical_devices()?
+			.filter(|physical_device| match self.check(physical_device) {
+				Ok(()) => true,
+				Err(err) => {
+					log::debug!(
+						"Physical device {} does not support profile {}: {}",
+						physical_device.properties().device_name,
+						self.name,
+						err
+					);
+					false
+				}
+			})
+			.collect();
+
+		Ok(devices)
+	}
+}
+impl fmt::Debug for DeviceProfile<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DeviceProfile")
+			.field("name", &self.name)
+			.field("api_version", &self.api_version)
+			.field("extensions", &self.extensions)
+			.field("features", &self.features)
+			.field("limits", &self.limits.len())
+			.finish()
+	}
+}
+
+const ROADMAP_2022_LIMITS: &[ProfileLimit] = &[
+	ProfileLimit::Min("maxImageDimension1D", |l| l.max_image_dimension1_d as u64, 8192),
+	ProfileLimit::Min("maxImageDimension2D", |l| l.max_image_dimension2_d as u64, 8192),
+	ProfileLimit::Min("maxImageDimensionCube", |l| l.max_image_dimension_cube as u64, 8192),
+	ProfileLimit::Min("maxImageArrayLayers", |l| l.max_image_array_layers as u64, 2048),
+	ProfileLimit::Min("maxUniformBufferRange", |l| l.max_uniform_buffer_range as u64, 65536),
+	ProfileLimit::Max("bufferImageGranularity", |l| l.buffer_image_granularity, 4096),
+	ProfileLimit::Min("maxPerStageDescriptorSamplers", |l| l.max_per_stage_descriptor_samplers as u64, 64),
+	ProfileLimit::Min("maxPerStageDescriptorUniformBuffers", |l| l.max_per_stage_descriptor_uniform_buffers as u64, 15),
+	ProfileLimit::Min("maxPerStageDescriptorStorageBuffers", |l| l.max_per_stage_descriptor_storage_buffers as u64, 30),
+	ProfileLimit::Min("maxPerStageDescriptorSampledImages", |l| l.max_per_stage_descriptor_sampled_images as u64, 200),
+	ProfileLimit::Min("maxPerStageDescriptorStorageImages", |l| l.max_per_stage_descriptor_storage_images as u64, 16),
+	ProfileLimit::Min("maxPerStageResources", |l| l.max_per_stage_resources as u64, 200),
+	ProfileLimit::Min("maxDescriptorSetSamplers", |l| l.max_descriptor_set_samplers as u64, 576),
+	ProfileLimit::Min("maxDescriptorSetUniformBuffers", |l| l.max_descriptor_set_uniform_buffers as u64, 90),
+	ProfileLimit::Min("maxDescriptorSetStorageBuffers", |l| l.max_descriptor_set_storage_buffers as u64, 96),
+	ProfileLimit::Min("maxDescriptorSetSampledImages", |l| l.max_descriptor_set_sampled_images as u64, 1800),
+	ProfileLimit::Min("maxDescriptorSetStorageImages", |l| l.max_descriptor_set_storage_images as u64, 144),
+	ProfileLimit::Min("maxFragmentCombinedOutputResources", |l| l.max_fragment_combined_output_resources as u64, 16),
+	ProfileLimit::Min("maxComputeWorkGroupInvocations", |l| l.max_compute_work_group_invocations as u64, 256),
+	ProfileLimit::Min("maxComputeWorkGroupSize[0]", |l| l.max_compute_work_group_size[0] as u64, 256),
+	ProfileLimit::Min("maxComputeWorkGroupSize[1]", |l| l.max_compute_work_group_size[1] as u64, 256),
+	ProfileLimit::Min("maxComputeWorkGroupSize[2]", |l| l.max_compute_work_group_size[2] as u64, 64),
+	ProfileLimit::Min("subTexelPrecisionBits", |l| l.sub_texel_precision_bits as u64, 8),
+	ProfileLimit::Min("mipmapPrecisionBits", |l| l.mipmap_precision_bits as u64, 6),
+	ProfileLimit::Min("maxColorAttachments", |l| l.max_color_attachments as u64, 7),
+	ProfileLimit::Min("standardSampleLocations", |l| l.standard_sample_locations as u64, 1)
+];
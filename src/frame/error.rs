@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+use crate::{command::error::CommandPoolError, memory::device::MapError, sync::fence::error::FenceError};
+
+#[derive(Error, Debug)]
+pub enum TransientResourcesError {
+	#[error("Staging buffer must have bound memory")]
+	StagingBufferNoMemory,
+	#[error("Could not create command pool")]
+	CommandPool(#[from] CommandPoolError)
+}
+
+#[derive(Error, Debug)]
+pub enum BeginFrameError {
+	#[error("Could not wait for or reset the frame fence")]
+	Fence(#[from] FenceError),
+	#[error("Could not reset command pool")]
+	CommandPool(#[from] CommandPoolError)
+}
+
+#[derive(Error, Debug)]
+pub enum StagingError {
+	#[error("Staging buffer does not have enough space left in this frame")]
+	OutOfSpace,
+	#[error("Could not map staging buffer memory")]
+	Map(#[from] MapError)
+}
@@ -0,0 +1,157 @@
+//! Per-frame transient resources.
+//!
+//! Resources that only live for the duration of one frame (command buffers, descriptor sets, staging data)
+//! can be allocated from `TransientResources` and are all reclaimed together once the frame fence is signaled.
+//!
+//! Usually there is one `TransientResources` object per frame in flight.
+
+use std::{fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use crate::{
+	memory::device::MappingAccessResult,
+	device::deletion_queue::DeferredResource,
+	prelude::{Buffer, CommandPool, DescriptorAllocator, Fence, HostMemoryAllocator, Queue, Vrc, Vutex}
+};
+
+pub mod error;
+
+/// Groups a transient command pool, a descriptor allocator and a staging buffer that are reset together.
+pub struct TransientResources {
+	command_pool: Vrc<CommandPool>,
+	descriptor_allocator: DescriptorAllocator,
+
+	staging_buffer: Vrc<Buffer>,
+	staging_offset: Vutex<vk::DeviceSize>,
+
+	deferred: Vutex<Vec<DeferredResource>>
+}
+impl TransientResources {
+	/// Creates new transient resources.
+	///
+	/// The command pool is created with `TRANSIENT` flag on the queue family of `queue`.
+	///
+	/// `staging_buffer` must have host visible and host coherent memory bound.
+	pub fn new(
+		queue: &Queue,
+		descriptor_allocator: DescriptorAllocator,
+		staging_buffer: Vrc<Buffer>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, error::TransientResourcesError> {
+		if staging_buffer.memory().is_none() {
+			return Err(error::TransientResourcesError::StagingBufferNoMemory)
+		}
+
+		let command_pool = CommandPool::new(
+			queue,
+			vk::CommandPoolCreateFlags::TRANSIENT,
+			host_memory_allocator
+		)?;
+
+		Ok(TransientResources {
+			command_pool,
+			descriptor_allocator,
+			staging_buffer,
+			staging_offset: Vutex::new(0),
+
+			deferred: Vutex::new(Vec::new())
+		})
+	}
+
+	/// Waits for `frame_fence`, resets it and then resets all transient resources.
+	///
+	/// `frame_fence` must be the fence that was signaled by the last submission that used these resources.
+	///
+	/// Command buffers allocated from the command pool are reset, descriptor pools that have no descriptor sets alive
+	/// are reset, the staging buffer is rewound to the beginning and resources passed to `defer_drop` are dropped.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if any `Vutex` is poisoned.
+	pub fn begin_frame(&self, frame_fence: &Fence) -> Result<(), error::BeginFrameError> {
+		log_trace_common!("Beginning frame:", self, frame_fence);
+
+		frame_fence.wait(Default::default())?;
+		frame_fence.reset()?;
+
+		self.command_pool.reset(false)?;
+		self.descriptor_allocator.recycle();
+		*self.staging_offset.lock().expect("vutex poisoned") = 0;
+		self.deferred.lock().expect("vutex poisoned").clear();
+
+		Ok(())
+	}
+
+	/// Keeps `resource` alive until the next `begin_frame` finishes waiting for the frame fence.
+	///
+	/// `resource` must not be used by any submission other than those of this frame.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn defer_drop(&self, resource: DeferredResource) {
+		self.deferred.lock().expect("vutex poisoned").push(resource);
+	}
+
+	/// Writes `data` into the staging buffer and returns the offset at which it was written.
+	///
+	/// The offset is aligned to `alignment`, which must be a power of two.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if any `Vutex` is poisoned.
+	pub fn stage<T: Copy>(&self, data: &[T], alignment: NonZeroU64) -> Result<vk::DeviceSize, error::StagingError> {
+		let mut staging_offset = self.staging_offset.lock().expect("vutex poisoned");
+
+		let offset = crate::util::align_up(*staging_offset as usize, alignment.get() as usize) as vk::DeviceSize;
+		let end = offset + std::mem::size_of_val(data) as vk::DeviceSize;
+		if end > self.staging_buffer.size().get() {
+			return Err(error::StagingError::OutOfSpace)
+		}
+
+		// Memory presence is checked in constructor
+		self.staging_buffer.memory().unwrap().map_memory_with(|mut access| {
+			access.write_slice(data, offset as usize, Default::default());
+			MappingAccessResult::Continue
+		})?;
+		*staging_offset = end;
+
+		Ok(offset)
+	}
+
+	/// Returns the number of bytes of the staging buffer used in this frame.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn staging_used(&self) -> vk::DeviceSize {
+		*self.staging_offset.lock().expect("vutex poisoned")
+	}
+
+	pub const fn command_pool(&self) -> &Vrc<CommandPool> {
+		&self.command_pool
+	}
+
+	pub const fn descriptor_allocator(&self) -> &DescriptorAllocator {
+		&self.descriptor_allocator
+	}
+
+	pub const fn staging_buffer(&self) -> &Vrc<Buffer> {
+		&self.staging_buffer
+	}
+}
+impl fmt::Debug for TransientResources {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TransientResources")
+			.field("command_pool", &self.command_pool)
+			.field("descriptor_allocator", &self.descriptor_allocator)
+			.field("staging_buffer", &self.staging_buffer)
+			.field("staging_offset", &self.staging_offset)
+			.field(
+				"deferred",
+				&self.deferred.lock().expect("vutex poisoned").len()
+			)
+			.finish()
+	}
+}
@@ -11,3 +11,13 @@ vk_result_error! {
 		RenderPassAttachmentsDeviceMismatch,
 	}
 }
+
+#[derive(Error, Debug)]
+pub enum RenderTargetError<AllocError: std::error::Error + 'static> {
+	#[error("Could not create image")]
+	Image(#[from] crate::resource::image::error::ImageError<AllocError>),
+	#[error("Could not create image view")]
+	ImageView(#[from] crate::resource::image::error::ImageViewError),
+	#[error("Could not create framebuffer")]
+	Framebuffer(#[from] FramebufferError)
+}
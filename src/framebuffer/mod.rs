@@ -5,6 +5,7 @@ use ash::vk;
 use crate::prelude::{HasHandle, HostMemoryAllocator, ImageView, RenderPass, Vrc};
 
 pub mod error;
+pub mod render_target;
 
 pub struct Framebuffer {
 	render_pass: Vrc<RenderPass>,
@@ -20,7 +21,7 @@ impl Framebuffer {
 		layers: NonZeroU32,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, error::FramebufferError> {
-		let attachments = collect_iter_faster!(attachments, 8);
+		let attachments = collect_iter_faster!(@vec attachments, 8);
 
 		#[cfg(feature = "runtime_implicit_validations")]
 		{
@@ -71,6 +72,7 @@ impl Framebuffer {
 			host_memory_allocator.as_ref()
 		)?;
 
+		render_pass.device().child_registry().register(framebuffer);
 		Ok(Vrc::new(Framebuffer {
 			render_pass,
 			attachments,
@@ -95,6 +97,7 @@ impl_common_handle_traits! {
 impl Drop for Framebuffer {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.render_pass.device().child_registry().unregister(self.framebuffer);
 
 		unsafe {
 			self.render_pass.device().destroy_framebuffer(
@@ -0,0 +1,267 @@
+//! Offscreen render targets bundling attachment images with a framebuffer.
+
+use std::{fmt, num::NonZeroU32};
+
+use ash::vk;
+
+use super::{error::RenderTargetError, Framebuffer};
+use crate::{
+	memory::device::allocator::ImageMemoryAllocator,
+	prelude::{HostMemoryAllocator, Image, ImageAllocatorParams, ImageView, RenderPass, Vrc},
+	queue::sharing_mode::SharingMode,
+	resource::image::{
+		params::{format_aspects, ImageSize, ImageSizeInfo, ImageTilingAndLayout, ImageViewRange, MipmapLevels},
+		MixedDynImage
+	}
+};
+
+/// Parameters of render target attachments.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderTargetParams {
+	pub color_format: vk::Format,
+	/// Usage of the image holding the final color in addition to `COLOR_ATTACHMENT`, for example `SAMPLED`.
+	pub color_usage: vk::ImageUsageFlags,
+	/// Sample count of the color and depth/stencil attachments.
+	///
+	/// When greater than one, a single-sampled resolve image is created in addition to the multisampled color image.
+	pub samples: vk::SampleCountFlags,
+	/// Format of the depth/stencil attachment, if any.
+	pub depth_stencil_format: Option<vk::Format>,
+	/// Queue family that uses the attachments.
+	pub queue_family_index: u32
+}
+
+struct Attachments {
+	color: Vrc<ImageView>,
+	depth_stencil: Option<Vrc<ImageView>>,
+	resolve: Option<Vrc<ImageView>>,
+	framebuffer: Vrc<Framebuffer>
+}
+
+/// Color image with optional depth/stencil and resolve images and a matching framebuffer.
+///
+/// The framebuffer attachments are, in order: the color attachment, the depth/stencil attachment if `depth_stencil_format` is set,
+/// and the resolve attachment if `samples` is greater than one. The render pass must declare compatible attachments in the same order.
+pub struct RenderTarget<A: ImageMemoryAllocator> {
+	render_pass: Vrc<RenderPass>,
+	params: RenderTargetParams,
+	size: [NonZeroU32; 2],
+
+	color: Vrc<ImageView>,
+	depth_stencil: Option<Vrc<ImageView>>,
+	resolve: Option<Vrc<ImageView>>,
+	framebuffer: Vrc<Framebuffer>,
+
+	allocator: A,
+	requirements: A::AllocationRequirements,
+	host_memory_allocator: HostMemoryAllocator
+}
+impl<A: ImageMemoryAllocator> RenderTarget<A>
+where
+	A::AllocationRequirements: Clone
+{
+	/// Creates the attachment images of `size` using `allocator` with `requirements` and a framebuffer for `render_pass`.
+	pub fn new(
+		render_pass: Vrc<RenderPass>,
+		params: RenderTargetParams,
+		size: [NonZeroU32; 2],
+		allocator: A,
+		requirements: A::AllocationRequirements,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, RenderTargetError<A::Error>> {
+		let Attachments { color, depth_stencil, resolve, framebuffer } = Self::create_attachments(
+			&render_pass,
+			&params,
+			size,
+			&allocator,
+			&requirements,
+			&host_memory_allocator
+		)?;
+
+		Ok(RenderTarget {
+			render_pass,
+			params,
+			size,
+
+			color,
+			depth_stencil,
+			resolve,
+			framebuffer,
+
+			allocator,
+			requirements,
+			host_memory_allocator
+		})
+	}
+
+	/// Recreates the attachment images and the framebuffer with `size`.
+	///
+	/// The previous images are released when no longer referenced, the device must not be using them through this render target anymore.
+	pub fn resize(&mut self, size: [NonZeroU32; 2]) -> Result<(), RenderTargetError<A::Error>> {
+		let Attachments { color, depth_stencil, resolve, framebuffer } = Self::create_attachments(
+			&self.render_pass,
+			&self.params,
+			size,
+			&self.allocator,
+			&self.requirements,
+			&self.host_memory_allocator
+		)?;
+
+		self.size = size;
+		self.color = color;
+		self.depth_stencil = depth_stencil;
+		self.resolve = resolve;
+		self.framebuffer = framebuffer;
+
+		Ok(())
+	}
+
+	fn create_attachments(
+		render_pass: &Vrc<RenderPass>,
+		params: &RenderTargetParams,
+		size: [NonZeroU32; 2],
+		allocator: &A,
+		requirements: &A::AllocationRequirements,
+		host_memory_allocator: &HostMemoryAllocator
+	) -> Result<Attachments, RenderTargetError<A::Error>> {
+		let multisampled = params.samples != vk::SampleCountFlags::TYPE_1;
+		let create_view = |format: vk::Format, usage: vk::ImageUsageFlags, samples: vk::SampleCountFlags| {
+			let size_info = if samples == vk::SampleCountFlags::TYPE_1 {
+				ImageSizeInfo::from(ImageSize::from(ImageSize::new_2d(
+					size[0],
+					size[1],
+					NonZeroU32::new(1).unwrap(),
+					MipmapLevels::One()
+				)))
+			} else {
+				ImageSizeInfo::Multisampled(
+					size[0],
+					size[1],
+					NonZeroU32::new(1).unwrap(),
+					samples
+				)
+			};
+
+			let image = Image::new(
+				render_pass.device().clone(),
+				format,
+				size_info,
+				ImageTilingAndLayout::OptimalUndefined(),
+				usage,
+				SharingMode::one(params.queue_family_index),
+				ImageAllocatorParams::Some { allocator, requirements: requirements.clone() },
+				*host_memory_allocator
+			)?;
+
+			let view = ImageView::new(
+				MixedDynImage::Image(image),
+				ImageViewRange::Type2D(0, NonZeroU32::new(1).unwrap(), 0),
+				None,
+				vk::ComponentMapping::default(),
+				format_aspects(format),
+				*host_memory_allocator
+			)?;
+
+			Ok::<_, RenderTargetError<A::Error>>(view)
+		};
+
+		let color = if multisampled {
+			create_view(
+				params.color_format,
+				vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+				params.samples
+			)?
+		} else {
+			create_view(
+				params.color_format,
+				vk::ImageUsageFlags::COLOR_ATTACHMENT | params.color_usage,
+				params.samples
+			)?
+		};
+		let depth_stencil = match params.depth_stencil_format {
+			Some(format) => Some(create_view(
+				format,
+				vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+				params.samples
+			)?),
+			None => None
+		};
+		let resolve = if multisampled {
+			Some(create_view(
+				params.color_format,
+				vk::ImageUsageFlags::COLOR_ATTACHMENT | params.color_usage,
+				vk::SampleCountFlags::TYPE_1
+			)?)
+		} else {
+			None
+		};
+
+		let attachments = std::iter::once(color.clone())
+			.chain(depth_stencil.clone())
+			.chain(resolve.clone());
+		let framebuffer = Framebuffer::new(
+			render_pass.clone(),
+			attachments,
+			size,
+			NonZeroU32::new(1).unwrap(),
+			*host_memory_allocator
+		)?;
+
+		Ok(Attachments { color, depth_stencil, resolve, framebuffer })
+	}
+}
+impl<A: ImageMemoryAllocator> RenderTarget<A> {
+	pub const fn render_pass(&self) -> &Vrc<RenderPass> {
+		&self.render_pass
+	}
+
+	pub const fn params(&self) -> &RenderTargetParams {
+		&self.params
+	}
+
+	pub const fn size(&self) -> [NonZeroU32; 2] {
+		self.size
+	}
+
+	/// Color attachment, which is multisampled if `samples` is greater than one.
+	pub const fn color(&self) -> &Vrc<ImageView> {
+		&self.color
+	}
+
+	pub const fn depth_stencil(&self) -> Option<&Vrc<ImageView>> {
+		self.depth_stencil.as_ref()
+	}
+
+	/// Single-sampled resolve attachment, present if `samples` is greater than one.
+	pub const fn resolve(&self) -> Option<&Vrc<ImageView>> {
+		self.resolve.as_ref()
+	}
+
+	/// Returns the view holding the final color, which is the resolve attachment if present and the color attachment otherwise.
+	pub fn output(&self) -> &Vrc<ImageView> {
+		self.resolve.as_ref().unwrap_or(&self.color)
+	}
+
+	pub const fn framebuffer(&self) -> &Vrc<Framebuffer> {
+		&self.framebuffer
+	}
+}
+impl<A: ImageMemoryAllocator> fmt::Debug for RenderTarget<A> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("RenderTarget")
+			.field("render_pass", &self.render_pass)
+			.field("params", &self.params)
+			.field("size", &self.size)
+			.field("color", &self.color)
+			.field("depth_stencil", &self.depth_stencil)
+			.field("resolve", &self.resolve)
+			.field("framebuffer", &self.framebuffer)
+			.field("allocator", &self.allocator)
+			.field("requirements", &self.requirements)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
@@ -0,0 +1,310 @@
+//! GPU culling compute pass producing indirect draw lists.
+//!
+//! The `CullingPass` owns a compute pipeline created from user-supplied SPIR-V, the indirect draw and count buffers
+//! and the descriptor set binding them. Recording the pass resets the count, dispatches the culling shader and inserts
+//! barriers so that the result can be consumed by `draw_indexed_indirect_count` in a following render pass.
+//!
+//! The culling shader must use the following interface:
+//! ```glsl
+//! layout(local_size_x = LOCAL_SIZE) in;
+//!
+//! // user-defined per-object input data
+//! layout(set = 0, binding = 0) readonly buffer Input { ... };
+//! // output draw commands
+//! layout(set = 0, binding = 1) writeonly buffer Draws { VkDrawIndexedIndirectCommand draws[]; };
+//! // output draw count, reset to zero before each dispatch
+//! layout(set = 0, binding = 2) buffer Count { uint count; };
+//!
+//! layout(push_constant) uniform Constants { uint input_count; };
+//! ```
+//! Surviving objects should append their draw command using `atomicAdd(count, 1)`.
+
+use std::{
+	fmt,
+	num::{NonZeroU32, NonZeroU64},
+	ops::Deref
+};
+
+use ash::vk;
+
+use super::error::CullingPassError;
+use crate::{
+	command::error::BindDescriptorSetsError,
+	prelude::{
+		Buffer,
+		BufferMemoryBarrier,
+		CommandBufferRecordingLockOutsideRenderPass,
+		ComputePipeline,
+		DescriptorBufferInfo,
+		DescriptorPool,
+		DescriptorPoolSize,
+		DescriptorSet,
+		DescriptorSetCopy,
+		DescriptorSetLayout,
+		DescriptorSetLayoutBinding,
+		DescriptorSetLayoutBindingGenericType,
+		DescriptorSetWrite,
+		DescriptorSetWriteData,
+		DescriptorTypeBuffer,
+		Device,
+		HasHandle,
+		HostMemoryAllocator,
+		ImageMemoryBarrier,
+		MemoryBarrier,
+		PipelineLayout,
+		PushConstantsTrait,
+		ShaderModule,
+		Vrc
+	}
+};
+
+const DRAW_COMMAND_STRIDE: u32 = std::mem::size_of::<vk::DrawIndexedIndirectCommand>() as u32;
+
+/// Push constants passed to the culling shader.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct CullingPushConstants {
+	pub input_count: u32
+}
+unsafe impl PushConstantsTrait for CullingPushConstants {
+	const STAGE_FLAGS: vk::ShaderStageFlags = vk::ShaderStageFlags::COMPUTE;
+}
+
+pub struct CullingPass {
+	pipeline: Vrc<ComputePipeline>,
+	layout: Vrc<PipelineLayout>,
+	descriptor_set: Vrc<DescriptorSet>,
+
+	input_buffer: Vrc<Buffer>,
+	draw_buffer: Vrc<Buffer>,
+	count_buffer: Vrc<Buffer>,
+
+	local_size: NonZeroU32,
+	max_draws: NonZeroU32
+}
+impl CullingPass {
+	/// Creates a new culling pass.
+	///
+	/// `code` is the SPIR-V of the culling shader with `main` entry point and `local_size` is its `local_size_x`.
+	///
+	/// * `input_buffer` must have `STORAGE_BUFFER` usage.
+	/// * `draw_buffer` must have `STORAGE_BUFFER` and `INDIRECT_BUFFER` usage. The maximum number of draws is derived from its size.
+	/// * `count_buffer` must have `STORAGE_BUFFER`, `INDIRECT_BUFFER` and `TRANSFER_DST` usage.
+	pub fn new(
+		device: Vrc<Device>,
+		code: impl AsRef<[u32]>,
+		local_size: NonZeroU32,
+		input_buffer: Vrc<Buffer>,
+		draw_buffer: Vrc<Buffer>,
+		count_buffer: Vrc<Buffer>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, CullingPassError> {
+		if !input_buffer.usage().contains(vk::BufferUsageFlags::STORAGE_BUFFER) {
+			return Err(CullingPassError::InvalidInputBuffer)
+		}
+		let max_draws = NonZeroU32::new((draw_buffer.size().get() / DRAW_COMMAND_STRIDE as u64) as u32)
+			.filter(|_| draw_buffer.usage().contains(vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::INDIRECT_BUFFER))
+			.ok_or(CullingPassError::InvalidDrawBuffer)?;
+		if !count_buffer
+			.usage()
+			.contains(vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::INDIRECT_BUFFER | vk::BufferUsageFlags::TRANSFER_DST)
+			|| count_buffer.size().get() < 4
+		{
+			return Err(CullingPassError::InvalidCountBuffer)
+		}
+
+		let shader = ShaderModule::new(
+			device.clone(),
+			code,
+			host_memory_allocator.clone()
+		)?;
+
+		let set_layout = DescriptorSetLayout::new(
+			device.clone(),
+			vk::DescriptorSetLayoutCreateFlags::empty(),
+			(0 .. 3).map(|_| {
+				DescriptorSetLayoutBinding::Generic(
+					DescriptorSetLayoutBindingGenericType::STORAGE_BUFFER,
+					NonZeroU32::new(1).unwrap(),
+					vk::ShaderStageFlags::COMPUTE
+				)
+			}),
+			host_memory_allocator.clone()
+		)?;
+		let descriptor_pool = DescriptorPool::new(
+			device.clone(),
+			vk::DescriptorPoolCreateFlags::empty(),
+			NonZeroU32::new(1).unwrap(),
+			std::iter::once(DescriptorPoolSize { descriptor_type: vk::DescriptorType::STORAGE_BUFFER, count: NonZeroU32::new(3).unwrap() }),
+			None,
+			host_memory_allocator.clone()
+		)?;
+		let descriptor_set = DescriptorSet::new(descriptor_pool, set_layout.clone())?;
+
+		let buffer_infos = [
+			[DescriptorBufferInfo::new(&input_buffer, 0, input_buffer.size())],
+			[DescriptorBufferInfo::new(&draw_buffer, 0, draw_buffer.size())],
+			[DescriptorBufferInfo::new(&count_buffer, 0, NonZeroU64::new(4).unwrap())]
+		];
+		let writes = buffer_infos
+			.iter()
+			.enumerate()
+			.map(|(binding, info)| {
+				DescriptorSetWrite::new(
+					descriptor_set.safe_handle(),
+					binding as u32,
+					0,
+					DescriptorSetWriteData::Buffer(DescriptorTypeBuffer::STORAGE_BUFFER, info)
+				)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		DescriptorSet::update(&device, writes, [] as [DescriptorSetCopy; 0]);
+
+		let layout = PipelineLayout::new(
+			device.clone(),
+			[set_layout.deref()],
+			[CullingPushConstants::layout_range()],
+			host_memory_allocator.clone()
+		)?;
+
+		crate::describe_compute_pipeline! {
+			let create_info;
+
+			Shaders {
+				stage: shader => vk::ShaderStageFlags::COMPUTE
+			}
+
+			Deps {
+				layout: layout
+			}
+		}
+		let pipeline = unsafe { ComputePipeline::from_create_info(device, create_info, host_memory_allocator)? };
+
+		Ok(CullingPass { pipeline, layout, descriptor_set, input_buffer, draw_buffer, count_buffer, local_size, max_draws })
+	}
+
+	/// Records the culling dispatch over `input_count` input objects.
+	///
+	/// Must be recorded outside of the render pass that consumes the draws.
+	/// If `input_count` is zero, only the draw count is reset to zero and no dispatch is recorded.
+	pub fn record_cull(&self, recording: &CommandBufferRecordingLockOutsideRenderPass, input_count: u32) -> Result<(), BindDescriptorSetsError> {
+		// Previous draws must finish reading before the buffers are overwritten
+		recording.pipeline_barrier(
+			vk::PipelineStageFlags::DRAW_INDIRECT,
+			vk::PipelineStageFlags::TRANSFER | vk::PipelineStageFlags::COMPUTE_SHADER,
+			[] as [MemoryBarrier; 0],
+			[] as [BufferMemoryBarrier; 0],
+			[] as [ImageMemoryBarrier; 0]
+		);
+
+		recording
+			.fill_buffer(
+				&self.count_buffer,
+				0,
+				NonZeroU64::new(4).unwrap(),
+				0
+			)
+			.expect("count buffer is validated in `new`");
+		recording.pipeline_barrier(
+			vk::PipelineStageFlags::TRANSFER,
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+			[] as [MemoryBarrier; 0],
+			[BufferMemoryBarrier::new(
+				&self.count_buffer,
+				0,
+				NonZeroU64::new(4).unwrap(),
+				vk::AccessFlags::TRANSFER_WRITE,
+				vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE
+			)],
+			[] as [ImageMemoryBarrier; 0]
+		);
+
+		let group_count = input_count.div_ceil(self.local_size.get());
+		if group_count != 0 {
+			recording.bind_compute_pipeline(&self.pipeline);
+			recording.bind_descriptor_sets(
+				vk::PipelineBindPoint::COMPUTE,
+				&self.layout,
+				0,
+				[self.descriptor_set.deref()],
+				[] as [u32; 0]
+			)?;
+			recording.push_constants(&self.layout, &CullingPushConstants { input_count });
+			recording.dispatch([group_count, 1, 1]);
+		}
+
+		recording.pipeline_barrier(
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+			vk::PipelineStageFlags::DRAW_INDIRECT,
+			[] as [MemoryBarrier; 0],
+			[
+				BufferMemoryBarrier::new(
+					&self.draw_buffer,
+					0,
+					self.draw_buffer.size(),
+					vk::AccessFlags::SHADER_WRITE,
+					vk::AccessFlags::INDIRECT_COMMAND_READ
+				),
+				BufferMemoryBarrier::new(
+					&self.count_buffer,
+					0,
+					NonZeroU64::new(4).unwrap(),
+					vk::AccessFlags::SHADER_WRITE,
+					vk::AccessFlags::INDIRECT_COMMAND_READ
+				)
+			],
+			[] as [ImageMemoryBarrier; 0]
+		);
+
+		Ok(())
+	}
+
+	/// Records an indirect draw of the culled draw list.
+	///
+	/// The pipeline, vertex and index buffers must already be bound.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	#[cfg(feature = "vulkan1_2")]
+	pub fn record_draw(&self, recording: &crate::prelude::CommandBufferRecordingLockInsideRenderPass) {
+		recording.draw_indexed_indirect_count(
+			&self.draw_buffer,
+			0,
+			&self.count_buffer,
+			0,
+			self.max_draws.get(),
+			DRAW_COMMAND_STRIDE
+		);
+	}
+
+	pub const fn input_buffer(&self) -> &Vrc<Buffer> {
+		&self.input_buffer
+	}
+
+	pub const fn draw_buffer(&self) -> &Vrc<Buffer> {
+		&self.draw_buffer
+	}
+
+	pub const fn count_buffer(&self) -> &Vrc<Buffer> {
+		&self.count_buffer
+	}
+
+	pub const fn max_draws(&self) -> NonZeroU32 {
+		self.max_draws
+	}
+}
+impl fmt::Debug for CullingPass {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("CullingPass")
+			.field("pipeline", &self.pipeline)
+			.field("layout", &self.layout)
+			.field("descriptor_set", &self.descriptor_set)
+			.field("input_buffer", &self.input_buffer)
+			.field("draw_buffer", &self.draw_buffer)
+			.field("count_buffer", &self.count_buffer)
+			.field("local_size", &self.local_size)
+			.field("max_draws", &self.max_draws)
+			.finish()
+	}
+}
@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+use crate::{
+	descriptor::error::{DescriptorPoolError, DescriptorSetError, DescriptorSetLayoutError, DescriptorSetWriteError},
+	pipeline::error::{ComputePipelineError, PipelineLayoutError},
+	shader::error::ShaderError
+};
+
+#[derive(Error, Debug)]
+pub enum CullingPassError {
+	#[error("Draw buffer must have STORAGE_BUFFER and INDIRECT_BUFFER usage and hold at least one command")]
+	InvalidDrawBuffer,
+	#[error("Count buffer must have STORAGE_BUFFER, INDIRECT_BUFFER and TRANSFER_DST usage and hold at least one count")]
+	InvalidCountBuffer,
+	#[error("Input buffer must have STORAGE_BUFFER usage")]
+	InvalidInputBuffer,
+	#[error("Could not create shader module")]
+	Shader(#[from] ShaderError),
+	#[error("Could not create descriptor set layout")]
+	DescriptorSetLayout(#[from] DescriptorSetLayoutError),
+	#[error("Could not create descriptor pool")]
+	DescriptorPool(#[from] DescriptorPoolError),
+	#[error("Could not allocate descriptor set")]
+	DescriptorSet(#[from] DescriptorSetError),
+	#[error("Could not write descriptor set")]
+	DescriptorSetWrite(#[from] DescriptorSetWriteError),
+	#[error("Could not create pipeline layout")]
+	PipelineLayout(#[from] PipelineLayoutError),
+	#[error("Could not create compute pipeline")]
+	ComputePipeline(#[from] ComputePipelineError)
+}
@@ -0,0 +1,4 @@
+//! Higher-level rendering helpers built on top of the core wrappers.
+
+pub mod culling;
+pub mod error;
@@ -0,0 +1,267 @@
+//! Frame capture control through the RenderDoc in-application API.
+//!
+//! When the application is launched from RenderDoc, the RenderDoc library is already loaded into the process and `CaptureControl`
+//! can start and end captures programmatically, for example to capture only frames that fail a check.
+//! Otherwise `CaptureControl` is inert and all methods do nothing.
+//!
+//! Command buffer regions opened with `CommandBufferRecordingLockCommon::begin_capture_region` during a capture are
+//! recorded as debug labels, which RenderDoc shows in its event browser, and their names are written into the comments
+//! of the capture file so that captures can be correlated with the regions they contain.
+
+use std::{
+	ffi::{CStr, CString},
+	fmt,
+	os::raw::{c_char, c_int, c_void}
+};
+
+use ash::vk::Handle;
+
+use super::Instance;
+use crate::prelude::{HasHandle, Vrc, Vutex};
+
+/// `eRENDERDOC_API_Version_1_4_0`
+const RENDERDOC_API_VERSION: c_int = 10400;
+
+type GetApiFn = unsafe extern "C" fn(version: c_int, out_api_pointers: *mut *mut c_void) -> c_int;
+
+/// Prefix of `RENDERDOC_API_1_4_0` up to the last function used here.
+#[repr(C)]
+struct RenderDocApiTable {
+	get_api_version: unsafe extern "C" fn(major: *mut c_int, minor: *mut c_int, patch: *mut c_int),
+	_options_and_overlay: [*const c_void; 8],
+	_remove_hooks: *const c_void,
+	_unload_crash_handler: *const c_void,
+	_set_capture_file_path_template: *const c_void,
+	_get_capture_file_path_template: *const c_void,
+	get_num_captures: unsafe extern "C" fn() -> u32,
+	_get_capture: *const c_void,
+	trigger_capture: unsafe extern "C" fn(),
+	_is_target_control_connected: *const c_void,
+	_launch_replay_ui: *const c_void,
+	_set_active_window: *const c_void,
+	start_frame_capture: unsafe extern "C" fn(device: *const c_void, window: *const c_void),
+	is_frame_capturing: unsafe extern "C" fn() -> u32,
+	end_frame_capture: unsafe extern "C" fn(device: *const c_void, window: *const c_void) -> u32,
+	_trigger_multi_frame_capture: *const c_void,
+	set_capture_file_comments: unsafe extern "C" fn(file_path: *const c_char, comments: *const c_char),
+	discard_frame_capture: unsafe extern "C" fn(device: *const c_void, window: *const c_void) -> u32
+}
+
+struct RenderDocApi {
+	table: &'static RenderDocApiTable,
+	// Keeps the library reference count up while the table is used
+	_library: libloading::Library
+}
+// SAFETY: The table is never written to and the RenderDoc API functions may be called from any thread
+unsafe impl Send for RenderDocApi {}
+unsafe impl Sync for RenderDocApi {}
+impl RenderDocApi {
+	#[cfg(target_os = "linux")]
+	unsafe fn open_loaded_library() -> Option<libloading::Library> {
+		// Only returns the library if it is already loaded
+		const RTLD_NOLOAD: c_int = 0x4;
+
+		libloading::os::unix::Library::open(
+			Some("librenderdoc.so"),
+			libloading::os::unix::RTLD_NOW | RTLD_NOLOAD
+		)
+		.ok()
+		.map(Into::into)
+	}
+
+	#[cfg(windows)]
+	unsafe fn open_loaded_library() -> Option<libloading::Library> {
+		libloading::os::windows::Library::open_already_loaded("renderdoc.dll").ok().map(Into::into)
+	}
+
+	#[cfg(not(any(target_os = "linux", windows)))]
+	unsafe fn open_loaded_library() -> Option<libloading::Library> {
+		None
+	}
+
+	fn load() -> Option<Self> {
+		unsafe {
+			let library = Self::open_loaded_library()?;
+			let get_api = *library.get::<GetApiFn>(b"RENDERDOC_GetAPI\0").ok()?;
+
+			let mut table: *mut c_void = std::ptr::null_mut();
+			if get_api(RENDERDOC_API_VERSION, &mut table) != 1 || table.is_null() {
+				log::warn!("RenderDoc library is loaded but does not support API version 1.4.0");
+				return None
+			}
+
+			Some(RenderDocApi { table: &*(table as *const RenderDocApiTable), _library: library })
+		}
+	}
+
+	fn version(&self) -> (c_int, c_int, c_int) {
+		let (mut major, mut minor, mut patch) = (0, 0, 0);
+		unsafe { (self.table.get_api_version)(&mut major, &mut minor, &mut patch) };
+
+		(major, minor, patch)
+	}
+}
+
+#[derive(Debug, Default)]
+struct CaptureState {
+	capturing: bool,
+	regions: Vec<CString>
+}
+
+/// Controls RenderDoc frame captures of an instance, see the module documentation.
+pub struct CaptureControl {
+	instance: Vrc<Instance>,
+	api: Option<RenderDocApi>,
+	state: Vutex<CaptureState>
+}
+impl CaptureControl {
+	/// Connects to RenderDoc if the application was launched from it.
+	pub fn new(instance: Vrc<Instance>) -> Self {
+		let api = RenderDocApi::load();
+		match api {
+			None => log::debug!("RenderDoc is not loaded, frame capture is disabled"),
+			Some(ref api) => log::info!("RenderDoc API {:?} loaded", api.version())
+		}
+
+		CaptureControl { instance, api, state: Vutex::new(CaptureState::default()) }
+	}
+
+	/// RenderDoc device pointer of the instance, which is the dispatch table pointer of the dispatchable handle.
+	fn device_pointer(&self) -> *const c_void {
+		unsafe { *(self.instance.handle().as_raw() as usize as *const *const c_void) }
+	}
+
+	/// Returns whether the application runs under RenderDoc.
+	pub fn is_available(&self) -> bool {
+		self.api.is_some()
+	}
+
+	/// Captures the next frame presented by any swapchain.
+	pub fn trigger_capture(&self) {
+		if let Some(ref api) = self.api {
+			unsafe { (api.table.trigger_capture)() }
+		}
+	}
+
+	/// Starts capturing all commands of the instance until `end_frame_capture` or `discard_frame_capture`.
+	///
+	/// Returns `false` if RenderDoc is not available.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn start_frame_capture(&self) -> bool {
+		let api = match self.api {
+			None => return false,
+			Some(ref api) => api
+		};
+
+		let mut state = self.state.lock().expect("vutex poisoned");
+		state.capturing = true;
+		state.regions.clear();
+
+		unsafe { (api.table.start_frame_capture)(self.device_pointer(), std::ptr::null()) }
+
+		true
+	}
+
+	/// Ends the current capture and saves it.
+	///
+	/// The names of the regions recorded during the capture are written into the capture file comments.
+	/// Returns whether the capture was saved successfully.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn end_frame_capture(&self) -> bool {
+		let api = match self.api {
+			None => return false,
+			Some(ref api) => api
+		};
+
+		let mut state = self.state.lock().expect("vutex poisoned");
+		state.capturing = false;
+
+		let saved = unsafe { (api.table.end_frame_capture)(self.device_pointer(), std::ptr::null()) } == 1;
+		if saved && !state.regions.is_empty() {
+			let comments = state.regions.iter().map(|name| name.to_string_lossy()).collect::<Vec<_>>().join("\n");
+			// Interior nul bytes are not possible since the names come from `CStr`s
+			let comments = CString::new(format!("Regions:\n{}", comments)).unwrap();
+
+			// Null path means the most recent capture
+			unsafe { (api.table.set_capture_file_comments)(std::ptr::null(), comments.as_ptr()) }
+		}
+
+		saved
+	}
+
+	/// Ends the current capture without saving it.
+	///
+	/// Returns whether a capture was discarded.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn discard_frame_capture(&self) -> bool {
+		let api = match self.api {
+			None => return false,
+			Some(ref api) => api
+		};
+
+		let mut state = self.state.lock().expect("vutex poisoned");
+		state.capturing = false;
+		state.regions.clear();
+
+		unsafe { (api.table.discard_frame_capture)(self.device_pointer(), std::ptr::null()) == 1 }
+	}
+
+	/// Returns whether a capture is in progress, including captures started from the RenderDoc UI.
+	pub fn is_frame_capturing(&self) -> bool {
+		match self.api {
+			None => false,
+			Some(ref api) => unsafe { (api.table.is_frame_capturing)() == 1 }
+		}
+	}
+
+	/// Returns the number of captures made so far.
+	pub fn capture_count(&self) -> u32 {
+		match self.api {
+			None => 0,
+			Some(ref api) => unsafe { (api.table.get_num_captures)() }
+		}
+	}
+
+	/// Records region `name` if a capture started by `start_frame_capture` is in progress.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn record_region(&self, name: &CStr) {
+		let mut state = self.state.lock().expect("vutex poisoned");
+		if state.capturing {
+			state.regions.push(name.to_owned());
+		}
+	}
+
+	/// Returns the names of regions recorded during the current or last capture.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn regions(&self) -> Vec<CString> {
+		self.state.lock().expect("vutex poisoned").regions.clone()
+	}
+
+	pub const fn instance(&self) -> &Vrc<Instance> {
+		&self.instance
+	}
+}
+impl fmt::Debug for CaptureControl {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("CaptureControl")
+			.field("instance", &self.instance)
+			.field("available", &self.api.is_some())
+			.field("state", &self.state)
+			.finish()
+	}
+}
@@ -1,7 +1,7 @@
 use std::{
 	borrow::Cow,
 	ffi::{c_void, CStr},
-	fmt::Write
+	fmt::{self, Write}
 };
 
 use ash::vk::{
@@ -20,7 +20,7 @@ unsafe_enum_variants! {
 		pub None => { None },
 		/// A default debug callback provided by Vulkayes will be registered.
 		pub Default => {
-			Some(
+			Some((
 				DebugUtilsMessengerCreateInfoEXT::builder()
 					.message_severity(
 						DebugUtilsMessageSeverityFlagsEXT::VERBOSE
@@ -34,12 +34,29 @@ unsafe_enum_variants! {
 						| DebugUtilsMessageTypeFlagsEXT::PERFORMANCE
 					)
 					.pfn_user_callback(Some(default_debug_callback))
-					.build()
-			)
+					.build(),
+				None
+			))
+		},
+		/// A user closure will be registered, receiving only messages matching `severity` and `message_type`.
+		pub User {
+			callback: DebugUserCallback,
+			severity: DebugUtilsMessageSeverityFlagsEXT,
+			message_type: DebugUtilsMessageTypeFlagsEXT
+		} => {
+			let callback = Box::new(callback);
+			let info = DebugUtilsMessengerCreateInfoEXT::builder()
+				.message_severity(severity)
+				.message_type(message_type)
+				.pfn_user_callback(Some(user_debug_callback))
+				.user_data(callback.as_ref() as *const DebugUserCallback as *mut c_void)
+				.build();
+
+			Some((info, Some(callback)))
 		},
 		/// A custom debug callback will be registered.
-		{unsafe} pub Custom { info: DebugUtilsMessengerCreateInfoEXT } => { Some(info) }
-	} as pub DebugCallback impl Into<Option<DebugUtilsMessengerCreateInfoEXT>>
+		{unsafe} pub Custom { info: DebugUtilsMessengerCreateInfoEXT } => { Some((info, None)) }
+	} as pub DebugCallback impl Into<Option<(DebugUtilsMessengerCreateInfoEXT, Option<Box<DebugUserCallback>>)>>
 }
 impl Default for DebugCallback {
 	fn default() -> Self {
@@ -47,6 +64,115 @@ impl Default for DebugCallback {
 	}
 }
 
+/// Object referenced by a debug message.
+#[derive(Debug, Clone)]
+pub struct DebugMessageObject {
+	pub object_type: vk::ObjectType,
+	pub handle: u64,
+	pub name: Option<String>
+}
+
+/// Owned and parsed debug messenger message.
+#[derive(Debug, Clone)]
+pub struct DebugMessage {
+	pub severity: DebugUtilsMessageSeverityFlagsEXT,
+	pub message_type: DebugUtilsMessageTypeFlagsEXT,
+	pub id_name: Option<String>,
+	pub id_number: i32,
+	pub message: String,
+	pub objects: Vec<DebugMessageObject>
+}
+impl DebugMessage {
+	/// ### Safety
+	///
+	/// `data` must be a valid callback data structure as passed to a debug messenger callback.
+	pub unsafe fn from_raw(
+		severity: DebugUtilsMessageSeverityFlagsEXT,
+		message_type: DebugUtilsMessageTypeFlagsEXT,
+		data: &DebugUtilsMessengerCallbackDataEXT
+	) -> Self {
+		unsafe fn owned_str(ptr: *const std::os::raw::c_char) -> Option<String> {
+			if ptr.is_null() {
+				None
+			} else {
+				Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+			}
+		}
+
+		let objects = if data.object_count == 0 {
+			Vec::new()
+		} else {
+			std::slice::from_raw_parts(
+				data.p_objects,
+				data.object_count as usize
+			)
+			.iter()
+			.map(|object| DebugMessageObject {
+				object_type: object.object_type,
+				handle: object.object_handle,
+				name: owned_str(object.p_object_name)
+			})
+			.collect()
+		};
+
+		DebugMessage {
+			severity,
+			message_type,
+			id_name: owned_str(data.p_message_id_name),
+			id_number: data.message_id_number,
+			message: owned_str(data.p_message).unwrap_or_default(),
+			objects
+		}
+	}
+}
+
+/// User closure called for each debug message.
+///
+/// The return value is passed back to the layer that emitted the message. Returning `true` aborts the Vulkan call
+/// that triggered the message, which is only supported by validation layers and should otherwise be `false`.
+pub struct DebugUserCallback {
+	callback: Box<VSendSync![dyn Fn(DebugMessage) -> bool]>
+}
+impl DebugUserCallback {
+	pub fn new(callback: Box<VSendSync![dyn Fn(DebugMessage) -> bool]>) -> Self {
+		DebugUserCallback { callback }
+	}
+}
+impl fmt::Debug for DebugUserCallback {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DebugUserCallback")
+			.field("callback", &"<closure>")
+			.finish()
+	}
+}
+
+/// Callback trampoline for `DebugCallback::User`.
+///
+/// `user_data` must point to a `DebugUserCallback`.
+unsafe extern "system" fn user_debug_callback(
+	message_severity: DebugUtilsMessageSeverityFlagsEXT,
+	message_type: DebugUtilsMessageTypeFlagsEXT,
+	p_callback_data: *const DebugUtilsMessengerCallbackDataEXT,
+	user_data: *mut c_void
+) -> Bool32 {
+	let callback = &*(user_data as *const DebugUserCallback);
+	let message = DebugMessage::from_raw(
+		message_severity,
+		message_type,
+		&*p_callback_data
+	);
+
+	// unwinding across the FFI boundary is undefined behavior
+	match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| (callback.callback)(message))) {
+		Ok(true) => vk::TRUE,
+		Ok(false) => vk::FALSE,
+		Err(_) => {
+			log::error!("Debug user callback panicked");
+			vk::FALSE
+		}
+	}
+}
+
 /// Final message will look like this:
 ///
 /// `{PERF} PREFIX (LOCATION:CODE) <OBJ_TYPE OBJ> MESSAGE`
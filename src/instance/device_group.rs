@@ -0,0 +1,80 @@
+//! Device groups of physical devices that can be used together as one logical device.
+//!
+//! A device created from a group (see `Device::from_device_group`) executes commands on a subset of its physical devices
+//! selected by a device mask, where bit `i` selects the `i`-th physical device of the group. Device masks are passed
+//! to `Queue::submit_device_group`, `CommandBufferRecordingLockOutsideRenderPass::begin_render_pass_device_group`
+//! and `CommandBufferRecordingLockCommon::set_device_mask`, which allows alternate-frame and split-frame rendering.
+
+use std::fmt;
+
+use crate::prelude::PhysicalDevice;
+
+/// Group of physical devices that can be used to create one device.
+#[derive(Clone)]
+pub struct PhysicalDeviceGroup {
+	physical_devices: Vec<PhysicalDevice>,
+	subset_allocation: bool
+}
+impl PhysicalDeviceGroup {
+	/// Physical devices of the group, never empty.
+	pub fn physical_devices(&self) -> &[PhysicalDevice] {
+		&self.physical_devices
+	}
+
+	/// Whether memory can be allocated on a subset of the physical devices.
+	pub const fn subset_allocation(&self) -> bool {
+		self.subset_allocation
+	}
+
+	/// Returns the device mask selecting all physical devices of the group.
+	pub fn all_devices_mask(&self) -> u32 {
+		device_mask_all(self.physical_devices.len() as u32)
+	}
+}
+impl fmt::Debug for PhysicalDeviceGroup {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PhysicalDeviceGroup")
+			.field("physical_devices", &self.physical_devices)
+			.field("subset_allocation", &self.subset_allocation)
+			.finish()
+	}
+}
+
+/// Returns the device mask selecting the first `count` physical devices of a group.
+pub const fn device_mask_all(count: u32) -> u32 {
+	if count >= 32 {
+		!0
+	} else {
+		(1 << count) - 1
+	}
+}
+
+#[cfg(feature = "vulkan1_1")]
+impl super::Instance {
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkEnumeratePhysicalDeviceGroups.html>.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0.
+	pub fn physical_device_groups(self: &crate::prelude::Vrc<Self>) -> Result<Vec<PhysicalDeviceGroup>, super::error::PhysicalDeviceEnumerationError> {
+		let groups = unsafe {
+			let mut groups = vec![ash::vk::PhysicalDeviceGroupProperties::default(); self.enumerate_physical_device_groups_len()?];
+			self.enumerate_physical_device_groups(&mut groups)?;
+
+			groups
+		};
+
+		let groups = groups
+			.into_iter()
+			.map(|group| PhysicalDeviceGroup {
+				physical_devices: group.physical_devices[.. group.physical_device_count as usize]
+					.iter()
+					.map(|&physical_device| unsafe { PhysicalDevice::from_existing(self.clone(), physical_device) })
+					.collect(),
+				subset_allocation: group.subset_allocation == ash::vk::TRUE
+			})
+			.collect();
+
+		Ok(groups)
+	}
+}
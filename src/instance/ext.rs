@@ -0,0 +1,101 @@
+//! Lazily-initialized instance extension loaders.
+
+use std::{fmt, ops::Deref};
+
+use ash::extensions::{ext, khr};
+
+use super::Instance;
+use crate::util::sync::VOnce;
+
+/// Storage of instance extension loaders, shared by all objects created from the instance.
+#[derive(Default)]
+pub(super) struct InstanceExtensionTable {
+	surface: VOnce<khr::Surface>,
+	display: VOnce<khr::Display>,
+	headless_surface: VOnce<ext::HeadlessSurface>,
+	get_surface_capabilities2: VOnce<khr::GetSurfaceCapabilities2>,
+	debug_utils: VOnce<ext::DebugUtils>
+}
+impl fmt::Debug for InstanceExtensionTable {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("InstanceExtensionTable")
+			.field("surface", &self.surface.get().is_some())
+			.field("display", &self.display.get().is_some())
+			.field("headless_surface", &self.headless_surface.get().is_some())
+			.field(
+				"get_surface_capabilities2",
+				&self.get_surface_capabilities2.get().is_some()
+			)
+			.field("debug_utils", &self.debug_utils.get().is_some())
+			.finish()
+	}
+}
+
+/// Accessor of instance extension loaders, see `Instance::ext`.
+///
+/// Loaders are created on first access and then shared. The corresponding extension must be enabled on the instance,
+/// otherwise calling the loader functions will panic.
+#[derive(Debug, Clone, Copy)]
+pub struct InstanceExt<'a> {
+	instance: &'a Instance
+}
+impl<'a> InstanceExt<'a> {
+	pub(super) const fn new(instance: &'a Instance) -> Self {
+		InstanceExt { instance }
+	}
+
+	/// `VK_KHR_surface` loader.
+	pub fn surface(self) -> &'a khr::Surface {
+		let instance = self.instance;
+
+		instance
+			.extensions
+			.surface
+			.get_or_init(|| khr::Surface::new(instance.entry().deref(), instance.deref()))
+	}
+
+	/// `VK_KHR_display` loader.
+	pub fn display(self) -> &'a khr::Display {
+		let instance = self.instance;
+
+		instance
+			.extensions
+			.display
+			.get_or_init(|| khr::Display::new(instance.entry().deref(), instance.deref()))
+	}
+
+	/// `VK_EXT_headless_surface` loader.
+	pub fn headless_surface(self) -> &'a ext::HeadlessSurface {
+		let instance = self.instance;
+
+		instance
+			.extensions
+			.headless_surface
+			.get_or_init(|| ext::HeadlessSurface::new(instance.entry().deref(), instance.deref()))
+	}
+
+	/// `VK_KHR_get_surface_capabilities2` loader.
+	pub fn get_surface_capabilities2(self) -> &'a khr::GetSurfaceCapabilities2 {
+		let instance = self.instance;
+
+		instance
+			.extensions
+			.get_surface_capabilities2
+			.get_or_init(|| khr::GetSurfaceCapabilities2::new(instance.entry().deref(), instance.deref()))
+	}
+
+	/// `VK_EXT_debug_utils` loader.
+	///
+	/// Returns the loader used by the debug callback, if one was registered during creation.
+	pub fn debug_utils(self) -> &'a ext::DebugUtils {
+		let instance = self.instance;
+
+		match instance.debug_utils() {
+			Some(loader) => loader,
+			None => instance
+				.extensions
+				.debug_utils
+				.get_or_init(|| ext::DebugUtils::new(instance.entry().deref(), instance.deref()))
+		}
+	}
+}
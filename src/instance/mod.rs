@@ -9,10 +9,14 @@ use std::{
 
 use ash::{extensions::ext::DebugUtils, vk};
 
-use crate::{entry::Entry, memory::host::HostMemoryAllocator, physical_device::PhysicalDevice, prelude::Vrc, util::fmt::VkVersion};
+use crate::{entry::Entry, memory::host::HostMemoryAllocator, physical_device::PhysicalDevice, prelude::Vrc, util::{destruction::ChildRegistry, fmt::VkVersion}};
 
+#[cfg(feature = "renderdoc")]
+pub mod capture;
 pub mod debug;
+pub mod device_group;
 pub mod error;
+pub mod ext;
 #[cfg(test)]
 pub mod test;
 
@@ -28,6 +32,8 @@ pub struct ApplicationInfo<'a> {
 struct InstanceDebug {
 	loader: DebugUtils,
 	callback: vk::DebugUtilsMessengerEXT,
+	// Must outlive the messenger, dropped after it is destroyed in `Instance::drop`.
+	user_callback: Option<Box<debug::DebugUserCallback>>,
 	host_memory_allocator: HostMemoryAllocator
 }
 impl Debug for InstanceDebug {
@@ -35,6 +41,7 @@ impl Debug for InstanceDebug {
 		f.debug_struct("InstanceDebug")
 			.field("loader", &"<ash::_::DebugReport>")
 			.field("callback", &self.callback)
+			.field("user_callback", &self.user_callback)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
@@ -49,7 +56,10 @@ pub struct Instance {
 	instance_handle: vk::Instance,
 	host_memory_allocator: HostMemoryAllocator,
 
-	debug: Option<InstanceDebug>
+	debug: Option<InstanceDebug>,
+	extensions: ext::InstanceExtensionTable,
+
+	children: ChildRegistry
 }
 impl Instance {
 	/// Creates a new instance from an existing entry.
@@ -100,6 +110,36 @@ impl Instance {
 		}
 	}
 
+	/// Creates a new instance without application and engine names or versions and without any layers.
+	///
+	/// The api version is the highest version supported by the instance, see `Entry::instance_version`.
+	pub fn minimal<'a>(
+		entry: Entry,
+		extensions: impl IntoIterator<Item = &'a CStr> + std::fmt::Debug,
+		debug_callback: debug::DebugCallback
+	) -> Result<Vrc<Self>, error::InstanceError> {
+		let api_version = entry.instance_version();
+		log::info!("Vulkan instance version {}", api_version);
+
+		let app_info = vk::ApplicationInfo::builder().api_version(api_version.0);
+
+		log::debug!("Instance create info {:#?}", extensions);
+
+		let ptr_extensions: Vec<*const c_char> = extensions.into_iter().map(CStr::as_ptr).collect();
+		let create_info = vk::InstanceCreateInfo::builder()
+			.application_info(&app_info)
+			.enabled_extension_names(ptr_extensions.as_slice());
+
+		unsafe {
+			Instance::from_create_info(
+				entry,
+				create_info,
+				HostMemoryAllocator::Unspecified(),
+				debug_callback
+			)
+		}
+	}
+
 	/// Creates a new `Instance` from existing `InstanceCreateInfo`.
 	///
 	/// ### Safety
@@ -127,11 +167,11 @@ impl Instance {
 
 		let debug = match debug_callback.into() {
 			None => None,
-			Some(ref create_info) => {
+			Some((ref create_info, user_callback)) => {
 				let loader = DebugUtils::new(entry.deref(), &instance);
 				let callback = loader.create_debug_utils_messenger(create_info, None)?;
 
-				Some(InstanceDebug { loader, callback, host_memory_allocator: HostMemoryAllocator::Unspecified() /* TODO: Allow callbacks */ })
+				Some(InstanceDebug { loader, callback, user_callback, host_memory_allocator: HostMemoryAllocator::Unspecified() /* TODO: Allow callbacks */ })
 			}
 		};
 
@@ -140,7 +180,9 @@ impl Instance {
 			instance_handle: instance.handle(),
 			instance,
 			host_memory_allocator,
-			debug
+			debug,
+			extensions: Default::default(),
+			children: ChildRegistry::new()
 		}))
 	}
 
@@ -159,6 +201,31 @@ impl Instance {
 
 		Ok(enumerator)
 	}
+
+	/// Returns lazily-initialized extension loaders shared by objects created from this instance.
+	pub const fn ext(&self) -> ext::InstanceExt<'_> {
+		ext::InstanceExt::new(self)
+	}
+
+	/// Returns the `VK_EXT_debug_utils` loader if a debug callback was registered during creation.
+	pub fn debug_utils(&self) -> Option<&DebugUtils> {
+		self.debug.as_ref().map(|d| &d.loader)
+	}
+
+	/// Registry of objects created from this instance.
+	///
+	/// Only tracks anything when the `debug_destruction_order` feature is enabled.
+	pub const fn child_registry(&self) -> &ChildRegistry {
+		&self.children
+	}
+
+	/// Logs a warning listing the objects created from this instance that are still alive and returns their number.
+	///
+	/// Intended to be called at teardown after dropping all objects created from this instance, see the `util::destruction` module.
+	/// Always returns `0` unless the `debug_destruction_order` feature is enabled.
+	pub fn report_live_children(&self) -> usize {
+		self.children.report_live(crate::util::fmt::format_handle(self.instance_handle))
+	}
 }
 impl_common_handle_traits! {
 	impl HasHandle<vk::Instance>, Borrow, Eq, Hash, Ord for Instance {
@@ -175,6 +242,7 @@ impl Deref for Instance {
 impl Drop for Instance {
 	fn drop(&mut self) {
 		log_trace_common!(info; "Dropping", self);
+		self.children.report_surviving(crate::util::fmt::format_handle(self.instance_handle));
 
 		unsafe {
 			if let Some(debug) = self.debug.as_mut() {
@@ -33,6 +33,89 @@
 //!
 //! These validations might not be cheap. It is recommended to only enabled them when debugging, not in release/production builds.
 //!
+//! ### `debug_destruction_order`
+//!
+//! Instances and devices keep a registry of objects created from them. Since children keep their parent alive, a leaked child
+//! (for example forgotten using `std::mem::forget`) leaks its parent as well. `Device::report_live_children` and
+//! `Instance::report_live_children` log the children that are still alive and are intended to be called at teardown,
+//! after all objects owned by the application were dropped. See the `util::destruction` module.
+//!
+//! This adds a mutex lock to every object creation and destruction. It is recommended to only enabled it when debugging.
+//!
+//! ### `deterministic_handle_ids`
+//!
+//! Objects created through this crate are assigned a small id, counted separately for each object type, which `format_handle`
+//! and thus the `Debug` output and logs show instead of the raw handle value. Unlike raw handles, the ids do not change between runs
+//! as long as the objects are created in the same order, so logs of two runs can be diffed. See the `util::handle_id` module.
+//!
+//! This adds a global mutex lock to every object creation, destruction and handle formatting. It is intended for debugging only.
+//!
+//! ### `raw_window_handle`
+//!
+//! Adds `Surface::from_raw_handles` which creates a surface from `raw-window-handle` display and window handles.
+//! Supported platforms are Win32, Xlib, Xcb, Wayland, Android and Metal.
+//!
+//! ### `testing`
+//!
+//! Adds the `testing` module with helpers for writing headless GPU tests, such as creating a minimal compute context,
+//! running a compute shader over buffers and comparing the results with tolerance. Requires `naive_device_allocator` feature.
+//!
+//! ### `shader_reflection`
+//!
+//! Adds the `shader::reflect` module with a built-in SPIR-V parser. Shader modules are reflected on creation and graphics pipeline
+//! creation validates that fragment shader inputs are written by the previous stage and that vertex shader inputs have vertex attributes.
+//! The reflection can also generate descriptor set layout definitions, push constant ranges and vertex input attributes.
+//!
+//! ### `render_pass_analysis`
+//!
+//! In debug builds, analyzes render pass descriptions on creation and logs warnings about load and store operations that waste
+//! memory bandwidth on tiled GPUs. See the `render_pass::analysis` module.
+//!
+//! ### `sync2`
+//!
+//! Adds `VK_KHR_synchronization2` pipeline barriers and `Queue::submit2`. When the extension and its feature are not enabled
+//! on the device, they fall back to the legacy commands. See the `sync::sync2` module.
+//!
+//! ### `smallvec`
+//!
+//! Collects temporary arrays of handles, barriers and submit infos on hot paths, such as submitting, binding descriptor sets,
+//! recording barriers and clears and presenting, into stack-allocated `SmallVec`s instead of `Vec`s while the counts stay small.
+//! Only these temporary arrays stop allocating, other allocations made during a frame are not affected.
+//! The `steady_state_frames_do_not_allocate` integration test checks that submitting, acquiring and presenting
+//! with trace logging disabled makes no heap allocations with this feature.
+//!
+//! ### `shader_compile`
+//!
+//! Adds the `shader::compile` module and `ShaderModule::from_glsl`, which compile GLSL to SPIR-V at runtime using shaderc.
+//! Intended for development, for example to iterate on shaders without a separate build step.
+//!
+//! ### `shader_object`
+//!
+//! Adds the experimental `shader::object` module, which creates shader objects using `VK_EXT_shader_object`,
+//! and recording lock commands binding them and setting the dynamic state required to draw without pipelines.
+//!
+//! ### `renderdoc`
+//!
+//! Adds the `instance::capture` module, which triggers RenderDoc frame captures through its in-application API
+//! when the application runs under RenderDoc and annotates them with command buffer regions recorded through the recording lock.
+//!
+//! ### `profiles`
+//!
+//! Adds the `device::profile` module with `DeviceProfile`, which describes a baseline of api version, extensions, features and limits
+//! such as the `VP_KHR_roadmap_2022` profile. Physical devices can be checked against a profile and `DeviceBuilder::profile` enables
+//! everything it requires. `bootstrap::quick_init` selects a device supporting `BootstrapOptions::profile`. Requires `vulkan1_2` feature.
+//!
+//! ### `ray_tracing`
+//!
+//! Adds the `raytracing` module with acceleration structures, ray tracing pipelines and shader binding tables
+//! using `VK_KHR_acceleration_structure` and `VK_KHR_ray_tracing_pipeline`. Requires `vulkan1_2` feature.
+//!
+//! ### `integration_tests`
+//!
+//! Adds tests that render off-screen, read the results back and check the pixels. They are ignored by default because they need
+//! a Vulkan driver, a software one such as lavapipe or SwiftShader is enough. Run them using `cargo test --features integration_tests -- --ignored`.
+//! Requires `testing` and `shader_compile` features.
+//!
 //! ### `vulkan1_1` and `vulkan1_2`
 //!
 //! `vulkan1_1` enables methods that will panic on Vulkan 1.0
@@ -47,31 +130,91 @@
 pub use ash;
 // Export `log` so that `log_*` features can be applied to all vulkayes crates
 pub use log;
+// Export `smallvec` for the `collect_iter_faster!` macro
+#[cfg(feature = "smallvec")]
+#[doc(hidden)]
+pub use smallvec;
 
 // Macros used inside and outside of the crate.
 #[macro_use]
 pub mod util;
 
+pub mod bootstrap;
 pub mod command;
+pub mod debug;
 pub mod descriptor;
 pub mod device;
 pub mod entry;
+pub mod frame;
 pub mod framebuffer;
+pub mod graphics;
 pub mod instance;
 pub mod memory;
 pub mod physical_device;
 pub mod pipeline;
 pub mod prelude;
 pub mod queue;
+#[cfg(feature = "ray_tracing")]
+pub mod raytracing;
 pub mod render_pass;
 pub mod resource;
 pub mod shader;
 pub mod surface;
 pub mod swapchain;
 pub mod sync;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 #[cfg(test)]
 mod test {
+	use std::{
+		alloc::{GlobalAlloc, Layout, System},
+		cell::Cell
+	};
+
+	/// Global allocator of the tests counting the heap allocations made on each thread, see `count_allocations`.
+	struct CountingAllocator;
+	thread_local! {
+		static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+	}
+	impl CountingAllocator {
+		fn count() {
+			// The thread local may already be destroyed while the thread exits
+			let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+		}
+	}
+	unsafe impl GlobalAlloc for CountingAllocator {
+		unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+			Self::count();
+			System.alloc(layout)
+		}
+
+		unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+			Self::count();
+			System.alloc_zeroed(layout)
+		}
+
+		unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+			Self::count();
+			System.realloc(ptr, layout, new_size)
+		}
+
+		unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+			System.dealloc(ptr, layout)
+		}
+	}
+	#[global_allocator]
+	static COUNTING_ALLOCATOR: CountingAllocator = CountingAllocator;
+
+	/// Calls `f` and returns its result together with the number of heap allocations it made on the current thread.
+	pub fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+		let before = ALLOCATIONS.with(Cell::get);
+		let result = f();
+		let after = ALLOCATIONS.with(Cell::get);
+
+		(result, after - before)
+	}
+
 	pub fn setup_testing_logger() {
 		use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -124,4 +267,18 @@ mod test {
 		print_size::<crate::instance::Instance>("Instance");
 		print_size::<crate::device::Device>("Device");
 	}
+
+	#[test]
+	fn count_collect_iter_faster_allocations() {
+		let (small, allocations) = count_allocations(|| collect_iter_faster!(0 .. 4u32, 4));
+		assert_eq!(small.as_slice(), &[0, 1, 2, 3]);
+		#[cfg(feature = "smallvec")]
+		assert_eq!(allocations, 0);
+		#[cfg(not(feature = "smallvec"))]
+		assert_eq!(allocations, 1);
+
+		let (large, allocations) = count_allocations(|| collect_iter_faster!(0 .. 16u32, 4));
+		assert_eq!(large.len(), 16);
+		assert!(allocations >= 1);
+	}
 }
@@ -2,7 +2,7 @@ use std::{fmt, num::NonZeroU64, ops::Deref, ptr::NonNull};
 
 use ash::vk;
 use mapped::DeviceMemoryMapping;
-pub use mapped::{DeviceMemoryMappingAccess, MapError, MappingAccessResult, SliceWriteStride};
+pub use mapped::{DeviceMemoryMappingAccess, FlushError, MapError, MappingAccessResult, SliceWriteStride};
 
 use crate::{device::Device, prelude::Vrc, util::sync::Vutex};
 
@@ -13,6 +13,32 @@ mod mapped;
 pub mod naive;
 pub mod never;
 
+/// Policy for handling unified memory architecture devices.
+///
+/// The default is `Ignore`, `MapDirectly` has to be opted into since it changes which memory types are selected
+/// and requires the destinations of direct writes to be idle, see `StagingUploader::upload_to_buffer`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UmaPolicy {
+	/// Treat device local memory the same on all devices.
+	Ignore,
+	/// On UMA devices prefer host visible device local memory and write to it directly instead of staging.
+	MapDirectly
+}
+impl UmaPolicy {
+	/// Returns whether direct mapping should be used on a device with `memory_properties`.
+	pub fn map_directly(self, memory_properties: &crate::physical_device::enumerate::PhysicalDeviceMemoryProperties) -> bool {
+		match self {
+			UmaPolicy::Ignore => false,
+			UmaPolicy::MapDirectly => memory_properties.is_uma()
+		}
+	}
+}
+impl Default for UmaPolicy {
+	fn default() -> Self {
+		UmaPolicy::Ignore
+	}
+}
+
 type DropAllocImpl = Box<VSendSync![dyn FnOnce(&Vrc<Device>, vk::DeviceMemory, vk::DeviceSize, NonZeroU64)]>;
 type MapMemoryImpl = Box<VSendSync![dyn FnMut(&Vrc<Device>, vk::DeviceMemory, vk::DeviceSize, NonZeroU64) -> Result<NonNull<[u8]>, MapError>]>;
 type UnmapMemoryImpl = Box<VSendSync![dyn FnMut(&Vrc<Device>, vk::DeviceMemory, vk::DeviceSize, NonZeroU64, NonNull<[u8]>)]>;
@@ -73,6 +99,41 @@ impl DeviceMemoryAllocation {
 		}
 	}
 
+	/// Creates a new memory allocation that owns the whole `memory` object.
+	///
+	/// The memory is mapped using `vkMapMemory` and freed using `vkFreeMemory` when the allocation is dropped.
+	/// Freeing also removes `memory` from the memory stats registry of the device.
+	///
+	/// ### Safety
+	///
+	/// * `memory` must have been allocated from the `device` with allocation size `size`.
+	/// * `memory` must not be freed by anything else.
+	pub unsafe fn new_whole_memory(device: Vrc<Device>, memory: vk::DeviceMemory, size: NonZeroU64) -> Self {
+		Self::new(
+			device,
+			memory,
+			0,
+			size,
+			Box::new(|device, memory, offset, size| {
+				let ptr = device.map_memory(
+					memory,
+					offset,
+					size.get(),
+					vk::MemoryMapFlags::empty()
+				)? as *mut u8;
+				debug_assert_ne!(ptr, std::ptr::null_mut());
+
+				let slice_ptr = std::slice::from_raw_parts_mut(ptr, size.get() as usize) as *mut [u8];
+				Ok(NonNull::new_unchecked(slice_ptr))
+			}),
+			Box::new(|device, memory, _, _, _| device.unmap_memory(memory)),
+			Box::new(|device, memory, _, _| {
+				device.memory_stats().record_free(memory);
+				device.free_memory(memory, None)
+			})
+		)
+	}
+
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
@@ -85,6 +146,33 @@ impl DeviceMemoryAllocation {
 		self.size
 	}
 
+	/// Returns the property flags of the memory type of the underlying `vk::DeviceMemory` object.
+	///
+	/// Returns `None` if the memory is not recorded in the memory stats registry of the device, which all allocators of this crate do.
+	pub fn memory_property_flags(&self) -> Option<vk::MemoryPropertyFlags> {
+		let tracked = self.device.memory_stats().get(self.memory)?;
+		let memory_properties = self.device.physical_device().memory_properties();
+
+		memory_properties
+			.memory_types
+			.get(tracked.memory_type_index as usize)
+			.map(|memory_type| memory_type.property_flags)
+	}
+
+	/// Returns the number of bytes currently committed to the whole underlying `vk::DeviceMemory` object.
+	///
+	/// Only lazily allocated memory can be queried, so this returns `None` if the memory type is not `LAZILY_ALLOCATED`
+	/// or if the memory is not recorded in the memory stats registry of the device, which all allocators of this crate do.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetDeviceMemoryCommitment.html>.
+	pub fn commitment(&self) -> Option<vk::DeviceSize> {
+		if !self.memory_property_flags()?.contains(vk::MemoryPropertyFlags::LAZILY_ALLOCATED) {
+			return None
+		}
+
+		Some(unsafe { self.device.get_device_memory_commitment(self.memory) })
+	}
+
 	/// Returns true if this memory is currently mapped.
 	///
 	/// Note that this check requires locking a `Vutex`.
@@ -1,10 +1,11 @@
-use std::{num::NonZeroU64, ops::Deref, ptr::NonNull};
+use std::{num::NonZeroU64, ops::Deref};
 
 use ash::vk;
 
 use super::{
 	allocator::{BufferMemoryAllocator, ImageMemoryAllocator},
-	DeviceMemoryAllocation
+	DeviceMemoryAllocation,
+	UmaPolicy
 };
 use crate::{device::Device, physical_device::enumerate::PhysicalDeviceMemoryProperties, prelude::Vrc};
 
@@ -28,63 +29,60 @@ vk_result_error! {
 ///
 /// Allocates new memory for each request. This allocator is useful when prototyping or debugging,
 /// but not in bigger production applications.
+///
+/// With `UmaPolicy::MapDirectly` on UMA devices, requests for `DEVICE_LOCAL` memory prefer memory types
+/// that are also `HOST_VISIBLE` and `HOST_COHERENT` so that they can be written to directly.
 #[derive(Debug, Clone)]
 pub struct NaiveDeviceMemoryAllocator {
 	device: Vrc<Device>,
-	properties: PhysicalDeviceMemoryProperties
+	properties: PhysicalDeviceMemoryProperties,
+	map_directly: bool
 }
 impl NaiveDeviceMemoryAllocator {
 	pub fn new(device: Vrc<Device>) -> Self {
+		Self::with_uma_policy(device, UmaPolicy::default())
+	}
+
+	pub fn with_uma_policy(device: Vrc<Device>, uma_policy: UmaPolicy) -> Self {
 		let properties = device.physical_device().memory_properties();
+		let map_directly = uma_policy.map_directly(&properties);
 
-		NaiveDeviceMemoryAllocator { device, properties }
+		NaiveDeviceMemoryAllocator { device, properties, map_directly }
 	}
 
 	fn find_memory_index(&self, requirements: vk::MemoryRequirements, required_flags: vk::MemoryPropertyFlags) -> Result<u32, AllocationError> {
-		for (index, memory_type) in self.properties.memory_types.iter().enumerate() {
-			// If this type is in the mask of allowed types
-			if requirements.memory_type_bits & (1 << index as u32) != 0 {
-				// and contains all the required flags
-				if memory_type.property_flags.contains(required_flags) {
-					return Ok(index as u32)
-				}
+		if self.map_directly && required_flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL) {
+			let preferred_flags = required_flags | vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT;
+			if let Ok(index) = self.find_memory_index_exact(requirements, preferred_flags) {
+				return Ok(index)
 			}
 		}
 
-		Err(AllocationError::NoSuitableMemoryType)
+		self.find_memory_index_exact(requirements, required_flags)
+	}
+
+	fn find_memory_index_exact(&self, requirements: vk::MemoryRequirements, required_flags: vk::MemoryPropertyFlags) -> Result<u32, AllocationError> {
+		self.properties
+			.find_memory_type(requirements.memory_type_bits, required_flags)
+			.ok_or(AllocationError::NoSuitableMemoryType)
 	}
 
 	fn allocate(&self, info: impl Deref<Target = vk::MemoryAllocateInfo>) -> Result<DeviceMemoryAllocation, AllocationError> {
 		let memory = unsafe { self.device.allocate_memory(&info, None)? };
 		let size = unsafe { NonZeroU64::new_unchecked(info.allocation_size) };
+		self.device.memory_stats().record_allocation(memory, info.memory_type_index, size);
 
-		Ok(unsafe {
-			DeviceMemoryAllocation::new(
-				self.device.clone(),
-				memory,
-				0,
-				size,
-				Box::new(|device, memory, offset, size| {
-					let ptr = device.map_memory(
-						memory,
-						offset,
-						size.get(),
-						vk::MemoryMapFlags::empty()
-					)? as *mut u8;
-					debug_assert_ne!(ptr, std::ptr::null_mut());
-
-					let slice_ptr = std::slice::from_raw_parts_mut(ptr, size.get() as usize) as *mut [u8];
-					Ok(NonNull::new_unchecked(slice_ptr))
-				}),
-				Box::new(|device, memory, _, _, _| device.unmap_memory(memory)),
-				Box::new(|device, memory, _, _| device.free_memory(memory, None))
-			)
-		})
+		Ok(unsafe { DeviceMemoryAllocation::new_whole_memory(self.device.clone(), memory, size) })
 	}
 
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
+
+	/// Returns whether device local allocations prefer directly mappable memory.
+	pub const fn maps_directly(&self) -> bool {
+		self.map_directly
+	}
 }
 unsafe impl ImageMemoryAllocator for NaiveDeviceMemoryAllocator {
 	type AllocationRequirements = vk::MemoryPropertyFlags;
@@ -0,0 +1,354 @@
+//! Buffers and images shared between two devices, such as an integrated and a discrete GPU.
+//!
+//! A `CrossDevice` resource is created on the source device with exportable memory and imported into the destination device.
+//! Work on the source device that writes the resource is submitted with `submit_write`, which signals a shared semaphore,
+//! and work on the destination device that reads it is submitted with `submit_read`, which waits on it.
+//!
+//! Both devices must support the chosen handle types. `OPAQUE_FD` only works between devices with the same
+//! `deviceUUID` and `driverUUID`, devices from different vendors generally need `DMA_BUF_EXT` memory and linear images.
+//!
+//! The resource must be released to `vk::QUEUE_FAMILY_EXTERNAL` at the end of the write and acquired from it
+//! at the start of the read using queue family ownership transfer barriers.
+
+use std::num::NonZeroU64;
+
+use ash::vk;
+use thiserror::Error;
+
+use super::{
+	export_memory_fd,
+	export_semaphore_fd,
+	exportable_semaphore,
+	import_semaphore_fd,
+	ExportMemoryAllocator,
+	ExternalMemoryError,
+	ExternalSemaphoreError,
+	ImportFdMemoryAllocator
+};
+use crate::{
+	memory::device::allocator::{BufferMemoryAllocator, ImageMemoryAllocator},
+	prelude::{
+		BinarySemaphore,
+		Buffer,
+		BufferAllocatorParams,
+		CommandBuffer,
+		Device,
+		Fence,
+		HostMemoryAllocator,
+		Image,
+		ImageAllocatorParams,
+		Queue,
+		Vrc
+	},
+	queue::error::QueueSubmitError,
+	resource::{
+		buffer::error::BufferError,
+		image::{
+			error::ImageError,
+			params::{ImageSizeInfo, ImageTilingAndLayout}
+		}
+	}
+};
+
+#[derive(Error, Debug)]
+pub enum CrossDeviceError {
+	#[error("Could not create buffer")]
+	Buffer(#[from] BufferError<ExternalMemoryError>),
+	#[error("Could not create image")]
+	Image(#[from] ImageError<ExternalMemoryError>),
+	#[error("Could not export memory")]
+	Memory(#[from] ExternalMemoryError),
+	#[error("Could not share semaphore")]
+	Semaphore(#[from] ExternalSemaphoreError),
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Source and destination devices must be different")]
+	SameDevice
+}
+
+/// Parameters of resource sharing between devices.
+#[derive(Debug, Clone, Copy)]
+pub struct CrossDeviceParams {
+	pub memory_handle_type: vk::ExternalMemoryHandleTypeFlags,
+	pub semaphore_handle_type: vk::ExternalSemaphoreHandleTypeFlags,
+	/// Memory properties required on the source device.
+	pub source_memory_flags: vk::MemoryPropertyFlags,
+	/// Memory properties required on the destination device.
+	pub destination_memory_flags: vk::MemoryPropertyFlags
+}
+impl Default for CrossDeviceParams {
+	fn default() -> Self {
+		CrossDeviceParams {
+			memory_handle_type: vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+			semaphore_handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+			source_memory_flags: vk::MemoryPropertyFlags::DEVICE_LOCAL,
+			destination_memory_flags: vk::MemoryPropertyFlags::empty()
+		}
+	}
+}
+
+/// Resource `R` backed by the same memory on two devices.
+#[derive(Debug)]
+pub struct CrossDevice<R> {
+	source: Vrc<R>,
+	destination: Vrc<R>,
+
+	source_semaphore: BinarySemaphore,
+	destination_semaphore: BinarySemaphore
+}
+impl<R> CrossDevice<R> {
+	fn with_semaphores(
+		source: Vrc<R>,
+		destination: Vrc<R>,
+		source_device: &Vrc<Device>,
+		destination_device: &Vrc<Device>,
+		params: &CrossDeviceParams,
+		host_memory_allocator: &HostMemoryAllocator
+	) -> Result<Self, CrossDeviceError> {
+		let source_semaphore = exportable_semaphore(
+			source_device.clone(),
+			params.semaphore_handle_type,
+			host_memory_allocator.clone()
+		)
+		.map_err(ExternalSemaphoreError::from)?;
+		let fd = export_semaphore_fd(&source_semaphore, params.semaphore_handle_type)?;
+		let destination_semaphore = import_semaphore_fd(
+			destination_device.clone(),
+			params.semaphore_handle_type,
+			fd,
+			host_memory_allocator.clone()
+		)?;
+
+		Ok(CrossDevice { source, destination, source_semaphore, destination_semaphore })
+	}
+
+	/// Resource on the source device.
+	pub const fn source(&self) -> &Vrc<R> {
+		&self.source
+	}
+
+	/// Resource on the destination device.
+	pub const fn destination(&self) -> &Vrc<R> {
+		&self.destination
+	}
+
+	/// Semaphore on the source device signaled by `submit_write`.
+	pub const fn source_semaphore(&self) -> &BinarySemaphore {
+		&self.source_semaphore
+	}
+
+	/// Semaphore on the destination device waited on by `submit_read`.
+	pub const fn destination_semaphore(&self) -> &BinarySemaphore {
+		&self.destination_semaphore
+	}
+
+	/// Submits `command_buffer` writing `source` on `queue` of the source device and signals the shared semaphore.
+	///
+	/// Every write must be followed by exactly one `submit_read` before the next write.
+	pub fn submit_write(&self, queue: &Queue, command_buffer: &CommandBuffer, fence: Option<&Fence>) -> Result<(), QueueSubmitError> {
+		queue.submit(
+			[],
+			[],
+			[command_buffer],
+			[&self.source_semaphore],
+			fence
+		)
+	}
+
+	/// Submits `command_buffer` reading `destination` on `queue` of the destination device after the last `submit_write` finishes.
+	///
+	/// `wait_stage` is the first pipeline stage of `command_buffer` that accesses the resource.
+	pub fn submit_read(
+		&self,
+		queue: &Queue,
+		command_buffer: &CommandBuffer,
+		wait_stage: vk::PipelineStageFlags,
+		fence: Option<&Fence>
+	) -> Result<(), QueueSubmitError> {
+		queue.submit(
+			[&self.destination_semaphore],
+			[wait_stage],
+			[command_buffer],
+			[],
+			fence
+		)
+	}
+}
+impl CrossDevice<Buffer> {
+	/// Creates a buffer of `size` on `source_device` with `source_usage` and imports it into `destination_device` with `destination_usage`.
+	pub fn new_buffer(
+		source_device: Vrc<Device>,
+		destination_device: Vrc<Device>,
+		size: NonZeroU64,
+		source_usage: vk::BufferUsageFlags,
+		destination_usage: vk::BufferUsageFlags,
+		params: CrossDeviceParams,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, CrossDeviceError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if source_device == destination_device {
+				return Err(CrossDeviceError::SameDevice)
+			}
+		}
+
+		let export_allocator = ExportMemoryAllocator::new(source_device.clone(), params.memory_handle_type);
+		let source = create_buffer(
+			source_device.clone(),
+			size,
+			source_usage,
+			params.memory_handle_type,
+			BufferAllocatorParams::Some { allocator: &export_allocator, requirements: params.source_memory_flags },
+			host_memory_allocator.clone()
+		)?;
+
+		let source_memory = source.memory().expect("exported buffer must have memory");
+		let import_allocator = ImportFdMemoryAllocator::new(
+			destination_device.clone(),
+			params.memory_handle_type,
+			export_memory_fd(source_memory, params.memory_handle_type)?,
+			source_memory.size()
+		);
+		let destination = create_buffer(
+			destination_device.clone(),
+			size,
+			destination_usage,
+			params.memory_handle_type,
+			BufferAllocatorParams::Some { allocator: &import_allocator, requirements: params.destination_memory_flags },
+			host_memory_allocator.clone()
+		)?;
+
+		Self::with_semaphores(
+			source,
+			destination,
+			&source_device,
+			&destination_device,
+			&params,
+			&host_memory_allocator
+		)
+	}
+}
+impl CrossDevice<Image> {
+	/// Creates an image on `source_device` with `source_usage` and imports it into `destination_device` with `destination_usage`.
+	///
+	/// Both images are created with the same `format`, `size_info` and `tiling_and_layout`.
+	pub fn new_image(
+		source_device: Vrc<Device>,
+		destination_device: Vrc<Device>,
+		format: vk::Format,
+		size_info: ImageSizeInfo,
+		tiling_and_layout: ImageTilingAndLayout,
+		source_usage: vk::ImageUsageFlags,
+		destination_usage: vk::ImageUsageFlags,
+		params: CrossDeviceParams,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, CrossDeviceError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if source_device == destination_device {
+				return Err(CrossDeviceError::SameDevice)
+			}
+		}
+
+		let export_allocator = ExportMemoryAllocator::new(source_device.clone(), params.memory_handle_type);
+		let source = create_image(
+			source_device.clone(),
+			format,
+			size_info,
+			tiling_and_layout,
+			source_usage,
+			params.memory_handle_type,
+			ImageAllocatorParams::Some { allocator: &export_allocator, requirements: params.source_memory_flags },
+			host_memory_allocator.clone()
+		)?;
+
+		let source_memory = source.memory().expect("exported image must have memory");
+		let import_allocator = ImportFdMemoryAllocator::new(
+			destination_device.clone(),
+			params.memory_handle_type,
+			export_memory_fd(source_memory, params.memory_handle_type)?,
+			source_memory.size()
+		);
+		let destination = create_image(
+			destination_device.clone(),
+			format,
+			size_info,
+			tiling_and_layout,
+			destination_usage,
+			params.memory_handle_type,
+			ImageAllocatorParams::Some { allocator: &import_allocator, requirements: params.destination_memory_flags },
+			host_memory_allocator.clone()
+		)?;
+
+		Self::with_semaphores(
+			source,
+			destination,
+			&source_device,
+			&destination_device,
+			&params,
+			&host_memory_allocator
+		)
+	}
+}
+
+fn create_buffer<A: BufferMemoryAllocator>(
+	device: Vrc<Device>,
+	size: NonZeroU64,
+	usage: vk::BufferUsageFlags,
+	memory_handle_type: vk::ExternalMemoryHandleTypeFlags,
+	allocator_params: BufferAllocatorParams<A>,
+	host_memory_allocator: HostMemoryAllocator
+) -> Result<Vrc<Buffer>, BufferError<A::Error>> {
+	let mut external_info = vk::ExternalMemoryBufferCreateInfo::builder().handle_types(memory_handle_type);
+	let create_info = vk::BufferCreateInfo::builder()
+		.size(size.get())
+		.usage(usage)
+		.sharing_mode(vk::SharingMode::EXCLUSIVE)
+		.push_next(&mut external_info);
+
+	unsafe {
+		Buffer::from_create_info(
+			device,
+			create_info,
+			allocator_params,
+			host_memory_allocator
+		)
+	}
+}
+
+pub(super) fn create_image<A: ImageMemoryAllocator>(
+	device: Vrc<Device>,
+	format: vk::Format,
+	size_info: ImageSizeInfo,
+	tiling_and_layout: ImageTilingAndLayout,
+	usage: vk::ImageUsageFlags,
+	memory_handle_type: vk::ExternalMemoryHandleTypeFlags,
+	allocator_params: ImageAllocatorParams<A>,
+	host_memory_allocator: HostMemoryAllocator
+) -> Result<Vrc<Image>, ImageError<A::Error>> {
+	let (size, samples, flags) = size_info.into();
+	let (tiling, layout) = tiling_and_layout.into();
+
+	let mut external_info = vk::ExternalMemoryImageCreateInfo::builder().handle_types(memory_handle_type);
+	let create_info = vk::ImageCreateInfo::builder()
+		.flags(flags)
+		.image_type(size.image_type())
+		.format(format)
+		.extent(size.into())
+		.mip_levels(size.mipmap_levels().get())
+		.array_layers(size.array_layers().get())
+		.samples(samples)
+		.tiling(tiling)
+		.usage(usage)
+		.sharing_mode(vk::SharingMode::EXCLUSIVE)
+		.initial_layout(layout)
+		.push_next(&mut external_info);
+
+	unsafe {
+		Image::from_create_info(
+			device,
+			create_info,
+			allocator_params,
+			host_memory_allocator
+		)
+	}
+}
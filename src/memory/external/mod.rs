@@ -0,0 +1,354 @@
+//! Sharing device memory and semaphores through POSIX file descriptors.
+//!
+//! Memory and semaphores are exported from one device as file descriptors and imported into another device
+//! or another process. This requires `VK_KHR_external_memory_fd` and `VK_KHR_external_semaphore_fd` to be enabled
+//! on all participating devices.
+//!
+//! Exported resources are always allocated with a dedicated allocation, which some handle types require.
+
+use std::{
+	fmt,
+	num::NonZeroU64,
+	ops::Deref,
+	os::unix::io::{FromRawFd, IntoRawFd, OwnedFd}
+};
+
+use ash::vk;
+
+use super::device::{
+	allocator::{BufferMemoryAllocator, ImageMemoryAllocator},
+	DeviceMemoryAllocation
+};
+use crate::{
+	physical_device::enumerate::PhysicalDeviceMemoryProperties,
+	prelude::{BinarySemaphore, Device, HasHandle, HostMemoryAllocator, Semaphore, Vrc},
+	sync::semaphore::error::SemaphoreError,
+	util::sync::Vutex
+};
+
+pub mod cross_device;
+pub mod shared;
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum ExternalMemoryError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_TOO_MANY_OBJECTS,
+			ERROR_INVALID_EXTERNAL_HANDLE
+		}
+
+		#[error("Suitable memory type could not be found")]
+		NoSuitableMemoryType,
+
+		#[error("The file descriptor has already been consumed by a previous import")]
+		HandleConsumed,
+	}
+}
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum ExternalSemaphoreError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_TOO_MANY_OBJECTS,
+			ERROR_INVALID_EXTERNAL_HANDLE
+		}
+
+		#[error("Could not create semaphore")]
+		Semaphore(#[from] SemaphoreError),
+	}
+}
+
+/// Selects which kind of resource a dedicated allocation is made for.
+#[derive(Debug, Clone, Copy)]
+enum DedicatedResource {
+	Buffer(vk::Buffer),
+	Image(vk::Image)
+}
+impl DedicatedResource {
+	fn requirements(self, device: &Device) -> vk::MemoryRequirements {
+		unsafe {
+			match self {
+				DedicatedResource::Buffer(buffer) => device.get_buffer_memory_requirements(buffer),
+				DedicatedResource::Image(image) => device.get_image_memory_requirements(image)
+			}
+		}
+	}
+
+	fn dedicated_info(self) -> vk::MemoryDedicatedAllocateInfoBuilder<'static> {
+		match self {
+			DedicatedResource::Buffer(buffer) => vk::MemoryDedicatedAllocateInfo::builder().buffer(buffer),
+			DedicatedResource::Image(image) => vk::MemoryDedicatedAllocateInfo::builder().image(image)
+		}
+	}
+}
+
+/// Device memory allocator that allocates memory which can be exported as a file descriptor using `export_memory_fd`.
+///
+/// The buffer or image the memory is allocated for must be created with `vk::ExternalMemoryBufferCreateInfo`
+/// or `vk::ExternalMemoryImageCreateInfo` respectively, containing `handle_type`.
+#[derive(Debug, Clone)]
+pub struct ExportMemoryAllocator {
+	device: Vrc<Device>,
+	properties: PhysicalDeviceMemoryProperties,
+	handle_type: vk::ExternalMemoryHandleTypeFlags
+}
+impl ExportMemoryAllocator {
+	pub fn new(device: Vrc<Device>, handle_type: vk::ExternalMemoryHandleTypeFlags) -> Self {
+		let properties = device.physical_device().memory_properties();
+
+		ExportMemoryAllocator { device, properties, handle_type }
+	}
+
+	fn allocate(&self, resource: DedicatedResource, required_flags: vk::MemoryPropertyFlags) -> Result<DeviceMemoryAllocation, ExternalMemoryError> {
+		let requirements = resource.requirements(&self.device);
+		let memory_index = self
+			.properties
+			.find_memory_type(requirements.memory_type_bits, required_flags)
+			.ok_or(ExternalMemoryError::NoSuitableMemoryType)?;
+
+		let mut export_info = vk::ExportMemoryAllocateInfo::builder().handle_types(self.handle_type);
+		let mut dedicated_info = resource.dedicated_info();
+		let alloc_info = vk::MemoryAllocateInfo::builder()
+			.allocation_size(requirements.size)
+			.memory_type_index(memory_index)
+			.push_next(&mut export_info)
+			.push_next(&mut dedicated_info);
+
+		log_trace_common!(
+			"Allocating exportable memory:",
+			resource,
+			required_flags,
+			alloc_info.deref()
+		);
+		unsafe {
+			let memory = self.device.allocate_memory(&alloc_info, None)?;
+
+			Ok(DeviceMemoryAllocation::new_whole_memory(
+				self.device.clone(),
+				memory,
+				NonZeroU64::new_unchecked(requirements.size)
+			))
+		}
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+
+	pub const fn handle_type(&self) -> vk::ExternalMemoryHandleTypeFlags {
+		self.handle_type
+	}
+}
+unsafe impl ImageMemoryAllocator for ExportMemoryAllocator {
+	type AllocationRequirements = vk::MemoryPropertyFlags;
+	type Error = ExternalMemoryError;
+
+	fn allocate(&self, image: vk::Image, required_flags: Self::AllocationRequirements) -> Result<DeviceMemoryAllocation, Self::Error> {
+		self.allocate(DedicatedResource::Image(image), required_flags)
+	}
+}
+unsafe impl BufferMemoryAllocator for ExportMemoryAllocator {
+	type AllocationRequirements = vk::MemoryPropertyFlags;
+	type Error = ExternalMemoryError;
+
+	fn allocate(&self, buffer: vk::Buffer, required_flags: Self::AllocationRequirements) -> Result<DeviceMemoryAllocation, Self::Error> {
+		self.allocate(DedicatedResource::Buffer(buffer), required_flags)
+	}
+}
+
+/// Device memory "allocator" that imports memory from a file descriptor.
+///
+/// The file descriptor is consumed by the first successful allocation, any further allocation returns `HandleConsumed`.
+/// The buffer or image the memory is imported for must be created with `vk::ExternalMemoryBufferCreateInfo`
+/// or `vk::ExternalMemoryImageCreateInfo` respectively, containing `handle_type`.
+pub struct ImportFdMemoryAllocator {
+	device: Vrc<Device>,
+	properties: PhysicalDeviceMemoryProperties,
+	handle_type: vk::ExternalMemoryHandleTypeFlags,
+	allocation_size: NonZeroU64,
+
+	fd: Vutex<Option<OwnedFd>>
+}
+impl ImportFdMemoryAllocator {
+	/// Creates a new import allocator.
+	///
+	/// `allocation_size` must be the size of the exported allocation, see `DeviceMemoryAllocation::size`.
+	pub fn new(device: Vrc<Device>, handle_type: vk::ExternalMemoryHandleTypeFlags, fd: OwnedFd, allocation_size: NonZeroU64) -> Self {
+		let properties = device.physical_device().memory_properties();
+
+		ImportFdMemoryAllocator { device, properties, handle_type, allocation_size, fd: Vutex::new(Some(fd)) }
+	}
+
+	fn allocate(&self, resource: DedicatedResource, required_flags: vk::MemoryPropertyFlags) -> Result<DeviceMemoryAllocation, ExternalMemoryError> {
+		let mut lock = self.fd.lock().expect("vutex poisoned");
+		let fd = lock.take().ok_or(ExternalMemoryError::HandleConsumed)?.into_raw_fd();
+
+		let result = self.allocate_fd(resource, required_flags, fd);
+		if result.is_err() {
+			// ownership of the file descriptor is only transferred to the implementation on success
+			*lock = Some(unsafe { OwnedFd::from_raw_fd(fd) });
+		}
+
+		result
+	}
+
+	fn allocate_fd(
+		&self,
+		resource: DedicatedResource,
+		required_flags: vk::MemoryPropertyFlags,
+		fd: i32
+	) -> Result<DeviceMemoryAllocation, ExternalMemoryError> {
+		let mut memory_type_bits = resource.requirements(&self.device).memory_type_bits;
+		if self.handle_type != vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD {
+			let fd_properties = unsafe {
+				self.device
+					.ext()
+					.external_memory_fd()
+					.get_memory_fd_properties(self.handle_type, fd)?
+			};
+			memory_type_bits &= fd_properties.memory_type_bits;
+		}
+
+		let memory_index = self
+			.properties
+			.find_memory_type(memory_type_bits, required_flags)
+			.ok_or(ExternalMemoryError::NoSuitableMemoryType)?;
+
+		let mut import_info = vk::ImportMemoryFdInfoKHR::builder().handle_type(self.handle_type).fd(fd);
+		let mut dedicated_info = resource.dedicated_info();
+		let alloc_info = vk::MemoryAllocateInfo::builder()
+			.allocation_size(self.allocation_size.get())
+			.memory_type_index(memory_index)
+			.push_next(&mut import_info)
+			.push_next(&mut dedicated_info);
+
+		log_trace_common!(
+			"Importing memory:",
+			resource,
+			required_flags,
+			alloc_info.deref()
+		);
+		unsafe {
+			let memory = self.device.allocate_memory(&alloc_info, None)?;
+
+			Ok(DeviceMemoryAllocation::new_whole_memory(
+				self.device.clone(),
+				memory,
+				self.allocation_size
+			))
+		}
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+
+	pub const fn handle_type(&self) -> vk::ExternalMemoryHandleTypeFlags {
+		self.handle_type
+	}
+
+	pub const fn allocation_size(&self) -> NonZeroU64 {
+		self.allocation_size
+	}
+}
+unsafe impl ImageMemoryAllocator for ImportFdMemoryAllocator {
+	type AllocationRequirements = vk::MemoryPropertyFlags;
+	type Error = ExternalMemoryError;
+
+	fn allocate(&self, image: vk::Image, required_flags: Self::AllocationRequirements) -> Result<DeviceMemoryAllocation, Self::Error> {
+		self.allocate(DedicatedResource::Image(image), required_flags)
+	}
+}
+unsafe impl BufferMemoryAllocator for ImportFdMemoryAllocator {
+	type AllocationRequirements = vk::MemoryPropertyFlags;
+	type Error = ExternalMemoryError;
+
+	fn allocate(&self, buffer: vk::Buffer, required_flags: Self::AllocationRequirements) -> Result<DeviceMemoryAllocation, Self::Error> {
+		self.allocate(DedicatedResource::Buffer(buffer), required_flags)
+	}
+}
+impl fmt::Debug for ImportFdMemoryAllocator {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ImportFdMemoryAllocator")
+			.field("device", &self.device)
+			.field("handle_type", &self.handle_type)
+			.field("allocation_size", &self.allocation_size)
+			.field("fd", &self.fd)
+			.finish()
+	}
+}
+
+/// Exports `memory` as a new file descriptor of `handle_type`.
+///
+/// `memory` must have been allocated by `ExportMemoryAllocator` with `handle_type`.
+pub fn export_memory_fd(memory: &DeviceMemoryAllocation, handle_type: vk::ExternalMemoryHandleTypeFlags) -> Result<OwnedFd, ExternalMemoryError> {
+	let info = vk::MemoryGetFdInfoKHR::builder()
+		.memory(*memory.deref())
+		.handle_type(handle_type);
+
+	log_trace_common!("Exporting memory:", memory, handle_type);
+	unsafe {
+		let fd = memory.device().ext().external_memory_fd().get_memory_fd(&info)?;
+
+		Ok(OwnedFd::from_raw_fd(fd))
+	}
+}
+
+/// Creates a new binary semaphore that can be exported as a file descriptor of `handle_type` using `export_semaphore_fd`.
+pub fn exportable_semaphore(
+	device: Vrc<Device>,
+	handle_type: vk::ExternalSemaphoreHandleTypeFlags,
+	host_memory_allocator: HostMemoryAllocator
+) -> Result<BinarySemaphore, SemaphoreError> {
+	let mut export_info = vk::ExportSemaphoreCreateInfo::builder().handle_types(handle_type);
+	let create_info = vk::SemaphoreCreateInfo::builder().push_next(&mut export_info);
+
+	unsafe { Semaphore::from_create_info(device, create_info, host_memory_allocator).map(|s| BinarySemaphore::new(s)) }
+}
+
+/// Exports the payload of `semaphore` as a new file descriptor of `handle_type`.
+///
+/// `semaphore` must have been created by `exportable_semaphore` with `handle_type`.
+pub fn export_semaphore_fd(semaphore: &Semaphore, handle_type: vk::ExternalSemaphoreHandleTypeFlags) -> Result<OwnedFd, ExternalSemaphoreError> {
+	let info = vk::SemaphoreGetFdInfoKHR::builder()
+		.semaphore(semaphore.handle())
+		.handle_type(handle_type);
+
+	log_trace_common!("Exporting semaphore:", semaphore, handle_type);
+	unsafe {
+		let fd = semaphore.device().ext().external_semaphore_fd().get_semaphore_fd(&info)?;
+
+		Ok(OwnedFd::from_raw_fd(fd))
+	}
+}
+
+/// Creates a new binary semaphore on `device` and permanently imports the payload from `fd` of `handle_type` into it.
+pub fn import_semaphore_fd(
+	device: Vrc<Device>,
+	handle_type: vk::ExternalSemaphoreHandleTypeFlags,
+	fd: OwnedFd,
+	host_memory_allocator: HostMemoryAllocator
+) -> Result<BinarySemaphore, ExternalSemaphoreError> {
+	let semaphore = Semaphore::binary(device, host_memory_allocator)?;
+
+	let fd = fd.into_raw_fd();
+	let info = vk::ImportSemaphoreFdInfoKHR::builder()
+		.semaphore(semaphore.handle())
+		.handle_type(handle_type)
+		.fd(fd);
+
+	log_trace_common!("Importing semaphore:", semaphore, handle_type, fd);
+	let result = unsafe { semaphore.device().ext().external_semaphore_fd().import_semaphore_fd(&info) };
+	if let Err(err) = result {
+		// ownership of the file descriptor is only transferred to the implementation on success
+		drop(unsafe { OwnedFd::from_raw_fd(fd) });
+
+		return Err(err.into())
+	}
+
+	Ok(semaphore)
+}
@@ -0,0 +1,359 @@
+//! Images shared between processes cooperating on the same device, such as a compositor and its plugins.
+//!
+//! The producing process creates the image with `export_shared_image`, which returns the image, a semaphore
+//! and `SharedImageHandles` containing file descriptors of the image memory and of the semaphore payload.
+//! The file descriptors are sent to the consuming process over a unix domain socket (`SCM_RIGHTS`)
+//! together with the serialized `SharedImageMetadata`, see `SharedImageMetadata::to_bytes`.
+//! The consuming process then recreates the image with `import_shared_image`.
+//!
+//! The producer signals the semaphore when it finishes writing the image and the consumer waits on it before reading.
+//! The semaphore is binary, so each signal must be waited on exactly once. Signaling in the other direction, for example
+//! to return the image to the producer, requires a second semaphore created with `exportable_semaphore`.
+//!
+//! Both processes must use the same physical device, this is checked by comparing the device UUIDs.
+//! The image must be released to `vk::QUEUE_FAMILY_EXTERNAL` at the end of the write and acquired from it
+//! at the start of the read using queue family ownership transfer barriers.
+
+use std::{
+	convert::TryInto,
+	num::{NonZeroU32, NonZeroU64},
+	os::unix::io::OwnedFd
+};
+
+use ash::vk;
+use thiserror::Error;
+
+use super::{
+	cross_device::create_image,
+	export_memory_fd,
+	export_semaphore_fd,
+	exportable_semaphore,
+	import_semaphore_fd,
+	ExportMemoryAllocator,
+	ExternalMemoryError,
+	ExternalSemaphoreError,
+	ImportFdMemoryAllocator
+};
+use crate::{
+	prelude::{BinarySemaphore, Device, HostMemoryAllocator, Image, ImageAllocatorParams, Vrc},
+	resource::image::{
+		error::ImageError,
+		params::{ImageSize, ImageSizeInfo, ImageTilingAndLayout}
+	}
+};
+
+#[derive(Error, Debug)]
+pub enum SharedImageError {
+	#[error("Could not create image")]
+	Image(#[from] ImageError<ExternalMemoryError>),
+	#[error("Could not export memory")]
+	Memory(#[from] ExternalMemoryError),
+	#[error("Could not share semaphore")]
+	Semaphore(#[from] ExternalSemaphoreError),
+	#[error("The image was exported from a different physical device")]
+	DeviceMismatch
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharedImageMetadataError {
+	#[error("Expected {} bytes of metadata, got {0}", SharedImageMetadata::SERIALIZED_SIZE)]
+	InvalidLength(usize),
+	#[error("The data does not contain shared image metadata")]
+	InvalidMagic,
+	#[error("Metadata version {0} is not supported")]
+	UnsupportedVersion(u32),
+	#[error("Image dimensions must not be zero")]
+	ZeroSize
+}
+
+/// Parameters an image was created with, needed to import it in another process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedImageMetadata {
+	pub format: vk::Format,
+	pub size: ImageSize,
+	pub samples: vk::SampleCountFlags,
+	pub flags: vk::ImageCreateFlags,
+	pub tiling: vk::ImageTiling,
+	pub usage: vk::ImageUsageFlags,
+
+	pub memory_handle_type: vk::ExternalMemoryHandleTypeFlags,
+	pub semaphore_handle_type: vk::ExternalSemaphoreHandleTypeFlags,
+	/// Size of the exported allocation, see `DeviceMemoryAllocation::size`.
+	pub allocation_size: NonZeroU64,
+	/// `deviceUUID` of the exporting physical device.
+	pub device_uuid: [u8; vk::UUID_SIZE]
+}
+impl SharedImageMetadata {
+	const MAGIC: [u8; 8] = *b"VKYSHIMG";
+	const VERSION: u32 = 1;
+
+	/// Length of the serialized metadata in bytes.
+	pub const SERIALIZED_SIZE: usize = 8 + Self::WORD_COUNT * 4 + 8 + vk::UUID_SIZE;
+	const WORD_COUNT: usize = 14;
+
+	/// Serializes the metadata into a versioned little-endian byte representation.
+	pub fn to_bytes(&self) -> [u8; Self::SERIALIZED_SIZE] {
+		let words: [u32; Self::WORD_COUNT] = [
+			Self::VERSION,
+			self.format.as_raw() as u32,
+			self.size.image_type().as_raw() as u32,
+			self.size.width().get(),
+			self.size.height().get(),
+			self.size.depth().get(),
+			self.size.array_layers().get(),
+			self.size.mipmap_levels().get(),
+			self.samples.as_raw(),
+			self.flags.as_raw(),
+			self.tiling.as_raw() as u32,
+			self.usage.as_raw(),
+			self.memory_handle_type.as_raw(),
+			self.semaphore_handle_type.as_raw()
+		];
+
+		let mut bytes = [0u8; Self::SERIALIZED_SIZE];
+		bytes[.. 8].copy_from_slice(&Self::MAGIC);
+		for (chunk, word) in bytes[8 ..].chunks_exact_mut(4).zip(words.iter()) {
+			chunk.copy_from_slice(&word.to_le_bytes());
+		}
+
+		let offset = 8 + Self::WORD_COUNT * 4;
+		bytes[offset .. offset + 8].copy_from_slice(&self.allocation_size.get().to_le_bytes());
+		bytes[offset + 8 ..].copy_from_slice(&self.device_uuid);
+
+		bytes
+	}
+
+	/// Deserializes metadata created by `to_bytes`.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, SharedImageMetadataError> {
+		if bytes.len() != Self::SERIALIZED_SIZE {
+			return Err(SharedImageMetadataError::InvalidLength(bytes.len()))
+		}
+		if bytes[.. 8] != Self::MAGIC {
+			return Err(SharedImageMetadataError::InvalidMagic)
+		}
+
+		let mut words = bytes[8 .. 8 + Self::WORD_COUNT * 4]
+			.chunks_exact(4)
+			.map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+		let mut next = || words.next().unwrap();
+
+		let version = next();
+		if version != Self::VERSION {
+			return Err(SharedImageMetadataError::UnsupportedVersion(version))
+		}
+
+		let format = vk::Format::from_raw(next() as i32);
+		let image_type = vk::ImageType::from_raw(next() as i32);
+		let mut dimension = || NonZeroU32::new(next()).ok_or(SharedImageMetadataError::ZeroSize);
+		let width = dimension()?;
+		let height = dimension()?;
+		let depth = dimension()?;
+		let array_layers = dimension()?;
+		let mipmap_levels = dimension()?;
+		// SAFETY: the size is only used to recreate an image with the same parameters as the exported one
+		let size = unsafe {
+			ImageSize::new(
+				image_type,
+				width,
+				height,
+				depth,
+				array_layers,
+				mipmap_levels
+			)
+		};
+
+		let samples = vk::SampleCountFlags::from_raw(next());
+		let flags = vk::ImageCreateFlags::from_raw(next());
+		let tiling = vk::ImageTiling::from_raw(next() as i32);
+		let usage = vk::ImageUsageFlags::from_raw(next());
+		let memory_handle_type = vk::ExternalMemoryHandleTypeFlags::from_raw(next());
+		let semaphore_handle_type = vk::ExternalSemaphoreHandleTypeFlags::from_raw(next());
+
+		let offset = 8 + Self::WORD_COUNT * 4;
+		let allocation_size = NonZeroU64::new(u64::from_le_bytes(
+			bytes[offset .. offset + 8].try_into().unwrap()
+		))
+		.ok_or(SharedImageMetadataError::ZeroSize)?;
+		let device_uuid = bytes[offset + 8 ..].try_into().unwrap();
+
+		Ok(SharedImageMetadata {
+			format,
+			size,
+			samples,
+			flags,
+			tiling,
+			usage,
+			memory_handle_type,
+			semaphore_handle_type,
+			allocation_size,
+			device_uuid
+		})
+	}
+}
+
+/// File descriptors of an exported image, to be sent to the importing process.
+#[derive(Debug)]
+pub struct SharedImageHandles {
+	pub memory_fd: OwnedFd,
+	pub semaphore_fd: OwnedFd
+}
+
+/// Image shared between processes together with the semaphore used to synchronize access to it.
+#[derive(Debug)]
+pub struct SharedImage {
+	pub image: Vrc<Image>,
+	pub semaphore: BinarySemaphore,
+	pub metadata: SharedImageMetadata
+}
+
+/// Creates an image on `device` whose memory and semaphore can be imported by another process using `import_shared_image`.
+///
+/// `memory_flags` are the memory properties required for the image memory.
+pub fn export_shared_image(
+	device: Vrc<Device>,
+	format: vk::Format,
+	size_info: ImageSizeInfo,
+	tiling_and_layout: ImageTilingAndLayout,
+	usage: vk::ImageUsageFlags,
+	memory_handle_type: vk::ExternalMemoryHandleTypeFlags,
+	semaphore_handle_type: vk::ExternalSemaphoreHandleTypeFlags,
+	memory_flags: vk::MemoryPropertyFlags,
+	host_memory_allocator: HostMemoryAllocator
+) -> Result<(SharedImage, SharedImageHandles), SharedImageError> {
+	let (size, samples, flags) = size_info.into();
+	let (tiling, _) = tiling_and_layout.into();
+
+	let export_allocator = ExportMemoryAllocator::new(device.clone(), memory_handle_type);
+	let image = create_image(
+		device.clone(),
+		format,
+		size_info,
+		tiling_and_layout,
+		usage,
+		memory_handle_type,
+		ImageAllocatorParams::Some { allocator: &export_allocator, requirements: memory_flags },
+		host_memory_allocator.clone()
+	)?;
+
+	let memory = image.memory().expect("exported image must have memory");
+	let memory_fd = export_memory_fd(memory, memory_handle_type)?;
+
+	let semaphore = exportable_semaphore(device.clone(), semaphore_handle_type, host_memory_allocator)
+		.map_err(ExternalSemaphoreError::from)?;
+	let semaphore_fd = export_semaphore_fd(&semaphore, semaphore_handle_type)?;
+
+	let metadata = SharedImageMetadata {
+		format,
+		size,
+		samples,
+		flags,
+		tiling,
+		usage,
+		memory_handle_type,
+		semaphore_handle_type,
+		allocation_size: memory.size(),
+		device_uuid: device.physical_device().id_properties().device_uuid
+	};
+
+	Ok((
+		SharedImage { image, semaphore, metadata },
+		SharedImageHandles { memory_fd, semaphore_fd }
+	))
+}
+
+/// Imports an image exported by `export_shared_image` in another process into `device`.
+///
+/// `metadata` and `handles` must come from the same call to `export_shared_image`.
+/// `memory_flags` are the memory properties required for the image memory.
+pub fn import_shared_image(
+	device: Vrc<Device>,
+	metadata: SharedImageMetadata,
+	handles: SharedImageHandles,
+	memory_flags: vk::MemoryPropertyFlags,
+	host_memory_allocator: HostMemoryAllocator
+) -> Result<SharedImage, SharedImageError> {
+	if device.physical_device().id_properties().device_uuid != metadata.device_uuid {
+		return Err(SharedImageError::DeviceMismatch)
+	}
+
+	// SAFETY: the parameters are the same as the ones the exported image was successfully created with
+	let (size_info, tiling_and_layout) = unsafe {
+		(
+			ImageSizeInfo::Custom(metadata.size, metadata.samples, metadata.flags),
+			ImageTilingAndLayout::Custom(metadata.tiling, vk::ImageLayout::UNDEFINED)
+		)
+	};
+
+	let import_allocator = ImportFdMemoryAllocator::new(
+		device.clone(),
+		metadata.memory_handle_type,
+		handles.memory_fd,
+		metadata.allocation_size
+	);
+	let image = create_image(
+		device.clone(),
+		metadata.format,
+		size_info,
+		tiling_and_layout,
+		metadata.usage,
+		metadata.memory_handle_type,
+		ImageAllocatorParams::Some { allocator: &import_allocator, requirements: memory_flags },
+		host_memory_allocator.clone()
+	)?;
+
+	let semaphore = import_semaphore_fd(
+		device,
+		metadata.semaphore_handle_type,
+		handles.semaphore_fd,
+		host_memory_allocator
+	)?;
+
+	Ok(SharedImage { image, semaphore, metadata })
+}
+
+#[cfg(test)]
+mod test {
+	use std::num::{NonZeroU32, NonZeroU64};
+
+	use ash::vk;
+
+	use super::{SharedImageMetadata, SharedImageMetadataError};
+	use crate::resource::image::params::{ImageSize, MipmapLevels};
+
+	#[test]
+	fn metadata_round_trip() {
+		let metadata = SharedImageMetadata {
+			format: vk::Format::B8G8R8A8_UNORM,
+			size: ImageSize::new_2d(
+				NonZeroU32::new(1920).unwrap(),
+				NonZeroU32::new(1080).unwrap(),
+				NonZeroU32::new(1).unwrap(),
+				MipmapLevels::One()
+			)
+			.into(),
+			samples: vk::SampleCountFlags::TYPE_1,
+			flags: vk::ImageCreateFlags::empty(),
+			tiling: vk::ImageTiling::OPTIMAL,
+			usage: vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+			memory_handle_type: vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+			semaphore_handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+			allocation_size: NonZeroU64::new(1920 * 1080 * 4).unwrap(),
+			device_uuid: [7; vk::UUID_SIZE]
+		};
+
+		let bytes = metadata.to_bytes();
+		assert_eq!(SharedImageMetadata::from_bytes(&bytes), Ok(metadata));
+
+		assert_eq!(
+			SharedImageMetadata::from_bytes(&bytes[1 ..]),
+			Err(SharedImageMetadataError::InvalidLength(SharedImageMetadata::SERIALIZED_SIZE - 1))
+		);
+
+		let mut corrupted = bytes;
+		corrupted[0] = 0;
+		assert_eq!(
+			SharedImageMetadata::from_bytes(&corrupted),
+			Err(SharedImageMetadataError::InvalidMagic)
+		);
+	}
+}
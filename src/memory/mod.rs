@@ -1,2 +1,7 @@
 pub mod device;
+#[cfg(all(unix, feature = "vulkan1_1"))]
+pub mod external;
 pub mod host;
+pub mod ring;
+pub mod staging;
+pub mod stats;
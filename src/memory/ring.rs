@@ -0,0 +1,266 @@
+//! Ring buffer sub-allocation of per-frame uniform data.
+//!
+//! The buffer is split into equally sized frame regions. Data for a frame is allocated linearly from its region
+//! and once the frame is submitted, the region is guarded by the fence of the submission. When the ring comes
+//! back to the region, `begin_frame` waits for that fence so that data still read by the device is never overwritten.
+
+use std::{
+	fmt,
+	num::{NonZeroU32, NonZeroU64}
+};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+	memory::device::{MapError, MappingAccessResult},
+	prelude::{Buffer, DescriptorBufferInfo, Fence, Vrc, Vutex},
+	sync::fence::error::FenceError,
+	util::WaitTimeout
+};
+
+#[derive(Error, Debug)]
+pub enum UniformRingBufferError {
+	#[error("Buffer must be created with UNIFORM_BUFFER usage")]
+	NotUniformBuffer,
+	#[error("Buffer must have bound memory")]
+	NoMemory,
+	#[error("Could not map buffer memory")]
+	Map(#[from] MapError),
+	#[error("Buffer is too small to be split into {0} frame regions")]
+	TooManyFrames(NonZeroU32)
+}
+
+#[derive(Error, Debug)]
+pub enum UniformRingPushError {
+	#[error("Data of size {0} does not fit into a frame region")]
+	TooLarge(vk::DeviceSize),
+	#[error("Frame region is full, could not allocate {0} bytes")]
+	FrameFull(vk::DeviceSize),
+	#[error("Alignment {0} is not a power of two")]
+	InvalidAlignment(vk::DeviceSize),
+	#[error("Could not map buffer memory")]
+	Map(#[from] MapError)
+}
+
+/// Sub-allocates per-frame uniform data from a persistently mapped host-visible buffer.
+///
+/// Each allocation returns a dynamic offset aligned to at least `minUniformBufferOffsetAlignment` that can be passed
+/// to the `dynamic_offsets` parameter of `bind_descriptor_sets` for a `UNIFORM_BUFFER_DYNAMIC` descriptor.
+///
+/// A frame is started with `begin_frame`, which moves to the next frame region and waits for it to be released by the device,
+/// and finished with `finish_frame`, which associates the region with the fence of the submission using its data.
+/// A ring buffer created with `new` has a single frame region, which is rewound using `reset`.
+pub struct UniformRingBuffer {
+	buffer: Vrc<Buffer>,
+	alignment: vk::DeviceSize,
+	frame_size: vk::DeviceSize,
+
+	fences: Vec<Option<Vrc<Fence>>>,
+	current_frame: usize,
+	head: Vutex<vk::DeviceSize>
+}
+impl UniformRingBuffer {
+	/// Creates a new ring buffer over `buffer` with a single frame region.
+	///
+	/// `buffer` must have host visible and host coherent memory bound. The memory is mapped immediately and stays mapped.
+	pub fn new(buffer: Vrc<Buffer>) -> Result<Self, UniformRingBufferError> {
+		Self::with_frames(buffer, NonZeroU32::new(1).unwrap())
+	}
+
+	/// Creates a new ring buffer over `buffer` split into `frame_count` frame regions.
+	///
+	/// The size of each frame region is the buffer size divided by `frame_count` rounded down to the offset alignment.
+	/// See `new` for the requirements on `buffer`.
+	pub fn with_frames(buffer: Vrc<Buffer>, frame_count: NonZeroU32) -> Result<Self, UniformRingBufferError> {
+		if !buffer.usage().contains(vk::BufferUsageFlags::UNIFORM_BUFFER) {
+			return Err(UniformRingBufferError::NotUniformBuffer)
+		}
+
+		buffer
+			.memory()
+			.ok_or(UniformRingBufferError::NoMemory)?
+			.map_memory_with(|_| MappingAccessResult::Continue)?;
+
+		let alignment = buffer.device().physical_properties().limits.min_uniform_buffer_offset_alignment.max(1);
+		let frame_size = buffer.size().get() / frame_count.get() as vk::DeviceSize / alignment * alignment;
+		if frame_size == 0 {
+			return Err(UniformRingBufferError::TooManyFrames(frame_count))
+		}
+
+		Ok(UniformRingBuffer {
+			buffer,
+			alignment,
+			frame_size,
+
+			fences: vec![None; frame_count.get() as usize],
+			current_frame: 0,
+			head: Vutex::new(0)
+		})
+	}
+
+	/// Moves to the next frame region, waiting for the fence of its previous frame with `timeout`.
+	///
+	/// Returns `false` if the timeout expired, in which case the current frame is not changed.
+	pub fn begin_frame(&mut self, timeout: WaitTimeout) -> Result<bool, FenceError> {
+		let next_frame = (self.current_frame + 1) % self.fences.len();
+
+		if let Some(ref fence) = self.fences[next_frame] {
+			if !fence.wait(timeout)? {
+				return Ok(false)
+			}
+		}
+		self.fences[next_frame] = None;
+
+		self.current_frame = next_frame;
+		*self.head.lock().expect("vutex poisoned") = 0;
+
+		Ok(true)
+	}
+
+	/// Guards the current frame region with `fence`.
+	///
+	/// `fence` must be signaled by the submission that reads the data allocated in this frame.
+	pub fn finish_frame(&mut self, fence: Vrc<Fence>) {
+		self.fences[self.current_frame] = Some(fence);
+	}
+
+	/// Reserves `size` bytes aligned to `align` in the current frame region and returns their offset in the buffer.
+	fn reserve(&self, size: vk::DeviceSize, align: vk::DeviceSize) -> Result<vk::DeviceSize, UniformRingPushError> {
+		if !align.is_power_of_two() {
+			return Err(UniformRingPushError::InvalidAlignment(align))
+		}
+		if size > self.frame_size {
+			return Err(UniformRingPushError::TooLarge(size))
+		}
+
+		let mut head = self.head.lock().expect("vutex poisoned");
+
+		let align = align.max(self.alignment);
+		let frame_offset = crate::util::align_up(*head as usize, align as usize) as vk::DeviceSize;
+		if frame_offset + size > self.frame_size {
+			return Err(UniformRingPushError::FrameFull(size))
+		}
+		*head = frame_offset + size;
+
+		Ok(self.current_frame as vk::DeviceSize * self.frame_size + frame_offset)
+	}
+
+	/// Allocates `size` bytes aligned to `align` in the current frame region.
+	///
+	/// Returns the dynamic offset of the allocation and its mapped memory. The alignment is raised to the offset alignment if lower.
+	///
+	/// ### Safety
+	///
+	/// The memory of the buffer must not be unmapped while the returned slice is alive.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if a `Vutex` is poisoned.
+	pub unsafe fn allocate(&mut self, size: vk::DeviceSize, align: vk::DeviceSize) -> Result<(u32, &mut [u8]), UniformRingPushError> {
+		let offset = self.reserve(size, align)?;
+
+		let mut mapped = std::ptr::null_mut();
+		// Memory presence is checked in constructor
+		self.buffer.memory().unwrap().map_memory_with(|mut access| {
+			mapped = access.bytes_mut().as_mut_ptr();
+			MappingAccessResult::Continue
+		})?;
+
+		// The range is inside the current frame region, which is not used by the device, and is not handed out twice
+		let bytes = std::slice::from_raw_parts_mut(mapped.add(offset as usize), size as usize);
+
+		Ok((offset as u32, bytes))
+	}
+
+	/// Pushes `data` into the current frame region and returns its dynamic offset.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if a `Vutex` is poisoned.
+	pub fn push<T: Copy>(&self, data: &T) -> Result<u32, UniformRingPushError> {
+		self.push_slice(std::slice::from_ref(data))
+	}
+
+	/// Pushes `data` into the current frame region and returns its dynamic offset.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if a `Vutex` is poisoned.
+	pub fn push_slice<T: Copy>(&self, data: &[T]) -> Result<u32, UniformRingPushError> {
+		let size = std::mem::size_of_val(data);
+		let offset = self.reserve(
+			size as vk::DeviceSize,
+			std::mem::align_of::<T>() as vk::DeviceSize
+		)? as usize;
+
+		// Memory presence is checked in constructor
+		self.buffer.memory().unwrap().map_memory_with(|mut access| {
+			// SAFETY: `T` is `Copy` and the reserved range is exactly `size` bytes large
+			let source = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, size) };
+			access.bytes_mut()[offset .. offset + size].copy_from_slice(source);
+			MappingAccessResult::Continue
+		})?;
+
+		Ok(offset as u32)
+	}
+
+	/// Rewinds the current frame region to its beginning.
+	///
+	/// Data pushed into the region since the last `begin_frame` must no longer be in use by the device.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn reset(&self) {
+		*self.head.lock().expect("vutex poisoned") = 0;
+	}
+
+	/// Returns descriptor buffer info for a `UNIFORM_BUFFER_DYNAMIC` descriptor with given `range`.
+	///
+	/// `range` should be the size of the largest structure allocated for this descriptor.
+	pub fn descriptor_info(&self, range: NonZeroU64) -> DescriptorBufferInfo<'_> {
+		DescriptorBufferInfo::new(&self.buffer, 0, range)
+	}
+
+	/// Returns the number of bytes still available in the current frame region, ignoring alignment.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn remaining(&self) -> vk::DeviceSize {
+		self.frame_size - *self.head.lock().expect("vutex poisoned")
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+
+	pub const fn alignment(&self) -> vk::DeviceSize {
+		self.alignment
+	}
+
+	pub const fn frame_size(&self) -> vk::DeviceSize {
+		self.frame_size
+	}
+
+	pub fn frame_count(&self) -> usize {
+		self.fences.len()
+	}
+
+	pub const fn current_frame(&self) -> usize {
+		self.current_frame
+	}
+}
+impl fmt::Debug for UniformRingBuffer {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("UniformRingBuffer")
+			.field("buffer", &self.buffer)
+			.field("alignment", &self.alignment)
+			.field("frame_size", &self.frame_size)
+			.field("fences", &self.fences)
+			.field("current_frame", &self.current_frame)
+			.field("head", &self.head)
+			.finish()
+	}
+}
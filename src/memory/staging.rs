@@ -0,0 +1,659 @@
+//! Uploading data into device-local resources through temporary staging buffers.
+//!
+//! Each upload creates a host-visible staging buffer, records the copy and barriers into a transient command buffer
+//! and submits it. The returned `PendingUpload` keeps the staging resources alive until the copy is finished.
+//!
+//! On UMA devices with `UmaPolicy::MapDirectly`, buffer uploads into host visible memory skip staging and write into the destination
+//! memory directly, flushing it if it is not host coherent. Such writes are not synchronized with the device, so the destination
+//! must not be in use by any pending submission.
+
+use std::{
+	fmt,
+	num::{NonZeroU32, NonZeroU64},
+	ops::Deref
+};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+	command::error::{CommandBufferError, CommandPoolError},
+	memory::device::{allocator::BufferMemoryAllocator, FlushError, MapError, MappingAccessResult, UmaPolicy},
+	prelude::{
+		Buffer,
+		BufferAllocatorParams,
+		BufferBufferCopy,
+		BufferImageCopy,
+		BufferMemoryBarrier,
+		CommandBuffer,
+		CommandBufferBeginInfo,
+		CommandBufferRecordingLockOutsideRenderPass,
+		CommandPool,
+		Fence,
+		FencePool,
+		HostMemoryAllocator,
+		Image,
+		ImageLayoutDestination,
+		ImageLayoutFinal,
+		ImageMemoryBarrier,
+		ImageSubresourceLayers,
+		ImageSubresourceRange,
+		MemoryBarrier,
+		Queue,
+		SharingMode,
+		Vrc
+	},
+	queue::error::QueueSubmitError,
+	resource::{
+		buffer::error::BufferError,
+		image::{
+			error::{ImageCopySourceError, ImageUseError},
+			params::{validate_packed_copy_source, ImageSize}
+		}
+	},
+	sync::fence::error::{FenceError, FenceStatusError}
+};
+
+#[derive(Error, Debug)]
+pub enum StagingUploadError<AllocError: std::error::Error + 'static> {
+	#[error("Source data must not be empty")]
+	SourceEmpty,
+	#[error("Range of {size} bytes at offset {offset} is out of bounds of buffer of size {buffer_size}")]
+	OutOfBounds { offset: vk::DeviceSize, size: vk::DeviceSize, buffer_size: vk::DeviceSize },
+	#[error("Region at {offset:?} of extent {extent:?} in mipmap level {mipmap_level} is out of bounds of the image")]
+	ImageRegionOutOfBounds { mipmap_level: u32, offset: vk::Offset3D, extent: vk::Extent3D },
+	#[error("Queue family {queue} does not match the command pool queue family {pool}")]
+	QueueFamilyMismatch { queue: u32, pool: u32 },
+	#[error("Buffer was not created with the {0:?} usage")]
+	BufferUsageMissing(vk::BufferUsageFlags),
+	#[error("Destination image cannot be uploaded to")]
+	Image(#[from] ImageUseError),
+	#[error("Source data does not cover the image region")]
+	ImageSource(#[from] ImageCopySourceError),
+	#[error("Could not create staging buffer")]
+	Buffer(#[from] BufferError<AllocError>),
+	#[error("Staging buffer has no bound memory")]
+	BufferNoMemory,
+	#[error("Could not map staging buffer memory")]
+	Map(#[from] MapError),
+	#[error("Could not flush directly written memory")]
+	Flush(#[from] FlushError),
+	#[error("Could not record command buffer")]
+	CommandBuffer(#[from] CommandBufferError),
+	#[error("Could not create fence")]
+	Fence(#[from] FenceError),
+	#[error("Could not submit upload")]
+	QueueSubmit(#[from] QueueSubmitError)
+}
+
+struct SubmittedUpload {
+	fence: Vrc<Fence>,
+	command_buffer: Vrc<CommandBuffer>,
+	staging_buffer: Vrc<Buffer>
+}
+
+/// Upload that was submitted but may not have finished yet.
+///
+/// Dropping a pending upload blocks until the upload is finished.
+pub struct PendingUpload {
+	/// `None` if the data was written directly without staging.
+	submitted: Option<SubmittedUpload>
+}
+impl PendingUpload {
+	/// Returns whether the upload has finished.
+	pub fn is_complete(&self) -> Result<bool, FenceStatusError> {
+		match self.submitted {
+			None => Ok(true),
+			Some(ref submitted) => submitted.fence.status()
+		}
+	}
+
+	/// Blocks until the upload is finished.
+	pub fn wait(self) -> Result<(), FenceError> {
+		match self.submitted {
+			None => Ok(()),
+			Some(ref submitted) => submitted.fence.wait(Default::default()).map(|_| ())
+		}
+	}
+
+	/// Returns the fence that is signaled when the upload is finished.
+	///
+	/// Returns `None` if the upload was written directly and is already finished.
+	pub fn fence(&self) -> Option<&Vrc<Fence>> {
+		self.submitted.as_ref().map(|s| &s.fence)
+	}
+
+	/// Returns whether the upload skipped staging and was written directly.
+	pub const fn is_direct(&self) -> bool {
+		self.submitted.is_none()
+	}
+}
+impl Drop for PendingUpload {
+	fn drop(&mut self) {
+		// The staging buffer and command buffer must outlive the execution
+		if let Some(ref submitted) = self.submitted {
+			if let Err(err) = submitted.fence.wait(Default::default()) {
+				log::error!("Could not wait for pending upload: {}", err);
+			}
+		}
+	}
+}
+impl fmt::Debug for PendingUpload {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut d = f.debug_struct("PendingUpload");
+		if let Some(ref submitted) = self.submitted {
+			d.field("fence", &submitted.fence)
+				.field("command_buffer", &submitted.command_buffer)
+				.field("staging_buffer", &submitted.staging_buffer);
+		}
+
+		d.finish()
+	}
+}
+
+/// Uploads data into buffers and images using temporary host-visible staging buffers.
+///
+/// `requirements` passed to the constructor must select host visible and host coherent memory.
+pub struct StagingUploader<A: BufferMemoryAllocator>
+where
+	A::AllocationRequirements: Clone
+{
+	command_pool: Vrc<CommandPool>,
+	fence_pool: FencePool,
+
+	allocator: A,
+	requirements: A::AllocationRequirements,
+	map_directly: bool,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl<A: BufferMemoryAllocator> StagingUploader<A>
+where
+	A::AllocationRequirements: Clone
+{
+	/// Creates a new uploader that submits to queues from the queue family of `queue`.
+	pub fn new(
+		queue: &Queue,
+		allocator: A,
+		requirements: A::AllocationRequirements,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, CommandPoolError> {
+		Self::with_uma_policy(
+			queue,
+			allocator,
+			requirements,
+			UmaPolicy::default(),
+			host_memory_allocator
+		)
+	}
+
+	/// Creates a new uploader with explicit `uma_policy`.
+	pub fn with_uma_policy(
+		queue: &Queue,
+		allocator: A,
+		requirements: A::AllocationRequirements,
+		uma_policy: UmaPolicy,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, CommandPoolError> {
+		let map_directly = uma_policy.map_directly(&queue.device().physical_device().memory_properties());
+		let command_pool = CommandPool::new(
+			queue,
+			vk::CommandPoolCreateFlags::TRANSIENT,
+			host_memory_allocator.clone()
+		)?;
+
+		let fence_pool = FencePool::new(queue.device().clone(), host_memory_allocator.clone());
+
+		Ok(StagingUploader { command_pool, fence_pool, allocator, requirements, map_directly, host_memory_allocator })
+	}
+
+	/// Checks that `queue` is from the queue family of the command pool.
+	fn validate_queue(&self, queue: &Queue) -> Result<(), StagingUploadError<A::Error>> {
+		if queue.queue_family_index() != self.command_pool.queue_family_index() {
+			return Err(StagingUploadError::QueueFamilyMismatch {
+				queue: queue.queue_family_index(),
+				pool: self.command_pool.queue_family_index()
+			})
+		}
+
+		Ok(())
+	}
+
+	fn create_staging(&self, queue: &Queue, src: &[u8]) -> Result<Vrc<Buffer>, StagingUploadError<A::Error>> {
+		let size = NonZeroU64::new(src.len() as u64).ok_or(StagingUploadError::SourceEmpty)?;
+
+		let staging_buffer = Buffer::new(
+			queue.device().clone(),
+			size,
+			vk::BufferUsageFlags::TRANSFER_SRC,
+			SharingMode::from(queue),
+			BufferAllocatorParams::Some { allocator: &self.allocator, requirements: self.requirements.clone() },
+			self.host_memory_allocator.clone()
+		)?;
+
+		staging_buffer
+			.memory()
+			.ok_or(StagingUploadError::BufferNoMemory)?
+			.map_memory_with(|mut access| {
+				access.write_slice(src, 0, Default::default());
+				MappingAccessResult::Unmap
+			})?;
+
+		Ok(staging_buffer)
+	}
+
+	fn submit(
+		&self,
+		queue: &Queue,
+		staging_buffer: Vrc<Buffer>,
+		record: impl FnOnce(&CommandBufferRecordingLockOutsideRenderPass, &Buffer)
+	) -> Result<PendingUpload, StagingUploadError<A::Error>> {
+		let [command_buffer] = CommandBuffer::new(self.command_pool.clone(), false)?;
+		{
+			let recording = command_buffer.begin_recording(CommandBufferBeginInfo::OneTime)?;
+			record(&recording, &staging_buffer);
+			recording.end()?;
+		}
+
+		let fence = self.fence_pool.rent()?;
+		if let Err(err) = queue.submit(
+			[],
+			[],
+			[command_buffer.deref()],
+			[],
+			Some(&fence)
+		) {
+			// The fence was not submitted and only referenced here
+			unsafe { self.fence_pool.return_unsubmitted(fence) };
+			return Err(err.into())
+		}
+
+		Ok(PendingUpload { submitted: Some(SubmittedUpload { fence, command_buffer, staging_buffer }) })
+	}
+
+	/// Uploads `src` into `dst` at `offset`.
+	///
+	/// `queue` must be from the queue family the uploader was created with and `dst` must have the `TRANSFER_DST` usage.
+	/// The copy waits for all previous commands on `queue` and is followed by a memory barrier making the data visible
+	/// to all subsequent commands on `queue`.
+	///
+	/// If direct mapping is enabled and `dst` has bound host visible memory, the data is written into it directly, the memory
+	/// is flushed if it is not host coherent and the returned upload is already complete. Host writes are made visible to the
+	/// device by the next queue submission. The direct write is not synchronized with the device, so `dst` must not be
+	/// in use by any pending submission in that case.
+	pub fn upload_to_buffer(&self, queue: &Queue, src: &[u8], dst: &Buffer, offset: vk::DeviceSize) -> Result<PendingUpload, StagingUploadError<A::Error>> {
+		log_trace_common!(
+			"Uploading to buffer:",
+			queue,
+			src.len(),
+			dst,
+			offset
+		);
+
+		self.validate_queue(queue)?;
+		if src.is_empty() {
+			return Err(StagingUploadError::SourceEmpty)
+		}
+		check_buffer_usage(dst, vk::BufferUsageFlags::TRANSFER_DST)?;
+		check_range(offset, src.len() as vk::DeviceSize, dst.size().get())?;
+
+		if self.map_directly {
+			let direct = dst
+				.memory()
+				.and_then(|memory| Some((memory, memory.memory_property_flags()?)))
+				.filter(|(_, flags)| flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE));
+
+			if let Some((memory, flags)) = direct {
+				let mut flush_result = Ok(());
+
+				// keep persistent mappings intact
+				let was_mapped = memory.is_mapped();
+				memory.map_memory_with(|mut access| {
+					let start = offset as usize;
+					access.bytes_mut()[start .. start + src.len()].copy_from_slice(src);
+					if !flags.contains(vk::MemoryPropertyFlags::HOST_COHERENT) {
+						flush_result = access.flush();
+					}
+
+					if was_mapped {
+						MappingAccessResult::Continue
+					} else {
+						MappingAccessResult::Unmap
+					}
+				})?;
+				flush_result?;
+
+				return Ok(PendingUpload { submitted: None })
+			}
+		}
+
+		let staging_buffer = self.create_staging(queue, src)?;
+		self.submit(queue, staging_buffer, |recording, staging| {
+			// previous reads and writes of `dst`, such as vertex fetches of earlier frames, must finish before it is overwritten
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::ALL_COMMANDS,
+				vk::PipelineStageFlags::TRANSFER,
+				[MemoryBarrier::new(
+					vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
+					vk::AccessFlags::TRANSFER_WRITE
+				)],
+				[] as [BufferMemoryBarrier; 0],
+				[] as [ImageMemoryBarrier; 0]
+			);
+			recording.copy_buffer_to_buffer(
+				staging,
+				dst,
+				[BufferBufferCopy::new(0, offset, staging.size())]
+			);
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::ALL_COMMANDS,
+				[MemoryBarrier::new(
+					vk::AccessFlags::TRANSFER_WRITE,
+					vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE
+				)],
+				[] as [BufferMemoryBarrier; 0],
+				[] as [ImageMemoryBarrier; 0]
+			);
+		})
+	}
+
+	/// Uploads tightly packed texel data `src` into the `offset` and `extent` region of `dst`.
+	///
+	/// Only the base mipmap level of `subresource_range` is written, the whole range is transitioned from `old_layout`
+	/// to `TRANSFER_DST_OPTIMAL` and then to `final_layout`, making it available to `final_stages` and `final_access`.
+	/// The first transition waits for previous `old_access` accesses in `old_stages`, pass `ALL_COMMANDS` and `MEMORY_WRITE`
+	/// if they are not known.
+	///
+	/// `queue` must be from the queue family the uploader was created with and `dst` must have the `TRANSFER_DST` usage.
+	/// `src` must contain at least the bytes of the region in all layers of `subresource_range`, see `TexelBlock::packed_size`.
+	pub fn upload_to_image(
+		&self,
+		queue: &Queue,
+		src: &[u8],
+		dst: &Image,
+		subresource_range: ImageSubresourceRange,
+		offset: vk::Offset3D,
+		extent: vk::Extent3D,
+		old_layout: vk::ImageLayout,
+		old_stages: vk::PipelineStageFlags,
+		old_access: vk::AccessFlags,
+		final_layout: ImageLayoutFinal,
+		final_stages: vk::PipelineStageFlags,
+		final_access: vk::AccessFlags
+	) -> Result<PendingUpload, StagingUploadError<A::Error>> {
+		log_trace_common!(
+			"Uploading to image:",
+			queue,
+			src.len(),
+			dst,
+			subresource_range,
+			offset,
+			extent,
+			old_layout,
+			final_layout
+		);
+
+		self.validate_queue(queue)?;
+		dst.validate_usage(vk::ImageUsageFlags::TRANSFER_DST)?;
+		check_image_region(dst.size(), subresource_range, offset, extent)?;
+		validate_packed_copy_source(
+			src.len() as u64,
+			dst.format(),
+			subresource_range.aspect_mask,
+			extent,
+			subresource_range.array_layers.get()
+		)?;
+
+		let staging_buffer = self.create_staging(queue, src)?;
+		self.submit(queue, staging_buffer, |recording, staging| {
+			recording.pipeline_barrier(
+				old_stages,
+				vk::PipelineStageFlags::TRANSFER,
+				[] as [MemoryBarrier; 0],
+				[] as [BufferMemoryBarrier; 0],
+				[ImageMemoryBarrier::new(
+					dst,
+					subresource_range,
+					old_layout,
+					ImageLayoutFinal::TRANSFER_DST_OPTIMAL,
+					old_access,
+					vk::AccessFlags::TRANSFER_WRITE
+				)]
+			);
+			recording.copy_buffer_to_image(
+				staging,
+				dst,
+				ImageLayoutDestination::TRANSFER_DST_OPTIMAL,
+				[BufferImageCopy::new(
+					0,
+					None,
+					ImageSubresourceLayers::new(
+						subresource_range.aspect_mask,
+						subresource_range.mipmap_levels_base,
+						subresource_range.array_layers_base,
+						subresource_range.array_layers
+					),
+					offset,
+					extent
+				)]
+			);
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::TRANSFER,
+				final_stages,
+				[] as [MemoryBarrier; 0],
+				[] as [BufferMemoryBarrier; 0],
+				[ImageMemoryBarrier::new(
+					dst,
+					subresource_range,
+					vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+					final_layout,
+					vk::AccessFlags::TRANSFER_WRITE,
+					final_access
+				)]
+			);
+		})
+	}
+
+	/// Copies `region` from `src` into `dst` on the device, keeping `src` alive until the copy is finished.
+	///
+	/// `queue` must be from the queue family the uploader was created with, `src` must have the `TRANSFER_SRC` usage
+	/// and `dst` must have the `TRANSFER_DST` usage.
+	/// The copy waits for all previous commands on `queue` and is followed by a memory barrier making the data visible
+	/// to all subsequent commands on `queue`.
+	pub fn copy_buffer(&self, queue: &Queue, src: Vrc<Buffer>, dst: &Buffer, region: BufferBufferCopy) -> Result<PendingUpload, StagingUploadError<A::Error>> {
+		log_trace_common!(
+			"Copying buffer:",
+			queue,
+			src,
+			dst,
+			region
+		);
+
+		self.validate_queue(queue)?;
+		check_buffer_usage(&src, vk::BufferUsageFlags::TRANSFER_SRC)?;
+		check_buffer_usage(dst, vk::BufferUsageFlags::TRANSFER_DST)?;
+		check_range(region.src_offset, region.size, src.size().get())?;
+		check_range(region.dst_offset, region.size, dst.size().get())?;
+
+		self.submit(queue, src, |recording, src| {
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::ALL_COMMANDS,
+				vk::PipelineStageFlags::TRANSFER,
+				[MemoryBarrier::new(
+					vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
+					vk::AccessFlags::TRANSFER_READ | vk::AccessFlags::TRANSFER_WRITE
+				)],
+				[] as [BufferMemoryBarrier; 0],
+				[] as [ImageMemoryBarrier; 0]
+			);
+			recording.copy_buffer_to_buffer(src, dst, [region]);
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::ALL_COMMANDS,
+				[MemoryBarrier::new(
+					vk::AccessFlags::TRANSFER_WRITE,
+					vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE
+				)],
+				[] as [BufferMemoryBarrier; 0],
+				[] as [ImageMemoryBarrier; 0]
+			);
+		})
+	}
+
+	pub const fn command_pool(&self) -> &Vrc<CommandPool> {
+		&self.command_pool
+	}
+
+	/// Returns whether buffer uploads are written directly without staging.
+	pub const fn maps_directly(&self) -> bool {
+		self.map_directly
+	}
+}
+impl<A: BufferMemoryAllocator> fmt::Debug for StagingUploader<A>
+where
+	A::AllocationRequirements: Clone
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("StagingUploader")
+			.field("command_pool", &self.command_pool)
+			.field("fence_pool", &self.fence_pool)
+			.field("allocator", &self.allocator)
+			.field("requirements", &self.requirements)
+			.field("map_directly", &self.map_directly)
+			.field("host_memory_allocator", &self.host_memory_allocator)
+			.finish()
+	}
+}
+
+/// Checks that `size` bytes at `offset` fit into a buffer of `buffer_size` bytes.
+fn check_range<AllocError: std::error::Error + 'static>(
+	offset: vk::DeviceSize,
+	size: vk::DeviceSize,
+	buffer_size: vk::DeviceSize
+) -> Result<(), StagingUploadError<AllocError>> {
+	match offset.checked_add(size) {
+		Some(end) if end <= buffer_size => Ok(()),
+		_ => Err(StagingUploadError::OutOfBounds { offset, size, buffer_size })
+	}
+}
+
+/// Checks that `buffer` was created with all of `usage`.
+fn check_buffer_usage<AllocError: std::error::Error + 'static>(
+	buffer: &Buffer,
+	usage: vk::BufferUsageFlags
+) -> Result<(), StagingUploadError<AllocError>> {
+	if !buffer.usage().contains(usage) {
+		return Err(StagingUploadError::BufferUsageMissing(usage))
+	}
+
+	Ok(())
+}
+
+/// Checks that the `offset` and `extent` region of the base mipmap level of `subresource_range` fits into an image of `size`.
+fn check_image_region<AllocError: std::error::Error + 'static>(
+	size: ImageSize,
+	subresource_range: ImageSubresourceRange,
+	offset: vk::Offset3D,
+	extent: vk::Extent3D
+) -> Result<(), StagingUploadError<AllocError>> {
+	let mipmap_level = subresource_range.mipmap_levels_base;
+
+	let layers_end = subresource_range
+		.array_layers_base
+		.checked_add(subresource_range.array_layers.get());
+	let subresources_fit = mipmap_level < size.mipmap_levels().get() && matches!(layers_end, Some(end) if end <= size.array_layers().get());
+
+	let fits = |offset: i32, extent: u32, dimension: NonZeroU32| {
+		let level_dimension = dimension.get().checked_shr(mipmap_level).unwrap_or(0).max(1);
+
+		offset >= 0 && extent != 0 && matches!((offset as u32).checked_add(extent), Some(end) if end <= level_dimension)
+	};
+	let region_fits = fits(offset.x, extent.width, size.width())
+		&& fits(offset.y, extent.height, size.height())
+		&& fits(offset.z, extent.depth, size.depth());
+
+	if !subresources_fit || !region_fits {
+		return Err(StagingUploadError::ImageRegionOutOfBounds { mipmap_level, offset, extent })
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use std::num::NonZeroU32;
+
+	use super::{check_image_region, check_range, StagingUploadError};
+	use crate::{
+		memory::device::UmaPolicy,
+		physical_device::enumerate::PhysicalDeviceMemoryProperties,
+		resource::image::params::{ImageSize, ImageSubresourceRange, MipmapLevels}
+	};
+
+	type Error = StagingUploadError<std::fmt::Error>;
+
+	#[test]
+	fn upload_range_bounds() {
+		assert!(check_range::<std::fmt::Error>(0, 16, 16).is_ok());
+		assert!(check_range::<std::fmt::Error>(8, 8, 16).is_ok());
+
+		match check_range::<std::fmt::Error>(12, 8, 16) {
+			Err(Error::OutOfBounds { offset: 12, size: 8, buffer_size: 16 }) => (),
+			other => panic!("unexpected result {:?}", other)
+		}
+		assert!(check_range::<std::fmt::Error>(vk::DeviceSize::MAX, 1, 16).is_err());
+	}
+
+	#[test]
+	fn uma_policy_opt_in() {
+		let mut raw = vk::PhysicalDeviceMemoryProperties::default();
+		raw.memory_type_count = 1;
+		raw.memory_types[0].property_flags = vk::MemoryPropertyFlags::DEVICE_LOCAL | vk::MemoryPropertyFlags::HOST_VISIBLE;
+		raw.memory_heap_count = 1;
+		let uma: PhysicalDeviceMemoryProperties = raw.into();
+
+		raw.memory_types[0].property_flags = vk::MemoryPropertyFlags::DEVICE_LOCAL;
+		let discrete: PhysicalDeviceMemoryProperties = raw.into();
+
+		assert_eq!(UmaPolicy::default(), UmaPolicy::Ignore);
+		assert!(!UmaPolicy::default().map_directly(&uma));
+		assert!(UmaPolicy::MapDirectly.map_directly(&uma));
+		assert!(!UmaPolicy::MapDirectly.map_directly(&discrete));
+	}
+
+	#[test]
+	fn upload_image_region_bounds() {
+		let size: ImageSize = ImageSize::new_2d(
+			NonZeroU32::new(16).unwrap(),
+			NonZeroU32::new(8).unwrap(),
+			NonZeroU32::new(2).unwrap(),
+			MipmapLevels::Most()
+		)
+		.into();
+		let range = |mipmap_levels_base: u32, array_layers_base: u32| ImageSubresourceRange {
+			aspect_mask: vk::ImageAspectFlags::COLOR,
+			mipmap_levels_base,
+			mipmap_levels: NonZeroU32::new(1).unwrap(),
+			array_layers_base,
+			array_layers: NonZeroU32::new(1).unwrap()
+		};
+		let offset = |x: i32, y: i32| vk::Offset3D { x, y, z: 0 };
+		let extent = |width: u32, height: u32| vk::Extent3D { width, height, depth: 1 };
+
+		assert!(check_image_region::<std::fmt::Error>(size, range(0, 1), offset(0, 0), extent(16, 8)).is_ok());
+		assert!(check_image_region::<std::fmt::Error>(size, range(1, 0), offset(4, 0), extent(4, 4)).is_ok());
+		assert!(check_image_region::<std::fmt::Error>(size, range(4, 0), offset(0, 0), extent(1, 1)).is_ok());
+
+		match check_image_region::<std::fmt::Error>(size, range(1, 0), offset(4, 0), extent(8, 4)) {
+			Err(Error::ImageRegionOutOfBounds { mipmap_level: 1, .. }) => (),
+			other => panic!("unexpected result {:?}", other)
+		}
+		assert!(check_image_region::<std::fmt::Error>(size, range(0, 2), offset(0, 0), extent(1, 1)).is_err());
+		assert!(check_image_region::<std::fmt::Error>(size, range(5, 0), offset(0, 0), extent(1, 1)).is_err());
+		assert!(check_image_region::<std::fmt::Error>(size, range(0, 0), offset(-1, 0), extent(1, 1)).is_err());
+		assert!(check_image_region::<std::fmt::Error>(size, range(0, 0), offset(0, 0), extent(0, 1)).is_err());
+	}
+}
@@ -0,0 +1,245 @@
+//! Device memory usage statistics.
+//!
+//! Allocations made by the device allocators of this crate are recorded in the `MemoryStatsRegistry` of the device.
+//! `Device::memory_usage_report` aggregates them per memory type and heap and, when `VK_EXT_memory_budget` is enabled,
+//! adds the heap budgets and usage reported by the implementation, which also include memory allocated by other processes
+//! and by the driver itself. This is useful for diagnosing out-of-memory errors.
+
+use std::{fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use crate::{
+	physical_device::enumerate::PhysicalDeviceMemoryProperties,
+	util::{hash::VHashMap, sync::Vutex}
+};
+
+/// Live device memory allocation recorded in a `MemoryStatsRegistry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedAllocation {
+	pub memory: vk::DeviceMemory,
+	pub memory_type_index: u32,
+	pub size: NonZeroU64
+}
+
+/// Registry of live device memory allocations of a device.
+///
+/// Custom allocators should call `record_allocation` and `record_free` for each `vk::DeviceMemory` object
+/// they allocate and free so that their allocations show up in the usage report.
+pub struct MemoryStatsRegistry {
+	allocations: Vutex<VHashMap<vk::DeviceMemory, TrackedAllocation>>
+}
+impl MemoryStatsRegistry {
+	pub fn new() -> Self {
+		MemoryStatsRegistry { allocations: Vutex::new(VHashMap::default()) }
+	}
+
+	/// Records a new allocation of `memory` with `size` from memory type `memory_type_index`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn record_allocation(&self, memory: vk::DeviceMemory, memory_type_index: u32, size: NonZeroU64) {
+		self.allocations
+			.lock()
+			.expect("vutex poisoned")
+			.insert(memory, TrackedAllocation { memory, memory_type_index, size });
+	}
+
+	/// Removes `memory` from the registry. Does nothing if it was never recorded.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn record_free(&self, memory: vk::DeviceMemory) {
+		self.allocations.lock().expect("vutex poisoned").remove(&memory);
+	}
+
+	/// Returns the recorded allocation of `memory`, if any.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn get(&self, memory: vk::DeviceMemory) -> Option<TrackedAllocation> {
+		self.allocations.lock().expect("vutex poisoned").get(&memory).copied()
+	}
+
+	/// Returns a snapshot of all live recorded allocations.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn allocations(&self) -> Vec<TrackedAllocation> {
+		self.allocations.lock().expect("vutex poisoned").values().copied().collect()
+	}
+
+	/// Builds a usage report from the recorded allocations.
+	///
+	/// `budget` is the result of a `VK_EXT_memory_budget` query, if available.
+	/// At most `largest_count` largest allocations are included in the report.
+	pub fn report(
+		&self,
+		memory_properties: &PhysicalDeviceMemoryProperties,
+		budget: Option<&vk::PhysicalDeviceMemoryBudgetPropertiesEXT>,
+		largest_count: usize
+	) -> MemoryUsageReport {
+		let mut types: Vec<_> = memory_properties
+			.memory_types
+			.iter()
+			.enumerate()
+			.map(|(index, memory_type)| MemoryTypeReport {
+				memory_type_index: index as u32,
+				heap_index: memory_type.heap_index,
+				property_flags: memory_type.property_flags,
+				allocated: 0,
+				allocation_count: 0
+			})
+			.collect();
+		let mut heaps: Vec<_> = memory_properties
+			.memory_heaps
+			.iter()
+			.enumerate()
+			.map(|(index, heap)| MemoryHeapReport {
+				heap_index: index as u32,
+				size: heap.size,
+				flags: heap.flags,
+				budget: budget.map(|b| b.heap_budget[index]),
+				usage: budget.map(|b| b.heap_usage[index]),
+				allocated: 0,
+				allocation_count: 0
+			})
+			.collect();
+
+		let mut allocations = self.allocations();
+		for allocation in allocations.iter() {
+			if let Some(memory_type) = types.get_mut(allocation.memory_type_index as usize) {
+				memory_type.allocated += allocation.size.get();
+				memory_type.allocation_count += 1;
+
+				if let Some(heap) = heaps.get_mut(memory_type.heap_index as usize) {
+					heap.allocated += allocation.size.get();
+					heap.allocation_count += 1;
+				}
+			}
+		}
+
+		allocations.sort_unstable_by_key(|a| std::cmp::Reverse(a.size));
+		allocations.truncate(largest_count);
+
+		MemoryUsageReport { heaps, types, largest_allocations: allocations }
+	}
+}
+impl Default for MemoryStatsRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl fmt::Debug for MemoryStatsRegistry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("MemoryStatsRegistry")
+			.field("allocations", &self.allocations)
+			.finish()
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryHeapReport {
+	pub heap_index: u32,
+	pub size: vk::DeviceSize,
+	pub flags: vk::MemoryHeapFlags,
+	/// Heap budget reported by `VK_EXT_memory_budget`.
+	pub budget: Option<vk::DeviceSize>,
+	/// Heap usage of this process reported by `VK_EXT_memory_budget`.
+	pub usage: Option<vk::DeviceSize>,
+	/// Sum of sizes of the recorded allocations in this heap.
+	pub allocated: vk::DeviceSize,
+	pub allocation_count: usize
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryTypeReport {
+	pub memory_type_index: u32,
+	pub heap_index: u32,
+	pub property_flags: vk::MemoryPropertyFlags,
+	/// Sum of sizes of the recorded allocations of this type.
+	pub allocated: vk::DeviceSize,
+	pub allocation_count: usize
+}
+
+/// Return type of `Device::memory_usage_report`.
+#[derive(Debug, Clone)]
+pub struct MemoryUsageReport {
+	pub heaps: Vec<MemoryHeapReport>,
+	pub types: Vec<MemoryTypeReport>,
+	/// Largest recorded allocations sorted by size in descending order.
+	pub largest_allocations: Vec<TrackedAllocation>
+}
+impl fmt::Display for MemoryUsageReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for heap in self.heaps.iter() {
+			write!(
+				f,
+				"heap {} ({:?}): size {}, allocated {} in {} allocations",
+				heap.heap_index, heap.flags, heap.size, heap.allocated, heap.allocation_count
+			)?;
+			if let (Some(budget), Some(usage)) = (heap.budget, heap.usage) {
+				write!(f, ", budget {}, usage {}", budget, usage)?;
+			}
+			writeln!(f)?;
+
+			for memory_type in self.types.iter().filter(|t| t.heap_index == heap.heap_index && t.allocation_count > 0) {
+				writeln!(
+					f,
+					"\ttype {} ({:?}): allocated {} in {} allocations",
+					memory_type.memory_type_index, memory_type.property_flags, memory_type.allocated, memory_type.allocation_count
+				)?;
+			}
+		}
+
+		if !self.largest_allocations.is_empty() {
+			writeln!(f, "largest allocations:")?;
+			for allocation in self.largest_allocations.iter() {
+				writeln!(
+					f,
+					"\t{} bytes of type {} ({})",
+					allocation.size,
+					allocation.memory_type_index,
+					crate::util::fmt::format_handle(allocation.memory)
+				)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::num::NonZeroU64;
+
+	use ash::vk::{self, Handle};
+
+	use super::MemoryStatsRegistry;
+
+	#[test]
+	fn report_aggregates_allocations() {
+		let mut raw = vk::PhysicalDeviceMemoryProperties::default();
+		raw.memory_type_count = 2;
+		raw.memory_types[0].heap_index = 0;
+		raw.memory_types[1].heap_index = 1;
+		raw.memory_heap_count = 2;
+
+		let registry = MemoryStatsRegistry::new();
+		registry.record_allocation(vk::DeviceMemory::from_raw(1), 0, NonZeroU64::new(64).unwrap());
+		registry.record_allocation(vk::DeviceMemory::from_raw(2), 1, NonZeroU64::new(256).unwrap());
+		registry.record_allocation(vk::DeviceMemory::from_raw(3), 1, NonZeroU64::new(128).unwrap());
+		registry.record_free(vk::DeviceMemory::from_raw(1));
+
+		let report = registry.report(&raw.into(), None, 1);
+		assert_eq!(report.heaps[0].allocation_count, 0);
+		assert_eq!(report.heaps[1].allocated, 384);
+		assert_eq!(report.types[1].allocation_count, 2);
+		assert_eq!(report.largest_allocations.len(), 1);
+		assert_eq!(report.largest_allocations[0].size.get(), 256);
+	}
+}
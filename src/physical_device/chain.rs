@@ -0,0 +1,160 @@
+//! Typed queries of extension structures through `vkGetPhysicalDeviceProperties2` and `vkGetPhysicalDeviceFeatures2`.
+//!
+//! Instead of building the `p_next` chain by hand, the requested structures are selected using `PropertiesChainRequest`
+//! or `FeaturesChainRequest` and returned as optional members:
+//!
+//! ```ignore
+//! let properties = physical_device.properties2(PropertiesChainRequest { subgroup: true, driver: true, ..Default::default() });
+//! let subgroup_size = properties.subgroup.unwrap().subgroup_size;
+//! ```
+//!
+//! Requested structures of extensions must be supported by the physical device, otherwise their contents are unspecified.
+//! The `p_next` members of the returned structures are always null.
+
+use std::convert::TryInto;
+
+use ash::vk;
+
+use super::{enumerate::PhysicalDeviceProperties, PhysicalDevice};
+
+/// Pushes the present members of the chain onto `$builder`.
+macro_rules! push_chain {
+	(
+		$builder: ident: $($member: ident),+
+	) => {
+		$(
+			if let Some(ref mut member) = $member {
+				$builder = $builder.push_next(member);
+			}
+		)+
+	};
+}
+
+/// Clears the `p_next` members of the present members of the chain.
+macro_rules! detach_chain {
+	(
+		$($member: ident),+
+	) => {
+		$(
+			if let Some(ref mut member) = $member {
+				member.p_next = std::ptr::null_mut();
+			}
+		)+
+	};
+}
+
+/// Selects the structures queried by `PhysicalDevice::properties2`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PropertiesChainRequest {
+	/// `vk::PhysicalDeviceSubgroupProperties`, core in Vulkan 1.1.
+	pub subgroup: bool,
+	/// `vk::PhysicalDeviceDescriptorIndexingProperties` of `VK_EXT_descriptor_indexing`, core in Vulkan 1.2.
+	pub descriptor_indexing: bool,
+	/// `vk::PhysicalDeviceDriverProperties` of `VK_KHR_driver_properties`, core in Vulkan 1.2.
+	pub driver: bool,
+	/// `vk::PhysicalDevicePushDescriptorPropertiesKHR` of `VK_KHR_push_descriptor`.
+	pub push_descriptor: bool,
+	/// `vk::PhysicalDeviceRayTracingPipelinePropertiesKHR` of `VK_KHR_ray_tracing_pipeline`.
+	pub ray_tracing_pipeline: bool,
+	/// `vk::PhysicalDeviceAccelerationStructurePropertiesKHR` of `VK_KHR_acceleration_structure`.
+	pub acceleration_structure: bool
+}
+
+/// Properties of a physical device together with the structures selected by a `PropertiesChainRequest`.
+#[derive(Debug)]
+pub struct PropertiesChain {
+	pub properties: PhysicalDeviceProperties,
+	pub subgroup: Option<vk::PhysicalDeviceSubgroupProperties>,
+	pub descriptor_indexing: Option<vk::PhysicalDeviceDescriptorIndexingProperties>,
+	pub driver: Option<vk::PhysicalDeviceDriverProperties>,
+	pub push_descriptor: Option<vk::PhysicalDevicePushDescriptorPropertiesKHR>,
+	pub ray_tracing_pipeline: Option<vk::PhysicalDeviceRayTracingPipelinePropertiesKHR>,
+	pub acceleration_structure: Option<vk::PhysicalDeviceAccelerationStructurePropertiesKHR>
+}
+// `p_next` pointers are cleared after the query.
+unsafe impl Send for PropertiesChain {}
+unsafe impl Sync for PropertiesChain {}
+impl PropertiesChain {
+	pub(super) fn query(physical_device: &PhysicalDevice, request: PropertiesChainRequest) -> Self {
+		let mut subgroup = request.subgroup.then(vk::PhysicalDeviceSubgroupProperties::default);
+		let mut descriptor_indexing = request.descriptor_indexing.then(vk::PhysicalDeviceDescriptorIndexingProperties::default);
+		let mut driver = request.driver.then(vk::PhysicalDeviceDriverProperties::default);
+		let mut push_descriptor = request.push_descriptor.then(vk::PhysicalDevicePushDescriptorPropertiesKHR::default);
+		let mut ray_tracing_pipeline = request.ray_tracing_pipeline.then(vk::PhysicalDeviceRayTracingPipelinePropertiesKHR::default);
+		let mut acceleration_structure = request
+			.acceleration_structure
+			.then(vk::PhysicalDeviceAccelerationStructurePropertiesKHR::default);
+
+		let mut properties2 = vk::PhysicalDeviceProperties2::builder();
+		push_chain!(properties2: subgroup, descriptor_indexing, driver, push_descriptor, ray_tracing_pipeline, acceleration_structure);
+
+		unsafe {
+			physical_device
+				.instance()
+				.get_physical_device_properties2(**physical_device, &mut properties2);
+		}
+		let properties = properties2.properties.try_into().unwrap();
+
+		detach_chain!(subgroup, descriptor_indexing, driver, push_descriptor, ray_tracing_pipeline, acceleration_structure);
+
+		PropertiesChain { properties, subgroup, descriptor_indexing, driver, push_descriptor, ray_tracing_pipeline, acceleration_structure }
+	}
+}
+
+/// Selects the structures queried by `PhysicalDevice::features2`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FeaturesChainRequest {
+	/// `vk::PhysicalDevice16BitStorageFeatures` of `VK_KHR_16bit_storage`, core in Vulkan 1.1.
+	pub storage_16bit: bool,
+	/// `vk::PhysicalDeviceDescriptorIndexingFeatures` of `VK_EXT_descriptor_indexing`, core in Vulkan 1.2.
+	pub descriptor_indexing: bool,
+	/// `vk::PhysicalDeviceTimelineSemaphoreFeatures` of `VK_KHR_timeline_semaphore`, core in Vulkan 1.2.
+	pub timeline_semaphore: bool,
+	/// `vk::PhysicalDeviceBufferDeviceAddressFeatures` of `VK_KHR_buffer_device_address`, core in Vulkan 1.2.
+	pub buffer_device_address: bool,
+	/// `vk::PhysicalDeviceRayTracingPipelineFeaturesKHR` of `VK_KHR_ray_tracing_pipeline`.
+	pub ray_tracing_pipeline: bool,
+	/// `vk::PhysicalDeviceAccelerationStructureFeaturesKHR` of `VK_KHR_acceleration_structure`.
+	pub acceleration_structure: bool
+}
+
+/// Features of a physical device together with the structures selected by a `FeaturesChainRequest`.
+#[derive(Debug)]
+pub struct FeaturesChain {
+	pub features: vk::PhysicalDeviceFeatures,
+	pub storage_16bit: Option<vk::PhysicalDevice16BitStorageFeatures>,
+	pub descriptor_indexing: Option<vk::PhysicalDeviceDescriptorIndexingFeatures>,
+	pub timeline_semaphore: Option<vk::PhysicalDeviceTimelineSemaphoreFeatures>,
+	pub buffer_device_address: Option<vk::PhysicalDeviceBufferDeviceAddressFeatures>,
+	pub ray_tracing_pipeline: Option<vk::PhysicalDeviceRayTracingPipelineFeaturesKHR>,
+	pub acceleration_structure: Option<vk::PhysicalDeviceAccelerationStructureFeaturesKHR>
+}
+// `p_next` pointers are cleared after the query.
+unsafe impl Send for FeaturesChain {}
+unsafe impl Sync for FeaturesChain {}
+impl FeaturesChain {
+	pub(super) fn query(physical_device: &PhysicalDevice, request: FeaturesChainRequest) -> Self {
+		let mut storage_16bit = request.storage_16bit.then(vk::PhysicalDevice16BitStorageFeatures::default);
+		let mut descriptor_indexing = request.descriptor_indexing.then(vk::PhysicalDeviceDescriptorIndexingFeatures::default);
+		let mut timeline_semaphore = request.timeline_semaphore.then(vk::PhysicalDeviceTimelineSemaphoreFeatures::default);
+		let mut buffer_device_address = request.buffer_device_address.then(vk::PhysicalDeviceBufferDeviceAddressFeatures::default);
+		let mut ray_tracing_pipeline = request.ray_tracing_pipeline.then(vk::PhysicalDeviceRayTracingPipelineFeaturesKHR::default);
+		let mut acceleration_structure = request
+			.acceleration_structure
+			.then(vk::PhysicalDeviceAccelerationStructureFeaturesKHR::default);
+
+		let mut features2 = vk::PhysicalDeviceFeatures2::builder();
+		push_chain!(features2: storage_16bit, descriptor_indexing, timeline_semaphore, buffer_device_address, ray_tracing_pipeline, acceleration_structure);
+
+		unsafe {
+			physical_device
+				.instance()
+				.get_physical_device_features2(**physical_device, &mut features2);
+		}
+		let features = features2.features;
+
+		detach_chain!(storage_16bit, descriptor_indexing, timeline_semaphore, buffer_device_address, ray_tracing_pipeline, acceleration_structure);
+
+		FeaturesChain { features, storage_16bit, descriptor_indexing, timeline_semaphore, buffer_device_address, ray_tracing_pipeline, acceleration_structure }
+	}
+}
@@ -73,6 +73,31 @@ impl From<ash::vk::PhysicalDeviceMemoryProperties> for PhysicalDeviceMemoryPrope
 		PhysicalDeviceMemoryProperties { memory_types, memory_heaps }
 	}
 }
+impl PhysicalDeviceMemoryProperties {
+	/// Returns whether the device has unified memory architecture.
+	///
+	/// That is, whether all device local memory types (except lazily allocated ones) are also host visible,
+	/// as is common on integrated GPUs.
+	pub fn is_uma(&self) -> bool {
+		let mut device_local = self
+			.memory_types
+			.iter()
+			.map(|t| t.property_flags)
+			.filter(|f| f.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL) && !f.contains(vk::MemoryPropertyFlags::LAZILY_ALLOCATED))
+			.peekable();
+
+		device_local.peek().is_some() && device_local.all(|f| f.contains(vk::MemoryPropertyFlags::HOST_VISIBLE))
+	}
+
+	/// Returns the index of the first memory type allowed by `type_bits` that has all of `required_flags`.
+	pub fn find_memory_type(&self, type_bits: u32, required_flags: vk::MemoryPropertyFlags) -> Option<u32> {
+		self.memory_types
+			.iter()
+			.enumerate()
+			.find(|(index, memory_type)| type_bits & (1 << *index as u32) != 0 && memory_type.property_flags.contains(required_flags))
+			.map(|(index, _)| index as u32)
+	}
+}
 
 pub struct PhysicalDeviceProperties {
 	pub api_version: VkVersion,
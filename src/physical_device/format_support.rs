@@ -0,0 +1,61 @@
+//! Format feature queries built on `PhysicalDevice::format_properties`.
+//!
+//! Selecting a depth format is a typical use:
+//!
+//! ```ignore
+//! let depth_format = physical_device.find_supported_format(
+//! 	&[vk::Format::D32_SFLOAT, vk::Format::D32_SFLOAT_S8_UINT, vk::Format::D24_UNORM_S8_UINT],
+//! 	vk::ImageTiling::OPTIMAL,
+//! 	vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT
+//! );
+//! ```
+
+use ash::vk;
+
+use super::PhysicalDevice;
+
+impl PhysicalDevice {
+	/// Returns the features supported by images of `format` created with `tiling`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `tiling` is neither `LINEAR` nor `OPTIMAL`.
+	pub fn format_tiling_features(&self, format: vk::Format, tiling: vk::ImageTiling) -> vk::FormatFeatureFlags {
+		let properties = self.format_properties(format);
+
+		match tiling {
+			vk::ImageTiling::LINEAR => properties.linear_tiling_features,
+			vk::ImageTiling::OPTIMAL => properties.optimal_tiling_features,
+			_ => panic!("Unsupported image tiling {:?}", tiling)
+		}
+	}
+
+	/// Returns whether optimal tiling images of `format` can be sampled with linear filtering.
+	pub fn supports_sampled_linear(&self, format: vk::Format) -> bool {
+		self.format_tiling_features(format, vk::ImageTiling::OPTIMAL)
+			.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR)
+	}
+
+	/// Returns the first format of `candidates` whose images created with `tiling` support all of `features`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `tiling` is neither `LINEAR` nor `OPTIMAL`.
+	pub fn find_supported_format(&self, candidates: &[vk::Format], tiling: vk::ImageTiling, features: vk::FormatFeatureFlags) -> Option<vk::Format> {
+		candidates
+			.iter()
+			.copied()
+			.find(|&format| self.format_tiling_features(format, tiling).contains(features))
+	}
+
+	/// Returns whether optimal tiling images of `src_format` can be blitted to optimal tiling images of `dst_format`.
+	///
+	/// Blitting with `vk::Filter::LINEAR` additionally requires `supports_sampled_linear(src_format)`.
+	pub fn supports_blit(&self, src_format: vk::Format, dst_format: vk::Format) -> bool {
+		self.format_tiling_features(src_format, vk::ImageTiling::OPTIMAL)
+			.contains(vk::FormatFeatureFlags::BLIT_SRC)
+			&& self
+				.format_tiling_features(dst_format, vk::ImageTiling::OPTIMAL)
+				.contains(vk::FormatFeatureFlags::BLIT_DST)
+	}
+}
@@ -19,7 +19,10 @@ use ash::vk::{
 
 use crate::prelude::{HasHandle, Instance, Vrc};
 
+#[cfg(feature = "vulkan1_1")]
+pub mod chain;
 pub mod enumerate;
+pub mod format_support;
 
 #[derive(Clone)]
 pub struct PhysicalDevice {
@@ -94,6 +97,25 @@ impl PhysicalDevice {
 		}
 	}
 
+	/// Returns memory properties together with the heap budgets and usage of `VK_EXT_memory_budget`.
+	///
+	/// The extension must be supported by this physical device.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/VkPhysicalDeviceMemoryBudgetPropertiesEXT.html>.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn memory_budget_properties(&self) -> (enumerate::PhysicalDeviceMemoryProperties, vk::PhysicalDeviceMemoryBudgetPropertiesEXT) {
+		let mut budget = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+		let mut properties = vk::PhysicalDeviceMemoryProperties2::builder().push_next(&mut budget);
+
+		unsafe {
+			self.instance
+				.get_physical_device_memory_properties2(self.physical_device, &mut properties);
+		}
+		let memory_properties = properties.memory_properties.into();
+
+		(memory_properties, budget)
+	}
+
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceProperties.html>.
 	pub fn properties(&self) -> enumerate::PhysicalDeviceProperties {
 		unsafe {
@@ -104,6 +126,49 @@ impl PhysicalDevice {
 		}
 	}
 
+	/// Returns the properties together with the extension structures selected by `request`, see the `chain` module.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceProperties2.html>.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn properties2(&self, request: chain::PropertiesChainRequest) -> chain::PropertiesChain {
+		chain::PropertiesChain::query(self, request)
+	}
+
+	/// Returns the identifiers of this physical device and its driver.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/VkPhysicalDeviceIDProperties.html>.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn id_properties(&self) -> vk::PhysicalDeviceIDProperties {
+		let mut id_properties = vk::PhysicalDeviceIDProperties::default();
+		let mut properties = vk::PhysicalDeviceProperties2::builder().push_next(&mut id_properties);
+
+		unsafe {
+			self.instance
+				.get_physical_device_properties2(self.physical_device, &mut properties);
+		}
+
+		id_properties
+	}
+
+	/// Returns the acceleration structure and ray tracing pipeline properties, see the `raytracing` module.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/VkPhysicalDeviceRayTracingPipelinePropertiesKHR.html>.
+	#[cfg(feature = "ray_tracing")]
+	pub fn ray_tracing_properties(&self) -> (vk::PhysicalDeviceAccelerationStructurePropertiesKHR, vk::PhysicalDeviceRayTracingPipelinePropertiesKHR) {
+		let mut acceleration_structure_properties = vk::PhysicalDeviceAccelerationStructurePropertiesKHR::default();
+		let mut ray_tracing_pipeline_properties = vk::PhysicalDeviceRayTracingPipelinePropertiesKHR::default();
+		let mut properties = vk::PhysicalDeviceProperties2::builder()
+			.push_next(&mut acceleration_structure_properties)
+			.push_next(&mut ray_tracing_pipeline_properties);
+
+		unsafe {
+			self.instance
+				.get_physical_device_properties2(self.physical_device, &mut properties);
+		}
+
+		(acceleration_structure_properties, ray_tracing_pipeline_properties)
+	}
+
 	/// Returns number of family queues supported by this physical device.
 	pub fn queue_family_count(&self) -> std::num::NonZeroU32 {
 		let mut queue_count: u32 = 0;
@@ -137,6 +202,14 @@ impl PhysicalDevice {
 		}
 	}
 
+	/// Returns the features together with the extension structures selected by `request`, see the `chain` module.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceFeatures2.html>.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn features2(&self, request: chain::FeaturesChainRequest) -> chain::FeaturesChain {
+		chain::FeaturesChain::query(self, request)
+	}
+
 	pub const fn instance(&self) -> &Vrc<Instance> {
 		&self.instance
 	}
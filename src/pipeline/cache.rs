@@ -0,0 +1,136 @@
+use std::{fmt, ops::Deref};
+
+use ash::vk;
+
+use super::error::PipelineCacheError;
+use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Vrc};
+
+/// Pipeline cache shared by pipeline creations to avoid repeated compilation.
+///
+/// The cache data can be saved with `data` and passed to `new` in a later run. Data from a different device
+/// or driver version is ignored by the implementation.
+pub struct PipelineCache {
+	device: Vrc<Device>,
+	cache: vk::PipelineCache,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl PipelineCache {
+	/// Creates a new pipeline cache, optionally prefilled with `initial_data` previously returned from `data`.
+	pub fn new(device: Vrc<Device>, initial_data: &[u8], host_memory_allocator: HostMemoryAllocator) -> Result<Vrc<Self>, PipelineCacheError> {
+		let create_info = vk::PipelineCacheCreateInfo::builder().initial_data(initial_data);
+
+		unsafe {
+			Self::from_create_info(
+				device,
+				create_info,
+				host_memory_allocator
+			)
+		}
+	}
+
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkCreatePipelineCache.html>.
+	pub unsafe fn from_create_info(
+		device: Vrc<Device>,
+		create_info: impl Deref<Target = vk::PipelineCacheCreateInfo>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, PipelineCacheError> {
+		log_trace_common!(
+			"Creating pipeline cache:",
+			device,
+			create_info.initial_data_size,
+			host_memory_allocator
+		);
+
+		let cache = device.create_pipeline_cache(
+			create_info.deref(),
+			host_memory_allocator.as_ref()
+		)?;
+
+		device.child_registry().register(cache);
+		Ok(Vrc::new(PipelineCache { device, cache, host_memory_allocator }))
+	}
+
+	/// Returns the cache data, which can be saved and used to create the cache in a later run.
+	pub fn data(&self) -> Result<Vec<u8>, PipelineCacheError> {
+		unsafe { self.device.get_pipeline_cache_data(self.cache).map_err(Into::into) }
+	}
+
+	/// Merges `sources` into this cache.
+	pub fn merge(&self, sources: &[&PipelineCache]) -> Result<(), PipelineCacheError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if sources.iter().any(|source| source.cache == self.cache) {
+				return Err(PipelineCacheError::MergeIntoSelf)
+			}
+			if sources.iter().any(|source| source.device != self.device) {
+				return Err(PipelineCacheError::DeviceMismatch)
+			}
+		}
+
+		let sources: Vec<_> = sources.iter().map(|source| source.cache).collect();
+		unsafe {
+			self.device
+				.merge_pipeline_caches(self.cache, &sources)
+				.map_err(Into::into)
+		}
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl_common_handle_traits! {
+	impl HasHandle<vk::PipelineCache>, Deref, Borrow, Eq, Hash, Ord for PipelineCache {
+		target = { cache }
+	}
+}
+impl Drop for PipelineCache {
+	fn drop(&mut self) {
+		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.cache);
+
+		unsafe {
+			self.device.destroy_pipeline_cache(
+				self.cache,
+				self.host_memory_allocator.as_ref()
+			)
+		}
+	}
+}
+impl fmt::Debug for PipelineCache {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PipelineCache")
+			.field("device", &self.device)
+			.field("cache", &self.safe_handle())
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
+
+/// Outcome of a pipeline creation that is not allowed to compile, such as `GraphicsPipeline::from_create_info_if_cached`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineCreation<T> {
+	/// The pipeline was created without compilation.
+	Created(T),
+	/// The pipeline was not created because it would have to be compiled, `PIPELINE_COMPILE_REQUIRED`.
+	CompileRequired
+}
+impl<T> PipelineCreation<T> {
+	/// Returns the created pipeline or `None` if compilation is required.
+	pub fn created(self) -> Option<T> {
+		match self {
+			PipelineCreation::Created(pipeline) => Some(pipeline),
+			PipelineCreation::CompileRequired => None
+		}
+	}
+
+	pub fn is_compile_required(&self) -> bool {
+		matches!(self, PipelineCreation::CompileRequired)
+	}
+}
@@ -2,12 +2,17 @@ use std::{fmt, ops::Deref};
 
 use ash::vk;
 
-use super::error::ComputePipelineError;
+use super::{
+	cache::{PipelineCache, PipelineCreation},
+	error::ComputePipelineError,
+	feedback::{FeedbackChain, PipelineCreationFeedback}
+};
 use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Vrc};
 
 pub struct ComputePipeline {
 	device: Vrc<Device>,
 	pipeline: vk::Pipeline,
+	creation_feedback: Option<PipelineCreationFeedback>,
 	host_memory_allocator: HostMemoryAllocator
 }
 impl ComputePipeline {
@@ -19,6 +24,44 @@ impl ComputePipeline {
 		create_info: impl Deref<Target = vk::ComputePipelineCreateInfo>,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, ComputePipelineError> {
+		Self::create(
+			device,
+			create_info.deref(),
+			None,
+			host_memory_allocator
+		)?
+		.created()
+		.ok_or(ComputePipelineError::ERROR_PIPELINE_COMPILE_REQUIRED_EXT)
+	}
+
+	/// Creates the pipeline only if it can be created without compiling it, see `GraphicsPipeline::from_create_info_if_cached`.
+	///
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.3-extensions/man/html/vkCreateComputePipelines.html>.
+	pub unsafe fn from_create_info_if_cached(
+		device: Vrc<Device>,
+		create_info: impl Deref<Target = vk::ComputePipelineCreateInfo>,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<PipelineCreation<Vrc<Self>>, ComputePipelineError> {
+		let mut create_info = *create_info.deref();
+		create_info.flags |= vk::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED;
+
+		Self::create(
+			device,
+			&create_info,
+			cache,
+			host_memory_allocator
+		)
+	}
+
+	unsafe fn create(
+		device: Vrc<Device>,
+		create_info: &vk::ComputePipelineCreateInfo,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<PipelineCreation<Vrc<Self>>, ComputePipelineError> {
 		if log::log_enabled!(log::Level::Trace) {
 			log_trace_common!(
 				"Creating compute pipeline:",
@@ -26,27 +69,40 @@ impl ComputePipeline {
 				create_info.flags,
 				create_info.stage,
 				create_info.layout,
+				cache,
 				host_memory_allocator
 			);
 		}
 
-		let pipeline = device
-			.create_compute_pipelines(
-				vk::PipelineCache::null(),
-				&[*create_info.deref()],
-				host_memory_allocator.as_ref()
-			)
-			.map_err(|e| e.1)?
-			.into_iter()
-			.next().unwrap()
-		;
+		let mut create_info = *create_info;
+		let mut feedback = FeedbackChain::for_device(&device, 1);
+		if let Some(feedback) = feedback.as_mut() {
+			feedback.chain(&mut create_info.p_next);
+		}
+
+		let pipeline = match device.create_compute_pipelines(
+			cache.map(|c| c.handle()).unwrap_or_default(),
+			&[create_info],
+			host_memory_allocator.as_ref()
+		) {
+			Ok(pipelines) => pipelines[0],
+			Err((_, vk::Result::PIPELINE_COMPILE_REQUIRED)) => return Ok(PipelineCreation::CompileRequired),
+			Err((_, err)) => return Err(err.into())
+		};
+		device.child_registry().register(pipeline);
 		let me = ComputePipeline {
 			device,
 			pipeline,
+			creation_feedback: feedback.map(FeedbackChain::into_feedback),
 			host_memory_allocator
 		};
 
-		Ok(Vrc::new(me))
+		Ok(PipelineCreation::Created(Vrc::new(me)))
+	}
+
+	/// Returns the creation feedback, if `VK_EXT_pipeline_creation_feedback` is enabled, see the `feedback` module.
+	pub const fn creation_feedback(&self) -> Option<&PipelineCreationFeedback> {
+		self.creation_feedback.as_ref()
 	}
 
 	pub const fn device(&self) -> &Vrc<Device> {
@@ -61,6 +117,7 @@ impl_common_handle_traits! {
 impl Drop for ComputePipeline {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.pipeline);
 
 		unsafe {
 			self.device.destroy_pipeline(
@@ -75,6 +132,7 @@ impl fmt::Debug for ComputePipeline {
 		f.debug_struct("ComputePipeline")
 			.field("device", &self.device)
 			.field("pipeline", &self.safe_handle())
+			.field("creation_feedback", &self.creation_feedback)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
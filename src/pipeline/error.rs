@@ -9,6 +9,12 @@ vk_result_error! {
 		#[cfg(feature = "runtime_implicit_validations")]
 		#[error("Stage flags field of push constant range must not be empty.")]
 		StageFlagsEmpty,
+
+		#[error("Push constant ranges {0} and {1} both include stages {2:?}")]
+		PushConstantStagesOverlap(usize, usize, ash::vk::ShaderStageFlags),
+
+		#[error("Push constant range {0} exceeds maxPushConstantsSize")]
+		PushConstantRangeTooLarge(usize),
 	}
 }
 
@@ -34,5 +40,56 @@ vk_result_error! {
 			ERROR_OUT_OF_DEVICE_MEMORY,
 			ERROR_INVALID_SHADER_NV
 		}
+
+		#[cfg(feature = "shader_reflection")]
+		#[error("Shader stage interfaces do not match")]
+		ShaderInterface(#[from] crate::shader::reflect::ShaderInterfaceError),
+	}
+}
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum PipelineCacheError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY
+		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Pipeline cache cannot be merged into itself")]
+		MergeIntoSelf,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Pipeline caches must be from the same device")]
+		DeviceMismatch,
+	}
+}
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum GraphicsPipelineLibraryError {
+		vk {
+			ERROR_PIPELINE_COMPILE_REQUIRED_EXT,
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_INVALID_SHADER_NV
+		}
+
+		#[error("Pipeline library must contain at least one part")]
+		PartsEmpty,
+
+		#[error("Pipeline library parts {0:?} are already provided by another library")]
+		PartsOverlap(super::library::GraphicsPipelineLibraryParts),
+
+		#[error("Pipeline libraries only provide parts {0:?}, all parts are required")]
+		PartsMissing(super::library::GraphicsPipelineLibraryParts),
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("VK_EXT_graphics_pipeline_library must be enabled on the device")]
+		ExtensionNotEnabled,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Pipeline libraries, layout and cache must be from the same device")]
+		DeviceMismatch,
 	}
 }
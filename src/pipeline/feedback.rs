@@ -0,0 +1,123 @@
+//! Pipeline creation feedback using `VK_EXT_pipeline_creation_feedback`.
+//!
+//! When the extension is enabled on the device, pipeline constructors chain `vk::PipelineCreationFeedbackCreateInfo`
+//! into the create info and store the reported durations and cache hits on the created pipeline.
+
+use std::{os::raw::c_void, time::Duration};
+
+use ash::vk;
+
+use crate::prelude::Device;
+
+/// Feedback about the creation of a whole pipeline or of a single stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CreationFeedback {
+	/// Whether the pipeline cache passed to the constructor already contained the result.
+	pub cache_hit: bool,
+	/// Whether the base pipeline was used to accelerate the creation.
+	pub base_pipeline_acceleration: bool,
+	pub duration: Duration
+}
+impl CreationFeedback {
+	/// Returns `None` if the implementation did not provide valid feedback.
+	pub fn from_raw(raw: vk::PipelineCreationFeedback) -> Option<Self> {
+		if !raw.flags.contains(vk::PipelineCreationFeedbackFlags::VALID) {
+			return None
+		}
+
+		Some(CreationFeedback {
+			cache_hit: raw.flags.contains(vk::PipelineCreationFeedbackFlags::APPLICATION_PIPELINE_CACHE_HIT),
+			base_pipeline_acceleration: raw.flags.contains(vk::PipelineCreationFeedbackFlags::BASE_PIPELINE_ACCELERATION),
+			duration: Duration::from_nanos(raw.duration)
+		})
+	}
+}
+
+/// Feedback about the creation of a pipeline and its stages, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineCreationFeedback {
+	/// Feedback for the whole pipeline, `None` if not provided by the implementation.
+	pub pipeline: Option<CreationFeedback>,
+	/// Feedback for each stage in the order of the create info stages.
+	pub stages: Vec<Option<CreationFeedback>>
+}
+impl PipelineCreationFeedback {
+	/// Returns whether the whole pipeline was found in the pipeline cache.
+	pub fn cache_hit(&self) -> bool {
+		self.pipeline.map(|feedback| feedback.cache_hit).unwrap_or(false)
+	}
+
+	/// Returns the duration of the whole pipeline creation, if provided.
+	pub fn duration(&self) -> Option<Duration> {
+		self.pipeline.map(|feedback| feedback.duration)
+	}
+}
+
+/// Storage the implementation writes the feedback into during pipeline creation.
+pub(super) struct FeedbackChain {
+	pipeline: vk::PipelineCreationFeedback,
+	stages: Vec<vk::PipelineCreationFeedback>,
+	info: vk::PipelineCreationFeedbackCreateInfo
+}
+impl FeedbackChain {
+	/// Returns `None` if `VK_EXT_pipeline_creation_feedback` is not enabled on `device`.
+	pub fn for_device(device: &Device, stage_count: u32) -> Option<Self> {
+		if !device.is_extension_enabled(vk::ExtPipelineCreationFeedbackFn::name()) {
+			return None
+		}
+
+		Some(FeedbackChain {
+			pipeline: Default::default(),
+			stages: vec![Default::default(); stage_count as usize],
+			info: Default::default()
+		})
+	}
+
+	/// Prepends the feedback create info to the chain starting at `p_next`.
+	///
+	/// ### Safety
+	///
+	/// `self` must not be moved until the pipeline is created.
+	pub unsafe fn chain(&mut self, p_next: &mut *const c_void) {
+		self.info.p_pipeline_creation_feedback = &mut self.pipeline;
+		self.info.pipeline_stage_creation_feedback_count = self.stages.len() as u32;
+		self.info.p_pipeline_stage_creation_feedbacks = self.stages.as_mut_ptr();
+		self.info.p_next = *p_next;
+
+		*p_next = &self.info as *const vk::PipelineCreationFeedbackCreateInfo as *const c_void;
+	}
+
+	pub fn into_feedback(self) -> PipelineCreationFeedback {
+		let feedback = PipelineCreationFeedback {
+			pipeline: CreationFeedback::from_raw(self.pipeline),
+			stages: self.stages.into_iter().map(CreationFeedback::from_raw).collect()
+		};
+		log::trace!("Pipeline creation feedback: {:?}", feedback);
+
+		feedback
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use ash::vk;
+
+	use super::CreationFeedback;
+
+	#[test]
+	fn creation_feedback_from_raw() {
+		let invalid = vk::PipelineCreationFeedback { flags: vk::PipelineCreationFeedbackFlags::APPLICATION_PIPELINE_CACHE_HIT, duration: 5 };
+		assert_eq!(CreationFeedback::from_raw(invalid), None);
+
+		let valid = vk::PipelineCreationFeedback {
+			flags: vk::PipelineCreationFeedbackFlags::VALID | vk::PipelineCreationFeedbackFlags::APPLICATION_PIPELINE_CACHE_HIT,
+			duration: 1_500
+		};
+		assert_eq!(
+			CreationFeedback::from_raw(valid),
+			Some(CreationFeedback { cache_hit: true, base_pipeline_acceleration: false, duration: Duration::from_nanos(1_500) })
+		);
+	}
+}
@@ -2,12 +2,17 @@ use std::{fmt, ops::Deref};
 
 use ash::vk;
 
-use super::error::GraphicsPipelineError;
+use super::{
+	cache::{PipelineCache, PipelineCreation},
+	error::GraphicsPipelineError,
+	feedback::{FeedbackChain, PipelineCreationFeedback}
+};
 use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Vrc};
 
 pub struct GraphicsPipeline {
 	device: Vrc<Device>,
 	pipeline: vk::Pipeline,
+	creation_feedback: Option<PipelineCreationFeedback>,
 	host_memory_allocator: HostMemoryAllocator
 }
 impl GraphicsPipeline {
@@ -19,6 +24,50 @@ impl GraphicsPipeline {
 		create_info: impl Deref<Target = vk::GraphicsPipelineCreateInfo>,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, GraphicsPipelineError> {
+		Self::create(
+			device,
+			create_info.deref(),
+			None,
+			host_memory_allocator
+		)?
+		.created()
+		.ok_or(GraphicsPipelineError::ERROR_PIPELINE_COMPILE_REQUIRED_EXT)
+	}
+
+	/// Creates the pipeline only if it can be created without compiling it, for example because it is in `cache`.
+	///
+	/// `FAIL_ON_PIPELINE_COMPILE_REQUIRED` is added to the flags of `create_info`. If compilation would be required,
+	/// `PipelineCreation::CompileRequired` is returned instead of an error, so that a fallback can be used
+	/// while the pipeline is compiled in the background with `from_create_info`.
+	///
+	/// Vulkan 1.3 or the `VK_EXT_pipeline_creation_cache_control` extension with the `pipelineCreationCacheControl` feature is required.
+	///
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.3-extensions/man/html/vkCreateGraphicsPipelines.html>.
+	pub unsafe fn from_create_info_if_cached(
+		device: Vrc<Device>,
+		create_info: impl Deref<Target = vk::GraphicsPipelineCreateInfo>,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<PipelineCreation<Vrc<Self>>, GraphicsPipelineError> {
+		let mut create_info = *create_info.deref();
+		create_info.flags |= vk::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED;
+
+		Self::create(
+			device,
+			&create_info,
+			cache,
+			host_memory_allocator
+		)
+	}
+
+	unsafe fn create(
+		device: Vrc<Device>,
+		create_info: &vk::GraphicsPipelineCreateInfo,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<PipelineCreation<Vrc<Self>>, GraphicsPipelineError> {
 		if log::log_enabled!(log::Level::Trace) {
 			let create_info = debugize_struct!(
 				create_info;
@@ -70,26 +119,63 @@ impl GraphicsPipeline {
 				create_info.layout,
 				create_info.render_pass,
 				create_info.subpass,
+				cache,
 				host_memory_allocator
 			);
 		}
 
-		let pipeline = device
-			.create_graphics_pipelines(
-				vk::PipelineCache::null(),
-				&[*create_info.deref()],
-				host_memory_allocator.as_ref()
-			)
-			.map_err(|e| e.1)?
-			.into_iter()
-			.next()
-			.unwrap();
+		#[cfg(feature = "shader_reflection")]
+		device.shader_reflections().validate_graphics_pipeline(create_info)?;
+
+		let mut create_info = *create_info;
+		let mut feedback = FeedbackChain::for_device(&device, create_info.stage_count);
+		if let Some(feedback) = feedback.as_mut() {
+			feedback.chain(&mut create_info.p_next);
+		}
+
+		let pipeline = match device.create_graphics_pipelines(
+			cache.map(|c| c.handle()).unwrap_or_default(),
+			&[create_info],
+			host_memory_allocator.as_ref()
+		) {
+			Ok(pipelines) => pipelines[0],
+			Err((_, vk::Result::PIPELINE_COMPILE_REQUIRED)) => return Ok(PipelineCreation::CompileRequired),
+			Err((_, err)) => return Err(err.into())
+		};
+
+		device.child_registry().register(pipeline);
+		Ok(PipelineCreation::Created(Vrc::new(GraphicsPipeline {
+			device,
+			pipeline,
+			creation_feedback: feedback.map(FeedbackChain::into_feedback),
+			host_memory_allocator
+		})))
+	}
 
-		Ok(Vrc::new(GraphicsPipeline {
+	/// Wraps a pipeline created by other means, such as linking pipeline libraries.
+	///
+	/// ### Safety
+	///
+	/// `pipeline` must be a valid graphics pipeline created from `device` with `host_memory_allocator`.
+	pub(super) unsafe fn from_existing(
+		device: Vrc<Device>,
+		pipeline: vk::Pipeline,
+		creation_feedback: Option<PipelineCreationFeedback>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Vrc<Self> {
+		device.child_registry().register(pipeline);
+
+		Vrc::new(GraphicsPipeline {
 			device,
 			pipeline,
+			creation_feedback,
 			host_memory_allocator
-		}))
+		})
+	}
+
+	/// Returns the creation feedback, if `VK_EXT_pipeline_creation_feedback` is enabled, see the `feedback` module.
+	pub const fn creation_feedback(&self) -> Option<&PipelineCreationFeedback> {
+		self.creation_feedback.as_ref()
 	}
 
 	pub const fn device(&self) -> &Vrc<Device> {
@@ -104,6 +190,7 @@ impl_common_handle_traits! {
 impl Drop for GraphicsPipeline {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.pipeline);
 
 		unsafe {
 			self.device.destroy_pipeline(
@@ -118,6 +205,7 @@ impl fmt::Debug for GraphicsPipeline {
 		f.debug_struct("GraphicsPipeline")
 			.field("device", &self.device)
 			.field("pipeline", &self.safe_handle())
+			.field("creation_feedback", &self.creation_feedback)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
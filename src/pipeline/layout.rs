@@ -3,7 +3,14 @@ use std::{fmt, ops::Deref};
 use ash::vk;
 
 use super::error::PipelineLayoutError;
-use crate::prelude::{Device, HasHandle, HostMemoryAllocator, SafeHandle, Transparent, Vrc};
+use crate::{
+	descriptor::layout::DescriptorSetLayoutDefinition,
+	prelude::{DescriptorSetLayout, Device, HasHandle, HostMemoryAllocator, PushConstantsTrait, Transparent, Vrc},
+	util::{
+		hash::VHashMap,
+		sync::{Vutex, Vweak}
+	}
+};
 
 vk_builder_wrap! {
 	pub struct PushConstantRange {
@@ -28,16 +35,38 @@ vk_builder_wrap! {
 	}
 }
 
+/// Descriptor set layout at one set index of a pipeline layout.
+#[derive(Debug, Clone)]
+pub struct PipelineLayoutSet {
+	pub layout: vk::DescriptorSetLayout,
+	/// `None` if the pipeline layout was created using `from_create_info`.
+	pub definition: Option<DescriptorSetLayoutDefinition>
+}
+impl PipelineLayoutSet {
+	/// Returns whether `layout` is compatible with this set.
+	///
+	/// Layouts are compatible if they are the same handle or have equal definitions.
+	///
+	/// If the definition is unknown, the layout cannot be checked and is always considered compatible.
+	pub fn is_compatible_with(&self, layout: &DescriptorSetLayout) -> bool {
+		match self.definition {
+			None => true,
+			Some(ref definition) => self.layout == layout.handle() || definition == layout.definition()
+		}
+	}
+}
+
 pub struct PipelineLayout {
 	device: Vrc<Device>,
 	layout: vk::PipelineLayout,
+	set_layouts: Vec<PipelineLayoutSet>,
 
 	host_memory_allocator: HostMemoryAllocator
 }
 impl PipelineLayout {
 	pub fn new<'a>(
 		device: Vrc<Device>,
-		descriptor_set_layouts: impl AsRef<[SafeHandle<'a, vk::DescriptorSetLayout>]>,
+		descriptor_set_layouts: impl AsRef<[&'a DescriptorSetLayout]>,
 		push_constant_ranges: impl AsRef<[PushConstantRange]>,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, PipelineLayoutError> {
@@ -50,18 +79,24 @@ impl PipelineLayout {
 			}
 		}
 
+		let set_layouts: Vec<_> = descriptor_set_layouts
+			.as_ref()
+			.iter()
+			.map(|l| PipelineLayoutSet { layout: l.handle(), definition: Some(l.definition().clone()) })
+			.collect();
+		let set_layouts_raw: Vec<_> = set_layouts.iter().map(|s| s.layout).collect();
+
 		let create_info = vk::PipelineLayoutCreateInfo::builder()
-			.set_layouts(Transparent::transmute_slice(
-				descriptor_set_layouts.as_ref()
-			))
+			.set_layouts(&set_layouts_raw)
 			.push_constant_ranges(Transparent::transmute_slice_twice(
 				push_constant_ranges.as_ref()
 			));
 
 		unsafe {
-			Self::from_create_info(
+			Self::create(
 				device,
 				create_info,
+				set_layouts,
 				host_memory_allocator
 			)
 		}
@@ -74,6 +109,32 @@ impl PipelineLayout {
 		device: Vrc<Device>,
 		create_info: impl Deref<Target = vk::PipelineLayoutCreateInfo>,
 		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, PipelineLayoutError> {
+		let set_layouts = if create_info.set_layout_count == 0 {
+			Vec::new()
+		} else {
+			std::slice::from_raw_parts(
+				create_info.p_set_layouts,
+				create_info.set_layout_count as usize
+			)
+			.iter()
+			.map(|&layout| PipelineLayoutSet { layout, definition: None })
+			.collect()
+		};
+
+		Self::create(
+			device,
+			create_info,
+			set_layouts,
+			host_memory_allocator
+		)
+	}
+
+	unsafe fn create(
+		device: Vrc<Device>,
+		create_info: impl Deref<Target = vk::PipelineLayoutCreateInfo>,
+		set_layouts: Vec<PipelineLayoutSet>,
+		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, PipelineLayoutError> {
 		log_trace_common!(
 			"Creating pipeline layout:",
@@ -87,9 +148,11 @@ impl PipelineLayout {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(layout);
 		Ok(Vrc::new(PipelineLayout {
 			device,
 			layout,
+			set_layouts,
 			host_memory_allocator
 		}))
 	}
@@ -97,6 +160,11 @@ impl PipelineLayout {
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
+
+	/// Descriptor set layouts indexed by set number.
+	pub fn set_layouts(&self) -> &[PipelineLayoutSet] {
+		&self.set_layouts
+	}
 }
 impl_common_handle_traits! {
 	impl HasHandle<vk::PipelineLayout>, Deref, Borrow, Eq, Hash, Ord for PipelineLayout {
@@ -106,6 +174,7 @@ impl_common_handle_traits! {
 impl Drop for PipelineLayout {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.layout);
 
 		unsafe {
 			self.device.destroy_pipeline_layout(
@@ -120,6 +189,7 @@ impl fmt::Debug for PipelineLayout {
 		f.debug_struct("PipelineLayout")
 			.field("device", &self.device)
 			.field("layout", &self.safe_handle())
+			.field("set_layouts", &self.set_layouts)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
@@ -127,3 +197,178 @@ impl fmt::Debug for PipelineLayout {
 			.finish()
 	}
 }
+
+/// Builder of pipeline layouts from descriptor set layouts and typed push constants.
+///
+/// Identical push constant ranges are only added once. Building fails if two ranges include the same stage
+/// or a range exceeds `maxPushConstantsSize`.
+#[derive(Debug)]
+pub struct PipelineLayoutBuilder {
+	device: Vrc<Device>,
+	set_layouts: Vec<Vrc<DescriptorSetLayout>>,
+	push_constant_ranges: Vec<PushConstantRange>
+}
+impl PipelineLayoutBuilder {
+	pub fn new(device: Vrc<Device>) -> Self {
+		PipelineLayoutBuilder { device, set_layouts: Vec::new(), push_constant_ranges: Vec::new() }
+	}
+
+	/// Appends `layout` as the next descriptor set.
+	pub fn set_layout(mut self, layout: Vrc<DescriptorSetLayout>) -> Self {
+		self.set_layouts.push(layout);
+		self
+	}
+
+	/// Adds the push constant range of `P`, see `PushConstantsTrait::layout_range`.
+	pub fn push_constants<P: PushConstantsTrait>(self) -> Self {
+		self.push_constant_range(P::layout_range())
+	}
+
+	/// Adds `range` unless an identical range was already added.
+	pub fn push_constant_range(mut self, range: PushConstantRange) -> Self {
+		let is_duplicate = self
+			.push_constant_ranges
+			.iter()
+			.any(|r| r.stage_flags == range.stage_flags && r.offset == range.offset && r.size == range.size);
+		if !is_duplicate {
+			self.push_constant_ranges.push(range);
+		}
+
+		self
+	}
+
+	/// Validates the push constant ranges.
+	pub fn validate(&self) -> Result<(), PipelineLayoutError> {
+		let max_size = self.device.physical_properties().limits.max_push_constants_size;
+
+		for (index, range) in self.push_constant_ranges.iter().enumerate() {
+			if range.offset + range.size > max_size {
+				return Err(PipelineLayoutError::PushConstantRangeTooLarge(index))
+			}
+
+			for (other_index, other) in self.push_constant_ranges.iter().enumerate().skip(index + 1) {
+				let common = range.stage_flags & other.stage_flags;
+				if !common.is_empty() {
+					return Err(PipelineLayoutError::PushConstantStagesOverlap(
+						index,
+						other_index,
+						common
+					))
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Validates and creates a new pipeline layout.
+	pub fn build(self, host_memory_allocator: HostMemoryAllocator) -> Result<Vrc<PipelineLayout>, PipelineLayoutError> {
+		self.validate()?;
+
+		let set_layouts: Vec<&DescriptorSetLayout> = self.set_layouts.iter().map(|l| l.as_ref()).collect();
+		PipelineLayout::new(
+			self.device,
+			set_layouts,
+			self.push_constant_ranges,
+			host_memory_allocator
+		)
+	}
+
+	/// Validates and returns a pipeline layout from `cache`, creating it if no identical layout was cached yet.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `cache` belongs to a different device.
+	pub fn build_cached(
+		self,
+		cache: &PipelineLayoutCache,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<PipelineLayout>, PipelineLayoutError> {
+		assert!(
+			cache.device == self.device,
+			"pipeline layout cache belongs to a different device"
+		);
+		self.validate()?;
+
+		let key = PipelineLayoutKey {
+			set_layouts: self.set_layouts.iter().map(|l| l.handle()).collect(),
+			push_constant_ranges: self
+				.push_constant_ranges
+				.iter()
+				.map(|r| (r.stage_flags, r.offset, r.size))
+				.collect()
+		};
+
+		let mut layouts = cache.layouts.lock().expect("vutex poisoned");
+		if let Some(layout) = layouts.get(&key).and_then(|cached| cached.layout.upgrade()) {
+			return Ok(layout)
+		}
+
+		let set_layouts = self.set_layouts.clone();
+		let layout = self.build(host_memory_allocator)?;
+		layouts.retain(|_, cached| cached.layout.strong_count() > 0);
+		layouts.insert(key, CachedPipelineLayout { _set_layouts: set_layouts, layout: Vrc::downgrade(&layout) });
+
+		Ok(layout)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PipelineLayoutKey {
+	set_layouts: Vec<vk::DescriptorSetLayout>,
+	push_constant_ranges: Vec<(vk::ShaderStageFlags, u32, u32)>
+}
+
+struct CachedPipelineLayout {
+	/// Keeps the handles in the key from being destroyed and reused by other set layouts.
+	_set_layouts: Vec<Vrc<DescriptorSetLayout>>,
+	layout: Vweak<PipelineLayout>
+}
+
+/// Cache of pipeline layouts of one device, see `PipelineLayoutBuilder::build_cached`.
+///
+/// Layouts are keyed by the descriptor set layout handles and push constant ranges. The cache only holds weak references
+/// to the pipeline layouts, so a layout is destroyed once it is no longer used and created again on the next request.
+/// Each entry holds the descriptor set layouts of its key until the entry is pruned after its layout is destroyed or the cache is cleared,
+/// so that their handles cannot be reused while the entry exists.
+/// The cache must not be stored in the device to avoid a reference cycle.
+pub struct PipelineLayoutCache {
+	device: Vrc<Device>,
+	layouts: Vutex<VHashMap<PipelineLayoutKey, CachedPipelineLayout>>
+}
+impl PipelineLayoutCache {
+	pub fn new(device: Vrc<Device>) -> Self {
+		PipelineLayoutCache { device, layouts: Vutex::new(VHashMap::default()) }
+	}
+
+	/// Returns the number of cached layouts that are still alive.
+	pub fn len(&self) -> usize {
+		self.layouts
+			.lock()
+			.expect("vutex poisoned")
+			.values()
+			.filter(|cached| cached.layout.strong_count() > 0)
+			.count()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Forgets all cached layouts.
+	pub fn clear(&self) {
+		self.layouts.lock().expect("vutex poisoned").clear();
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl fmt::Debug for PipelineLayoutCache {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PipelineLayoutCache")
+			.field("device", &self.device)
+			.field("count", &self.len())
+			.finish()
+	}
+}
@@ -0,0 +1,466 @@
+//! Graphics pipeline libraries using `VK_EXT_graphics_pipeline_library`.
+//!
+//! A graphics pipeline is split into four parts which can be compiled independently as libraries:
+//! vertex input interface, pre-rasterization shaders, fragment shader and fragment output interface.
+//! Complete pipelines are then linked from one library of each part, which is much faster than compiling the whole pipeline,
+//! so that draw permutations can be linked on demand instead of being compiled ahead of time.
+//!
+//! `GraphicsPipelineLinker` memoizes linked pipelines by their libraries.
+//!
+//! ash does not provide definitions for this extension, so the structures are defined here. The extension and
+//! `VK_KHR_pipeline_library` must be enabled on the device. Pipeline layouts used by libraries with different descriptor sets
+//! should be created with `PIPELINE_LAYOUT_CREATE_INDEPENDENT_SETS_BIT_EXT`.
+
+use std::{
+	ffi::CStr,
+	fmt,
+	ops::{BitOr, BitOrAssign, Deref},
+	os::raw::c_void
+};
+
+use ash::vk;
+
+use super::{
+	cache::PipelineCache,
+	error::GraphicsPipelineLibraryError,
+	feedback::{FeedbackChain, PipelineCreationFeedback},
+	graphics::GraphicsPipeline,
+	layout::PipelineLayout
+};
+use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Vrc, Vutex};
+
+/// Name of the `VK_EXT_graphics_pipeline_library` extension.
+pub fn extension_name() -> &'static CStr {
+	unsafe { CStr::from_bytes_with_nul_unchecked(b"VK_EXT_graphics_pipeline_library\0") }
+}
+
+/// `VK_STRUCTURE_TYPE_GRAPHICS_PIPELINE_LIBRARY_CREATE_INFO_EXT`
+pub const STRUCTURE_TYPE_GRAPHICS_PIPELINE_LIBRARY_CREATE_INFO_EXT: vk::StructureType = vk::StructureType::from_raw(1000320002);
+/// `VK_PIPELINE_CREATE_LINK_TIME_OPTIMIZATION_BIT_EXT`
+pub const PIPELINE_CREATE_LINK_TIME_OPTIMIZATION_BIT_EXT: vk::PipelineCreateFlags = vk::PipelineCreateFlags::from_raw(0x0000_0400);
+/// `VK_PIPELINE_CREATE_RETAIN_LINK_TIME_OPTIMIZATION_INFO_BIT_EXT`
+pub const PIPELINE_CREATE_RETAIN_LINK_TIME_OPTIMIZATION_INFO_BIT_EXT: vk::PipelineCreateFlags = vk::PipelineCreateFlags::from_raw(0x0080_0000);
+/// `VK_PIPELINE_LAYOUT_CREATE_INDEPENDENT_SETS_BIT_EXT`
+pub const PIPELINE_LAYOUT_CREATE_INDEPENDENT_SETS_BIT_EXT: vk::PipelineLayoutCreateFlags = vk::PipelineLayoutCreateFlags::from_raw(0x2);
+
+/// `VkGraphicsPipelineLibraryCreateInfoEXT`, extends `vk::GraphicsPipelineCreateInfo`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GraphicsPipelineLibraryCreateInfoEXT {
+	pub s_type: vk::StructureType,
+	pub p_next: *const c_void,
+	pub flags: u32
+}
+impl GraphicsPipelineLibraryCreateInfoEXT {
+	pub fn new(parts: GraphicsPipelineLibraryParts) -> Self {
+		GraphicsPipelineLibraryCreateInfoEXT {
+			s_type: STRUCTURE_TYPE_GRAPHICS_PIPELINE_LIBRARY_CREATE_INFO_EXT,
+			p_next: std::ptr::null(),
+			flags: parts.as_raw()
+		}
+	}
+}
+unsafe impl vk::ExtendsGraphicsPipelineCreateInfo for GraphicsPipelineLibraryCreateInfoEXT {}
+
+/// Parts of a graphics pipeline, `VkGraphicsPipelineLibraryFlagsEXT`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphicsPipelineLibraryParts(u32);
+impl GraphicsPipelineLibraryParts {
+	/// Vertex input and input assembly state.
+	pub const VERTEX_INPUT_INTERFACE: Self = GraphicsPipelineLibraryParts(0x1);
+	/// Vertex, tessellation and geometry shaders, viewport and rasterization state.
+	pub const PRE_RASTERIZATION_SHADERS: Self = GraphicsPipelineLibraryParts(0x2);
+	/// Fragment shader, depth stencil state and sample shading.
+	pub const FRAGMENT_SHADER: Self = GraphicsPipelineLibraryParts(0x4);
+	/// Color blend state, multisample state and attachment formats.
+	pub const FRAGMENT_OUTPUT_INTERFACE: Self = GraphicsPipelineLibraryParts(0x8);
+
+	pub const fn empty() -> Self {
+		GraphicsPipelineLibraryParts(0)
+	}
+
+	pub const fn all() -> Self {
+		GraphicsPipelineLibraryParts(0xF)
+	}
+
+	pub const fn as_raw(self) -> u32 {
+		self.0
+	}
+
+	pub const fn is_empty(self) -> bool {
+		self.0 == 0
+	}
+
+	pub const fn contains(self, other: Self) -> bool {
+		self.0 & other.0 == other.0
+	}
+
+	pub const fn intersects(self, other: Self) -> bool {
+		self.0 & other.0 != 0
+	}
+}
+impl BitOr for GraphicsPipelineLibraryParts {
+	type Output = Self;
+
+	fn bitor(self, rhs: Self) -> Self {
+		GraphicsPipelineLibraryParts(self.0 | rhs.0)
+	}
+}
+impl BitOrAssign for GraphicsPipelineLibraryParts {
+	fn bitor_assign(&mut self, rhs: Self) {
+		self.0 |= rhs.0;
+	}
+}
+
+/// Graphics pipeline library containing one or more pipeline parts, see the module documentation.
+pub struct GraphicsPipelineLibrary {
+	device: Vrc<Device>,
+	pipeline: vk::Pipeline,
+	parts: GraphicsPipelineLibraryParts,
+	creation_feedback: Option<PipelineCreationFeedback>,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl GraphicsPipelineLibrary {
+	/// Creates a vertex input interface library.
+	pub fn vertex_input(
+		device: Vrc<Device>,
+		bindings: &[vk::VertexInputBindingDescription],
+		attributes: &[vk::VertexInputAttributeDescription],
+		topology: vk::PrimitiveTopology,
+		primitive_restart_enable: bool,
+		dynamic_states: &[vk::DynamicState],
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, GraphicsPipelineLibraryError> {
+		let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+			.vertex_binding_descriptions(bindings)
+			.vertex_attribute_descriptions(attributes);
+		let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+			.topology(topology)
+			.primitive_restart_enable(primitive_restart_enable);
+		let dynamic_state = vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+		let create_info = vk::GraphicsPipelineCreateInfo::builder()
+			.vertex_input_state(&vertex_input_state)
+			.input_assembly_state(&input_assembly_state)
+			.dynamic_state(&dynamic_state);
+
+		unsafe {
+			Self::from_create_info(
+				device,
+				GraphicsPipelineLibraryParts::VERTEX_INPUT_INTERFACE,
+				create_info,
+				false,
+				cache,
+				host_memory_allocator
+			)
+		}
+	}
+
+	/// Creates a library containing `parts` from `create_info`.
+	///
+	/// The library flags and the library create info are added to `create_info`. Only the state belonging to `parts` is read.
+	///
+	/// If `retain_link_time_optimization_info` is set, the library can be linked with link time optimization.
+	///
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.3-extensions/man/html/VkGraphicsPipelineLibraryCreateInfoEXT.html>.
+	pub unsafe fn from_create_info(
+		device: Vrc<Device>,
+		parts: GraphicsPipelineLibraryParts,
+		create_info: impl Deref<Target = vk::GraphicsPipelineCreateInfo>,
+		retain_link_time_optimization_info: bool,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, GraphicsPipelineLibraryError> {
+		if parts.is_empty() {
+			return Err(GraphicsPipelineLibraryError::PartsEmpty)
+		}
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if !device.is_extension_enabled(extension_name()) {
+				return Err(GraphicsPipelineLibraryError::ExtensionNotEnabled)
+			}
+			if let Some(cache) = cache {
+				if cache.device() != &device {
+					return Err(GraphicsPipelineLibraryError::DeviceMismatch)
+				}
+			}
+		}
+
+		let mut library_info = GraphicsPipelineLibraryCreateInfoEXT::new(parts);
+		let mut create_info = *create_info.deref();
+		library_info.p_next = create_info.p_next;
+		create_info.p_next = &library_info as *const GraphicsPipelineLibraryCreateInfoEXT as *const c_void;
+		create_info.flags |= vk::PipelineCreateFlags::LIBRARY_KHR;
+		if retain_link_time_optimization_info {
+			create_info.flags |= PIPELINE_CREATE_RETAIN_LINK_TIME_OPTIMIZATION_INFO_BIT_EXT;
+		}
+		let mut feedback = FeedbackChain::for_device(&device, create_info.stage_count);
+		if let Some(feedback) = feedback.as_mut() {
+			feedback.chain(&mut create_info.p_next);
+		}
+
+		log_trace_common!(
+			"Creating graphics pipeline library:",
+			device,
+			parts,
+			create_info.flags,
+			create_info.layout,
+			create_info.render_pass,
+			create_info.subpass,
+			cache,
+			host_memory_allocator
+		);
+		let pipeline = device
+			.create_graphics_pipelines(
+				cache.map(|c| c.handle()).unwrap_or_default(),
+				&[create_info],
+				host_memory_allocator.as_ref()
+			)
+			.map_err(|e| e.1)?
+			.into_iter()
+			.next()
+			.unwrap();
+
+		device.child_registry().register(pipeline);
+		Ok(Vrc::new(GraphicsPipelineLibrary {
+			device,
+			pipeline,
+			parts,
+			creation_feedback: feedback.map(FeedbackChain::into_feedback),
+			host_memory_allocator
+		}))
+	}
+
+	/// Links a complete graphics pipeline from `libraries`, which must together contain each part exactly once.
+	///
+	/// With `link_time_optimization`, the implementation may optimize across the parts, which is slower.
+	/// The libraries must then have been created with `retain_link_time_optimization_info`.
+	pub fn link(
+		device: Vrc<Device>,
+		libraries: &[&GraphicsPipelineLibrary],
+		layout: &PipelineLayout,
+		link_time_optimization: bool,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<GraphicsPipeline>, GraphicsPipelineLibraryError> {
+		let mut parts = GraphicsPipelineLibraryParts::empty();
+		for library in libraries.iter() {
+			if parts.intersects(library.parts) {
+				return Err(GraphicsPipelineLibraryError::PartsOverlap(library.parts))
+			}
+			parts |= library.parts;
+		}
+		if parts != GraphicsPipelineLibraryParts::all() {
+			return Err(GraphicsPipelineLibraryError::PartsMissing(parts))
+		}
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if libraries.iter().any(|library| library.device != device) || layout.device() != &device {
+				return Err(GraphicsPipelineLibraryError::DeviceMismatch)
+			}
+		}
+
+		let handles: Vec<_> = libraries.iter().map(|library| library.pipeline).collect();
+		let library_info = vk::PipelineLibraryCreateInfoKHR::builder().libraries(&handles);
+		let mut create_info = vk::GraphicsPipelineCreateInfo::builder().layout(layout.handle());
+		if link_time_optimization {
+			create_info = create_info.flags(PIPELINE_CREATE_LINK_TIME_OPTIMIZATION_BIT_EXT);
+		}
+		// ash only allows chaining the library info into ray tracing pipeline create infos
+		create_info.p_next = library_info.deref() as *const vk::PipelineLibraryCreateInfoKHR as *const c_void;
+		let mut feedback = FeedbackChain::for_device(&device, 0);
+
+		log_trace_common!(
+			"Linking graphics pipeline:",
+			device,
+			handles,
+			layout,
+			link_time_optimization,
+			cache,
+			host_memory_allocator
+		);
+		unsafe {
+			if let Some(feedback) = feedback.as_mut() {
+				feedback.chain(&mut create_info.p_next);
+			}
+
+			let pipeline = device
+				.create_graphics_pipelines(
+					cache.map(|c| c.handle()).unwrap_or_default(),
+					&[*create_info],
+					host_memory_allocator.as_ref()
+				)
+				.map_err(|e| e.1)?
+				.into_iter()
+				.next()
+				.unwrap();
+
+			Ok(GraphicsPipeline::from_existing(
+				device,
+				pipeline,
+				feedback.map(FeedbackChain::into_feedback),
+				host_memory_allocator
+			))
+		}
+	}
+
+	pub const fn parts(&self) -> GraphicsPipelineLibraryParts {
+		self.parts
+	}
+
+	/// Returns the creation feedback, if `VK_EXT_pipeline_creation_feedback` is enabled, see the `feedback` module.
+	pub const fn creation_feedback(&self) -> Option<&PipelineCreationFeedback> {
+		self.creation_feedback.as_ref()
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl_common_handle_traits! {
+	impl HasHandle<vk::Pipeline>, Deref, Borrow, Eq, Hash, Ord for GraphicsPipelineLibrary {
+		target = { pipeline }
+	}
+}
+impl Drop for GraphicsPipelineLibrary {
+	fn drop(&mut self) {
+		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.pipeline);
+
+		unsafe {
+			self.device.destroy_pipeline(
+				self.pipeline,
+				self.host_memory_allocator.as_ref()
+			)
+		}
+	}
+}
+impl fmt::Debug for GraphicsPipelineLibrary {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("GraphicsPipelineLibrary")
+			.field("device", &self.device)
+			.field("pipeline", &self.safe_handle())
+			.field("parts", &self.parts)
+			.field("creation_feedback", &self.creation_feedback)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
+
+/// Links graphics pipelines from libraries on demand and memoizes them by their libraries.
+///
+/// Memoized pipelines keep their libraries alive until `clear` is called.
+pub struct GraphicsPipelineLinker {
+	layout: Vrc<PipelineLayout>,
+	cache: Option<Vrc<PipelineCache>>,
+	link_time_optimization: bool,
+	host_memory_allocator: HostMemoryAllocator,
+
+	linked: Vutex<crate::util::hash::VHashMap<Vec<vk::Pipeline>, (Vec<Vrc<GraphicsPipelineLibrary>>, Vrc<GraphicsPipeline>)>>
+}
+impl GraphicsPipelineLinker {
+	pub fn new(
+		layout: Vrc<PipelineLayout>,
+		cache: Option<Vrc<PipelineCache>>,
+		link_time_optimization: bool,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Self {
+		GraphicsPipelineLinker {
+			layout,
+			cache,
+			link_time_optimization,
+			host_memory_allocator,
+
+			linked: Vutex::new(Default::default())
+		}
+	}
+
+	/// Returns the pipeline linked from `libraries`, linking it if it was not linked yet.
+	///
+	/// The order of `libraries` does not matter.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn get_or_link(&self, libraries: &[&Vrc<GraphicsPipelineLibrary>]) -> Result<Vrc<GraphicsPipeline>, GraphicsPipelineLibraryError> {
+		let mut key: Vec<_> = libraries.iter().map(|library| library.pipeline).collect();
+		key.sort_unstable();
+
+		let mut linked = self.linked.lock().expect("vutex poisoned");
+		if let Some((_, pipeline)) = linked.get(&key) {
+			return Ok(pipeline.clone())
+		}
+
+		let library_refs: Vec<&GraphicsPipelineLibrary> = libraries.iter().map(|library| library.as_ref()).collect();
+		let pipeline = GraphicsPipelineLibrary::link(
+			self.layout.device().clone(),
+			&library_refs,
+			&self.layout,
+			self.link_time_optimization,
+			self.cache.as_deref(),
+			self.host_memory_allocator
+		)?;
+		linked.insert(
+			key,
+			(
+				libraries.iter().map(|&library| library.clone()).collect(),
+				pipeline.clone()
+			)
+		);
+
+		Ok(pipeline)
+	}
+
+	/// Returns the number of linked pipelines.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn len(&self) -> usize {
+		self.linked.lock().expect("vutex poisoned").len()
+	}
+
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn is_empty(&self) -> bool {
+		self.linked.lock().expect("vutex poisoned").is_empty()
+	}
+
+	/// Drops all memoized pipelines and the libraries they keep alive.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn clear(&self) {
+		self.linked.lock().expect("vutex poisoned").clear();
+	}
+
+	pub const fn layout(&self) -> &Vrc<PipelineLayout> {
+		&self.layout
+	}
+}
+impl fmt::Debug for GraphicsPipelineLinker {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("GraphicsPipelineLinker")
+			.field("layout", &self.layout)
+			.field("cache", &self.cache)
+			.field(
+				"link_time_optimization",
+				&self.link_time_optimization
+			)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.field("linked", &self.linked)
+			.finish()
+	}
+}
@@ -1,5 +1,8 @@
+pub mod cache;
 pub mod compute;
 pub mod error;
+pub mod feedback;
 pub mod graphics;
 pub mod layout;
+pub mod library;
 pub mod params;
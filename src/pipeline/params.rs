@@ -232,6 +232,74 @@ impl Into<(bool, vk::LogicOp)> for BlendLogicOp {
 	}
 }
 
+/// Ready-made color blend attachment states.
+///
+/// All presets write all color components. The mask can be changed by calling `color_write_mask` on the returned builder
+/// or by using the `preset & mask` syntax of `color_blend_state_expr!`.
+pub mod blend_preset {
+	use ash::vk;
+
+	fn enabled(
+		src_color: vk::BlendFactor,
+		dst_color: vk::BlendFactor,
+		src_alpha: vk::BlendFactor,
+		dst_alpha: vk::BlendFactor
+	) -> vk::PipelineColorBlendAttachmentStateBuilder<'static> {
+		vk::PipelineColorBlendAttachmentState::builder()
+			.blend_enable(true)
+			.src_color_blend_factor(src_color)
+			.dst_color_blend_factor(dst_color)
+			.color_blend_op(vk::BlendOp::ADD)
+			.src_alpha_blend_factor(src_alpha)
+			.dst_alpha_blend_factor(dst_alpha)
+			.alpha_blend_op(vk::BlendOp::ADD)
+			.color_write_mask(vk::ColorComponentFlags::RGBA)
+	}
+
+	/// Standard alpha blending with straight (non-premultiplied) alpha.
+	///
+	/// `color = src.rgb * src.a + dst.rgb * (1 - src.a)`, `alpha = src.a + dst.a * (1 - src.a)`
+	pub fn alpha_blend() -> vk::PipelineColorBlendAttachmentStateBuilder<'static> {
+		enabled(
+			vk::BlendFactor::SRC_ALPHA,
+			vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+			vk::BlendFactor::ONE,
+			vk::BlendFactor::ONE_MINUS_SRC_ALPHA
+		)
+	}
+
+	/// Alpha blending where the source color is already multiplied by its alpha.
+	///
+	/// `color = src.rgb + dst.rgb * (1 - src.a)`, `alpha = src.a + dst.a * (1 - src.a)`
+	pub fn premultiplied() -> vk::PipelineColorBlendAttachmentStateBuilder<'static> {
+		enabled(
+			vk::BlendFactor::ONE,
+			vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+			vk::BlendFactor::ONE,
+			vk::BlendFactor::ONE_MINUS_SRC_ALPHA
+		)
+	}
+
+	/// Additive blending.
+	///
+	/// `color = src.rgb + dst.rgb`, `alpha = src.a + dst.a`
+	pub fn additive() -> vk::PipelineColorBlendAttachmentStateBuilder<'static> {
+		enabled(
+			vk::BlendFactor::ONE,
+			vk::BlendFactor::ONE,
+			vk::BlendFactor::ONE,
+			vk::BlendFactor::ONE
+		)
+	}
+
+	/// Blending disabled, the source overwrites the destination.
+	pub fn opaque() -> vk::PipelineColorBlendAttachmentStateBuilder<'static> {
+		vk::PipelineColorBlendAttachmentState::builder()
+			.blend_enable(false)
+			.color_write_mask(vk::ColorComponentFlags::RGBA)
+	}
+}
+
 /// Expands to a tuple of `(vk::Viewport, vk::Rect2D)` or into a tuple of `([vk::Viewport], [vk::Rect2D], bool, bool)`.
 ///
 /// Syntax: `area offset? depth? scissor?`
@@ -432,6 +500,9 @@ macro_rules! viewport_scissor_expr {
 ///
 /// The macro also accepts the token `disabled & mask`, which returns a builder with blending disabled.
 ///
+/// Presets from [`blend_preset`](pipeline/params/blend_preset/index.html) can be used by name with an optional mask: `preset & mask?`,
+/// where `preset` is one of `alpha_blend`, `premultiplied`, `additive` or `opaque`.
+///
 /// Example:
 /// ```
 /// # use vulkayes_core::color_blend_state_expr;
@@ -446,6 +517,12 @@ macro_rules! viewport_scissor_expr {
 /// color_blend_state_expr!(
 /// 	disabled & vk::ColorComponentFlags::RGBA
 /// );
+/// // Standard alpha blending using a preset, doesn't mask anything
+/// color_blend_state_expr!(alpha_blend);
+/// // Premultiplied alpha blending, only writes color
+/// color_blend_state_expr!(
+/// 	premultiplied & vk::ColorComponentFlags::R | vk::ColorComponentFlags::G | vk::ColorComponentFlags::B
+/// );
 /// // Same as the first one, but variables are expressions instead of identifiers.
 /// color_blend_state_expr!(
 /// 	rgba(
@@ -475,7 +552,7 @@ macro_rules! color_blend_state_expr {
 			$crate::color_blend_state_expr!(
 				rgba(
 					(S * BlendFactor::$src_color_blend_factor) {BlendOp::$color_blend_op} (D * BlendFactor::$dst_color_blend_factor),
-					(S * BlendFactor::$src_alpha_blend_factor) {BlendOp::$color_blend_op} (D * BlendFactor::$dst_alpha_blend_factor)
+					(S * BlendFactor::$src_alpha_blend_factor) {BlendOp::$alpha_blend_op} (D * BlendFactor::$dst_alpha_blend_factor)
 				) & $color_write_mask
 			)
 		}
@@ -496,6 +573,13 @@ macro_rules! color_blend_state_expr {
 			.alpha_blend_op($alpha_blend_op)
 			.color_write_mask($color_write_mask)
 	};
+
+	(
+		$preset: ident $(& $color_write_mask: expr)?
+	) => {
+		$crate::pipeline::params::blend_preset::$preset()
+			$(.color_write_mask($color_write_mask))?
+	};
 }
 
 /// Graphics pipeline creation macro that makes it easier to specify parameters.
@@ -546,6 +630,11 @@ macro_rules! color_blend_state_expr {
 /// 	* `layout` - any value defining `fn handle(&self) -> vk::PipelineLayout`
 /// 	* `render_pass` - any value defining `fn handle(&self) -> vk::RenderPass`
 /// 	* `subpass?` - value of type `u32`, default: `0`
+/// 	* `rendering` - used instead of `render_pass` and `subpass` for dynamic rendering, block with fields:
+/// 		* `color_formats` - array of `vk::Format` values
+/// 		* `depth_format?` - value of type `vk::Format`, default: `vk::Format::UNDEFINED`
+/// 		* `stencil_format?` - value of type `vk::Format`, default: `vk::Format::UNDEFINED`
+/// 		* `view_mask?` - value of type `u32`, default: `0`
 ///
 /// Note that some sections are optional altogether, however, not specifying a section means it won't be included at all in the
 /// create info and no defaults will be provided (the struct pointer will be null). Commonly only the `Tessellation` section is left out,
@@ -917,6 +1006,51 @@ macro_rules! describe_graphics_pipeline {
 		;
 	};
 
+	(
+		@Deps($output_builder: expr)
+		layout: $layout: expr,
+		rendering: {
+			color_formats: [$($color_format: expr),* $(,)?]
+			$(, depth_format: $depth_format: expr)?
+			$(, stencil_format: $stencil_format: expr)?
+			$(, view_mask: $view_mask: expr)?
+			$(,)?
+		}
+		$(,)?
+	) => {
+		let layout: $crate::ash::vk::PipelineLayout = $layout.handle();
+
+		let color_formats: &[$crate::ash::vk::Format] = &[$($color_format),*];
+
+		#[allow(unused_assignments, unused_mut)]
+		let mut depth_format = $crate::ash::vk::Format::UNDEFINED;
+		$(
+			depth_format = $depth_format;
+		)?
+		#[allow(unused_assignments, unused_mut)]
+		let mut stencil_format = $crate::ash::vk::Format::UNDEFINED;
+		$(
+			stencil_format = $stencil_format;
+		)?
+		#[allow(unused_assignments, unused_mut)]
+		let mut view_mask: u32 = 0;
+		$(
+			view_mask = $view_mask;
+		)?
+
+		let mut rendering_info = $crate::ash::vk::PipelineRenderingCreateInfo::builder()
+			.view_mask(view_mask)
+			.color_attachment_formats(color_formats)
+			.depth_attachment_format(depth_format)
+			.stencil_attachment_format(stencil_format)
+		;
+
+		$output_builder = $output_builder
+			.layout(layout)
+			.push_next(&mut rendering_info)
+		;
+	};
+
 	(
 		let $create_info_variable_name: ident;
 
@@ -1060,6 +1194,7 @@ macro_rules! describe_compute_pipeline {
 		$(,)?
 	) => {
 		let specialization_info: Option<$crate::ash::vk::SpecializationInfoBuilder> = {
+			#[allow(unused_assignments, unused_mut)]
 			let mut info = None::<$crate::ash::vk::SpecializationInfoBuilder>;
 			$(
 				$(
@@ -1071,6 +1206,7 @@ macro_rules! describe_compute_pipeline {
 		};
 
 		let stage: $crate::ash::vk::PipelineShaderStageCreateInfoBuilder = {
+			#[allow(unused_assignments, unused_mut)]
 			let mut entry_name: $crate::shader::params::ShaderEntryPoint = Default::default();
 			$(
 				entry_name = $entry_name;
@@ -1292,4 +1428,49 @@ mod test {
 			p_dynamic_state[1]{p_dynamic_states[9]}
 		);
 	}
+
+	#[test]
+	fn test_graphics_pipeline_dynamic_rendering() {
+		struct LayoutHandle;
+		impl LayoutHandle {
+			fn handle(&self) -> vvk::PipelineLayout {
+				vvk::PipelineLayout::null()
+			}
+		}
+
+		describe_graphics_pipeline! {
+			let create_info;
+
+			Shaders {
+				stages: []
+				input: {}
+				topology: vvk::PrimitiveTopology::TRIANGLE_LIST
+			}
+
+			Rasterization {
+				polygon_mode: super::PolygonMode::default(),
+				depth_bias: super::DepthBias::Disabled
+			}
+
+			ColorBlend {
+				attachments: [{ alpha_blend }]
+			}
+
+			Deps {
+				layout: LayoutHandle,
+				rendering: {
+					color_formats: [vvk::Format::B8G8R8A8_SRGB],
+					depth_format: vvk::Format::D32_SFLOAT
+				}
+			}
+		};
+
+		assert_eq!(create_info.render_pass, vvk::RenderPass::null());
+		let rendering_info = unsafe { &*(create_info.p_next as *const vvk::PipelineRenderingCreateInfo) };
+		assert_eq!(rendering_info.s_type, vvk::StructureType::PIPELINE_RENDERING_CREATE_INFO);
+		assert_eq!(rendering_info.color_attachment_count, 1);
+		assert_eq!(unsafe { *rendering_info.p_color_attachment_formats }, vvk::Format::B8G8R8A8_SRGB);
+		assert_eq!(rendering_info.depth_attachment_format, vvk::Format::D32_SFLOAT);
+		assert_eq!(rendering_info.stencil_attachment_format, vvk::Format::UNDEFINED);
+	}
 }
@@ -3,9 +3,11 @@ pub use crate::{
 		buffer::{
 			recording::{
 				common::CommandBufferRecordingLockCommon,
+				inside::clear::{ClearAttachment, ClearRect},
 				outside::{
 					barrier::{BufferMemoryBarrier, ImageMemoryBarrier, MemoryBarrier},
-					copy::{BufferBufferCopy, BufferImageCopy, ImageSubresourceLayers}
+					copy::{BufferBufferCopy, BufferImageCopy, ImageBlit, ImageSubresourceLayers},
+					transition::LayoutTransition
 				},
 				CommandBufferBeginInfo,
 				CommandBufferRecordingLockInsideRenderPass,
@@ -13,9 +15,10 @@ pub use crate::{
 			},
 			CommandBuffer
 		},
-		pool::CommandPool
+		pool::{CommandPool, ThreadLocalPools}
 	},
 	descriptor::{
+		allocator::DescriptorAllocator,
 		layout::{
 			params::{DescriptorSetLayoutBinding, DescriptorSetLayoutBindingGenericType},
 			DescriptorSetLayout
@@ -36,8 +39,9 @@ pub use crate::{
 			DescriptorSet
 		}
 	},
-	device::{Device, QueueCreateInfo},
+	device::{builder::DeviceBuilder, features::DeviceFeatures, Device, QueueCreateInfo},
 	entry::Entry,
+	frame::TransientResources,
 	framebuffer::Framebuffer,
 	instance::{ApplicationInfo, Instance},
 	memory::{
@@ -51,20 +55,26 @@ pub use crate::{
 	pipeline::{
 		compute::ComputePipeline,
 		graphics::GraphicsPipeline,
-		layout::{PipelineLayout, PushConstantRange},
+		layout::{PipelineLayout, PipelineLayoutBuilder, PipelineLayoutCache, PushConstantRange},
 		params::{BlendLogicOp, DepthBias, DepthBoundsTest, DepthTest, PolygonMode, StencilTest}
 	},
-	queue::{sharing_mode::SharingMode, Queue},
+	queue::{
+		batch::SubmitBatch,
+		sharing_mode::SharingMode,
+		sparse::{SparseBufferBinds, SparseBufferMemoryBind, SparseImageBinds, SparseImageMemoryBind, SparseImageOpaqueBinds},
+		Queue
+	},
 	render_pass::{
-		params::{AttachmentOps, SubpassDescription},
+		params::{AttachmentOps, SubpassDependency, SubpassDescription},
 		RenderPass
 	},
 	resource::{
-		buffer::{params::BufferAllocatorParams, view::BufferView, Buffer},
+		buffer::{params::BufferAllocatorParams, typed::TypedBuffer, view::BufferView, Buffer},
 		image::{
 			layout::{
 				ImageLayoutAttachment,
 				ImageLayoutClearColorImage,
+				ImageLayoutClearDepthStencilImage,
 				ImageLayoutSource,
 				ImageLayoutDestination,
 				ImageLayoutFinal,
@@ -93,19 +103,20 @@ pub use crate::{
 	},
 	surface::Surface,
 	swapchain::{
-		image::{SwapchainCreateImageInfo, SwapchainImage},
+		image::{AcquiredImage, SwapchainCreateImageInfo, SwapchainImage},
+		target::{HeadlessTarget, PresentTarget},
 		AcquireSynchronization,
 		Swapchain,
 		SwapchainCreateInfo
 	},
 	sync::{
-		fence::Fence,
+		fence::{pool::FencePool, Fence},
 		semaphore::{BinarySemaphore, Semaphore}
 	},
 	util::{
 		fmt::VkVersion,
 		handle::{HasHandle, HasSynchronizedHandle, SafeHandle},
-		sync::{Vrc, Vutex, VutexGuard},
+		sync::{Vrc, Vutex, VutexGuard, Vweak},
 		transparent::Transparent
 	}
 };
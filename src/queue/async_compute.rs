@@ -0,0 +1,295 @@
+//! Overlapping compute work with graphics work on separate queues.
+//!
+//! In the usual async compute pattern, compute work producing data for a frame runs on a compute queue while the graphics queue
+//! is still busy with the previous frame. The graphics submission of the frame then waits for the compute submission at the stages
+//! that consume its results, and the next compute submission of the same frame slot waits for that graphics submission
+//! so that compute does not overwrite data graphics still reads.
+//!
+//! `AsyncComputeContext` owns the semaphores for each frame in flight and inserts these waits and signals.
+//!
+//! When the compute and graphics queues are from different families, resources written by compute with exclusive sharing mode
+//! must additionally be transferred to the graphics family, see `buffer_to_graphics` and `image_to_graphics`.
+
+use std::{fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use super::{error::QueueSubmitError, Queue};
+use crate::{
+	command::{error::OwnershipTransferError, ownership::OwnershipTransfer},
+	prelude::{
+		Buffer,
+		CommandBuffer,
+		Fence,
+		HasHandle,
+		HostMemoryAllocator,
+		Image,
+		ImageLayoutFinal,
+		ImageSubresourceRange,
+		Semaphore,
+		Vrc,
+		Vutex
+	},
+	sync::semaphore::{error::SemaphoreError, BinarySemaphore}
+};
+
+struct FrameSemaphores {
+	compute_finished: BinarySemaphore,
+	graphics_finished: BinarySemaphore
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct FrameState {
+	/// `compute_finished` was signaled and not waited on yet.
+	compute_pending: bool,
+	/// `graphics_finished` was signaled and not waited on yet.
+	graphics_pending: bool
+}
+
+/// Graphics and compute queue pair with semaphores preconfigured for async compute, see the module documentation.
+pub struct AsyncComputeContext {
+	graphics_queue: Vrc<Queue>,
+	compute_queue: Vrc<Queue>,
+
+	semaphores: Vec<FrameSemaphores>,
+	states: Vutex<Vec<FrameState>>
+}
+impl AsyncComputeContext {
+	/// Creates a new context with semaphores for `frames_in_flight` frame slots.
+	pub fn new(
+		graphics_queue: Vrc<Queue>,
+		compute_queue: Vrc<Queue>,
+		frames_in_flight: usize,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, SemaphoreError> {
+		let semaphores = (0 .. frames_in_flight.max(1))
+			.map(|_| {
+				Ok(FrameSemaphores {
+					compute_finished: Semaphore::binary(
+						graphics_queue.device().clone(),
+						host_memory_allocator
+					)?,
+					graphics_finished: Semaphore::binary(
+						graphics_queue.device().clone(),
+						host_memory_allocator
+					)?
+				})
+			})
+			.collect::<Result<Vec<_>, SemaphoreError>>()?;
+		let states = Vutex::new(vec![FrameState::default(); semaphores.len()]);
+
+		Ok(AsyncComputeContext { graphics_queue, compute_queue, semaphores, states })
+	}
+
+	unsafe fn submit(
+		queue: &Queue,
+		wait_for: &[(vk::Semaphore, vk::PipelineStageFlags)],
+		buffers: &[&CommandBuffer],
+		signal_after: &[vk::Semaphore],
+		fence: Option<&Fence>
+	) -> Result<(), QueueSubmitError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			for cb in buffers.iter() {
+				if cb.pool().queue_family_index() != queue.queue_family_index() {
+					return Err(QueueSubmitError::QueueFamilyMismatch)
+				}
+			}
+		}
+
+		let wait_semaphores = collect_iter_faster!(wait_for.iter().map(|(semaphore, _)| *semaphore), 4);
+		let wait_stages = collect_iter_faster!(wait_for.iter().map(|(_, stages)| *stages), 4);
+		let buffers_locks = collect_iter_faster!(buffers.iter().map(|b| b.lock().expect("vutex poisoned")), 4);
+		let buffers_raw = collect_iter_faster!(buffers_locks.iter().map(|l| **l), 4);
+
+		let submit_info = vk::SubmitInfo::builder()
+			.wait_semaphores(wait_semaphores.as_slice())
+			.wait_dst_stage_mask(wait_stages.as_slice())
+			.command_buffers(buffers_raw.as_slice())
+			.signal_semaphores(signal_after)
+			.build();
+
+		queue.submit_raw([submit_info], fence)
+	}
+
+	/// Submits `buffers` to the compute queue for frame slot `frame`.
+	///
+	/// The submission waits for the previous graphics submission of the same frame slot at the compute shader stage
+	/// and signals the graphics submission of this frame.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `frame` is out of range or if the `Vutex` is poisoned.
+	pub fn submit_compute_before_graphics(
+		&self,
+		frame: usize,
+		wait_for: &[(&Semaphore, vk::PipelineStageFlags)],
+		buffers: &[&CommandBuffer],
+		signal_after: &[&Semaphore],
+		fence: Option<&Fence>
+	) -> Result<(), QueueSubmitError> {
+		let mut states = self.states.lock().expect("vutex poisoned");
+		let state = &mut states[frame];
+		let semaphores = &self.semaphores[frame];
+
+		let mut waits = collect_iter_faster!(wait_for.iter().map(|(semaphore, stages)| (semaphore.handle(), *stages)), 4);
+		if state.graphics_pending {
+			waits.push((
+				semaphores.graphics_finished.handle(),
+				vk::PipelineStageFlags::COMPUTE_SHADER
+			));
+		}
+		if state.compute_pending {
+			// Unsignal without blocking anything so that it can be signaled again,
+			// the new signal covers all previously submitted compute work
+			waits.push((
+				semaphores.compute_finished.handle(),
+				vk::PipelineStageFlags::BOTTOM_OF_PIPE
+			));
+		}
+		let signals = collect_iter_faster!(
+			signal_after
+				.iter()
+				.map(|semaphore| semaphore.handle())
+				.chain(std::iter::once(semaphores.compute_finished.handle())),
+			4
+		);
+
+		unsafe {
+			Self::submit(
+				&self.compute_queue,
+				waits.as_slice(),
+				buffers,
+				signals.as_slice(),
+				fence
+			)?;
+		}
+		state.graphics_pending = false;
+		state.compute_pending = true;
+
+		Ok(())
+	}
+
+	/// Submits `buffers` to the graphics queue for frame slot `frame`.
+	///
+	/// If compute work was submitted for this frame slot, the submission waits for it at `consume_stages`,
+	/// which should be the stages reading the compute results, for example `VERTEX_INPUT` for generated vertices.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `frame` is out of range or if the `Vutex` is poisoned.
+	pub fn submit_graphics_waiting_compute(
+		&self,
+		frame: usize,
+		consume_stages: vk::PipelineStageFlags,
+		wait_for: &[(&Semaphore, vk::PipelineStageFlags)],
+		buffers: &[&CommandBuffer],
+		signal_after: &[&Semaphore],
+		fence: Option<&Fence>
+	) -> Result<(), QueueSubmitError> {
+		let mut states = self.states.lock().expect("vutex poisoned");
+		let state = &mut states[frame];
+		let semaphores = &self.semaphores[frame];
+
+		let mut waits = collect_iter_faster!(wait_for.iter().map(|(semaphore, stages)| (semaphore.handle(), *stages)), 4);
+		if state.compute_pending {
+			waits.push((semaphores.compute_finished.handle(), consume_stages));
+		}
+		if state.graphics_pending {
+			// Unsignal without blocking anything so that it can be signaled again,
+			// the new signal covers all previously submitted graphics work
+			waits.push((
+				semaphores.graphics_finished.handle(),
+				vk::PipelineStageFlags::BOTTOM_OF_PIPE
+			));
+		}
+		let signals = collect_iter_faster!(
+			signal_after
+				.iter()
+				.map(|semaphore| semaphore.handle())
+				.chain(std::iter::once(semaphores.graphics_finished.handle())),
+			4
+		);
+
+		unsafe {
+			Self::submit(
+				&self.graphics_queue,
+				waits.as_slice(),
+				buffers,
+				signals.as_slice(),
+				fence
+			)?;
+		}
+		state.compute_pending = false;
+		state.graphics_pending = true;
+
+		Ok(())
+	}
+
+	/// Returns whether the queues are from different families and exclusive resources need ownership transfers.
+	pub fn needs_ownership_transfer(&self) -> bool {
+		self.graphics_queue.queue_family_index() != self.compute_queue.queue_family_index()
+	}
+
+	/// Creates a transfer of `buffer` range written by compute to the graphics queue family.
+	///
+	/// Record the release into the compute command buffers and the acquire into the graphics command buffers.
+	pub fn buffer_to_graphics<'a>(
+		&self,
+		buffer: &'a Buffer,
+		offset: vk::DeviceSize,
+		size: NonZeroU64
+	) -> Result<OwnershipTransfer<'a>, OwnershipTransferError> {
+		OwnershipTransfer::buffer(
+			buffer,
+			offset,
+			size,
+			&self.compute_queue,
+			&self.graphics_queue
+		)
+	}
+
+	/// Creates a transfer of `image` written by compute to the graphics queue family, transitioning it from `old_layout` to `new_layout`.
+	///
+	/// Record the release into the compute command buffers and the acquire into the graphics command buffers.
+	pub fn image_to_graphics<'a>(
+		&self,
+		image: &'a Image,
+		subresource_range: ImageSubresourceRange,
+		old_layout: vk::ImageLayout,
+		new_layout: ImageLayoutFinal
+	) -> Result<OwnershipTransfer<'a>, OwnershipTransferError> {
+		OwnershipTransfer::image(
+			image,
+			subresource_range,
+			old_layout,
+			new_layout,
+			&self.compute_queue,
+			&self.graphics_queue
+		)
+	}
+
+	pub const fn graphics_queue(&self) -> &Vrc<Queue> {
+		&self.graphics_queue
+	}
+
+	pub const fn compute_queue(&self) -> &Vrc<Queue> {
+		&self.compute_queue
+	}
+
+	pub fn frames_in_flight(&self) -> usize {
+		self.semaphores.len()
+	}
+}
+impl fmt::Debug for AsyncComputeContext {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let semaphores: Vec<_> = self.semaphores.iter().map(|s| (&s.compute_finished, &s.graphics_finished)).collect();
+
+		f.debug_struct("AsyncComputeContext")
+			.field("graphics_queue", &self.graphics_queue)
+			.field("compute_queue", &self.compute_queue)
+			.field("semaphores", &semaphores)
+			.field("states", &self.states)
+			.finish()
+	}
+}
@@ -0,0 +1,26 @@
+//! Typed batch descriptions for `Queue::submit_batched`.
+
+use ash::vk;
+
+use crate::prelude::{CommandBuffer, Semaphore};
+
+/// One batch of `Queue::submit_batched`, corresponding to one `vk::SubmitInfo`.
+///
+/// Batches are executed in order of submission, but may overlap unless synchronized using semaphores.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubmitBatch<'a> {
+	/// Semaphores waited on before the stages of the batch are executed.
+	pub wait_for: &'a [(&'a Semaphore, vk::PipelineStageFlags)],
+	pub buffers: &'a [&'a CommandBuffer],
+	/// Semaphores signaled after all `buffers` complete.
+	pub signal_after: &'a [&'a Semaphore]
+}
+impl<'a> SubmitBatch<'a> {
+	pub const fn new(
+		wait_for: &'a [(&'a Semaphore, vk::PipelineStageFlags)],
+		buffers: &'a [&'a CommandBuffer],
+		signal_after: &'a [&'a Semaphore]
+	) -> Self {
+		SubmitBatch { wait_for, buffers, signal_after }
+	}
+}
@@ -1,4 +1,7 @@
 use ash::vk;
+use thiserror::Error;
+
+use crate::{command::error::CommandBufferError, sync::fence::error::FenceError, util::result::VkResultExt};
 
 vk_result_error! {
 	#[derive(Debug)]
@@ -25,6 +28,33 @@ vk_result_error! {
 		#[cfg(feature = "runtime_implicit_validations")]
 		#[error("Wait semaphores, command buffers and signal semaphores must be from the same device")]
 		WaitBufferSignalDeviceMismatch,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Semaphore device index must be less than the number of physical devices of the device")]
+		DeviceIndexOutOfBounds,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Command buffer device mask must not be zero and must only select physical devices of the device")]
+		DeviceMaskInvalid,
+	}
+}
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum QueueBindSparseError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_DEVICE_LOST
+		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Queue family of the queue does not support sparse binding")]
+		SparseBindingNotSupported,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Queue, semaphores, resources and fence must be from the same device")]
+		DeviceMismatch,
 	}
 }
 
@@ -39,6 +69,16 @@ vk_result_error! {
 	}
 }
 
+#[derive(Error, Debug)]
+pub enum ImmediateSubmitError {
+	#[error("Could not allocate or record command buffer")]
+	CommandBuffer(#[from] CommandBufferError),
+	#[error("Could not create or wait for fence")]
+	Fence(#[from] FenceError),
+	#[error("Could not submit command buffer")]
+	QueueSubmit(#[from] QueueSubmitError)
+}
+
 vk_result_error! {
 	#[derive(Debug)]
 	pub enum QueuePresentError {
@@ -75,10 +115,29 @@ impl From<bool> for QueuePresentSuccess {
 		}
 	}
 }
-pub fn match_queue_present_result(result: vk::Result) -> Result<QueuePresentSuccess, QueuePresentError> {
-	match result {
-		vk::Result::SUCCESS => Ok(QueuePresentSuccess::SUCCESS),
-		vk::Result::SUBOPTIMAL_KHR => Ok(QueuePresentSuccess::SUBOPTIMAL_KHR),
-		err => Err(QueuePresentError::from(err))
+impl QueuePresentError {
+	/// Returns whether recreating the swapchain resolves this error, see `VkResultExt::is_recoverable_swapchain_issue`.
+	pub fn is_recoverable_swapchain_issue(&self) -> bool {
+		self.vk_result().map_or(false, VkResultExt::is_recoverable_swapchain_issue)
 	}
 }
+pub fn match_queue_present_result(result: vk::Result) -> Result<QueuePresentSuccess, QueuePresentError> {
+	let result = result.to_crate_error::<QueuePresentError>()?;
+
+	Ok(QueuePresentSuccess::from(result == vk::Result::SUBOPTIMAL_KHR))
+}
+
+#[derive(Error, Debug)]
+pub enum QueueFamilyPlanError {
+	#[error("Physical device has no queue family with graphics support")]
+	NoGraphicsFamily,
+
+	#[error("Physical device has no queue family that can present to the surface")]
+	NoPresentFamily,
+
+	#[error("Could not query surface support")]
+	SurfaceSupport(#[from] crate::surface::error::SurfaceSupportError),
+
+	#[error("No queue from queue family {0} was created")]
+	QueueNotCreated(u32)
+}
@@ -0,0 +1,77 @@
+//! Scoped queue wait idle.
+//!
+//! Waiting for a queue to become idle stalls both the host and the device, so it is mostly a debugging hammer.
+//! `ScopedIdle` makes the pattern explicit and visible: every wait is logged with the caller location and its duration,
+//! and release builds additionally log a warning so that accidental per-frame waits show up.
+
+use std::{fmt, panic::Location, time::Instant};
+
+use super::{error::QueueWaitError, Queue};
+
+/// Guard that waits for its queue to become idle when dropped or finished, see the module documentation.
+#[must_use = "the queue is waited on when the guard is dropped"]
+pub struct ScopedIdle<'a> {
+	queue: &'a Queue,
+	location: &'static Location<'static>,
+	start: Instant
+}
+impl<'a> ScopedIdle<'a> {
+	#[track_caller]
+	pub(super) fn new(queue: &'a Queue) -> Self {
+		let location = Location::caller();
+		if !cfg!(debug_assertions) {
+			log::warn!(
+				"Queue wait idle scope at {} in a release build",
+				location
+			);
+		}
+		log_trace_common!("Beginning queue wait idle scope:", queue, location);
+
+		ScopedIdle { queue, location, start: Instant::now() }
+	}
+
+	/// Waits for the queue to become idle and returns the result instead of logging it.
+	pub fn finish(self) -> Result<(), QueueWaitError> {
+		let guard = std::mem::ManuallyDrop::new(self);
+
+		guard.wait()
+	}
+
+	fn wait(&self) -> Result<(), QueueWaitError> {
+		let wait_start = Instant::now();
+		let result = self.queue.wait();
+
+		log::trace!(
+			"Queue wait idle scope at {} took {:?} (waited {:?})",
+			self.location,
+			self.start.elapsed(),
+			wait_start.elapsed()
+		);
+
+		result
+	}
+
+	pub const fn queue(&self) -> &'a Queue {
+		self.queue
+	}
+}
+impl Drop for ScopedIdle<'_> {
+	fn drop(&mut self) {
+		if let Err(err) = self.wait() {
+			log::error!(
+				"Could not wait for queue idle in scope at {}: {}",
+				self.location,
+				err
+			);
+		}
+	}
+}
+impl fmt::Debug for ScopedIdle<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ScopedIdle")
+			.field("queue", &self.queue)
+			.field("location", &self.location)
+			.field("start", &self.start)
+			.finish()
+	}
+}
@@ -1,14 +1,37 @@
 use std::{
+	ffi::c_void,
 	fmt::{Debug, Formatter},
 	ops::Deref
 };
 
 use ash::vk::{self, DeviceQueueCreateFlags, DeviceQueueInfo2};
 
-use crate::{prelude::{CommandBuffer, Device, Fence, Semaphore, SwapchainImage, Vrc}, util::handle::HasHandle};
+use crate::{
+	prelude::{
+		CommandBuffer,
+		CommandBufferBeginInfo,
+		CommandBufferRecordingLockOutsideRenderPass,
+		CommandPool,
+		Device,
+		Fence,
+		FencePool,
+		HostMemoryAllocator,
+		Semaphore,
+		SwapchainImage,
+		Transparent,
+		Vrc
+	},
+	util::handle::HasHandle
+};
 
+pub mod async_compute;
+pub mod batch;
 pub mod error;
+pub mod idle;
+pub mod plan;
 pub mod sharing_mode;
+pub mod sparse;
+pub mod transfer;
 
 /// An internally synchronized device queue.
 pub struct Queue {
@@ -17,7 +40,10 @@ pub struct Queue {
 
 	// TODO: Creation flags?
 	queue_family_index: u32,
-	queue_index: u32
+	queue_index: u32,
+
+	/// Fences of `immediate_submit`.
+	fence_pool: FencePool
 }
 impl Queue {
 	pub fn submit<const WAITS: usize, const BUFFERS: usize, const SIGNALS: usize>(
@@ -28,24 +54,216 @@ impl Queue {
 		signal_after: [&Semaphore; SIGNALS],
 		fence: Option<&Fence>
 	) -> Result<(), error::QueueSubmitError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		self.validate_submit(
+			&wait_for,
+			&wait_for_stages,
+			&buffers,
+			&signal_after,
+			fence
+		)?;
+
+		let wait_for_raw = wait_for.map(|s| s.handle());
+		let buffers_locks = buffers.map(|s| s.lock().expect("vutex poisoned"));
+		let buffers_raw = buffers_locks.map(|l| *l);
+		let signal_after_raw = signal_after.map(|s| s.handle());
+
+		let submit_info = vk::SubmitInfo::builder()
+			.wait_semaphores(&wait_for_raw)
+			.wait_dst_stage_mask(&wait_for_stages)
+			.command_buffers(&buffers_raw)
+			.signal_semaphores(&signal_after_raw)
+			.build();
+
+		unsafe { self.submit_raw([submit_info], fence) }
+	}
+
+	/// Submits all `batches` in a single `vkQueueSubmit` call with one `fence` signaled after all of them complete.
+	///
+	/// All command buffers of all batches are locked for the duration of the call, so a command buffer must not appear
+	/// in more than one batch or more than once in a batch.
+	pub fn submit_batched<'a>(&self, batches: impl AsRef<[batch::SubmitBatch<'a>]>, fence: Option<&Fence>) -> Result<(), error::QueueSubmitError> {
+		let batches = batches.as_ref();
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		for batch in batches.iter() {
+			let wait_for = collect_iter_faster!(batch.wait_for.iter().map(|&(semaphore, _)| semaphore), 4);
+			let wait_for_stages = collect_iter_faster!(batch.wait_for.iter().map(|&(_, stages)| stages), 4);
+
+			self.validate_submit(
+				wait_for.as_slice(),
+				wait_for_stages.as_slice(),
+				batch.buffers,
+				batch.signal_after,
+				fence
+			)?;
+		}
+
+		// Handles of all batches are stored contiguously and each submit info points into its part
+		let wait_for_raw = collect_iter_faster!(batches.iter().flat_map(|b| b.wait_for.iter().map(|(s, _)| s.handle())), 8);
+		let wait_for_stages = collect_iter_faster!(batches.iter().flat_map(|b| b.wait_for.iter().map(|&(_, stages)| stages)), 8);
+		let buffers_locks = collect_iter_faster!(
+			batches
+				.iter()
+				.flat_map(|b| b.buffers.iter().map(|s| s.lock().expect("vutex poisoned"))),
+			8
+		);
+		let buffers_raw = collect_iter_faster!(buffers_locks.iter().map(|l| **l), 8);
+		let signal_after_raw = collect_iter_faster!(batches.iter().flat_map(|b| b.signal_after.iter().map(|s| s.handle())), 8);
+
+		let mut offsets = [0usize; 3];
+		let submit_infos = collect_iter_faster!(
+			batches.iter().map(|b| {
+				let [waits, buffers, signals] = offsets;
+				offsets = [waits + b.wait_for.len(), buffers + b.buffers.len(), signals + b.signal_after.len()];
+
+				vk::SubmitInfo::builder()
+					.wait_semaphores(&wait_for_raw[waits .. offsets[0]])
+					.wait_dst_stage_mask(&wait_for_stages[waits .. offsets[0]])
+					.command_buffers(&buffers_raw[buffers .. offsets[1]])
+					.signal_semaphores(&signal_after_raw[signals .. offsets[2]])
+					.build()
+			}),
+			4
+		);
+
+		unsafe { self.submit_raw(submit_infos.as_slice(), fence) }
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn validate_submit(
+		&self,
+		wait_for: &[&Semaphore],
+		wait_for_stages: &[vk::PipelineStageFlags],
+		buffers: &[&CommandBuffer],
+		signal_after: &[&Semaphore],
+		fence: Option<&Fence>
+	) -> Result<(), error::QueueSubmitError> {
+		for stage in wait_for_stages.iter() {
+			if stage.is_empty() {
+				return Err(error::QueueSubmitError::WaitStagesEmpty)
+			}
+		}
+		// check that all waits, buffers and signals come from the same device
+		if !crate::util::validations::validate_all_match(
+			wait_for
+				.iter()
+				.map(|w| w.device())
+				.chain(buffers.iter().map(|b| b.pool().device()))
+				.chain(signal_after.iter().map(|s| s.device()))
+		) {
+			return Err(error::QueueSubmitError::WaitBufferSignalDeviceMismatch)
+		}
+		for cb in buffers.iter() {
+			if cb.pool().queue_family_index() != self.queue_family_index() {
+				return Err(error::QueueSubmitError::QueueFamilyMismatch)
+			}
+		}
+		if let Some(fence) = fence {
+			if self.device() != fence.device() {
+				return Err(error::QueueSubmitError::QueueFenceDeviceMismatch)
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Submits `buffers` to the physical devices of a device group, see the `instance::device_group` module.
+	///
+	/// Each wait and signal semaphore operation is executed on the physical device with the given device index
+	/// and each command buffer on the physical devices selected by its device mask.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0.
+	#[cfg(feature = "vulkan1_1")]
+	pub fn submit_device_group<const WAITS: usize, const BUFFERS: usize, const SIGNALS: usize>(
+		&self,
+		wait_for: [(&Semaphore, vk::PipelineStageFlags, u32); WAITS],
+		buffers: [(&CommandBuffer, u32); BUFFERS],
+		signal_after: [(&Semaphore, u32); SIGNALS],
+		fence: Option<&Fence>
+	) -> Result<(), error::QueueSubmitError> {
+		let wait_for_stages = wait_for.map(|(_, stages, _)| stages);
+
 		#[cfg(feature = "runtime_implicit_validations")]
 		{
-			for stage in wait_for_stages.iter() {
-				if stage.is_empty() {
-					return Err(error::QueueSubmitError::WaitStagesEmpty)
-				}
+			self.validate_submit(
+				&wait_for.map(|(semaphore, _, _)| semaphore),
+				&wait_for_stages,
+				&buffers.map(|(buffer, _)| buffer),
+				&signal_after.map(|(semaphore, _)| semaphore),
+				fence
+			)?;
+
+			let group_size = self.device.device_group_size().get();
+			if wait_for.iter().map(|w| w.2).chain(signal_after.iter().map(|s| s.1)).any(|index| index >= group_size) {
+				return Err(error::QueueSubmitError::DeviceIndexOutOfBounds)
 			}
-			{
-				// check that all waits, buffers and signals come from the same device
-				if !crate::util::validations::validate_all_match(
-					wait_for
-						.iter()
-						.map(|w| w.device())
-						.chain(buffers.iter().map(|b| b.pool().device()))
-						.chain(signal_after.iter().map(|s| s.device()))
-				) {
-					return Err(error::QueueSubmitError::WaitBufferSignalDeviceMismatch)
-				}
+			let all_devices_mask = self.device.all_devices_mask();
+			if buffers.iter().any(|&(_, mask)| mask == 0 || mask & !all_devices_mask != 0) {
+				return Err(error::QueueSubmitError::DeviceMaskInvalid)
+			}
+		}
+
+		let wait_for_raw = wait_for.map(|(s, _, _)| s.handle());
+		let wait_device_indices = wait_for.map(|(_, _, index)| index);
+		let buffers_locks = buffers.map(|(b, _)| b.lock().expect("vutex poisoned"));
+		let buffers_raw = buffers_locks.map(|l| *l);
+		let buffer_device_masks = buffers.map(|(_, mask)| mask);
+		let signal_after_raw = signal_after.map(|(s, _)| s.handle());
+		let signal_device_indices = signal_after.map(|(_, index)| index);
+
+		let mut device_group_info = vk::DeviceGroupSubmitInfo::builder()
+			.wait_semaphore_device_indices(&wait_device_indices)
+			.command_buffer_device_masks(&buffer_device_masks)
+			.signal_semaphore_device_indices(&signal_device_indices);
+		let submit_info = vk::SubmitInfo::builder()
+			.wait_semaphores(&wait_for_raw)
+			.wait_dst_stage_mask(&wait_for_stages)
+			.command_buffers(&buffers_raw)
+			.signal_semaphores(&signal_after_raw)
+			.push_next(&mut device_group_info)
+			.build();
+
+		unsafe { self.submit_raw([submit_info], fence) }
+	}
+
+	/// Submits `buffers` using `vkQueueSubmit2`, waiting for and signaling semaphores at the given stages.
+	///
+	/// Falls back to `vkQueueSubmit` when synchronization2 is not enabled on the device, in which case
+	/// the wait stages are converted to legacy stages and the signal stages are ignored.
+	#[cfg(feature = "sync2")]
+	pub fn submit2<const WAITS: usize, const BUFFERS: usize, const SIGNALS: usize>(
+		&self,
+		wait_for: [(&Semaphore, vk::PipelineStageFlags2); WAITS],
+		buffers: [&CommandBuffer; BUFFERS],
+		signal_after: [(&Semaphore, vk::PipelineStageFlags2); SIGNALS],
+		fence: Option<&Fence>
+	) -> Result<(), error::QueueSubmitError> {
+		if !self.device.synchronization2_enabled() {
+			let wait_for_stages =
+				wait_for.map(|(_, stages)| crate::sync::sync2::legacy_stages(stages, vk::PipelineStageFlags::ALL_COMMANDS));
+
+			return self.submit(
+				wait_for.map(|(semaphore, _)| semaphore),
+				wait_for_stages,
+				buffers,
+				signal_after.map(|(semaphore, _)| semaphore),
+				fence
+			)
+		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if !crate::util::validations::validate_all_match(
+				wait_for
+					.iter()
+					.map(|(w, _)| w.device())
+					.chain(buffers.iter().map(|b| b.pool().device()))
+					.chain(signal_after.iter().map(|(s, _)| s.device()))
+			) {
+				return Err(error::QueueSubmitError::WaitBufferSignalDeviceMismatch)
 			}
 			for cb in buffers.iter() {
 				if cb.pool().queue_family_index() != self.queue_family_index() {
@@ -59,19 +277,43 @@ impl Queue {
 			}
 		}
 
-		let wait_for_raw = wait_for.map(|s| s.handle());
+		let wait_infos = wait_for.map(|(s, stages)| {
+			vk::SemaphoreSubmitInfo::builder()
+				.semaphore(s.handle())
+				.stage_mask(stages)
+				.build()
+		});
 		let buffers_locks = buffers.map(|s| s.lock().expect("vutex poisoned"));
-		let buffers_raw = buffers_locks.map(|l| *l);
-		let signal_after_raw = signal_after.map(|s| s.handle());
+		let buffer_infos = buffers_locks.map(|l| vk::CommandBufferSubmitInfo::builder().command_buffer(*l).build());
+		let signal_infos = signal_after.map(|(s, stages)| {
+			vk::SemaphoreSubmitInfo::builder()
+				.semaphore(s.handle())
+				.stage_mask(stages)
+				.build()
+		});
 
-		let submit_info = vk::SubmitInfo::builder()
-			.wait_semaphores(&wait_for_raw)
-			.wait_dst_stage_mask(&wait_for_stages)
-			.command_buffers(&buffers_raw)
-			.signal_semaphores(&signal_after_raw)
+		let submit_info = vk::SubmitInfo2::builder()
+			.wait_semaphore_infos(&wait_infos)
+			.command_buffer_infos(&buffer_infos)
+			.signal_semaphore_infos(&signal_infos)
 			.build();
 
-		unsafe { self.submit_raw([submit_info], fence) }
+		log_trace_common!(
+			"Submitting2 on queue:",
+			self,
+			crate::util::fmt::format_handle(self.queue),
+			submit_info,
+			fence
+		);
+		unsafe {
+			self.device.ext().synchronization2().queue_submit2(
+				self.queue,
+				&[submit_info],
+				fence.map(|f| f.handle()).unwrap_or(vk::Fence::null())
+			)?;
+		}
+
+		Ok(())
 	}
 
 	pub fn present_with_all_results<const WAITS: usize, const IMAGES: usize>(
@@ -79,38 +321,73 @@ impl Queue {
 		wait_for: [&Semaphore; WAITS],
 		images: [&SwapchainImage; IMAGES]
 	) -> [Result<error::QueuePresentSuccess, error::QueuePresentError>; IMAGES] {
+		let mut results = [vk::Result::SUCCESS; IMAGES];
+		let valid = self.present_raw_results(&wait_for, &images, &mut results);
+
+		results.map(|result| Self::present_result(valid, result))
+	}
+
+	/// Same as `present_with_all_results` but with the number of images known only at runtime.
+	///
+	/// With the `smallvec` feature, the results of up to 4 images are not allocated on the heap.
+	pub fn present_slice_with_all_results(
+		&self,
+		wait_for: &[&Semaphore],
+		images: &[&SwapchainImage]
+	) -> impl ExactSizeIterator<Item = Result<error::QueuePresentSuccess, error::QueuePresentError>> {
+		let mut results = collect_iter_faster!(images.iter().map(|_| vk::Result::SUCCESS), 4);
+		let valid = self.present_raw_results(wait_for, images, &mut results);
+
+		results.into_iter().map(move |result| Self::present_result(valid, result))
+	}
+
+	/// Presents `images` and writes the result of each into `results`, which must be as long as `images`.
+	///
+	/// Returns `false` without presenting if the images and semaphores are not from the same instance.
+	fn present_raw_results(&self, wait_for: &[&Semaphore], images: &[&SwapchainImage], results: &mut [vk::Result]) -> bool {
+		if images.is_empty() {
+			return true
+		}
+
 		#[cfg(feature = "runtime_implicit_validations")]
 		{
-			if IMAGES == 0 {
-				return [(); IMAGES].map(|_| Err(error::QueuePresentError::SwapchainsEmpty))
-			}
 			if !crate::util::validations::validate_all_match(
 				images
 					.iter()
 					.map(|&i| i.device().instance())
 					.chain(wait_for.iter().map(|&w| w.device().instance()))
 			) {
-				return [(); IMAGES].map(|_| Err(error::QueuePresentError::SwapchainsSempahoredInstanceMismatch))
+				return false
 			}
 		}
 
 		let any_swapchain = images[0].swapchain();
 
-		let wait_for_raw = wait_for.map(|s| s.handle());
-		let swapchains_raw = images.map(|i| i.swapchain().handle());
-		let indices = images.map(|i| i.index());
-
-		let mut results = [vk::Result::SUCCESS; IMAGES];
+		let wait_for_raw = collect_iter_faster!(wait_for.iter().map(|s| s.handle()), 4);
+		let swapchains_raw = collect_iter_faster!(images.iter().map(|i| i.swapchain().handle()), 4);
+		let indices = collect_iter_faster!(images.iter().map(|i| i.index()), 4);
 
 		let present_info = vk::PresentInfoKHR::builder()
 			.wait_semaphores(&wait_for_raw)
 			.swapchains(&swapchains_raw)
 			.image_indices(&indices)
-			.results(&mut results);
+			.results(results);
 
 		let _ = unsafe { any_swapchain.present(self, present_info) };
 
-		results.map(error::match_queue_present_result)
+		true
+	}
+
+	#[allow(unused_variables)]
+	fn present_result(valid: bool, result: vk::Result) -> Result<error::QueuePresentSuccess, error::QueuePresentError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if !valid {
+				return Err(error::QueuePresentError::SwapchainsSempahoredInstanceMismatch)
+			}
+		}
+
+		error::match_queue_present_result(result)
 	}
 
 	pub fn present<const WAITS: usize, const IMAGES: usize>(
@@ -119,19 +396,7 @@ impl Queue {
 		images: [&SwapchainImage; IMAGES]
 	) -> Result<error::QueuePresentSuccess, error::QueuePresentError> {
 		#[cfg(feature = "runtime_implicit_validations")]
-		{
-			if IMAGES == 0 {
-				return Err(error::QueuePresentError::SwapchainsEmpty)
-			}
-			if !crate::util::validations::validate_all_match(
-				images
-					.iter()
-					.map(|&i| i.device().instance())
-					.chain(wait_for.iter().map(|&w| w.device().instance()))
-			) {
-				return Err(error::QueuePresentError::SwapchainsSempahoredInstanceMismatch)
-			}
-		}
+		Self::validate_present(&wait_for, &images)?;
 
 		let any_swapchain = images[0].swapchain();
 
@@ -147,6 +412,143 @@ impl Queue {
 		unsafe { any_swapchain.present(self, present_info) }
 	}
 
+	/// Same as `present` but with the number of images known only at runtime and with `next` as the `p_next` chain of the present info.
+	///
+	/// ### Safety
+	///
+	/// `next` must be null or a valid `p_next` chain of `vk::PresentInfoKHR` in which each structure describes exactly `images`.
+	pub unsafe fn present_with_next(
+		&self,
+		wait_for: &[&Semaphore],
+		images: &[&SwapchainImage],
+		next: *const c_void
+	) -> Result<error::QueuePresentSuccess, error::QueuePresentError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		Self::validate_present(wait_for, images)?;
+
+		let any_swapchain = images[0].swapchain();
+
+		let wait_for_raw = collect_iter_faster!(wait_for.iter().map(|s| s.handle()), 4);
+		let swapchains_raw = collect_iter_faster!(images.iter().map(|i| i.swapchain().handle()), 4);
+		let indices = collect_iter_faster!(images.iter().map(|i| i.index()), 4);
+
+		let mut present_info = vk::PresentInfoKHR::builder()
+			.wait_semaphores(&wait_for_raw)
+			.swapchains(&swapchains_raw)
+			.image_indices(&indices)
+			.build();
+		present_info.p_next = next;
+
+		any_swapchain.present(self, &present_info)
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn validate_present(wait_for: &[&Semaphore], images: &[&SwapchainImage]) -> Result<(), error::QueuePresentError> {
+		if images.is_empty() {
+			return Err(error::QueuePresentError::SwapchainsEmpty)
+		}
+		if !crate::util::validations::validate_all_match(
+			images
+				.iter()
+				.map(|&i| i.device().instance())
+				.chain(wait_for.iter().map(|&w| w.device().instance()))
+		) {
+			return Err(error::QueuePresentError::SwapchainsSempahoredInstanceMismatch)
+		}
+
+		Ok(())
+	}
+
+	/// Binds memory to sparse resources.
+	///
+	/// The queue family of this queue must support `SPARSE_BINDING`.
+	///
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkQueueBindSparse.html>
+	pub unsafe fn bind_sparse<const WAITS: usize, const SIGNALS: usize>(
+		&self,
+		wait_for: [&Semaphore; WAITS],
+		buffer_binds: &[sparse::SparseBufferBinds],
+		image_opaque_binds: &[sparse::SparseImageOpaqueBinds],
+		image_binds: &[sparse::SparseImageBinds],
+		signal_after: [&Semaphore; SIGNALS],
+		fence: Option<&Fence>
+	) -> Result<(), error::QueueBindSparseError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			let family = &self.device.physical_device().queue_family_properties()[self.queue_family_index as usize];
+			if !family.queue_flags.contains(vk::QueueFlags::SPARSE_BINDING) {
+				return Err(error::QueueBindSparseError::SparseBindingNotSupported)
+			}
+			if !crate::util::validations::validate_all_match(
+				std::iter::once(&self.device)
+					.chain(wait_for.iter().map(|w| w.device()))
+					.chain(buffer_binds.iter().map(|b| b.buffer.device()))
+					.chain(image_opaque_binds.iter().map(|b| b.image.device()))
+					.chain(image_binds.iter().map(|b| b.image.device()))
+					.chain(signal_after.iter().map(|s| s.device()))
+					.chain(fence.iter().map(|f| f.device()))
+			) {
+				return Err(error::QueueBindSparseError::DeviceMismatch)
+			}
+		}
+
+		let wait_for_raw = wait_for.map(|s| s.handle());
+		let signal_after_raw = signal_after.map(|s| s.handle());
+
+		let buffer_binds_raw = collect_iter_faster!(
+			buffer_binds.iter().map(|b| {
+				vk::SparseBufferMemoryBindInfo::builder()
+					.buffer(b.buffer.handle())
+					.binds(Transparent::transmute_slice_twice(b.binds))
+					.build()
+			}),
+			4
+		);
+		let image_opaque_binds_raw = collect_iter_faster!(
+			image_opaque_binds.iter().map(|b| {
+				vk::SparseImageOpaqueMemoryBindInfo::builder()
+					.image(b.image.handle())
+					.binds(Transparent::transmute_slice_twice(b.binds))
+					.build()
+			}),
+			4
+		);
+		let image_binds_raw = collect_iter_faster!(
+			image_binds.iter().map(|b| {
+				vk::SparseImageMemoryBindInfo::builder()
+					.image(b.image.handle())
+					.binds(Transparent::transmute_slice_twice(b.binds))
+					.build()
+			}),
+			4
+		);
+
+		let info = vk::BindSparseInfo::builder()
+			.wait_semaphores(&wait_for_raw)
+			.buffer_binds(buffer_binds_raw.as_slice())
+			.image_opaque_binds(image_opaque_binds_raw.as_slice())
+			.image_binds(image_binds_raw.as_slice())
+			.signal_semaphores(&signal_after_raw);
+
+		log_trace_common!(
+			"Binding sparse memory on queue:",
+			self,
+			crate::util::fmt::format_handle(self.queue),
+			info.deref(),
+			fence
+		);
+
+		self.device.queue_bind_sparse(
+			self.queue,
+			&[info.build()],
+			fence.map(|f| f.handle()).unwrap_or(vk::Fence::null())
+		)?;
+
+		Ok(())
+	}
+
 	/// Gets a queue from the logical device.
 	///
 	/// ### Safety
@@ -179,7 +581,9 @@ impl Queue {
 			mem.assume_init()
 		};
 
-		Vrc::new(Queue { device, queue, queue_family_index, queue_index })
+		let fence_pool = FencePool::new(device.clone(), HostMemoryAllocator::Unspecified());
+
+		Vrc::new(Queue { device, queue, queue_family_index, queue_index, fence_pool })
 	}
 
 	/// Submits to given queue.
@@ -188,13 +592,30 @@ impl Queue {
 	///
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkQueueSubmit.html>
 	pub unsafe fn submit_raw(&self, infos: impl AsRef<[vk::SubmitInfo]>, fence: Option<&Fence>) -> Result<(), error::QueueSubmitError> {
-		log_trace_common!(
-			"Submitting on queue:",
-			self,
-			crate::util::fmt::format_handle(self.queue),
-			infos.as_ref(),
-			fence
-		);
+		if log::log_enabled!(log::Level::Trace) {
+			let infos: Vec<_> = infos
+				.as_ref()
+				.iter()
+				.map(|info| {
+					debugize_struct!(
+						info;
+						{
+							wait_semaphores: *[wait_semaphore_count] p_wait_semaphores;
+							wait_dst_stage_mask: *[wait_semaphore_count] p_wait_dst_stage_mask;
+							command_buffers: *[command_buffer_count] p_command_buffers;
+							signal_semaphores: *[signal_semaphore_count] p_signal_semaphores;
+						}
+					)
+				})
+				.collect();
+			log_trace_common!(
+				"Submitting on queue:",
+				self,
+				crate::util::fmt::format_handle(self.queue),
+				infos,
+				fence
+			);
+		}
 
 		self.device
 			.queue_submit(
@@ -207,11 +628,62 @@ impl Queue {
 		Ok(())
 	}
 
+	/// Records commands into a new primary command buffer allocated from `pool`, submits it and waits until it finishes.
+	///
+	/// The command buffer is begun with `ONE_TIME_SUBMIT`, `record` is called to record the commands and the result it returns
+	/// is returned once the submission finishes. The command buffer is freed before returning.
+	///
+	/// This is intended for uploads and initialization, the calling thread is blocked for the whole execution of the command buffer.
+	/// The fence waited on is rented from a pool owned by the queue, so repeated calls do not create new fences.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the pool or the command buffer `Vutex` is poisoned.
+	#[track_caller]
+	pub fn immediate_submit<R>(
+		&self,
+		pool: &Vrc<CommandPool>,
+		record: impl FnOnce(&CommandBufferRecordingLockOutsideRenderPass) -> R
+	) -> Result<R, error::ImmediateSubmitError> {
+		let [command_buffer] = CommandBuffer::new(pool.clone(), false)?;
+		let result = {
+			let recording = command_buffer.begin_recording(CommandBufferBeginInfo::OneTime)?;
+			let result = record(&recording);
+			recording.end()?;
+
+			result
+		};
+
+		let fence = self.fence_pool.rent()?;
+		if let Err(err) = self.submit(
+			[],
+			[],
+			[command_buffer.deref()],
+			[],
+			Some(&fence)
+		) {
+			// The fence was not submitted and only referenced here
+			unsafe { self.fence_pool.return_unsubmitted(fence) };
+			return Err(err.into())
+		}
+		fence.wait(Default::default())?;
+
+		Ok(result)
+	}
+
 	/// Waits until all outstanding operations on the queue are completed.
 	pub fn wait(&self) -> Result<(), error::QueueWaitError> {
 		unsafe { self.device.queue_wait_idle(self.queue).map_err(Into::into) }
 	}
 
+	/// Returns a guard that waits until all outstanding operations on the queue are completed when it goes out of scope.
+	///
+	/// The wait is logged together with the caller location and release builds log a warning, see the `idle` module.
+	#[track_caller]
+	pub fn scoped_idle(&self) -> idle::ScopedIdle<'_> {
+		idle::ScopedIdle::new(self)
+	}
+
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
@@ -239,6 +711,7 @@ impl Debug for Queue {
 				&self.queue_family_index
 			)
 			.field("queue_index", &self.queue_index)
+			.field("fence_pool", &self.fence_pool)
 			.finish()
 	}
 }
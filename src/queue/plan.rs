@@ -0,0 +1,188 @@
+//! Queue family selection.
+
+use ash::vk;
+
+use super::{error::QueueFamilyPlanError, Queue};
+use crate::{
+	device::{DeviceData, QueueCreateInfo},
+	physical_device::PhysicalDevice,
+	prelude::Vrc,
+	surface::Surface
+};
+
+/// Recommended selection of queue families for a physical device.
+///
+/// * The graphics family is combined with presentation when some family supports both.
+/// * The compute family is a dedicated family with compute but without graphics support, if one exists.
+/// * The transfer family is a dedicated family with transfer but without graphics or compute support, if one exists.
+///
+/// Example:
+/// ```no_run
+/// # use vulkayes_core::prelude::*;
+/// # use vulkayes_core::queue::plan::QueueFamilyPlan;
+/// # fn f(physical_device: PhysicalDevice, surface: Surface) -> Result<(), Box<dyn std::error::Error>> {
+/// let plan = QueueFamilyPlan::new(&physical_device, Some(&surface))?;
+///
+/// let device_data = DeviceBuilder::new(physical_device)
+/// 	.queues(plan.queue_create_infos())
+/// 	.build()?;
+/// let queues = plan.queues(&device_data)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFamilyPlan {
+	graphics: u32,
+	present: Option<u32>,
+	compute: Option<u32>,
+	transfer: Option<u32>
+}
+impl QueueFamilyPlan {
+	pub fn new(physical_device: &PhysicalDevice, surface: Option<&Surface>) -> Result<Self, QueueFamilyPlanError> {
+		let families = physical_device.queue_family_properties();
+		let family_has = |flags: vk::QueueFlags| {
+			families
+				.iter()
+				.enumerate()
+				.filter(move |(_, family)| family.queue_count > 0 && family.queue_flags.contains(flags))
+				.map(|(index, family)| (index as u32, family.queue_flags))
+		};
+
+		let graphics_families: Vec<u32> = family_has(vk::QueueFlags::GRAPHICS).map(|(index, _)| index).collect();
+		let (graphics, present) = match surface {
+			None => (
+				*graphics_families
+					.first()
+					.ok_or(QueueFamilyPlanError::NoGraphicsFamily)?,
+				None
+			),
+			Some(surface) => {
+				let mut present_families = Vec::new();
+				for index in 0 .. families.len() as u32 {
+					if surface.physical_device_surface_support(physical_device, index)? {
+						present_families.push(index);
+					}
+				}
+
+				let combined = graphics_families
+					.iter()
+					.copied()
+					.find(|index| present_families.contains(index));
+				match combined {
+					Some(index) => (index, Some(index)),
+					None => (
+						*graphics_families
+							.first()
+							.ok_or(QueueFamilyPlanError::NoGraphicsFamily)?,
+						Some(
+							*present_families
+								.first()
+								.ok_or(QueueFamilyPlanError::NoPresentFamily)?
+						)
+					)
+				}
+			}
+		};
+
+		let compute = family_has(vk::QueueFlags::COMPUTE)
+			.find(|(_, flags)| !flags.contains(vk::QueueFlags::GRAPHICS))
+			.map(|(index, _)| index);
+		let transfer = family_has(vk::QueueFlags::TRANSFER)
+			.find(|(_, flags)| !flags.intersects(vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE))
+			.map(|(index, _)| index);
+
+		let plan = QueueFamilyPlan { graphics, present, compute, transfer };
+		log::debug!("Queue family plan {:#?}", plan);
+
+		Ok(plan)
+	}
+
+	/// Family with graphics support.
+	pub const fn graphics(&self) -> u32 {
+		self.graphics
+	}
+
+	/// Family that can present to the surface, `None` if no surface was given.
+	pub const fn present(&self) -> Option<u32> {
+		self.present
+	}
+
+	/// Dedicated compute family.
+	pub const fn compute(&self) -> Option<u32> {
+		self.compute
+	}
+
+	/// Dedicated transfer family.
+	pub const fn transfer(&self) -> Option<u32> {
+		self.transfer
+	}
+
+	/// Returns the unique planned families in order graphics, present, compute, transfer.
+	pub fn families(&self) -> Vec<u32> {
+		let mut families = vec![self.graphics];
+		for family in [self.present, self.compute, self.transfer].iter().flatten() {
+			if !families.contains(family) {
+				families.push(*family);
+			}
+		}
+
+		families
+	}
+
+	/// Returns queue create infos requesting one queue with priority `1.0` from each planned family.
+	pub fn queue_create_infos(&self) -> Vec<QueueCreateInfo<[f32; 1]>> {
+		self.families()
+			.into_iter()
+			.map(|queue_family_index| QueueCreateInfo { queue_family_index, queue_priorities: [1.0] })
+			.collect()
+	}
+
+	/// Maps the queues in `device_data` to the planned roles.
+	///
+	/// Roles without a dedicated family fall back to the compute family and then to the graphics family.
+	pub fn queues(&self, device_data: &DeviceData) -> Result<PlannedQueues, QueueFamilyPlanError> {
+		let find = |family: u32| {
+			device_data
+				.queues
+				.iter()
+				.find(|queue| queue.queue_family_index() == family)
+				.cloned()
+				.ok_or(QueueFamilyPlanError::QueueNotCreated(family))
+		};
+
+		let graphics = find(self.graphics)?;
+		let present = self.present.map(find).transpose()?;
+		let compute = match self.compute {
+			None => graphics.clone(),
+			Some(family) => find(family)?
+		};
+		let transfer = match self.transfer {
+			None => compute.clone(),
+			Some(family) => find(family)?
+		};
+
+		Ok(PlannedQueues { graphics, present, compute, transfer })
+	}
+}
+
+/// Queues selected according to a `QueueFamilyPlan`.
+///
+/// Multiple roles may refer to the same queue.
+#[derive(Debug, Clone)]
+pub struct PlannedQueues {
+	pub graphics: Vrc<Queue>,
+	pub present: Option<Vrc<Queue>>,
+	pub compute: Vrc<Queue>,
+	pub transfer: Vrc<Queue>
+}
+impl PlannedQueues {
+	/// Returns the present queue, or the graphics queue if no surface was given.
+	pub fn present_queue(&self) -> &Vrc<Queue> {
+		self.present.as_ref().unwrap_or(&self.graphics)
+	}
+
+	/// Returns whether presentation happens on a different queue family than graphics, see `PresentHandoff`.
+	pub fn separate_present(&self) -> bool {
+		self.present_queue().queue_family_index() != self.graphics.queue_family_index()
+	}
+}
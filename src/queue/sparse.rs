@@ -0,0 +1,99 @@
+//! Typed structures for sparse memory binding.
+
+use std::num::NonZeroU64;
+
+use ash::vk;
+
+use crate::{
+	memory::device::DeviceMemoryAllocation,
+	prelude::{Buffer, Image}
+};
+
+vk_builder_wrap! {
+	/// Transparent wrapper over `vk::SparseMemoryBind`.
+	///
+	/// Used both for sparse buffers and for opaque binds of sparse images.
+	pub struct SparseBufferMemoryBind ['a] {
+		builder: vk::SparseMemoryBindBuilder<'a> => vk::SparseMemoryBind
+	}
+	impl ['a] {
+		/// Binds `size` bytes of `memory` starting at `memory_offset` to the resource at `resource_offset`.
+		///
+		/// If `memory` is `None` the range is unbound.
+		pub fn new(
+			resource_offset: vk::DeviceSize,
+			size: NonZeroU64,
+			memory: Option<(&'a DeviceMemoryAllocation, vk::DeviceSize)>,
+			flags: vk::SparseMemoryBindFlags
+		) -> Self {
+			let (memory, memory_offset) = match memory {
+				Some((memory, offset)) => (**memory, memory.bind_offset() + offset),
+				None => (vk::DeviceMemory::null(), 0)
+			};
+
+			SparseBufferMemoryBind {
+				builder: vk::SparseMemoryBind::builder()
+					.resource_offset(resource_offset)
+					.size(size.get())
+					.memory(memory)
+					.memory_offset(memory_offset)
+					.flags(flags)
+			}
+		}
+	}
+}
+
+vk_builder_wrap! {
+	/// Transparent wrapper over `vk::SparseImageMemoryBind`.
+	pub struct SparseImageMemoryBind ['a] {
+		builder: vk::SparseImageMemoryBindBuilder<'a> => vk::SparseImageMemoryBind
+	}
+	impl ['a] {
+		/// Binds `memory` starting at `memory_offset` to the `offset` and `extent` region of `subresource`.
+		///
+		/// If `memory` is `None` the region is unbound.
+		pub fn new(
+			subresource: vk::ImageSubresource,
+			offset: vk::Offset3D,
+			extent: vk::Extent3D,
+			memory: Option<(&'a DeviceMemoryAllocation, vk::DeviceSize)>,
+			flags: vk::SparseMemoryBindFlags
+		) -> Self {
+			let (memory, memory_offset) = match memory {
+				Some((memory, offset)) => (**memory, memory.bind_offset() + offset),
+				None => (vk::DeviceMemory::null(), 0)
+			};
+
+			SparseImageMemoryBind {
+				builder: vk::SparseImageMemoryBind::builder()
+					.subresource(subresource)
+					.offset(offset)
+					.extent(extent)
+					.memory(memory)
+					.memory_offset(memory_offset)
+					.flags(flags)
+			}
+		}
+	}
+}
+
+/// Sparse binds of a buffer.
+#[derive(Debug)]
+pub struct SparseBufferBinds<'a> {
+	pub buffer: &'a Buffer,
+	pub binds: &'a [SparseBufferMemoryBind<'a>]
+}
+
+/// Opaque sparse binds of an image.
+#[derive(Debug)]
+pub struct SparseImageOpaqueBinds<'a> {
+	pub image: &'a Image,
+	pub binds: &'a [SparseBufferMemoryBind<'a>]
+}
+
+/// Sparse residency binds of an image.
+#[derive(Debug)]
+pub struct SparseImageBinds<'a> {
+	pub image: &'a Image,
+	pub binds: &'a [SparseImageMemoryBind<'a>]
+}
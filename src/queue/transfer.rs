@@ -0,0 +1,677 @@
+//! Background uploads on a dedicated transfer queue.
+//!
+//! `TransferScheduler` collects upload jobs and submits them in batches to its transfer queue. Each batch is guarded
+//! by a fence, `poll` reports finished batches without blocking so that asset streaming can run alongside rendering.
+//!
+//! When the destination queue family differs from the transfer queue family, each batch releases ownership of the
+//! destination resources to the destination family. The destination queue must then acquire them by recording the barriers
+//! from `TransferBatch::record_acquire` before using the resources. Queue family ownership only applies to resources
+//! created with exclusive sharing mode. Resources with concurrent sharing mode are not released and need no acquire,
+//! their writes are only made available and images are transitioned to their final layout.
+
+use std::{collections::VecDeque, fmt, num::NonZeroU64, ops::Deref};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+	command::error::{CommandBufferError, CommandPoolError},
+	memory::device::{allocator::BufferMemoryAllocator, MapError, MappingAccessResult},
+	prelude::{
+		Buffer,
+		BufferAllocatorParams,
+		BufferBufferCopy,
+		BufferImageCopy,
+		BufferMemoryBarrier,
+		CommandBuffer,
+		CommandBufferBeginInfo,
+		CommandBufferRecordingLockOutsideRenderPass,
+		CommandPool,
+		Fence,
+		FencePool,
+		HasHandle,
+		HostMemoryAllocator,
+		Image,
+		ImageLayoutDestination,
+		ImageLayoutFinal,
+		ImageMemoryBarrier,
+		ImageSubresourceLayers,
+		ImageSubresourceRange,
+		MemoryBarrier,
+		Queue,
+		Semaphore,
+		SharingMode,
+		Vrc
+	},
+	queue::error::QueueSubmitError,
+	resource::{
+		buffer::error::BufferError,
+		image::{error::ImageCopySourceError, params::validate_packed_copy_source}
+	},
+	sync::fence::error::{FenceError, FenceStatusError}
+};
+
+#[derive(Error, Debug)]
+pub enum TransferError<AllocError: std::error::Error + 'static> {
+	#[error("Source data must not be empty")]
+	SourceEmpty,
+	#[error("Source data does not cover the image region")]
+	ImageSource(#[from] ImageCopySourceError),
+	#[error("Could not create staging buffer")]
+	Buffer(#[from] BufferError<AllocError>),
+	#[error("Staging buffer has no bound memory")]
+	BufferNoMemory,
+	#[error("Could not map staging buffer memory")]
+	Map(#[from] MapError),
+	#[error("Could not record command buffer")]
+	CommandBuffer(#[from] CommandBufferError),
+	#[error("Could not create fence")]
+	Fence(#[from] FenceError),
+	#[error("Could not submit transfer batch")]
+	QueueSubmit(#[from] QueueSubmitError)
+}
+
+/// Identifier of a batch of transfer jobs.
+///
+/// Batches are numbered in submission order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransferBatchId(u64);
+
+enum TransferJob {
+	Buffer {
+		staging: Vrc<Buffer>,
+		destination: Vrc<Buffer>,
+		offset: vk::DeviceSize
+	},
+	Image {
+		staging: Vrc<Buffer>,
+		destination: Vrc<Image>,
+		subresource_range: ImageSubresourceRange,
+		offset: vk::Offset3D,
+		extent: vk::Extent3D,
+		old_layout: vk::ImageLayout,
+		final_layout: ImageLayoutFinal
+	}
+}
+impl fmt::Debug for TransferJob {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			TransferJob::Buffer { staging, destination, offset } => f
+				.debug_struct("Buffer")
+				.field("staging", staging)
+				.field("destination", destination)
+				.field("offset", offset)
+				.finish(),
+			TransferJob::Image { staging, destination, subresource_range, offset, extent, old_layout, final_layout } => f
+				.debug_struct("Image")
+				.field("staging", staging)
+				.field("destination", destination)
+				.field("subresource_range", subresource_range)
+				.field("offset", offset)
+				.field("extent", extent)
+				.field("old_layout", old_layout)
+				.field("final_layout", final_layout)
+				.finish()
+		}
+	}
+}
+
+/// Ownership transfer barrier of a buffer or an image.
+///
+/// The same parameters are used for the release on the transfer queue and the acquire on the destination queue.
+#[derive(Debug, Clone)]
+enum OwnershipTransfer {
+	Buffer {
+		buffer: Vrc<Buffer>,
+		offset: vk::DeviceSize,
+		size: vk::DeviceSize
+	},
+	Image {
+		image: Vrc<Image>,
+		subresource_range: ImageSubresourceRange,
+		final_layout: ImageLayoutFinal
+	}
+}
+impl OwnershipTransfer {
+	/// Returns whether the resource is shared concurrently and its ownership is never transferred.
+	fn is_concurrent(&self) -> bool {
+		let sharing_mode = match self {
+			OwnershipTransfer::Buffer { buffer, .. } => buffer.sharing_mode(),
+			OwnershipTransfer::Image { image, .. } => image.sharing_mode()
+		};
+
+		sharing_mode == vk::SharingMode::CONCURRENT
+	}
+
+	fn buffer_barrier(
+		&self,
+		families: (u32, u32),
+		source_access: vk::AccessFlags,
+		destination_access: vk::AccessFlags
+	) -> Option<BufferMemoryBarrier<'_>> {
+		match self {
+			OwnershipTransfer::Buffer { buffer, offset, size } => Some(unsafe {
+				BufferMemoryBarrier::from_raw(
+					vk::BufferMemoryBarrier::builder()
+						.buffer(buffer.handle())
+						.offset(*offset)
+						.size(*size)
+						.src_access_mask(source_access)
+						.dst_access_mask(destination_access)
+						.src_queue_family_index(families.0)
+						.dst_queue_family_index(families.1)
+				)
+			}),
+			_ => None
+		}
+	}
+
+	fn image_barrier(
+		&self,
+		families: (u32, u32),
+		source_access: vk::AccessFlags,
+		destination_access: vk::AccessFlags
+	) -> Option<ImageMemoryBarrier<'_>> {
+		match self {
+			OwnershipTransfer::Image { image, subresource_range, final_layout } => Some(unsafe {
+				ImageMemoryBarrier::from_raw(
+					vk::ImageMemoryBarrier::builder()
+						.image(image.handle())
+						.subresource_range(vk::ImageSubresourceRangeBuilder::from(*subresource_range).build())
+						.old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+						.new_layout((*final_layout).into())
+						.src_access_mask(source_access)
+						.dst_access_mask(destination_access)
+						.src_queue_family_index(families.0)
+						.dst_queue_family_index(families.1)
+				)
+			}),
+			_ => None
+		}
+	}
+}
+
+/// Batch of transfer jobs submitted by `TransferScheduler::flush`.
+#[derive(Debug, Clone)]
+pub struct TransferBatch {
+	id: TransferBatchId,
+	/// Source and destination queue family, if ownership is transferred.
+	families: Option<(u32, u32)>,
+	transfers: Vec<OwnershipTransfer>
+}
+impl TransferBatch {
+	pub const fn id(&self) -> TransferBatchId {
+		self.id
+	}
+
+	/// Returns whether the destination queue must acquire the resources of this batch.
+	///
+	/// Resources with concurrent sharing mode are never acquired.
+	pub const fn needs_acquire(&self) -> bool {
+		self.families.is_some()
+	}
+
+	/// Records the acquire barriers of the resources of this batch on the destination queue.
+	///
+	/// Does nothing if no ownership transfer is needed. `recording` must be submitted to a queue of the destination family
+	/// and must not execute before the batch is finished, either by waiting on the semaphore passed to `flush_signaling`
+	/// or by submitting after `poll` reported the batch as finished.
+	pub fn record_acquire(
+		&self,
+		recording: &CommandBufferRecordingLockOutsideRenderPass,
+		destination_stages: vk::PipelineStageFlags,
+		destination_access: vk::AccessFlags
+	) {
+		let families = match self.families {
+			None => return,
+			Some(families) => families
+		};
+
+		let buffer_barriers: Vec<_> = self
+			.transfers
+			.iter()
+			.filter_map(|t| t.buffer_barrier(families, vk::AccessFlags::empty(), destination_access))
+			.collect();
+		let image_barriers: Vec<_> = self
+			.transfers
+			.iter()
+			.filter_map(|t| t.image_barrier(families, vk::AccessFlags::empty(), destination_access))
+			.collect();
+
+		recording.pipeline_barrier(
+			vk::PipelineStageFlags::TOP_OF_PIPE,
+			destination_stages,
+			[] as [MemoryBarrier; 0],
+			buffer_barriers,
+			image_barriers
+		);
+	}
+}
+
+struct InFlightBatch {
+	id: TransferBatchId,
+	fence: Vrc<Fence>,
+	command_buffer: Vrc<CommandBuffer>,
+	jobs: Vec<TransferJob>
+}
+impl fmt::Debug for InFlightBatch {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("InFlightBatch")
+			.field("id", &self.id)
+			.field("fence", &self.fence)
+			.field("command_buffer", &self.command_buffer)
+			.field("jobs", &self.jobs)
+			.finish()
+	}
+}
+
+/// Batches uploads and submits them to a dedicated transfer queue, see the module documentation.
+///
+/// `requirements` passed to the constructor must select host visible and host coherent memory for staging buffers.
+///
+/// Dropping the scheduler blocks until all submitted batches are finished.
+pub struct TransferScheduler<A: BufferMemoryAllocator>
+where
+	A::AllocationRequirements: Clone
+{
+	queue: Vrc<Queue>,
+	command_pool: Vrc<CommandPool>,
+	fence_pool: FencePool,
+	destination_queue_family_index: u32,
+
+	allocator: A,
+	requirements: A::AllocationRequirements,
+	host_memory_allocator: HostMemoryAllocator,
+
+	pending: Vec<TransferJob>,
+	in_flight: VecDeque<InFlightBatch>,
+	next_batch: u64
+}
+impl<A: BufferMemoryAllocator> TransferScheduler<A>
+where
+	A::AllocationRequirements: Clone
+{
+	/// Creates a new scheduler submitting to `queue` for resources used by `destination_queue_family_index`.
+	pub fn new(
+		queue: Vrc<Queue>,
+		destination_queue_family_index: u32,
+		allocator: A,
+		requirements: A::AllocationRequirements,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, CommandPoolError> {
+		let command_pool = CommandPool::new(
+			&queue,
+			vk::CommandPoolCreateFlags::TRANSIENT,
+			host_memory_allocator
+		)?;
+
+		let fence_pool = FencePool::new(queue.device().clone(), host_memory_allocator);
+
+		Ok(TransferScheduler {
+			queue,
+			command_pool,
+			fence_pool,
+			destination_queue_family_index,
+
+			allocator,
+			requirements,
+			host_memory_allocator,
+
+			pending: Vec::new(),
+			in_flight: VecDeque::new(),
+			next_batch: 0
+		})
+	}
+
+	fn create_staging(&self, src: &[u8]) -> Result<Vrc<Buffer>, TransferError<A::Error>> {
+		let size = NonZeroU64::new(src.len() as u64).ok_or(TransferError::SourceEmpty)?;
+
+		let staging_buffer = Buffer::new(
+			self.queue.device().clone(),
+			size,
+			vk::BufferUsageFlags::TRANSFER_SRC,
+			SharingMode::from(self.queue.deref()),
+			BufferAllocatorParams::Some { allocator: &self.allocator, requirements: self.requirements.clone() },
+			self.host_memory_allocator
+		)?;
+
+		staging_buffer
+			.memory()
+			.ok_or(TransferError::BufferNoMemory)?
+			.map_memory_with(|mut access| {
+				access.write_slice(src, 0, Default::default());
+				MappingAccessResult::Unmap
+			})?;
+
+		Ok(staging_buffer)
+	}
+
+	/// Queues an upload of `src` into `destination` at `offset`.
+	///
+	/// Returns the id of the batch the job will be submitted in by the next flush.
+	pub fn upload_to_buffer(
+		&mut self,
+		src: &[u8],
+		destination: Vrc<Buffer>,
+		offset: vk::DeviceSize
+	) -> Result<TransferBatchId, TransferError<A::Error>> {
+		let staging = self.create_staging(src)?;
+		self.pending.push(TransferJob::Buffer { staging, destination, offset });
+
+		Ok(TransferBatchId(self.next_batch))
+	}
+
+	/// Queues an upload of tightly packed texel data `src` into the `offset` and `extent` region of `destination`.
+	///
+	/// Only the base mipmap level of `subresource_range` is written, the whole range is transitioned from `old_layout`
+	/// to `final_layout`. Returns the id of the batch the job will be submitted in by the next flush.
+	///
+	/// `src` must contain at least the bytes of the region in all layers of `subresource_range`, see `TexelBlock::packed_size`.
+	pub fn upload_to_image(
+		&mut self,
+		src: &[u8],
+		destination: Vrc<Image>,
+		subresource_range: ImageSubresourceRange,
+		offset: vk::Offset3D,
+		extent: vk::Extent3D,
+		old_layout: vk::ImageLayout,
+		final_layout: ImageLayoutFinal
+	) -> Result<TransferBatchId, TransferError<A::Error>> {
+		validate_packed_copy_source(
+			src.len() as u64,
+			destination.format(),
+			subresource_range.aspect_mask,
+			extent,
+			subresource_range.array_layers.get()
+		)?;
+
+		let staging = self.create_staging(src)?;
+		self.pending.push(TransferJob::Image {
+			staging,
+			destination,
+			subresource_range,
+			offset,
+			extent,
+			old_layout,
+			final_layout
+		});
+
+		Ok(TransferBatchId(self.next_batch))
+	}
+
+	fn ownership_families(&self) -> Option<(u32, u32)> {
+		let transfer_family = self.queue.queue_family_index();
+
+		if transfer_family == self.destination_queue_family_index {
+			None
+		} else {
+			Some((transfer_family, self.destination_queue_family_index))
+		}
+	}
+
+	fn record(&self, recording: &CommandBufferRecordingLockOutsideRenderPass, transfers: &[OwnershipTransfer]) {
+		let to_transfer_dst: Vec<_> = self
+			.pending
+			.iter()
+			.filter_map(|job| match job {
+				TransferJob::Image { destination, subresource_range, old_layout, .. } => Some(ImageMemoryBarrier::new(
+					destination,
+					*subresource_range,
+					*old_layout,
+					ImageLayoutFinal::TRANSFER_DST_OPTIMAL,
+					vk::AccessFlags::empty(),
+					vk::AccessFlags::TRANSFER_WRITE
+				)),
+				_ => None
+			})
+			.collect();
+		if !to_transfer_dst.is_empty() {
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::TOP_OF_PIPE,
+				vk::PipelineStageFlags::TRANSFER,
+				[] as [MemoryBarrier; 0],
+				[] as [BufferMemoryBarrier; 0],
+				to_transfer_dst
+			);
+		}
+
+		for job in self.pending.iter() {
+			match job {
+				TransferJob::Buffer { staging, destination, offset } => recording.copy_buffer_to_buffer(
+					staging,
+					destination,
+					[BufferBufferCopy::new(0, *offset, staging.size())]
+				),
+				TransferJob::Image { staging, destination, subresource_range, offset, extent, .. } => recording.copy_buffer_to_image(
+					staging,
+					destination,
+					ImageLayoutDestination::TRANSFER_DST_OPTIMAL,
+					[BufferImageCopy::new(
+						0,
+						None,
+						ImageSubresourceLayers::new(
+							subresource_range.aspect_mask,
+							subresource_range.mipmap_levels_base,
+							subresource_range.array_layers_base,
+							subresource_range.array_layers
+						),
+						*offset,
+						*extent
+					)]
+				)
+			}
+		}
+
+		// Resources of exclusive sharing mode are released to the destination family if it differs
+		let release_families = self.ownership_families();
+		let (released, kept): (Vec<&OwnershipTransfer>, Vec<&OwnershipTransfer>) =
+			transfers.iter().partition(|t| release_families.is_some() && !t.is_concurrent());
+
+		if let Some(families) = release_families {
+			Self::record_release_barrier(
+				recording,
+				&released,
+				families,
+				vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+				vk::AccessFlags::empty()
+			);
+		}
+		// Other barriers only make the writes available and transition the images
+		Self::record_release_barrier(
+			recording,
+			&kept,
+			(vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED),
+			vk::PipelineStageFlags::ALL_COMMANDS,
+			vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE
+		);
+	}
+
+	fn record_release_barrier(
+		recording: &CommandBufferRecordingLockOutsideRenderPass,
+		transfers: &[&OwnershipTransfer],
+		families: (u32, u32),
+		destination_stages: vk::PipelineStageFlags,
+		destination_access: vk::AccessFlags
+	) {
+		if transfers.is_empty() {
+			return
+		}
+
+		let buffer_barriers: Vec<_> = transfers
+			.iter()
+			.filter_map(|t| t.buffer_barrier(families, vk::AccessFlags::TRANSFER_WRITE, destination_access))
+			.collect();
+		let image_barriers: Vec<_> = transfers
+			.iter()
+			.filter_map(|t| t.image_barrier(families, vk::AccessFlags::TRANSFER_WRITE, destination_access))
+			.collect();
+		recording.pipeline_barrier(
+			vk::PipelineStageFlags::TRANSFER,
+			destination_stages,
+			[] as [MemoryBarrier; 0],
+			buffer_barriers,
+			image_barriers
+		);
+	}
+
+	fn submit_pending(&mut self, signal_after: Option<&Semaphore>) -> Result<Option<TransferBatch>, TransferError<A::Error>> {
+		if self.pending.is_empty() {
+			return Ok(None)
+		}
+
+		let transfers: Vec<_> = self
+			.pending
+			.iter()
+			.map(|job| match job {
+				TransferJob::Buffer { staging, destination, offset } => OwnershipTransfer::Buffer {
+					buffer: destination.clone(),
+					offset: *offset,
+					size: staging.size().get()
+				},
+				TransferJob::Image { destination, subresource_range, final_layout, .. } => OwnershipTransfer::Image {
+					image: destination.clone(),
+					subresource_range: *subresource_range,
+					final_layout: *final_layout
+				}
+			})
+			.collect();
+
+		let [command_buffer] = CommandBuffer::new(self.command_pool.clone(), false)?;
+		{
+			let recording = command_buffer.begin_recording(CommandBufferBeginInfo::OneTime)?;
+			self.record(&recording, &transfers);
+			recording.end()?;
+		}
+
+		let fence = self.fence_pool.rent()?;
+		let submitted = match signal_after {
+			None => self.queue.submit(
+				[],
+				[],
+				[command_buffer.deref()],
+				[],
+				Some(&fence)
+			),
+			Some(semaphore) => self.queue.submit(
+				[],
+				[],
+				[command_buffer.deref()],
+				[semaphore],
+				Some(&fence)
+			)
+		};
+		if let Err(err) = submitted {
+			// The fence was not submitted and only referenced here
+			unsafe { self.fence_pool.return_unsubmitted(fence) };
+			return Err(err.into())
+		}
+
+		let id = TransferBatchId(self.next_batch);
+		self.next_batch += 1;
+		self.in_flight.push_back(InFlightBatch { id, fence, command_buffer, jobs: std::mem::take(&mut self.pending) });
+
+		// Only released resources are acquired
+		let acquired: Vec<_> = transfers.into_iter().filter(|t| !t.is_concurrent()).collect();
+		let families = self.ownership_families().filter(|_| !acquired.is_empty());
+
+		Ok(Some(TransferBatch { id, families, transfers: acquired }))
+	}
+
+	/// Submits all queued jobs as one batch.
+	///
+	/// Returns `None` if there were no queued jobs.
+	pub fn flush(&mut self) -> Result<Option<TransferBatch>, TransferError<A::Error>> {
+		self.submit_pending(None)
+	}
+
+	/// Submits all queued jobs as one batch that signals `semaphore` when finished.
+	///
+	/// The destination queue can wait on `semaphore` instead of polling. Returns `None` and does not signal
+	/// `semaphore` if there were no queued jobs.
+	pub fn flush_signaling(&mut self, semaphore: &Semaphore) -> Result<Option<TransferBatch>, TransferError<A::Error>> {
+		self.submit_pending(Some(semaphore))
+	}
+
+	/// Releases the resources of finished batches and returns their ids.
+	///
+	/// Does not block.
+	pub fn poll(&mut self) -> Result<Vec<TransferBatchId>, FenceStatusError> {
+		let mut finished = Vec::new();
+
+		while let Some(batch) = self.in_flight.front() {
+			if !batch.fence.status()? {
+				break
+			}
+
+			finished.push(batch.id);
+			self.in_flight.pop_front();
+		}
+
+		Ok(finished)
+	}
+
+	/// Returns whether the batch `id` is finished, as of the last `poll`.
+	pub fn is_finished(&self, id: TransferBatchId) -> bool {
+		match self.in_flight.front() {
+			Some(batch) => id < batch.id,
+			None => id.0 < self.next_batch
+		}
+	}
+
+	/// Returns the number of jobs waiting for the next flush.
+	pub fn pending_jobs(&self) -> usize {
+		self.pending.len()
+	}
+
+	/// Returns the number of submitted batches that were not reported as finished yet.
+	pub fn in_flight_batches(&self) -> usize {
+		self.in_flight.len()
+	}
+
+	pub const fn queue(&self) -> &Vrc<Queue> {
+		&self.queue
+	}
+
+	pub const fn command_pool(&self) -> &Vrc<CommandPool> {
+		&self.command_pool
+	}
+
+	pub const fn destination_queue_family_index(&self) -> u32 {
+		self.destination_queue_family_index
+	}
+}
+impl<A: BufferMemoryAllocator> Drop for TransferScheduler<A>
+where
+	A::AllocationRequirements: Clone
+{
+	fn drop(&mut self) {
+		// Staging buffers and command buffers must outlive the execution
+		for batch in self.in_flight.iter() {
+			if let Err(err) = batch.fence.wait(Default::default()) {
+				log::error!("Could not wait for transfer batch: {}", err);
+			}
+		}
+	}
+}
+impl<A: BufferMemoryAllocator> fmt::Debug for TransferScheduler<A>
+where
+	A::AllocationRequirements: Clone
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TransferScheduler")
+			.field("queue", &self.queue)
+			.field("command_pool", &self.command_pool)
+			.field("fence_pool", &self.fence_pool)
+			.field(
+				"destination_queue_family_index",
+				&self.destination_queue_family_index
+			)
+			.field("allocator", &self.allocator)
+			.field("requirements", &self.requirements)
+			.field("host_memory_allocator", &self.host_memory_allocator)
+			.field("pending", &self.pending)
+			.field("in_flight", &self.in_flight)
+			.field("next_batch", &self.next_batch)
+			.finish()
+	}
+}
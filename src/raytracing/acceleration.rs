@@ -0,0 +1,431 @@
+use std::{convert::Infallible, fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use super::error::AccelerationStructureError;
+use crate::{
+	memory::device::allocator::BufferMemoryAllocator,
+	prelude::{Buffer, Device, HasHandle, HostMemoryAllocator, Vrc},
+	queue::sharing_mode::SharingMode,
+	resource::buffer::{
+		error::{BufferDeviceAddressError, BufferError},
+		params::BufferAllocatorParams
+	}
+};
+
+/// Geometry of an acceleration structure build.
+///
+/// All buffers must be created with `ACCELERATION_STRUCTURE_BUILD_INPUT_READ_ONLY_KHR` and `SHADER_DEVICE_ADDRESS` usage.
+#[derive(Debug, Clone, Copy)]
+pub enum AccelerationStructureGeometry<'a> {
+	/// Triangles of a bottom-level acceleration structure.
+	Triangles {
+		vertices: &'a Buffer,
+		vertex_offset: vk::DeviceSize,
+		vertex_format: vk::Format,
+		vertex_stride: vk::DeviceSize,
+		/// Highest index of a vertex referenced by the triangles.
+		max_vertex: u32,
+		/// Index buffer, offset and index type, `None` for non-indexed triangles.
+		indices: Option<(&'a Buffer, vk::DeviceSize, vk::IndexType)>,
+		triangle_count: u32,
+		flags: vk::GeometryFlagsKHR
+	},
+	/// Axis-aligned bounding boxes of procedural geometry of a bottom-level acceleration structure.
+	Aabbs { aabbs: &'a Buffer, offset: vk::DeviceSize, stride: vk::DeviceSize, count: u32, flags: vk::GeometryFlagsKHR },
+	/// Instances of a top-level acceleration structure, see `AccelerationStructure::instance`.
+	Instances { instances: &'a Buffer, offset: vk::DeviceSize, count: u32, flags: vk::GeometryFlagsKHR }
+}
+impl AccelerationStructureGeometry<'_> {
+	/// Returns the number of triangles, bounding boxes or instances.
+	pub const fn primitive_count(&self) -> u32 {
+		match *self {
+			AccelerationStructureGeometry::Triangles { triangle_count, .. } => triangle_count,
+			AccelerationStructureGeometry::Aabbs { count, .. } => count,
+			AccelerationStructureGeometry::Instances { count, .. } => count
+		}
+	}
+
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	pub fn to_raw(&self) -> Result<vk::AccelerationStructureGeometryKHR, BufferDeviceAddressError> {
+		let (geometry_type, geometry, flags) = match *self {
+			AccelerationStructureGeometry::Triangles {
+				vertices,
+				vertex_offset,
+				vertex_format,
+				vertex_stride,
+				max_vertex,
+				indices,
+				flags,
+				..
+			} => {
+				let triangles = vk::AccelerationStructureGeometryTrianglesDataKHR::builder()
+					.vertex_format(vertex_format)
+					.vertex_data(vk::DeviceOrHostAddressConstKHR { device_address: vertices.device_address()? + vertex_offset })
+					.vertex_stride(vertex_stride)
+					.max_vertex(max_vertex)
+					.index_type(indices.map(|(_, _, index_type)| index_type).unwrap_or(vk::IndexType::NONE_KHR))
+					.index_data(vk::DeviceOrHostAddressConstKHR {
+						device_address: indices
+							.map(|(buffer, offset, _)| buffer.device_address().map(|address| address + offset))
+							.transpose()?
+							.unwrap_or(0)
+					})
+					.build();
+
+				(
+					vk::GeometryTypeKHR::TRIANGLES,
+					vk::AccelerationStructureGeometryDataKHR { triangles },
+					flags
+				)
+			}
+			AccelerationStructureGeometry::Aabbs { aabbs, offset, stride, flags, .. } => {
+				let aabbs = vk::AccelerationStructureGeometryAabbsDataKHR::builder()
+					.data(vk::DeviceOrHostAddressConstKHR { device_address: aabbs.device_address()? + offset })
+					.stride(stride)
+					.build();
+
+				(
+					vk::GeometryTypeKHR::AABBS,
+					vk::AccelerationStructureGeometryDataKHR { aabbs },
+					flags
+				)
+			}
+			AccelerationStructureGeometry::Instances { instances, offset, flags, .. } => {
+				let instances = vk::AccelerationStructureGeometryInstancesDataKHR::builder()
+					.array_of_pointers(false)
+					.data(vk::DeviceOrHostAddressConstKHR { device_address: instances.device_address()? + offset })
+					.build();
+
+				(
+					vk::GeometryTypeKHR::INSTANCES,
+					vk::AccelerationStructureGeometryDataKHR { instances },
+					flags
+				)
+			}
+		};
+
+		Ok(vk::AccelerationStructureGeometryKHR::builder().geometry_type(geometry_type).geometry(geometry).flags(flags).build())
+	}
+}
+
+/// Description of an acceleration structure build.
+///
+/// Bottom-level builds contain triangles or bounding boxes, top-level builds contain exactly one `Instances` geometry.
+#[derive(Debug, Clone)]
+pub struct AccelerationStructureBuild<'a> {
+	pub ty: vk::AccelerationStructureTypeKHR,
+	pub flags: vk::BuildAccelerationStructureFlagsKHR,
+	pub geometries: Vec<AccelerationStructureGeometry<'a>>
+}
+impl<'a> AccelerationStructureBuild<'a> {
+	pub fn bottom_level(flags: vk::BuildAccelerationStructureFlagsKHR, geometries: Vec<AccelerationStructureGeometry<'a>>) -> Self {
+		AccelerationStructureBuild { ty: vk::AccelerationStructureTypeKHR::BOTTOM_LEVEL, flags, geometries }
+	}
+
+	/// `instances` must be the `Instances` geometry.
+	pub fn top_level(flags: vk::BuildAccelerationStructureFlagsKHR, instances: AccelerationStructureGeometry<'a>) -> Self {
+		AccelerationStructureBuild { ty: vk::AccelerationStructureTypeKHR::TOP_LEVEL, flags, geometries: vec![instances] }
+	}
+
+	/// Returns the raw geometries and their build ranges.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	pub fn raw_geometries(
+		&self
+	) -> Result<(Vec<vk::AccelerationStructureGeometryKHR>, Vec<vk::AccelerationStructureBuildRangeInfoKHR>), BufferDeviceAddressError> {
+		let geometries = self.geometries.iter().map(AccelerationStructureGeometry::to_raw).collect::<Result<Vec<_>, _>>()?;
+		let ranges = self
+			.geometries
+			.iter()
+			.map(|geometry| vk::AccelerationStructureBuildRangeInfoKHR::builder().primitive_count(geometry.primitive_count()).build())
+			.collect();
+
+		Ok((geometries, ranges))
+	}
+
+	/// Returns the sizes of the acceleration structure and of the scratch buffer required by this build.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetAccelerationStructureBuildSizesKHR.html>.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	pub fn build_sizes(&self, device: &Device) -> Result<vk::AccelerationStructureBuildSizesInfoKHR, BufferDeviceAddressError> {
+		let (geometries, ranges) = self.raw_geometries()?;
+		let max_primitive_counts: Vec<u32> = ranges.iter().map(|range| range.primitive_count).collect();
+
+		let build_info = vk::AccelerationStructureBuildGeometryInfoKHR::builder()
+			.ty(self.ty)
+			.flags(self.flags)
+			.mode(vk::BuildAccelerationStructureModeKHR::BUILD)
+			.geometries(&geometries);
+
+		Ok(unsafe {
+			device.ext().acceleration_structure().get_acceleration_structure_build_sizes(
+				vk::AccelerationStructureBuildTypeKHR::DEVICE,
+				&build_info,
+				&max_primitive_counts
+			)
+		})
+	}
+}
+
+pub struct AccelerationStructure {
+	buffer: Vrc<Buffer>,
+	acceleration_structure: vk::AccelerationStructureKHR,
+
+	ty: vk::AccelerationStructureTypeKHR,
+	offset: vk::DeviceSize,
+	size: NonZeroU64,
+	device_address: vk::DeviceAddress,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl AccelerationStructure {
+	/// Creates a new acceleration structure together with a storage buffer of `size` allocated using `allocator_params`.
+	///
+	/// `size` is usually `acceleration_structure_size` returned by `AccelerationStructureBuild::build_sizes`.
+	/// The memory must be allocated with the `DEVICE_ADDRESS` allocate flag.
+	pub fn new<A: BufferMemoryAllocator>(
+		device: Vrc<Device>,
+		ty: vk::AccelerationStructureTypeKHR,
+		size: NonZeroU64,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		allocator_params: BufferAllocatorParams<A>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, AccelerationStructureError<A::Error>> {
+		let buffer = Buffer::new(
+			device,
+			size,
+			vk::BufferUsageFlags::ACCELERATION_STRUCTURE_STORAGE_KHR | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS,
+			sharing_mode,
+			allocator_params,
+			host_memory_allocator
+		)?;
+
+		unsafe { Self::create(buffer, 0, size, ty, host_memory_allocator) }
+	}
+
+	/// Creates a new acceleration structure in `size` bytes of `buffer` starting at `offset`.
+	///
+	/// `offset` must be a multiple of 256.
+	pub fn from_buffer(
+		buffer: Vrc<Buffer>,
+		offset: vk::DeviceSize,
+		size: NonZeroU64,
+		ty: vk::AccelerationStructureTypeKHR,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, AccelerationStructureError<Infallible>> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if !buffer.usage().contains(vk::BufferUsageFlags::ACCELERATION_STRUCTURE_STORAGE_KHR) {
+				return Err(AccelerationStructureError::UsageMissing)
+			}
+			if offset % 256 != 0 {
+				return Err(AccelerationStructureError::OffsetNotAligned)
+			}
+			if offset + size.get() > buffer.size().get() {
+				return Err(AccelerationStructureError::RangeOutOfBounds)
+			}
+		}
+
+		unsafe { Self::create(buffer, offset, size, ty, host_memory_allocator) }
+	}
+
+	unsafe fn create<E: std::error::Error + 'static>(
+		buffer: Vrc<Buffer>,
+		offset: vk::DeviceSize,
+		size: NonZeroU64,
+		ty: vk::AccelerationStructureTypeKHR,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, AccelerationStructureError<E>> {
+		let create_info = vk::AccelerationStructureCreateInfoKHR::builder()
+			.buffer(buffer.handle())
+			.offset(offset)
+			.size(size.get())
+			.ty(ty);
+
+		log_trace_common!(
+			"Creating acceleration structure:",
+			buffer,
+			offset,
+			size,
+			ty,
+			host_memory_allocator
+		);
+		let device = buffer.device();
+		let loader = device.ext().acceleration_structure();
+		let acceleration_structure = loader.create_acceleration_structure(&create_info, host_memory_allocator.as_ref())?;
+		let device_address = loader.get_acceleration_structure_device_address(
+			&vk::AccelerationStructureDeviceAddressInfoKHR::builder().acceleration_structure(acceleration_structure)
+		);
+
+		device.child_registry().register(acceleration_structure);
+		Ok(Vrc::new(AccelerationStructure {
+			buffer,
+			acceleration_structure,
+			ty,
+			offset,
+			size,
+			device_address,
+			host_memory_allocator
+		}))
+	}
+
+	/// Returns an instance of this bottom-level acceleration structure to be written into the instance buffer of a top-level build.
+	///
+	/// `transform` is a row-major 3x4 matrix. Only the lower 24 bits of `custom_index` and `sbt_record_offset` are used.
+	pub fn instance(
+		&self,
+		transform: [[f32; 4]; 3],
+		custom_index: u32,
+		mask: u8,
+		sbt_record_offset: u32,
+		flags: vk::GeometryInstanceFlagsKHR
+	) -> vk::AccelerationStructureInstanceKHR {
+		let mut matrix = [0.0; 12];
+		for (row, values) in matrix.chunks_exact_mut(4).zip(transform.iter()) {
+			row.copy_from_slice(values);
+		}
+
+		vk::AccelerationStructureInstanceKHR {
+			transform: vk::TransformMatrixKHR { matrix },
+			instance_custom_index_and_mask: vk::Packed24_8::new(custom_index, mask),
+			instance_shader_binding_table_record_offset_and_flags: vk::Packed24_8::new(sbt_record_offset, flags.as_raw() as u8),
+			acceleration_structure_reference: vk::AccelerationStructureReferenceKHR { device_handle: self.device_address }
+		}
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+
+	pub fn device(&self) -> &Vrc<Device> {
+		self.buffer.device()
+	}
+
+	pub const fn ty(&self) -> vk::AccelerationStructureTypeKHR {
+		self.ty
+	}
+
+	pub const fn offset(&self) -> vk::DeviceSize {
+		self.offset
+	}
+
+	pub const fn size(&self) -> NonZeroU64 {
+		self.size
+	}
+
+	pub const fn device_address(&self) -> vk::DeviceAddress {
+		self.device_address
+	}
+}
+impl_common_handle_traits! {
+	impl HasHandle<vk::AccelerationStructureKHR>, Deref, Borrow, Eq, Hash, Ord for AccelerationStructure {
+		target = { acceleration_structure }
+	}
+}
+impl Drop for AccelerationStructure {
+	fn drop(&mut self) {
+		log_trace_common!("Dropping", self);
+		let device = self.buffer.device();
+		device.child_registry().unregister(self.acceleration_structure);
+
+		unsafe {
+			device.ext().acceleration_structure().destroy_acceleration_structure(
+				self.acceleration_structure,
+				self.host_memory_allocator.as_ref()
+			)
+		}
+	}
+}
+impl fmt::Debug for AccelerationStructure {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("AccelerationStructure")
+			.field("buffer", &self.buffer)
+			.field(
+				"acceleration_structure",
+				&self.safe_handle()
+			)
+			.field("ty", &self.ty)
+			.field("offset", &self.offset)
+			.field("size", &self.size)
+			.field("device_address", &self.device_address)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
+
+/// Scratch memory of acceleration structure builds.
+///
+/// The buffer is padded so that `address` is aligned to `minAccelerationStructureScratchOffsetAlignment`.
+/// One scratch buffer can be reused by builds that are not executed concurrently.
+#[derive(Clone)]
+pub struct ScratchBuffer {
+	buffer: Vrc<Buffer>,
+	address: vk::DeviceAddress,
+	size: NonZeroU64
+}
+impl ScratchBuffer {
+	/// Creates a new scratch buffer of at least `size` bytes allocated using `allocator_params`.
+	///
+	/// `size` is usually `build_scratch_size` returned by `AccelerationStructureBuild::build_sizes`.
+	/// The memory must be allocated with the `DEVICE_ADDRESS` allocate flag.
+	pub fn new<A: BufferMemoryAllocator>(
+		device: Vrc<Device>,
+		size: NonZeroU64,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		allocator_params: BufferAllocatorParams<A>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, BufferError<A::Error>> {
+		let alignment = (device
+			.physical_device()
+			.ray_tracing_properties()
+			.0
+			.min_acceleration_structure_scratch_offset_alignment as vk::DeviceSize)
+			.max(1);
+
+		let buffer = Buffer::new(
+			device,
+			NonZeroU64::new(size.get() + alignment - 1).unwrap(),
+			vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS,
+			sharing_mode,
+			allocator_params,
+			host_memory_allocator
+		)?;
+		let address = super::align_up(buffer.device_address().expect("scratch buffer is created with SHADER_DEVICE_ADDRESS usage"), alignment);
+
+		Ok(ScratchBuffer { buffer, address, size })
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+
+	/// Returns the aligned device address of the scratch memory.
+	pub const fn address(&self) -> vk::DeviceAddress {
+		self.address
+	}
+
+	/// Returns the usable size of the scratch memory starting at `address`.
+	pub const fn size(&self) -> NonZeroU64 {
+		self.size
+	}
+}
+impl fmt::Debug for ScratchBuffer {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ScratchBuffer")
+			.field("buffer", &self.buffer)
+			.field("address", &self.address)
+			.field("size", &self.size)
+			.finish()
+	}
+}
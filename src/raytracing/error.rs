@@ -0,0 +1,60 @@
+use thiserror::Error;
+
+use crate::resource::buffer::error::{BufferError, BufferSliceWriteError};
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum AccelerationStructureError [AllocError] where [AllocError: std::error::Error + 'static] {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_INVALID_OPAQUE_CAPTURE_ADDRESS
+		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Buffer must be created with ACCELERATION_STRUCTURE_STORAGE_KHR usage")]
+		UsageMissing,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Offset must be a multiple of 256")]
+		OffsetNotAligned,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Offset and size must lie within the buffer")]
+		RangeOutOfBounds,
+
+		#[error("Could not create the storage buffer")]
+		Buffer(#[from] BufferError<AllocError>),
+	}
+}
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum RayTracingPipelineError {
+		vk {
+			ERROR_PIPELINE_COMPILE_REQUIRED_EXT,
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_INVALID_OPAQUE_CAPTURE_ADDRESS
+		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Shader group {0} references stage {1} which does not exist or has a wrong stage type")]
+		InvalidGroupStage(usize, u32),
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Max ray recursion depth {0} exceeds maxRayRecursionDepth {1}")]
+		RecursionDepthTooLarge(u32, u32),
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum ShaderBindingTableError<AllocError: std::error::Error + 'static> {
+	#[error("Shader group {0} is out of range of the pipeline")]
+	GroupOutOfRange(u32),
+	#[error("Could not get shader group handles")]
+	Handles(#[from] RayTracingPipelineError),
+	#[error("Could not create the table buffer")]
+	Buffer(#[from] BufferError<AllocError>),
+	#[error("Could not write the table")]
+	Write(#[from] BufferSliceWriteError)
+}
@@ -0,0 +1,32 @@
+//! Ray tracing using `VK_KHR_acceleration_structure` and `VK_KHR_ray_tracing_pipeline`.
+//!
+//! Geometry is described by `AccelerationStructureBuild`, whose `build_sizes` returns the sizes of the acceleration structure
+//! and of the scratch memory needed to build it. Both are allocated through a device memory allocator and the build is recorded
+//! on the recording lock:
+//! ```ignore
+//! let build = AccelerationStructureBuild::bottom_level(vk::BuildAccelerationStructureFlagsKHR::PREFER_FAST_TRACE, geometries);
+//! let sizes = build.build_sizes(&device)?;
+//!
+//! let blas = AccelerationStructure::new(device.clone(), build.ty, size(sizes.acceleration_structure_size), sharing_mode, allocator_params, host)?;
+//! let scratch = ScratchBuffer::new(device.clone(), size(sizes.build_scratch_size), sharing_mode, scratch_allocator_params, host)?;
+//! lock.build_acceleration_structure(&build, None, &blas, &scratch);
+//! ```
+//!
+//! Top-level acceleration structures are built the same way from a buffer of instances created with `AccelerationStructure::instance`.
+//! A `RayTracingPipeline` is traced using a `ShaderBindingTable` created from its shader group handles, see
+//! `CommandBufferRecordingLockOutsideRenderPass::trace_rays`.
+//!
+//! Both extensions, their features and the `bufferDeviceAddress` feature must be enabled on the device.
+//! Buffer memory must be allocated with the `DEVICE_ADDRESS` allocate flag.
+
+use ash::vk;
+
+pub mod acceleration;
+pub mod error;
+pub mod pipeline;
+pub mod sbt;
+
+/// Same as `util::align_up` for device sizes and addresses, `alignment` must be a power of two.
+const fn align_up(value: vk::DeviceSize, alignment: vk::DeviceSize) -> vk::DeviceSize {
+	(value + alignment - 1) & !(alignment - 1)
+}
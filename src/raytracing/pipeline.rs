@@ -0,0 +1,246 @@
+use std::fmt;
+
+use ash::vk;
+
+use super::error::RayTracingPipelineError;
+use crate::{
+	pipeline::cache::PipelineCache,
+	prelude::{Device, HasHandle, HostMemoryAllocator, PipelineLayout, Transparent, Vrc}
+};
+
+/// Shader group of a ray tracing pipeline.
+///
+/// Indices refer to the stages passed to `RayTracingPipeline::new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RayTracingShaderGroup {
+	/// Ray generation, miss or callable shader.
+	General(u32),
+	/// Hit group of triangle geometry.
+	TrianglesHit { closest_hit: Option<u32>, any_hit: Option<u32> },
+	/// Hit group of procedural geometry.
+	ProceduralHit { intersection: u32, closest_hit: Option<u32>, any_hit: Option<u32> }
+}
+impl RayTracingShaderGroup {
+	pub fn to_raw(self) -> vk::RayTracingShaderGroupCreateInfoKHR {
+		let (ty, general, closest_hit, any_hit, intersection) = match self {
+			RayTracingShaderGroup::General(general) => (vk::RayTracingShaderGroupTypeKHR::GENERAL, Some(general), None, None, None),
+			RayTracingShaderGroup::TrianglesHit { closest_hit, any_hit } => (
+				vk::RayTracingShaderGroupTypeKHR::TRIANGLES_HIT_GROUP,
+				None,
+				closest_hit,
+				any_hit,
+				None
+			),
+			RayTracingShaderGroup::ProceduralHit { intersection, closest_hit, any_hit } => (
+				vk::RayTracingShaderGroupTypeKHR::PROCEDURAL_HIT_GROUP,
+				None,
+				closest_hit,
+				any_hit,
+				Some(intersection)
+			)
+		};
+
+		vk::RayTracingShaderGroupCreateInfoKHR::builder()
+			.ty(ty)
+			.general_shader(general.unwrap_or(vk::SHADER_UNUSED_KHR))
+			.closest_hit_shader(closest_hit.unwrap_or(vk::SHADER_UNUSED_KHR))
+			.any_hit_shader(any_hit.unwrap_or(vk::SHADER_UNUSED_KHR))
+			.intersection_shader(intersection.unwrap_or(vk::SHADER_UNUSED_KHR))
+			.build()
+	}
+
+	/// Returns the stages referenced by this group together with the stage types they must have.
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn stage_requirements(self) -> impl Iterator<Item = (u32, vk::ShaderStageFlags)> {
+		let (general, closest_hit, any_hit, intersection) = match self {
+			RayTracingShaderGroup::General(general) => (Some(general), None, None, None),
+			RayTracingShaderGroup::TrianglesHit { closest_hit, any_hit } => (None, closest_hit, any_hit, None),
+			RayTracingShaderGroup::ProceduralHit { intersection, closest_hit, any_hit } => (None, closest_hit, any_hit, Some(intersection))
+		};
+
+		general
+			.map(|index| {
+				(
+					index,
+					vk::ShaderStageFlags::RAYGEN_KHR | vk::ShaderStageFlags::MISS_KHR | vk::ShaderStageFlags::CALLABLE_KHR
+				)
+			})
+			.into_iter()
+			.chain(closest_hit.map(|index| (index, vk::ShaderStageFlags::CLOSEST_HIT_KHR)))
+			.chain(any_hit.map(|index| (index, vk::ShaderStageFlags::ANY_HIT_KHR)))
+			.chain(intersection.map(|index| (index, vk::ShaderStageFlags::INTERSECTION_KHR)))
+	}
+}
+
+/// Layout of shader group handles in shader binding tables, from the ray tracing pipeline properties of the physical device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderGroupHandleLayout {
+	/// `shaderGroupHandleSize`
+	pub handle_size: u32,
+	/// `shaderGroupHandleAlignment`
+	pub handle_alignment: u32,
+	/// `shaderGroupBaseAlignment`
+	pub base_alignment: u32
+}
+
+pub struct RayTracingPipeline {
+	device: Vrc<Device>,
+	pipeline: vk::Pipeline,
+	group_count: u32,
+	handle_layout: ShaderGroupHandleLayout,
+	host_memory_allocator: HostMemoryAllocator
+}
+impl RayTracingPipeline {
+	/// Creates a new ray tracing pipeline from shader `stages` and shader `groups`.
+	///
+	/// Stage create infos can be obtained from `ShaderModule::stage_create_info`.
+	pub fn new<'a>(
+		device: Vrc<Device>,
+		stages: &[vk::PipelineShaderStageCreateInfoBuilder<'a>],
+		groups: &[RayTracingShaderGroup],
+		max_recursion_depth: u32,
+		layout: &PipelineLayout,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, RayTracingPipelineError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			for (group_index, group) in groups.iter().enumerate() {
+				for (stage_index, allowed_stages) in group.stage_requirements() {
+					let valid = stages
+						.get(stage_index as usize)
+						.map(|stage| allowed_stages.contains(stage.stage))
+						.unwrap_or(false);
+					if !valid {
+						return Err(RayTracingPipelineError::InvalidGroupStage(group_index, stage_index))
+					}
+				}
+			}
+
+			let max_ray_recursion_depth = device.physical_device().ray_tracing_properties().1.max_ray_recursion_depth;
+			if max_recursion_depth > max_ray_recursion_depth {
+				return Err(RayTracingPipelineError::RecursionDepthTooLarge(
+					max_recursion_depth,
+					max_ray_recursion_depth
+				))
+			}
+		}
+
+		let groups_raw: Vec<_> = groups.iter().map(|group| group.to_raw()).collect();
+		let create_info = vk::RayTracingPipelineCreateInfoKHR::builder()
+			.stages(Transparent::transmute_slice(stages))
+			.groups(&groups_raw)
+			.max_pipeline_ray_recursion_depth(max_recursion_depth)
+			.layout(layout.handle());
+
+		unsafe { Self::from_create_info(device, &create_info, cache, host_memory_allocator) }
+	}
+
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkCreateRayTracingPipelinesKHR.html>.
+	pub unsafe fn from_create_info(
+		device: Vrc<Device>,
+		create_info: &vk::RayTracingPipelineCreateInfoKHR,
+		cache: Option<&PipelineCache>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, RayTracingPipelineError> {
+		if log::log_enabled!(log::Level::Trace) {
+			log_trace_common!(
+				"Creating ray tracing pipeline:",
+				device,
+				create_info.flags,
+				create_info.stage_count,
+				create_info.group_count,
+				create_info.max_pipeline_ray_recursion_depth,
+				create_info.layout,
+				cache,
+				host_memory_allocator
+			);
+		}
+
+		let pipeline = device.ext().ray_tracing_pipeline().create_ray_tracing_pipelines(
+			vk::DeferredOperationKHR::null(),
+			cache.map(|c| c.handle()).unwrap_or_default(),
+			&[*create_info],
+			host_memory_allocator.as_ref()
+		)?[0];
+		let properties = device.physical_device().ray_tracing_properties().1;
+		let handle_layout = ShaderGroupHandleLayout {
+			handle_size: properties.shader_group_handle_size,
+			handle_alignment: properties.shader_group_handle_alignment,
+			base_alignment: properties.shader_group_base_alignment
+		};
+
+		device.child_registry().register(pipeline);
+		Ok(Vrc::new(RayTracingPipeline {
+			device,
+			pipeline,
+			group_count: create_info.group_count,
+			handle_layout,
+			host_memory_allocator
+		}))
+	}
+
+	/// Returns the shader group handles of `group_count` groups starting at `first_group`, each `shaderGroupHandleSize` bytes long.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetRayTracingShaderGroupHandlesKHR.html>.
+	pub fn shader_group_handles(&self, first_group: u32, group_count: u32) -> Result<Vec<u8>, RayTracingPipelineError> {
+		let data_size = (group_count * self.handle_layout.handle_size) as usize;
+
+		let handles = unsafe {
+			self.device.ext().ray_tracing_pipeline().get_ray_tracing_shader_group_handles(
+				self.pipeline,
+				first_group,
+				group_count,
+				data_size
+			)?
+		};
+
+		Ok(handles)
+	}
+
+	pub const fn group_count(&self) -> u32 {
+		self.group_count
+	}
+
+	pub const fn handle_layout(&self) -> ShaderGroupHandleLayout {
+		self.handle_layout
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl_common_handle_traits! {
+	impl HasHandle<vk::Pipeline>, Deref, Borrow, Eq, Hash, Ord for RayTracingPipeline {
+		target = { pipeline }
+	}
+}
+impl Drop for RayTracingPipeline {
+	fn drop(&mut self) {
+		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.pipeline);
+
+		unsafe {
+			self.device.destroy_pipeline(
+				self.pipeline,
+				self.host_memory_allocator.as_ref()
+			)
+		}
+	}
+}
+impl fmt::Debug for RayTracingPipeline {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("RayTracingPipeline")
+			.field("device", &self.device)
+			.field("pipeline", &self.safe_handle())
+			.field("group_count", &self.group_count)
+			.field("handle_layout", &self.handle_layout)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
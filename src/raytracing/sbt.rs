@@ -0,0 +1,147 @@
+use std::{fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use super::{align_up, error::ShaderBindingTableError, pipeline::RayTracingPipeline};
+use crate::{
+	memory::device::{allocator::BufferMemoryAllocator, MappingAccessResult},
+	prelude::{Buffer, HostMemoryAllocator, Vrc},
+	queue::sharing_mode::SharingMode,
+	resource::buffer::{error::BufferSliceWriteError, params::BufferAllocatorParams}
+};
+
+/// Shader binding table of a ray tracing pipeline.
+///
+/// The table stores the shader group handles of the ray generation, miss, hit and callable regions in one buffer,
+/// laid out according to `shaderGroupHandleAlignment` and `shaderGroupBaseAlignment`.
+#[derive(Clone)]
+pub struct ShaderBindingTable {
+	buffer: Vrc<Buffer>,
+	raygen: vk::StridedDeviceAddressRegionKHR,
+	miss: vk::StridedDeviceAddressRegionKHR,
+	hit: vk::StridedDeviceAddressRegionKHR,
+	callable: vk::StridedDeviceAddressRegionKHR
+}
+impl ShaderBindingTable {
+	/// Creates a new shader binding table from the shader group indices of `pipeline` in each region.
+	///
+	/// The memory allocated using `allocator_params` must be host visible and allocated with the `DEVICE_ADDRESS` allocate flag.
+	pub fn new<A: BufferMemoryAllocator>(
+		pipeline: &RayTracingPipeline,
+		raygen: u32,
+		miss: &[u32],
+		hit: &[u32],
+		callable: &[u32],
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		allocator_params: BufferAllocatorParams<A>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, ShaderBindingTableError<A::Error>> {
+		if let Some(&group) = std::iter::once(&raygen)
+			.chain(miss)
+			.chain(hit)
+			.chain(callable)
+			.find(|&&group| group >= pipeline.group_count())
+		{
+			return Err(ShaderBindingTableError::GroupOutOfRange(group))
+		}
+
+		let layout = pipeline.handle_layout();
+		let handle_size = layout.handle_size as vk::DeviceSize;
+		let handle_stride = align_up(handle_size, layout.handle_alignment as vk::DeviceSize);
+		let base_alignment = layout.base_alignment as vk::DeviceSize;
+
+		// Regions are placed one after another, each starting at a multiple of the base alignment
+		let regions = [std::slice::from_ref(&raygen), miss, hit, callable];
+		let mut region_offsets = [0; 4];
+		let mut size = 0;
+		for (offset, groups) in region_offsets.iter_mut().zip(regions.iter()) {
+			*offset = size;
+			size += align_up(groups.len() as vk::DeviceSize * handle_stride, base_alignment);
+		}
+
+		let handles = pipeline.shader_group_handles(0, pipeline.group_count())?;
+		let mut data = vec![0u8; size as usize];
+		for (offset, groups) in region_offsets.iter().zip(regions.iter()) {
+			for (index, &group) in groups.iter().enumerate() {
+				let source = group as usize * handle_size as usize;
+				let destination = (offset + index as vk::DeviceSize * handle_stride) as usize;
+				data[destination .. destination + handle_size as usize].copy_from_slice(&handles[source .. source + handle_size as usize]);
+			}
+		}
+
+		// Pad the buffer so that the table can start at an address aligned to the base alignment
+		let buffer = Buffer::new(
+			pipeline.device().clone(),
+			NonZeroU64::new(size + base_alignment - 1).unwrap(),
+			vk::BufferUsageFlags::SHADER_BINDING_TABLE_KHR | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS,
+			sharing_mode,
+			allocator_params,
+			host_memory_allocator
+		)?;
+		let buffer_address = buffer.device_address().expect("table buffer is created with SHADER_DEVICE_ADDRESS usage");
+		let address = align_up(buffer_address, base_alignment);
+
+		buffer
+			.memory()
+			.ok_or(BufferSliceWriteError::NoMemory)?
+			.map_memory_with(|mut access| {
+				let start = (address - buffer_address) as usize;
+				access.bytes_mut()[start .. start + data.len()].copy_from_slice(&data);
+				MappingAccessResult::Continue
+			})
+			.map_err(BufferSliceWriteError::from)?;
+
+		let region = |index: usize, stride: vk::DeviceSize| {
+			let count = regions[index].len() as vk::DeviceSize;
+			if count == 0 {
+				return vk::StridedDeviceAddressRegionKHR::default()
+			}
+
+			vk::StridedDeviceAddressRegionKHR {
+				device_address: address + region_offsets[index],
+				stride,
+				size: align_up(count * handle_stride, stride.max(handle_stride))
+			}
+		};
+
+		Ok(ShaderBindingTable {
+			// The size of the ray generation region must be equal to its stride
+			raygen: region(0, align_up(handle_stride, base_alignment)),
+			miss: region(1, handle_stride),
+			hit: region(2, handle_stride),
+			callable: region(3, handle_stride),
+			buffer
+		})
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+
+	pub const fn raygen_region(&self) -> &vk::StridedDeviceAddressRegionKHR {
+		&self.raygen
+	}
+
+	pub const fn miss_region(&self) -> &vk::StridedDeviceAddressRegionKHR {
+		&self.miss
+	}
+
+	pub const fn hit_region(&self) -> &vk::StridedDeviceAddressRegionKHR {
+		&self.hit
+	}
+
+	pub const fn callable_region(&self) -> &vk::StridedDeviceAddressRegionKHR {
+		&self.callable
+	}
+}
+impl fmt::Debug for ShaderBindingTable {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ShaderBindingTable")
+			.field("buffer", &self.buffer)
+			.field("raygen", &self.raygen)
+			.field("miss", &self.miss)
+			.field("hit", &self.hit)
+			.field("callable", &self.callable)
+			.finish()
+	}
+}
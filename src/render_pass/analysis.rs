@@ -0,0 +1,228 @@
+//! Analysis of render pass descriptions for wasted memory bandwidth.
+//!
+//! Tiled GPUs load attachments from memory at the start of a render pass and store them at the end,
+//! so unnecessary `LOAD` and `STORE` operations directly cost bandwidth.
+//!
+//! With the `render_pass_analysis` feature in debug builds, every render pass is analyzed on creation and the warnings are logged.
+
+use std::fmt;
+
+use ash::vk;
+
+use crate::resource::image::params::format_aspects;
+
+/// Potential bandwidth waste found in a render pass description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderPassWarning {
+	/// Attachment is loaded even though its initial layout is `UNDEFINED`, so the loaded contents are undefined anyway.
+	LoadFromUndefined { attachment: u32 },
+	/// Multisampled attachment is stored even though it is resolved in every subpass that writes it.
+	StoreResolvedMultisample { attachment: u32 },
+	/// Attachment is stored, but its final layout is an attachment layout, suggesting that it is never sampled or copied later.
+	StoreNotSampled { attachment: u32, final_layout: vk::ImageLayout },
+	/// Attachment is not used by any subpass, but is loaded or stored.
+	UnusedAttachment { attachment: u32 }
+}
+impl fmt::Display for RenderPassWarning {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			RenderPassWarning::LoadFromUndefined { attachment } => write!(
+				f,
+				"attachment {} uses LOAD with UNDEFINED initial layout, use CLEAR or DONT_CARE instead",
+				attachment
+			),
+			RenderPassWarning::StoreResolvedMultisample { attachment } => write!(
+				f,
+				"multisampled attachment {} is resolved but also uses STORE, use DONT_CARE instead",
+				attachment
+			),
+			RenderPassWarning::StoreNotSampled { attachment, final_layout } => write!(
+				f,
+				"attachment {} uses STORE but its final layout is {:?}, use DONT_CARE if it isn't loaded by a later render pass",
+				attachment, final_layout
+			),
+			RenderPassWarning::UnusedAttachment { attachment } => write!(
+				f,
+				"attachment {} is not used by any subpass but is loaded or stored",
+				attachment
+			)
+		}
+	}
+}
+
+/// Analyzes render pass `create_info`.
+///
+/// ### Safety
+///
+/// `create_info` must be a valid `vk::RenderPassCreateInfo`.
+pub unsafe fn analyze_create_info(create_info: &vk::RenderPassCreateInfo) -> Vec<RenderPassWarning> {
+	let attachments = raw_slice(
+		create_info.p_attachments,
+		create_info.attachment_count
+	);
+	let subpasses = raw_slice(
+		create_info.p_subpasses,
+		create_info.subpass_count
+	);
+
+	let mut used = vec![false; attachments.len()];
+	let mut written_unresolved = vec![false; attachments.len()];
+	let mark = |reference: &vk::AttachmentReference, target: &mut Vec<bool>| {
+		if let Some(value) = target.get_mut(reference.attachment as usize) {
+			*value = true;
+		}
+	};
+
+	for subpass in subpasses {
+		let colors = raw_slice(
+			subpass.p_color_attachments,
+			subpass.color_attachment_count
+		);
+		let resolves = raw_slice(
+			subpass.p_resolve_attachments,
+			if subpass.p_resolve_attachments.is_null() { 0 } else { subpass.color_attachment_count }
+		);
+
+		for (index, color) in colors.iter().enumerate() {
+			mark(color, &mut used);
+
+			let resolved = resolves
+				.get(index)
+				.map(|r| r.attachment != vk::ATTACHMENT_UNUSED)
+				.unwrap_or(false);
+			if !resolved {
+				mark(color, &mut written_unresolved);
+			}
+		}
+		for reference in resolves
+			.iter()
+			.chain(raw_slice(subpass.p_input_attachments, subpass.input_attachment_count))
+			.chain(subpass.p_depth_stencil_attachment.as_ref())
+		{
+			mark(reference, &mut used);
+			mark(reference, &mut written_unresolved);
+		}
+	}
+
+	let mut warnings = Vec::new();
+	for (index, attachment) in attachments.iter().enumerate() {
+		let index_u32 = index as u32;
+		let aspects = format_aspects(attachment.format);
+		let has_stencil = aspects.contains(vk::ImageAspectFlags::STENCIL);
+		let has_main = aspects.intersects(vk::ImageAspectFlags::COLOR | vk::ImageAspectFlags::DEPTH);
+
+		let loads = (has_main && attachment.load_op == vk::AttachmentLoadOp::LOAD)
+			|| (has_stencil && attachment.stencil_load_op == vk::AttachmentLoadOp::LOAD);
+		let stores = (has_main && attachment.store_op == vk::AttachmentStoreOp::STORE)
+			|| (has_stencil && attachment.stencil_store_op == vk::AttachmentStoreOp::STORE);
+
+		if !used[index] {
+			if loads || stores {
+				warnings.push(RenderPassWarning::UnusedAttachment { attachment: index_u32 });
+			}
+			continue
+		}
+
+		if loads && attachment.initial_layout == vk::ImageLayout::UNDEFINED {
+			warnings.push(RenderPassWarning::LoadFromUndefined { attachment: index_u32 });
+		}
+
+		if stores {
+			if attachment.samples != vk::SampleCountFlags::TYPE_1 && !written_unresolved[index] {
+				warnings.push(RenderPassWarning::StoreResolvedMultisample { attachment: index_u32 });
+			} else if is_attachment_layout(attachment.final_layout) {
+				warnings.push(RenderPassWarning::StoreNotSampled { attachment: index_u32, final_layout: attachment.final_layout });
+			}
+		}
+	}
+
+	warnings
+}
+
+fn is_attachment_layout(layout: vk::ImageLayout) -> bool {
+	match layout {
+		vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL | vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL => true,
+		#[cfg(feature = "vulkan1_2")]
+		vk::ImageLayout::DEPTH_ATTACHMENT_OPTIMAL | vk::ImageLayout::STENCIL_ATTACHMENT_OPTIMAL => true,
+		_ => false
+	}
+}
+
+unsafe fn raw_slice<'a, T>(ptr: *const T, len: u32) -> &'a [T] {
+	if ptr.is_null() || len == 0 {
+		&[]
+	} else {
+		std::slice::from_raw_parts(ptr, len as usize)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{analyze_create_info, RenderPassWarning};
+
+	#[test]
+	fn analyze_wasteful_ops() {
+		let attachments = [
+			// multisampled color, resolved, stored
+			vk::AttachmentDescription::builder()
+				.format(vk::Format::R8G8B8A8_UNORM)
+				.samples(vk::SampleCountFlags::TYPE_4)
+				.load_op(vk::AttachmentLoadOp::LOAD)
+				.store_op(vk::AttachmentStoreOp::STORE)
+				.initial_layout(vk::ImageLayout::UNDEFINED)
+				.final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+				.build(),
+			// resolve target, presented
+			vk::AttachmentDescription::builder()
+				.format(vk::Format::R8G8B8A8_UNORM)
+				.samples(vk::SampleCountFlags::TYPE_1)
+				.load_op(vk::AttachmentLoadOp::DONT_CARE)
+				.store_op(vk::AttachmentStoreOp::STORE)
+				.initial_layout(vk::ImageLayout::UNDEFINED)
+				.final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+				.build(),
+			// depth, stored but not sampled later
+			vk::AttachmentDescription::builder()
+				.format(vk::Format::D32_SFLOAT)
+				.samples(vk::SampleCountFlags::TYPE_1)
+				.load_op(vk::AttachmentLoadOp::CLEAR)
+				.store_op(vk::AttachmentStoreOp::STORE)
+				.initial_layout(vk::ImageLayout::UNDEFINED)
+				.final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+				.build(),
+			// unused
+			vk::AttachmentDescription::builder()
+				.format(vk::Format::R8_UNORM)
+				.samples(vk::SampleCountFlags::TYPE_1)
+				.load_op(vk::AttachmentLoadOp::LOAD)
+				.store_op(vk::AttachmentStoreOp::DONT_CARE)
+				.initial_layout(vk::ImageLayout::GENERAL)
+				.final_layout(vk::ImageLayout::GENERAL)
+				.build()
+		];
+		let color = [vk::AttachmentReference { attachment: 0, layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL }];
+		let resolve = [vk::AttachmentReference { attachment: 1, layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL }];
+		let depth = vk::AttachmentReference { attachment: 2, layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL };
+		let subpasses = [vk::SubpassDescription::builder()
+			.color_attachments(&color)
+			.resolve_attachments(&resolve)
+			.depth_stencil_attachment(&depth)
+			.build()];
+		let create_info = vk::RenderPassCreateInfo::builder()
+			.attachments(&attachments)
+			.subpasses(&subpasses);
+
+		let warnings = unsafe { analyze_create_info(&create_info) };
+		assert_eq!(
+			warnings,
+			[
+				RenderPassWarning::LoadFromUndefined { attachment: 0 },
+				RenderPassWarning::StoreResolvedMultisample { attachment: 0 },
+				RenderPassWarning::StoreNotSampled { attachment: 2, final_layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL },
+				RenderPassWarning::UnusedAttachment { attachment: 3 }
+			]
+		);
+	}
+}
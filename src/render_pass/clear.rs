@@ -0,0 +1,146 @@
+//! Color-management aware clear values.
+//!
+//! Clear values are written to attachments without going through the shader, so they must be encoded
+//! the same way as the rendered content:
+//! * `_SRGB` formats encode on write, the clear value is passed through as linear.
+//! * `_UNORM` formats presented in `SRGB_NONLINEAR` color space must be sRGB-encoded manually.
+//! * Float formats in `EXTENDED_SRGB_LINEAR` color space are linear.
+//! * `HDR10_ST2084` color space requires conversion to BT.2020 primaries and PQ encoding.
+
+use ash::vk;
+
+/// Default luminance of SDR white in nits used when encoding into HDR10 PQ.
+pub const DEFAULT_SDR_WHITE_NITS: f32 = 203.0;
+
+/// Applies the sRGB transfer function (OETF) to a linear `value`.
+pub fn linear_to_srgb(value: f32) -> f32 {
+	if value <= 0.0031308 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+/// Encodes absolute luminance `nits` using the SMPTE ST 2084 (PQ) transfer function.
+pub fn pq_encode(nits: f32) -> f32 {
+	const M1: f32 = 2610.0 / 16384.0;
+	const M2: f32 = 2523.0 / 4096.0 * 128.0;
+	const C1: f32 = 3424.0 / 4096.0;
+	const C2: f32 = 2413.0 / 4096.0 * 32.0;
+	const C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+	let y = (nits / 10000.0).max(0.0).min(1.0).powf(M1);
+
+	((C1 + C2 * y) / (1.0 + C3 * y)).powf(M2)
+}
+
+/// Converts linear color with BT.709 (sRGB) primaries to BT.2020 primaries.
+pub fn bt709_to_bt2020(rgb: [f32; 3]) -> [f32; 3] {
+	[
+		0.6274 * rgb[0] + 0.3293 * rgb[1] + 0.0433 * rgb[2],
+		0.0691 * rgb[0] + 0.9195 * rgb[1] + 0.0114 * rgb[2],
+		0.0164 * rgb[0] + 0.0880 * rgb[1] + 0.8956 * rgb[2]
+	]
+}
+
+/// Returns whether `format` performs sRGB encoding on write.
+pub fn is_srgb_format(format: vk::Format) -> bool {
+	match format {
+		vk::Format::R8_SRGB
+		| vk::Format::R8G8_SRGB
+		| vk::Format::R8G8B8_SRGB
+		| vk::Format::B8G8R8_SRGB
+		| vk::Format::R8G8B8A8_SRGB
+		| vk::Format::B8G8R8A8_SRGB
+		| vk::Format::A8B8G8R8_SRGB_PACK32 => true,
+		_ => false
+	}
+}
+
+/// Returns whether `format` stores floating point color.
+pub fn is_float_format(format: vk::Format) -> bool {
+	match format {
+		vk::Format::R16_SFLOAT
+		| vk::Format::R16G16_SFLOAT
+		| vk::Format::R16G16B16_SFLOAT
+		| vk::Format::R16G16B16A16_SFLOAT
+		| vk::Format::R32_SFLOAT
+		| vk::Format::R32G32_SFLOAT
+		| vk::Format::R32G32B32_SFLOAT
+		| vk::Format::R32G32B32A32_SFLOAT
+		| vk::Format::B10G11R11_UFLOAT_PACK32
+		| vk::Format::E5B9G9R9_UFLOAT_PACK32 => true,
+		_ => false
+	}
+}
+
+/// Encodes linear sRGB `color` for an attachment of `format` presented in `color_space`.
+///
+/// Alpha is always passed through unchanged.
+pub fn encode_color(format: vk::Format, color_space: vk::ColorSpaceKHR, color: [f32; 4], sdr_white_nits: f32) -> [f32; 4] {
+	let [r, g, b, a] = color;
+
+	match color_space {
+		vk::ColorSpaceKHR::HDR10_ST2084_EXT => {
+			let [r, g, b] = bt709_to_bt2020([r, g, b]);
+			[
+				pq_encode(r * sdr_white_nits),
+				pq_encode(g * sdr_white_nits),
+				pq_encode(b * sdr_white_nits),
+				a
+			]
+		}
+		vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => color,
+		_ if is_srgb_format(format) || is_float_format(format) => color,
+		_ => [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a]
+	}
+}
+
+/// Creates a color clear value from linear sRGB `color` for an attachment of `format` presented in `color_space`.
+///
+/// HDR10 targets use `DEFAULT_SDR_WHITE_NITS` as the luminance of `1.0`.
+pub fn clear_color(format: vk::Format, color_space: vk::ColorSpaceKHR, color: [f32; 4]) -> vk::ClearValue {
+	clear_color_with_white(format, color_space, color, DEFAULT_SDR_WHITE_NITS)
+}
+
+/// Same as `clear_color` but with explicit luminance of `1.0` for HDR10 targets.
+pub fn clear_color_with_white(format: vk::Format, color_space: vk::ColorSpaceKHR, color: [f32; 4], sdr_white_nits: f32) -> vk::ClearValue {
+	vk::ClearValue {
+		color: vk::ClearColorValue { float32: encode_color(format, color_space, color, sdr_white_nits) }
+	}
+}
+
+/// Creates a depth-stencil clear value.
+pub fn clear_depth_stencil(depth: f32, stencil: u32) -> vk::ClearValue {
+	vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth, stencil } }
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{encode_color, pq_encode};
+
+	#[test]
+	fn encode_color_by_target() {
+		let color = [0.5, 0.0, 1.0, 0.5];
+
+		assert_eq!(
+			encode_color(vk::Format::B8G8R8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR, color, 203.0),
+			color
+		);
+
+		let unorm = encode_color(vk::Format::B8G8R8A8_UNORM, vk::ColorSpaceKHR::SRGB_NONLINEAR, color, 203.0);
+		assert!((unorm[0] - 0.7354).abs() < 0.001);
+		assert_eq!(unorm[1], 0.0);
+		assert!((unorm[2] - 1.0).abs() < 0.001);
+		assert_eq!(unorm[3], 0.5);
+	}
+
+	#[test]
+	fn pq_encode_reference_points() {
+		assert!(pq_encode(0.0) < 0.001);
+		assert!((pq_encode(10000.0) - 1.0).abs() < 0.001);
+		assert!((pq_encode(100.0) - 0.508).abs() < 0.001);
+	}
+}
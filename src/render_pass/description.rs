@@ -1,5 +1,8 @@
 /// Generates render pass attachment descriptions and subpass descriptions.
 ///
+/// Descriptor writes for the input attachments of a subpass are created using `SubpassDescriptionHolder::input_attachment_infos`
+/// with a framebuffer of the render pass.
+///
 /// The syntax is:
 /// ```
 /// # use vulkayes_core::render_pass_description;
@@ -406,4 +409,29 @@ mod test {
 			}
 		}
 	}
+
+	#[test]
+	fn test_attachment_ops_validate() {
+		assert!(AttachmentOps::color_dont_care().validate(vk::Format::R8G8B8A8_UNORM).is_ok());
+		assert!(AttachmentOps::color_dont_care().validate(vk::Format::D32_SFLOAT).is_err());
+		assert!(AttachmentOps::depth_stencil_dont_care().validate(vk::Format::R8G8B8A8_UNORM).is_err());
+		assert!(AttachmentOps::depth_stencil_dont_care().validate(vk::Format::D32_SFLOAT).is_ok());
+		assert!(AttachmentOps::Preserve.validate(vk::Format::S8_UINT).is_ok());
+
+		let depth_only = AttachmentOps::DepthOnly {
+			load: vk::AttachmentLoadOp::CLEAR,
+			store: vk::AttachmentStoreOp::STORE
+		};
+		assert!(depth_only.validate(vk::Format::D24_UNORM_S8_UINT).is_ok());
+		assert!(depth_only.validate(vk::Format::S8_UINT).is_err());
+
+		let stencil = AttachmentOps::DepthStencil {
+			depth_load: vk::AttachmentLoadOp::CLEAR,
+			depth_store: vk::AttachmentStoreOp::STORE,
+			stencil_load: vk::AttachmentLoadOp::LOAD,
+			stencil_store: vk::AttachmentStoreOp::DONT_CARE
+		};
+		assert!(stencil.validate(vk::Format::D16_UNORM_S8_UINT).is_ok());
+		assert!(stencil.validate(vk::Format::D16_UNORM).is_err());
+	}
 }
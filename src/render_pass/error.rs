@@ -1,3 +1,5 @@
+use ash::vk;
+
 vk_result_error! {
 	#[derive(Debug)]
 	pub enum RenderPassError {
@@ -11,18 +13,65 @@ vk_result_error! {
 		SubpassesEmpty,
 
 		#[cfg(feature = "runtime_implicit_validations")]
-		#[error("Source stage mask of subpass dependency must not be 0")]
-		SrcStageMaskZero,
-
-		#[cfg(feature = "runtime_implicit_validations")]
-		#[error("Destination stage mask of subpass dependency must not be 0")]
-		DstStageMaskZero,
+		#[error("Subpass dependency {0} is invalid")]
+		InvalidDependency(usize, #[source] SubpassDependencyError),
 	}
 }
 
+#[derive(Error, Debug)]
+pub enum SubpassDependencyError {
+	#[error("Subpass {subpass} is out of range of {subpass_count} subpasses")]
+	SubpassOutOfRange { subpass: u32, subpass_count: u32 },
+
+	#[error("Source and destination subpass must not both be external")]
+	BothExternal,
+
+	#[error("Source subpass {source_subpass} must not be after destination subpass {destination_subpass}")]
+	Backwards { source_subpass: u32, destination_subpass: u32 },
+
+	#[error("Source stage mask of subpass dependency must not be 0")]
+	SrcStageMaskZero,
+
+	#[error("Destination stage mask of subpass dependency must not be 0")]
+	DstStageMaskZero
+}
+
 #[derive(Error, Debug)]
 pub enum SubpassDescriptionError {
 	#[cfg(feature = "runtime_implicit_validations")]
 	#[error("Number of resolve attachment references must match number of color attachment references")]
 	ResolveAttachmentsLengthMismatch
 }
+
+#[derive(Error, Debug)]
+pub enum AttachmentOpsError {
+	#[error("Color attachment operations cannot be used with depth/stencil format {0:?}")]
+	ColorOpsOnDepthStencilFormat(vk::Format),
+
+	#[error("Depth/stencil attachment operations cannot be used with color format {0:?}")]
+	DepthStencilOpsOnColorFormat(vk::Format),
+
+	#[error("Depth operations other than DONT_CARE require a depth aspect, but format {0:?} doesn't have one")]
+	MissingDepthAspect(vk::Format),
+
+	#[error("Stencil operations other than DONT_CARE require a stencil aspect, but format {0:?} doesn't have one")]
+	MissingStencilAspect(vk::Format)
+}
+
+#[derive(Error, Debug)]
+pub enum RenderGraphError<AllocError: std::error::Error + 'static> {
+	#[error("Pass {pass} accesses image {image} without a layout")]
+	ImageLayoutMissing { pass: String, image: usize },
+
+	#[error("Pass {pass} declares image {image} more than once")]
+	DuplicateImage { pass: String, image: usize },
+
+	#[error("Pass {pass} declares buffer {buffer} more than once")]
+	DuplicateBuffer { pass: String, buffer: usize },
+
+	#[error("Pass {pass} reads transient image {image} before any pass writes it")]
+	ReadBeforeWrite { pass: String, image: usize },
+
+	#[error("Could not create transient image")]
+	Image(#[from] crate::resource::image::error::ImageError<AllocError>)
+}
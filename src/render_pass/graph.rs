@@ -0,0 +1,805 @@
+//! Render graph deriving barriers between passes from their declared resource accesses.
+//!
+//! Each pass declares which images and buffers it reads and writes using `ResourceAccess`. The graph runs the passes on a single
+//! queue in declaration order and, before each pass, records one pipeline barrier with the execution and memory dependencies
+//! and layout transitions the declared accesses need. Recording inside the passes goes through the usual recording locks.
+//!
+//! Images are either imported, such as swapchain images, or transient, in which case the graph creates them with the usage
+//! derived from their accesses. Transient images start in `UNDEFINED` layout on each execution, so their contents do not
+//! survive between executions. Passes whose writes are never read and which do not write any imported resource are culled.
+//!
+//! ```no_run
+//! # use vulkayes_core::prelude::*;
+//! # use vulkayes_core::render_pass::graph::{RenderGraph, ResourceAccess, TransientImageInfo};
+//! # use vulkayes_core::memory::device::naive::NaiveDeviceMemoryAllocator;
+//! # fn f(device: Vrc<Device>, queue: &Queue, target: Vrc<Image>, recording: CommandBufferRecordingLockOutsideRenderPass) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut graph = RenderGraph::new();
+//! let target = graph.import_image(target);
+//! let scene = graph.transient_image(TransientImageInfo::new_2d(
+//! 	ash::vk::Format::R8G8B8A8_UNORM,
+//! 	[std::num::NonZeroU32::new(800).unwrap(), std::num::NonZeroU32::new(600).unwrap()]
+//! ));
+//!
+//! graph.pass("scene").image(scene, ResourceAccess::COLOR_ATTACHMENT_WRITE).record(|recording, _resources| {
+//! 	// begin render pass with a framebuffer of `scene`, draw, end render pass
+//! 	recording
+//! });
+//! graph
+//! 	.pass("blit")
+//! 	.image(scene, ResourceAccess::TRANSFER_READ)
+//! 	.image(target, ResourceAccess::TRANSFER_WRITE)
+//! 	.record(|recording, resources| {
+//! 		// blit `resources.image(scene)` to `resources.image(target)`
+//! 		recording
+//! 	});
+//!
+//! let mut compiled = graph.compile(
+//! 	device.clone(),
+//! 	queue.queue_family_index(),
+//! 	&NaiveDeviceMemoryAllocator::new(device),
+//! 	ash::vk::MemoryPropertyFlags::DEVICE_LOCAL,
+//! 	HostMemoryAllocator::Unspecified()
+//! )?;
+//! let recording = compiled.execute(recording);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, num::NonZeroU32};
+
+use ash::vk;
+
+use super::error::RenderGraphError;
+use crate::{
+	memory::device::allocator::ImageMemoryAllocator,
+	prelude::{
+		Buffer,
+		BufferMemoryBarrier,
+		CommandBufferRecordingLockOutsideRenderPass,
+		Device,
+		HostMemoryAllocator,
+		Image,
+		ImageAllocatorParams,
+		ImageLayoutFinal,
+		ImageMemoryBarrier,
+		MemoryBarrier,
+		Vrc
+	},
+	queue::sharing_mode::SharingMode,
+	resource::image::params::{ImageSize, ImageSizeInfo, ImageTilingAndLayout, MipmapLevels}
+};
+
+/// Access flags that write memory.
+const WRITE_ACCESS: vk::AccessFlags = vk::AccessFlags::from_raw(
+	vk::AccessFlags::SHADER_WRITE.as_raw()
+		| vk::AccessFlags::COLOR_ATTACHMENT_WRITE.as_raw()
+		| vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE.as_raw()
+		| vk::AccessFlags::TRANSFER_WRITE.as_raw()
+		| vk::AccessFlags::HOST_WRITE.as_raw()
+		| vk::AccessFlags::MEMORY_WRITE.as_raw()
+);
+
+/// Access of a pass to an image or a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceAccess {
+	pub stages: vk::PipelineStageFlags,
+	pub access: vk::AccessFlags,
+	/// Layout the image must be in during the pass, must be `Some` for image accesses and is ignored for buffer accesses.
+	pub layout: Option<ImageLayoutFinal>
+}
+impl ResourceAccess {
+	pub const COLOR_ATTACHMENT_WRITE: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+		access: vk::AccessFlags::from_raw(vk::AccessFlags::COLOR_ATTACHMENT_READ.as_raw() | vk::AccessFlags::COLOR_ATTACHMENT_WRITE.as_raw()),
+		layout: Some(ImageLayoutFinal::COLOR_ATTACHMENT_OPTIMAL)
+	};
+	pub const DEPTH_STENCIL_ATTACHMENT_WRITE: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::from_raw(
+			vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS.as_raw() | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS.as_raw()
+		),
+		access: vk::AccessFlags::from_raw(
+			vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ.as_raw() | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE.as_raw()
+		),
+		layout: Some(ImageLayoutFinal::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+	};
+	pub const DEPTH_STENCIL_ATTACHMENT_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::from_raw(
+			vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS.as_raw() | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS.as_raw()
+		),
+		access: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ,
+		layout: Some(ImageLayoutFinal::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+	};
+	pub const FRAGMENT_SAMPLED_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::FRAGMENT_SHADER,
+		access: vk::AccessFlags::SHADER_READ,
+		layout: Some(ImageLayoutFinal::SHADER_READ_ONLY_OPTIMAL)
+	};
+	pub const COMPUTE_SAMPLED_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::COMPUTE_SHADER,
+		access: vk::AccessFlags::SHADER_READ,
+		layout: Some(ImageLayoutFinal::SHADER_READ_ONLY_OPTIMAL)
+	};
+	/// Storage image or storage buffer read from compute shaders.
+	pub const COMPUTE_STORAGE_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::COMPUTE_SHADER,
+		access: vk::AccessFlags::SHADER_READ,
+		layout: Some(ImageLayoutFinal::GENERAL)
+	};
+	/// Storage image or storage buffer read and written from compute shaders.
+	pub const COMPUTE_STORAGE_WRITE: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::COMPUTE_SHADER,
+		access: vk::AccessFlags::from_raw(vk::AccessFlags::SHADER_READ.as_raw() | vk::AccessFlags::SHADER_WRITE.as_raw()),
+		layout: Some(ImageLayoutFinal::GENERAL)
+	};
+	pub const TRANSFER_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::TRANSFER,
+		access: vk::AccessFlags::TRANSFER_READ,
+		layout: Some(ImageLayoutFinal::TRANSFER_SRC_OPTIMAL)
+	};
+	pub const TRANSFER_WRITE: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::TRANSFER,
+		access: vk::AccessFlags::TRANSFER_WRITE,
+		layout: Some(ImageLayoutFinal::TRANSFER_DST_OPTIMAL)
+	};
+	/// Swapchain image handed to presentation after the graph, declared by the last pass using the image.
+	pub const PRESENT: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+		access: vk::AccessFlags::empty(),
+		layout: Some(ImageLayoutFinal::PRESENT_SRC_KHR)
+	};
+	pub const VERTEX_BUFFER_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::VERTEX_INPUT,
+		access: vk::AccessFlags::VERTEX_ATTRIBUTE_READ,
+		layout: None
+	};
+	pub const INDEX_BUFFER_READ: Self =
+		ResourceAccess { stages: vk::PipelineStageFlags::VERTEX_INPUT, access: vk::AccessFlags::INDEX_READ, layout: None };
+	pub const INDIRECT_BUFFER_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::DRAW_INDIRECT,
+		access: vk::AccessFlags::INDIRECT_COMMAND_READ,
+		layout: None
+	};
+	pub const UNIFORM_BUFFER_READ: Self = ResourceAccess {
+		stages: vk::PipelineStageFlags::from_raw(
+			vk::PipelineStageFlags::VERTEX_SHADER.as_raw()
+				| vk::PipelineStageFlags::FRAGMENT_SHADER.as_raw()
+				| vk::PipelineStageFlags::COMPUTE_SHADER.as_raw()
+		),
+		access: vk::AccessFlags::UNIFORM_READ,
+		layout: None
+	};
+
+	pub const fn new(stages: vk::PipelineStageFlags, access: vk::AccessFlags, layout: Option<ImageLayoutFinal>) -> Self {
+		ResourceAccess { stages, access, layout }
+	}
+
+	/// Returns whether this access writes the resource.
+	pub fn is_write(&self) -> bool {
+		self.access.intersects(WRITE_ACCESS)
+	}
+
+	/// Returns the image usage this access requires.
+	pub fn image_usage(&self) -> vk::ImageUsageFlags {
+		let mut usage = vk::ImageUsageFlags::empty();
+
+		if self
+			.access
+			.intersects(vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+		{
+			usage |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
+		}
+		if self
+			.access
+			.intersects(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+		{
+			usage |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
+		}
+		if self.access.contains(vk::AccessFlags::INPUT_ATTACHMENT_READ) {
+			usage |= vk::ImageUsageFlags::INPUT_ATTACHMENT;
+		}
+		if self.access.intersects(vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE) {
+			usage |= match self.layout {
+				Some(ImageLayoutFinal::GENERAL) => vk::ImageUsageFlags::STORAGE,
+				_ => vk::ImageUsageFlags::SAMPLED
+			};
+		}
+		if self.access.contains(vk::AccessFlags::TRANSFER_READ) {
+			usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+		}
+		if self.access.contains(vk::AccessFlags::TRANSFER_WRITE) {
+			usage |= vk::ImageUsageFlags::TRANSFER_DST;
+		}
+
+		usage
+	}
+}
+
+/// Image registered in a `RenderGraph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImageId(usize);
+/// Buffer registered in a `RenderGraph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferId(usize);
+
+/// Description of a transient image created by the graph.
+///
+/// The usage is the union of the usages of all accesses to the image, see `ResourceAccess::image_usage`.
+#[derive(Debug, Clone, Copy)]
+pub struct TransientImageInfo {
+	pub format: vk::Format,
+	pub size: [NonZeroU32; 2],
+	pub samples: vk::SampleCountFlags,
+	/// Usage in addition to the derived usage.
+	pub additional_usage: vk::ImageUsageFlags
+}
+impl TransientImageInfo {
+	/// Creates a single-sampled 2D image description without additional usage.
+	pub const fn new_2d(format: vk::Format, size: [NonZeroU32; 2]) -> Self {
+		TransientImageInfo { format, size, samples: vk::SampleCountFlags::TYPE_1, additional_usage: vk::ImageUsageFlags::empty() }
+	}
+}
+
+#[derive(Debug)]
+enum GraphImage {
+	Imported(Vrc<Image>),
+	Transient(TransientImageInfo)
+}
+
+type PassRecord<'a> =
+	dyn for<'l> FnMut(CommandBufferRecordingLockOutsideRenderPass<'l>, &GraphResources) -> CommandBufferRecordingLockOutsideRenderPass<'l> + 'a;
+
+struct Pass<'a> {
+	name: &'a str,
+	images: Vec<(ImageId, ResourceAccess)>,
+	buffers: Vec<(BufferId, ResourceAccess)>,
+	record: Box<PassRecord<'a>>
+}
+impl fmt::Debug for Pass<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Pass")
+			.field("name", &self.name)
+			.field("images", &self.images)
+			.field("buffers", &self.buffers)
+			.field("record", &"<closure>")
+			.finish()
+	}
+}
+
+/// Declaration of passes and resources, compiled into a `CompiledRenderGraph`.
+#[derive(Debug, Default)]
+pub struct RenderGraph<'a> {
+	images: Vec<GraphImage>,
+	buffers: Vec<Vrc<Buffer>>,
+	passes: Vec<Pass<'a>>
+}
+impl<'a> RenderGraph<'a> {
+	pub fn new() -> Self {
+		RenderGraph { images: Vec::new(), buffers: Vec::new(), passes: Vec::new() }
+	}
+
+	/// Registers an existing image.
+	///
+	/// The layout of the image before the first pass is read from its layout tracker on each execution and the tracker is updated
+	/// by the barriers of the graph. The whole image must be in the same layout.
+	///
+	/// The first access to the image in the graph waits for all previously submitted commands on the queue.
+	pub fn import_image(&mut self, image: Vrc<Image>) -> ImageId {
+		self.images.push(GraphImage::Imported(image));
+
+		ImageId(self.images.len() - 1)
+	}
+
+	/// Registers an image created by the graph during `compile`.
+	///
+	/// The contents of the image are discarded before its first access in each execution. The first access still waits for
+	/// all previously submitted commands on the queue, which may include accesses of the previous execution of the graph.
+	pub fn transient_image(&mut self, info: TransientImageInfo) -> ImageId {
+		self.images.push(GraphImage::Transient(info));
+
+		ImageId(self.images.len() - 1)
+	}
+
+	/// Registers an existing buffer.
+	///
+	/// The first access to the buffer in the graph waits for all previously submitted commands on the queue.
+	pub fn import_buffer(&mut self, buffer: Vrc<Buffer>) -> BufferId {
+		self.buffers.push(buffer);
+
+		BufferId(self.buffers.len() - 1)
+	}
+
+	/// Starts declaring a pass named `name`, which is added to the graph by `PassBuilder::record`.
+	pub fn pass<'g>(&'g mut self, name: &'a str) -> PassBuilder<'g, 'a> {
+		PassBuilder { graph: self, name, images: Vec::new(), buffers: Vec::new() }
+	}
+
+	/// Validates the passes, culls unused passes and creates the transient images.
+	///
+	/// Transient images are created on the queue family `queue_family_index` using `allocator` with `requirements`.
+	pub fn compile<A: ImageMemoryAllocator>(
+		self,
+		device: Vrc<Device>,
+		queue_family_index: u32,
+		allocator: &A,
+		requirements: A::AllocationRequirements,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<CompiledRenderGraph<'a>, RenderGraphError<A::Error>>
+	where
+		A::AllocationRequirements: Clone
+	{
+		let mut written = vec![false; self.images.len()];
+		let mut usages = vec![vk::ImageUsageFlags::empty(); self.images.len()];
+		for pass in self.passes.iter() {
+			for (index, &(ImageId(image), access)) in pass.images.iter().enumerate() {
+				if access.layout.is_none() {
+					return Err(RenderGraphError::ImageLayoutMissing { pass: pass.name.to_string(), image })
+				}
+				if pass.images[.. index].iter().any(|&(ImageId(previous), _)| previous == image) {
+					return Err(RenderGraphError::DuplicateImage { pass: pass.name.to_string(), image })
+				}
+				if let GraphImage::Transient(_) = self.images[image] {
+					// The first access of a transient image has undefined contents to read from
+					if !access.is_write() && !written[image] {
+						return Err(RenderGraphError::ReadBeforeWrite { pass: pass.name.to_string(), image })
+					}
+				}
+
+				written[image] |= access.is_write();
+				usages[image] |= access.image_usage();
+			}
+			for (index, &(BufferId(buffer), _)) in pass.buffers.iter().enumerate() {
+				if pass.buffers[.. index].iter().any(|&(BufferId(previous), _)| previous == buffer) {
+					return Err(RenderGraphError::DuplicateBuffer { pass: pass.name.to_string(), buffer })
+				}
+			}
+		}
+
+		let live = Self::live_passes(&self.images, &self.passes);
+		let passes: Vec<Pass<'a>> = self
+			.passes
+			.into_iter()
+			.zip(live)
+			.filter_map(|(pass, live)| {
+				if !live {
+					log::debug!("Culling render graph pass {}", pass.name);
+					return None
+				}
+
+				Some(pass)
+			})
+			.collect();
+
+		let mut images = Vec::with_capacity(self.images.len());
+		let mut transient = Vec::with_capacity(self.images.len());
+		for (image, usage) in self.images.into_iter().zip(usages) {
+			match image {
+				GraphImage::Imported(image) => {
+					images.push(image);
+					transient.push(false);
+				}
+				GraphImage::Transient(info) => {
+					let size_info = if info.samples == vk::SampleCountFlags::TYPE_1 {
+						ImageSizeInfo::from(ImageSize::from(ImageSize::new_2d(
+							info.size[0],
+							info.size[1],
+							NonZeroU32::new(1).unwrap(),
+							MipmapLevels::One()
+						)))
+					} else {
+						ImageSizeInfo::Multisampled(
+							info.size[0],
+							info.size[1],
+							NonZeroU32::new(1).unwrap(),
+							info.samples
+						)
+					};
+
+					images.push(Image::new(
+						device.clone(),
+						info.format,
+						size_info,
+						ImageTilingAndLayout::OptimalUndefined(),
+						usage | info.additional_usage,
+						SharingMode::one(queue_family_index),
+						ImageAllocatorParams::Some { allocator, requirements: requirements.clone() },
+						host_memory_allocator
+					)?);
+					transient.push(true);
+				}
+			}
+		}
+
+		Ok(CompiledRenderGraph { resources: GraphResources { images, buffers: self.buffers }, transient, passes })
+	}
+
+	/// Returns which passes contribute to imported resources, directly or through transient images read by other live passes.
+	fn live_passes(images: &[GraphImage], passes: &[Pass]) -> Vec<bool> {
+		let mut live = vec![false; passes.len()];
+		let mut needed = vec![false; images.len()];
+
+		for (index, pass) in passes.iter().enumerate().rev() {
+			let writes_needed = pass.images.iter().any(|&(ImageId(image), access)| {
+				access.is_write() && (needed[image] || matches!(images[image], GraphImage::Imported(_)))
+			}) || pass.buffers.iter().any(|(_, access)| access.is_write());
+			if !writes_needed {
+				continue
+			}
+
+			live[index] = true;
+			for &(ImageId(image), _) in pass.images.iter() {
+				needed[image] = true;
+			}
+		}
+
+		live
+	}
+}
+
+/// Declaration of the accesses of one pass, see `RenderGraph::pass`.
+pub struct PassBuilder<'g, 'a> {
+	graph: &'g mut RenderGraph<'a>,
+	name: &'a str,
+	images: Vec<(ImageId, ResourceAccess)>,
+	buffers: Vec<(BufferId, ResourceAccess)>
+}
+impl<'g, 'a> PassBuilder<'g, 'a> {
+	/// Declares that the pass accesses `image` with `access`.
+	pub fn image(mut self, image: ImageId, access: ResourceAccess) -> Self {
+		self.images.push((image, access));
+		self
+	}
+
+	/// Declares that the pass accesses the whole `buffer` with `access`.
+	pub fn buffer(mut self, buffer: BufferId, access: ResourceAccess) -> Self {
+		self.buffers.push((buffer, access));
+		self
+	}
+
+	/// Adds the pass to the graph with `record` recording its commands.
+	///
+	/// `record` receives the recording lock after the barriers of the pass and must return it, possibly after beginning and ending render passes.
+	pub fn record(
+		self,
+		record: impl for<'l> FnMut(CommandBufferRecordingLockOutsideRenderPass<'l>, &GraphResources) -> CommandBufferRecordingLockOutsideRenderPass<'l>
+			+ 'a
+	) {
+		self.graph.passes.push(Pass { name: self.name, images: self.images, buffers: self.buffers, record: Box::new(record) });
+	}
+}
+impl fmt::Debug for PassBuilder<'_, '_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PassBuilder")
+			.field("name", &self.name)
+			.field("images", &self.images)
+			.field("buffers", &self.buffers)
+			.finish()
+	}
+}
+
+/// Images and buffers of a compiled graph, indexed by their ids.
+#[derive(Debug)]
+pub struct GraphResources {
+	images: Vec<Vrc<Image>>,
+	buffers: Vec<Vrc<Buffer>>
+}
+impl GraphResources {
+	/// ### Panic
+	///
+	/// This function will panic if `id` was not returned by the graph these resources belong to.
+	pub fn image(&self, id: ImageId) -> &Vrc<Image> {
+		&self.images[id.0]
+	}
+
+	/// ### Panic
+	///
+	/// This function will panic if `id` was not returned by the graph these resources belong to.
+	pub fn buffer(&self, id: BufferId) -> &Vrc<Buffer> {
+		&self.buffers[id.0]
+	}
+}
+
+/// Render graph with created transient images, ready to be recorded into command buffers.
+#[derive(Debug)]
+pub struct CompiledRenderGraph<'a> {
+	resources: GraphResources,
+	transient: Vec<bool>,
+	passes: Vec<Pass<'a>>
+}
+impl<'a> CompiledRenderGraph<'a> {
+	/// Records barriers and all passes into `recording` and returns it.
+	///
+	/// The layout trackers of imported images are updated as the barriers are recorded, so the command buffer must be submitted
+	/// before the graph, or anything else relying on the trackers, is recorded again.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if an imported image is not in the same layout in all its subresources.
+	pub fn execute<'l>(&mut self, mut recording: CommandBufferRecordingLockOutsideRenderPass<'l>) -> CommandBufferRecordingLockOutsideRenderPass<'l> {
+		let resources = &self.resources;
+
+		let mut image_states: Vec<ResourceState> = resources
+			.images
+			.iter()
+			.zip(self.transient.iter())
+			.map(|(image, &transient)| {
+				if transient {
+					return ResourceState::transient()
+				}
+
+				let layout = image
+					.layout_tracker()
+					.layout(image.whole_subresource_range())
+					.expect("imported image must be in the same layout in all subresources");
+
+				ResourceState::imported(layout)
+			})
+			.collect();
+		let mut buffer_states = vec![ResourceState::imported(vk::ImageLayout::UNDEFINED); resources.buffers.len()];
+
+		for pass in self.passes.iter_mut() {
+			let mut source_stages = vk::PipelineStageFlags::empty();
+			let mut destination_stages = vk::PipelineStageFlags::empty();
+
+			let mut image_barriers = Vec::new();
+			for &(ImageId(index), access) in pass.images.iter() {
+				// Checked in `compile`
+				let layout = access.layout.unwrap();
+				if let Some(scope) = image_states[index].access(access.stages, access.access, Some(layout.into())) {
+					let image = &resources.images[index];
+					let range = image.whole_subresource_range();
+
+					source_stages |= scope.source_stages;
+					destination_stages |= access.stages;
+					image_barriers.push(ImageMemoryBarrier::new(
+						image,
+						range,
+						scope.old_layout,
+						layout,
+						scope.source_access,
+						access.access
+					));
+					image.layout_tracker().set_layout(range, layout.into());
+				}
+			}
+
+			let mut buffer_barriers = Vec::new();
+			for &(BufferId(index), access) in pass.buffers.iter() {
+				if let Some(scope) = buffer_states[index].access(access.stages, access.access, None) {
+					let buffer = &resources.buffers[index];
+
+					source_stages |= scope.source_stages;
+					destination_stages |= access.stages;
+					buffer_barriers.push(BufferMemoryBarrier::new(
+						buffer,
+						0,
+						buffer.size(),
+						scope.source_access,
+						access.access
+					));
+				}
+			}
+
+			if !image_barriers.is_empty() || !buffer_barriers.is_empty() {
+				log::trace!(
+					"Render graph pass {} barriers: {} images, {} buffers",
+					pass.name,
+					image_barriers.len(),
+					buffer_barriers.len()
+				);
+				recording.pipeline_barrier(
+					source_stages,
+					destination_stages,
+					[] as [MemoryBarrier; 0],
+					buffer_barriers,
+					image_barriers
+				);
+			}
+
+			recording = (pass.record)(recording, resources);
+		}
+
+		recording
+	}
+
+	pub const fn resources(&self) -> &GraphResources {
+		&self.resources
+	}
+
+	/// Returns the names of the passes that were not culled, in execution order.
+	pub fn pass_names(&self) -> impl Iterator<Item = &str> {
+		self.passes.iter().map(|pass| pass.name)
+	}
+}
+
+/// Source scope of a barrier needed before an access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BarrierScope {
+	source_stages: vk::PipelineStageFlags,
+	source_access: vk::AccessFlags,
+	old_layout: vk::ImageLayout
+}
+
+/// Synchronization state of one resource during `CompiledRenderGraph::execute`.
+#[derive(Debug, Clone, Copy)]
+struct ResourceState {
+	layout: vk::ImageLayout,
+	/// Stages and accesses of the last write, including layout transitions.
+	write_stages: vk::PipelineStageFlags,
+	write_access: vk::AccessFlags,
+	/// Stages that read the resource since the last write.
+	read_stages: vk::PipelineStageFlags,
+	/// Stages and accesses the last write was made visible to.
+	visible_stages: vk::PipelineStageFlags,
+	visible_access: vk::AccessFlags
+}
+impl ResourceState {
+	fn new(layout: vk::ImageLayout) -> Self {
+		ResourceState {
+			layout,
+			write_stages: vk::PipelineStageFlags::empty(),
+			write_access: vk::AccessFlags::empty(),
+			read_stages: vk::PipelineStageFlags::empty(),
+			visible_stages: vk::PipelineStageFlags::empty(),
+			visible_access: vk::AccessFlags::empty()
+		}
+	}
+
+	/// State of a resource that may have been written by any previous commands outside of the graph.
+	fn imported(layout: vk::ImageLayout) -> Self {
+		ResourceState {
+			write_stages: vk::PipelineStageFlags::ALL_COMMANDS,
+			write_access: vk::AccessFlags::MEMORY_WRITE,
+			..Self::new(layout)
+		}
+	}
+
+	/// State of a transient image at the start of an execution.
+	///
+	/// The contents are discarded, but a previous execution of the graph may still be accessing the image.
+	fn transient() -> Self {
+		ResourceState { write_stages: vk::PipelineStageFlags::ALL_COMMANDS, ..Self::new(vk::ImageLayout::UNDEFINED) }
+	}
+
+	/// Records an access in `stages` with `access` and, for images, in `layout`, returning the barrier needed before it if any.
+	fn access(&mut self, stages: vk::PipelineStageFlags, access: vk::AccessFlags, layout: Option<vk::ImageLayout>) -> Option<BarrierScope> {
+		let is_write = access.intersects(WRITE_ACCESS);
+		let layout_change = layout.map(|layout| layout != self.layout).unwrap_or(false);
+		let old_layout = self.layout;
+		let visible = self.write_stages.is_empty() || (self.visible_stages.contains(stages) && self.visible_access.contains(access));
+
+		let scope = if is_write || layout_change {
+			// Writes and layout transitions must wait for all previous accesses
+			let source_stages = self.write_stages | self.read_stages;
+			if source_stages.is_empty() && !layout_change {
+				None
+			} else {
+				Some(BarrierScope {
+					source_stages: if source_stages.is_empty() { vk::PipelineStageFlags::TOP_OF_PIPE } else { source_stages },
+					source_access: self.write_access,
+					old_layout
+				})
+			}
+		} else if !visible {
+			Some(BarrierScope { source_stages: self.write_stages, source_access: self.write_access, old_layout })
+		} else {
+			None
+		};
+
+		if is_write || layout_change {
+			self.write_stages = stages;
+			self.write_access = access & WRITE_ACCESS;
+			self.read_stages = if is_write { vk::PipelineStageFlags::empty() } else { stages };
+			self.visible_stages = stages;
+			self.visible_access = access;
+		} else {
+			self.read_stages |= stages;
+			if scope.is_some() {
+				self.visible_stages |= stages;
+				self.visible_access |= access;
+			}
+		}
+		if let Some(layout) = layout {
+			self.layout = layout;
+		}
+
+		scope
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{BarrierScope, ResourceAccess, ResourceState};
+
+	#[test]
+	fn derives_barriers() {
+		let mut state = ResourceState::transient();
+		let color = ResourceAccess::COLOR_ATTACHMENT_WRITE;
+		let sampled = ResourceAccess::FRAGMENT_SAMPLED_READ;
+		let compute = ResourceAccess::COMPUTE_SAMPLED_READ;
+
+		// Initial layout transition waits for any previous execution
+		let initial = Some(BarrierScope {
+			source_stages: vk::PipelineStageFlags::ALL_COMMANDS,
+			source_access: vk::AccessFlags::empty(),
+			old_layout: vk::ImageLayout::UNDEFINED
+		});
+		assert_eq!(
+			state.access(color.stages, color.access, color.layout.map(Into::into)),
+			initial
+		);
+		// Repeated write in the same layout still needs to wait for the previous write
+		assert!(state.access(color.stages, color.access, color.layout.map(Into::into)).is_some());
+
+		// Read after write with a layout transition
+		assert_eq!(
+			state.access(sampled.stages, sampled.access, sampled.layout.map(Into::into)),
+			Some(BarrierScope {
+				source_stages: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				source_access: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+				old_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+			})
+		);
+		// The same read again is already visible
+		assert_eq!(
+			state.access(sampled.stages, sampled.access, sampled.layout.map(Into::into)),
+			None
+		);
+		// A read in another stage waits for the transition
+		assert_eq!(
+			state.access(compute.stages, compute.access, compute.layout.map(Into::into)),
+			Some(BarrierScope {
+				source_stages: vk::PipelineStageFlags::FRAGMENT_SHADER,
+				source_access: vk::AccessFlags::empty(),
+				old_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+			})
+		);
+
+		// Write after both reads waits for both
+		let scope = state.access(color.stages, color.access, color.layout.map(Into::into)).unwrap();
+		assert_eq!(
+			scope.source_stages,
+			vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER
+		);
+
+		// A second execution discards the contents again, but its first write must not overtake the reads of the first execution
+		let mut state = ResourceState::transient();
+		assert_eq!(
+			state.access(color.stages, color.access, color.layout.map(Into::into)),
+			initial
+		);
+	}
+
+	#[test]
+	fn imported_first_access_barriers() {
+		let sampled = ResourceAccess::FRAGMENT_SAMPLED_READ;
+		let transfer = ResourceAccess::TRANSFER_WRITE;
+
+		// Read without a layout change still waits for writes outside of the graph
+		let mut state = ResourceState::imported(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+		assert_eq!(
+			state.access(sampled.stages, sampled.access, sampled.layout.map(Into::into)),
+			Some(BarrierScope {
+				source_stages: vk::PipelineStageFlags::ALL_COMMANDS,
+				source_access: vk::AccessFlags::MEMORY_WRITE,
+				old_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+			})
+		);
+		assert_eq!(
+			state.access(sampled.stages, sampled.access, sampled.layout.map(Into::into)),
+			None
+		);
+
+		// Buffer write without any layout
+		let mut state = ResourceState::imported(vk::ImageLayout::UNDEFINED);
+		assert_eq!(
+			state.access(transfer.stages, transfer.access, None),
+			Some(BarrierScope {
+				source_stages: vk::PipelineStageFlags::ALL_COMMANDS,
+				source_access: vk::AccessFlags::MEMORY_WRITE,
+				old_layout: vk::ImageLayout::UNDEFINED
+			})
+		);
+	}
+}
@@ -5,7 +5,10 @@ use error::RenderPassError;
 
 use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Transparent, Vrc};
 
+pub mod analysis;
+pub mod clear;
 pub mod error;
+pub mod graph;
 pub mod params;
 
 pub mod description;
@@ -20,7 +23,7 @@ impl RenderPass {
 		device: Vrc<Device>,
 		attachments: &[params::AttachmentDescription],
 		subpasses: &[params::SubpassDescription],
-		dependencies: &[vk::SubpassDependency],
+		dependencies: &[params::SubpassDependency],
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, RenderPassError> {
 		#[cfg(feature = "runtime_implicit_validations")]
@@ -29,12 +32,9 @@ impl RenderPass {
 				return Err(RenderPassError::SubpassesEmpty)
 			}
 
-			for dependency in dependencies {
-				if dependency.src_stage_mask.is_empty() {
-					return Err(RenderPassError::SrcStageMaskZero)
-				}
-				if dependency.dst_stage_mask.is_empty() {
-					return Err(RenderPassError::DstStageMaskZero)
+			for (index, dependency) in dependencies.iter().enumerate() {
+				if let Err(err) = dependency.validate(subpasses.len() as u32) {
+					return Err(RenderPassError::InvalidDependency(index, err))
 				}
 			}
 		}
@@ -46,7 +46,9 @@ impl RenderPass {
 			.subpasses(Transparent::transmute_slice_twice(
 				subpasses
 			))
-			.dependencies(dependencies);
+			.dependencies(Transparent::transmute_slice_twice(
+				dependencies
+			));
 
 		unsafe {
 			Self::from_create_info(
@@ -85,11 +87,19 @@ impl RenderPass {
 			);
 		}
 
+		#[cfg(all(feature = "render_pass_analysis", debug_assertions))]
+		{
+			for warning in analysis::analyze_create_info(create_info.deref()) {
+				log::warn!("Render pass analysis: {}", warning);
+			}
+		}
+
 		let render_pass = device.create_render_pass(
 			create_info.deref(),
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(render_pass);
 		Ok(Vrc::new(RenderPass {
 			device,
 			render_pass,
@@ -117,6 +127,7 @@ impl RenderPass {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(render_pass);
 		Ok(Vrc::new(RenderPass {
 			device,
 			render_pass,
@@ -136,6 +147,7 @@ impl_common_handle_traits! {
 impl Drop for RenderPass {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.render_pass);
 
 		unsafe {
 			self.device.destroy_render_pass(
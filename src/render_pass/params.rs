@@ -2,12 +2,20 @@ use std::convert::TryFrom;
 
 use ash::vk;
 
-use super::error::SubpassDescriptionError;
+use super::error::{AttachmentOpsError, SubpassDependencyError, SubpassDescriptionError};
 use crate::{
-	prelude::Transparent,
-	resource::image::layout::{ImageLayoutAttachment, ImageLayoutFinal}
+	descriptor::{error::InputAttachmentError, set::update::InputAttachmentInfos},
+	prelude::{Framebuffer, Transparent},
+	resource::image::{
+		layout::{ImageLayoutAttachment, ImageLayoutFinal},
+		params::format_aspects
+	}
 };
 
+/// Load and store operations of an attachment.
+///
+/// Operations for aspects that are not selected by the variant are set to `DONT_CARE`
+/// so that tiled architectures don't spend bandwidth loading or storing them.
 #[derive(Debug)]
 pub enum AttachmentOps {
 	Color {
@@ -19,6 +27,98 @@ pub enum AttachmentOps {
 		depth_store: vk::AttachmentStoreOp,
 		stencil_load: vk::AttachmentLoadOp,
 		stencil_store: vk::AttachmentStoreOp
+	},
+	/// Operations for the depth aspect only, stencil is `DONT_CARE`.
+	DepthOnly {
+		load: vk::AttachmentLoadOp,
+		store: vk::AttachmentStoreOp
+	},
+	/// Operations for the stencil aspect only, depth is `DONT_CARE`.
+	StencilOnly {
+		load: vk::AttachmentLoadOp,
+		store: vk::AttachmentStoreOp
+	},
+	/// Loads and stores all aspects of the attachment format.
+	Preserve
+}
+impl AttachmentOps {
+	/// Color attachment with contents that are neither loaded nor stored.
+	pub const fn color_dont_care() -> Self {
+		AttachmentOps::Color { load: vk::AttachmentLoadOp::DONT_CARE, store: vk::AttachmentStoreOp::DONT_CARE }
+	}
+
+	/// Depth/stencil attachment with contents that are neither loaded nor stored.
+	pub const fn depth_stencil_dont_care() -> Self {
+		AttachmentOps::DepthStencil {
+			depth_load: vk::AttachmentLoadOp::DONT_CARE,
+			depth_store: vk::AttachmentStoreOp::DONT_CARE,
+			stencil_load: vk::AttachmentLoadOp::DONT_CARE,
+			stencil_store: vk::AttachmentStoreOp::DONT_CARE
+		}
+	}
+
+	/// Checks that these operations are compatible with the aspects of `format`.
+	///
+	/// Operations other than `DONT_CARE` on aspects that the format doesn't have are rejected.
+	pub fn validate(&self, format: vk::Format) -> Result<(), AttachmentOpsError> {
+		let aspects = format_aspects(format);
+		let has_depth = aspects.contains(vk::ImageAspectFlags::DEPTH);
+		let has_stencil = aspects.contains(vk::ImageAspectFlags::STENCIL);
+		let is_dont_care = |load: vk::AttachmentLoadOp, store: vk::AttachmentStoreOp| {
+			load == vk::AttachmentLoadOp::DONT_CARE && store == vk::AttachmentStoreOp::DONT_CARE
+		};
+
+		match *self {
+			AttachmentOps::Color { .. } => {
+				if has_depth || has_stencil {
+					return Err(AttachmentOpsError::ColorOpsOnDepthStencilFormat(format))
+				}
+			}
+			AttachmentOps::DepthStencil { depth_load, depth_store, stencil_load, stencil_store } => {
+				if !has_depth && !has_stencil {
+					return Err(AttachmentOpsError::DepthStencilOpsOnColorFormat(format))
+				}
+				if !has_depth && !is_dont_care(depth_load, depth_store) {
+					return Err(AttachmentOpsError::MissingDepthAspect(format))
+				}
+				if !has_stencil && !is_dont_care(stencil_load, stencil_store) {
+					return Err(AttachmentOpsError::MissingStencilAspect(format))
+				}
+			}
+			AttachmentOps::DepthOnly { .. } => {
+				if !has_depth {
+					return Err(AttachmentOpsError::MissingDepthAspect(format))
+				}
+			}
+			AttachmentOps::StencilOnly { .. } => {
+				if !has_stencil {
+					return Err(AttachmentOpsError::MissingStencilAspect(format))
+				}
+			}
+			AttachmentOps::Preserve => ()
+		}
+
+		Ok(())
+	}
+
+	/// Returns `(load, store, stencil_load, stencil_store)` operations for an attachment of `format`.
+	fn resolve(&self, format: vk::Format) -> (vk::AttachmentLoadOp, vk::AttachmentStoreOp, vk::AttachmentLoadOp, vk::AttachmentStoreOp) {
+		const DONT_CARE: (vk::AttachmentLoadOp, vk::AttachmentStoreOp) = (vk::AttachmentLoadOp::DONT_CARE, vk::AttachmentStoreOp::DONT_CARE);
+		const PRESERVE: (vk::AttachmentLoadOp, vk::AttachmentStoreOp) = (vk::AttachmentLoadOp::LOAD, vk::AttachmentStoreOp::STORE);
+
+		match *self {
+			AttachmentOps::Color { load, store } => (load, store, vk::AttachmentLoadOp::default(), vk::AttachmentStoreOp::default()),
+			AttachmentOps::DepthStencil { depth_load, depth_store, stencil_load, stencil_store } => (depth_load, depth_store, stencil_load, stencil_store),
+			AttachmentOps::DepthOnly { load, store } => (load, store, DONT_CARE.0, DONT_CARE.1),
+			AttachmentOps::StencilOnly { load, store } => (DONT_CARE.0, DONT_CARE.1, load, store),
+			AttachmentOps::Preserve => {
+				let aspects = format_aspects(format);
+				let stencil = if aspects.contains(vk::ImageAspectFlags::STENCIL) { PRESERVE } else { DONT_CARE };
+				let main = if aspects.intersects(vk::ImageAspectFlags::COLOR | vk::ImageAspectFlags::DEPTH) { PRESERVE } else { DONT_CARE };
+
+				(main.0, main.1, stencil.0, stencil.1)
+			}
+		}
 	}
 }
 
@@ -28,6 +128,9 @@ vk_builder_wrap! {
 		builder: vk::AttachmentDescriptionBuilder<'static> => vk::AttachmentDescription
 	}
 	impl {
+		/// ### Panic
+		///
+		/// With `runtime_implicit_validations` this function panics if `ops` are not compatible with `format`, see `AttachmentOps::validate`.
 		pub fn new(
 			may_alias: bool,
 			format: vk::Format,
@@ -36,9 +139,21 @@ vk_builder_wrap! {
 			initial_layout: vk::ImageLayout,
 			final_layout: ImageLayoutFinal
 		) -> Self {
+			#[cfg(feature = "runtime_implicit_validations")]
+			{
+				if let Err(err) = ops.validate(format) {
+					panic!("{}", err);
+				}
+			}
+
+			let (load, store, stencil_load, stencil_store) = ops.resolve(format);
 			let mut builder = vk::AttachmentDescription::builder()
 				.format(format)
 				.samples(samples)
+				.load_op(load)
+				.store_op(store)
+				.stencil_load_op(stencil_load)
+				.stencil_store_op(stencil_store)
 				.initial_layout(initial_layout)
 				.final_layout(final_layout.into())
 			;
@@ -46,14 +161,6 @@ vk_builder_wrap! {
 			if may_alias {
 				builder = builder.flags(vk::AttachmentDescriptionFlags::MAY_ALIAS);
 			}
-			match ops {
-				AttachmentOps::Color { load, store } => {
-					builder = builder.load_op(load).store_op(store);
-				}
-				AttachmentOps::DepthStencil { depth_load, depth_store, stencil_load, stencil_store } => {
-					builder = builder.load_op(depth_load).store_op(depth_store).stencil_load_op(stencil_load).stencil_store_op(stencil_store);
-				}
-			}
 
 			AttachmentDescription {
 				builder
@@ -145,6 +252,143 @@ vk_builder_wrap! {
 		}
 	}
 }
+vk_builder_wrap! {
+	/// Wrapper struct that is transparent over `vk::SubpassDependencyBuilder`.
+	///
+	/// Subpasses are given as `Some(index)` or as `None` for `VK_SUBPASS_EXTERNAL`.
+	pub struct SubpassDependency {
+		builder: vk::SubpassDependencyBuilder<'static> => vk::SubpassDependency
+	}
+	impl {
+		pub fn new(
+			source_subpass: Option<u32>,
+			destination_subpass: Option<u32>,
+			source_stages: vk::PipelineStageFlags,
+			destination_stages: vk::PipelineStageFlags,
+			source_access: vk::AccessFlags,
+			destination_access: vk::AccessFlags
+		) -> Self {
+			let builder = vk::SubpassDependency::builder()
+				.src_subpass(source_subpass.unwrap_or(vk::SUBPASS_EXTERNAL))
+				.dst_subpass(destination_subpass.unwrap_or(vk::SUBPASS_EXTERNAL))
+				.src_stage_mask(source_stages)
+				.dst_stage_mask(destination_stages)
+				.src_access_mask(source_access)
+				.dst_access_mask(destination_access)
+			;
+
+			SubpassDependency {
+				builder
+			}
+		}
+
+		/// Color attachment writes of `source` happen before color attachment reads and writes of `destination`.
+		pub fn color_write_after_color_write(source: u32, destination: u32) -> Self {
+			Self::new(
+				Some(source),
+				Some(destination),
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+				vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+			)
+		}
+
+		/// Color attachment writes of `source` happen before input attachment reads of `destination`.
+		pub fn input_read_after_color_write(source: u32, destination: u32) -> Self {
+			Self::new(
+				Some(source),
+				Some(destination),
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::PipelineStageFlags::FRAGMENT_SHADER,
+				vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+				vk::AccessFlags::INPUT_ATTACHMENT_READ
+			)
+		}
+
+		/// Depth/stencil attachment writes of `source` happen before depth/stencil reads of `destination`,
+		/// either by depth/stencil tests or as an input attachment.
+		pub fn depth_read_after_write(source: u32, destination: u32) -> Self {
+			Self::new(
+				Some(source),
+				Some(destination),
+				vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+				vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS | vk::PipelineStageFlags::FRAGMENT_SHADER,
+				vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+				vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::INPUT_ATTACHMENT_READ
+			)
+		}
+
+		/// Color attachment output of `destination` waits for the color attachment output stage before the render pass.
+		///
+		/// This is the usual dependency of a render pass drawing into a swapchain image acquired with a semaphore waited on in the
+		/// `COLOR_ATTACHMENT_OUTPUT` stage: the layout transition from the initial layout then also waits for the semaphore.
+		pub fn external_to_color_output(destination: u32) -> Self {
+			Self::new(
+				None,
+				Some(destination),
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::AccessFlags::empty(),
+				vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+			)
+		}
+
+		/// Color attachment writes of `source` and the final layout transition happen before `destination_stages`
+		/// accessing with `destination_access` after the render pass.
+		pub fn color_output_to_external(
+			source: u32,
+			destination_stages: vk::PipelineStageFlags,
+			destination_access: vk::AccessFlags
+		) -> Self {
+			Self::new(
+				Some(source),
+				None,
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				destination_stages,
+				vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+				destination_access
+			)
+		}
+
+		/// Makes the dependency framebuffer-local by adding `BY_REGION`.
+		///
+		/// Only valid when the destination accesses only the same pixel the source wrote, such as attachment and input attachment accesses.
+		pub fn by_region(self) -> Self {
+			let flags = self.builder.dependency_flags | vk::DependencyFlags::BY_REGION;
+
+			SubpassDependency {
+				builder: self.builder.dependency_flags(flags)
+			}
+		}
+
+		/// Validates this dependency against a render pass with `subpass_count` subpasses.
+		pub fn validate(&self, subpass_count: u32) -> Result<(), SubpassDependencyError> {
+			let source = self.builder.src_subpass;
+			let destination = self.builder.dst_subpass;
+
+			for &subpass in [source, destination].iter() {
+				if subpass != vk::SUBPASS_EXTERNAL && subpass >= subpass_count {
+					return Err(SubpassDependencyError::SubpassOutOfRange { subpass, subpass_count })
+				}
+			}
+			if source == vk::SUBPASS_EXTERNAL && destination == vk::SUBPASS_EXTERNAL {
+				return Err(SubpassDependencyError::BothExternal)
+			}
+			if source != vk::SUBPASS_EXTERNAL && destination != vk::SUBPASS_EXTERNAL && source > destination {
+				return Err(SubpassDependencyError::Backwards { source_subpass: source, destination_subpass: destination })
+			}
+			if self.builder.src_stage_mask.is_empty() {
+				return Err(SubpassDependencyError::SrcStageMaskZero)
+			}
+			if self.builder.dst_stage_mask.is_empty() {
+				return Err(SubpassDependencyError::DstStageMaskZero)
+			}
+
+			Ok(())
+		}
+	}
+}
 impl<'a, I, CR, P> TryFrom<&'a SubpassDescriptionHolder<I, CR, P>> for SubpassDescription<'a>
 where
 	I: AsRef<[AttachmentReference]>,
@@ -181,3 +425,52 @@ where
 	pub depth_stencil_attachment: Option<AttachmentReference>,
 	pub preserve_attachments: Option<P>
 }
+impl<I, CR, P> SubpassDescriptionHolder<I, CR, P>
+where
+	I: AsRef<[AttachmentReference]>,
+	CR: AsRef<[AttachmentReference]>,
+	P: AsRef<[u32]>
+{
+	/// Returns image infos of the input attachments of this subpass using the views of `framebuffer`, see `InputAttachmentInfos`.
+	pub fn input_attachment_infos<'f>(&self, framebuffer: &'f Framebuffer) -> Result<InputAttachmentInfos<'f>, InputAttachmentError> {
+		let input_attachments = self.input_attachments.as_ref().map(AsRef::as_ref).unwrap_or(&[]);
+
+		InputAttachmentInfos::new(input_attachments, framebuffer)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{SubpassDependency, SubpassDependencyError};
+
+	#[test]
+	fn subpass_dependency_validation() {
+		let dependency = SubpassDependency::input_read_after_color_write(0, 1).by_region();
+		assert_eq!(dependency.dependency_flags, vk::DependencyFlags::BY_REGION);
+		assert!(dependency.validate(2).is_ok());
+		assert!(matches!(
+			dependency.validate(1),
+			Err(SubpassDependencyError::SubpassOutOfRange { subpass: 1, subpass_count: 1 })
+		));
+
+		assert!(SubpassDependency::external_to_color_output(0).validate(1).is_ok());
+		assert!(matches!(
+			SubpassDependency::color_write_after_color_write(1, 0).validate(2),
+			Err(SubpassDependencyError::Backwards { source_subpass: 1, destination_subpass: 0 })
+		));
+		assert!(matches!(
+			SubpassDependency::new(
+				None,
+				None,
+				vk::PipelineStageFlags::TOP_OF_PIPE,
+				vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+				vk::AccessFlags::empty(),
+				vk::AccessFlags::empty()
+			)
+			.validate(1),
+			Err(SubpassDependencyError::BothExternal)
+		));
+	}
+}
@@ -0,0 +1,144 @@
+//! Tables of buffer device addresses for GPU-driven rendering.
+//!
+//! A `DeviceAddressTable` keeps a list of `MeshAddresses` entries on the host and writes them into a buffer
+//! only when they change. Shaders can then index the table by mesh id and dereference the pointers directly.
+//!
+//! The table layout matches the following GLSL declaration (std430):
+//! ```glsl
+//! struct MeshAddresses {
+//! 	uint64_t vertex;
+//! 	uint64_t index;
+//! 	uint64_t material;
+//! };
+//! ```
+
+use std::num::NonZeroU64;
+
+use ash::vk;
+
+use super::{error, suballoc::BufferSlice, Buffer};
+
+/// Device addresses of resources used by one mesh.
+///
+/// Zero address means the resource is not present.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MeshAddresses {
+	pub vertex: vk::DeviceAddress,
+	pub index: vk::DeviceAddress,
+	pub material: vk::DeviceAddress
+}
+impl MeshAddresses {
+	/// Creates new mesh addresses from whole buffers.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	pub fn from_buffers(vertex: &Buffer, index: &Buffer, material: Option<&Buffer>) -> Result<Self, error::BufferDeviceAddressError> {
+		Ok(MeshAddresses {
+			vertex: vertex.device_address()?,
+			index: index.device_address()?,
+			material: material.map(Buffer::device_address).transpose()?.unwrap_or(0)
+		})
+	}
+
+	/// Creates new mesh addresses from buffer slices.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	pub fn from_slices(vertex: &BufferSlice, index: &BufferSlice, material: Option<&BufferSlice>) -> Result<Self, error::BufferDeviceAddressError> {
+		Ok(MeshAddresses {
+			vertex: slice_address(vertex)?,
+			index: slice_address(index)?,
+			material: material.map(slice_address).transpose()?.unwrap_or(0)
+		})
+	}
+}
+
+/// Returns the device address of the beginning of `slice`.
+///
+/// ### Panic
+///
+/// This function will panic under Vulkan 1.0 and 1.1.
+pub fn slice_address(slice: &BufferSlice) -> Result<vk::DeviceAddress, error::BufferDeviceAddressError> {
+	Ok(slice.buffer.device_address()? + slice.offset)
+}
+
+/// Host-side table of mesh addresses that is uploaded into a buffer when changed.
+#[derive(Debug, Default, Clone)]
+pub struct DeviceAddressTable {
+	entries: Vec<MeshAddresses>,
+	dirty: bool
+}
+impl DeviceAddressTable {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Appends a new entry and returns its index.
+	pub fn push(&mut self, entry: MeshAddresses) -> u32 {
+		self.entries.push(entry);
+		self.dirty = true;
+
+		(self.entries.len() - 1) as u32
+	}
+
+	/// Replaces the entry at `index`.
+	///
+	/// The table is only marked as changed if the new entry differs from the old one.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `index` is out of bounds.
+	pub fn set(&mut self, index: u32, entry: MeshAddresses) {
+		let old = &mut self.entries[index as usize];
+		if *old != entry {
+			*old = entry;
+			self.dirty = true;
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.dirty = true;
+	}
+
+	pub fn entries(&self) -> &[MeshAddresses] {
+		&self.entries
+	}
+
+	/// Returns whether the table changed since the last upload.
+	pub const fn is_dirty(&self) -> bool {
+		self.dirty
+	}
+
+	/// Returns the size of the table in bytes, or `None` if the table is empty.
+	pub fn size(&self) -> Option<NonZeroU64> {
+		NonZeroU64::new(std::mem::size_of_val(self.entries.as_slice()) as u64)
+	}
+
+	/// Writes the table into `target` if it changed since the last upload.
+	///
+	/// `target` must be host visible and its offset must be aligned to 8 bytes.
+	///
+	/// Returns whether the table was written.
+	pub fn upload(&mut self, target: &BufferSlice) -> Result<bool, error::DeviceAddressTableError> {
+		if !self.dirty {
+			return Ok(false)
+		}
+
+		let size = self.size().map(NonZeroU64::get).unwrap_or(0);
+		if size > target.size.get() {
+			return Err(error::DeviceAddressTableError::BufferTooSmall(
+				target.size.get(),
+				size
+			))
+		}
+
+		target.write(&self.entries)?;
+		self.dirty = false;
+
+		Ok(true)
+	}
+}
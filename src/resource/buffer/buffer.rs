@@ -1,4 +1,4 @@
-use std::{fmt, num::NonZeroU64, ops::Deref};
+use std::{convert::Infallible, fmt, num::NonZeroU64, ops::Deref};
 
 use ash::vk;
 
@@ -6,7 +6,7 @@ use super::{error, params};
 use crate::{
 	device::Device,
 	memory::{
-		device::{allocator::BufferMemoryAllocator, DeviceMemoryAllocation},
+		device::{allocator::BufferMemoryAllocator, never::NeverDeviceAllocator, DeviceMemoryAllocation, MappingAccessResult, SliceWriteStride},
 		host::HostMemoryAllocator
 	},
 	prelude::Vrc,
@@ -20,8 +20,9 @@ pub struct Buffer {
 
 	usage: vk::BufferUsageFlags,
 	size: NonZeroU64,
+	sharing_mode: vk::SharingMode,
+	queue_family_indices: Vec<u32>,
 
-	// TODO: Sharing mode + indices?
 	host_memory_allocator: HostMemoryAllocator
 }
 impl Buffer {
@@ -56,6 +57,80 @@ impl Buffer {
 		}
 	}
 
+	/// Creates a new buffer of the size of `data` and writes `data` into it.
+	///
+	/// The memory allocated using `allocator_params` must be host visible.
+	/// `data` is written tightly packed, the same as `DeviceMemoryMappingAccess::write_slice` with `SliceWriteStride::Implicit`.
+	pub fn new_with_data<A: BufferMemoryAllocator, T: Copy>(
+		device: Vrc<Device>,
+		data: &[T],
+		usage: vk::BufferUsageFlags,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		allocator_params: params::BufferAllocatorParams<A>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, error::BufferDataError<A::Error>> {
+		let size = NonZeroU64::new(std::mem::size_of_val(data) as u64).ok_or(error::BufferDataError::DataEmpty)?;
+
+		let buffer = Self::new(
+			device,
+			size,
+			usage,
+			sharing_mode,
+			allocator_params,
+			host_memory_allocator
+		)?;
+
+		buffer
+			.memory()
+			.ok_or(error::BufferSliceWriteError::NoMemory)?
+			.map_memory_with(|mut access| {
+				access.write_slice(data, 0, SliceWriteStride::Implicit);
+				MappingAccessResult::Continue
+			})
+			.map_err(error::BufferSliceWriteError::from)?;
+
+		Ok(buffer)
+	}
+
+	/// Creates a new sparse buffer without any memory bound.
+	///
+	/// `sparse_flags` must contain `SPARSE_BINDING` and may contain `SPARSE_RESIDENCY` and `SPARSE_ALIASED`.
+	/// Memory is bound later using `Queue::bind_sparse`.
+	pub fn new_sparse(
+		device: Vrc<Device>,
+		size: NonZeroU64,
+		usage: vk::BufferUsageFlags,
+		sparse_flags: vk::BufferCreateFlags,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, error::BufferError<Infallible>> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if usage.is_empty() {
+				return Err(error::BufferError::UsageEmpty)
+			}
+			if !sparse_flags.contains(vk::BufferCreateFlags::SPARSE_BINDING) {
+				return Err(error::BufferError::SparseBindingFlagMissing)
+			}
+		}
+
+		let create_info = vk::BufferCreateInfo::builder()
+			.flags(sparse_flags)
+			.size(size.get())
+			.usage(usage)
+			.sharing_mode(sharing_mode.sharing_mode())
+			.queue_family_indices(sharing_mode.indices());
+
+		unsafe {
+			Self::from_create_info(
+				device,
+				create_info,
+				params::BufferAllocatorParams::<NeverDeviceAllocator>::None,
+				host_memory_allocator
+			)
+		}
+	}
+
 	/// Creates a new `Buffer` from existing `BufferCreateInfo`
 	///
 	/// ### Safety
@@ -103,13 +178,25 @@ impl Buffer {
 		};
 
 		let size = NonZeroU64::new_unchecked(create_info.size);
+		let queue_family_indices = if c_info.sharing_mode == vk::SharingMode::CONCURRENT {
+			std::slice::from_raw_parts(
+				c_info.p_queue_family_indices,
+				c_info.queue_family_index_count as usize
+			)
+			.to_vec()
+		} else {
+			Vec::new()
+		};
 
+		device.child_registry().register(buffer);
 		Ok(Vrc::new(Buffer {
 			device,
 			buffer,
 			memory,
 			usage: c_info.usage,
 			size,
+			sharing_mode: c_info.sharing_mode,
+			queue_family_indices,
 			host_memory_allocator
 		}))
 	}
@@ -126,6 +213,15 @@ impl Buffer {
 		self.size
 	}
 
+	pub const fn sharing_mode(&self) -> vk::SharingMode {
+		self.sharing_mode
+	}
+
+	/// Returns the queue families sharing this buffer, empty if the sharing mode is `EXCLUSIVE`.
+	pub fn queue_family_indices(&self) -> &[u32] {
+		&self.queue_family_indices
+	}
+
 	/// Returns the length of this buffer in number of `T`s.
 	pub fn size_of<T>(&self) -> usize {
 		self.size().get() as usize / std::mem::size_of::<T>()
@@ -135,6 +231,27 @@ impl Buffer {
 	pub fn memory(&self) -> Option<&DeviceMemoryAllocation> {
 		self.memory.as_ref()
 	}
+
+	/// Returns the device address of this buffer.
+	///
+	/// The buffer must have been created with `SHADER_DEVICE_ADDRESS` usage and the `bufferDeviceAddress` feature must be enabled.
+	///
+	/// ### Panic
+	///
+	/// This function will panic under Vulkan 1.0 and 1.1.
+	#[cfg(feature = "vulkan1_2")]
+	pub fn device_address(&self) -> Result<vk::DeviceAddress, error::BufferDeviceAddressError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if !self.usage().contains(vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS) {
+				return Err(error::BufferDeviceAddressError::UsageMissing)
+			}
+		}
+
+		let info = vk::BufferDeviceAddressInfo::builder().buffer(self.buffer);
+
+		Ok(unsafe { self.device.get_buffer_device_address(&info) })
+	}
 }
 impl_common_handle_traits! {
 	impl HasHandle<vk::Buffer>, Deref, Borrow, Eq, Hash, Ord for Buffer {
@@ -144,6 +261,7 @@ impl_common_handle_traits! {
 impl Drop for Buffer {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.buffer);
 
 		unsafe {
 			self.device.destroy_buffer(
@@ -170,6 +288,11 @@ impl fmt::Debug for Buffer {
 			)
 			.field("usage", &self.usage)
 			.field("size", &self.size)
+			.field("sharing_mode", &self.sharing_mode)
+			.field(
+				"queue_family_indices",
+				&self.queue_family_indices
+			)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
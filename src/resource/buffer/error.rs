@@ -15,6 +15,10 @@ vk_result_error! {
 		#[error("The memory must be allocated from the same device")]
 		MemoryDeviceMismatch,
 
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Sparse flags must contain SPARSE_BINDING")]
+		SparseBindingFlagMissing,
+
 		#[error("Allocation error produced by the allocator parameter")]
 		AllocationError(AllocError),
 	}
@@ -27,5 +31,80 @@ vk_result_error! {
 			ERROR_OUT_OF_HOST_MEMORY,
 			ERROR_OUT_OF_DEVICE_MEMORY
 		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Buffer must be created with UNIFORM_TEXEL_BUFFER or STORAGE_TEXEL_BUFFER usage matching the view")]
+		UsageMismatch,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Format {0:?} does not support the texel buffer features required by the buffer usage")]
+		FormatNotSupported(ash::vk::Format),
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Offset must be a multiple of minTexelBufferOffsetAlignment")]
+		OffsetNotAligned,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Offset and range must lie within the buffer")]
+		RangeOutOfBounds,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Buffer in the create info must be the same as the buffer parameter")]
+		BufferMismatch,
 	}
 }
+
+#[derive(Error, Debug)]
+pub enum BufferDeviceAddressError {
+	#[cfg(feature = "runtime_implicit_validations")]
+	#[error("Buffer must be created with SHADER_DEVICE_ADDRESS usage")]
+	UsageMissing
+}
+
+#[derive(Error, Debug)]
+pub enum BufferDataError<AllocError: std::error::Error + 'static> {
+	#[error("Data must not be empty")]
+	DataEmpty,
+	#[error("Could not create buffer")]
+	Buffer(#[from] BufferError<AllocError>),
+	#[error("Could not write data")]
+	Write(#[from] BufferSliceWriteError)
+}
+
+#[derive(Error, Debug)]
+pub enum BufferSliceWriteError {
+	#[error("Buffer has no bound memory")]
+	NoMemory,
+	#[error("Writing {count} elements at element {first} is out of bounds of {len} elements")]
+	OutOfBounds { first: u64, count: u64, len: u64 },
+	#[error("Data of {size} bytes does not fit into a slice of {capacity} bytes")]
+	TooLarge { size: u64, capacity: u64 },
+	#[error("Could not map buffer memory")]
+	Map(#[from] crate::memory::device::MapError)
+}
+
+#[derive(Error, Debug)]
+pub enum TypedBufferError {
+	#[error("Buffer usage {0:?} does not contain the usage required by the typed buffer")]
+	UsageMissing(ash::vk::BufferUsageFlags),
+	#[error("Buffer is too small to hold a single element")]
+	BufferTooSmall
+}
+
+#[derive(Error, Debug)]
+pub enum DeviceAddressTableError {
+	#[error("Buffer of size {0} is too small to hold the table of size {1}")]
+	BufferTooSmall(u64, u64),
+	#[error("Could not write the table")]
+	Write(#[from] BufferSliceWriteError)
+}
+
+#[derive(Error, Debug)]
+pub enum GpuVecError<AllocError: std::error::Error + 'static> {
+	#[error("Could not create buffer")]
+	Buffer(#[from] BufferError<AllocError>),
+	#[error("Could not upload data")]
+	Upload(#[from] crate::memory::staging::StagingUploadError<AllocError>),
+	#[error("Could not query upload status")]
+	FenceStatus(#[from] crate::sync::fence::error::FenceStatusError)
+}
@@ -0,0 +1,293 @@
+//! Growable array of elements in a device buffer.
+//!
+//! A `GpuVec` owns a `TypedBuffer` whose length is the capacity of the vector. Elements are appended through a `StagingUploader`.
+//! When the capacity is exceeded, a new buffer is created and the existing elements are copied into it on the device.
+//!
+//! Growing replaces the buffer, so anything referring to the old one, such as descriptor sets or bound vertex buffers, has to be
+//! updated. `generation` is incremented on each growth to make this easy to detect.
+
+use std::{fmt, num::NonZeroU64, ops::Range};
+
+use ash::vk;
+
+use super::{
+	error::{BufferError, GpuVecError},
+	params::BufferAllocatorParams,
+	typed::{DescriptorBufferUsage, TypedBuffer, TypedBufferUsage},
+	Buffer
+};
+use crate::{
+	memory::{
+		device::allocator::BufferMemoryAllocator,
+		staging::{PendingUpload, StagingUploader}
+	},
+	prelude::{BufferBufferCopy, DescriptorBufferInfo, Device, HostMemoryAllocator, Queue, Vrc},
+	queue::sharing_mode::SharingMode
+};
+
+/// Device buffer with `Vec`-like length and capacity, holding `T`s with usage `U`.
+///
+/// All uploads and copies are submitted to the queue passed to the modifying methods. Accessing the elements from other queues
+/// requires synchronization with those submissions. Writes into the buffer made by the device, for example from shaders,
+/// must be made visible to transfer reads before the vector grows.
+pub struct GpuVec<T: Copy, U: TypedBufferUsage, A: BufferMemoryAllocator>
+where
+	A::AllocationRequirements: Clone
+{
+	storage: TypedBuffer<T, U>,
+	len: u64,
+	generation: u64,
+	/// Copies from previous buffers, keeping them alive until finished.
+	pending_copies: Vec<PendingUpload>,
+
+	additional_usage: vk::BufferUsageFlags,
+	sharing_mode: SharingMode<Vec<u32>>,
+	allocator: A,
+	requirements: A::AllocationRequirements,
+	host_memory_allocator: HostMemoryAllocator
+}
+impl<T: Copy, U: TypedBufferUsage, A: BufferMemoryAllocator> GpuVec<T, U, A>
+where
+	A::AllocationRequirements: Clone
+{
+	/// Creates a new empty vector with space for `capacity` elements.
+	///
+	/// The buffer is created with `U::USAGE`, `additional_usage` and the `TRANSFER_SRC` and `TRANSFER_DST` usages needed for uploads and growth.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `T` is zero-sized.
+	pub fn new(
+		device: Vrc<Device>,
+		capacity: NonZeroU64,
+		additional_usage: vk::BufferUsageFlags,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		allocator: A,
+		requirements: A::AllocationRequirements,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, BufferError<A::Error>> {
+		let additional_usage = additional_usage | vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+		// Cannot fail, the indices were already validated
+		let sharing_mode = SharingMode::new(sharing_mode.indices().to_vec()).unwrap();
+
+		let storage = TypedBuffer::new(
+			device,
+			capacity,
+			additional_usage,
+			sharing_mode.clone(),
+			BufferAllocatorParams::Some { allocator: &allocator, requirements: requirements.clone() },
+			host_memory_allocator.clone()
+		)?;
+
+		Ok(GpuVec {
+			storage,
+			len: 0,
+			generation: 0,
+			pending_copies: Vec::new(),
+			additional_usage,
+			sharing_mode,
+			allocator,
+			requirements,
+			host_memory_allocator
+		})
+	}
+
+	/// Ensures that at least `additional` more elements fit without growing.
+	///
+	/// If the buffer is replaced, the capacity is at least doubled and the copy of existing elements is submitted to `queue`.
+	pub fn reserve<SA: BufferMemoryAllocator<Error = A::Error>>(
+		&mut self,
+		uploader: &StagingUploader<SA>,
+		queue: &Queue,
+		additional: u64
+	) -> Result<(), GpuVecError<A::Error>>
+	where
+		SA::AllocationRequirements: Clone
+	{
+		self.poll()?;
+
+		let required = self.len + additional;
+		let capacity = self.capacity();
+		if required <= capacity {
+			return Ok(())
+		}
+
+		let new_capacity = NonZeroU64::new(required.max(capacity * 2)).unwrap();
+		log::debug!(
+			"Growing GpuVec from {} to {} elements",
+			capacity,
+			new_capacity
+		);
+
+		let storage = TypedBuffer::new(
+			self.storage.buffer().device().clone(),
+			new_capacity,
+			self.additional_usage,
+			self.sharing_mode.clone(),
+			BufferAllocatorParams::Some { allocator: &self.allocator, requirements: self.requirements.clone() },
+			self.host_memory_allocator.clone()
+		)?;
+
+		if let Some(used) = NonZeroU64::new(self.storage.byte_offset(self.len)) {
+			let copy = uploader.copy_buffer(
+				queue,
+				self.storage.buffer().clone(),
+				storage.buffer(),
+				BufferBufferCopy::new(0, 0, used)
+			)?;
+			self.pending_copies.push(copy);
+		}
+
+		self.storage = storage;
+		self.generation += 1;
+
+		Ok(())
+	}
+
+	/// Appends `data` to the end of the vector, growing it if needed.
+	///
+	/// The upload is submitted to `queue` after the copy of any growth, so that submissions to the same queue see both.
+	pub fn extend_from_slice<SA: BufferMemoryAllocator<Error = A::Error>>(
+		&mut self,
+		uploader: &StagingUploader<SA>,
+		queue: &Queue,
+		data: &[T]
+	) -> Result<PendingUpload, GpuVecError<A::Error>>
+	where
+		SA::AllocationRequirements: Clone
+	{
+		self.reserve(uploader, queue, data.len() as u64)?;
+
+		// Lay out the elements with the stride of the buffer, the padding is zeroed
+		let stride = self.storage.stride() as usize;
+		let mut bytes = vec![0u8; data.len() * stride];
+		for (index, element) in data.iter().enumerate() {
+			unsafe {
+				std::ptr::copy_nonoverlapping(
+					element as *const T as *const u8,
+					bytes.as_mut_ptr().add(index * stride),
+					std::mem::size_of::<T>()
+				);
+			}
+		}
+
+		let upload = uploader.upload_to_buffer(
+			queue,
+			&bytes,
+			self.storage.buffer(),
+			self.storage.byte_offset(self.len)
+		)?;
+		self.len += data.len() as u64;
+
+		Ok(upload)
+	}
+
+	/// Appends `value` to the end of the vector, see `extend_from_slice`.
+	pub fn push<SA: BufferMemoryAllocator<Error = A::Error>>(
+		&mut self,
+		uploader: &StagingUploader<SA>,
+		queue: &Queue,
+		value: T
+	) -> Result<PendingUpload, GpuVecError<A::Error>>
+	where
+		SA::AllocationRequirements: Clone
+	{
+		self.extend_from_slice(uploader, queue, std::slice::from_ref(&value))
+	}
+
+	/// Shortens the vector to `len` elements, keeping the capacity.
+	///
+	/// Has no effect if `len` is greater than the current length.
+	pub fn truncate(&mut self, len: u64) {
+		self.len = self.len.min(len);
+	}
+
+	/// Removes all elements, keeping the capacity.
+	pub fn clear(&mut self) {
+		self.len = 0;
+	}
+
+	/// Releases previous buffers whose copies have finished.
+	pub fn poll(&mut self) -> Result<(), GpuVecError<A::Error>> {
+		let mut index = 0;
+		while index < self.pending_copies.len() {
+			if self.pending_copies[index].is_complete()? {
+				self.pending_copies.swap_remove(index);
+			} else {
+				index += 1;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns the number of elements.
+	pub const fn len(&self) -> u64 {
+		self.len
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the number of elements that fit into the current buffer.
+	pub const fn capacity(&self) -> u64 {
+		self.storage.len().get()
+	}
+
+	/// Returns the number of times the buffer was replaced by growing.
+	pub const fn generation(&self) -> u64 {
+		self.generation
+	}
+
+	/// Returns the current buffer, valid until the vector grows.
+	pub const fn storage(&self) -> &TypedBuffer<T, U> {
+		&self.storage
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		self.storage.buffer()
+	}
+
+	/// Returns the byte range of the elements in the current buffer, or `None` if the vector is empty.
+	pub fn byte_range(&self) -> Option<Range<vk::DeviceSize>> {
+		if self.is_empty() {
+			return None
+		}
+
+		Some(self.storage.byte_range(0 .. self.len))
+	}
+}
+impl<T: Copy, U: DescriptorBufferUsage, A: BufferMemoryAllocator> GpuVec<T, U, A>
+where
+	A::AllocationRequirements: Clone
+{
+	/// Returns a descriptor buffer info of all elements in the current buffer, or `None` if the vector is empty.
+	///
+	/// Descriptor sets written using the returned info have to be rewritten when `generation` changes.
+	pub fn descriptor_info(&self) -> Option<DescriptorBufferInfo<'_>> {
+		if self.is_empty() {
+			return None
+		}
+
+		Some(self.storage.descriptor_info(0 .. self.len))
+	}
+}
+impl<T: Copy, U: TypedBufferUsage, A: BufferMemoryAllocator> fmt::Debug for GpuVec<T, U, A>
+where
+	A::AllocationRequirements: Clone
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("GpuVec")
+			.field("storage", &self.storage)
+			.field("len", &self.len)
+			.field("generation", &self.generation)
+			.field("pending_copies", &self.pending_copies)
+			.field("additional_usage", &self.additional_usage)
+			.field("sharing_mode", &self.sharing_mode)
+			.field("allocator", &self.allocator)
+			.field("requirements", &self.requirements)
+			.field("host_memory_allocator", &self.host_memory_allocator)
+			.finish()
+	}
+}
@@ -1,6 +1,11 @@
 pub use buffer::Buffer;
 
+#[cfg(feature = "vulkan1_2")]
+pub mod address;
 pub mod buffer;
 pub mod error;
+pub mod gpu_vec;
 pub mod params;
+pub mod suballoc;
+pub mod typed;
 pub mod view;
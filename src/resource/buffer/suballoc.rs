@@ -0,0 +1,146 @@
+//! Linear sub-allocation of small regions from one big buffer.
+
+use std::{fmt, num::NonZeroU64};
+
+use ash::vk;
+
+use super::{error, Buffer};
+use crate::{
+	memory::device::MappingAccessResult,
+	prelude::{DescriptorBufferInfo, Vrc, Vutex}
+};
+
+/// Region of a buffer handed out by `BufferArena`.
+#[derive(Debug, Clone)]
+pub struct BufferSlice {
+	pub buffer: Vrc<Buffer>,
+	pub offset: vk::DeviceSize,
+	pub size: NonZeroU64
+}
+impl BufferSlice {
+	/// Writes `data` at the beginning of this slice.
+	///
+	/// Returns `TooLarge` without writing anything if `data` does not fit into the slice.
+	pub fn write<T: Copy>(&self, data: &[T]) -> Result<(), error::BufferSliceWriteError> {
+		let size = std::mem::size_of_val(data) as u64;
+		if size > self.size.get() {
+			return Err(error::BufferSliceWriteError::TooLarge { size, capacity: self.size.get() })
+		}
+
+		self.buffer
+			.memory()
+			.ok_or(error::BufferSliceWriteError::NoMemory)?
+			.map_memory_with(|mut access| {
+				access.write_slice(data, self.offset as usize, Default::default());
+				MappingAccessResult::Continue
+			})?;
+
+		Ok(())
+	}
+
+	pub fn descriptor_info(&self) -> DescriptorBufferInfo<'_> {
+		DescriptorBufferInfo::new(&self.buffer, self.offset, self.size)
+	}
+}
+
+/// Linear allocator of aligned regions from one buffer.
+///
+/// The alignment of each region is at least the minimum offset alignment of each descriptor kind
+/// the buffer usage allows (uniform, storage and texel buffers).
+///
+/// Regions are never freed individually, instead the whole arena is reset at once, usually once per frame.
+pub struct BufferArena {
+	buffer: Vrc<Buffer>,
+	alignment: vk::DeviceSize,
+
+	head: Vutex<vk::DeviceSize>
+}
+impl BufferArena {
+	pub fn new(buffer: Vrc<Buffer>) -> Self {
+		let limits = &buffer.device().physical_properties().limits;
+		let usage = buffer.usage();
+
+		let mut alignment: vk::DeviceSize = 1;
+		if usage.contains(vk::BufferUsageFlags::UNIFORM_BUFFER) {
+			alignment = alignment.max(limits.min_uniform_buffer_offset_alignment);
+		}
+		if usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER) {
+			alignment = alignment.max(limits.min_storage_buffer_offset_alignment);
+		}
+		if usage.intersects(vk::BufferUsageFlags::UNIFORM_TEXEL_BUFFER | vk::BufferUsageFlags::STORAGE_TEXEL_BUFFER) {
+			alignment = alignment.max(limits.min_texel_buffer_offset_alignment);
+		}
+
+		BufferArena { buffer, alignment, head: Vutex::new(0) }
+	}
+
+	/// Allocates a region of `size` bytes aligned to the arena alignment.
+	///
+	/// Returns `None` if there isn't enough space left in the arena.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn allocate(&self, size: NonZeroU64) -> Option<BufferSlice> {
+		self.allocate_aligned(size, self.alignment)
+	}
+
+	/// Allocates a region of `size` bytes aligned to both `alignment` and the arena alignment.
+	///
+	/// `alignment` must be a power of two.
+	///
+	/// Returns `None` if there isn't enough space left in the arena.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn allocate_aligned(&self, size: NonZeroU64, alignment: vk::DeviceSize) -> Option<BufferSlice> {
+		let alignment = alignment.max(self.alignment);
+		let mut head = self.head.lock().expect("vutex poisoned");
+
+		let offset = crate::util::align_up(*head as usize, alignment as usize) as vk::DeviceSize;
+		if offset + size.get() > self.buffer.size().get() {
+			return None
+		}
+		*head = offset + size.get();
+
+		Some(BufferSlice { buffer: self.buffer.clone(), offset, size })
+	}
+
+	/// Resets the arena so that the whole buffer can be allocated again.
+	///
+	/// Slices allocated before the reset will alias the newly allocated slices.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn reset(&self) {
+		*self.head.lock().expect("vutex poisoned") = 0;
+	}
+
+	/// Returns the number of bytes allocated since the last reset, including alignment padding.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the `Vutex` is poisoned.
+	pub fn used(&self) -> vk::DeviceSize {
+		*self.head.lock().expect("vutex poisoned")
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+
+	pub const fn alignment(&self) -> vk::DeviceSize {
+		self.alignment
+	}
+}
+impl fmt::Debug for BufferArena {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("BufferArena")
+			.field("buffer", &self.buffer)
+			.field("alignment", &self.alignment)
+			.field("head", &self.head)
+			.finish()
+	}
+}
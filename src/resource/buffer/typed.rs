@@ -0,0 +1,253 @@
+//! Buffers holding an array of elements of one type.
+//!
+//! The intended usage of a `TypedBuffer` is a type parameter, so that the usage flags it requires are always set
+//! and usage-specific helpers, such as `index_type` or `descriptor_info`, are only available where they make sense.
+
+use std::{
+	fmt,
+	marker::PhantomData,
+	num::{NonZeroU64, NonZeroUsize},
+	ops::Range
+};
+
+use ash::vk;
+
+use super::{error, params, Buffer};
+use crate::{
+	memory::device::{allocator::BufferMemoryAllocator, MappingAccessResult, SliceWriteStride},
+	prelude::{DescriptorBufferInfo, Device, HostMemoryAllocator, Vrc},
+	queue::sharing_mode::SharingMode
+};
+
+/// Intended usage of a `TypedBuffer`.
+pub trait TypedBufferUsage {
+	/// Usage flags the buffer is required to have.
+	const USAGE: vk::BufferUsageFlags;
+
+	/// Returns the alignment of elements in the buffer.
+	///
+	/// The default is the alignment of the element type.
+	fn element_alignment<T>(_limits: &vk::PhysicalDeviceLimits) -> vk::DeviceSize {
+		std::mem::align_of::<T>() as vk::DeviceSize
+	}
+}
+/// Usage that allows binding elements as descriptors.
+pub trait DescriptorBufferUsage: TypedBufferUsage {}
+
+/// Vertex buffer usage.
+#[derive(Debug, Clone, Copy)]
+pub struct VertexUsage;
+impl TypedBufferUsage for VertexUsage {
+	const USAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::VERTEX_BUFFER;
+}
+
+/// Index buffer usage.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexUsage;
+impl TypedBufferUsage for IndexUsage {
+	const USAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::INDEX_BUFFER;
+}
+
+/// Uniform buffer usage.
+///
+/// Elements are aligned to `minUniformBufferOffsetAlignment` so that each of them can be bound separately.
+#[derive(Debug, Clone, Copy)]
+pub struct UniformUsage;
+impl TypedBufferUsage for UniformUsage {
+	const USAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::UNIFORM_BUFFER;
+
+	fn element_alignment<T>(limits: &vk::PhysicalDeviceLimits) -> vk::DeviceSize {
+		limits.min_uniform_buffer_offset_alignment.max(std::mem::align_of::<T>() as vk::DeviceSize)
+	}
+}
+impl DescriptorBufferUsage for UniformUsage {}
+
+/// Storage buffer usage.
+///
+/// Elements are tightly packed, the whole buffer is usually bound as one array.
+#[derive(Debug, Clone, Copy)]
+pub struct StorageUsage;
+impl TypedBufferUsage for StorageUsage {
+	const USAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::STORAGE_BUFFER;
+}
+impl DescriptorBufferUsage for StorageUsage {}
+
+/// Staging buffer usage, the source of transfers.
+#[derive(Debug, Clone, Copy)]
+pub struct StagingUsage;
+impl TypedBufferUsage for StagingUsage {
+	const USAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::TRANSFER_SRC;
+}
+
+/// Element type of index buffers.
+pub trait IndexElement: Copy {
+	const INDEX_TYPE: vk::IndexType;
+}
+impl IndexElement for u16 {
+	const INDEX_TYPE: vk::IndexType = vk::IndexType::UINT16;
+}
+impl IndexElement for u32 {
+	const INDEX_TYPE: vk::IndexType = vk::IndexType::UINT32;
+}
+
+/// Buffer holding an array of `T`s with usage `U`.
+pub struct TypedBuffer<T: Copy, U: TypedBufferUsage> {
+	buffer: Vrc<Buffer>,
+	len: NonZeroU64,
+	stride: vk::DeviceSize,
+
+	_marker: PhantomData<fn() -> (T, U)>
+}
+impl<T: Copy, U: TypedBufferUsage> TypedBuffer<T, U> {
+	/// Creates a new buffer holding `len` elements.
+	///
+	/// The buffer is created with `U::USAGE` and `additional_usage`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `T` is zero-sized or if the size of the buffer in bytes overflows `u64`.
+	pub fn new<A: BufferMemoryAllocator>(
+		device: Vrc<Device>,
+		len: NonZeroU64,
+		additional_usage: vk::BufferUsageFlags,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		allocator_params: params::BufferAllocatorParams<A>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, error::BufferError<A::Error>> {
+		let stride = Self::stride_for(&device);
+		let size = stride.checked_mul(len.get()).expect("buffer size must not overflow");
+		let size = NonZeroU64::new(size).expect("element type must not be zero-sized");
+
+		let buffer = Buffer::new(
+			device,
+			size,
+			U::USAGE | additional_usage,
+			sharing_mode,
+			allocator_params,
+			host_memory_allocator
+		)?;
+
+		Ok(TypedBuffer { buffer, len, stride, _marker: PhantomData })
+	}
+
+	/// Wraps an existing buffer, the number of elements is derived from its size.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `T` is zero-sized.
+	pub fn from_buffer(buffer: Vrc<Buffer>) -> Result<Self, error::TypedBufferError> {
+		if !buffer.usage().contains(U::USAGE) {
+			return Err(error::TypedBufferError::UsageMissing(buffer.usage()))
+		}
+
+		let stride = Self::stride_for(buffer.device());
+		assert!(stride > 0, "element type must not be zero-sized");
+		let len = NonZeroU64::new(buffer.size().get() / stride).ok_or(error::TypedBufferError::BufferTooSmall)?;
+
+		Ok(TypedBuffer { buffer, len, stride, _marker: PhantomData })
+	}
+
+	fn stride_for(device: &Device) -> vk::DeviceSize {
+		let alignment = U::element_alignment::<T>(&device.physical_properties().limits);
+
+		crate::util::align_up(std::mem::size_of::<T>(), alignment as usize) as vk::DeviceSize
+	}
+
+	/// Writes `data` starting at element `first`.
+	///
+	/// Returns an error if any of the elements do not fit into the buffer. The memory must be host visible.
+	pub fn write(&self, first: u64, data: &[T]) -> Result<(), error::BufferSliceWriteError> {
+		let count = data.len() as u64;
+		match first.checked_add(count) {
+			Some(end) if end <= self.len.get() => (),
+			_ => return Err(error::BufferSliceWriteError::OutOfBounds { first, count, len: self.len.get() })
+		}
+
+		let stride = SliceWriteStride::Stride(NonZeroUsize::new(self.stride as usize).unwrap());
+
+		self.buffer
+			.memory()
+			.ok_or(error::BufferSliceWriteError::NoMemory)?
+			.map_memory_with(|mut access| {
+				access.write_slice(
+					data,
+					self.byte_offset(first) as usize,
+					stride
+				);
+				MappingAccessResult::Continue
+			})?;
+
+		Ok(())
+	}
+
+	/// Returns the offset of element `index` in bytes.
+	pub const fn byte_offset(&self, index: u64) -> vk::DeviceSize {
+		index * self.stride
+	}
+
+	/// Returns the byte range covered by `elements`.
+	///
+	/// The range ends at the end of the last element, not including the padding after it.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `elements` is empty or out of bounds.
+	pub fn byte_range(&self, elements: Range<u64>) -> Range<vk::DeviceSize> {
+		assert!(
+			elements.start < elements.end && elements.end <= self.len.get(),
+			"element range {:?} is empty or out of bounds of {} elements",
+			elements,
+			self.len
+		);
+
+		self.byte_offset(elements.start) .. self.byte_offset(elements.end - 1) + std::mem::size_of::<T>() as vk::DeviceSize
+	}
+
+	/// Returns the number of elements.
+	pub const fn len(&self) -> NonZeroU64 {
+		self.len
+	}
+
+	/// Returns the distance between elements in bytes.
+	pub const fn stride(&self) -> vk::DeviceSize {
+		self.stride
+	}
+
+	pub const fn buffer(&self) -> &Vrc<Buffer> {
+		&self.buffer
+	}
+}
+impl<T: IndexElement> TypedBuffer<T, IndexUsage> {
+	/// Returns the index type to use when binding this buffer.
+	pub const fn index_type(&self) -> vk::IndexType {
+		T::INDEX_TYPE
+	}
+}
+impl<T: Copy, U: DescriptorBufferUsage> TypedBuffer<T, U> {
+	/// Returns a descriptor buffer info of `elements`, see `byte_range`.
+	pub fn descriptor_info(&self, elements: Range<u64>) -> DescriptorBufferInfo<'_> {
+		let range = self.byte_range(elements);
+
+		DescriptorBufferInfo::new(
+			&self.buffer,
+			range.start,
+			NonZeroU64::new(range.end - range.start).unwrap()
+		)
+	}
+}
+impl<T: Copy, U: TypedBufferUsage> Clone for TypedBuffer<T, U> {
+	fn clone(&self) -> Self {
+		TypedBuffer { buffer: self.buffer.clone(), len: self.len, stride: self.stride, _marker: PhantomData }
+	}
+}
+impl<T: Copy, U: TypedBufferUsage> fmt::Debug for TypedBuffer<T, U> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TypedBuffer")
+			.field("element", &std::any::type_name::<T>())
+			.field("usage", &U::USAGE)
+			.field("buffer", &self.buffer)
+			.field("len", &self.len)
+			.field("stride", &self.stride)
+			.finish()
+	}
+}
@@ -2,7 +2,7 @@ use std::{fmt, num::NonZeroU64, ops::Deref};
 
 use ash::vk;
 
-use crate::prelude::{Buffer, HasHandle, HostMemoryAllocator, Vrc};
+use crate::prelude::{Buffer, DescriptorTypeTexelBuffer, HasHandle, HostMemoryAllocator, Vrc};
 
 pub struct BufferView {
 	buffer: Vrc<Buffer>,
@@ -23,6 +23,22 @@ impl BufferView {
 		range: NonZeroU64,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<Vrc<Self>, super::error::BufferViewError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			let mut required_features = vk::FormatFeatureFlags::empty();
+			if buffer.usage().contains(vk::BufferUsageFlags::UNIFORM_TEXEL_BUFFER) {
+				required_features |= vk::FormatFeatureFlags::UNIFORM_TEXEL_BUFFER;
+			}
+			if buffer.usage().contains(vk::BufferUsageFlags::STORAGE_TEXEL_BUFFER) {
+				required_features |= vk::FormatFeatureFlags::STORAGE_TEXEL_BUFFER;
+			}
+			if required_features.is_empty() {
+				return Err(super::error::BufferViewError::UsageMismatch)
+			}
+
+			Self::validate(&buffer, format, offset, range, required_features)?;
+		}
+
 		let create_info = vk::BufferViewCreateInfo::builder()
 			.buffer(buffer.handle())
 			.format(format)
@@ -38,6 +54,84 @@ impl BufferView {
 		}
 	}
 
+	/// Creates a new buffer view to be used as a texel buffer descriptor of type `descriptor_type`.
+	///
+	/// Unlike `new`, only the buffer usage and format features required by `descriptor_type` are validated.
+	/// The view can be passed directly to `DescriptorSetWriteData::TexelBuffer` using `safe_handle`.
+	pub fn new_texel(
+		buffer: Vrc<Buffer>,
+		descriptor_type: DescriptorTypeTexelBuffer,
+		format: vk::Format,
+		offset: vk::DeviceSize,
+		range: NonZeroU64,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, super::error::BufferViewError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			let (required_usage, required_features) = match descriptor_type {
+				DescriptorTypeTexelBuffer::UNIFORM_TEXEL_BUFFER => (
+					vk::BufferUsageFlags::UNIFORM_TEXEL_BUFFER,
+					vk::FormatFeatureFlags::UNIFORM_TEXEL_BUFFER
+				),
+				DescriptorTypeTexelBuffer::STORAGE_TEXEL_BUFFER => (
+					vk::BufferUsageFlags::STORAGE_TEXEL_BUFFER,
+					vk::FormatFeatureFlags::STORAGE_TEXEL_BUFFER
+				)
+			};
+			if !buffer.usage().contains(required_usage) {
+				return Err(super::error::BufferViewError::UsageMismatch)
+			}
+
+			Self::validate(&buffer, format, offset, range, required_features)?;
+		}
+		#[cfg(not(feature = "runtime_implicit_validations"))]
+		let _ = descriptor_type;
+
+		let create_info = vk::BufferViewCreateInfo::builder()
+			.buffer(buffer.handle())
+			.format(format)
+			.offset(offset)
+			.range(range.get());
+
+		unsafe {
+			Self::from_create_info(
+				buffer,
+				create_info,
+				host_memory_allocator
+			)
+		}
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn validate(
+		buffer: &Buffer,
+		format: vk::Format,
+		offset: vk::DeviceSize,
+		range: NonZeroU64,
+		required_features: vk::FormatFeatureFlags
+	) -> Result<(), super::error::BufferViewError> {
+		let physical_device = buffer.device().physical_device();
+
+		if !physical_device
+			.format_properties(format)
+			.buffer_features
+			.contains(required_features)
+		{
+			return Err(super::error::BufferViewError::FormatNotSupported(format))
+		}
+
+		let alignment = buffer.device().physical_properties().limits.min_texel_buffer_offset_alignment;
+		if alignment != 0 && offset % alignment != 0 {
+			return Err(super::error::BufferViewError::OffsetNotAligned)
+		}
+
+		if offset.checked_add(range.get()).map(|end| end > buffer.size().get()).unwrap_or(true) {
+			return Err(super::error::BufferViewError::RangeOutOfBounds)
+		}
+
+		Ok(())
+	}
+
 	/// ### Safety
 	///
 	/// * See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkCreateBufferView.html>.
@@ -49,6 +143,13 @@ impl BufferView {
 	) -> Result<Vrc<Self>, super::error::BufferViewError> {
 		let c_info = create_info.deref();
 
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if c_info.buffer != buffer.handle() {
+				return Err(super::error::BufferViewError::BufferMismatch)
+			}
+		}
+
 		log_trace_common!(
 			"Create buffer view:",
 			buffer,
@@ -65,6 +166,7 @@ impl BufferView {
 			buffer.size().get() - offset
 		));
 
+		buffer.device().child_registry().register(view);
 		Ok(Vrc::new(BufferView {
 			buffer,
 			view,
@@ -99,6 +201,7 @@ impl_common_handle_traits! {
 impl Drop for BufferView {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.buffer.device().child_registry().unregister(self.view);
 
 		unsafe {
 			self.buffer.device().destroy_buffer_view(
@@ -1,3 +1,5 @@
+use thiserror::Error;
+
 vk_result_error! {
 	#[derive(Debug)]
 	pub enum ImageError [AllocError] where [AllocError: std::error::Error + 'static] {
@@ -14,6 +16,10 @@ vk_result_error! {
 		#[error("The memory must be allocated from the same device")]
 		MemoryDeviceMismatch,
 
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Sparse flags must contain SPARSE_BINDING")]
+		SparseBindingFlagMissing,
+
 		#[error("Allocation error produced by the allocator parameter")]
 		AllocationError(AllocError),
 	}
@@ -27,5 +33,40 @@ vk_result_error! {
 			ERROR_OUT_OF_HOST_MEMORY,
 			ERROR_OUT_OF_DEVICE_MEMORY
 		}
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Image format does not have the {0:?} aspect")]
+		AspectNotPresent(ash::vk::ImageAspectFlags),
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Image must be created with SAMPLED usage")]
+		SampledUsageMissing,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Format {0:?} does not support sampling with optimal tiling")]
+		FormatNotSampleable(ash::vk::Format),
 	}
 }
+
+/// Error returned when an image is used in a way not allowed by its usage or by whether it is a swapchain image.
+#[derive(Error, Debug)]
+pub enum ImageUseError {
+	#[error("Image was not created with the {0:?} usage")]
+	UsageMissing(ash::vk::ImageUsageFlags),
+
+	#[error("Layout {0:?} is not allowed for swapchain images")]
+	SwapchainImageLayout(ash::vk::ImageLayout),
+
+	#[error("Layout {0:?} is only allowed for swapchain images")]
+	PresentLayoutNotSwapchainImage(ash::vk::ImageLayout)
+}
+
+/// Error returned when the source data of a buffer-image copy does not cover the copied region.
+#[derive(Error, Debug)]
+pub enum ImageCopySourceError {
+	#[error("Texel block size of aspect {aspect:?} of format {format:?} is not known")]
+	UnknownTexelBlock { format: ash::vk::Format, aspect: ash::vk::ImageAspectFlags },
+
+	#[error("Source of {size} bytes is smaller than the {required} bytes read by the copy")]
+	TooSmall { size: u64, required: u64 }
+}
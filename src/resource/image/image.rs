@@ -1,10 +1,10 @@
-use std::{fmt, ops::Deref};
+use std::{convert::Infallible, fmt, ops::Deref};
 
 use ash::vk;
 
-use super::{error, params};
+use super::{error, layout::ImageLayoutTracker, params};
 use crate::{
-	memory::device::{allocator::ImageMemoryAllocator, DeviceMemoryAllocation},
+	memory::device::{allocator::ImageMemoryAllocator, never::NeverDeviceAllocator, DeviceMemoryAllocation},
 	prelude::{Device, HasHandle, HostMemoryAllocator, Vrc},
 	queue::sharing_mode::SharingMode
 };
@@ -17,7 +17,10 @@ pub struct Image {
 	usage: vk::ImageUsageFlags,
 	format: vk::Format,
 	size: params::ImageSize,
-	// TODO: Tiling and sharing mode + indices?
+	layout_tracker: ImageLayoutTracker,
+	swapchain_image: bool,
+	sharing_mode: vk::SharingMode,
+	// TODO: Tiling and queue family indices?
 	host_memory_allocator: HostMemoryAllocator
 }
 impl Image {
@@ -65,6 +68,57 @@ impl Image {
 		}
 	}
 
+	/// Creates a new sparse image without any memory bound.
+	///
+	/// `sparse_flags` must contain `SPARSE_BINDING` and may contain `SPARSE_RESIDENCY` and `SPARSE_ALIASED`.
+	/// They are combined with the flags derived from `size_info`. Memory is bound later using `Queue::bind_sparse`.
+	pub fn new_sparse(
+		device: Vrc<Device>,
+		format: vk::Format,
+		size_info: params::ImageSizeInfo,
+		tiling_and_layout: params::ImageTilingAndLayout,
+		usage: vk::ImageUsageFlags,
+		sparse_flags: vk::ImageCreateFlags,
+		sharing_mode: SharingMode<impl AsRef<[u32]>>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, error::ImageError<Infallible>> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if usage.is_empty() {
+				return Err(error::ImageError::UsageEmpty)
+			}
+			if !sparse_flags.contains(vk::ImageCreateFlags::SPARSE_BINDING) {
+				return Err(error::ImageError::SparseBindingFlagMissing)
+			}
+		}
+
+		let (size, samples, flags) = size_info.into();
+		let (tiling, layout) = tiling_and_layout.into();
+
+		let create_info = vk::ImageCreateInfo::builder()
+			.flags(flags | sparse_flags)
+			.image_type(size.image_type())
+			.format(format)
+			.extent(size.into())
+			.mip_levels(size.mipmap_levels().get())
+			.array_layers(size.array_layers().get())
+			.samples(samples)
+			.tiling(tiling)
+			.usage(usage)
+			.sharing_mode(sharing_mode.sharing_mode())
+			.queue_family_indices(sharing_mode.indices())
+			.initial_layout(layout);
+
+		unsafe {
+			Self::from_create_info(
+				device,
+				create_info,
+				params::ImageAllocatorParams::<NeverDeviceAllocator>::None,
+				host_memory_allocator
+			)
+		}
+	}
+
 	/// Creates a new `Image` from existing `ImageCreateInfo`
 	///
 	/// ### Safety
@@ -112,7 +166,9 @@ impl Image {
 		};
 
 		let size = params::ImageSize::from_image_create_info(c_info);
+		let layout_tracker = ImageLayoutTracker::new(size, c_info.initial_layout);
 
+		device.child_registry().register(image);
 		Ok(Vrc::new(Image {
 			device,
 			image,
@@ -120,6 +176,9 @@ impl Image {
 			usage: c_info.usage,
 			format: c_info.format,
 			size,
+			layout_tracker,
+			swapchain_image: false,
+			sharing_mode: c_info.sharing_mode,
 			host_memory_allocator
 		}))
 	}
@@ -131,6 +190,8 @@ impl Image {
 	/// * `image` must have been crated from the `device`.
 	/// * `memory` must have been allocated from the `device`.
 	/// * All parameters must match the parameters used when creating the image.
+	///
+	/// The tracked layout of all subresources is `UNDEFINED`.
 	pub unsafe fn from_existing(
 		device: Vrc<Device>,
 		image: vk::Image,
@@ -138,6 +199,7 @@ impl Image {
 		usage: vk::ImageUsageFlags,
 		format: vk::Format,
 		size: params::ImageSize,
+		sharing_mode: vk::SharingMode,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Self {
 		log_trace_common!(
@@ -147,16 +209,28 @@ impl Image {
 			memory,
 			format,
 			size,
+			sharing_mode,
 			host_memory_allocator
 		);
 
-		Image { device, image, memory, usage, format, size, host_memory_allocator }
+		let layout_tracker = ImageLayoutTracker::new(size, vk::ImageLayout::UNDEFINED);
+
+		Image { device, image, memory, usage, format, size, layout_tracker, swapchain_image: false, sharing_mode, host_memory_allocator }
+	}
+
+	/// Marks this image as owned by a swapchain, see `SwapchainImage::new`.
+	pub(crate) fn mark_swapchain_image(&mut self) {
+		self.swapchain_image = true;
 	}
 
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
 
+	pub const fn host_memory_allocator(&self) -> HostMemoryAllocator {
+		self.host_memory_allocator
+	}
+
 	pub const fn usage(&self) -> vk::ImageUsageFlags {
 		self.usage
 	}
@@ -169,10 +243,77 @@ impl Image {
 		self.format
 	}
 
+	pub const fn sharing_mode(&self) -> vk::SharingMode {
+		self.sharing_mode
+	}
+
+	/// Returns the subresource range covering the whole image with all aspects of its format.
+	pub fn whole_subresource_range(&self) -> params::ImageSubresourceRange {
+		params::ImageSubresourceRange::whole_image(
+			self.size,
+			params::format_aspects(self.format)
+		)
+	}
+
+	/// Returns the tracker of the current layout of each subresource of this image.
+	pub const fn layout_tracker(&self) -> &ImageLayoutTracker {
+		&self.layout_tracker
+	}
+
+	/// Returns whether this image is owned by a swapchain, for example when it is dereferenced from `SwapchainImage` or `MixedDynImage`.
+	pub const fn is_swapchain_image(&self) -> bool {
+		self.swapchain_image
+	}
+
+	/// Checks that this image was created with all of `usage`.
+	pub fn validate_usage(&self, usage: vk::ImageUsageFlags) -> Result<(), error::ImageUseError> {
+		if !self.usage.contains(usage) {
+			return Err(error::ImageUseError::UsageMissing(usage))
+		}
+
+		Ok(())
+	}
+
+	/// Checks that this image can be in `layout`.
+	///
+	/// Swapchain images are color images that are never preinitialized, so `PREINITIALIZED` and depth/stencil layouts are not allowed for them.
+	/// On the other hand, `PRESENT_SRC_KHR` and `SHARED_PRESENT_KHR` are only allowed for swapchain images.
+	pub fn validate_layout(&self, layout: vk::ImageLayout) -> Result<(), error::ImageUseError> {
+		let present_layout = layout == vk::ImageLayout::PRESENT_SRC_KHR || layout == vk::ImageLayout::SHARED_PRESENT_KHR;
+		if !self.swapchain_image {
+			if present_layout {
+				return Err(error::ImageUseError::PresentLayoutNotSwapchainImage(layout))
+			}
+
+			return Ok(())
+		}
+
+		match layout {
+			vk::ImageLayout::PREINITIALIZED
+			| vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+			| vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL
+			| vk::ImageLayout::DEPTH_READ_ONLY_STENCIL_ATTACHMENT_OPTIMAL
+			| vk::ImageLayout::DEPTH_ATTACHMENT_STENCIL_READ_ONLY_OPTIMAL
+			| vk::ImageLayout::DEPTH_ATTACHMENT_OPTIMAL
+			| vk::ImageLayout::DEPTH_READ_ONLY_OPTIMAL
+			| vk::ImageLayout::STENCIL_ATTACHMENT_OPTIMAL
+			| vk::ImageLayout::STENCIL_READ_ONLY_OPTIMAL => Err(error::ImageUseError::SwapchainImageLayout(layout)),
+			_ => Ok(())
+		}
+	}
+
 	// TODO: Cannot be const because of Sized
 	pub fn memory(&self) -> Option<&DeviceMemoryAllocation> {
 		self.memory.as_ref()
 	}
+
+	/// Returns the number of bytes committed to the lazily allocated memory of this image, see `DeviceMemoryAllocation::commitment`.
+	///
+	/// Useful to verify that `TRANSIENT_ATTACHMENT` images bound to `LAZILY_ALLOCATED` memory are not backed by main memory on tilers.
+	/// Returns `None` if the image has no bound memory or the memory is not lazily allocated.
+	pub fn memory_commitment(&self) -> Option<vk::DeviceSize> {
+		self.memory()?.commitment()
+	}
 }
 impl_common_handle_traits! {
 	impl HasHandle<vk::Image>, Deref, Borrow, Eq, Hash, Ord for Image {
@@ -182,6 +323,7 @@ impl_common_handle_traits! {
 impl Drop for Image {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.image);
 
 		unsafe {
 			self.device.destroy_image(
@@ -206,6 +348,9 @@ impl fmt::Debug for Image {
 			.field("usage", &self.usage)
 			.field("format", &self.format)
 			.field("size", &self.size)
+			.field("layout_tracker", &self.layout_tracker)
+			.field("swapchain_image", &self.swapchain_image)
+			.field("sharing_mode", &self.sharing_mode)
 			.field(
 				"host_memory_allocator",
 				&self.host_memory_allocator
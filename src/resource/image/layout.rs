@@ -1,5 +1,10 @@
+use std::fmt;
+
 use ash::vk;
 
+use super::params::{ImageSize, ImageSubresourceRange};
+use crate::util::sync::Vutex;
+
 vk_enum_subset! {
 	/// Enum for image layout that can be used in final or new layout position.
 	///
@@ -61,6 +66,7 @@ vk_enum_subset! {
 }
 
 pub type ImageLayoutClearColorImage = ImageLayoutDestination;
+pub type ImageLayoutClearDepthStencilImage = ImageLayoutDestination;
 
 vk_enum_subset! {
 	/// Enum for image layout that can be used as a sampled image in shaders.
@@ -77,3 +83,178 @@ vk_enum_subset! {
 }
 
 pub type ImageLayoutInputAttachment = ImageLayoutSampled;
+
+/// Returns the pipeline stages and access flags commonly used with an image in `layout`.
+///
+/// When `source` is true, the scope is the one that must complete before the image leaves `layout`, in which case
+/// read accesses are omitted because only writes need to be made available. Otherwise the scope is the one that must wait
+/// for the image to enter `layout`.
+///
+/// Layouts with unknown usage, such as `GENERAL`, return `ALL_COMMANDS` with all memory accesses.
+/// `PRESENT_SRC_KHR` as a source returns `ALL_COMMANDS` so that the barrier chains with any semaphore wait of the acquire.
+pub fn layout_access_scope(layout: vk::ImageLayout, source: bool) -> (vk::PipelineStageFlags, vk::AccessFlags) {
+	let (stages, read, write) = match layout {
+		vk::ImageLayout::UNDEFINED => (vk::PipelineStageFlags::TOP_OF_PIPE, vk::AccessFlags::empty(), vk::AccessFlags::empty()),
+		vk::ImageLayout::PREINITIALIZED => (vk::PipelineStageFlags::HOST, vk::AccessFlags::empty(), vk::AccessFlags::HOST_WRITE),
+		vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL => (
+			vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+			vk::AccessFlags::COLOR_ATTACHMENT_READ,
+			vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+		),
+		vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+		| vk::ImageLayout::DEPTH_ATTACHMENT_OPTIMAL
+		| vk::ImageLayout::STENCIL_ATTACHMENT_OPTIMAL
+		| vk::ImageLayout::DEPTH_READ_ONLY_STENCIL_ATTACHMENT_OPTIMAL
+		| vk::ImageLayout::DEPTH_ATTACHMENT_STENCIL_READ_ONLY_OPTIMAL => (
+			vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+			vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ,
+			vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE
+		),
+		vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL | vk::ImageLayout::DEPTH_READ_ONLY_OPTIMAL | vk::ImageLayout::STENCIL_READ_ONLY_OPTIMAL => (
+			vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+				| vk::PipelineStageFlags::LATE_FRAGMENT_TESTS
+				| vk::PipelineStageFlags::FRAGMENT_SHADER,
+			vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::SHADER_READ,
+			vk::AccessFlags::empty()
+		),
+		vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL => (
+			vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+			vk::AccessFlags::SHADER_READ,
+			vk::AccessFlags::empty()
+		),
+		vk::ImageLayout::TRANSFER_SRC_OPTIMAL => (vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::TRANSFER_READ, vk::AccessFlags::empty()),
+		vk::ImageLayout::TRANSFER_DST_OPTIMAL => (vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::empty(), vk::AccessFlags::TRANSFER_WRITE),
+		vk::ImageLayout::PRESENT_SRC_KHR if source => (vk::PipelineStageFlags::ALL_COMMANDS, vk::AccessFlags::empty(), vk::AccessFlags::empty()),
+		vk::ImageLayout::PRESENT_SRC_KHR => (vk::PipelineStageFlags::BOTTOM_OF_PIPE, vk::AccessFlags::empty(), vk::AccessFlags::empty()),
+		_ => (vk::PipelineStageFlags::ALL_COMMANDS, vk::AccessFlags::MEMORY_READ, vk::AccessFlags::MEMORY_WRITE)
+	};
+
+	if source {
+		(stages, write)
+	} else {
+		(stages, read | write)
+	}
+}
+
+/// Tracks the current layout of each mipmap level and array layer of an image.
+///
+/// The tracked layout is the layout the image will be in after all recorded commands execute in recording order.
+/// It is updated by the layout transition helpers when they are recorded, layout changes done in any other way
+/// (for example by render pass final layouts) must be reported with `set_layout`.
+///
+/// All aspects of a subresource share the tracked layout.
+pub struct ImageLayoutTracker {
+	mipmap_levels: u32,
+	array_layers: u32,
+	layouts: Vutex<Vec<vk::ImageLayout>>
+}
+impl ImageLayoutTracker {
+	/// Creates a new tracker with all subresources of an image with `size` in `initial_layout`.
+	pub fn new(size: ImageSize, initial_layout: vk::ImageLayout) -> Self {
+		let mipmap_levels = size.mipmap_levels().get();
+		let array_layers = size.array_layers().get();
+
+		ImageLayoutTracker {
+			mipmap_levels,
+			array_layers,
+			layouts: Vutex::new(vec![initial_layout; (mipmap_levels * array_layers) as usize])
+		}
+	}
+
+	fn indices(&self, range: ImageSubresourceRange) -> impl Iterator<Item = usize> {
+		debug_assert!(range.mipmap_levels_base + range.mipmap_levels.get() <= self.mipmap_levels);
+		debug_assert!(range.array_layers_base + range.array_layers.get() <= self.array_layers);
+
+		let mipmap_levels = self.mipmap_levels;
+		let levels = range.mipmap_levels_base .. range.mipmap_levels_base + range.mipmap_levels.get();
+		(range.array_layers_base .. range.array_layers_base + range.array_layers.get())
+			.flat_map(move |layer| levels.clone().map(move |level| (layer * mipmap_levels + level) as usize))
+	}
+
+	/// Returns the layout of all subresources in `range` or `None` if they are not all in the same layout.
+	pub fn layout(&self, range: ImageSubresourceRange) -> Option<vk::ImageLayout> {
+		let layouts = self.layouts.lock().expect("vutex poisoned");
+
+		let mut indices = self.indices(range);
+		let first = layouts[indices.next().unwrap()];
+		if indices.all(|index| layouts[index] == first) {
+			Some(first)
+		} else {
+			None
+		}
+	}
+
+	/// Sets the layout of all subresources in `range` to `layout`.
+	pub fn set_layout(&self, range: ImageSubresourceRange, layout: vk::ImageLayout) {
+		let mut layouts = self.layouts.lock().expect("vutex poisoned");
+
+		for index in self.indices(range) {
+			layouts[index] = layout;
+		}
+	}
+}
+impl fmt::Debug for ImageLayoutTracker {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ImageLayoutTracker")
+			.field("mipmap_levels", &self.mipmap_levels)
+			.field("array_layers", &self.array_layers)
+			.field("layouts", &self.layouts)
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::num::NonZeroU32;
+
+	use ash::vk;
+
+	use super::{layout_access_scope, ImageLayoutTracker};
+	use crate::resource::image::params::{ImageSize, ImageSubresourceRange, MipmapLevels};
+
+	#[test]
+	fn transition_scopes() {
+		assert_eq!(
+			layout_access_scope(vk::ImageLayout::UNDEFINED, true),
+			(vk::PipelineStageFlags::TOP_OF_PIPE, vk::AccessFlags::empty())
+		);
+		assert_eq!(
+			layout_access_scope(vk::ImageLayout::TRANSFER_DST_OPTIMAL, false),
+			(vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::TRANSFER_WRITE)
+		);
+		assert_eq!(
+			layout_access_scope(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL, true),
+			(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT, vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+		);
+		assert_eq!(
+			layout_access_scope(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL, true).1,
+			vk::AccessFlags::empty()
+		);
+	}
+
+	#[test]
+	fn layout_tracking() {
+		let size: ImageSize = ImageSize::new_2d(
+			NonZeroU32::new(64).unwrap(),
+			NonZeroU32::new(64).unwrap(),
+			NonZeroU32::new(2).unwrap(),
+			MipmapLevels::Most()
+		)
+		.into();
+		let tracker = ImageLayoutTracker::new(size, vk::ImageLayout::UNDEFINED);
+		let whole = ImageSubresourceRange::whole_image(size, vk::ImageAspectFlags::COLOR);
+		assert_eq!(tracker.layout(whole), Some(vk::ImageLayout::UNDEFINED));
+
+		let second_level = ImageSubresourceRange {
+			mipmap_levels_base: 1,
+			mipmap_levels: NonZeroU32::new(1).unwrap(),
+			..whole
+		};
+		tracker.set_layout(second_level, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+		assert_eq!(tracker.layout(whole), None);
+		assert_eq!(tracker.layout(second_level), Some(vk::ImageLayout::TRANSFER_DST_OPTIMAL));
+
+		tracker.set_layout(whole, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+		assert_eq!(tracker.layout(whole), Some(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL));
+	}
+}
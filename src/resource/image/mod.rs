@@ -20,6 +20,8 @@ pub mod view;
 
 deref_enum_dispatch! {
 	/// Mixed-dispatch image enum.
+	///
+	/// Functions taking `&Image` accept it through `Deref`. Swapchain images can still be recognized using `Image::is_swapchain_image`.
 	#[derive(Debug, Clone)]
 	pub enum MixedDynImage {
 		Image(Vrc<Image>),
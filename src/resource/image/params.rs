@@ -482,15 +482,16 @@ pub struct ImageSubresourceRange {
 	pub array_layers: NonZeroU32
 }
 impl ImageSubresourceRange {
-	// pub fn whole_image(image: &Image, aspect_mask: vk::ImageAspectFlags) -> Self {
-	// 	ImageSubresourceRange {
-	// 		aspect_mask,
-	// 		mipmap_levels_base: 0,
-	// 		mipmap_levels: image.size().mipmap_levels(),
-	// 		array_layers_base: 0,
-	// 		array_layers: image.size().array_layers()
-	// 	}
-	// }
+	/// Returns the range covering all mipmap levels and array layers of an image with `size`.
+	pub const fn whole_image(size: ImageSize, aspect_mask: vk::ImageAspectFlags) -> Self {
+		ImageSubresourceRange {
+			aspect_mask,
+			mipmap_levels_base: 0,
+			mipmap_levels: size.mipmap_levels(),
+			array_layers_base: 0,
+			array_layers: size.array_layers()
+		}
+	}
 
 	/// ### Safety
 	///
@@ -530,3 +531,219 @@ vk_builder_wrap! {
 		}
 	}
 }
+
+/// Returns the aspects contained in `format`.
+///
+/// Depth and stencil formats return `DEPTH` and/or `STENCIL`, `UNDEFINED` returns an empty mask and every other format returns `COLOR`.
+pub fn format_aspects(format: vk::Format) -> vk::ImageAspectFlags {
+	match format {
+		vk::Format::UNDEFINED => vk::ImageAspectFlags::empty(),
+		vk::Format::D16_UNORM | vk::Format::X8_D24_UNORM_PACK32 | vk::Format::D32_SFLOAT => vk::ImageAspectFlags::DEPTH,
+		vk::Format::S8_UINT => vk::ImageAspectFlags::STENCIL,
+		vk::Format::D16_UNORM_S8_UINT | vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT => {
+			vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+		}
+		_ => vk::ImageAspectFlags::COLOR
+	}
+}
+
+/// Size and extent of a texel block of a format as it is laid out in buffer memory by buffer-image copies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TexelBlock {
+	/// Size of one block in bytes.
+	pub size: u32,
+	/// Width and height of one block in texels, `1` for uncompressed formats.
+	pub extent: [u32; 2]
+}
+impl TexelBlock {
+	/// Returns the texel block of `aspect` of `format` in buffer memory.
+	///
+	/// Depth and stencil aspects of combined depth/stencil formats are copied separately, so `aspect` selects which one is returned.
+	/// Returns `None` for formats this function does not know, including multi-planar formats, and for aspects not present in `format`.
+	pub fn of(format: vk::Format, aspect: vk::ImageAspectFlags) -> Option<Self> {
+		let uncompressed = |size: u32| Some(TexelBlock { size, extent: [1, 1] });
+		let compressed = |size: u32, width: u32, height: u32| Some(TexelBlock { size, extent: [width, height] });
+
+		if aspect != format_aspects(format) {
+			return match (format, aspect) {
+				(vk::Format::D16_UNORM_S8_UINT, vk::ImageAspectFlags::DEPTH) => uncompressed(2),
+				(vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT, vk::ImageAspectFlags::DEPTH) => uncompressed(4),
+				(vk::Format::D16_UNORM_S8_UINT | vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT, vk::ImageAspectFlags::STENCIL) => {
+					uncompressed(1)
+				}
+				_ => None
+			}
+		}
+
+		// Ranges of raw values of the core formats, see the format compatibility classes of the specification
+		match format.as_raw() {
+			// R4G4_UNORM_PACK8
+			1 => uncompressed(1),
+			// R4G4B4A4_UNORM_PACK16 ..= A1R5G5B5_UNORM_PACK16
+			2 ..= 8 => uncompressed(2),
+			// R8_*
+			9 ..= 15 => uncompressed(1),
+			// R8G8_*
+			16 ..= 22 => uncompressed(2),
+			// R8G8B8_* and B8G8R8_*
+			23 ..= 36 => uncompressed(3),
+			// R8G8B8A8_*, B8G8R8A8_*, A8B8G8R8_*_PACK32, A2R10G10B10_*_PACK32 and A2B10G10R10_*_PACK32
+			37 ..= 69 => uncompressed(4),
+			// R16_*
+			70 ..= 76 => uncompressed(2),
+			// R16G16_*
+			77 ..= 83 => uncompressed(4),
+			// R16G16B16_*
+			84 ..= 90 => uncompressed(6),
+			// R16G16B16A16_*
+			91 ..= 97 => uncompressed(8),
+			// R32_*
+			98 ..= 100 => uncompressed(4),
+			// R32G32_*
+			101 ..= 103 => uncompressed(8),
+			// R32G32B32_*
+			104 ..= 106 => uncompressed(12),
+			// R32G32B32A32_*
+			107 ..= 109 => uncompressed(16),
+			// R64_*
+			110 ..= 112 => uncompressed(8),
+			// R64G64_*
+			113 ..= 115 => uncompressed(16),
+			// R64G64B64_*
+			116 ..= 118 => uncompressed(24),
+			// R64G64B64A64_*
+			119 ..= 121 => uncompressed(32),
+			// B10G11R11_UFLOAT_PACK32 and E5B9G9R9_UFLOAT_PACK32
+			122 ..= 123 => uncompressed(4),
+			// D16_UNORM
+			124 => uncompressed(2),
+			// X8_D24_UNORM_PACK32 and D32_SFLOAT
+			125 ..= 126 => uncompressed(4),
+			// S8_UINT
+			127 => uncompressed(1),
+			// BC1_*
+			131 ..= 134 => compressed(8, 4, 4),
+			// BC2_* and BC3_*
+			135 ..= 138 => compressed(16, 4, 4),
+			// BC4_*
+			139 ..= 140 => compressed(8, 4, 4),
+			// BC5_*, BC6H_* and BC7_*
+			141 ..= 146 => compressed(16, 4, 4),
+			// ETC2_R8G8B8_* and ETC2_R8G8B8A1_*
+			147 ..= 150 => compressed(8, 4, 4),
+			// ETC2_R8G8B8A8_*
+			151 ..= 152 => compressed(16, 4, 4),
+			// EAC_R11_*
+			153 ..= 154 => compressed(8, 4, 4),
+			// EAC_R11G11_*
+			155 ..= 156 => compressed(16, 4, 4),
+			// ASTC_*, each block size has an UNORM and an SRGB variant
+			157 ..= 184 => {
+				const ASTC_EXTENTS: [[u32; 2]; 14] = [
+					[4, 4],
+					[5, 4],
+					[5, 5],
+					[6, 5],
+					[6, 6],
+					[8, 5],
+					[8, 6],
+					[8, 8],
+					[10, 5],
+					[10, 6],
+					[10, 8],
+					[10, 10],
+					[12, 10],
+					[12, 12]
+				];
+				let [width, height] = ASTC_EXTENTS[(format.as_raw() - 157) as usize / 2];
+
+				compressed(16, width, height)
+			}
+			_ => None
+		}
+	}
+
+	/// Returns the size in bytes of tightly packed data covering `extent` in each of `array_layers` layers, or `None` on overflow.
+	pub fn packed_size(&self, extent: vk::Extent3D, array_layers: u32) -> Option<u64> {
+		let blocks_x = extent.width.div_ceil(self.extent[0]) as u64;
+		let blocks_y = extent.height.div_ceil(self.extent[1]) as u64;
+
+		(self.size as u64)
+			.checked_mul(blocks_x)?
+			.checked_mul(blocks_y)?
+			.checked_mul(extent.depth as u64)?
+			.checked_mul(array_layers as u64)
+	}
+}
+
+/// Checks that `source_size` bytes of tightly packed data are enough for a buffer-image copy of `extent` in `array_layers` layers
+/// of `aspect` of an image with `format`.
+pub fn validate_packed_copy_source(
+	source_size: u64,
+	format: vk::Format,
+	aspect: vk::ImageAspectFlags,
+	extent: vk::Extent3D,
+	array_layers: u32
+) -> Result<(), super::error::ImageCopySourceError> {
+	let block = TexelBlock::of(format, aspect).ok_or(super::error::ImageCopySourceError::UnknownTexelBlock { format, aspect })?;
+	let required = block.packed_size(extent, array_layers).unwrap_or(u64::MAX);
+
+	if source_size < required {
+		return Err(super::error::ImageCopySourceError::TooSmall { size: source_size, required })
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{validate_packed_copy_source, TexelBlock};
+	use crate::resource::image::error::ImageCopySourceError;
+
+	#[test]
+	fn texel_blocks() {
+		let block = |format| TexelBlock::of(format, super::format_aspects(format)).map(|block| (block.size, block.extent));
+
+		assert_eq!(block(vk::Format::R8_UNORM), Some((1, [1, 1])));
+		assert_eq!(block(vk::Format::R8G8B8_SRGB), Some((3, [1, 1])));
+		assert_eq!(block(vk::Format::B8G8R8A8_SRGB), Some((4, [1, 1])));
+		assert_eq!(block(vk::Format::A2B10G10R10_SINT_PACK32), Some((4, [1, 1])));
+		assert_eq!(block(vk::Format::R16G16B16A16_SFLOAT), Some((8, [1, 1])));
+		assert_eq!(block(vk::Format::R64G64B64A64_SFLOAT), Some((32, [1, 1])));
+		assert_eq!(block(vk::Format::E5B9G9R9_UFLOAT_PACK32), Some((4, [1, 1])));
+		assert_eq!(block(vk::Format::D32_SFLOAT), Some((4, [1, 1])));
+		assert_eq!(block(vk::Format::BC1_RGBA_SRGB_BLOCK), Some((8, [4, 4])));
+		assert_eq!(block(vk::Format::BC7_SRGB_BLOCK), Some((16, [4, 4])));
+		assert_eq!(block(vk::Format::EAC_R11G11_SNORM_BLOCK), Some((16, [4, 4])));
+		assert_eq!(block(vk::Format::ASTC_4X4_UNORM_BLOCK), Some((16, [4, 4])));
+		assert_eq!(block(vk::Format::ASTC_10X6_SRGB_BLOCK), Some((16, [10, 6])));
+		assert_eq!(block(vk::Format::ASTC_12X12_SRGB_BLOCK), Some((16, [12, 12])));
+		assert_eq!(block(vk::Format::G8_B8R8_2PLANE_420_UNORM), None);
+
+		let depth_stencil = |aspect| TexelBlock::of(vk::Format::D24_UNORM_S8_UINT, aspect).map(|block| block.size);
+		assert_eq!(depth_stencil(vk::ImageAspectFlags::DEPTH), Some(4));
+		assert_eq!(depth_stencil(vk::ImageAspectFlags::STENCIL), Some(1));
+		assert_eq!(depth_stencil(vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL), None);
+		assert_eq!(TexelBlock::of(vk::Format::R8_UNORM, vk::ImageAspectFlags::DEPTH), None);
+	}
+
+	#[test]
+	fn packed_copy_source_size() {
+		let extent = vk::Extent3D { width: 10, height: 6, depth: 1 };
+
+		assert!(validate_packed_copy_source(10 * 6 * 4 * 2, vk::Format::R8G8B8A8_UNORM, vk::ImageAspectFlags::COLOR, extent, 2).is_ok());
+		match validate_packed_copy_source(10 * 6 * 4, vk::Format::R8G8B8A8_UNORM, vk::ImageAspectFlags::COLOR, extent, 2) {
+			Err(ImageCopySourceError::TooSmall { size: 240, required: 480 }) => (),
+			other => panic!("unexpected result {:?}", other)
+		}
+
+		// partial blocks are rounded up to whole 4x4 blocks
+		assert!(validate_packed_copy_source(3 * 2 * 8, vk::Format::BC1_RGB_UNORM_BLOCK, vk::ImageAspectFlags::COLOR, extent, 1).is_ok());
+		assert!(validate_packed_copy_source(3 * 2 * 8 - 1, vk::Format::BC1_RGB_UNORM_BLOCK, vk::ImageAspectFlags::COLOR, extent, 1).is_err());
+
+		let huge = vk::Extent3D { width: u32::MAX, height: u32::MAX, depth: u32::MAX };
+		assert!(validate_packed_copy_source(u64::MAX - 1, vk::Format::R32G32B32A32_SFLOAT, vk::ImageAspectFlags::COLOR, huge, 4).is_err());
+	}
+}
@@ -50,6 +50,81 @@ impl ImageView {
 		}
 	}
 
+	/// Creates a view of the depth aspect of `image` for sampling.
+	///
+	/// Depth/stencil images can only be sampled through views with a single aspect selected,
+	/// which `new` with `format_aspects` of a combined format does not provide.
+	pub fn depth_view(
+		image: super::MixedDynImage,
+		view_range: super::params::ImageViewRange,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, super::error::ImageViewError> {
+		Self::sampled_aspect_view(
+			image,
+			view_range,
+			vk::ImageAspectFlags::DEPTH,
+			host_memory_allocator
+		)
+	}
+
+	/// Creates a view of the stencil aspect of `image` for sampling.
+	///
+	/// The stencil values are read from the red component as unsigned integers.
+	pub fn stencil_view(
+		image: super::MixedDynImage,
+		view_range: super::params::ImageViewRange,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, super::error::ImageViewError> {
+		Self::sampled_aspect_view(
+			image,
+			view_range,
+			vk::ImageAspectFlags::STENCIL,
+			host_memory_allocator
+		)
+	}
+
+	fn sampled_aspect_view(
+		image: super::MixedDynImage,
+		view_range: super::params::ImageViewRange,
+		aspect: vk::ImageAspectFlags,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, super::error::ImageViewError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		Self::validate_sampled_aspect(&image, aspect)?;
+
+		Self::new(
+			image,
+			view_range,
+			None,
+			vk::ComponentMapping::default(),
+			aspect,
+			host_memory_allocator
+		)
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn validate_sampled_aspect(image: &super::MixedDynImage, aspect: vk::ImageAspectFlags) -> Result<(), super::error::ImageViewError> {
+		if !super::params::format_aspects(image.format()).contains(aspect) {
+			return Err(super::error::ImageViewError::AspectNotPresent(aspect))
+		}
+
+		if !image.usage().contains(vk::ImageUsageFlags::SAMPLED) {
+			return Err(super::error::ImageViewError::SampledUsageMissing)
+		}
+
+		if !image
+			.device()
+			.physical_device()
+			.format_properties(image.format())
+			.optimal_tiling_features
+			.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE)
+		{
+			return Err(super::error::ImageViewError::FormatNotSampleable(image.format()))
+		}
+
+		Ok(())
+	}
+
 	/// Creates a new `ImageView` from create info.
 	///
 	/// ### Safety
@@ -94,6 +169,7 @@ impl ImageView {
 			)
 		};
 
+		image.device().child_registry().register(view);
 		Ok(Vrc::new(ImageView {
 			image,
 			view,
@@ -136,6 +212,7 @@ impl_common_handle_traits! {
 impl Drop for ImageView {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.image.device().child_registry().unregister(self.view);
 
 		unsafe {
 			self.image.device().destroy_image_view(
@@ -0,0 +1,291 @@
+//! Runtime compilation of GLSL shaders to SPIR-V using shaderc.
+//!
+//! Compiling at runtime is meant for development, for example when iterating on shaders without a separate build step.
+//! Includes are resolved relative to the including file and then in `CompileOptions::include_paths`.
+//! Compilation errors are reported as a list of diagnostics with the file and line they refer to.
+
+use std::{
+	cell::RefCell,
+	fmt,
+	path::{Path, PathBuf}
+};
+
+use ash::vk;
+use thiserror::Error;
+
+use super::{error::ShaderError, ShaderModule};
+use crate::prelude::{Device, HostMemoryAllocator, Vrc};
+
+/// Diagnostic reported by the compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileDiagnostic {
+	/// Name of the file the diagnostic refers to, if known.
+	pub file: Option<String>,
+	/// Line number in `file`, starting at 1.
+	pub line: Option<u32>,
+	pub message: String
+}
+impl CompileDiagnostic {
+	/// Parses diagnostics in the `file:line: error: message` format used by the compiler.
+	///
+	/// Lines not in this format are ignored. If no line matches, the whole `log` is returned as one diagnostic.
+	pub fn parse_log(log: &str) -> Vec<Self> {
+		let mut diagnostics: Vec<_> = log
+			.lines()
+			.filter_map(|line| {
+				let (location, message) = line.split_once(": error: ")?;
+
+				let diagnostic = match location.rsplit_once(':').map(|(file, line)| (file, line.trim().parse::<u32>())) {
+					Some((file, Ok(line))) => CompileDiagnostic {
+						file: Some(file.to_string()),
+						line: Some(line),
+						message: message.trim().to_string()
+					},
+					_ => CompileDiagnostic { file: Some(location.to_string()), line: None, message: message.trim().to_string() }
+				};
+
+				Some(diagnostic)
+			})
+			.collect();
+
+		if diagnostics.is_empty() {
+			diagnostics.push(CompileDiagnostic { file: None, line: None, message: log.trim().to_string() });
+		}
+
+		diagnostics
+	}
+}
+impl fmt::Display for CompileDiagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match (&self.file, self.line) {
+			(Some(file), Some(line)) => write!(f, "{}:{}: {}", file, line, self.message),
+			(Some(file), None) => write!(f, "{}: {}", file, self.message),
+			_ => write!(f, "{}", self.message)
+		}
+	}
+}
+
+fn format_diagnostics(diagnostics: &[CompileDiagnostic]) -> String {
+	diagnostics.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+#[derive(Error, Debug)]
+pub enum ShaderCompileError {
+	#[error("Could not initialize the shader compiler")]
+	CompilerInit,
+	#[error("Shader stage {0:?} is not supported by the compiler")]
+	UnsupportedStage(vk::ShaderStageFlags),
+	#[error("Shader compilation failed:\n{}", format_diagnostics(.0))]
+	Compile(Vec<CompileDiagnostic>),
+	#[error("Shader compiler error: {0}")]
+	Internal(String),
+	#[error("Could not create shader module")]
+	Shader(#[from] ShaderError)
+}
+
+/// Options of GLSL compilation.
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+	/// Name of the source used in diagnostics and to resolve relative includes.
+	pub file_name: String,
+	pub entry_point: String,
+	/// Directories searched for included files.
+	pub include_paths: Vec<PathBuf>,
+	/// Preprocessor macro definitions with optional values.
+	pub definitions: Vec<(String, Option<String>)>,
+	pub optimize: bool,
+	pub debug_info: bool
+}
+impl Default for CompileOptions {
+	fn default() -> Self {
+		CompileOptions {
+			file_name: "shader.glsl".to_string(),
+			entry_point: "main".to_string(),
+			include_paths: Vec::new(),
+			definitions: Vec::new(),
+			optimize: false,
+			debug_info: cfg!(debug_assertions)
+		}
+	}
+}
+
+fn shader_kind(stage: vk::ShaderStageFlags) -> Option<shaderc::ShaderKind> {
+	let kind = match stage {
+		vk::ShaderStageFlags::VERTEX => shaderc::ShaderKind::Vertex,
+		vk::ShaderStageFlags::TESSELLATION_CONTROL => shaderc::ShaderKind::TessControl,
+		vk::ShaderStageFlags::TESSELLATION_EVALUATION => shaderc::ShaderKind::TessEvaluation,
+		vk::ShaderStageFlags::GEOMETRY => shaderc::ShaderKind::Geometry,
+		vk::ShaderStageFlags::FRAGMENT => shaderc::ShaderKind::Fragment,
+		vk::ShaderStageFlags::COMPUTE => shaderc::ShaderKind::Compute,
+		vk::ShaderStageFlags::RAYGEN_KHR => shaderc::ShaderKind::RayGeneration,
+		vk::ShaderStageFlags::ANY_HIT_KHR => shaderc::ShaderKind::AnyHit,
+		vk::ShaderStageFlags::CLOSEST_HIT_KHR => shaderc::ShaderKind::ClosestHit,
+		vk::ShaderStageFlags::MISS_KHR => shaderc::ShaderKind::Miss,
+		vk::ShaderStageFlags::INTERSECTION_KHR => shaderc::ShaderKind::Intersection,
+		vk::ShaderStageFlags::CALLABLE_KHR => shaderc::ShaderKind::Callable,
+		vk::ShaderStageFlags::TASK_NV => shaderc::ShaderKind::Task,
+		vk::ShaderStageFlags::MESH_NV => shaderc::ShaderKind::Mesh,
+		_ => return None
+	};
+
+	Some(kind)
+}
+
+fn resolve_include(
+	include_paths: &[PathBuf],
+	requested: &str,
+	include_type: shaderc::IncludeType,
+	requesting: &str
+) -> Result<shaderc::ResolvedInclude, String> {
+	let relative_dir = match include_type {
+		shaderc::IncludeType::Relative => Path::new(requesting).parent(),
+		shaderc::IncludeType::Standard => None
+	};
+
+	for directory in relative_dir.into_iter().chain(include_paths.iter().map(PathBuf::as_path)) {
+		let path = directory.join(requested);
+		if path.is_file() {
+			let content = std::fs::read_to_string(&path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+
+			return Ok(shaderc::ResolvedInclude { resolved_name: path.to_string_lossy().into_owned(), content })
+		}
+	}
+
+	Err(format!(
+		"Could not find \"{}\" included from {}",
+		requested, requesting
+	))
+}
+
+/// SPIR-V code compiled by `compile_glsl_with_includes` together with the files it included.
+#[derive(Debug, Clone)]
+pub struct CompiledGlsl {
+	pub code: Vec<u32>,
+	/// Paths of all files resolved for `#include` directives, including nested ones, in the order they were first included.
+	pub includes: Vec<PathBuf>
+}
+
+/// Compiles GLSL `source` of `stage` to SPIR-V.
+///
+/// Warnings are logged.
+pub fn compile_glsl(source: &str, stage: vk::ShaderStageFlags, options: &CompileOptions) -> Result<Vec<u32>, ShaderCompileError> {
+	compile_glsl_with_includes(source, stage, options).map(|compiled| compiled.code)
+}
+
+/// Compiles GLSL `source` of `stage` to SPIR-V and records the files resolved for its includes.
+///
+/// Warnings are logged.
+pub fn compile_glsl_with_includes(
+	source: &str,
+	stage: vk::ShaderStageFlags,
+	options: &CompileOptions
+) -> Result<CompiledGlsl, ShaderCompileError> {
+	let includes = RefCell::new(Vec::new());
+	let kind = shader_kind(stage).ok_or(ShaderCompileError::UnsupportedStage(stage))?;
+
+	let compiler = shaderc::Compiler::new().ok_or(ShaderCompileError::CompilerInit)?;
+	let mut compile_options = shaderc::CompileOptions::new().ok_or(ShaderCompileError::CompilerInit)?;
+
+	#[cfg(feature = "vulkan1_2")]
+	let env_version = shaderc::EnvVersion::Vulkan1_2;
+	#[cfg(all(feature = "vulkan1_1", not(feature = "vulkan1_2")))]
+	let env_version = shaderc::EnvVersion::Vulkan1_1;
+	#[cfg(not(feature = "vulkan1_1"))]
+	let env_version = shaderc::EnvVersion::Vulkan1_0;
+	compile_options.set_target_env(shaderc::TargetEnv::Vulkan, env_version as u32);
+
+	for (name, value) in options.definitions.iter() {
+		compile_options.add_macro_definition(name, value.as_deref());
+	}
+	if options.optimize {
+		compile_options.set_optimization_level(shaderc::OptimizationLevel::Performance);
+	}
+	if options.debug_info {
+		compile_options.set_generate_debug_info();
+	}
+	let include_paths = options.include_paths.as_slice();
+	let includes_ref = &includes;
+	compile_options.set_include_callback(move |requested, include_type, requesting, _depth| {
+		let resolved = resolve_include(include_paths, requested, include_type, requesting)?;
+
+		let path = PathBuf::from(&resolved.resolved_name);
+		let mut includes = includes_ref.borrow_mut();
+		if !includes.contains(&path) {
+			includes.push(path);
+		}
+
+		Ok(resolved)
+	});
+
+	log::trace!(
+		"Compiling {:?} shader {}",
+		stage,
+		options.file_name
+	);
+	let artifact = compiler
+		.compile_into_spirv(
+			source,
+			kind,
+			&options.file_name,
+			&options.entry_point,
+			Some(&compile_options)
+		)
+		.map_err(|err| match err {
+			shaderc::Error::CompilationError(_, log) => ShaderCompileError::Compile(CompileDiagnostic::parse_log(&log)),
+			shaderc::Error::InternalError(message)
+			| shaderc::Error::InvalidStage(message)
+			| shaderc::Error::InvalidAssembly(message)
+			| shaderc::Error::NullResultObject(message) => ShaderCompileError::Internal(message)
+		})?;
+
+	if artifact.get_num_warnings() > 0 {
+		log::warn!(
+			"Shader {} compiled with warnings:\n{}",
+			options.file_name,
+			artifact.get_warning_messages()
+		);
+	}
+
+	Ok(CompiledGlsl { code: artifact.as_binary().to_vec(), includes: includes.take() })
+}
+
+impl ShaderModule {
+	/// Compiles GLSL `source` of `stage` and creates a shader module from it.
+	pub fn from_glsl(
+		device: Vrc<Device>,
+		source: &str,
+		stage: vk::ShaderStageFlags,
+		options: &CompileOptions,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, ShaderCompileError> {
+		let code = compile_glsl(source, stage, options)?;
+
+		ShaderModule::new(device, code, host_memory_allocator).map_err(ShaderCompileError::from)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::CompileDiagnostic;
+
+	#[test]
+	fn parse_compile_log() {
+		let log = "shader.glsl:12: error: 'color' : undeclared identifier\nlib/common.glsl: error: '#version' : must occur first\n2 errors generated.\n";
+
+		assert_eq!(
+			CompileDiagnostic::parse_log(log),
+			vec![
+				CompileDiagnostic {
+					file: Some("shader.glsl".to_string()),
+					line: Some(12),
+					message: "'color' : undeclared identifier".to_string()
+				},
+				CompileDiagnostic {
+					file: Some("lib/common.glsl".to_string()),
+					line: None,
+					message: "'#version' : must occur first".to_string()
+				}
+			]
+		);
+	}
+}
@@ -8,3 +8,22 @@ vk_result_error! {
 		}
 	}
 }
+
+#[cfg(feature = "shader_object")]
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum ShaderObjectError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_INITIALIZATION_FAILED
+		}
+
+		#[error("At least one shader stage must be specified")]
+		StagesEmpty,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("VK_EXT_shader_object must be enabled on the device")]
+		ExtensionNotEnabled,
+	}
+}
@@ -0,0 +1,285 @@
+//! Recreation of shader modules when their source files change.
+//!
+//! `ShaderWatcher` polls the modification times of watched files. When a file changes, a new `ShaderModule` is created
+//! from it and the generation of the corresponding `HotShader` is incremented. Applications can either compare
+//! generations to find pipelines that need to be rebuilt or register a callback using `ShaderWatcher::on_reload`.
+//!
+//! GLSL shaders are also reloaded when any of the files they `#include` change.
+//!
+//! When a reload fails, for example because the file contains a syntax error, the previous module stays in use.
+
+use std::{
+	fmt,
+	io,
+	path::{Path, PathBuf},
+	time::SystemTime
+};
+
+use thiserror::Error;
+
+use super::{error::ShaderError, ShaderModule};
+use crate::{
+	prelude::{Device, HostMemoryAllocator, Vrc, Vutex, Vweak},
+	util::sync::VutexGuard
+};
+
+#[derive(Error, Debug)]
+pub enum HotReloadError {
+	#[error("Could not read shader file")]
+	Io(#[from] io::Error),
+	#[error("Could not create shader module")]
+	Shader(#[from] ShaderError),
+	#[cfg(feature = "shader_compile")]
+	#[error("Could not compile shader")]
+	Compile(#[from] super::compile::ShaderCompileError)
+}
+
+/// Format of a watched shader file.
+#[derive(Debug, Clone)]
+pub enum ShaderSource {
+	/// SPIR-V binary.
+	Spirv,
+	/// GLSL source compiled for `stage` using `options`.
+	///
+	/// The file name in `options` is replaced by the path of the watched file so that relative includes are resolved correctly.
+	#[cfg(feature = "shader_compile")]
+	Glsl { stage: ash::vk::ShaderStageFlags, options: super::compile::CompileOptions }
+}
+
+struct HotShaderState {
+	module: Vrc<ShaderModule>,
+	generation: u64,
+	modified: Option<SystemTime>,
+	/// Files included by the last successfully compiled source with their modification times.
+	includes: Vec<(PathBuf, Option<SystemTime>)>
+}
+
+/// Shader module that is recreated when its source file changes.
+pub struct HotShader {
+	path: PathBuf,
+	source: ShaderSource,
+	state: Vutex<HotShaderState>
+}
+impl HotShader {
+	fn lock_state(&self) -> VutexGuard<'_, HotShaderState> {
+		self.state.lock().expect("vutex poisoned")
+	}
+
+	/// Returns the current module.
+	pub fn module(&self) -> Vrc<ShaderModule> {
+		self.lock_state().module.clone()
+	}
+
+	/// Returns the number of successful reloads.
+	///
+	/// Pipelines created with a module of an older generation should be rebuilt.
+	pub fn generation(&self) -> u64 {
+		self.lock_state().generation
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub const fn source(&self) -> &ShaderSource {
+		&self.source
+	}
+
+	/// Returns the files included by the current module, which are watched together with `path`.
+	///
+	/// This is always empty for SPIR-V sources.
+	pub fn includes(&self) -> Vec<PathBuf> {
+		self.lock_state().includes.iter().map(|(path, _)| path.clone()).collect()
+	}
+}
+impl fmt::Debug for HotShader {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let state = self.lock_state();
+
+		f.debug_struct("HotShader")
+			.field("path", &self.path)
+			.field("source", &self.source)
+			.field("module", &state.module)
+			.field("generation", &state.generation)
+			.field("includes", &state.includes)
+			.finish()
+	}
+}
+
+/// Result of reloading one shader during `ShaderWatcher::poll`.
+#[derive(Debug)]
+pub struct ReloadEvent {
+	pub shader: Vrc<HotShader>,
+	/// On error, the previous module stays in use until the file changes again.
+	pub result: Result<(), HotReloadError>
+}
+
+/// Watches shader files and recreates their modules when they change.
+///
+/// Watched shaders are only referenced weakly, a shader stops being watched once all `Vrc<HotShader>`s are dropped.
+pub struct ShaderWatcher {
+	device: Vrc<Device>,
+	host_memory_allocator: HostMemoryAllocator,
+
+	shaders: Vutex<Vec<Vweak<HotShader>>>,
+	callbacks: Vutex<Vec<Box<VSendSync![dyn FnMut(&HotShader)]>>>
+}
+impl ShaderWatcher {
+	pub fn new(device: Vrc<Device>, host_memory_allocator: HostMemoryAllocator) -> Self {
+		ShaderWatcher { device, host_memory_allocator, shaders: Vutex::new(Vec::new()), callbacks: Vutex::new(Vec::new()) }
+	}
+
+	/// Creates a module from the file at `path` and starts watching it.
+	pub fn watch(&self, path: impl Into<PathBuf>, source: ShaderSource) -> Result<Vrc<HotShader>, HotReloadError> {
+		let path = path.into();
+
+		let modified = Self::modified(&path);
+		let (module, includes) = self.load(&path, &source)?;
+
+		let shader = Vrc::new(HotShader {
+			path,
+			source,
+			state: Vutex::new(HotShaderState { module, generation: 0, modified, includes: Self::stamp_includes(includes) })
+		});
+		self.shaders.lock().expect("vutex poisoned").push(Vrc::downgrade(&shader));
+
+		Ok(shader)
+	}
+
+	/// Registers `callback` to be called with each successfully reloaded shader during `poll`.
+	pub fn on_reload(&self, callback: Box<VSendSync![dyn FnMut(&HotShader)]>) {
+		self.callbacks.lock().expect("vutex poisoned").push(callback);
+	}
+
+	/// Reloads all watched shaders whose files or included files were modified since they were last loaded.
+	///
+	/// This should be called periodically, for example once per frame.
+	pub fn poll(&self) -> Vec<ReloadEvent> {
+		let shaders: Vec<Vrc<HotShader>> = {
+			let mut shaders = self.shaders.lock().expect("vutex poisoned");
+			shaders.retain(|shader| shader.strong_count() > 0);
+
+			shaders.iter().filter_map(Vweak::upgrade).collect()
+		};
+
+		let mut events = Vec::new();
+		for shader in shaders {
+			let modified = match Self::modified(&shader.path) {
+				Some(modified) => modified,
+				// The file may be missing for a moment while an editor replaces it
+				None => continue
+			};
+			{
+				let state = shader.lock_state();
+				let includes_changed = state.includes.iter().any(|(path, modified)| Self::modified(path) != *modified);
+				if state.modified == Some(modified) && !includes_changed {
+					continue
+				}
+			}
+
+			log::debug!("Reloading shader {}", shader.path.display());
+			let result = self.load(&shader.path, &shader.source).map(|(module, includes)| {
+				let mut state = shader.lock_state();
+				state.module = module;
+				state.generation += 1;
+				state.includes = Self::stamp_includes(includes);
+			});
+			{
+				// On error, the previous includes are kept so that fixing them triggers another reload
+				let mut state = shader.lock_state();
+				state.modified = Some(modified);
+				for (path, modified) in state.includes.iter_mut() {
+					*modified = Self::modified(path);
+				}
+			}
+
+			match result {
+				Ok(()) => {
+					for callback in self.callbacks.lock().expect("vutex poisoned").iter_mut() {
+						callback(&shader);
+					}
+				}
+				Err(ref err) => log::warn!(
+					"Could not reload shader {}: {}",
+					shader.path.display(),
+					err
+				)
+			}
+
+			events.push(ReloadEvent { shader, result });
+		}
+
+		events
+	}
+
+	fn modified(path: &Path) -> Option<SystemTime> {
+		std::fs::metadata(path).and_then(|m| m.modified()).ok()
+	}
+
+	fn stamp_includes(includes: Vec<PathBuf>) -> Vec<(PathBuf, Option<SystemTime>)> {
+		includes
+			.into_iter()
+			.map(|path| {
+				let modified = Self::modified(&path);
+				(path, modified)
+			})
+			.collect()
+	}
+
+	/// Creates a module from the file at `path` and returns it together with the files it included.
+	fn load(&self, path: &Path, source: &ShaderSource) -> Result<(Vrc<ShaderModule>, Vec<PathBuf>), HotReloadError> {
+		let loaded = match source {
+			ShaderSource::Spirv => {
+				let bytes = std::fs::read(path)?;
+				let code = ShaderModule::load_spirv_bytes(&bytes)?;
+
+				let module = ShaderModule::new(
+					self.device.clone(),
+					code,
+					self.host_memory_allocator
+				)?;
+
+				(module, Vec::new())
+			}
+			#[cfg(feature = "shader_compile")]
+			ShaderSource::Glsl { stage, options } => {
+				let source = std::fs::read_to_string(path)?;
+				let options = super::compile::CompileOptions { file_name: path.to_string_lossy().into_owned(), ..options.clone() };
+
+				let compiled = super::compile::compile_glsl_with_includes(&source, *stage, &options)?;
+				let module = ShaderModule::new(
+					self.device.clone(),
+					compiled.code,
+					self.host_memory_allocator
+				)?;
+
+				(module, compiled.includes)
+			}
+		};
+
+		Ok(loaded)
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl fmt::Debug for ShaderWatcher {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ShaderWatcher")
+			.field("device", &self.device)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.field(
+				"shaders",
+				&self.shaders.lock().expect("vutex poisoned").len()
+			)
+			.field(
+				"callbacks",
+				&self.callbacks.lock().expect("vutex poisoned").len()
+			)
+			.finish()
+	}
+}
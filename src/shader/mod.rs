@@ -4,8 +4,15 @@ use ash::vk;
 
 use crate::prelude::{Device, HasHandle, HostMemoryAllocator, Vrc};
 
+#[cfg(feature = "shader_compile")]
+pub mod compile;
 pub mod error;
+pub mod hot_reload;
+#[cfg(feature = "shader_object")]
+pub mod object;
 pub mod params;
+#[cfg(feature = "shader_reflection")]
+pub mod reflect;
 
 pub struct ShaderModule {
 	device: Vrc<Device>,
@@ -52,6 +59,16 @@ impl ShaderModule {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(module);
+		#[cfg(feature = "shader_reflection")]
+		{
+			let code = std::slice::from_raw_parts(create_info.p_code, create_info.code_size / 4);
+			match reflect::ShaderReflection::parse(code) {
+				Ok(reflection) => device.shader_reflections().register(module, Vrc::new(reflection)),
+				Err(err) => log::warn!("Could not reflect shader module: {}", err)
+			}
+		}
+
 		Ok(Vrc::new(ShaderModule {
 			device,
 			module,
@@ -80,6 +97,12 @@ impl ShaderModule {
 	pub const fn device(&self) -> &Vrc<Device> {
 		&self.device
 	}
+
+	/// Returns the reflection of this module, or `None` if the module could not be reflected.
+	#[cfg(feature = "shader_reflection")]
+	pub fn reflection(&self) -> Option<Vrc<reflect::ShaderReflection>> {
+		self.device.shader_reflections().get(self.module)
+	}
 }
 impl_common_handle_traits! {
 	impl HasHandle<vk::ShaderModule>, Deref, Borrow, Eq, Hash, Ord for ShaderModule {
@@ -89,6 +112,9 @@ impl_common_handle_traits! {
 impl Drop for ShaderModule {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.module);
+		#[cfg(feature = "shader_reflection")]
+		self.device.shader_reflections().unregister(self.module);
 
 		unsafe {
 			self.device.destroy_shader_module(
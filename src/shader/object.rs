@@ -0,0 +1,394 @@
+//! Experimental support for shader objects using `VK_EXT_shader_object`.
+//!
+//! Shader objects are created directly from SPIR-V and bound to command buffers per stage, without creating pipelines.
+//! All pipeline state is then dynamic and must be set with the recording lock setters before drawing,
+//! see `CommandBufferRecordingLockCommon::set_shader_object_defaults`.
+//!
+//! ash does not provide definitions for this extension, so the structures and the function table are defined here.
+//! The extension and its `shaderObject` feature must be enabled on the device.
+
+use std::{
+	ffi::CStr,
+	fmt,
+	os::raw::{c_char, c_void}
+};
+
+use ash::vk;
+
+use super::error::ShaderObjectError;
+use crate::prelude::{DescriptorSetLayout, Device, HasHandle, HostMemoryAllocator, PushConstantRange, Transparent, Vrc};
+
+/// Name of the `VK_EXT_shader_object` extension.
+pub fn extension_name() -> &'static CStr {
+	unsafe { CStr::from_bytes_with_nul_unchecked(b"VK_EXT_shader_object\0") }
+}
+
+/// `VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_SHADER_OBJECT_FEATURES_EXT`
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SHADER_OBJECT_FEATURES_EXT: vk::StructureType = vk::StructureType::from_raw(1000482000);
+/// `VK_STRUCTURE_TYPE_SHADER_CREATE_INFO_EXT`
+pub const STRUCTURE_TYPE_SHADER_CREATE_INFO_EXT: vk::StructureType = vk::StructureType::from_raw(1000482002);
+/// `VK_OBJECT_TYPE_SHADER_EXT`
+pub const OBJECT_TYPE_SHADER_EXT: vk::ObjectType = vk::ObjectType::from_raw(1000482000);
+
+/// `VK_SHADER_CREATE_LINK_STAGE_BIT_EXT`
+pub const SHADER_CREATE_LINK_STAGE_BIT_EXT: u32 = 0x1;
+/// `VK_SHADER_CODE_TYPE_SPIRV_EXT`
+pub const SHADER_CODE_TYPE_SPIRV_EXT: i32 = 1;
+
+/// `VkShaderEXT` handle.
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShaderEXT(u64);
+impl vk::Handle for ShaderEXT {
+	const TYPE: vk::ObjectType = OBJECT_TYPE_SHADER_EXT;
+
+	fn as_raw(self) -> u64 {
+		self.0
+	}
+
+	fn from_raw(raw: u64) -> Self {
+		ShaderEXT(raw)
+	}
+}
+
+/// `VkPhysicalDeviceShaderObjectFeaturesEXT`, extends `vk::PhysicalDeviceFeatures2` and `vk::DeviceCreateInfo`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicalDeviceShaderObjectFeaturesEXT {
+	pub s_type: vk::StructureType,
+	pub p_next: *mut c_void,
+	pub shader_object: vk::Bool32
+}
+impl PhysicalDeviceShaderObjectFeaturesEXT {
+	pub fn new(shader_object: bool) -> Self {
+		PhysicalDeviceShaderObjectFeaturesEXT {
+			s_type: STRUCTURE_TYPE_PHYSICAL_DEVICE_SHADER_OBJECT_FEATURES_EXT,
+			p_next: std::ptr::null_mut(),
+			shader_object: shader_object as vk::Bool32
+		}
+	}
+}
+unsafe impl vk::ExtendsPhysicalDeviceFeatures2 for PhysicalDeviceShaderObjectFeaturesEXT {}
+unsafe impl vk::ExtendsDeviceCreateInfo for PhysicalDeviceShaderObjectFeaturesEXT {}
+
+/// `VkShaderCreateInfoEXT`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderCreateInfoEXT {
+	pub s_type: vk::StructureType,
+	pub p_next: *const c_void,
+	pub flags: u32,
+	pub stage: vk::ShaderStageFlags,
+	pub next_stage: vk::ShaderStageFlags,
+	pub code_type: i32,
+	pub code_size: usize,
+	pub p_code: *const c_void,
+	pub p_name: *const c_char,
+	pub set_layout_count: u32,
+	pub p_set_layouts: *const vk::DescriptorSetLayout,
+	pub push_constant_range_count: u32,
+	pub p_push_constant_ranges: *const vk::PushConstantRange,
+	pub p_specialization_info: *const vk::SpecializationInfo
+}
+
+/// `VkColorBlendEquationEXT`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorBlendEquationEXT {
+	pub src_color_blend_factor: vk::BlendFactor,
+	pub dst_color_blend_factor: vk::BlendFactor,
+	pub color_blend_op: vk::BlendOp,
+	pub src_alpha_blend_factor: vk::BlendFactor,
+	pub dst_alpha_blend_factor: vk::BlendFactor,
+	pub alpha_blend_op: vk::BlendOp
+}
+
+macro_rules! function_table {
+	(
+		$( #[$attr: meta] )*
+		pub struct $name: ident {
+			$(
+				$field: ident = $vk_name: literal: fn($( $arg_ty: ty ),*) $( -> $ret: ty )?
+			),+ $(,)?
+		}
+	) => {
+		$( #[$attr] )*
+		#[derive(Clone)]
+		pub struct $name {
+			$(
+				pub $field: unsafe extern "system" fn($( $arg_ty ),*) $( -> $ret )?
+			),+
+		}
+		impl $name {
+			/// Loads the functions using `load`. Functions that cannot be loaded panic when called.
+			pub fn load(mut load: impl FnMut(&CStr) -> *const c_void) -> Self {
+				$name {
+					$(
+						$field: unsafe {
+							unsafe extern "system" fn stub($( _: $arg_ty ),*) $( -> $ret )? {
+								panic!(concat!("Unable to load ", $vk_name))
+							}
+
+							let pointer = load(CStr::from_bytes_with_nul_unchecked(concat!($vk_name, "\0").as_bytes()));
+							if pointer.is_null() {
+								stub
+							} else {
+								std::mem::transmute::<*const c_void, unsafe extern "system" fn($( $arg_ty ),*) $( -> $ret )?>(pointer)
+							}
+						}
+					),+
+				}
+			}
+		}
+		impl fmt::Debug for $name {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				f.debug_struct(stringify!($name)).finish()
+			}
+		}
+	}
+}
+
+function_table! {
+	/// `VK_EXT_shader_object` function table, including the dynamic state setters required when drawing with shader objects.
+	pub struct ShaderObjectFn {
+		create_shaders = "vkCreateShadersEXT": fn(vk::Device, u32, *const ShaderCreateInfoEXT, *const vk::AllocationCallbacks, *mut ShaderEXT) -> vk::Result,
+		destroy_shader = "vkDestroyShaderEXT": fn(vk::Device, ShaderEXT, *const vk::AllocationCallbacks),
+		get_shader_binary_data = "vkGetShaderBinaryDataEXT": fn(vk::Device, ShaderEXT, *mut usize, *mut c_void) -> vk::Result,
+		cmd_bind_shaders = "vkCmdBindShadersEXT": fn(vk::CommandBuffer, u32, *const vk::ShaderStageFlags, *const ShaderEXT),
+
+		cmd_set_viewport_with_count = "vkCmdSetViewportWithCountEXT": fn(vk::CommandBuffer, u32, *const vk::Viewport),
+		cmd_set_scissor_with_count = "vkCmdSetScissorWithCountEXT": fn(vk::CommandBuffer, u32, *const vk::Rect2D),
+		cmd_set_rasterizer_discard_enable = "vkCmdSetRasterizerDiscardEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_primitive_topology = "vkCmdSetPrimitiveTopologyEXT": fn(vk::CommandBuffer, vk::PrimitiveTopology),
+		cmd_set_primitive_restart_enable = "vkCmdSetPrimitiveRestartEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_vertex_input = "vkCmdSetVertexInputEXT": fn(
+			vk::CommandBuffer,
+			u32,
+			*const vk::VertexInputBindingDescription2EXT,
+			u32,
+			*const vk::VertexInputAttributeDescription2EXT
+		),
+		cmd_set_polygon_mode = "vkCmdSetPolygonModeEXT": fn(vk::CommandBuffer, vk::PolygonMode),
+		cmd_set_cull_mode = "vkCmdSetCullModeEXT": fn(vk::CommandBuffer, vk::CullModeFlags),
+		cmd_set_front_face = "vkCmdSetFrontFaceEXT": fn(vk::CommandBuffer, vk::FrontFace),
+		cmd_set_depth_bias_enable = "vkCmdSetDepthBiasEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_rasterization_samples = "vkCmdSetRasterizationSamplesEXT": fn(vk::CommandBuffer, vk::SampleCountFlags),
+		cmd_set_sample_mask = "vkCmdSetSampleMaskEXT": fn(vk::CommandBuffer, vk::SampleCountFlags, *const vk::SampleMask),
+		cmd_set_alpha_to_coverage_enable = "vkCmdSetAlphaToCoverageEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_depth_test_enable = "vkCmdSetDepthTestEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_depth_write_enable = "vkCmdSetDepthWriteEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_depth_compare_op = "vkCmdSetDepthCompareOpEXT": fn(vk::CommandBuffer, vk::CompareOp),
+		cmd_set_depth_bounds_test_enable = "vkCmdSetDepthBoundsTestEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_stencil_test_enable = "vkCmdSetStencilTestEnableEXT": fn(vk::CommandBuffer, vk::Bool32),
+		cmd_set_stencil_op = "vkCmdSetStencilOpEXT": fn(
+			vk::CommandBuffer,
+			vk::StencilFaceFlags,
+			vk::StencilOp,
+			vk::StencilOp,
+			vk::StencilOp,
+			vk::CompareOp
+		),
+		cmd_set_color_blend_enable = "vkCmdSetColorBlendEnableEXT": fn(vk::CommandBuffer, u32, u32, *const vk::Bool32),
+		cmd_set_color_blend_equation = "vkCmdSetColorBlendEquationEXT": fn(vk::CommandBuffer, u32, u32, *const ColorBlendEquationEXT),
+		cmd_set_color_write_mask = "vkCmdSetColorWriteMaskEXT": fn(vk::CommandBuffer, u32, u32, *const vk::ColorComponentFlags)
+	}
+}
+
+/// Parameters of one shader stage passed to `ShaderObject::new` or `ShaderObject::new_linked`.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderObjectStage<'a> {
+	pub stage: vk::ShaderStageFlags,
+	/// Stages that may follow this stage, for example `FRAGMENT` for a vertex shader.
+	pub next_stage: vk::ShaderStageFlags,
+	pub code: &'a [u32],
+	pub entry_name: &'a CStr,
+	pub specialization_info: Option<&'a vk::SpecializationInfo>
+}
+
+/// Shader object of a single stage, see the module documentation.
+pub struct ShaderObject {
+	device: Vrc<Device>,
+	shader: ShaderEXT,
+	stage: vk::ShaderStageFlags,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl ShaderObject {
+	/// Creates an unlinked shader object which can be bound together with any other compatible shader objects.
+	pub fn new(
+		device: Vrc<Device>,
+		stage: ShaderObjectStage,
+		set_layouts: &[&DescriptorSetLayout],
+		push_constant_ranges: &[PushConstantRange],
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vrc<Self>, ShaderObjectError> {
+		let mut shaders = Self::create(
+			device,
+			&[stage],
+			0,
+			set_layouts,
+			push_constant_ranges,
+			host_memory_allocator
+		)?;
+
+		Ok(shaders.pop().unwrap())
+	}
+
+	/// Creates linked shader objects, one for each of `stages`, which must all be bound together.
+	///
+	/// Linking allows the implementation to optimize across the stages, similarly to a pipeline.
+	pub fn new_linked(
+		device: Vrc<Device>,
+		stages: &[ShaderObjectStage],
+		set_layouts: &[&DescriptorSetLayout],
+		push_constant_ranges: &[PushConstantRange],
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vec<Vrc<Self>>, ShaderObjectError> {
+		Self::create(
+			device,
+			stages,
+			SHADER_CREATE_LINK_STAGE_BIT_EXT,
+			set_layouts,
+			push_constant_ranges,
+			host_memory_allocator
+		)
+	}
+
+	fn create(
+		device: Vrc<Device>,
+		stages: &[ShaderObjectStage],
+		flags: u32,
+		set_layouts: &[&DescriptorSetLayout],
+		push_constant_ranges: &[PushConstantRange],
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Vec<Vrc<Self>>, ShaderObjectError> {
+		if stages.is_empty() {
+			return Err(ShaderObjectError::StagesEmpty)
+		}
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if !device.is_extension_enabled(extension_name()) {
+				return Err(ShaderObjectError::ExtensionNotEnabled)
+			}
+		}
+
+		let set_layouts: Vec<_> = set_layouts.iter().map(|layout| layout.handle()).collect();
+		let push_constant_ranges: &[vk::PushConstantRange] = Transparent::transmute_slice_twice(push_constant_ranges);
+		let create_infos: Vec<_> = stages
+			.iter()
+			.map(|stage| ShaderCreateInfoEXT {
+				s_type: STRUCTURE_TYPE_SHADER_CREATE_INFO_EXT,
+				p_next: std::ptr::null(),
+				flags,
+				stage: stage.stage,
+				next_stage: stage.next_stage,
+				code_type: SHADER_CODE_TYPE_SPIRV_EXT,
+				code_size: std::mem::size_of_val(stage.code),
+				p_code: stage.code.as_ptr() as *const c_void,
+				p_name: stage.entry_name.as_ptr(),
+				set_layout_count: set_layouts.len() as u32,
+				p_set_layouts: set_layouts.as_ptr(),
+				push_constant_range_count: push_constant_ranges.len() as u32,
+				p_push_constant_ranges: push_constant_ranges.as_ptr(),
+				p_specialization_info: stage.specialization_info.map(|info| info as *const _).unwrap_or(std::ptr::null())
+			})
+			.collect();
+
+		log_trace_common!(
+			"Creating shader objects:",
+			device,
+			create_infos,
+			host_memory_allocator
+		);
+		let mut shaders = vec![ShaderEXT::default(); create_infos.len()];
+		unsafe {
+			let loader = device.ext().shader_object();
+			(loader.create_shaders)(
+				device.handle(),
+				create_infos.len() as u32,
+				create_infos.as_ptr(),
+				host_memory_allocator.as_ref().map(|a| a as *const _).unwrap_or(std::ptr::null()),
+				shaders.as_mut_ptr()
+			)
+			.result()?;
+		}
+
+		Ok(shaders
+			.into_iter()
+			.zip(stages.iter())
+			.map(|(shader, stage)| {
+				device.child_registry().register(shader);
+
+				Vrc::new(ShaderObject { device: device.clone(), shader, stage: stage.stage, host_memory_allocator })
+			})
+			.collect())
+	}
+
+	/// Returns the implementation binary of this shader, which can be cached and used to create the shader again on the same device.
+	pub fn binary_data(&self) -> Result<Vec<u8>, ShaderObjectError> {
+		let loader = self.device.ext().shader_object();
+
+		unsafe {
+			let mut size = 0;
+			(loader.get_shader_binary_data)(
+				self.device.handle(),
+				self.shader,
+				&mut size,
+				std::ptr::null_mut()
+			)
+			.result()?;
+
+			let mut data = vec![0u8; size];
+			(loader.get_shader_binary_data)(
+				self.device.handle(),
+				self.shader,
+				&mut size,
+				data.as_mut_ptr() as *mut c_void
+			)
+			.result()?;
+			data.truncate(size);
+
+			Ok(data)
+		}
+	}
+
+	pub const fn stage(&self) -> vk::ShaderStageFlags {
+		self.stage
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl_common_handle_traits! {
+	impl HasHandle<ShaderEXT>, Deref, Borrow, Eq, Hash, Ord for ShaderObject {
+		target = { shader }
+	}
+}
+impl Drop for ShaderObject {
+	fn drop(&mut self) {
+		log_trace_common!("Dropping", self);
+		self.device.child_registry().unregister(self.shader);
+
+		unsafe {
+			(self.device.ext().shader_object().destroy_shader)(
+				self.device.handle(),
+				self.shader,
+				self.host_memory_allocator.as_ref().map(|a| a as *const _).unwrap_or(std::ptr::null())
+			)
+		}
+	}
+}
+impl fmt::Debug for ShaderObject {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ShaderObject")
+			.field("device", &self.device)
+			.field("shader", &self.safe_handle())
+			.field("stage", &self.stage)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
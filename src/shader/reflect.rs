@@ -0,0 +1,1008 @@
+//! Reflection of SPIR-V shader modules.
+//!
+//! A small built-in SPIR-V parser extracts the entry points of a module together with their input and output
+//! interface variables. Created shader modules register their reflection with the device so that graphics pipelines
+//! can validate the interfaces between their stages and against the vertex input state.
+//!
+//! Descriptor bindings and the push constant block are reflected for the whole module, so that descriptor set layouts,
+//! push constant ranges and vertex input attributes can be generated from the shaders instead of being written by hand.
+
+use std::{ffi::CStr, fmt, num::NonZeroU32, ops::Range};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+	descriptor::layout::{DescriptorSetLayoutBindingDefinition, DescriptorSetLayoutDefinition},
+	prelude::PushConstantRange,
+	util::{hash::VHashMap, sync::Vutex}
+};
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+const OP_NAME: u16 = 5;
+const OP_ENTRY_POINT: u16 = 15;
+const OP_TYPE_BOOL: u16 = 20;
+const OP_TYPE_INT: u16 = 21;
+const OP_TYPE_FLOAT: u16 = 22;
+const OP_TYPE_VECTOR: u16 = 23;
+const OP_TYPE_MATRIX: u16 = 24;
+const OP_TYPE_IMAGE: u16 = 25;
+const OP_TYPE_SAMPLER: u16 = 26;
+const OP_TYPE_SAMPLED_IMAGE: u16 = 27;
+const OP_TYPE_ARRAY: u16 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u16 = 29;
+const OP_TYPE_STRUCT: u16 = 30;
+const OP_TYPE_POINTER: u16 = 32;
+const OP_CONSTANT: u16 = 43;
+const OP_VARIABLE: u16 = 59;
+const OP_DECORATE: u16 = 71;
+const OP_MEMBER_DECORATE: u16 = 72;
+const OP_TYPE_ACCELERATION_STRUCTURE: u16 = 5341;
+
+const DECORATION_BUFFER_BLOCK: u32 = 3;
+const DECORATION_ARRAY_STRIDE: u32 = 6;
+const DECORATION_MATRIX_STRIDE: u32 = 7;
+const DECORATION_BUILT_IN: u32 = 11;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_COMPONENT: u32 = 31;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const STORAGE_CLASS_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_CLASS_INPUT: u32 = 1;
+const STORAGE_CLASS_UNIFORM: u32 = 2;
+const STORAGE_CLASS_OUTPUT: u32 = 3;
+const STORAGE_CLASS_PUSH_CONSTANT: u32 = 9;
+const STORAGE_CLASS_STORAGE_BUFFER: u32 = 12;
+
+const DIM_BUFFER: u32 = 5;
+const DIM_SUBPASS_DATA: u32 = 6;
+
+#[derive(Error, Debug)]
+pub enum ReflectError {
+	#[error("Code does not start with the SPIR-V magic number")]
+	InvalidMagic,
+	#[error("Code ended in the middle of an instruction")]
+	Truncated,
+	#[error("Instruction at word {0} has zero length")]
+	ZeroLengthInstruction(usize)
+}
+
+/// Scalar component type of an interface variable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScalarKind {
+	Bool,
+	Float,
+	Int,
+	Uint
+}
+
+/// Type of an interface variable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct InterfaceType {
+	pub kind: ScalarKind,
+	/// Width of the scalar in bits.
+	pub width: u32,
+	/// Number of vector components, `1` for scalars.
+	pub components: u32,
+	/// Number of matrix columns, `1` for non-matrices.
+	pub columns: u32,
+	/// Number of array elements, `1` for non-arrays.
+	pub array_length: u32
+}
+impl InterfaceType {
+	/// Returns the number of locations this type consumes.
+	pub fn location_count(&self) -> u32 {
+		let per_column = if self.width == 64 && self.components > 2 { 2 } else { 1 };
+
+		per_column * self.columns * self.array_length
+	}
+
+	/// Returns the vertex attribute format of one column of this type, or `None` if there is no such format.
+	pub fn format(&self) -> Option<vk::Format> {
+		let formats = match (self.kind, self.width) {
+			(ScalarKind::Float, 16) => [
+				vk::Format::R16_SFLOAT,
+				vk::Format::R16G16_SFLOAT,
+				vk::Format::R16G16B16_SFLOAT,
+				vk::Format::R16G16B16A16_SFLOAT
+			],
+			(ScalarKind::Float, 32) => [
+				vk::Format::R32_SFLOAT,
+				vk::Format::R32G32_SFLOAT,
+				vk::Format::R32G32B32_SFLOAT,
+				vk::Format::R32G32B32A32_SFLOAT
+			],
+			(ScalarKind::Float, 64) => [
+				vk::Format::R64_SFLOAT,
+				vk::Format::R64G64_SFLOAT,
+				vk::Format::R64G64B64_SFLOAT,
+				vk::Format::R64G64B64A64_SFLOAT
+			],
+			(ScalarKind::Int, 16) => [
+				vk::Format::R16_SINT,
+				vk::Format::R16G16_SINT,
+				vk::Format::R16G16B16_SINT,
+				vk::Format::R16G16B16A16_SINT
+			],
+			(ScalarKind::Int, 32) => [
+				vk::Format::R32_SINT,
+				vk::Format::R32G32_SINT,
+				vk::Format::R32G32B32_SINT,
+				vk::Format::R32G32B32A32_SINT
+			],
+			(ScalarKind::Int, 64) => [
+				vk::Format::R64_SINT,
+				vk::Format::R64G64_SINT,
+				vk::Format::R64G64B64_SINT,
+				vk::Format::R64G64B64A64_SINT
+			],
+			(ScalarKind::Uint, 16) => [
+				vk::Format::R16_UINT,
+				vk::Format::R16G16_UINT,
+				vk::Format::R16G16B16_UINT,
+				vk::Format::R16G16B16A16_UINT
+			],
+			(ScalarKind::Uint, 32) => [
+				vk::Format::R32_UINT,
+				vk::Format::R32G32_UINT,
+				vk::Format::R32G32B32_UINT,
+				vk::Format::R32G32B32A32_UINT
+			],
+			(ScalarKind::Uint, 64) => [
+				vk::Format::R64_UINT,
+				vk::Format::R64G64_UINT,
+				vk::Format::R64G64B64_UINT,
+				vk::Format::R64G64B64A64_UINT
+			],
+			_ => return None
+		};
+
+		formats.get(self.components.checked_sub(1)? as usize).copied()
+	}
+}
+impl fmt::Display for InterfaceType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:?}{}", self.kind, self.width)?;
+		if self.components > 1 {
+			write!(f, "x{}", self.components)?;
+		}
+		if self.columns > 1 {
+			write!(f, "x{}", self.columns)?;
+		}
+		if self.array_length > 1 {
+			write!(f, "[{}]", self.array_length)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Input or output variable of a shader entry point with an explicit location.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InterfaceVariable {
+	pub location: u32,
+	pub component: u32,
+	pub name: Option<String>,
+	/// `None` if the type is not supported by the reflection, such as a struct.
+	pub ty: Option<InterfaceType>
+}
+impl InterfaceVariable {
+	fn display_name(&self) -> &str {
+		self.name.as_deref().unwrap_or("<unnamed>")
+	}
+}
+
+/// Reflection of one entry point.
+#[derive(Debug, Clone)]
+pub struct EntryPointReflection {
+	pub name: String,
+	pub stage: vk::ShaderStageFlags,
+	/// Sorted by location.
+	pub inputs: Vec<InterfaceVariable>,
+	/// Sorted by location.
+	pub outputs: Vec<InterfaceVariable>
+}
+impl EntryPointReflection {
+	/// Returns vertex input attributes of the inputs of this entry point tightly packed in location order into one interleaved `binding`,
+	/// along with the stride of the binding.
+	///
+	/// Returns `None` if an input has an unsupported type or is not aligned to a location.
+	pub fn vertex_input_attributes(&self, binding: u32) -> Option<(Vec<vk::VertexInputAttributeDescription>, u32)> {
+		let mut attributes = Vec::with_capacity(self.inputs.len());
+		let mut offset = 0;
+
+		for input in self.inputs.iter() {
+			let ty = input.ty?;
+			if input.component != 0 {
+				return None
+			}
+
+			let format = ty.format()?;
+			let elements = ty.columns * ty.array_length;
+			let locations_per_element = ty.location_count() / elements;
+			for element in 0 .. elements {
+				attributes.push(
+					vk::VertexInputAttributeDescription::builder()
+						.location(input.location + element * locations_per_element)
+						.binding(binding)
+						.format(format)
+						.offset(offset)
+						.build()
+				);
+				offset += ty.components * ty.width / 8;
+			}
+		}
+
+		Some((attributes, offset))
+	}
+}
+
+/// Descriptor binding used by a shader module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DescriptorBinding {
+	pub set: u32,
+	pub binding: u32,
+	pub descriptor_type: vk::DescriptorType,
+	/// Number of descriptors, `0` for runtime arrays whose size is chosen when creating the layout.
+	pub descriptor_count: u32,
+	pub name: Option<String>
+}
+
+/// Reflection of a whole shader module.
+#[derive(Debug, Clone)]
+pub struct ShaderReflection {
+	pub entry_points: Vec<EntryPointReflection>,
+	/// Descriptor bindings declared in the module, sorted by set and binding.
+	pub descriptor_bindings: Vec<DescriptorBinding>,
+	/// Byte range of the push constant block declared in the module, if any.
+	pub push_constants: Option<Range<u32>>
+}
+impl ShaderReflection {
+	/// Parses SPIR-V `code`.
+	pub fn parse(code: &[u32]) -> Result<Self, ReflectError> {
+		if code.len() < 5 || code[0] != SPIRV_MAGIC {
+			return Err(ReflectError::InvalidMagic)
+		}
+
+		let mut names = VHashMap::<u32, String>::default();
+		let mut locations = VHashMap::<u32, u32>::default();
+		let mut components = VHashMap::<u32, u32>::default();
+		let mut builtins = Vec::<u32>::new();
+		let mut types = VHashMap::<u32, Option<InterfaceType>>::default();
+		let mut pointers = VHashMap::<u32, u32>::default();
+		let mut constants = VHashMap::<u32, u32>::default();
+		let mut variables = VHashMap::<u32, (u32, u32)>::default();
+		let mut entry_points = Vec::<(u32, String, Vec<u32>)>::new();
+
+		let mut resources = ResourceTypes::default();
+		let mut resource_variables = Vec::<(u32, u32, u32)>::new();
+		let mut descriptor_sets = VHashMap::<u32, u32>::default();
+		let mut bindings = VHashMap::<u32, u32>::default();
+
+		let mut index = 5;
+		while index < code.len() {
+			let word_count = (code[index] >> 16) as usize;
+			let opcode = (code[index] & 0xFFFF) as u16;
+			if word_count == 0 {
+				return Err(ReflectError::ZeroLengthInstruction(index))
+			}
+			let operands = code.get(index + 1 .. index + word_count).ok_or(ReflectError::Truncated)?;
+			index += word_count;
+
+			match opcode {
+				OP_NAME if operands.len() >= 2 => {
+					names.insert(operands[0], parse_string(&operands[1 ..]).0);
+				}
+				OP_ENTRY_POINT if operands.len() >= 3 => {
+					let (name, used) = parse_string(&operands[2 ..]);
+					entry_points.push((
+						operands[0],
+						name,
+						operands[2 + used ..].to_vec()
+					));
+				}
+				OP_DECORATE if operands.len() >= 2 => match operands[1] {
+					DECORATION_LOCATION if operands.len() >= 3 => {
+						locations.insert(operands[0], operands[2]);
+					}
+					DECORATION_COMPONENT if operands.len() >= 3 => {
+						components.insert(operands[0], operands[2]);
+					}
+					DECORATION_BUILT_IN => builtins.push(operands[0]),
+					DECORATION_BUFFER_BLOCK => resources.buffer_blocks.push(operands[0]),
+					DECORATION_ARRAY_STRIDE if operands.len() >= 3 => {
+						resources.array_strides.insert(operands[0], operands[2]);
+					}
+					DECORATION_BINDING if operands.len() >= 3 => {
+						bindings.insert(operands[0], operands[2]);
+					}
+					DECORATION_DESCRIPTOR_SET if operands.len() >= 3 => {
+						descriptor_sets.insert(operands[0], operands[2]);
+					}
+					_ => ()
+				},
+				OP_MEMBER_DECORATE if operands.len() >= 4 => match operands[2] {
+					DECORATION_OFFSET => {
+						resources.member_offsets.insert((operands[0], operands[1]), operands[3]);
+					}
+					DECORATION_MATRIX_STRIDE => {
+						resources.member_matrix_strides.insert((operands[0], operands[1]), operands[3]);
+					}
+					_ => ()
+				},
+				OP_TYPE_BOOL if !operands.is_empty() => {
+					types.insert(operands[0], Some(scalar(ScalarKind::Bool, 32)));
+					resources.types.insert(operands[0], ResourceType::Scalar(4));
+				}
+				OP_TYPE_INT if operands.len() >= 3 => {
+					let kind = if operands[2] == 0 { ScalarKind::Uint } else { ScalarKind::Int };
+					types.insert(operands[0], Some(scalar(kind, operands[1])));
+					resources.types.insert(operands[0], ResourceType::Scalar(operands[1] / 8));
+				}
+				OP_TYPE_FLOAT if operands.len() >= 2 => {
+					types.insert(operands[0], Some(scalar(ScalarKind::Float, operands[1])));
+					resources.types.insert(operands[0], ResourceType::Scalar(operands[1] / 8));
+				}
+				OP_TYPE_VECTOR if operands.len() >= 3 => {
+					let ty = types.get(&operands[1]).copied().flatten().map(|t| InterfaceType { components: operands[2], ..t });
+					types.insert(operands[0], ty);
+					resources.types.insert(operands[0], ResourceType::Vector(operands[1], operands[2]));
+				}
+				OP_TYPE_MATRIX if operands.len() >= 3 => {
+					let ty = types.get(&operands[1]).copied().flatten().map(|t| InterfaceType { columns: operands[2], ..t });
+					types.insert(operands[0], ty);
+					resources.types.insert(operands[0], ResourceType::Matrix(operands[1], operands[2]));
+				}
+				OP_TYPE_IMAGE if operands.len() >= 7 => {
+					resources.types.insert(
+						operands[0],
+						ResourceType::Image { dim: operands[2], sampled: operands[6] }
+					);
+				}
+				OP_TYPE_SAMPLER if !operands.is_empty() => {
+					resources.types.insert(operands[0], ResourceType::Sampler);
+				}
+				OP_TYPE_SAMPLED_IMAGE if operands.len() >= 2 => {
+					resources.types.insert(operands[0], ResourceType::SampledImage);
+				}
+				OP_TYPE_ACCELERATION_STRUCTURE if !operands.is_empty() => {
+					resources.types.insert(operands[0], ResourceType::AccelerationStructure);
+				}
+				OP_TYPE_ARRAY if operands.len() >= 3 => {
+					let ty = types.get(&operands[1]).copied().flatten().and_then(|t| {
+						constants
+							.get(&operands[2])
+							.map(|&length| InterfaceType { array_length: t.array_length * length, ..t })
+					});
+					types.insert(operands[0], ty);
+					if let Some(&length) = constants.get(&operands[2]) {
+						resources.types.insert(operands[0], ResourceType::Array(operands[1], length));
+					}
+				}
+				OP_TYPE_RUNTIME_ARRAY if operands.len() >= 2 => {
+					resources.types.insert(operands[0], ResourceType::RuntimeArray(operands[1]));
+				}
+				OP_TYPE_STRUCT if !operands.is_empty() => {
+					types.insert(operands[0], None);
+					resources.types.insert(operands[0], ResourceType::Struct(operands[1 ..].to_vec()));
+				}
+				OP_TYPE_POINTER if operands.len() >= 3 => {
+					pointers.insert(operands[0], operands[2]);
+				}
+				OP_CONSTANT if operands.len() >= 3 => {
+					constants.insert(operands[1], operands[2]);
+				}
+				OP_VARIABLE if operands.len() >= 3 => match operands[2] {
+					STORAGE_CLASS_INPUT | STORAGE_CLASS_OUTPUT => {
+						variables.insert(operands[1], (operands[0], operands[2]));
+					}
+					STORAGE_CLASS_UNIFORM_CONSTANT | STORAGE_CLASS_UNIFORM | STORAGE_CLASS_STORAGE_BUFFER | STORAGE_CLASS_PUSH_CONSTANT => {
+						resource_variables.push((operands[1], operands[0], operands[2]));
+					}
+					_ => ()
+				},
+				_ => ()
+			}
+		}
+
+		let entry_points = entry_points
+			.into_iter()
+			.map(|(execution_model, name, interface)| {
+				let mut inputs = Vec::new();
+				let mut outputs = Vec::new();
+
+				for id in interface {
+					if builtins.contains(&id) {
+						continue
+					}
+					let (pointer_type, storage_class) = match variables.get(&id) {
+						Some(&v) => v,
+						None => continue
+					};
+					let location = match locations.get(&id) {
+						Some(&l) => l,
+						None => continue
+					};
+
+					let variable = InterfaceVariable {
+						location,
+						component: components.get(&id).copied().unwrap_or(0),
+						name: names.get(&id).cloned().filter(|n| !n.is_empty()),
+						ty: pointers.get(&pointer_type).and_then(|t| types.get(t)).copied().flatten()
+					};
+					if storage_class == STORAGE_CLASS_INPUT {
+						inputs.push(variable);
+					} else {
+						outputs.push(variable);
+					}
+				}
+				inputs.sort_by_key(|v| (v.location, v.component));
+				outputs.sort_by_key(|v| (v.location, v.component));
+
+				EntryPointReflection { name, stage: execution_model_stage(execution_model), inputs, outputs }
+			})
+			.collect();
+
+		let mut descriptor_bindings = Vec::new();
+		let mut push_constants = None;
+		for (id, pointer_type, storage_class) in resource_variables {
+			let ty = match pointers.get(&pointer_type) {
+				Some(&t) => t,
+				None => continue
+			};
+
+			if storage_class == STORAGE_CLASS_PUSH_CONSTANT {
+				push_constants = resources.block_range(ty);
+				continue
+			}
+
+			let (set, binding) = match (descriptor_sets.get(&id), bindings.get(&id)) {
+				(Some(&set), Some(&binding)) => (set, binding),
+				_ => continue
+			};
+			if let Some((descriptor_type, descriptor_count)) = resources.descriptor(ty, storage_class) {
+				descriptor_bindings.push(DescriptorBinding {
+					set,
+					binding,
+					descriptor_type,
+					descriptor_count,
+					name: names.get(&id).cloned().filter(|n| !n.is_empty())
+				});
+			}
+		}
+		descriptor_bindings.sort_by_key(|b| (b.set, b.binding));
+
+		Ok(ShaderReflection { entry_points, descriptor_bindings, push_constants })
+	}
+
+	/// Returns the entry point with `name`.
+	pub fn entry_point(&self, name: &str) -> Option<&EntryPointReflection> {
+		self.entry_points.iter().find(|e| e.name == name)
+	}
+
+	/// Returns the union of stages of all entry points.
+	pub fn stages(&self) -> vk::ShaderStageFlags {
+		self.entry_points.iter().fold(vk::ShaderStageFlags::empty(), |acc, e| acc | e.stage)
+	}
+
+	/// Returns the push constant range of this module for the stages of all its entry points.
+	///
+	/// The range is widened to a multiple of four bytes.
+	pub fn push_constant_range(&self) -> Option<PushConstantRange> {
+		let range = self.push_constants.as_ref()?;
+
+		let offset_div_four = range.start / 4;
+		let size_div_four = NonZeroU32::new(range.end.div_ceil(4) - offset_div_four)?;
+		Some(PushConstantRange::new(
+			self.stages(),
+			offset_div_four,
+			size_div_four
+		))
+	}
+}
+
+/// Type information needed to reflect descriptors and push constant blocks.
+#[derive(Debug, Clone)]
+enum ResourceType {
+	/// Size in bytes.
+	Scalar(u32),
+	/// Component type and count.
+	Vector(u32, u32),
+	/// Column type and count.
+	Matrix(u32, u32),
+	/// Element type and length.
+	Array(u32, u32),
+	RuntimeArray(u32),
+	/// Member types.
+	Struct(Vec<u32>),
+	Image { dim: u32, sampled: u32 },
+	Sampler,
+	SampledImage,
+	AccelerationStructure
+}
+
+#[derive(Debug, Default)]
+struct ResourceTypes {
+	types: VHashMap<u32, ResourceType>,
+	buffer_blocks: Vec<u32>,
+	array_strides: VHashMap<u32, u32>,
+	member_offsets: VHashMap<(u32, u32), u32>,
+	member_matrix_strides: VHashMap<(u32, u32), u32>
+}
+impl ResourceTypes {
+	/// Returns the descriptor type and count of a variable of type `ty` in `storage_class`.
+	fn descriptor(&self, ty: u32, storage_class: u32) -> Option<(vk::DescriptorType, u32)> {
+		let descriptor_type = match self.types.get(&ty)? {
+			ResourceType::Array(element, length) => {
+				let (descriptor_type, count) = self.descriptor(*element, storage_class)?;
+				return Some((descriptor_type, count * length))
+			}
+			ResourceType::RuntimeArray(element) => {
+				let (descriptor_type, _) = self.descriptor(*element, storage_class)?;
+				return Some((descriptor_type, 0))
+			}
+			ResourceType::Sampler => vk::DescriptorType::SAMPLER,
+			ResourceType::SampledImage => vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+			ResourceType::Image { dim: DIM_BUFFER, sampled: 2 } => vk::DescriptorType::STORAGE_TEXEL_BUFFER,
+			ResourceType::Image { dim: DIM_BUFFER, .. } => vk::DescriptorType::UNIFORM_TEXEL_BUFFER,
+			ResourceType::Image { dim: DIM_SUBPASS_DATA, .. } => vk::DescriptorType::INPUT_ATTACHMENT,
+			ResourceType::Image { sampled: 2, .. } => vk::DescriptorType::STORAGE_IMAGE,
+			ResourceType::Image { .. } => vk::DescriptorType::SAMPLED_IMAGE,
+			ResourceType::AccelerationStructure => vk::DescriptorType::ACCELERATION_STRUCTURE_KHR,
+			ResourceType::Struct(_) => match storage_class {
+				STORAGE_CLASS_STORAGE_BUFFER => vk::DescriptorType::STORAGE_BUFFER,
+				STORAGE_CLASS_UNIFORM if self.buffer_blocks.contains(&ty) => vk::DescriptorType::STORAGE_BUFFER,
+				STORAGE_CLASS_UNIFORM => vk::DescriptorType::UNIFORM_BUFFER,
+				_ => return None
+			},
+			_ => return None
+		};
+
+		Some((descriptor_type, 1))
+	}
+
+	/// Returns the size of type `ty` in bytes, or `None` if it cannot be determined.
+	///
+	/// Matrices are assumed to be column-major.
+	fn size(&self, ty: u32) -> Option<u32> {
+		match self.types.get(&ty)? {
+			ResourceType::Scalar(size) => Some(*size),
+			ResourceType::Vector(component, count) => Some(self.size(*component)? * count),
+			ResourceType::Matrix(column, count) => Some(self.size(*column)? * count),
+			ResourceType::Array(element, length) => {
+				let stride = match self.array_strides.get(&ty) {
+					Some(&stride) => stride,
+					None => self.size(*element)?
+				};
+
+				Some(stride * length)
+			}
+			ResourceType::Struct(members) => self.member_ranges(ty, members)?.into_iter().map(|r| r.end).max().or(Some(0)),
+			_ => None
+		}
+	}
+
+	fn member_ranges(&self, ty: u32, members: &[u32]) -> Option<Vec<Range<u32>>> {
+		members
+			.iter()
+			.enumerate()
+			.map(|(index, &member)| {
+				let key = (ty, index as u32);
+				let offset = self.member_offsets.get(&key).copied().unwrap_or(0);
+				let size = match (self.types.get(&member), self.member_matrix_strides.get(&key)) {
+					(Some(ResourceType::Matrix(_, columns)), Some(stride)) => stride * columns,
+					_ => self.size(member)?
+				};
+
+				Some(offset .. offset + size)
+			})
+			.collect()
+	}
+
+	/// Returns the byte range of a block of type `ty` covered by its members.
+	fn block_range(&self, ty: u32) -> Option<Range<u32>> {
+		let members = match self.types.get(&ty)? {
+			ResourceType::Struct(members) => members,
+			_ => return None
+		};
+
+		let ranges = self.member_ranges(ty, members)?;
+		let start = ranges.iter().map(|r| r.start).min()?;
+		let end = ranges.iter().map(|r| r.end).max()?;
+
+		Some(start .. end)
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum ReflectLayoutError {
+	#[error("Binding {binding} of set {set} is declared with different types or counts")]
+	BindingMismatch { set: u32, binding: u32 }
+}
+
+/// Merges descriptor bindings of `reflections` into descriptor set layout definitions indexed by set number.
+///
+/// Stage flags of each binding are the stages of the modules using it. Sets without any binding get an empty definition.
+/// Runtime arrays have a descriptor count of `0`, which must be replaced by the desired count before creating the layout.
+pub fn set_layout_definitions(reflections: &[&ShaderReflection]) -> Result<Vec<DescriptorSetLayoutDefinition>, ReflectLayoutError> {
+	let mut definitions = Vec::<DescriptorSetLayoutDefinition>::new();
+
+	for reflection in reflections {
+		let stages = reflection.stages();
+
+		for binding in reflection.descriptor_bindings.iter() {
+			let set = binding.set as usize;
+			if definitions.len() <= set {
+				definitions.resize_with(set + 1, || DescriptorSetLayoutDefinition {
+					flags: vk::DescriptorSetLayoutCreateFlags::empty(),
+					bindings: Vec::new()
+				});
+			}
+			let bindings = &mut definitions[set].bindings;
+
+			match bindings.iter_mut().find(|b| b.binding == binding.binding) {
+				Some(existing) => {
+					if existing.descriptor_type != binding.descriptor_type || existing.descriptor_count != binding.descriptor_count {
+						return Err(ReflectLayoutError::BindingMismatch { set: binding.set, binding: binding.binding })
+					}
+					existing.stage_flags |= stages;
+				}
+				None => bindings.push(DescriptorSetLayoutBindingDefinition {
+					binding: binding.binding,
+					descriptor_type: binding.descriptor_type,
+					descriptor_count: binding.descriptor_count,
+					stage_flags: stages,
+					immutable_samplers: Vec::new()
+				})
+			}
+		}
+	}
+
+	for definition in definitions.iter_mut() {
+		definition.bindings.sort_by_key(|b| b.binding);
+	}
+
+	Ok(definitions)
+}
+
+fn scalar(kind: ScalarKind, width: u32) -> InterfaceType {
+	InterfaceType { kind, width, components: 1, columns: 1, array_length: 1 }
+}
+
+/// Parses a nul-terminated literal string and returns it along with the number of words it occupied.
+fn parse_string(words: &[u32]) -> (String, usize) {
+	let mut bytes = Vec::with_capacity(words.len() * 4);
+	let mut used = 0;
+	for word in words {
+		used += 1;
+		let word_bytes = word.to_le_bytes();
+		if let Some(end) = word_bytes.iter().position(|&b| b == 0) {
+			bytes.extend_from_slice(&word_bytes[.. end]);
+			break
+		}
+		bytes.extend_from_slice(&word_bytes);
+	}
+
+	(String::from_utf8_lossy(&bytes).into_owned(), used)
+}
+
+fn execution_model_stage(execution_model: u32) -> vk::ShaderStageFlags {
+	match execution_model {
+		0 => vk::ShaderStageFlags::VERTEX,
+		1 => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+		2 => vk::ShaderStageFlags::TESSELLATION_EVALUATION,
+		3 => vk::ShaderStageFlags::GEOMETRY,
+		4 => vk::ShaderStageFlags::FRAGMENT,
+		5 => vk::ShaderStageFlags::COMPUTE,
+		_ => vk::ShaderStageFlags::empty()
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum ShaderInterfaceError {
+	#[error("Fragment input \"{name}\" at location {location} is not written by the {producer:?} stage")]
+	MissingStageOutput { producer: vk::ShaderStageFlags, location: u32, name: String },
+	#[error("Fragment input \"{name}\" at location {location} has type {input} but the {producer:?} stage writes {output}")]
+	StageTypeMismatch { producer: vk::ShaderStageFlags, location: u32, name: String, input: InterfaceType, output: InterfaceType },
+	#[error("Vertex shader input \"{name}\" at location {location} has no vertex input attribute")]
+	MissingVertexAttribute { location: u32, name: String }
+}
+
+/// Validates that `consumer` inputs are written by `producer` outputs with compatible types.
+///
+/// An output may have more components than the corresponding input.
+pub fn validate_stage_interface(producer: &EntryPointReflection, consumer: &EntryPointReflection) -> Result<(), ShaderInterfaceError> {
+	for input in consumer.inputs.iter() {
+		let output = producer
+			.outputs
+			.iter()
+			.find(|o| o.location == input.location && o.component == input.component)
+			.ok_or_else(|| ShaderInterfaceError::MissingStageOutput {
+				producer: producer.stage,
+				location: input.location,
+				name: input.display_name().to_string()
+			})?;
+
+		if let (Some(input_ty), Some(output_ty)) = (input.ty, output.ty) {
+			let compatible = input_ty.kind == output_ty.kind
+				&& input_ty.width == output_ty.width
+				&& input_ty.components <= output_ty.components
+				&& input_ty.columns == output_ty.columns
+				&& input_ty.array_length == output_ty.array_length;
+			if !compatible {
+				return Err(ShaderInterfaceError::StageTypeMismatch {
+					producer: producer.stage,
+					location: input.location,
+					name: input.display_name().to_string(),
+					input: input_ty,
+					output: output_ty
+				})
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Validates that every location consumed by the `vertex` inputs has an attribute in `attributes`.
+pub fn validate_vertex_input(vertex: &EntryPointReflection, attributes: &[vk::VertexInputAttributeDescription]) -> Result<(), ShaderInterfaceError> {
+	for input in vertex.inputs.iter() {
+		let count = input.ty.map(|t| t.location_count()).unwrap_or(1);
+
+		for location in input.location .. input.location + count {
+			if !attributes.iter().any(|a| a.location == location) {
+				return Err(ShaderInterfaceError::MissingVertexAttribute { location, name: input.display_name().to_string() })
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Validates the interfaces of graphics pipeline `stages`.
+///
+/// Vertex shader inputs are validated against `attributes` and fragment shader inputs against the outputs
+/// of the last pre-rasterization stage. Arrayed interfaces of tessellation and geometry stages are not validated.
+pub fn validate_graphics_interfaces(stages: &[&EntryPointReflection], attributes: &[vk::VertexInputAttributeDescription]) -> Result<(), ShaderInterfaceError> {
+	let find = |stage: vk::ShaderStageFlags| stages.iter().copied().find(|e| e.stage == stage);
+
+	if let Some(vertex) = find(vk::ShaderStageFlags::VERTEX) {
+		validate_vertex_input(vertex, attributes)?;
+	}
+
+	if let Some(fragment) = find(vk::ShaderStageFlags::FRAGMENT) {
+		let producer = find(vk::ShaderStageFlags::GEOMETRY)
+			.or_else(|| find(vk::ShaderStageFlags::TESSELLATION_EVALUATION))
+			.or_else(|| find(vk::ShaderStageFlags::VERTEX));
+
+		if let Some(producer) = producer {
+			validate_stage_interface(producer, fragment)?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Registry of reflections of live shader modules of a device.
+pub struct ShaderReflectionRegistry {
+	modules: Vutex<VHashMap<vk::ShaderModule, crate::prelude::Vrc<ShaderReflection>>>
+}
+impl ShaderReflectionRegistry {
+	pub fn new() -> Self {
+		ShaderReflectionRegistry { modules: Vutex::new(VHashMap::default()) }
+	}
+
+	pub fn register(&self, module: vk::ShaderModule, reflection: crate::prelude::Vrc<ShaderReflection>) {
+		self.modules.lock().expect("vutex poisoned").insert(module, reflection);
+	}
+
+	pub fn unregister(&self, module: vk::ShaderModule) {
+		self.modules.lock().expect("vutex poisoned").remove(&module);
+	}
+
+	pub fn get(&self, module: vk::ShaderModule) -> Option<crate::prelude::Vrc<ShaderReflection>> {
+		self.modules.lock().expect("vutex poisoned").get(&module).cloned()
+	}
+
+	/// Validates the interfaces of graphics pipeline stages described by `create_info`.
+	///
+	/// Stages whose modules have no registered reflection are skipped.
+	///
+	/// ### Safety
+	///
+	/// All pointers in `create_info` must be valid.
+	pub unsafe fn validate_graphics_pipeline(&self, create_info: &vk::GraphicsPipelineCreateInfo) -> Result<(), ShaderInterfaceError> {
+		let raw_stages: &[vk::PipelineShaderStageCreateInfo] = if create_info.stage_count == 0 {
+			&[]
+		} else {
+			std::slice::from_raw_parts(
+				create_info.p_stages,
+				create_info.stage_count as usize
+			)
+		};
+
+		let reflections: Vec<_> = raw_stages
+			.iter()
+			.filter_map(|stage| {
+				let name = CStr::from_ptr(stage.p_name).to_string_lossy();
+				self.get(stage.module).map(|r| (r, name, stage.stage))
+			})
+			.collect();
+		let entry_points: Vec<_> = reflections
+			.iter()
+			.filter_map(|(reflection, name, stage)| reflection.entry_point(name).filter(|e| e.stage == *stage))
+			.collect();
+
+		let attributes: &[vk::VertexInputAttributeDescription] = match create_info.p_vertex_input_state.as_ref() {
+			Some(state) if state.vertex_attribute_description_count > 0 => std::slice::from_raw_parts(
+				state.p_vertex_attribute_descriptions,
+				state.vertex_attribute_description_count as usize
+			),
+			_ => &[]
+		};
+
+		validate_graphics_interfaces(&entry_points, attributes)
+	}
+}
+impl Default for ShaderReflectionRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl fmt::Debug for ShaderReflectionRegistry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ShaderReflectionRegistry")
+			.field(
+				"count",
+				&self.modules.lock().expect("vutex poisoned").len()
+			)
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{
+		set_layout_definitions,
+		validate_stage_interface,
+		InterfaceType,
+		InterfaceVariable,
+		ScalarKind,
+		ShaderInterfaceError,
+		ShaderReflection
+	};
+
+	fn instruction(opcode: u16, operands: &[u32]) -> Vec<u32> {
+		let mut words = vec![((operands.len() as u32 + 1) << 16) | opcode as u32];
+		words.extend_from_slice(operands);
+		words
+	}
+
+	#[test]
+	fn parse_vertex_interface() {
+		let main = u32::from_le_bytes(*b"main");
+		let pos = u32::from_le_bytes(*b"pos\0");
+
+		let code: Vec<u32> = [
+			vec![super::SPIRV_MAGIC, 0x0001_0000, 0, 20, 0],
+			instruction(super::OP_ENTRY_POINT, &[0, 1, main, 0, 10, 11]),
+			instruction(super::OP_NAME, &[10, pos]),
+			instruction(super::OP_DECORATE, &[10, super::DECORATION_LOCATION, 0]),
+			instruction(super::OP_DECORATE, &[11, super::DECORATION_BUILT_IN, 0]),
+			instruction(super::OP_TYPE_FLOAT, &[2, 32]),
+			instruction(super::OP_TYPE_VECTOR, &[3, 2, 3]),
+			instruction(super::OP_TYPE_POINTER, &[4, super::STORAGE_CLASS_INPUT, 3]),
+			instruction(super::OP_VARIABLE, &[4, 10, super::STORAGE_CLASS_INPUT]),
+			instruction(super::OP_VARIABLE, &[4, 11, super::STORAGE_CLASS_OUTPUT])
+		]
+		.concat();
+
+		let reflection = ShaderReflection::parse(&code).unwrap();
+		let entry = reflection.entry_point("main").unwrap();
+
+		assert_eq!(entry.stage, vk::ShaderStageFlags::VERTEX);
+		assert_eq!(
+			entry.inputs,
+			vec![InterfaceVariable {
+				location: 0,
+				component: 0,
+				name: Some("pos".to_string()),
+				ty: Some(InterfaceType { kind: ScalarKind::Float, width: 32, components: 3, columns: 1, array_length: 1 })
+			}]
+		);
+		assert!(entry.outputs.is_empty());
+
+		let (attributes, stride) = entry.vertex_input_attributes(0).unwrap();
+		assert_eq!(attributes.len(), 1);
+		assert_eq!(attributes[0].format, vk::Format::R32G32B32_SFLOAT);
+		assert_eq!(stride, 12);
+	}
+
+	#[test]
+	fn parse_resources() {
+		let main = u32::from_le_bytes(*b"main");
+
+		let code: Vec<u32> = [
+			vec![super::SPIRV_MAGIC, 0x0001_0000, 0, 30, 0],
+			instruction(super::OP_ENTRY_POINT, &[4, 1, main, 0]),
+			instruction(super::OP_DECORATE, &[20, super::DECORATION_DESCRIPTOR_SET, 0]),
+			instruction(super::OP_DECORATE, &[20, super::DECORATION_BINDING, 1]),
+			instruction(super::OP_DECORATE, &[21, super::DECORATION_DESCRIPTOR_SET, 1]),
+			instruction(super::OP_DECORATE, &[21, super::DECORATION_BINDING, 0]),
+			instruction(super::OP_MEMBER_DECORATE, &[6, 0, super::DECORATION_OFFSET, 16]),
+			instruction(super::OP_MEMBER_DECORATE, &[6, 1, super::DECORATION_OFFSET, 32]),
+			instruction(super::OP_TYPE_FLOAT, &[2, 32]),
+			instruction(super::OP_TYPE_VECTOR, &[3, 2, 4]),
+			instruction(super::OP_TYPE_IMAGE, &[7, 2, 1, 0, 0, 0, 1, 0]),
+			instruction(super::OP_TYPE_SAMPLED_IMAGE, &[8, 7]),
+			instruction(super::OP_TYPE_INT, &[9, 32, 0]),
+			instruction(super::OP_CONSTANT, &[9, 10, 4]),
+			instruction(super::OP_TYPE_ARRAY, &[11, 8, 10]),
+			instruction(super::OP_TYPE_STRUCT, &[5, 3]),
+			instruction(super::OP_TYPE_STRUCT, &[6, 3, 2]),
+			instruction(super::OP_TYPE_POINTER, &[12, super::STORAGE_CLASS_UNIFORM_CONSTANT, 11]),
+			instruction(super::OP_TYPE_POINTER, &[13, super::STORAGE_CLASS_UNIFORM, 5]),
+			instruction(super::OP_TYPE_POINTER, &[14, super::STORAGE_CLASS_PUSH_CONSTANT, 6]),
+			instruction(super::OP_VARIABLE, &[12, 20, super::STORAGE_CLASS_UNIFORM_CONSTANT]),
+			instruction(super::OP_VARIABLE, &[13, 21, super::STORAGE_CLASS_UNIFORM]),
+			instruction(super::OP_VARIABLE, &[14, 22, super::STORAGE_CLASS_PUSH_CONSTANT])
+		]
+		.concat();
+
+		let reflection = ShaderReflection::parse(&code).unwrap();
+		assert_eq!(reflection.push_constants, Some(16 .. 36));
+
+		let range = reflection.push_constant_range().unwrap();
+		assert_eq!(
+			(range.stage_flags, range.offset, range.size),
+			(vk::ShaderStageFlags::FRAGMENT, 16, 20)
+		);
+
+		let definitions = set_layout_definitions(&[&reflection]).unwrap();
+		assert_eq!(definitions.len(), 2);
+		assert_eq!(
+			(
+				definitions[0].bindings[0].binding,
+				definitions[0].bindings[0].descriptor_type,
+				definitions[0].bindings[0].descriptor_count
+			),
+			(1, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 4)
+		);
+		assert_eq!(
+			definitions[1].bindings[0].descriptor_type,
+			vk::DescriptorType::UNIFORM_BUFFER
+		);
+	}
+
+	#[test]
+	fn stage_interface_mismatch() {
+		let vec4 = InterfaceType { kind: ScalarKind::Float, width: 32, components: 4, columns: 1, array_length: 1 };
+		let variable = |location, name: &str, ty| InterfaceVariable { location, component: 0, name: Some(name.to_string()), ty: Some(ty) };
+
+		let vertex = super::EntryPointReflection {
+			name: "main".into(),
+			stage: vk::ShaderStageFlags::VERTEX,
+			inputs: Vec::new(),
+			outputs: vec![variable(0, "color", vec4)]
+		};
+		let fragment = super::EntryPointReflection {
+			name: "main".into(),
+			stage: vk::ShaderStageFlags::FRAGMENT,
+			inputs: vec![
+				variable(0, "color", InterfaceType { components: 3, ..vec4 }),
+				variable(1, "uv", vec4)
+			],
+			outputs: Vec::new()
+		};
+
+		match validate_stage_interface(&vertex, &fragment) {
+			Err(ShaderInterfaceError::MissingStageOutput { location: 1, name, .. }) => assert_eq!(name, "uv"),
+			other => panic!("unexpected result {:?}", other)
+		}
+	}
+}
@@ -0,0 +1,200 @@
+//! Direct presentation to displays without a window system using `VK_KHR_display`.
+//!
+//! Displays attached to a physical device are enumerated with `displays`, each display supports a number of modes
+//! returned by `display_modes`. Images are presented to display planes, which are composited onto a display.
+//! A surface for a plane showing a given mode is created with `Surface::display_plane`.
+//!
+//! The instance must have been created with `VK_KHR_display` enabled.
+
+use std::{ffi::CStr, ops::Deref};
+
+use ash::vk;
+
+use super::{error, Surface};
+use crate::prelude::{HostMemoryAllocator, Instance, PhysicalDevice, Vrc};
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum DisplayQueryError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY
+		}
+	}
+}
+
+/// Properties of a display attached to a physical device.
+#[derive(Debug, Clone)]
+pub struct DisplayProperties {
+	pub display: vk::DisplayKHR,
+	/// Human-readable name of the display, if the implementation provides one.
+	pub name: Option<String>,
+	/// Physical size of the display in millimeters.
+	pub physical_dimensions: vk::Extent2D,
+	pub physical_resolution: vk::Extent2D,
+	pub supported_transforms: vk::SurfaceTransformFlagsKHR,
+	pub plane_reorder_possible: bool,
+	pub persistent_content: bool
+}
+impl From<vk::DisplayPropertiesKHR> for DisplayProperties {
+	fn from(value: vk::DisplayPropertiesKHR) -> Self {
+		let name = if value.display_name.is_null() {
+			None
+		} else {
+			Some(
+				unsafe { CStr::from_ptr(value.display_name) }
+					.to_string_lossy()
+					.into_owned()
+			)
+		};
+
+		DisplayProperties {
+			display: value.display,
+			name,
+			physical_dimensions: value.physical_dimensions,
+			physical_resolution: value.physical_resolution,
+			supported_transforms: value.supported_transforms,
+			plane_reorder_possible: value.plane_reorder_possible == vk::TRUE,
+			persistent_content: value.persistent_content == vk::TRUE
+		}
+	}
+}
+
+/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceDisplayPropertiesKHR.html>.
+pub fn displays(physical_device: &PhysicalDevice) -> Result<Vec<DisplayProperties>, DisplayQueryError> {
+	let properties = unsafe {
+		physical_device
+			.instance()
+			.ext()
+			.display()
+			.get_physical_device_display_properties(*physical_device.deref())?
+	};
+
+	Ok(properties.into_iter().map(DisplayProperties::from).collect())
+}
+
+/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetDisplayModePropertiesKHR.html>.
+///
+/// `display` must be a display of `physical_device`, see `displays`.
+pub fn display_modes(physical_device: &PhysicalDevice, display: vk::DisplayKHR) -> Result<Vec<vk::DisplayModePropertiesKHR>, DisplayQueryError> {
+	let modes = unsafe {
+		physical_device
+			.instance()
+			.ext()
+			.display()
+			.get_display_mode_properties(*physical_device.deref(), display)?
+	};
+
+	Ok(modes)
+}
+
+/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceDisplayPlanePropertiesKHR.html>.
+///
+/// The index of a plane in the returned vector is its plane index.
+pub fn display_planes(physical_device: &PhysicalDevice) -> Result<Vec<vk::DisplayPlanePropertiesKHR>, DisplayQueryError> {
+	let planes = unsafe {
+		physical_device
+			.instance()
+			.ext()
+			.display()
+			.get_physical_device_display_plane_properties(*physical_device.deref())?
+	};
+
+	Ok(planes)
+}
+
+/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetDisplayPlaneSupportedDisplaysKHR.html>.
+pub fn plane_supported_displays(physical_device: &PhysicalDevice, plane_index: u32) -> Result<Vec<vk::DisplayKHR>, DisplayQueryError> {
+	let displays = unsafe {
+		physical_device
+			.instance()
+			.ext()
+			.display()
+			.get_display_plane_supported_displays(*physical_device.deref(), plane_index)?
+	};
+
+	Ok(displays)
+}
+
+/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetDisplayPlaneCapabilitiesKHR.html>.
+pub fn plane_capabilities(
+	physical_device: &PhysicalDevice,
+	mode: vk::DisplayModeKHR,
+	plane_index: u32
+) -> Result<vk::DisplayPlaneCapabilitiesKHR, DisplayQueryError> {
+	let capabilities = unsafe {
+		physical_device
+			.instance()
+			.ext()
+			.display()
+			.get_display_plane_capabilities(*physical_device.deref(), mode, plane_index)?
+	};
+
+	Ok(capabilities)
+}
+
+/// Parameters of a display plane surface.
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayPlaneSurfaceParams {
+	/// Mode of the display to present to, see `display_modes`.
+	pub mode: vk::DisplayModeKHR,
+	pub plane_index: u32,
+	pub plane_stack_index: u32,
+	pub transform: vk::SurfaceTransformFlagsKHR,
+	pub global_alpha: f32,
+	pub alpha_mode: vk::DisplayPlaneAlphaFlagsKHR,
+	pub image_extent: vk::Extent2D
+}
+impl DisplayPlaneSurfaceParams {
+	/// Creates parameters for an opaque surface covering the visible region of `mode` on `plane_index`.
+	pub fn new(mode: &vk::DisplayModePropertiesKHR, plane_index: u32, plane_stack_index: u32) -> Self {
+		DisplayPlaneSurfaceParams {
+			mode: mode.display_mode,
+			plane_index,
+			plane_stack_index,
+			transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
+			global_alpha: 1.0,
+			alpha_mode: vk::DisplayPlaneAlphaFlagsKHR::OPAQUE,
+			image_extent: mode.parameters.visible_region
+		}
+	}
+}
+
+impl Surface {
+	/// Creates a new surface presenting to a display plane.
+	///
+	/// The instance must have been created with `VK_KHR_display` enabled.
+	pub fn display_plane(
+		instance: Vrc<Instance>,
+		params: DisplayPlaneSurfaceParams,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, error::SurfaceError> {
+		let create_info = vk::DisplaySurfaceCreateInfoKHR::builder()
+			.display_mode(params.mode)
+			.plane_index(params.plane_index)
+			.plane_stack_index(params.plane_stack_index)
+			.transform(params.transform)
+			.global_alpha(params.global_alpha)
+			.alpha_mode(params.alpha_mode)
+			.image_extent(params.image_extent);
+
+		log_trace_common!(
+			"Creating display plane surface:",
+			instance,
+			create_info.deref(),
+			host_memory_allocator
+		);
+		unsafe {
+			let surface = instance
+				.ext()
+				.display()
+				.create_display_plane_surface(&create_info, host_memory_allocator.as_ref())?;
+
+			Ok(Self::from_existing(
+				instance,
+				surface,
+				host_memory_allocator
+			))
+		}
+	}
+}
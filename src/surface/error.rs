@@ -6,6 +6,9 @@ vk_result_error! {
 			ERROR_OUT_OF_DEVICE_MEMORY,
 			ERROR_NATIVE_WINDOW_IN_USE_KHR
 		}
+
+		#[error("The combination of display and window handles is not supported")]
+		UnsupportedPlatform,
 	}
 }
 
@@ -2,18 +2,21 @@
 
 use std::{
 	fmt::{Debug, Formatter},
-	ops::Deref
+	ops::{Deref, DerefMut}
 };
 
 use ash::vk;
 
 use crate::prelude::{HasHandle, HostMemoryAllocator, Instance, PhysicalDevice, Vrc};
 
+pub mod display;
 pub mod error;
+pub mod negotiate;
+#[cfg(feature = "raw_window_handle")]
+pub mod raw_window;
 
 pub struct Surface {
 	instance: Vrc<Instance>,
-	loader: ash::extensions::khr::Surface,
 	surface: ash::vk::SurfaceKHR,
 
 	host_memory_allocator: HostMemoryAllocator
@@ -26,18 +29,42 @@ impl Surface {
 	/// `instance` must be a parent of `surface`.
 	/// `surface` must be a valid surface handle for the whole lifetime of this object.
 	pub unsafe fn from_existing(instance: Vrc<Instance>, surface: vk::SurfaceKHR, host_memory_allocator: HostMemoryAllocator) -> Self {
-		let loader = ash::extensions::khr::Surface::new(
-			instance.entry().deref(),
-			instance.deref().deref()
-		);
-
 		log_trace_common!(
 			"Creating surface from existing handle:",
 			instance,
 			surface,
 			host_memory_allocator
 		);
-		Surface { instance, loader, surface, host_memory_allocator }
+		instance.child_registry().register(surface);
+
+		Surface { instance, surface, host_memory_allocator }
+	}
+
+	/// Creates a new surface not associated with any window system.
+	///
+	/// Presenting to a headless surface has no visible effect, which is useful for testing presentation without a display.
+	/// The instance must have been created with `VK_EXT_headless_surface` enabled.
+	pub fn headless(instance: Vrc<Instance>, host_memory_allocator: HostMemoryAllocator) -> Result<Self, error::SurfaceError> {
+		let create_info = vk::HeadlessSurfaceCreateInfoEXT::builder();
+
+		log_trace_common!(
+			"Creating headless surface:",
+			instance,
+			create_info.deref(),
+			host_memory_allocator
+		);
+		unsafe {
+			let surface = instance
+				.ext()
+				.headless_surface()
+				.create_headless_surface(&create_info, host_memory_allocator.as_ref())?;
+
+			Ok(Self::from_existing(
+				instance,
+				surface,
+				host_memory_allocator
+			))
+		}
 	}
 
 	/// Queries whether the given queue on the given physical device supports this surface.
@@ -51,7 +78,7 @@ impl Surface {
 		}
 
 		let supported = unsafe {
-			self.loader.get_physical_device_surface_support(
+			self.loader().get_physical_device_surface_support(
 				*physical_device.deref(),
 				queue_family_index,
 				self.surface
@@ -67,7 +94,7 @@ impl Surface {
 		physical_device: &PhysicalDevice
 	) -> Result<Vec<vk::PresentModeKHR>, error::SurfaceQueryError> {
 		let modes = unsafe {
-			self.loader
+			self.loader()
 				.get_physical_device_surface_present_modes(*physical_device.deref(), self.surface)?
 		};
 
@@ -80,7 +107,7 @@ impl Surface {
 		physical_device: &PhysicalDevice
 	) -> Result<vk::SurfaceCapabilitiesKHR, error::SurfaceQueryError> {
 		let capabilities = unsafe {
-			self.loader
+			self.loader()
 				.get_physical_device_surface_capabilities(*physical_device.deref(), self.surface)?
 		};
 
@@ -90,19 +117,51 @@ impl Surface {
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceSurfaceFormatsKHR.html>.
 	pub fn physical_device_surface_formats(&self, physical_device: &PhysicalDevice) -> Result<Vec<vk::SurfaceFormatKHR>, error::SurfaceQueryError> {
 		let formats = unsafe {
-			self.loader
+			self.loader()
 				.get_physical_device_surface_formats(*physical_device.deref(), self.surface)?
 		};
 
 		Ok(formats)
 	}
 
+	/// Queries whether full-screen exclusive mode with `full_screen` parameters is supported on `physical_device`.
+	///
+	/// `VK_KHR_get_surface_capabilities2` must be enabled on the instance, see `swapchain::full_screen`.
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkGetPhysicalDeviceSurfaceCapabilities2KHR.html>.
+	pub fn physical_device_full_screen_exclusive_supported(
+		&self,
+		physical_device: &PhysicalDevice,
+		full_screen: crate::swapchain::full_screen::FullScreenExclusiveInfo
+	) -> Result<bool, error::SurfaceQueryError> {
+		let mut full_screen_chain = full_screen.chain();
+		let surface_info = vk::PhysicalDeviceSurfaceInfo2KHR::builder()
+			.surface(self.surface)
+			.push_next(full_screen_chain.link());
+
+		let mut full_screen_capabilities = vk::SurfaceCapabilitiesFullScreenExclusiveEXT::default();
+		let mut capabilities = vk::SurfaceCapabilities2KHR::builder().push_next(&mut full_screen_capabilities);
+
+		// ash does not allow extending the returned capabilities, so the function pointer is called directly
+		unsafe {
+			(self.instance.ext().get_surface_capabilities2().fp().get_physical_device_surface_capabilities2_khr)(
+				*physical_device.deref(),
+				surface_info.deref(),
+				capabilities.deref_mut()
+			)
+			.result()?;
+		}
+
+		Ok(full_screen_capabilities.full_screen_exclusive_supported == vk::TRUE)
+	}
+
 	pub const fn instance(&self) -> &Vrc<Instance> {
 		&self.instance
 	}
 
-	pub const fn loader(&self) -> &ash::extensions::khr::Surface {
-		&self.loader
+	/// Returns the shared surface loader of the instance, see `Instance::ext`.
+	pub fn loader(&self) -> &ash::extensions::khr::Surface {
+		self.instance.ext().surface()
 	}
 }
 impl_common_handle_traits! {
@@ -113,9 +172,10 @@ impl_common_handle_traits! {
 impl Drop for Surface {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self);
+		self.instance.child_registry().unregister(self.surface);
 
 		unsafe {
-			self.loader.destroy_surface(
+			self.loader().destroy_surface(
 				self.surface,
 				self.host_memory_allocator.as_ref()
 			);
@@ -126,10 +186,6 @@ impl Debug for Surface {
 	fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
 		f.debug_struct("Surface")
 			.field("instance", &self.instance)
-			.field(
-				"loader",
-				&"<ash::extensions::khr::Surface>"
-			)
 			.field("surface", &self.safe_handle())
 			.field(
 				"host_memory_allocator",
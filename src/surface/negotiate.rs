@@ -0,0 +1,231 @@
+//! Negotiation of swapchain parameters with the surface capabilities of a physical device.
+//!
+//! `SurfaceNegotiator` queries the supported formats, present modes and capabilities once and chooses the best
+//! supported parameters for given preferences. `swapchain_create_info` combines the choices into a `SwapchainCreateInfo`.
+
+use std::num::NonZeroU32;
+
+use ash::vk;
+
+use super::{error::SurfaceQueryError, Surface};
+use crate::{
+	prelude::{ImageSize, MipmapLevels, PhysicalDevice, SharingMode},
+	swapchain::{image::SwapchainCreateImageInfo, SwapchainCreateInfo}
+};
+
+/// Preference between tearing, latency and power usage when choosing a present mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VsyncPreference {
+	/// Never tear, `FIFO`.
+	Vsync,
+	/// Tear only when a frame is late, `FIFO_RELAXED`, falling back to `FIFO`.
+	Adaptive,
+	/// Never tear but always display the latest frame, `MAILBOX`, falling back to `FIFO`.
+	LowLatency,
+	/// Present immediately, `IMMEDIATE`, falling back to `MAILBOX` and then `FIFO`.
+	Off
+}
+impl VsyncPreference {
+	/// Present modes in order of preference. `FIFO` is always last since it is always supported.
+	pub fn present_modes(self) -> &'static [vk::PresentModeKHR] {
+		match self {
+			VsyncPreference::Vsync => &[vk::PresentModeKHR::FIFO],
+			VsyncPreference::Adaptive => &[vk::PresentModeKHR::FIFO_RELAXED, vk::PresentModeKHR::FIFO],
+			VsyncPreference::LowLatency => &[vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::FIFO],
+			VsyncPreference::Off => &[
+				vk::PresentModeKHR::IMMEDIATE,
+				vk::PresentModeKHR::MAILBOX,
+				vk::PresentModeKHR::FIFO
+			]
+		}
+	}
+}
+impl Default for VsyncPreference {
+	fn default() -> Self {
+		VsyncPreference::Vsync
+	}
+}
+
+/// Supported surface parameters of a physical device, see the module documentation.
+#[derive(Debug, Clone)]
+pub struct SurfaceNegotiator {
+	pub capabilities: vk::SurfaceCapabilitiesKHR,
+	pub formats: Vec<vk::SurfaceFormatKHR>,
+	pub present_modes: Vec<vk::PresentModeKHR>
+}
+impl SurfaceNegotiator {
+	/// Queries the supported parameters of `surface` on `physical_device`.
+	pub fn query(surface: &Surface, physical_device: &PhysicalDevice) -> Result<Self, SurfaceQueryError> {
+		Ok(SurfaceNegotiator {
+			capabilities: surface.physical_device_surface_capabilities(physical_device)?,
+			formats: surface.physical_device_surface_formats(physical_device)?,
+			present_modes: surface.physical_device_surface_present_modes(physical_device)?
+		})
+	}
+
+	/// Returns the first format from `preferences` that is supported.
+	///
+	/// If none of them is supported, returns the first supported format. Returns `None` only if no formats are supported.
+	pub fn choose_surface_format(&self, preferences: &[vk::SurfaceFormatKHR]) -> Option<vk::SurfaceFormatKHR> {
+		// A single undefined format means there are no restrictions
+		if let [only] = self.formats.as_slice() {
+			if only.format == vk::Format::UNDEFINED {
+				return preferences.first().copied()
+			}
+		}
+
+		preferences
+			.iter()
+			.find(|preferred| self.formats.contains(preferred))
+			.or_else(|| self.formats.first())
+			.copied()
+	}
+
+	/// Returns the first supported present mode for `vsync_preference`.
+	pub fn choose_present_mode(&self, vsync_preference: VsyncPreference) -> vk::PresentModeKHR {
+		vsync_preference
+			.present_modes()
+			.iter()
+			.copied()
+			.find(|mode| self.present_modes.contains(mode))
+			.unwrap_or(vk::PresentModeKHR::FIFO)
+	}
+
+	/// Returns the extent of swapchain images for a window of `window_size`.
+	///
+	/// If the surface dictates its current extent, that extent is used instead of `window_size`, otherwise
+	/// `window_size` is clamped to the supported range. Returns `None` if the extent is zero, for example when the window is minimized.
+	pub fn choose_extent(&self, window_size: [NonZeroU32; 2]) -> Option<[NonZeroU32; 2]> {
+		let capabilities = &self.capabilities;
+
+		let extent = if capabilities.current_extent.width != std::u32::MAX {
+			capabilities.current_extent
+		} else {
+			vk::Extent2D {
+				width: window_size[0].get().max(capabilities.min_image_extent.width).min(capabilities.max_image_extent.width),
+				height: window_size[1].get().max(capabilities.min_image_extent.height).min(capabilities.max_image_extent.height)
+			}
+		};
+
+		Some([
+			NonZeroU32::new(extent.width)?,
+			NonZeroU32::new(extent.height)?
+		])
+	}
+
+	/// Returns `desired` image count clamped to the supported range.
+	pub fn choose_image_count(&self, desired: NonZeroU32) -> NonZeroU32 {
+		let mut count = desired.get().max(self.capabilities.min_image_count);
+		if self.capabilities.max_image_count != 0 {
+			count = count.min(self.capabilities.max_image_count);
+		}
+
+		NonZeroU32::new(count).unwrap_or(desired)
+	}
+
+	/// Returns `OPAQUE` if supported, otherwise the first supported composite alpha mode.
+	pub fn choose_composite_alpha(&self) -> vk::CompositeAlphaFlagsKHR {
+		let supported = self.capabilities.supported_composite_alpha;
+
+		[
+			vk::CompositeAlphaFlagsKHR::OPAQUE,
+			vk::CompositeAlphaFlagsKHR::INHERIT,
+			vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+			vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED
+		]
+		.iter()
+		.copied()
+		.find(|alpha| supported.contains(*alpha))
+		.unwrap_or(vk::CompositeAlphaFlagsKHR::OPAQUE)
+	}
+
+	/// Returns a create info with the chosen format, present mode, extent and image count.
+	///
+	/// The current transform of the surface is used as the pre-transform. `image_count` is the desired number of images,
+	/// usually one more than the number of frames in flight.
+	///
+	/// Returns `None` if no formats are supported or the extent is zero.
+	pub fn swapchain_create_info<A: AsRef<[u32]>>(
+		&self,
+		format_preferences: &[vk::SurfaceFormatKHR],
+		vsync_preference: VsyncPreference,
+		window_size: [NonZeroU32; 2],
+		image_count: NonZeroU32,
+		image_usage: vk::ImageUsageFlags,
+		sharing_mode: SharingMode<A>
+	) -> Option<SwapchainCreateInfo<A>> {
+		let format = self.choose_surface_format(format_preferences)?;
+		let [width, height] = self.choose_extent(window_size)?;
+
+		Some(SwapchainCreateInfo {
+			image_info: SwapchainCreateImageInfo {
+				min_image_count: self.choose_image_count(image_count),
+				image_format: format.format,
+				image_color_space: format.color_space,
+				image_size: ImageSize::new_2d(
+					width,
+					height,
+					NonZeroU32::new(1).unwrap(),
+					MipmapLevels::One()
+				),
+				image_usage
+			},
+			sharing_mode,
+			pre_transform: self.capabilities.current_transform,
+			composite_alpha: self.choose_composite_alpha(),
+			present_mode: self.choose_present_mode(vsync_preference),
+			clipped: true
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::num::NonZeroU32;
+
+	use ash::vk;
+
+	use super::{SurfaceNegotiator, VsyncPreference};
+
+	fn negotiator() -> SurfaceNegotiator {
+		SurfaceNegotiator {
+			capabilities: vk::SurfaceCapabilitiesKHR {
+				min_image_count: 2,
+				max_image_count: 3,
+				current_extent: vk::Extent2D { width: std::u32::MAX, height: std::u32::MAX },
+				min_image_extent: vk::Extent2D { width: 1, height: 1 },
+				max_image_extent: vk::Extent2D { width: 1024, height: 1024 },
+				..Default::default()
+			},
+			formats: vec![vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_UNORM, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR }],
+			present_modes: vec![vk::PresentModeKHR::FIFO, vk::PresentModeKHR::MAILBOX]
+		}
+	}
+
+	#[test]
+	fn negotiate() {
+		let negotiator = negotiator();
+
+		let preferred = vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_SRGB, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR };
+		assert_eq!(
+			negotiator.choose_surface_format(&[preferred]).unwrap().format,
+			vk::Format::B8G8R8A8_UNORM
+		);
+		assert_eq!(
+			negotiator.choose_present_mode(VsyncPreference::Off),
+			vk::PresentModeKHR::MAILBOX
+		);
+		assert_eq!(
+			negotiator.choose_present_mode(VsyncPreference::Adaptive),
+			vk::PresentModeKHR::FIFO
+		);
+		assert_eq!(
+			negotiator.choose_extent([NonZeroU32::new(2048).unwrap(), NonZeroU32::new(600).unwrap()]),
+			Some([NonZeroU32::new(1024).unwrap(), NonZeroU32::new(600).unwrap()])
+		);
+		assert_eq!(
+			negotiator.choose_image_count(NonZeroU32::new(4).unwrap()).get(),
+			3
+		);
+	}
+}
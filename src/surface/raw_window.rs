@@ -0,0 +1,148 @@
+//! Surface creation from `raw-window-handle` compatible handles.
+
+use std::{ffi::CStr, ops::Deref};
+
+use ash::{
+	extensions::{ext, khr},
+	vk
+};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle};
+
+use super::{error, Surface};
+use crate::prelude::{HostMemoryAllocator, Instance, Vrc};
+
+impl Surface {
+	/// Creates a new surface from raw display and window handles.
+	///
+	/// Supports Win32, Xlib, Xcb, Wayland, Android and Metal (AppKit and UiKit) platforms.
+	///
+	/// The instance must have been created with the extensions returned by [`Surface::required_extensions`](#method.required_extensions).
+	///
+	/// ### Safety
+	///
+	/// `display_handle` and `window_handle` must be valid handles and must outlive the created surface.
+	pub unsafe fn from_raw_handles(
+		instance: Vrc<Instance>,
+		display_handle: RawDisplayHandle,
+		window_handle: RawWindowHandle,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, error::SurfaceError> {
+		log_trace_common!(
+			"Creating surface from raw handles:",
+			instance,
+			display_handle,
+			window_handle,
+			host_memory_allocator
+		);
+
+		let entry = instance.entry().deref();
+		let surface = match (display_handle, window_handle) {
+			(RawDisplayHandle::Windows(_), RawWindowHandle::Win32(window)) => {
+				let create_info = vk::Win32SurfaceCreateInfoKHR::builder()
+					.hinstance(window.hinstance)
+					.hwnd(window.hwnd);
+
+				khr::Win32Surface::new(entry, instance.deref().deref()).create_win32_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			(RawDisplayHandle::Xlib(display), RawWindowHandle::Xlib(window)) => {
+				let create_info = vk::XlibSurfaceCreateInfoKHR::builder()
+					.dpy(display.display as *mut _)
+					.window(window.window);
+
+				khr::XlibSurface::new(entry, instance.deref().deref()).create_xlib_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			(RawDisplayHandle::Xcb(display), RawWindowHandle::Xcb(window)) => {
+				let create_info = vk::XcbSurfaceCreateInfoKHR::builder()
+					.connection(display.connection)
+					.window(window.window);
+
+				khr::XcbSurface::new(entry, instance.deref().deref()).create_xcb_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			(RawDisplayHandle::Wayland(display), RawWindowHandle::Wayland(window)) => {
+				let create_info = vk::WaylandSurfaceCreateInfoKHR::builder()
+					.display(display.display)
+					.surface(window.surface);
+
+				khr::WaylandSurface::new(entry, instance.deref().deref())
+					.create_wayland_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			(RawDisplayHandle::Android(_), RawWindowHandle::AndroidNdk(window)) => {
+				let create_info = vk::AndroidSurfaceCreateInfoKHR::builder().window(window.a_native_window);
+
+				khr::AndroidSurface::new(entry, instance.deref().deref())
+					.create_android_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			#[cfg(target_os = "macos")]
+			(RawDisplayHandle::AppKit(_), RawWindowHandle::AppKit(window)) => {
+				let layer = match raw_window_metal::appkit::metal_layer_from_handle(window) {
+					raw_window_metal::Layer::Existing(layer) | raw_window_metal::Layer::Allocated(layer) => layer,
+					raw_window_metal::Layer::None => return Err(error::SurfaceError::UnsupportedPlatform)
+				};
+				let create_info = vk::MetalSurfaceCreateInfoEXT::builder().layer(&*(layer as *const vk::CAMetalLayer));
+
+				ext::MetalSurface::new(entry, instance.deref().deref()).create_metal_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			#[cfg(target_os = "ios")]
+			(RawDisplayHandle::UiKit(_), RawWindowHandle::UiKit(window)) => {
+				let layer = match raw_window_metal::uikit::metal_layer_from_handle(window) {
+					raw_window_metal::Layer::Existing(layer) | raw_window_metal::Layer::Allocated(layer) => layer,
+					raw_window_metal::Layer::None => return Err(error::SurfaceError::UnsupportedPlatform)
+				};
+				let create_info = vk::MetalSurfaceCreateInfoEXT::builder().layer(&*(layer as *const vk::CAMetalLayer));
+
+				ext::MetalSurface::new(entry, instance.deref().deref()).create_metal_surface(&create_info, host_memory_allocator.as_ref())?
+			}
+
+			_ => return Err(error::SurfaceError::UnsupportedPlatform)
+		};
+
+		Ok(Surface::from_existing(
+			instance,
+			surface,
+			host_memory_allocator
+		))
+	}
+
+	/// Creates a new surface for `window`.
+	///
+	/// This is a shorthand for [`Surface::from_raw_handles`](#method.from_raw_handles) with the handles of `window`.
+	///
+	/// ### Safety
+	///
+	/// `window` must outlive the created surface.
+	pub unsafe fn from_window<W: HasRawDisplayHandle + HasRawWindowHandle>(
+		instance: Vrc<Instance>,
+		window: &W,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, error::SurfaceError> {
+		Self::from_raw_handles(
+			instance,
+			window.raw_display_handle(),
+			window.raw_window_handle(),
+			host_memory_allocator
+		)
+	}
+
+	/// Returns instance extensions required to create a surface for the given display handle.
+	///
+	/// Returns `None` if the platform is not supported by [`Surface::from_raw_handles`](#method.from_raw_handles).
+	pub fn required_extensions(display_handle: RawDisplayHandle) -> Option<[&'static CStr; 2]> {
+		let platform_extension = match display_handle {
+			RawDisplayHandle::Windows(_) => khr::Win32Surface::name(),
+			RawDisplayHandle::Xlib(_) => khr::XlibSurface::name(),
+			RawDisplayHandle::Xcb(_) => khr::XcbSurface::name(),
+			RawDisplayHandle::Wayland(_) => khr::WaylandSurface::name(),
+			RawDisplayHandle::Android(_) => khr::AndroidSurface::name(),
+			RawDisplayHandle::AppKit(_) | RawDisplayHandle::UiKit(_) => ext::MetalSurface::name(),
+			_ => return None
+		};
+
+		Some([khr::Surface::name(), platform_extension])
+	}
+}
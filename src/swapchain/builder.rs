@@ -0,0 +1,269 @@
+//! Validation of `SwapchainCreateInfo` against surface capabilities and a builder fed by them.
+
+use std::num::NonZeroU32;
+
+use ash::vk;
+
+use super::{error::SwapchainCreateInfoError, image::SwapchainCreateImageInfo, SwapchainCreateInfo};
+use crate::{
+	prelude::{ImageSize, MipmapLevels, PhysicalDevice, SharingMode},
+	surface::{negotiate::SurfaceNegotiator, Surface}
+};
+
+impl<A: AsRef<[u32]>> SwapchainCreateInfo<A> {
+	/// Checks that this create info is supported by `surface` on `physical_device`.
+	///
+	/// Queries the surface capabilities, see `validate_with` to reuse an existing `SurfaceNegotiator`.
+	pub fn validate(&self, surface: &Surface, physical_device: &PhysicalDevice) -> Result<(), SwapchainCreateInfoError> {
+		self.validate_with(&SurfaceNegotiator::query(surface, physical_device)?)
+	}
+
+	/// Checks that the format, image count, extent, usage, pre-transform, composite alpha and present mode are supported.
+	pub fn validate_with(&self, negotiator: &SurfaceNegotiator) -> Result<(), SwapchainCreateInfoError> {
+		let capabilities = &negotiator.capabilities;
+		let image_info = &self.image_info;
+
+		let format = vk::SurfaceFormatKHR { format: image_info.image_format, color_space: image_info.image_color_space };
+		let unrestricted_format = matches!(negotiator.formats.as_slice(), [only] if only.format == vk::Format::UNDEFINED);
+		if !unrestricted_format && !negotiator.formats.contains(&format) {
+			return Err(SwapchainCreateInfoError::FormatNotSupported(format))
+		}
+
+		let count = image_info.min_image_count.get();
+		if count < capabilities.min_image_count || (capabilities.max_image_count != 0 && count > capabilities.max_image_count) {
+			return Err(SwapchainCreateInfoError::ImageCountOutOfRange {
+				count,
+				min: capabilities.min_image_count,
+				max: capabilities.max_image_count
+			})
+		}
+
+		let size = ImageSize::from(image_info.image_size);
+		let extent: vk::Extent3D = size.into();
+		if extent.width < capabilities.min_image_extent.width
+			|| extent.height < capabilities.min_image_extent.height
+			|| extent.width > capabilities.max_image_extent.width
+			|| extent.height > capabilities.max_image_extent.height
+		{
+			return Err(SwapchainCreateInfoError::ExtentOutOfRange(vk::Extent2D { width: extent.width, height: extent.height }))
+		}
+		if size.array_layers().get() > capabilities.max_image_array_layers {
+			return Err(SwapchainCreateInfoError::ArrayLayersOutOfRange(size.array_layers().get()))
+		}
+
+		if !capabilities.supported_usage_flags.contains(image_info.image_usage) {
+			return Err(SwapchainCreateInfoError::UsageNotSupported(image_info.image_usage))
+		}
+		if self.pre_transform.as_raw().count_ones() != 1 || !capabilities.supported_transforms.contains(self.pre_transform) {
+			return Err(SwapchainCreateInfoError::PreTransformNotSupported(self.pre_transform))
+		}
+		if self.composite_alpha.as_raw().count_ones() != 1 || !capabilities.supported_composite_alpha.contains(self.composite_alpha) {
+			return Err(SwapchainCreateInfoError::CompositeAlphaNotSupported(self.composite_alpha))
+		}
+		if !negotiator.present_modes.contains(&self.present_mode) {
+			return Err(SwapchainCreateInfoError::PresentModeNotSupported(self.present_mode))
+		}
+
+		Ok(())
+	}
+}
+
+/// Builder for `SwapchainCreateInfo` with defaults taken from the surface capabilities in a `SurfaceNegotiator`.
+///
+/// With `runtime_implicit_validations`, `build` clamps the image count and extent into the supported ranges, removes
+/// unsupported usage flags and replaces an unsupported pre-transform, composite alpha or present mode with a supported one.
+/// Each adjustment is logged as a warning.
+#[derive(Debug, Clone)]
+pub struct SwapchainCreateInfoBuilder<'n, A: AsRef<[u32]>> {
+	#[cfg_attr(not(feature = "runtime_implicit_validations"), allow(dead_code))]
+	negotiator: &'n SurfaceNegotiator,
+	create_info: SwapchainCreateInfo<A>
+}
+impl<'n, A: AsRef<[u32]>> SwapchainCreateInfoBuilder<'n, A> {
+	/// Creates a builder for images of `surface_format` and `extent`.
+	///
+	/// Defaults to one image more than the supported minimum, `COLOR_ATTACHMENT` usage, the current transform of the surface,
+	/// composite alpha chosen by `SurfaceNegotiator::choose_composite_alpha`, `FIFO` present mode and clipping enabled.
+	pub fn new(negotiator: &'n SurfaceNegotiator, surface_format: vk::SurfaceFormatKHR, extent: [NonZeroU32; 2], sharing_mode: SharingMode<A>) -> Self {
+		let min_image_count = NonZeroU32::new(negotiator.capabilities.min_image_count + 1).unwrap();
+
+		SwapchainCreateInfoBuilder {
+			negotiator,
+			create_info: SwapchainCreateInfo {
+				image_info: SwapchainCreateImageInfo {
+					min_image_count: negotiator.choose_image_count(min_image_count),
+					image_format: surface_format.format,
+					image_color_space: surface_format.color_space,
+					image_size: ImageSize::new_2d(
+						extent[0],
+						extent[1],
+						NonZeroU32::new(1).unwrap(),
+						MipmapLevels::One()
+					),
+					image_usage: vk::ImageUsageFlags::COLOR_ATTACHMENT
+				},
+				sharing_mode,
+				pre_transform: negotiator.capabilities.current_transform,
+				composite_alpha: negotiator.choose_composite_alpha(),
+				present_mode: vk::PresentModeKHR::FIFO,
+				clipped: true
+			}
+		}
+	}
+
+	pub fn image_count(mut self, image_count: NonZeroU32) -> Self {
+		self.create_info.image_info.min_image_count = image_count;
+		self
+	}
+
+	pub fn image_usage(mut self, image_usage: vk::ImageUsageFlags) -> Self {
+		self.create_info.image_info.image_usage = image_usage;
+		self
+	}
+
+	pub fn pre_transform(mut self, pre_transform: vk::SurfaceTransformFlagsKHR) -> Self {
+		self.create_info.pre_transform = pre_transform;
+		self
+	}
+
+	pub fn composite_alpha(mut self, composite_alpha: vk::CompositeAlphaFlagsKHR) -> Self {
+		self.create_info.composite_alpha = composite_alpha;
+		self
+	}
+
+	pub fn present_mode(mut self, present_mode: vk::PresentModeKHR) -> Self {
+		self.create_info.present_mode = present_mode;
+		self
+	}
+
+	pub fn clipped(mut self, clipped: bool) -> Self {
+		self.create_info.clipped = clipped;
+		self
+	}
+
+	/// Returns the create info, adjusted to the surface capabilities with `runtime_implicit_validations`.
+	#[cfg_attr(not(feature = "runtime_implicit_validations"), allow(unused_mut))]
+	pub fn build(mut self) -> SwapchainCreateInfo<A> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		self.clamp_to_capabilities();
+
+		self.create_info
+	}
+
+	#[cfg(feature = "runtime_implicit_validations")]
+	fn clamp_to_capabilities(&mut self) {
+		let negotiator = self.negotiator;
+		let capabilities = &negotiator.capabilities;
+		let image_info = &mut self.create_info.image_info;
+
+		let image_count = negotiator.choose_image_count(image_info.min_image_count);
+		if image_count != image_info.min_image_count {
+			log::warn!(
+				"Swapchain image count {} clamped to {}",
+				image_info.min_image_count,
+				image_count
+			);
+			image_info.min_image_count = image_count;
+		}
+
+		let size = ImageSize::from(image_info.image_size);
+		let extent: vk::Extent3D = size.into();
+		let clamped = [
+			extent.width.max(capabilities.min_image_extent.width).min(capabilities.max_image_extent.width),
+			extent.height.max(capabilities.min_image_extent.height).min(capabilities.max_image_extent.height)
+		];
+		if let [Some(width), Some(height)] = [NonZeroU32::new(clamped[0]), NonZeroU32::new(clamped[1])] {
+			if clamped != [extent.width, extent.height] {
+				log::warn!(
+					"Swapchain extent {}x{} clamped to {}x{}",
+					extent.width,
+					extent.height,
+					width,
+					height
+				);
+				image_info.image_size = ImageSize::new_2d(width, height, size.array_layers(), MipmapLevels::One());
+			}
+		}
+
+		let unsupported_usage = image_info.image_usage & !capabilities.supported_usage_flags;
+		if !unsupported_usage.is_empty() {
+			log::warn!("Unsupported swapchain image usage {:?} removed", unsupported_usage);
+			image_info.image_usage &= capabilities.supported_usage_flags;
+		}
+
+		let create_info = &mut self.create_info;
+		if create_info.pre_transform.as_raw().count_ones() != 1 || !capabilities.supported_transforms.contains(create_info.pre_transform) {
+			log::warn!(
+				"Unsupported swapchain pre-transform {:?} replaced by {:?}",
+				create_info.pre_transform,
+				capabilities.current_transform
+			);
+			create_info.pre_transform = capabilities.current_transform;
+		}
+		if create_info.composite_alpha.as_raw().count_ones() != 1 || !capabilities.supported_composite_alpha.contains(create_info.composite_alpha) {
+			let composite_alpha = negotiator.choose_composite_alpha();
+			log::warn!(
+				"Unsupported swapchain composite alpha {:?} replaced by {:?}",
+				create_info.composite_alpha,
+				composite_alpha
+			);
+			create_info.composite_alpha = composite_alpha;
+		}
+		if !negotiator.present_modes.contains(&create_info.present_mode) {
+			log::warn!(
+				"Unsupported swapchain present mode {:?} replaced by FIFO",
+				create_info.present_mode
+			);
+			create_info.present_mode = vk::PresentModeKHR::FIFO;
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::num::NonZeroU32;
+
+	use ash::vk;
+
+	use super::SwapchainCreateInfoBuilder;
+	use crate::{prelude::SharingMode, surface::negotiate::SurfaceNegotiator, swapchain::error::SwapchainCreateInfoError};
+
+	#[test]
+	fn validate_and_build() {
+		let format = vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_UNORM, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR };
+		let negotiator = SurfaceNegotiator {
+			capabilities: vk::SurfaceCapabilitiesKHR {
+				min_image_count: 2,
+				max_image_count: 3,
+				current_extent: vk::Extent2D { width: std::u32::MAX, height: std::u32::MAX },
+				min_image_extent: vk::Extent2D { width: 1, height: 1 },
+				max_image_extent: vk::Extent2D { width: 1024, height: 1024 },
+				max_image_array_layers: 1,
+				supported_transforms: vk::SurfaceTransformFlagsKHR::IDENTITY,
+				current_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
+				supported_composite_alpha: vk::CompositeAlphaFlagsKHR::OPAQUE,
+				supported_usage_flags: vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_DST
+			},
+			formats: vec![format],
+			present_modes: vec![vk::PresentModeKHR::FIFO]
+		};
+		let extent = [NonZeroU32::new(800).unwrap(), NonZeroU32::new(600).unwrap()];
+
+		let builder = SwapchainCreateInfoBuilder::new(&negotiator, format, extent, SharingMode::one(0));
+		assert!(builder.clone().build().validate_with(&negotiator).is_ok());
+
+		let builder = builder.present_mode(vk::PresentModeKHR::MAILBOX).image_count(NonZeroU32::new(8).unwrap());
+		let mut create_info = builder.clone().build();
+		if cfg!(feature = "runtime_implicit_validations") {
+			assert_eq!(create_info.present_mode, vk::PresentModeKHR::FIFO);
+			assert_eq!(create_info.image_info.min_image_count.get(), 3);
+		}
+
+		create_info.image_info.min_image_count = NonZeroU32::new(3).unwrap();
+		create_info.present_mode = vk::PresentModeKHR::MAILBOX;
+		assert!(matches!(
+			create_info.validate_with(&negotiator),
+			Err(SwapchainCreateInfoError::PresentModeNotSupported(vk::PresentModeKHR::MAILBOX))
+		));
+	}
+}
@@ -1,3 +1,8 @@
+use ash::vk;
+use thiserror::Error;
+
+use crate::util::result::VkResultExt;
+
 vk_result_error! {
 	#[derive(Debug)]
 	pub enum SwapchainError {
@@ -13,9 +18,19 @@ vk_result_error! {
 		#[error("Swapchain is retired and can no longer be used")]
 		SwapchainRetired,
 
+		#[error("Surface extent is zero, the swapchain cannot be created until it changes")]
+		SurfaceExtentZero,
+
+		#[error("Could not query the surface")]
+		SurfaceQuery(#[from] crate::surface::error::SurfaceQueryError),
+
 		#[cfg(feature = "runtime_implicit_validations")]
 		#[error("Image usage must not be empty")]
 		ImageUsageEmpty,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Present mode must be contained in the list of switchable present modes")]
+		PresentModeNotInList,
 	}
 }
 
@@ -54,5 +69,178 @@ impl AcquireResultValue {
 			AcquireResultValue::SUCCESS(i) | AcquireResultValue::SUBOPTIMAL_KHR(i) => i
 		}
 	}
+
+	/// Returns whether the swapchain should be recreated even though the image was acquired.
+	pub fn is_suboptimal(&self) -> bool {
+		matches!(self, AcquireResultValue::SUBOPTIMAL_KHR(_))
+	}
+}
+impl AcquireError {
+	/// Returns whether recreating the swapchain resolves this error, see `VkResultExt::is_recoverable_swapchain_issue`.
+	pub fn is_recoverable_swapchain_issue(&self) -> bool {
+		self.vk_result().map_or(false, VkResultExt::is_recoverable_swapchain_issue)
+	}
+}
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum FullScreenExclusiveError {
+		vk {
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_INITIALIZATION_FAILED,
+			ERROR_SURFACE_LOST_KHR
+		}
+
+		#[error("Swapchain is retired and can no longer be used")]
+		SwapchainRetired,
+
+		#[cfg(feature = "runtime_implicit_validations")]
+		#[error("Swapchain must be created with application controlled full-screen exclusive mode")]
+		NotApplicationControlled
+	}
 }
+
 pub type AcquireResult = Result<AcquireResultValue, AcquireError>;
+
+/// Value of a successful acquire, implemented by `AcquireResultValue` and `AcquiredImage`.
+pub trait AcquiredValue {
+	/// Returns the index of the acquired image.
+	fn index(&self) -> u32;
+
+	/// Returns whether the swapchain should be recreated even though the image was acquired.
+	fn is_suboptimal(&self) -> bool;
+}
+impl AcquiredValue for AcquireResultValue {
+	fn index(&self) -> u32 {
+		AcquireResultValue::index(self)
+	}
+
+	fn is_suboptimal(&self) -> bool {
+		AcquireResultValue::is_suboptimal(self)
+	}
+}
+
+/// Outcome of an acquire, reporting whether its synchronization primitives were signaled.
+///
+/// `T` is the value of a successful acquire, `AcquireResultValue` for the raw index or `AcquiredImage` for `SwapchainData::acquire_image`.
+///
+/// The semaphore and fence passed to the acquire are only signaled when an image is acquired, even if the swapchain is suboptimal.
+/// On any error, including `ERROR_OUT_OF_DATE_KHR` and `TIMEOUT`, they are left untouched and can be reused for the next acquire.
+#[derive(Debug)]
+pub enum AcquireOutcome<T = AcquireResultValue> {
+	/// An image was acquired, the synchronization primitives will be signaled once it can be used.
+	Acquired(T),
+	/// No image was acquired, the synchronization primitives were not signaled.
+	NotAcquired(AcquireError)
+}
+impl<T: AcquiredValue> AcquireOutcome<T> {
+	/// Returns the index of the acquired image, if any.
+	pub fn index(&self) -> Option<u32> {
+		match self {
+			AcquireOutcome::Acquired(value) => Some(value.index()),
+			AcquireOutcome::NotAcquired(_) => None
+		}
+	}
+
+	/// Returns whether the semaphore and fence passed to the acquire will be signaled.
+	pub fn synchronization_signaled(&self) -> bool {
+		matches!(self, AcquireOutcome::Acquired(_))
+	}
+
+	/// Returns whether the swapchain should be recreated, either because the acquired image is suboptimal
+	/// or because the acquire failed with an error that recreation resolves.
+	pub fn needs_recreate(&self) -> bool {
+		match self {
+			AcquireOutcome::Acquired(value) => value.is_suboptimal(),
+			AcquireOutcome::NotAcquired(err) => err.is_recoverable_swapchain_issue()
+		}
+	}
+
+	pub fn into_result(self) -> Result<T, AcquireError> {
+		match self {
+			AcquireOutcome::Acquired(value) => Ok(value),
+			AcquireOutcome::NotAcquired(err) => Err(err)
+		}
+	}
+}
+impl<T> From<Result<T, AcquireError>> for AcquireOutcome<T> {
+	fn from(result: Result<T, AcquireError>) -> Self {
+		match result {
+			Ok(value) => AcquireOutcome::Acquired(value),
+			Err(err) => AcquireOutcome::NotAcquired(err)
+		}
+	}
+}
+
+vk_result_error! {
+	#[derive(Debug)]
+	pub enum PresentWaitError {
+		vk {
+			TIMEOUT,
+			ERROR_OUT_OF_HOST_MEMORY,
+			ERROR_OUT_OF_DEVICE_MEMORY,
+			ERROR_DEVICE_LOST,
+			ERROR_OUT_OF_DATE_KHR,
+			ERROR_SURFACE_LOST_KHR,
+			ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT
+		}
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum SwapchainCreateInfoError {
+	#[error("Could not query the surface")]
+	SurfaceQuery(#[from] crate::surface::error::SurfaceQueryError),
+
+	#[error("Surface format {0:?} is not supported")]
+	FormatNotSupported(vk::SurfaceFormatKHR),
+
+	#[error("Image count {count} is outside of the supported range {min}..={max} (0 means unlimited)")]
+	ImageCountOutOfRange { count: u32, min: u32, max: u32 },
+
+	#[error("Image extent {0:?} is outside of the supported range")]
+	ExtentOutOfRange(vk::Extent2D),
+
+	#[error("Image array layer count {0} is larger than the supported maximum")]
+	ArrayLayersOutOfRange(u32),
+
+	#[error("Image usage {0:?} is not supported by the surface")]
+	UsageNotSupported(vk::ImageUsageFlags),
+
+	#[error("Pre-transform {0:?} is not supported by the surface")]
+	PreTransformNotSupported(vk::SurfaceTransformFlagsKHR),
+
+	#[error("Composite alpha {0:?} is not supported by the surface")]
+	CompositeAlphaNotSupported(vk::CompositeAlphaFlagsKHR),
+
+	#[error("Present mode {0:?} is not supported by the surface")]
+	PresentModeNotSupported(vk::PresentModeKHR)
+}
+
+#[derive(Error, Debug)]
+pub enum PresentHandoffError {
+	#[error("Could not create command pool")]
+	CommandPool(#[from] crate::command::error::CommandPoolError),
+	#[error("Could not record ownership acquire")]
+	CommandBuffer(#[from] crate::command::error::CommandBufferError),
+	#[error("Invalid ownership transfer")]
+	OwnershipTransfer(#[from] crate::command::error::OwnershipTransferError),
+	#[error("Could not create semaphore")]
+	Semaphore(#[from] crate::sync::semaphore::error::SemaphoreError),
+	#[error("Fence error")]
+	Fence(#[from] crate::sync::fence::error::FenceError),
+	#[error("Could not submit ownership acquire")]
+	QueueSubmit(#[from] crate::queue::error::QueueSubmitError),
+	#[error("Could not present")]
+	Present(#[from] crate::queue::error::QueuePresentError)
+}
+
+#[derive(Error, Debug)]
+pub enum HeadlessTargetError {
+	#[error("Headless target must have at least one image")]
+	ImagesEmpty,
+	#[error("All images of a headless target must have the same format and size")]
+	ImagesMismatch,
+	#[error("Images must be from the same device as the queue")]
+	ImageQueueDeviceMismatch
+}
@@ -0,0 +1,94 @@
+//! Full-screen exclusive mode using `VK_EXT_full_screen_exclusive`.
+//!
+//! On Windows, full-screen exclusive mode bypasses the compositor for the lowest presentation latency.
+//! The mode is requested when creating the swapchain, see `Swapchain::new_full_screen_exclusive`. With `APPLICATION_CONTROLLED`
+//! the application decides when to enter and leave the exclusive mode using `Swapchain::acquire_full_screen_exclusive_mode`
+//! and `Swapchain::release_full_screen_exclusive_mode`.
+//!
+//! The extension must be enabled on the device and requires `VK_KHR_get_surface_capabilities2` on the instance.
+
+use std::os::raw::c_void;
+
+use ash::vk;
+
+/// Full-screen exclusive parameters of a swapchain or a surface query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FullScreenExclusiveInfo {
+	pub mode: vk::FullScreenExclusiveEXT,
+	/// `HMONITOR` of the monitor the window is on, `0` if not used.
+	///
+	/// Required on Win32 surfaces when `mode` is `APPLICATION_CONTROLLED`.
+	/// It is stored as an integer so that the swapchain stays `Send` and `Sync`.
+	pub hmonitor: usize
+}
+impl FullScreenExclusiveInfo {
+	pub const fn new(mode: vk::FullScreenExclusiveEXT) -> Self {
+		FullScreenExclusiveInfo { mode, hmonitor: 0 }
+	}
+
+	/// Application controlled exclusive mode on the monitor `hmonitor`.
+	pub const fn application_controlled(hmonitor: usize) -> Self {
+		FullScreenExclusiveInfo { mode: vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED, hmonitor }
+	}
+
+	/// Returns the structures to chain into `p_next`, see `FullScreenExclusiveChain::link`.
+	pub fn chain(&self) -> FullScreenExclusiveChain {
+		FullScreenExclusiveChain {
+			info: vk::SurfaceFullScreenExclusiveInfoEXT::builder().full_screen_exclusive(self.mode).build(),
+			win32_info: vk::SurfaceFullScreenExclusiveWin32InfoEXT::builder()
+				.hmonitor(self.hmonitor as vk::HMONITOR)
+				.build()
+		}
+	}
+}
+
+/// `vk::SurfaceFullScreenExclusiveInfoEXT` and, if a monitor is set, `vk::SurfaceFullScreenExclusiveWin32InfoEXT`.
+///
+/// Both structures extend `vk::SwapchainCreateInfoKHR` and `vk::PhysicalDeviceSurfaceInfo2KHR`.
+#[derive(Debug, Clone, Copy)]
+pub struct FullScreenExclusiveChain {
+	pub info: vk::SurfaceFullScreenExclusiveInfoEXT,
+	pub win32_info: vk::SurfaceFullScreenExclusiveWin32InfoEXT
+}
+impl FullScreenExclusiveChain {
+	/// Links the structures together and returns the head of the chain, which can then be passed to `push_next`.
+	///
+	/// The chain must not be moved while it is in use.
+	pub fn link(&mut self) -> &mut vk::SurfaceFullScreenExclusiveInfoEXT {
+		self.info.p_next = if self.win32_info.hmonitor.is_null() {
+			std::ptr::null_mut()
+		} else {
+			&mut self.win32_info as *mut _ as *mut c_void
+		};
+
+		&mut self.info
+	}
+}
+
+/// Reads the full-screen exclusive parameters from the `p_next` chain of `create_info`.
+///
+/// ### Safety
+///
+/// `create_info` must be a valid `vk::SwapchainCreateInfoKHR`, including its `p_next` chain.
+pub(super) unsafe fn full_screen_exclusive_info(create_info: &vk::SwapchainCreateInfoKHR) -> Option<FullScreenExclusiveInfo> {
+	let mut result: Option<FullScreenExclusiveInfo> = None;
+	let mut hmonitor = 0;
+
+	let mut next = create_info.p_next as *const vk::BaseInStructure;
+	while let Some(structure) = next.as_ref() {
+		match structure.s_type {
+			vk::StructureType::SURFACE_FULL_SCREEN_EXCLUSIVE_INFO_EXT => {
+				let info = &*(next as *const vk::SurfaceFullScreenExclusiveInfoEXT);
+				result = Some(FullScreenExclusiveInfo::new(info.full_screen_exclusive));
+			}
+			vk::StructureType::SURFACE_FULL_SCREEN_EXCLUSIVE_WIN32_INFO_EXT => {
+				let info = &*(next as *const vk::SurfaceFullScreenExclusiveWin32InfoEXT);
+				hmonitor = info.hmonitor as usize;
+			}
+			_ => ()
+		}
+		next = structure.p_next;
+	}
+
+	result.map(|info| FullScreenExclusiveInfo { hmonitor, ..info })
+}
@@ -0,0 +1,268 @@
+//! Presentation from a queue family other than the one that renders.
+//!
+//! On some hardware no queue family supports both graphics and presentation to a surface, so rendering and presentation
+//! happen on different queues. `QueueFamilyPlan` selects a separate present family in that case and `PresentHandoff`
+//! takes care of the rest:
+//!
+//! * When both queues are from the same family, the image is only transitioned to `PRESENT_SRC_KHR`.
+//! * When the swapchain images are shared concurrently between the families, the image is transitioned as well
+//!   and presentation waits on the render semaphore from the other queue.
+//! * When the swapchain images are exclusive, the ownership of the image is released on the graphics queue and acquired on
+//!   the present queue by a pre-recorded command buffer submitted between rendering and presentation.
+//!
+//! ```ignore
+//! let handoff = PresentHandoff::new(&swapchain_data, graphics_queue, present_queue, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL, HostMemoryAllocator::Unspecified())?;
+//! // at the end of the frame command buffer
+//! handoff.record_release(&recording, index, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT, vk::AccessFlags::COLOR_ATTACHMENT_WRITE)?;
+//! // submit to the graphics queue signaling `render_semaphore`, then
+//! handoff.present(&mut pacer, &swapchain_data, &render_semaphore, index)?;
+//! ```
+//!
+//! The handoff refers to the images of one swapchain and must be recreated together with the swapchain.
+
+use std::fmt;
+
+use ash::vk;
+
+use super::{error::PresentHandoffError, image::SwapchainImage, pacing::FramePacer, SwapchainData};
+use crate::{
+	command::ownership::OwnershipTransfer,
+	prelude::{
+		BufferMemoryBarrier,
+		CommandBuffer,
+		CommandBufferBeginInfo,
+		CommandBufferRecordingLockOutsideRenderPass,
+		CommandPool,
+		Fence,
+		HostMemoryAllocator,
+		ImageLayoutFinal,
+		ImageMemoryBarrier,
+		MemoryBarrier,
+		Queue,
+		Semaphore,
+		Vrc
+	},
+	queue::error::QueuePresentSuccess,
+	sync::semaphore::BinarySemaphore,
+	util::WaitTimeout
+};
+
+/// How `PresentHandoff` hands images from the graphics queue to the present queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoffMode {
+	/// Both queues are from the same family.
+	SameFamily,
+	/// The queues are from different families and the swapchain images are shared concurrently.
+	Concurrent,
+	/// The queues are from different families and the ownership of the exclusive swapchain images is transferred.
+	OwnershipTransfer
+}
+
+/// Pre-recorded acquire half of the ownership transfer of one swapchain image.
+#[derive(Debug)]
+struct HandoffAcquire {
+	command_buffer: Vrc<CommandBuffer>,
+	semaphore: BinarySemaphore,
+	/// Signaled when the previous submission of `command_buffer` finished.
+	fence: Vrc<Fence>
+}
+
+/// Hands rendered swapchain images over to the present queue, see the module documentation.
+pub struct PresentHandoff {
+	graphics_queue: Vrc<Queue>,
+	present_queue: Vrc<Queue>,
+	mode: HandoffMode,
+	render_layout: vk::ImageLayout,
+
+	images: Vec<Vrc<SwapchainImage>>,
+	/// One per image, empty unless the mode is `OwnershipTransfer`.
+	acquires: Vec<HandoffAcquire>
+}
+impl PresentHandoff {
+	/// Creates a handoff of the images of `swapchain` rendered on `graphics_queue` and presented on `present_queue`.
+	///
+	/// `render_layout` is the layout the images are in when rendering finishes, for example `COLOR_ATTACHMENT_OPTIMAL`.
+	pub fn new(
+		swapchain: &SwapchainData,
+		graphics_queue: Vrc<Queue>,
+		present_queue: Vrc<Queue>,
+		render_layout: vk::ImageLayout,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<Self, PresentHandoffError> {
+		let mode = if graphics_queue.queue_family_index() == present_queue.queue_family_index() {
+			HandoffMode::SameFamily
+		} else if swapchain.swapchain.image_sharing_mode() == vk::SharingMode::CONCURRENT {
+			HandoffMode::Concurrent
+		} else {
+			HandoffMode::OwnershipTransfer
+		};
+		log::debug!(
+			"Present handoff from queue family {} to {}: {:?}",
+			graphics_queue.queue_family_index(),
+			present_queue.queue_family_index(),
+			mode
+		);
+
+		let mut acquires = Vec::new();
+		if mode == HandoffMode::OwnershipTransfer {
+			let device = present_queue.device();
+			let pool = CommandPool::new(
+				&present_queue,
+				vk::CommandPoolCreateFlags::empty(),
+				host_memory_allocator
+			)?;
+
+			for image in swapchain.images.iter() {
+				let [command_buffer] = CommandBuffer::new(pool.clone(), false)?;
+				{
+					let recording = command_buffer.begin_recording(CommandBufferBeginInfo::ManyTimes { simultaneous: false })?;
+					OwnershipTransfer::image(
+						image,
+						image.whole_subresource_range(),
+						render_layout,
+						ImageLayoutFinal::PRESENT_SRC_KHR,
+						&graphics_queue,
+						&present_queue
+					)?
+					.record_acquire(
+						&recording,
+						vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+						vk::AccessFlags::empty()
+					)?;
+					recording.end()?;
+				}
+
+				acquires.push(HandoffAcquire {
+					command_buffer,
+					semaphore: Semaphore::binary(device.clone(), host_memory_allocator)?,
+					fence: Fence::new(device.clone(), true, host_memory_allocator)?
+				});
+			}
+		}
+
+		Ok(PresentHandoff { graphics_queue, present_queue, mode, render_layout, images: swapchain.images.clone(), acquires })
+	}
+
+	/// Records the transition of the image at `index` to `PRESENT_SRC_KHR` into `recording`, which must be submitted to the graphics queue.
+	///
+	/// With `OwnershipTransfer` mode, the transition is the release half of the ownership transfer.
+	/// `source_stages` and `source_access` describe the last use of the image in rendering.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `index` is not an index of the swapchain images.
+	pub fn record_release(
+		&self,
+		recording: &CommandBufferRecordingLockOutsideRenderPass,
+		index: u32,
+		source_stages: vk::PipelineStageFlags,
+		source_access: vk::AccessFlags
+	) -> Result<(), PresentHandoffError> {
+		let image = &self.images[index as usize];
+		let range = image.whole_subresource_range();
+
+		match self.mode {
+			HandoffMode::OwnershipTransfer => {
+				OwnershipTransfer::image(
+					image,
+					range,
+					self.render_layout,
+					ImageLayoutFinal::PRESENT_SRC_KHR,
+					&self.graphics_queue,
+					&self.present_queue
+				)?
+				.record_release(recording, source_stages, source_access)?;
+			}
+			HandoffMode::SameFamily | HandoffMode::Concurrent => {
+				recording.pipeline_barrier(
+					source_stages,
+					vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+					[] as [MemoryBarrier; 0],
+					[] as [BufferMemoryBarrier; 0],
+					[ImageMemoryBarrier::new(
+						image,
+						range,
+						self.render_layout,
+						ImageLayoutFinal::PRESENT_SRC_KHR,
+						source_access,
+						vk::AccessFlags::empty()
+					)]
+				);
+			}
+		}
+		image.layout_tracker().set_layout(range, vk::ImageLayout::PRESENT_SRC_KHR);
+
+		Ok(())
+	}
+
+	/// Hands the image at `index` to the present queue after `wait_for` is signaled and returns the semaphore presentation must wait on.
+	///
+	/// With `OwnershipTransfer` mode, this submits the acquire half of the transfer to the present queue, waiting for the previous
+	/// submission for the same image first. Otherwise `wait_for` is returned as it is.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `index` is not an index of the swapchain images.
+	pub fn handoff<'s>(&'s self, index: u32, wait_for: &'s Semaphore) -> Result<&'s Semaphore, PresentHandoffError> {
+		if self.acquires.is_empty() {
+			return Ok(wait_for)
+		}
+
+		let acquire = &self.acquires[index as usize];
+		acquire.fence.wait(WaitTimeout::Forever)?;
+		acquire.fence.reset()?;
+
+		let semaphore: &Semaphore = &acquire.semaphore;
+		let result = self.present_queue.submit(
+			[wait_for],
+			[vk::PipelineStageFlags::ALL_COMMANDS],
+			[&acquire.command_buffer],
+			[semaphore],
+			Some(&acquire.fence)
+		);
+		if let Err(err) = result {
+			// Signal the fence with an empty submission so that the next handoff of this image doesn't wait forever
+			let _ = self.present_queue.submit([], [], [], [], Some(&acquire.fence));
+			return Err(err.into())
+		}
+
+		Ok(semaphore)
+	}
+
+	/// Hands the image at `index` to the present queue using `handoff` and presents it through `pacer`.
+	pub fn present(
+		&self,
+		pacer: &mut FramePacer,
+		swapchain: &SwapchainData,
+		wait_for: &Semaphore,
+		index: u32
+	) -> Result<QueuePresentSuccess, PresentHandoffError> {
+		let semaphore = self.handoff(index, wait_for)?;
+
+		Ok(pacer.present(swapchain, &self.present_queue, &[semaphore], index)?)
+	}
+
+	pub const fn graphics_queue(&self) -> &Vrc<Queue> {
+		&self.graphics_queue
+	}
+
+	pub const fn present_queue(&self) -> &Vrc<Queue> {
+		&self.present_queue
+	}
+
+	pub const fn mode(&self) -> HandoffMode {
+		self.mode
+	}
+}
+impl fmt::Debug for PresentHandoff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PresentHandoff")
+			.field("graphics_queue", &self.graphics_queue)
+			.field("present_queue", &self.present_queue)
+			.field("mode", &self.mode)
+			.field("render_layout", &self.render_layout)
+			.field("images", &self.images.len())
+			.field("acquires", &self.acquires)
+			.finish()
+	}
+}
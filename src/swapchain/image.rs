@@ -1,13 +1,17 @@
-use std::{mem::ManuallyDrop, num::NonZeroU32, ops::Deref};
+use std::{fmt, mem::ManuallyDrop, num::NonZeroU32, ops::Deref};
 
 use ash::vk;
 
 use super::Swapchain;
 use crate::{
-	prelude::{Device, Vrc},
+	prelude::{Device, Queue, Semaphore, Vrc},
+	queue::error::{QueuePresentError, QueuePresentSuccess},
 	resource::image::{
-		params::{ImageSize, ImageSize2D},
-		Image
+		error::ImageViewError,
+		params::{ImageSize, ImageSize2D, ImageViewRange},
+		view::ImageView,
+		Image,
+		MixedDynImage
 	}
 };
 
@@ -46,7 +50,9 @@ impl SwapchainImage {
 	///
 	/// * `image` must be an image crated from `swapchain` using `.get_swapchain_images`.
 	/// * `index` must be the index of the image as returned by the `.get_swapchain_images`.
-	pub unsafe fn new(swapchain: Vrc<Swapchain>, image: Image, index: u32) -> Vrc<Self> {
+	pub unsafe fn new(swapchain: Vrc<Swapchain>, mut image: Image, index: u32) -> Vrc<Self> {
+		image.mark_swapchain_image();
+
 		Vrc::new(SwapchainImage { swapchain, image: ManuallyDrop::new(image), index })
 	}
 
@@ -57,6 +63,32 @@ impl SwapchainImage {
 	pub const fn index(&self) -> u32 {
 		self.index
 	}
+
+	/// Creates a color view of all array layers of `image` with identity component mapping.
+	///
+	/// The view uses the host memory allocator the image was wrapped with.
+	pub fn create_view(image: &Vrc<Self>) -> Result<Vrc<ImageView>, ImageViewError> {
+		let array_layers = image.size().array_layers();
+		let view_range = if array_layers.get() == 1 {
+			ImageViewRange::Type2D(0, NonZeroU32::new(1).unwrap(), 0)
+		} else {
+			ImageViewRange::Type2DArray(
+				0,
+				NonZeroU32::new(1).unwrap(),
+				0,
+				array_layers
+			)
+		};
+
+		ImageView::new(
+			MixedDynImage::SwapchainImage(image.clone()),
+			view_range,
+			None,
+			vk::ComponentMapping::default(),
+			vk::ImageAspectFlags::COLOR,
+			image.host_memory_allocator()
+		)
+	}
 }
 impl Deref for SwapchainImage {
 	type Target = Image;
@@ -75,3 +107,75 @@ impl Drop for SwapchainImage {
 		unsafe { std::ptr::drop_in_place(self.image.device() as *const Vrc<Device> as *mut Vrc<Device>) }
 	}
 }
+
+/// Swapchain image that is currently acquired and has not been presented yet.
+///
+/// Returned by `SwapchainData::acquire_image` inside an `AcquireOutcome`, it must be consumed by `present` or explicitly by `abandon`,
+/// which makes presenting an index that was never acquired impossible. Dropping it without consuming logs a warning.
+#[must_use = "acquired images must be presented or abandoned"]
+pub struct AcquiredImage {
+	image: Vrc<SwapchainImage>,
+	suboptimal: bool,
+	consumed: bool
+}
+impl AcquiredImage {
+	pub(super) fn new(image: Vrc<SwapchainImage>, suboptimal: bool) -> Self {
+		AcquiredImage { image, suboptimal, consumed: false }
+	}
+
+	/// Presents this image on `queue` after waiting for `wait_for`.
+	pub fn present(mut self, queue: &Queue, wait_for: &[&Semaphore]) -> Result<QueuePresentSuccess, QueuePresentError> {
+		self.consumed = true;
+
+		self.image.present_chained(queue, wait_for, None)
+	}
+
+	/// Gives up on presenting this image.
+	///
+	/// The image stays acquired from the presentation engine until the swapchain is recreated,
+	/// so this should only be used when the swapchain is about to be recreated anyway.
+	pub fn abandon(mut self) {
+		self.consumed = true;
+	}
+
+	pub const fn image(&self) -> &Vrc<SwapchainImage> {
+		&self.image
+	}
+
+	pub fn index(&self) -> u32 {
+		self.image.index()
+	}
+
+	/// Returns whether the acquire returned `SUBOPTIMAL_KHR`.
+	pub const fn is_suboptimal(&self) -> bool {
+		self.suboptimal
+	}
+}
+impl super::error::AcquiredValue for AcquiredImage {
+	fn index(&self) -> u32 {
+		AcquiredImage::index(self)
+	}
+
+	fn is_suboptimal(&self) -> bool {
+		self.suboptimal
+	}
+}
+impl Drop for AcquiredImage {
+	fn drop(&mut self) {
+		if !self.consumed {
+			log::warn!(
+				"Swapchain image {} was acquired but neither presented nor abandoned",
+				self.image.index()
+			);
+		}
+	}
+}
+impl fmt::Debug for AcquiredImage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("AcquiredImage")
+			.field("image", &self.image)
+			.field("suboptimal", &self.suboptimal)
+			.field("consumed", &self.consumed)
+			.finish()
+	}
+}
@@ -0,0 +1,219 @@
+//! Management of multiple swapchains, for example one per window.
+//!
+//! `SwapchainSet` acquires an image from every tracked swapchain, presents all acquired images with a single
+//! `vkQueuePresentKHR` call and reports the results per swapchain. A swapchain that reports `ERROR_OUT_OF_DATE_KHR`
+//! or `SUBOPTIMAL_KHR` is flagged for recreation without affecting the other swapchains:
+//! ```ignore
+//! loop {
+//! 	for (window, result) in set.acquire_all(WaitTimeout::Forever, |window| (&acquire_semaphores[window]).into()) {
+//! 		// record and submit for each window that acquired an image
+//! 	}
+//! 	set.present_all(&queue, &[&render_semaphore]);
+//! 	set.recreate_flagged(|window| window_sizes.get(window).copied(), Default::default());
+//! }
+//! ```
+
+use std::{fmt, num::NonZeroU32};
+
+use super::{error, image::SwapchainImage, AcquireSynchronization, SwapchainData};
+use crate::{
+	memory::host::HostMemoryAllocator,
+	prelude::{Queue, Semaphore, Vrc},
+	queue::error::{QueuePresentError, QueuePresentSuccess},
+	util::WaitTimeout
+};
+
+struct SwapchainSetEntry<K> {
+	key: K,
+	data: SwapchainData,
+	acquired: Option<u32>,
+	needs_recreation: bool
+}
+
+/// Set of swapchains keyed by `K`, see the module documentation.
+pub struct SwapchainSet<K> {
+	entries: Vec<SwapchainSetEntry<K>>
+}
+impl<K: Clone + PartialEq> SwapchainSet<K> {
+	pub const fn new() -> Self {
+		SwapchainSet { entries: Vec::new() }
+	}
+
+	fn entry(&self, key: &K) -> Option<&SwapchainSetEntry<K>> {
+		self.entries.iter().find(|entry| entry.key == *key)
+	}
+
+	/// Inserts a swapchain under `key`, returning the previous swapchain under that key.
+	pub fn insert(&mut self, key: K, data: SwapchainData) -> Option<SwapchainData> {
+		let new_entry = SwapchainSetEntry { key, data, acquired: None, needs_recreation: false };
+
+		match self.entries.iter_mut().find(|entry| entry.key == new_entry.key) {
+			Some(entry) => Some(std::mem::replace(entry, new_entry).data),
+			None => {
+				self.entries.push(new_entry);
+				None
+			}
+		}
+	}
+
+	/// Removes the swapchain under `key`, for example when the window is closed.
+	///
+	/// If an image of the swapchain is acquired, it is never presented.
+	pub fn remove(&mut self, key: &K) -> Option<SwapchainData> {
+		let index = self.entries.iter().position(|entry| entry.key == *key)?;
+
+		Some(self.entries.remove(index).data)
+	}
+
+	pub fn get(&self, key: &K) -> Option<&SwapchainData> {
+		self.entry(key).map(|entry| &entry.data)
+	}
+
+	/// Returns the image acquired by the last `acquire_all` for swapchain under `key`, if any.
+	pub fn acquired_image(&self, key: &K) -> Option<&Vrc<SwapchainImage>> {
+		let entry = self.entry(key)?;
+
+		entry.acquired.map(|index| &entry.data.images[index as usize])
+	}
+
+	/// Returns whether the swapchain under `key` was flagged for recreation.
+	pub fn needs_recreation(&self, key: &K) -> bool {
+		self.entry(key).map(|entry| entry.needs_recreation).unwrap_or(false)
+	}
+
+	/// Flags the swapchain under `key` for recreation, for example after the window was resized.
+	pub fn flag_for_recreation(&mut self, key: &K) {
+		if let Some(entry) = self.entries.iter_mut().find(|entry| entry.key == *key) {
+			entry.needs_recreation = true;
+		}
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &K> {
+		self.entries.iter().map(|entry| &entry.key)
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Acquires the next image of every swapchain that does not already have an acquired image and is not flagged for recreation.
+	///
+	/// `synchronization` is called for each of these swapchains and must return distinct synchronization primitives for each of them.
+	/// `timeout` applies to each acquire separately.
+	///
+	/// Swapchains that return `ERROR_OUT_OF_DATE_KHR` or `SUBOPTIMAL_KHR` are flagged for recreation.
+	/// A suboptimal swapchain still acquires its image so it can be presented this frame.
+	pub fn acquire_all<'s>(
+		&mut self,
+		timeout: WaitTimeout,
+		mut synchronization: impl FnMut(&K) -> AcquireSynchronization<'s>
+	) -> Vec<(K, error::AcquireResult)> {
+		let mut results = Vec::with_capacity(self.entries.len());
+
+		for entry in self.entries.iter_mut().filter(|entry| entry.acquired.is_none() && !entry.needs_recreation) {
+			let outcome = error::AcquireOutcome::from(entry.data.swapchain.acquire_next(timeout, synchronization(&entry.key)));
+
+			entry.acquired = outcome.index();
+			entry.needs_recreation = outcome.needs_recreate();
+
+			results.push((entry.key.clone(), outcome.into_result()));
+		}
+
+		results
+	}
+
+	/// Presents all acquired images in one `vkQueuePresentKHR` call after waiting for `wait_for`.
+	///
+	/// Returns the result for each presented swapchain. Swapchains that return `ERROR_OUT_OF_DATE_KHR` or `SUBOPTIMAL_KHR`
+	/// are flagged for recreation. Does nothing if no images are acquired.
+	///
+	/// Present modes switched by `Swapchain::set_present_mode` are not applied, use `PresentTarget` for that.
+	pub fn present_all(&mut self, queue: &Queue, wait_for: &[&Semaphore]) -> Vec<(K, Result<QueuePresentSuccess, QueuePresentError>)> {
+		let presented: Vec<_> = self.entries.iter_mut().filter(|entry| entry.acquired.is_some()).collect();
+		if presented.is_empty() {
+			return Vec::new()
+		}
+
+		let images: Vec<_> = presented
+			.iter()
+			.map(|entry| &*entry.data.images[entry.acquired.unwrap() as usize])
+			.collect();
+		let results = queue.present_slice_with_all_results(wait_for, &images);
+
+		presented
+			.into_iter()
+			.zip(results)
+			.map(|(entry, result)| {
+				entry.acquired = None;
+				match &result {
+					Ok(success) => entry.needs_recreation |= matches!(success, QueuePresentSuccess::SUBOPTIMAL_KHR),
+					Err(err) => entry.needs_recreation |= err.is_recoverable_swapchain_issue()
+				}
+
+				(entry.key.clone(), result)
+			})
+			.collect()
+	}
+
+	/// Recreates all flagged swapchains with `Swapchain::recreate_resized`.
+	///
+	/// `window_size` returns the current size of the window of each swapchain. Swapchains for which it returns `None`,
+	/// for example minimized windows, and swapchains whose surface extent is zero stay flagged and are retried on the next call.
+	///
+	/// Returns the results of the swapchains that were attempted. Swapchains that fail stay in the set with their old data.
+	pub fn recreate_flagged(
+		&mut self,
+		mut window_size: impl FnMut(&K) -> Option<[NonZeroU32; 2]>,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Vec<(K, Result<(), error::SwapchainError>)> {
+		let mut results = Vec::new();
+
+		for entry in self.entries.iter_mut().filter(|entry| entry.needs_recreation && entry.acquired.is_none()) {
+			let size = match window_size(&entry.key) {
+				None => continue,
+				Some(size) => size
+			};
+
+			let result = entry
+				.data
+				.swapchain
+				.recreate_resized(size, host_memory_allocator)
+				.map(|data| {
+					entry.data = data;
+					entry.needs_recreation = false;
+				});
+			if let Err(error::SwapchainError::SurfaceExtentZero) = result {
+				continue
+			}
+
+			results.push((entry.key.clone(), result));
+		}
+
+		results
+	}
+}
+impl<K: Clone + PartialEq> Default for SwapchainSet<K> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl<K: fmt::Debug> fmt::Debug for SwapchainSet<K> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_map()
+			.entries(self.entries.iter().map(|entry| {
+				(
+					&entry.key,
+					(
+						&entry.data.swapchain,
+						entry.acquired,
+						entry.needs_recreation
+					)
+				)
+			}))
+			.finish()
+	}
+}
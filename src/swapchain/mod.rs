@@ -22,11 +22,21 @@ use crate::{
 	},
 	surface::Surface,
 	sync::{fence::Fence, semaphore::BinarySemaphore},
-	util::{sync::AtomicVool, handle::HasHandle}
+	util::{
+		handle::HasHandle,
+		sync::{AtomicVool, Vutex}
+	}
 };
 
+pub mod builder;
 pub mod error;
+pub mod full_screen;
+pub mod handoff;
 pub mod image;
+pub mod manager;
+pub mod pacing;
+pub mod present_mode;
+pub mod target;
 
 #[derive(Debug)]
 pub enum AcquireSynchronization<'a> {
@@ -73,6 +83,25 @@ pub struct SwapchainData {
 	pub swapchain: Vrc<Swapchain>,
 	pub images: Vec<Vrc<image::SwapchainImage>>
 }
+impl SwapchainData {
+	/// Acquires the next image and returns a guard that must be presented or abandoned.
+	///
+	/// This returns the same `AcquireOutcome` as `FramePacer::acquire`, with the guard in place of the raw index.
+	pub fn acquire_image(
+		&self,
+		timeout: crate::util::WaitTimeout,
+		synchronization: AcquireSynchronization
+	) -> error::AcquireOutcome<image::AcquiredImage> {
+		let result = self.swapchain.acquire_next(timeout, synchronization).map(|value| {
+			image::AcquiredImage::new(
+				self.images[value.index() as usize].clone(),
+				value.is_suboptimal()
+			)
+		});
+
+		result.into()
+	}
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct SwapchainCreateInfo<A: AsRef<[u32]>> {
@@ -84,13 +113,63 @@ pub struct SwapchainCreateInfo<A: AsRef<[u32]>> {
 	pub clipped: bool
 }
 
+/// Parameters the swapchain was created with, kept around for recreation.
+#[derive(Debug, Clone)]
+struct SwapchainParameters {
+	image_info: image::SwapchainCreateImageInfo,
+	image_sharing_mode: vk::SharingMode,
+	queue_family_indices: Vec<u32>,
+	pre_transform: vk::SurfaceTransformFlagsKHR,
+	composite_alpha: vk::CompositeAlphaFlagsKHR,
+	/// Present modes that can be switched between at present time, empty if switching is not enabled.
+	present_modes: Vec<vk::PresentModeKHR>,
+	full_screen_exclusive: Option<full_screen::FullScreenExclusiveInfo>,
+	clipped: bool
+}
+impl SwapchainParameters {
+	unsafe fn from_create_info(c_info: &vk::SwapchainCreateInfoKHR) -> Self {
+		let queue_family_indices = if c_info.queue_family_index_count > 0 && !c_info.p_queue_family_indices.is_null() {
+			std::slice::from_raw_parts(
+				c_info.p_queue_family_indices,
+				c_info.queue_family_index_count as usize
+			)
+			.to_vec()
+		} else {
+			Vec::new()
+		};
+
+		SwapchainParameters {
+			image_info: image::SwapchainCreateImageInfo {
+				min_image_count: NonZeroU32::new_unchecked(c_info.min_image_count),
+				image_format: c_info.image_format,
+				image_color_space: c_info.image_color_space,
+				image_size: ImageSize::new_2d(
+					NonZeroU32::new_unchecked(c_info.image_extent.width),
+					NonZeroU32::new_unchecked(c_info.image_extent.height),
+					NonZeroU32::new_unchecked(c_info.image_array_layers),
+					MipmapLevels::One()
+				),
+				image_usage: c_info.image_usage
+			},
+			image_sharing_mode: c_info.image_sharing_mode,
+			queue_family_indices,
+			pre_transform: c_info.pre_transform,
+			composite_alpha: c_info.composite_alpha,
+			present_modes: present_mode::switchable_present_modes(c_info),
+			full_screen_exclusive: full_screen::full_screen_exclusive_info(c_info),
+			clipped: c_info.clipped == vk::TRUE
+		}
+	}
+}
+
 pub struct Swapchain {
 	surface: Vrc<Surface>,
 
 	device: Vrc<Device>,
-	loader: ash::extensions::khr::Swapchain,
 	swapchain: vk::SwapchainKHR,
 	retired: AtomicVool,
+	parameters: SwapchainParameters,
+	present_mode: Vutex<vk::PresentModeKHR>,
 
 	host_memory_allocator: HostMemoryAllocator
 }
@@ -129,6 +208,91 @@ impl Swapchain {
 		}
 	}
 
+	/// Creates a new swapchain that can switch between `present_modes` at present time without being recreated.
+	///
+	/// `present_modes` must contain `create_info.present_mode` and the `VK_EXT_swapchain_maintenance1` extension must be enabled on the device.
+	/// See `set_present_mode` and `set_vsync`.
+	pub fn new_switchable(
+		device: Vrc<Device>,
+		surface: Surface,
+		create_info: SwapchainCreateInfo<impl AsRef<[u32]>>,
+		present_modes: &[vk::PresentModeKHR],
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<SwapchainData, error::SwapchainError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if create_info.image_info.image_usage.is_empty() {
+				return Err(error::SwapchainError::ImageUsageEmpty)
+			}
+			if !present_modes.contains(&create_info.present_mode) {
+				return Err(error::SwapchainError::PresentModeNotInList)
+			}
+		}
+
+		let mut present_modes_info = present_mode::SwapchainPresentModesCreateInfoEXT::new(present_modes);
+		let c_info = vk::SwapchainCreateInfoKHR::builder()
+			.surface(*surface)
+			.pre_transform(create_info.pre_transform)
+			.composite_alpha(create_info.composite_alpha)
+			.present_mode(create_info.present_mode)
+			.clipped(create_info.clipped)
+			.image_sharing_mode(create_info.sharing_mode.sharing_mode())
+			.queue_family_indices(create_info.sharing_mode.indices())
+			.push_next(&mut present_modes_info);
+
+		let c_info = create_info.image_info.add_to_create_info(c_info);
+
+		unsafe {
+			Self::from_create_info(
+				device,
+				Vrc::new(surface),
+				c_info,
+				host_memory_allocator
+			)
+		}
+	}
+
+	/// Creates a new swapchain with the full-screen exclusive parameters `full_screen`.
+	///
+	/// The `VK_EXT_full_screen_exclusive` extension must be enabled on the device, see the `full_screen` module.
+	/// The parameters are kept when the swapchain is recreated with `recreate_resized`.
+	pub fn new_full_screen_exclusive(
+		device: Vrc<Device>,
+		surface: Surface,
+		create_info: SwapchainCreateInfo<impl AsRef<[u32]>>,
+		full_screen: full_screen::FullScreenExclusiveInfo,
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<SwapchainData, error::SwapchainError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if create_info.image_info.image_usage.is_empty() {
+				return Err(error::SwapchainError::ImageUsageEmpty)
+			}
+		}
+
+		let mut full_screen_chain = full_screen.chain();
+		let c_info = vk::SwapchainCreateInfoKHR::builder()
+			.surface(*surface)
+			.pre_transform(create_info.pre_transform)
+			.composite_alpha(create_info.composite_alpha)
+			.present_mode(create_info.present_mode)
+			.clipped(create_info.clipped)
+			.image_sharing_mode(create_info.sharing_mode.sharing_mode())
+			.queue_family_indices(create_info.sharing_mode.indices())
+			.push_next(full_screen_chain.link());
+
+		let c_info = create_info.image_info.add_to_create_info(c_info);
+
+		unsafe {
+			Self::from_create_info(
+				device,
+				Vrc::new(surface),
+				c_info,
+				host_memory_allocator
+			)
+		}
+	}
+
 	pub fn recreate(
 		&self,
 		create_info: SwapchainCreateInfo<impl AsRef<[u32]>>,
@@ -165,8 +329,88 @@ impl Swapchain {
 		}
 	}
 
+	/// Recreates the swapchain with a new extent, reusing all other parameters this swapchain was created with.
+	///
+	/// The extent is clamped to the current surface capabilities. If the surface dictates its current extent,
+	/// that extent is used instead of `new_extent`. The image count is clamped as well.
+	///
+	/// This is intended to be called after `acquire_next` or `present` return `ERROR_OUT_OF_DATE_KHR` or `SUBOPTIMAL_KHR`.
+	/// This swapchain is retired and the old images can be dropped once they are no longer in use.
+	pub fn recreate_resized(
+		&self,
+		new_extent: [NonZeroU32; 2],
+		host_memory_allocator: HostMemoryAllocator
+	) -> Result<SwapchainData, error::SwapchainError> {
+		if self.retired.load(std::sync::atomic::Ordering::Relaxed) {
+			return Err(error::SwapchainError::SwapchainRetired)
+		}
+
+		let capabilities = self
+			.surface
+			.physical_device_surface_capabilities(self.device.physical_device())?;
+
+		let extent = if capabilities.current_extent.width != std::u32::MAX {
+			capabilities.current_extent
+		} else {
+			vk::Extent2D {
+				width: new_extent[0].get().max(capabilities.min_image_extent.width).min(capabilities.max_image_extent.width),
+				height: new_extent[1].get().max(capabilities.min_image_extent.height).min(capabilities.max_image_extent.height)
+			}
+		};
+		if extent.width == 0 || extent.height == 0 {
+			return Err(error::SwapchainError::SurfaceExtentZero)
+		}
+
+		let mut min_image_count = self.parameters.image_info.min_image_count.get().max(capabilities.min_image_count);
+		if capabilities.max_image_count != 0 {
+			min_image_count = min_image_count.min(capabilities.max_image_count);
+		}
+
+		self.retired.store(
+			true,
+			std::sync::atomic::Ordering::Relaxed
+		);
+
+		let parameters = &self.parameters;
+		let mut c_info = vk::SwapchainCreateInfoKHR::builder()
+			.surface(**self.surface)
+			.min_image_count(min_image_count)
+			.image_format(parameters.image_info.image_format)
+			.image_color_space(parameters.image_info.image_color_space)
+			.image_extent(extent)
+			.image_array_layers(parameters.image_info.image_size.array_layers().get())
+			.image_usage(parameters.image_info.image_usage)
+			.pre_transform(parameters.pre_transform)
+			.composite_alpha(parameters.composite_alpha)
+			.present_mode(self.present_mode())
+			.clipped(parameters.clipped)
+			.old_swapchain(self.swapchain)
+			.image_sharing_mode(parameters.image_sharing_mode)
+			.queue_family_indices(&parameters.queue_family_indices);
+
+		let mut present_modes_info = present_mode::SwapchainPresentModesCreateInfoEXT::new(&parameters.present_modes);
+		if !parameters.present_modes.is_empty() {
+			c_info = c_info.push_next(&mut present_modes_info);
+		}
+		let mut full_screen_chain = parameters.full_screen_exclusive.map(|info| info.chain());
+		if let Some(full_screen_chain) = full_screen_chain.as_mut() {
+			c_info = c_info.push_next(full_screen_chain.link());
+		}
+
+		unsafe {
+			Self::from_create_info(
+				self.device.clone(),
+				self.surface.clone(),
+				c_info,
+				host_memory_allocator
+			)
+		}
+	}
+
 	/// Creates a new `Swapchain` from an existing `SwapchainCreateInfoKHR`.
 	///
+	/// The swapchain images are wrapped with the same `host_memory_allocator`.
+	///
 	/// ### Safety
 	///
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkCreateSwapchainKHR.html>.
@@ -176,10 +420,30 @@ impl Swapchain {
 		create_info: impl Deref<Target = vk::SwapchainCreateInfoKHR>,
 		host_memory_allocator: HostMemoryAllocator
 	) -> Result<SwapchainData, error::SwapchainError> {
-		let loader = ash::extensions::khr::Swapchain::new(
-			device.instance().deref().deref(),
-			device.deref().deref()
-		);
+		Self::from_create_info_with_image_allocator(
+			device,
+			surface,
+			create_info,
+			host_memory_allocator,
+			host_memory_allocator
+		)
+	}
+
+	/// Creates a new `Swapchain` from an existing `SwapchainCreateInfoKHR`, wrapping the swapchain images with `image_host_memory_allocator`.
+	///
+	/// The image allocator is inherited by views created using `SwapchainImage::create_view`.
+	///
+	/// ### Safety
+	///
+	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkCreateSwapchainKHR.html>.
+	pub unsafe fn from_create_info_with_image_allocator(
+		device: Vrc<Device>,
+		surface: Vrc<Surface>,
+		create_info: impl Deref<Target = vk::SwapchainCreateInfoKHR>,
+		host_memory_allocator: HostMemoryAllocator,
+		image_host_memory_allocator: HostMemoryAllocator
+	) -> Result<SwapchainData, error::SwapchainError> {
+		let loader = device.ext().swapchain();
 
 		let c_info = create_info.deref();
 
@@ -191,19 +455,21 @@ impl Swapchain {
 			host_memory_allocator
 		);
 		let swapchain = loader.create_swapchain(c_info, host_memory_allocator.as_ref())?;
+		device.child_registry().register(swapchain);
 
 		let me = Vrc::new(Swapchain {
 			surface,
 			device: device.clone(),
-			loader,
 			swapchain,
 			retired: AtomicVool::new(false),
+			parameters: SwapchainParameters::from_create_info(c_info),
+			present_mode: Vutex::new(c_info.present_mode),
 
 			host_memory_allocator
 		});
 
 		let images: Vec<_> = me
-			.loader
+			.loader()
 			.get_swapchain_images(swapchain)? // This is still okay since we haven't given anyone else access to the `swapchain` or `me` object, no synchronization problem
 			.into_iter()
 			.enumerate()
@@ -223,7 +489,8 @@ impl Swapchain {
 							MipmapLevels::One()
 						)
 						.into(),
-						HostMemoryAllocator::Unspecified()
+						c_info.image_sharing_mode,
+						image_host_memory_allocator
 					),
 					index as u32
 				)
@@ -239,14 +506,24 @@ impl Swapchain {
 	///
 	/// See <https://www.khronos.org/registry/vulkan/specs/1.2-extensions/man/html/vkQueuePresentKHR.html>
 	pub unsafe fn present(&self, queue: &Queue, info: impl Deref<Target = vk::PresentInfoKHR>) -> Result<QueuePresentSuccess, QueuePresentError> {
-		log_trace_common!(
-			"Presenting on queue:",
-			self,
-			queue,
-			info.deref()
-		);
+		if log::log_enabled!(log::Level::Trace) {
+			let info = debugize_struct!(
+				info;
+				{
+					wait_semaphores: *[wait_semaphore_count] p_wait_semaphores;
+					swapchains: *[swapchain_count] p_swapchains;
+					image_indices: *[swapchain_count] p_image_indices;
+				}
+			);
+			log_trace_common!(
+				"Presenting on queue:",
+				self,
+				queue,
+				info
+			);
+		}
 
-		self.loader
+		self.loader()
 			.queue_present(queue.handle(), info.deref())
 			.map(Into::into)
 			.map_err(Into::into)
@@ -268,7 +545,7 @@ impl Swapchain {
 		}
 
 		let result = unsafe {
-			self.loader.acquire_next_image(
+			self.loader().acquire_next_image(
 				self.swapchain,
 				timeout.into(),
 				synchronization.semaphore().map(|s| s.handle()).unwrap_or(vk::Semaphore::null()),
@@ -289,12 +566,108 @@ impl Swapchain {
 		&self.device
 	}
 
+	/// Returns the image parameters this swapchain was created with.
+	pub const fn image_info(&self) -> &image::SwapchainCreateImageInfo {
+		&self.parameters.image_info
+	}
+
 	pub const fn surface(&self) -> &Vrc<Surface> {
 		&self.surface
 	}
 
-	pub const fn loader(&self) -> &ash::extensions::khr::Swapchain {
-		&self.loader
+	/// Returns the shared swapchain loader of the device, see `Device::ext`.
+	pub fn loader(&self) -> &ash::extensions::khr::Swapchain {
+		self.device.ext().swapchain()
+	}
+
+	/// Returns whether the images are shared between queue families concurrently or owned by one family at a time.
+	pub const fn image_sharing_mode(&self) -> vk::SharingMode {
+		self.parameters.image_sharing_mode
+	}
+
+	/// Returns the present mode used for the next present.
+	pub fn present_mode(&self) -> vk::PresentModeKHR {
+		*self.present_mode.lock().expect("vutex poisoned")
+	}
+
+	/// Returns the present modes this swapchain can switch between without recreation, see `new_switchable`.
+	pub fn switchable_present_modes(&self) -> &[vk::PresentModeKHR] {
+		&self.parameters.present_modes
+	}
+
+	/// Switches the present mode used by subsequent presents through `PresentTarget`.
+	///
+	/// Returns `false` without changing anything if `mode` is not one of `switchable_present_modes`,
+	/// in which case the swapchain must be recreated with the new present mode instead.
+	pub fn set_present_mode(&self, mode: vk::PresentModeKHR) -> bool {
+		if !self.parameters.present_modes.contains(&mode) {
+			return false
+		}
+
+		log::debug!("Switching swapchain present mode to {:?}", mode);
+		*self.present_mode.lock().expect("vutex poisoned") = mode;
+
+		true
+	}
+
+	/// Switches to `FIFO` when `vsync` is true or to `MAILBOX` or `IMMEDIATE` when it is false.
+	///
+	/// Returns `false` if no suitable mode is among `switchable_present_modes`, see `set_present_mode`.
+	pub fn set_vsync(&self, vsync: bool) -> bool {
+		match present_mode::vsync_present_mode(vsync, &self.parameters.present_modes) {
+			None => false,
+			Some(mode) => self.set_present_mode(mode)
+		}
+	}
+
+	/// Returns the full-screen exclusive parameters this swapchain was created with, if any.
+	pub const fn full_screen_exclusive(&self) -> Option<&full_screen::FullScreenExclusiveInfo> {
+		self.parameters.full_screen_exclusive.as_ref()
+	}
+
+	/// Enters full-screen exclusive mode.
+	///
+	/// The swapchain must be created with `APPLICATION_CONTROLLED` full-screen exclusive mode, see `new_full_screen_exclusive`.
+	/// Exclusive mode can be lost at any time, which is reported as `ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT`
+	/// from acquire and present, after which this function can be called again.
+	pub fn acquire_full_screen_exclusive_mode(&self) -> Result<(), error::FullScreenExclusiveError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if self.parameters.full_screen_exclusive.map(|info| info.mode) != Some(vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED) {
+				return Err(error::FullScreenExclusiveError::NotApplicationControlled)
+			}
+		}
+		if self.retired() {
+			return Err(error::FullScreenExclusiveError::SwapchainRetired)
+		}
+
+		log_trace_common!("Acquiring full-screen exclusive mode:", self);
+		unsafe {
+			self.device
+				.ext()
+				.full_screen_exclusive()
+				.acquire_full_screen_exclusive_mode(self.swapchain)
+				.map_err(Into::into)
+		}
+	}
+
+	/// Leaves full-screen exclusive mode previously entered with `acquire_full_screen_exclusive_mode`.
+	pub fn release_full_screen_exclusive_mode(&self) -> Result<(), error::FullScreenExclusiveError> {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if self.parameters.full_screen_exclusive.map(|info| info.mode) != Some(vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED) {
+				return Err(error::FullScreenExclusiveError::NotApplicationControlled)
+			}
+		}
+
+		log_trace_common!("Releasing full-screen exclusive mode:", self);
+		unsafe {
+			self.device
+				.ext()
+				.full_screen_exclusive()
+				.release_full_screen_exclusive_mode(self.swapchain)
+				.map_err(Into::into)
+		}
 	}
 
 	pub fn retired(&self) -> bool {
@@ -309,9 +682,10 @@ impl_common_handle_traits! {
 impl Drop for Swapchain {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self, self.swapchain);
+		self.device.child_registry().unregister(self.swapchain);
 
 		unsafe {
-			self.loader.destroy_swapchain(
+			self.loader().destroy_swapchain(
 				self.swapchain,
 				self.host_memory_allocator.as_ref()
 			);
@@ -323,10 +697,6 @@ impl Debug for Swapchain {
 		f.debug_struct("Swapchain")
 			.field("surface", &self.surface)
 			.field("device", &self.device)
-			.field(
-				"loader",
-				&"<ash::extensions::khr::Swapchain>"
-			)
 			.field("swapchain", &self.swapchain)
 			.field(
 				"host_memory_allocator",
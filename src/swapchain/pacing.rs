@@ -0,0 +1,319 @@
+//! Frame pacing for low-latency render loops.
+//!
+//! Starting a frame too early queues it behind frames already waiting for presentation and adds input latency,
+//! starting it too late misses the display refresh. `FramePacer` decides when the CPU starts working on the next frame
+//! by combining an optional target frame time, `VK_KHR_present_wait` and the time spent blocked in acquire.
+//!
+//! A render loop calls `begin_frame`, then acquires and presents through the pacer:
+//! ```ignore
+//! loop {
+//! 	pacer.begin_frame(&swapchain_data.swapchain)?;
+//! 	let outcome = pacer.acquire(&swapchain_data, WaitTimeout::Forever, (&acquire_semaphore).into());
+//! 	if !outcome.synchronization_signaled() && outcome.needs_recreate() {
+//! 		// recreate the swapchain, `acquire_semaphore` was not signaled and can be reused
+//! 		continue;
+//! 	}
+//! 	let index = outcome.into_result()?.index();
+//! 	// record and submit
+//! 	pacer.present(&swapchain_data, &queue, &[&render_semaphore], index)?;
+//! }
+//! ```
+
+use std::{
+	num::NonZeroU32,
+	time::{Duration, Instant}
+};
+
+use ash::vk;
+
+use super::{error, target::PresentTarget, AcquireSynchronization, Swapchain, SwapchainData};
+use crate::{
+	prelude::{HasHandle, Queue, Semaphore},
+	queue::error::{QueuePresentError, QueuePresentSuccess},
+	util::WaitTimeout
+};
+
+/// Timeout of a single present wait in `FramePacer::begin_frame`.
+///
+/// The pacer never blocks longer than this, even if the presentation engine stalls.
+pub const PRESENT_WAIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Weight of the newest frame in the moving averages of `FramePacingStatistics`.
+const AVERAGE_WEIGHT: f64 = 0.1;
+
+/// Where the time of a frame is spent, measured from the start of the CPU work of one frame to the start of the next one.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FrameTimings {
+	/// Duration of the whole frame.
+	pub frame: Duration,
+	/// Time spent sleeping to reach the target frame time.
+	pub sleep: Duration,
+	/// Time spent blocked waiting for the GPU and presentation engine, in present wait and acquire.
+	pub wait: Duration,
+	/// The rest of the frame, spent doing CPU work.
+	pub cpu: Duration
+}
+impl FrameTimings {
+	fn blend(self, new: FrameTimings, weight: f64) -> Self {
+		let mix = |old: Duration, new: Duration| old.mul_f64(1.0 - weight) + new.mul_f64(weight);
+
+		FrameTimings {
+			frame: mix(self.frame, new.frame),
+			sleep: mix(self.sleep, new.sleep),
+			wait: mix(self.wait, new.wait),
+			cpu: mix(self.cpu, new.cpu)
+		}
+	}
+}
+
+/// What limits the frame rate, as estimated by `FramePacingStatistics::bound`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameBound {
+	/// Frames are limited by the target frame time, both the CPU and the GPU have spare time.
+	Target,
+	/// The CPU waits for the GPU or the presentation engine most of the frame.
+	Gpu,
+	/// The CPU is busy most of the frame.
+	Cpu
+}
+
+/// Statistics collected by `FramePacer`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FramePacingStatistics {
+	/// Number of completed frames.
+	pub frame_count: u64,
+	/// Timings of the last completed frame.
+	pub last: FrameTimings,
+	/// Exponential moving average of the frame timings.
+	pub average: FrameTimings
+}
+impl FramePacingStatistics {
+	/// Estimates what limits the frame rate from the average timings.
+	///
+	/// Returns `None` until at least one frame is completed.
+	pub fn bound(&self) -> Option<FrameBound> {
+		if self.frame_count == 0 {
+			return None
+		}
+
+		let average = &self.average;
+		let bound = if average.sleep > average.wait && average.sleep > average.frame / 10 {
+			FrameBound::Target
+		} else if average.wait > average.cpu {
+			FrameBound::Gpu
+		} else {
+			FrameBound::Cpu
+		};
+
+		Some(bound)
+	}
+
+	fn push(&mut self, timings: FrameTimings) {
+		self.average = if self.frame_count == 0 {
+			timings
+		} else {
+			self.average.blend(timings, AVERAGE_WEIGHT)
+		};
+		self.last = timings;
+		self.frame_count += 1;
+	}
+}
+
+/// Computes when to start frames and collects frame timing statistics.
+///
+/// The pacer is meant to be owned by the render loop thread.
+#[derive(Debug)]
+pub struct FramePacer {
+	target_frame_time: Option<Duration>,
+	present_wait_latency: Option<NonZeroU32>,
+
+	present_swapchain: vk::SwapchainKHR,
+	last_present_id: u64,
+
+	frame_start: Option<Instant>,
+	sleep: Duration,
+	wait: Duration,
+
+	statistics: FramePacingStatistics
+}
+impl FramePacer {
+	/// Creates a new frame pacer.
+	///
+	/// When `target_frame_time` is set, frames start at most once per `target_frame_time`.
+	///
+	/// When `present_wait_latency` is set, `begin_frame` waits until at most `present_wait_latency` presented frames are
+	/// still waiting to be displayed. This requires `VK_KHR_present_id` and `VK_KHR_present_wait` to be enabled on the device
+	/// together with their `presentId` and `presentWait` features.
+	pub fn new(target_frame_time: Option<Duration>, present_wait_latency: Option<NonZeroU32>) -> Self {
+		FramePacer {
+			target_frame_time,
+			present_wait_latency,
+
+			present_swapchain: vk::SwapchainKHR::null(),
+			last_present_id: 0,
+
+			frame_start: None,
+			sleep: Duration::from_secs(0),
+			wait: Duration::from_secs(0),
+
+			statistics: FramePacingStatistics::default()
+		}
+	}
+
+	/// Waits until the next frame should start and completes the timings of the previous frame.
+	///
+	/// `swapchain` is the swapchain the previous frames were presented to. Present wait is skipped if it differs,
+	/// for example right after the swapchain was recreated.
+	///
+	/// Present wait timeouts are ignored, see `PRESENT_WAIT_TIMEOUT`.
+	pub fn begin_frame(&mut self, swapchain: &Swapchain) -> Result<(), error::PresentWaitError> {
+		if let Some(latency) = self.present_wait_latency {
+			let latency = latency.get() as u64;
+
+			if swapchain.handle() == self.present_swapchain && self.last_present_id >= latency {
+				let wait_start = Instant::now();
+				let result = unsafe {
+					swapchain.device().ext().present_wait().wait_for_present(
+						swapchain.handle(),
+						self.last_present_id + 1 - latency,
+						PRESENT_WAIT_TIMEOUT.as_nanos() as u64
+					)
+				};
+				self.wait += wait_start.elapsed();
+
+				match result {
+					Ok(()) | Err(vk::Result::TIMEOUT) => (),
+					Err(err) => return Err(err.into())
+				}
+			}
+		}
+
+		if let (Some(target), Some(frame_start)) = (self.target_frame_time, self.frame_start) {
+			let deadline = frame_start + target;
+			let now = Instant::now();
+
+			if now < deadline {
+				std::thread::sleep(deadline - now);
+				self.sleep += Instant::now() - now;
+			}
+		}
+
+		let now = Instant::now();
+		if let Some(frame_start) = self.frame_start {
+			let frame = now - frame_start;
+
+			self.statistics.push(FrameTimings {
+				frame,
+				sleep: self.sleep,
+				wait: self.wait,
+				cpu: frame.saturating_sub(self.sleep + self.wait)
+			});
+		}
+		self.frame_start = Some(now);
+		self.sleep = Duration::from_secs(0);
+		self.wait = Duration::from_secs(0);
+
+		Ok(())
+	}
+
+	/// Acquires the next image of `target` and accounts the time spent blocked to the current frame.
+	///
+	/// If the outcome reports that the synchronization primitives were not signaled, they can be passed to the next acquire as they are.
+	pub fn acquire(
+		&mut self,
+		target: &impl PresentTarget,
+		timeout: WaitTimeout,
+		synchronization: AcquireSynchronization
+	) -> error::AcquireOutcome {
+		let acquire_start = Instant::now();
+		let result = target.acquire(timeout, synchronization);
+		self.wait += acquire_start.elapsed();
+
+		result.into()
+	}
+
+	/// Presents the image at `index` of `swapchain` on `queue` after waiting for `wait_for`.
+	///
+	/// With present wait enabled, the present is tagged with a present id so that `begin_frame` can wait for it.
+	pub fn present(
+		&mut self,
+		swapchain: &SwapchainData,
+		queue: &Queue,
+		wait_for: &[&Semaphore],
+		index: u32
+	) -> Result<QueuePresentSuccess, QueuePresentError> {
+		if self.present_wait_latency.is_none() {
+			return swapchain.present_with_id(queue, wait_for, index, None)
+		}
+
+		// present ids must be increasing per swapchain, so start over when the swapchain changes
+		if swapchain.swapchain.handle() != self.present_swapchain {
+			self.present_swapchain = swapchain.swapchain.handle();
+			self.last_present_id = 0;
+		}
+
+		let result = swapchain.present_with_id(
+			queue,
+			wait_for,
+			index,
+			Some(self.last_present_id + 1)
+		);
+		if result.is_ok() {
+			self.last_present_id += 1;
+		}
+
+		result
+	}
+
+	pub const fn target_frame_time(&self) -> Option<Duration> {
+		self.target_frame_time
+	}
+
+	pub fn set_target_frame_time(&mut self, target_frame_time: Option<Duration>) {
+		self.target_frame_time = target_frame_time;
+	}
+
+	pub const fn present_wait_latency(&self) -> Option<NonZeroU32> {
+		self.present_wait_latency
+	}
+
+	pub const fn statistics(&self) -> &FramePacingStatistics {
+		&self.statistics
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use super::{FrameBound, FramePacingStatistics, FrameTimings};
+
+	fn timings(sleep: u64, wait: u64, cpu: u64) -> FrameTimings {
+		FrameTimings {
+			frame: Duration::from_millis(sleep + wait + cpu),
+			sleep: Duration::from_millis(sleep),
+			wait: Duration::from_millis(wait),
+			cpu: Duration::from_millis(cpu)
+		}
+	}
+
+	#[test]
+	fn frame_bound_heuristics() {
+		let mut statistics = FramePacingStatistics::default();
+		assert_eq!(statistics.bound(), None);
+
+		statistics.push(timings(8, 1, 7));
+		assert_eq!(statistics.bound(), Some(FrameBound::Target));
+
+		let mut statistics = FramePacingStatistics::default();
+		statistics.push(timings(0, 10, 6));
+		assert_eq!(statistics.bound(), Some(FrameBound::Gpu));
+
+		for _ in 0 .. 100 {
+			statistics.push(timings(0, 1, 15));
+		}
+		assert_eq!(statistics.bound(), Some(FrameBound::Cpu));
+		assert_eq!(statistics.frame_count, 101);
+		assert_eq!(statistics.last, timings(0, 1, 15));
+	}
+}
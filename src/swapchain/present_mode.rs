@@ -0,0 +1,101 @@
+//! Present mode switching at present time using `VK_EXT_swapchain_maintenance1`.
+//!
+//! A swapchain created with a list of present modes (see `Swapchain::new_switchable`) can switch between them
+//! on each present without being recreated. The extension must be enabled on the device.
+
+use std::{ffi::CStr, os::raw::c_void};
+
+use ash::vk;
+
+/// Name of the `VK_EXT_swapchain_maintenance1` extension.
+pub fn extension_name() -> &'static CStr {
+	unsafe { CStr::from_bytes_with_nul_unchecked(b"VK_EXT_swapchain_maintenance1\0") }
+}
+
+/// `VK_STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODES_CREATE_INFO_EXT`
+pub const STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODES_CREATE_INFO_EXT: vk::StructureType = vk::StructureType::from_raw(1000275002);
+/// `VK_STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODE_INFO_EXT`
+pub const STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODE_INFO_EXT: vk::StructureType = vk::StructureType::from_raw(1000275003);
+
+/// `VkSwapchainPresentModesCreateInfoEXT`, extends `vk::SwapchainCreateInfoKHR`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SwapchainPresentModesCreateInfoEXT {
+	pub s_type: vk::StructureType,
+	pub p_next: *const c_void,
+	pub present_mode_count: u32,
+	pub p_present_modes: *const vk::PresentModeKHR
+}
+impl SwapchainPresentModesCreateInfoEXT {
+	pub fn new(present_modes: &[vk::PresentModeKHR]) -> Self {
+		SwapchainPresentModesCreateInfoEXT {
+			s_type: STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODES_CREATE_INFO_EXT,
+			p_next: std::ptr::null(),
+			present_mode_count: present_modes.len() as u32,
+			p_present_modes: present_modes.as_ptr()
+		}
+	}
+}
+unsafe impl vk::ExtendsSwapchainCreateInfoKHR for SwapchainPresentModesCreateInfoEXT {}
+
+/// `VkSwapchainPresentModeInfoEXT`, extends `vk::PresentInfoKHR`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SwapchainPresentModeInfoEXT {
+	pub s_type: vk::StructureType,
+	pub p_next: *const c_void,
+	pub swapchain_count: u32,
+	pub p_present_modes: *const vk::PresentModeKHR
+}
+impl SwapchainPresentModeInfoEXT {
+	/// `present_modes` must contain one present mode for each swapchain in the present info.
+	pub fn new(present_modes: &[vk::PresentModeKHR]) -> Self {
+		SwapchainPresentModeInfoEXT {
+			s_type: STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODE_INFO_EXT,
+			p_next: std::ptr::null(),
+			swapchain_count: present_modes.len() as u32,
+			p_present_modes: present_modes.as_ptr()
+		}
+	}
+}
+unsafe impl vk::ExtendsPresentInfoKHR for SwapchainPresentModeInfoEXT {}
+
+/// Reads the present modes from a `SwapchainPresentModesCreateInfoEXT` in the `p_next` chain of `create_info`.
+///
+/// ### Safety
+///
+/// `create_info` must be a valid `vk::SwapchainCreateInfoKHR`, including its `p_next` chain.
+pub(super) unsafe fn switchable_present_modes(create_info: &vk::SwapchainCreateInfoKHR) -> Vec<vk::PresentModeKHR> {
+	let mut next = create_info.p_next as *const vk::BaseInStructure;
+	while let Some(structure) = next.as_ref() {
+		if structure.s_type == STRUCTURE_TYPE_SWAPCHAIN_PRESENT_MODES_CREATE_INFO_EXT {
+			let info = &*(next as *const SwapchainPresentModesCreateInfoEXT);
+			if info.p_present_modes.is_null() {
+				break
+			}
+
+			return std::slice::from_raw_parts(
+				info.p_present_modes,
+				info.present_mode_count as usize
+			)
+			.to_vec()
+		}
+		next = structure.p_next;
+	}
+
+	Vec::new()
+}
+
+/// Selects a present mode from `available` for the given vsync setting.
+///
+/// With vsync `FIFO` is used, which is always supported. Without vsync `MAILBOX` is preferred, then `IMMEDIATE`.
+/// Returns `None` if no suitable mode is available.
+pub fn vsync_present_mode(vsync: bool, available: &[vk::PresentModeKHR]) -> Option<vk::PresentModeKHR> {
+	let preference: &[vk::PresentModeKHR] = if vsync {
+		&[vk::PresentModeKHR::FIFO]
+	} else {
+		&[vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::IMMEDIATE]
+	};
+
+	preference.iter().copied().find(|mode| available.contains(mode))
+}
@@ -0,0 +1,212 @@
+//! Abstraction over presentation targets.
+//!
+//! Render loops written against `PresentTarget` can run both with a real swapchain and with
+//! off-screen images through `HeadlessTarget`, for example in headless tests.
+
+use std::{ffi::c_void, fmt};
+
+use ash::vk;
+
+use super::{error, image::SwapchainImage, present_mode::SwapchainPresentModeInfoEXT, AcquireSynchronization, SwapchainData};
+use crate::{
+	prelude::{HasHandle, Image, Queue, Semaphore, Vrc},
+	queue::error::{QueuePresentError, QueuePresentSuccess, QueueSubmitError},
+	resource::image::params::ImageSize,
+	util::{sync::Vutex, WaitTimeout}
+};
+
+pub trait PresentTarget {
+	/// Acquires the next image to render into and returns its index.
+	fn acquire(&self, timeout: WaitTimeout, synchronization: AcquireSynchronization) -> error::AcquireResult;
+
+	/// Presents the image at `index` on `queue` after waiting for `wait_for`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `index` is out of bounds.
+	fn present(&self, queue: &Queue, wait_for: &[&Semaphore], index: u32) -> Result<QueuePresentSuccess, QueuePresentError>;
+
+	/// Returns the image at `index`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `index` is out of bounds.
+	fn image(&self, index: u32) -> &Image;
+
+	/// Returns the number of images in this target.
+	fn image_count(&self) -> u32;
+
+	/// Returns the extent of the target images.
+	fn extent(&self) -> vk::Extent2D;
+
+	/// Returns the format of the target images.
+	fn format(&self) -> vk::Format;
+}
+impl SwapchainImage {
+	/// Presents this image through `Queue::present_with_next`, chaining the current present mode of switchable swapchains
+	/// and, if `present_id` is set, the present id using `VK_KHR_present_id`.
+	pub(super) fn present_chained(&self, queue: &Queue, wait_for: &[&Semaphore], present_id: Option<u64>) -> Result<QueuePresentSuccess, QueuePresentError> {
+		let present_modes = [self.swapchain().present_mode()];
+		let mut present_mode_info = SwapchainPresentModeInfoEXT::new(&present_modes);
+		let present_ids = [present_id.unwrap_or(0)];
+		let mut present_id_info = vk::PresentIdKHR::builder().present_ids(&present_ids).build();
+
+		let mut next: *const c_void = std::ptr::null();
+		if !self.swapchain().switchable_present_modes().is_empty() {
+			present_mode_info.p_next = next;
+			next = &present_mode_info as *const SwapchainPresentModeInfoEXT as *const c_void;
+		}
+		if present_id.is_some() {
+			present_id_info.p_next = next;
+			next = &present_id_info as *const vk::PresentIdKHR as *const c_void;
+		}
+
+		unsafe { queue.present_with_next(wait_for, &[self], next) }
+	}
+}
+impl SwapchainData {
+	/// Presents the image at `index` and, if `present_id` is set, chains it using `VK_KHR_present_id`.
+	pub(super) fn present_with_id(
+		&self,
+		queue: &Queue,
+		wait_for: &[&Semaphore],
+		index: u32,
+		present_id: Option<u64>
+	) -> Result<QueuePresentSuccess, QueuePresentError> {
+		self.images[index as usize].present_chained(queue, wait_for, present_id)
+	}
+}
+impl PresentTarget for SwapchainData {
+	fn acquire(&self, timeout: WaitTimeout, synchronization: AcquireSynchronization) -> error::AcquireResult {
+		self.swapchain.acquire_next(timeout, synchronization)
+	}
+
+	fn present(&self, queue: &Queue, wait_for: &[&Semaphore], index: u32) -> Result<QueuePresentSuccess, QueuePresentError> {
+		self.present_with_id(queue, wait_for, index, None)
+	}
+
+	fn image(&self, index: u32) -> &Image {
+		&self.images[index as usize]
+	}
+
+	fn image_count(&self) -> u32 {
+		self.images.len() as u32
+	}
+
+	fn extent(&self) -> vk::Extent2D {
+		ImageSize::from(self.swapchain.image_info().image_size).into()
+	}
+
+	fn format(&self) -> vk::Format {
+		self.swapchain.image_info().image_format
+	}
+}
+
+/// Off-screen `PresentTarget` over images owned by the application, for running render loops without a surface.
+///
+/// Images are acquired in round-robin order. The synchronization of an acquire is signaled by an empty submission
+/// on the queue the target was created with. Presenting only waits for `wait_for` with an empty submission on the given queue,
+/// after which the contents of the image can be read back.
+pub struct HeadlessTarget {
+	queue: Vrc<Queue>,
+	images: Vec<Vrc<Image>>,
+	next_index: Vutex<u32>
+}
+impl HeadlessTarget {
+	/// Creates a new headless target over `images`.
+	///
+	/// All images must have the same format and size and must be from the same device as `queue`.
+	pub fn new(queue: Vrc<Queue>, images: Vec<Vrc<Image>>) -> Result<Self, error::HeadlessTargetError> {
+		let first = images.first().ok_or(error::HeadlessTargetError::ImagesEmpty)?;
+		if images.iter().any(|image| image.format() != first.format() || image.size() != first.size()) {
+			return Err(error::HeadlessTargetError::ImagesMismatch)
+		}
+		if images.iter().any(|image| image.device() != queue.device()) {
+			return Err(error::HeadlessTargetError::ImageQueueDeviceMismatch)
+		}
+
+		Ok(HeadlessTarget { queue, images, next_index: Vutex::new(0) })
+	}
+
+	pub const fn queue(&self) -> &Vrc<Queue> {
+		&self.queue
+	}
+
+	pub fn images(&self) -> &[Vrc<Image>] {
+		&self.images
+	}
+}
+impl PresentTarget for HeadlessTarget {
+	fn acquire(&self, _timeout: WaitTimeout, synchronization: AcquireSynchronization) -> error::AcquireResult {
+		#[cfg(feature = "runtime_implicit_validations")]
+		{
+			if let Some(semaphore) = synchronization.semaphore() {
+				if semaphore.device() != self.queue.device() {
+					return Err(error::AcquireError::SemaphoreSwapchainDeviceMismatch)
+				}
+			}
+			if let Some(fence) = synchronization.fence() {
+				if fence.device() != self.queue.device() {
+					return Err(error::AcquireError::FenceSwapchainDeviceMismatch)
+				}
+			}
+		}
+
+		let signal_raw = synchronization.semaphore().map(|s| s.handle());
+		let submit_info = vk::SubmitInfo::builder().signal_semaphores(signal_raw.as_slice()).build();
+		unsafe { self.queue.submit_raw([submit_info], synchronization.fence()) }.map_err(submit_error::<error::AcquireError>)?;
+
+		let mut next_index = self.next_index.lock().expect("vutex poisoned");
+		let index = *next_index;
+		*next_index = (index + 1) % self.images.len() as u32;
+
+		Ok(error::AcquireResultValue::SUCCESS(index))
+	}
+
+	fn present(&self, queue: &Queue, wait_for: &[&Semaphore], index: u32) -> Result<QueuePresentSuccess, QueuePresentError> {
+		assert!((index as usize) < self.images.len(), "index out of bounds");
+
+		if !wait_for.is_empty() {
+			let wait_for_raw = collect_iter_faster!(wait_for.iter().map(|s| s.handle()), 4);
+			let wait_for_stages = collect_iter_faster!(wait_for.iter().map(|_| vk::PipelineStageFlags::ALL_COMMANDS), 4);
+
+			let submit_info = vk::SubmitInfo::builder()
+				.wait_semaphores(&wait_for_raw)
+				.wait_dst_stage_mask(&wait_for_stages)
+				.build();
+			unsafe { queue.submit_raw([submit_info], None) }.map_err(submit_error::<QueuePresentError>)?;
+		}
+
+		Ok(QueuePresentSuccess::SUCCESS)
+	}
+
+	fn image(&self, index: u32) -> &Image {
+		&self.images[index as usize]
+	}
+
+	fn image_count(&self) -> u32 {
+		self.images.len() as u32
+	}
+
+	fn extent(&self) -> vk::Extent2D {
+		self.images[0].size().into()
+	}
+
+	fn format(&self) -> vk::Format {
+		self.images[0].format()
+	}
+}
+impl fmt::Debug for HeadlessTarget {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("HeadlessTarget")
+			.field("queue", &self.queue)
+			.field("images", &self.images)
+			.field("next_index", &self.next_index)
+			.finish()
+	}
+}
+
+/// Converts an error of `Queue::submit_raw`, which only returns Vulkan errors, into the error of a `PresentTarget` function.
+fn submit_error<E: From<vk::Result>>(err: QueueSubmitError) -> E {
+	E::from(err.vk_result().unwrap_or(vk::Result::ERROR_UNKNOWN))
+}
@@ -8,6 +8,7 @@ use ash::vk;
 use crate::{device::Device, memory::host::HostMemoryAllocator, prelude::Vrc};
 
 pub mod error;
+pub mod pool;
 
 pub struct Fence {
 	device: Vrc<Device>,
@@ -49,6 +50,7 @@ impl Fence {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(fence);
 		Ok(Vrc::new(Fence {
 			device,
 			fence: fence,
@@ -103,6 +105,7 @@ impl_common_handle_traits! {
 impl Drop for Fence {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self, self.fence);
+		self.device.child_registry().unregister(self.fence);
 
 		unsafe {
 			self.device.destroy_fence(
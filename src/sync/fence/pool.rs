@@ -0,0 +1,120 @@
+//! Pool of reusable fences.
+
+use std::fmt;
+
+use super::{error::FenceError, Fence};
+use crate::prelude::{Device, HostMemoryAllocator, Vrc, Vutex};
+
+#[derive(Debug, Default)]
+struct FencePoolFences {
+	/// Fences that were rented and may still be in use.
+	rented: Vec<Vrc<Fence>>,
+	/// Fences that were reset and can be rented again.
+	free: Vec<Vrc<Fence>>
+}
+
+/// Pool renting unsignaled fences and reclaiming them once they are signaled.
+///
+/// A rented fence is reclaimed when it is no longer referenced outside of the pool and is signaled.
+/// The pool resets reclaimed fences itself, so rented fences are always unsignaled.
+///
+/// Fences that are never submitted are never signaled and thus never reclaimed, they must be given back using `return_unsubmitted`.
+pub struct FencePool {
+	device: Vrc<Device>,
+	fences: Vutex<FencePoolFences>,
+
+	host_memory_allocator: HostMemoryAllocator
+}
+impl FencePool {
+	pub fn new(device: Vrc<Device>, host_memory_allocator: HostMemoryAllocator) -> Self {
+		FencePool { device, fences: Vutex::new(Default::default()), host_memory_allocator }
+	}
+
+	/// Rents an unsignaled fence, reusing a reclaimed fence if possible.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the fences `Vutex` is poisoned.
+	pub fn rent(&self) -> Result<Vrc<Fence>, FenceError> {
+		let mut fences = self.fences.lock().expect("vutex poisoned");
+		Self::reclaim(&mut fences)?;
+
+		let fence = match fences.free.pop() {
+			Some(fence) => fence,
+			None => Fence::new(
+				self.device.clone(),
+				false,
+				self.host_memory_allocator.clone()
+			)?
+		};
+		fences.rented.push(fence.clone());
+
+		Ok(fence)
+	}
+
+	/// Gives a rented `fence` that was never submitted back to the pool so that it can be rented again.
+	///
+	/// Does nothing if `fence` was not rented from this pool.
+	///
+	/// ### Safety
+	///
+	/// `fence` must not have been submitted and must not be referenced anywhere else outside of the pool.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the fences `Vutex` is poisoned.
+	pub unsafe fn return_unsubmitted(&self, fence: Vrc<Fence>) {
+		let mut fences = self.fences.lock().expect("vutex poisoned");
+
+		if let Some(index) = fences.rented.iter().position(|rented| Vrc::ptr_eq(rented, &fence)) {
+			fences.rented.swap_remove(index);
+			fences.free.push(fence);
+		}
+	}
+
+	/// Resets and moves signaled fences that are only referenced by the pool to the free list.
+	fn reclaim(fences: &mut FencePoolFences) -> Result<(), FenceError> {
+		let mut index = 0;
+		while index < fences.rented.len() {
+			let fence = &fences.rented[index];
+			// A device loss is reported by the next submission, the fence is simply not reclaimed
+			if Vrc::strong_count(fence) == 1 && fence.status().unwrap_or(false) {
+				let fence = fences.rented.swap_remove(index);
+				fence.reset()?;
+
+				fences.free.push(fence);
+			} else {
+				index += 1;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns the number of rented and free fences.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if the fences `Vutex` is poisoned.
+	pub fn counts(&self) -> (usize, usize) {
+		let fences = self.fences.lock().expect("vutex poisoned");
+
+		(fences.rented.len(), fences.free.len())
+	}
+
+	pub const fn device(&self) -> &Vrc<Device> {
+		&self.device
+	}
+}
+impl fmt::Debug for FencePool {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("FencePool")
+			.field("device", &self.device)
+			.field("fences", &self.fences)
+			.field(
+				"host_memory_allocator",
+				&self.host_memory_allocator
+			)
+			.finish()
+	}
+}
@@ -1,2 +1,4 @@
 pub mod fence;
 pub mod semaphore;
+#[cfg(feature = "sync2")]
+pub mod sync2;
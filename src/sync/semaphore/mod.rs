@@ -72,6 +72,7 @@ impl Semaphore {
 			host_memory_allocator.as_ref()
 		)?;
 
+		device.child_registry().register(semaphore);
 		Ok(Vrc::new(Semaphore {
 			device,
 			semaphore: semaphore,
@@ -91,6 +92,7 @@ impl_common_handle_traits! {
 impl Drop for Semaphore {
 	fn drop(&mut self) {
 		log_trace_common!("Dropping", self, self.semaphore);
+		self.device.child_registry().unregister(self.semaphore);
 
 		unsafe {
 			self.device.destroy_semaphore(
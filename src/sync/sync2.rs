@@ -0,0 +1,113 @@
+//! Conversions between `VK_KHR_synchronization2` and legacy synchronization flags.
+//!
+//! Synchronization2 commands fall back to the legacy commands when the extension is not enabled on the device,
+//! in which case the 64-bit stage and access flags are mapped onto the closest legacy flags.
+
+use ash::vk;
+
+/// Converts `stages` to legacy pipeline stage flags.
+///
+/// Stages that only exist in synchronization2 are widened to the legacy stages that contain them,
+/// for example `COPY` becomes `TRANSFER`. Empty flags become `empty_fallback`, which should be `TOP_OF_PIPE`
+/// for source stages and `BOTTOM_OF_PIPE` for destination stages.
+pub fn legacy_stages(stages: vk::PipelineStageFlags2, empty_fallback: vk::PipelineStageFlags) -> vk::PipelineStageFlags {
+	if stages.is_empty() {
+		return empty_fallback
+	}
+
+	// the lower 32 bits are shared with the legacy flags
+	let mut result = vk::PipelineStageFlags::from_raw(stages.as_raw() as u32);
+
+	if stages.intersects(
+		vk::PipelineStageFlags2::COPY
+			| vk::PipelineStageFlags2::RESOLVE
+			| vk::PipelineStageFlags2::BLIT
+			| vk::PipelineStageFlags2::CLEAR
+	) {
+		result |= vk::PipelineStageFlags::TRANSFER;
+	}
+	if stages.intersects(vk::PipelineStageFlags2::INDEX_INPUT | vk::PipelineStageFlags2::VERTEX_ATTRIBUTE_INPUT) {
+		result |= vk::PipelineStageFlags::VERTEX_INPUT;
+	}
+	if stages.contains(vk::PipelineStageFlags2::PRE_RASTERIZATION_SHADERS) {
+		result |= vk::PipelineStageFlags::VERTEX_SHADER
+			| vk::PipelineStageFlags::TESSELLATION_CONTROL_SHADER
+			| vk::PipelineStageFlags::TESSELLATION_EVALUATION_SHADER
+			| vk::PipelineStageFlags::GEOMETRY_SHADER;
+	}
+
+	result
+}
+
+/// Converts `access` to legacy access flags.
+///
+/// Access flags that only exist in synchronization2 are widened to the legacy flags that contain them,
+/// for example `SHADER_SAMPLED_READ` becomes `SHADER_READ`.
+pub fn legacy_access(access: vk::AccessFlags2) -> vk::AccessFlags {
+	// the lower 32 bits are shared with the legacy flags
+	let mut result = vk::AccessFlags::from_raw(access.as_raw() as u32);
+
+	if access.intersects(vk::AccessFlags2::SHADER_SAMPLED_READ | vk::AccessFlags2::SHADER_STORAGE_READ) {
+		result |= vk::AccessFlags::SHADER_READ;
+	}
+	if access.contains(vk::AccessFlags2::SHADER_STORAGE_WRITE) {
+		result |= vk::AccessFlags::SHADER_WRITE;
+	}
+
+	result
+}
+
+/// Returns whether the `synchronization2` feature is enabled in the `p_next` chain of `create_info`.
+///
+/// ### Safety
+///
+/// `create_info` must be a valid `vk::DeviceCreateInfo`, including its `p_next` chain.
+pub(crate) unsafe fn synchronization2_feature_enabled(create_info: &vk::DeviceCreateInfo) -> bool {
+	let mut next = create_info.p_next as *const vk::BaseInStructure;
+	while let Some(structure) = next.as_ref() {
+		let enabled = match structure.s_type {
+			vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES => {
+				(*(next as *const vk::PhysicalDeviceSynchronization2Features)).synchronization2
+			}
+			vk::StructureType::PHYSICAL_DEVICE_VULKAN_1_3_FEATURES => (*(next as *const vk::PhysicalDeviceVulkan13Features)).synchronization2,
+			_ => vk::FALSE
+		};
+		if enabled == vk::TRUE {
+			return true
+		}
+
+		next = structure.p_next;
+	}
+
+	false
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::{legacy_access, legacy_stages};
+
+	#[test]
+	fn legacy_conversions() {
+		assert_eq!(
+			legacy_stages(
+				vk::PipelineStageFlags2::FRAGMENT_SHADER | vk::PipelineStageFlags2::COPY,
+				vk::PipelineStageFlags::TOP_OF_PIPE
+			),
+			vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::TRANSFER
+		);
+		assert_eq!(
+			legacy_stages(
+				vk::PipelineStageFlags2::NONE,
+				vk::PipelineStageFlags::BOTTOM_OF_PIPE
+			),
+			vk::PipelineStageFlags::BOTTOM_OF_PIPE
+		);
+
+		assert_eq!(
+			legacy_access(vk::AccessFlags2::SHADER_SAMPLED_READ | vk::AccessFlags2::TRANSFER_WRITE),
+			vk::AccessFlags::SHADER_READ | vk::AccessFlags::TRANSFER_WRITE
+		);
+	}
+}
@@ -0,0 +1,483 @@
+//! Integration tests exercising the GPU path, see the `integration_tests` feature.
+//!
+//! The tests are ignored by default because they need a Vulkan driver. A software driver such as lavapipe or SwiftShader is enough.
+
+use std::{error::Error, num::NonZeroU32, ops::Deref};
+
+use ash::vk;
+
+use super::{compare_approx, ComputeContext};
+use crate::{
+	bootstrap::{self, BootstrapContext},
+	framebuffer::render_target::{RenderTarget, RenderTargetParams},
+	memory::device::naive::NaiveDeviceMemoryAllocator,
+	prelude::{
+		Buffer,
+		BufferAllocatorParams,
+		BufferImageCopy,
+		BufferMemoryBarrier,
+		CommandBuffer,
+		CommandBufferBeginInfo,
+		CommandBufferRecordingLockInsideRenderPass,
+		CommandPool,
+		DescriptorSetLayout,
+		Fence,
+		GraphicsPipeline,
+		HasHandle,
+		HeadlessTarget,
+		HostMemoryAllocator,
+		Image,
+		ImageAllocatorParams,
+		ImageLayoutAttachment,
+		ImageLayoutFinal,
+		ImageMemoryBarrier,
+		ImageSize,
+		ImageSizeInfo,
+		ImageSubresourceLayers,
+		ImageTilingAndLayout,
+		MemoryBarrier,
+		MipmapLevels,
+		PipelineLayout,
+		PresentTarget,
+		PushConstantRange,
+		RenderPass,
+		Semaphore,
+		ShaderModule,
+		SharingMode,
+		SubpassDependency,
+		SubpassDescription,
+		Vrc
+	},
+	render_pass::params::{AttachmentDescription, AttachmentOps, AttachmentReference},
+	shader::compile::CompileOptions
+};
+
+const SIZE: u32 = 32;
+const CLEAR: [u8; 4] = [0, 0, 255, 255];
+const RED: [u8; 4] = [255, 0, 0, 255];
+
+/// Renders into a `SIZE`x`SIZE` `R8G8B8A8_UNORM` image cleared to `CLEAR` and reads the pixels back in row-major order.
+fn render_offscreen(
+	context: &BootstrapContext,
+	record: impl FnOnce(&Vrc<RenderPass>, &CommandBufferRecordingLockInsideRenderPass) -> Result<(), Box<dyn Error>>
+) -> Result<Vec<[u8; 4]>, Box<dyn Error>> {
+	let device = &context.device;
+	let queue = &context.queues.graphics;
+	let size = NonZeroU32::new(SIZE).unwrap();
+
+	let attachments = [AttachmentDescription::new(
+		false,
+		vk::Format::R8G8B8A8_UNORM,
+		vk::SampleCountFlags::TYPE_1,
+		AttachmentOps::Color { load: vk::AttachmentLoadOp::CLEAR, store: vk::AttachmentStoreOp::STORE },
+		vk::ImageLayout::UNDEFINED,
+		ImageLayoutFinal::TRANSFER_SRC_OPTIMAL
+	)];
+	let color_references = [AttachmentReference::new(
+		Some(0),
+		ImageLayoutAttachment::COLOR_ATTACHMENT_OPTIMAL
+	)];
+	let subpasses = [SubpassDescription::new(
+		None,
+		Some((&color_references, None)),
+		None,
+		None
+	)?];
+	// Makes the color writes and the final layout transition visible to the copy
+	let dependencies = [SubpassDependency::color_output_to_external(
+		0,
+		vk::PipelineStageFlags::TRANSFER,
+		vk::AccessFlags::TRANSFER_READ
+	)];
+	let render_pass = RenderPass::new(
+		device.clone(),
+		&attachments,
+		&subpasses,
+		&dependencies,
+		HostMemoryAllocator::Unspecified()
+	)?;
+
+	let render_target = RenderTarget::new(
+		render_pass.clone(),
+		RenderTargetParams {
+			color_format: vk::Format::R8G8B8A8_UNORM,
+			color_usage: vk::ImageUsageFlags::TRANSFER_SRC,
+			samples: vk::SampleCountFlags::TYPE_1,
+			depth_stencil_format: None,
+			queue_family_index: queue.queue_family_index()
+		},
+		[size, size],
+		NaiveDeviceMemoryAllocator::new(device.clone()),
+		vk::MemoryPropertyFlags::DEVICE_LOCAL,
+		HostMemoryAllocator::Unspecified()
+	)?;
+
+	let allocator = NaiveDeviceMemoryAllocator::new(device.clone());
+	let readback = Buffer::new(
+		device.clone(),
+		std::num::NonZeroU64::new(SIZE as u64 * SIZE as u64 * 4).unwrap(),
+		vk::BufferUsageFlags::TRANSFER_DST,
+		SharingMode::one(queue.queue_family_index()),
+		BufferAllocatorParams::Some {
+			allocator: &allocator,
+			requirements: vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT
+		},
+		HostMemoryAllocator::Unspecified()
+	)?;
+
+	let command_pool = CommandPool::new(
+		queue,
+		vk::CommandPoolCreateFlags::TRANSIENT,
+		HostMemoryAllocator::Unspecified()
+	)?;
+	let [command_buffer] = CommandBuffer::new(command_pool, false)?;
+	{
+		let recording = command_buffer.begin_recording(CommandBufferBeginInfo::OneTime)?;
+		let inside = recording.begin_render_pass(
+			&render_pass,
+			render_target.framebuffer(),
+			vk::Rect2D { offset: Default::default(), extent: vk::Extent2D { width: SIZE, height: SIZE } },
+			[vk::ClearValue {
+				color: vk::ClearColorValue { float32: [0.0, 0.0, 1.0, 1.0] }
+			}],
+			true
+		);
+		record(&render_pass, &inside)?;
+		let recording = inside.end_render_pass();
+
+		recording.copy_image_to_buffer(
+			render_target.color().image(),
+			vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+			&readback,
+			[BufferImageCopy::new(
+				0,
+				None,
+				ImageSubresourceLayers::new(
+					vk::ImageAspectFlags::COLOR,
+					0,
+					0,
+					NonZeroU32::new(1).unwrap()
+				),
+				Default::default(),
+				vk::Extent3D { width: SIZE, height: SIZE, depth: 1 }
+			)]
+		);
+		recording.pipeline_barrier(
+			vk::PipelineStageFlags::TRANSFER,
+			vk::PipelineStageFlags::HOST,
+			[MemoryBarrier::new(
+				vk::AccessFlags::TRANSFER_WRITE,
+				vk::AccessFlags::HOST_READ
+			)],
+			[] as [BufferMemoryBarrier; 0],
+			[] as [ImageMemoryBarrier; 0]
+		);
+		recording.end()?;
+	}
+
+	let fence = Fence::new(
+		device.clone(),
+		false,
+		HostMemoryAllocator::Unspecified()
+	)?;
+	queue.submit(
+		[],
+		[],
+		[command_buffer.deref()],
+		[],
+		Some(&fence)
+	)?;
+	fence.wait(Default::default())?;
+
+	Ok(ComputeContext::read_buffer(&readback)?)
+}
+
+fn headless_context() -> Result<BootstrapContext, Box<dyn Error>> {
+	crate::test::setup_testing_logger();
+
+	Ok(bootstrap::quick_init(None, Default::default())?)
+}
+
+#[test]
+#[ignore]
+fn clear_readback() -> Result<(), Box<dyn Error>> {
+	let context = headless_context()?;
+
+	let pixels = render_offscreen(&context, |_, _| Ok(()))?;
+
+	assert_eq!(pixels.len(), (SIZE * SIZE) as usize);
+	assert!(pixels.iter().all(|&pixel| pixel == CLEAR));
+
+	Ok(())
+}
+
+#[test]
+#[ignore]
+fn draw_left_half() -> Result<(), Box<dyn Error>> {
+	const VERTEX: &str = "
+		#version 450
+		const vec2 POSITIONS[6] = vec2[](
+			vec2(-1.0, -1.0), vec2(0.0, -1.0), vec2(-1.0, 1.0),
+			vec2(0.0, -1.0), vec2(0.0, 1.0), vec2(-1.0, 1.0)
+		);
+		void main() {
+			gl_Position = vec4(POSITIONS[gl_VertexIndex], 0.0, 1.0);
+		}
+	";
+	const FRAGMENT: &str = "
+		#version 450
+		layout(location = 0) out vec4 color;
+		void main() {
+			color = vec4(1.0, 0.0, 0.0, 1.0);
+		}
+	";
+
+	let context = headless_context()?;
+	let device = &context.device;
+
+	let vertex = ShaderModule::from_glsl(
+		device.clone(),
+		VERTEX,
+		vk::ShaderStageFlags::VERTEX,
+		&Default::default(),
+		HostMemoryAllocator::Unspecified()
+	)?;
+	let fragment = ShaderModule::from_glsl(
+		device.clone(),
+		FRAGMENT,
+		vk::ShaderStageFlags::FRAGMENT,
+		&Default::default(),
+		HostMemoryAllocator::Unspecified()
+	)?;
+	let pipeline_layout = PipelineLayout::new(
+		device.clone(),
+		[] as [&DescriptorSetLayout; 0],
+		[] as [PushConstantRange; 0],
+		HostMemoryAllocator::Unspecified()
+	)?;
+
+	let pixels = render_offscreen(&context, |render_pass, recording| {
+		crate::describe_graphics_pipeline! {
+			let create_info;
+
+			Shaders {
+				stages: [
+					vertex => vk::ShaderStageFlags::VERTEX,
+					fragment => vk::ShaderStageFlags::FRAGMENT
+				]
+				input: {}
+				topology: vk::PrimitiveTopology::TRIANGLE_LIST
+			}
+
+			Viewport {
+				viewports: {
+					[
+						[SIZE as f32, SIZE as f32]
+					]
+				}
+			}
+
+			Rasterization {
+				polygon_mode: Default::default()
+			}
+
+			Multisampling {
+				samples: vk::SampleCountFlags::TYPE_1
+			}
+
+			ColorBlend {
+				attachments: [
+					{ opaque }
+				]
+			}
+
+			Deps {
+				layout: pipeline_layout,
+				render_pass: render_pass
+			}
+		}
+		let pipeline = unsafe {
+			GraphicsPipeline::from_create_info(
+				device.clone(),
+				create_info,
+				HostMemoryAllocator::Unspecified()
+			)?
+		};
+
+		recording.bind_graphics_pipeline(&pipeline);
+		recording.draw(6, 1, 0, 0);
+
+		Ok(())
+	})?;
+
+	for (index, &pixel) in pixels.iter().enumerate() {
+		let expected = if (index as u32 % SIZE) < SIZE / 2 { RED } else { CLEAR };
+		assert_eq!(
+			pixel,
+			expected,
+			"pixel [{}, {}]",
+			index as u32 % SIZE,
+			index as u32 / SIZE
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+#[ignore]
+fn compute_doubles_values() -> Result<(), Box<dyn Error>> {
+	const COMPUTE: &str = "
+		#version 450
+		layout(local_size_x = 1) in;
+		layout(set = 0, binding = 0) buffer Values { float values[]; };
+		void main() {
+			values[gl_GlobalInvocationID.x] *= 2.0;
+		}
+	";
+
+	crate::test::setup_testing_logger();
+	let context = ComputeContext::new()?;
+
+	let code = crate::shader::compile::compile_glsl(
+		COMPUTE,
+		vk::ShaderStageFlags::COMPUTE,
+		&CompileOptions::default()
+	)?;
+	let buffer = context.upload_buffer(&[1.0f32, 2.0, 3.0, 4.0])?;
+	context.run_compute(&code, &[&buffer], [4, 1, 1])?;
+
+	let result: Vec<f32> = ComputeContext::read_buffer(&buffer)?;
+	assert_eq!(
+		compare_approx(&result, &[2.0, 4.0, 6.0, 8.0], 0.001),
+		None
+	);
+
+	Ok(())
+}
+
+#[test]
+#[ignore]
+fn headless_target_round_robin() -> Result<(), Box<dyn Error>> {
+	let context = headless_context()?;
+	let device = &context.device;
+	let queue = &context.queues.graphics;
+	let size = NonZeroU32::new(SIZE).unwrap();
+
+	let allocator = NaiveDeviceMemoryAllocator::new(device.clone());
+	let images = (0 .. 2)
+		.map(|_| {
+			Image::new(
+				device.clone(),
+				vk::Format::R8G8B8A8_UNORM,
+				ImageSizeInfo::from(ImageSize::from(ImageSize::new_2d(
+					size,
+					size,
+					NonZeroU32::new(1).unwrap(),
+					MipmapLevels::One()
+				))),
+				ImageTilingAndLayout::OptimalUndefined(),
+				vk::ImageUsageFlags::COLOR_ATTACHMENT,
+				SharingMode::one(queue.queue_family_index()),
+				ImageAllocatorParams::Some { allocator: &allocator, requirements: vk::MemoryPropertyFlags::DEVICE_LOCAL },
+				HostMemoryAllocator::Unspecified()
+			)
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+	let target = HeadlessTarget::new(queue.clone(), images)?;
+
+	assert_eq!(target.image_count(), 2);
+	assert_eq!(target.extent(), vk::Extent2D { width: SIZE, height: SIZE });
+
+	let semaphore = Semaphore::binary(device.clone(), HostMemoryAllocator::Unspecified())?;
+	let fence = Fence::new(device.clone(), false, HostMemoryAllocator::Unspecified())?;
+	for expected in [0, 1, 0] {
+		let index = target.acquire(Default::default(), (&semaphore, fence.deref()).into())?.index();
+		assert_eq!(index, expected);
+
+		target.present(queue, &[&semaphore], index)?;
+		fence.wait(Default::default())?;
+		fence.reset()?;
+	}
+
+	Ok(())
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+#[ignore]
+fn steady_state_frames_do_not_allocate() -> Result<(), Box<dyn Error>> {
+	let context = headless_context()?;
+	let device = &context.device;
+	let queue = &context.queues.graphics;
+	let size = NonZeroU32::new(SIZE).unwrap();
+
+	let allocator = NaiveDeviceMemoryAllocator::new(device.clone());
+	let images = (0 .. 2)
+		.map(|_| {
+			Image::new(
+				device.clone(),
+				vk::Format::R8G8B8A8_UNORM,
+				ImageSizeInfo::from(ImageSize::from(ImageSize::new_2d(
+					size,
+					size,
+					NonZeroU32::new(1).unwrap(),
+					MipmapLevels::One()
+				))),
+				ImageTilingAndLayout::OptimalUndefined(),
+				vk::ImageUsageFlags::COLOR_ATTACHMENT,
+				SharingMode::one(queue.queue_family_index()),
+				ImageAllocatorParams::Some { allocator: &allocator, requirements: vk::MemoryPropertyFlags::DEVICE_LOCAL },
+				HostMemoryAllocator::Unspecified()
+			)
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+	let target = HeadlessTarget::new(queue.clone(), images)?;
+
+	let command_pool = CommandPool::new(
+		queue,
+		vk::CommandPoolCreateFlags::empty(),
+		HostMemoryAllocator::Unspecified()
+	)?;
+	let [command_buffer] = CommandBuffer::new(command_pool, false)?;
+	command_buffer
+		.begin_recording(CommandBufferBeginInfo::ManyTimes { simultaneous: false })?
+		.end()?;
+
+	let acquire_semaphore = Semaphore::binary(device.clone(), HostMemoryAllocator::Unspecified())?;
+	let render_semaphore = Semaphore::binary(device.clone(), HostMemoryAllocator::Unspecified())?;
+	let fence = Fence::new(device.clone(), false, HostMemoryAllocator::Unspecified())?;
+
+	let frame = || -> Result<(), Box<dyn Error>> {
+		let index = target.acquire(Default::default(), (&acquire_semaphore).into())?.index();
+		queue.submit(
+			[&acquire_semaphore],
+			[vk::PipelineStageFlags::ALL_COMMANDS],
+			[command_buffer.deref()],
+			[&render_semaphore],
+			Some(&fence)
+		)?;
+		target.present(queue, &[&render_semaphore], index)?;
+
+		fence.wait(Default::default())?;
+		fence.reset()?;
+
+		Ok(())
+	};
+
+	// Warm up, for example lazily initialized thread locals
+	for _ in 0 .. 4 {
+		frame()?;
+	}
+
+	// Trace logs are formatted on the heap
+	let max_level = log::max_level();
+	log::set_max_level(log::LevelFilter::Off);
+	let (result, allocations) = super::super::test::count_allocations(|| (0 .. 16).try_for_each(|_| frame()));
+	log::set_max_level(max_level);
+	result?;
+
+	assert_eq!(allocations, 0);
+
+	Ok(())
+}
@@ -0,0 +1,409 @@
+//! Utilities for writing headless GPU tests against vulkayes.
+//!
+//! The `ComputeContext` creates a minimal instance, device and compute queue, and provides helpers
+//! to upload data into host visible buffers, run a compute shader over them and read the results back.
+//!
+//! ```no_run
+//! # use vulkayes_core::testing::{ComputeContext, compare_approx};
+//! # let spirv: Vec<u32> = Vec::new();
+//! let context = ComputeContext::new().unwrap();
+//!
+//! let buffer = context.upload_buffer(&[1.0f32, 2.0, 3.0, 4.0]).unwrap();
+//! context.run_compute(&spirv, &[&buffer], [4, 1, 1]).unwrap();
+//!
+//! let result: Vec<f32> = ComputeContext::read_buffer(&buffer).unwrap();
+//! assert_eq!(compare_approx(&result, &[2.0, 4.0, 6.0, 8.0], 0.001), None);
+//! ```
+
+use std::{
+	num::{NonZeroU32, NonZeroU64},
+	ops::Deref
+};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+	command::error::{BindDescriptorSetsError, CommandBufferError, CommandPoolError},
+	descriptor::error::{DescriptorPoolError, DescriptorSetError, DescriptorSetLayoutError, DescriptorSetWriteError},
+	device::error::DeviceError,
+	instance::error::{InstanceError, PhysicalDeviceEnumerationError},
+	memory::device::{
+		naive::{AllocationError, NaiveDeviceMemoryAllocator},
+		MapError,
+		MappingAccessResult
+	},
+	pipeline::error::{ComputePipelineError, PipelineLayoutError},
+	prelude::{
+		ApplicationInfo,
+		Buffer,
+		BufferAllocatorParams,
+		BufferMemoryBarrier,
+		CommandBuffer,
+		CommandBufferBeginInfo,
+		CommandPool,
+		ComputePipeline,
+		DescriptorBufferInfo,
+		DescriptorPool,
+		DescriptorPoolSize,
+		DescriptorSet,
+		DescriptorSetCopy,
+		DescriptorSetLayout,
+		DescriptorSetLayoutBinding,
+		DescriptorSetLayoutBindingGenericType,
+		DescriptorSetWrite,
+		DescriptorSetWriteData,
+		DescriptorTypeBuffer,
+		Device,
+		Entry,
+		Fence,
+		HasHandle,
+		HostMemoryAllocator,
+		ImageMemoryBarrier,
+		Instance,
+		MemoryBarrier,
+		PipelineLayout,
+		PushConstantRange,
+		QueueCreateInfo,
+		SharingMode,
+		ShaderModule,
+		Vrc
+	},
+	queue::{error::QueueSubmitError, Queue},
+	resource::buffer::error::BufferError,
+	shader::error::ShaderError,
+	sync::fence::error::FenceError,
+	util::fmt::VkVersion
+};
+
+#[derive(Error, Debug)]
+pub enum TestingError {
+	#[error("Could not load Vulkan entry")]
+	Loading(#[from] ash::LoadingError),
+	#[error("Could not create instance")]
+	Instance(#[from] InstanceError),
+	#[error("Could not enumerate physical devices")]
+	PhysicalDeviceEnumeration(#[from] PhysicalDeviceEnumerationError),
+	#[error("No physical device with a compute queue family was found")]
+	NoComputeDevice,
+	#[error("Could not create device")]
+	Device(#[from] DeviceError),
+	#[error("Could not create buffer")]
+	Buffer(#[from] BufferError<AllocationError>),
+	#[error("Buffer has no bound memory")]
+	BufferNoMemory,
+	#[error("Could not map memory")]
+	Map(#[from] MapError),
+	#[error("Could not create shader module")]
+	Shader(#[from] ShaderError),
+	#[error("Could not create descriptor set layout")]
+	DescriptorSetLayout(#[from] DescriptorSetLayoutError),
+	#[error("Could not create descriptor pool")]
+	DescriptorPool(#[from] DescriptorPoolError),
+	#[error("Could not allocate descriptor set")]
+	DescriptorSet(#[from] DescriptorSetError),
+	#[error("Could not write descriptor set")]
+	DescriptorSetWrite(#[from] DescriptorSetWriteError),
+	#[error("Could not create pipeline layout")]
+	PipelineLayout(#[from] PipelineLayoutError),
+	#[error("Could not create compute pipeline")]
+	ComputePipeline(#[from] ComputePipelineError),
+	#[error("Could not create command pool")]
+	CommandPool(#[from] CommandPoolError),
+	#[error("Could not record command buffer")]
+	CommandBuffer(#[from] CommandBufferError),
+	#[error("Could not bind descriptor sets")]
+	BindDescriptorSets(#[from] BindDescriptorSetsError),
+	#[error("Could not submit to queue")]
+	QueueSubmit(#[from] QueueSubmitError),
+	#[error("Could not wait for fence")]
+	Fence(#[from] FenceError)
+}
+
+/// Minimal headless context with a compute queue.
+#[derive(Debug)]
+pub struct ComputeContext {
+	pub device: Vrc<Device>,
+	pub queue: Vrc<Queue>,
+	pub allocator: NaiveDeviceMemoryAllocator
+}
+impl ComputeContext {
+	/// Creates a new instance without any layers or extensions and a device on the first physical device that has a compute queue family.
+	pub fn new() -> Result<Self, TestingError> {
+		let entry = Entry::new()?;
+		let api_version = entry.instance_version();
+
+		let instance = Instance::new(
+			entry,
+			ApplicationInfo {
+				application_name: "vulkayes testing",
+				engine_name: "vulkayes",
+				application_version: VkVersion::new(0, 1, 0),
+				engine_version: VkVersion::new(0, 1, 0),
+				api_version
+			},
+			None,
+			None,
+			HostMemoryAllocator::Unspecified(),
+			crate::instance::debug::DebugCallback::None()
+		)?;
+
+		let (physical_device, queue_family_index) = instance
+			.physical_devices()?
+			.filter_map(|physical_device| {
+				let family = physical_device
+					.queue_family_properties()
+					.iter()
+					.position(|properties| properties.queue_flags.contains(vk::QueueFlags::COMPUTE));
+
+				family.map(|index| (physical_device, index as u32))
+			})
+			.next()
+			.ok_or(TestingError::NoComputeDevice)?;
+
+		let mut device_data = Device::new(
+			physical_device,
+			[QueueCreateInfo { queue_family_index, queue_priorities: [1.0] }],
+			None,
+			None,
+			Default::default(),
+			HostMemoryAllocator::Unspecified()
+		)?;
+
+		let queue = device_data.queues.remove(0);
+		let allocator = NaiveDeviceMemoryAllocator::new(device_data.device.clone());
+
+		Ok(ComputeContext { device: device_data.device, queue, allocator })
+	}
+
+	/// Creates a host visible and coherent storage buffer and fills it with `data`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `data` is empty.
+	pub fn upload_buffer<T: Copy>(&self, data: &[T]) -> Result<Vrc<Buffer>, TestingError> {
+		let size = NonZeroU64::new(std::mem::size_of_val(data) as u64).expect("data must not be empty");
+
+		let buffer = Buffer::new(
+			self.device.clone(),
+			size,
+			vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST,
+			SharingMode::one(self.queue.queue_family_index()),
+			BufferAllocatorParams::Some {
+				allocator: &self.allocator,
+				requirements: vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT
+			},
+			HostMemoryAllocator::Unspecified()
+		)?;
+
+		buffer
+			.memory()
+			.ok_or(TestingError::BufferNoMemory)?
+			.map_memory_with(|mut access| {
+				access.write_slice(data, 0, Default::default());
+				MappingAccessResult::Unmap
+			})?;
+
+		Ok(buffer)
+	}
+
+	/// Reads the contents of a host visible buffer as a vector of `T`s.
+	///
+	/// Trailing bytes which do not form a whole `T` are ignored.
+	pub fn read_buffer<T: Copy>(buffer: &Buffer) -> Result<Vec<T>, TestingError> {
+		let count = buffer.size_of::<T>();
+		let mut result = Vec::with_capacity(count);
+
+		buffer.memory().ok_or(TestingError::BufferNoMemory)?.map_memory_with(|mut access| {
+			let bytes = access.bytes_mut();
+			for index in 0 .. count {
+				result.push(unsafe { std::ptr::read_unaligned(bytes.as_ptr().add(index * std::mem::size_of::<T>()) as *const T) });
+			}
+
+			MappingAccessResult::Unmap
+		})?;
+
+		Ok(result)
+	}
+
+	/// Runs the `main` entry point of compute shader `code` once and waits for it to finish.
+	///
+	/// `buffers` are bound as storage buffers to bindings `0 .. buffers.len()` of descriptor set `0`.
+	///
+	/// ### Panic
+	///
+	/// This function will panic if `buffers` is empty.
+	pub fn run_compute(&self, code: impl AsRef<[u32]>, buffers: &[&Vrc<Buffer>], group_count: [u32; 3]) -> Result<(), TestingError> {
+		let buffer_count = NonZeroU32::new(buffers.len() as u32).expect("buffers must not be empty");
+
+		let shader = ShaderModule::new(
+			self.device.clone(),
+			code,
+			HostMemoryAllocator::Unspecified()
+		)?;
+
+		let set_layout = DescriptorSetLayout::new(
+			self.device.clone(),
+			vk::DescriptorSetLayoutCreateFlags::empty(),
+			buffers.iter().map(|_| {
+				DescriptorSetLayoutBinding::Generic(
+					DescriptorSetLayoutBindingGenericType::STORAGE_BUFFER,
+					NonZeroU32::new(1).unwrap(),
+					vk::ShaderStageFlags::COMPUTE
+				)
+			}),
+			HostMemoryAllocator::Unspecified()
+		)?;
+		let descriptor_pool = DescriptorPool::new(
+			self.device.clone(),
+			vk::DescriptorPoolCreateFlags::empty(),
+			NonZeroU32::new(1).unwrap(),
+			std::iter::once(DescriptorPoolSize { descriptor_type: vk::DescriptorType::STORAGE_BUFFER, count: buffer_count }),
+			None,
+			HostMemoryAllocator::Unspecified()
+		)?;
+		let descriptor_set = DescriptorSet::new(descriptor_pool, set_layout.clone())?;
+
+		let buffer_infos: Vec<_> = buffers
+			.iter()
+			.map(|buffer| [DescriptorBufferInfo::new(buffer, 0, buffer.size())])
+			.collect();
+		let writes = buffer_infos
+			.iter()
+			.enumerate()
+			.map(|(binding, info)| {
+				DescriptorSetWrite::new(
+					descriptor_set.safe_handle(),
+					binding as u32,
+					0,
+					DescriptorSetWriteData::Buffer(DescriptorTypeBuffer::STORAGE_BUFFER, info)
+				)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		DescriptorSet::update(&self.device, writes, [] as [DescriptorSetCopy; 0]);
+
+		let pipeline_layout = PipelineLayout::new(
+			self.device.clone(),
+			[set_layout.deref()],
+			[] as [PushConstantRange; 0],
+			HostMemoryAllocator::Unspecified()
+		)?;
+
+		crate::describe_compute_pipeline! {
+			let create_info;
+
+			Shaders {
+				stage: shader => vk::ShaderStageFlags::COMPUTE
+			}
+
+			Deps {
+				layout: pipeline_layout
+			}
+		}
+		let pipeline = unsafe {
+			ComputePipeline::from_create_info(
+				self.device.clone(),
+				create_info,
+				HostMemoryAllocator::Unspecified()
+			)?
+		};
+
+		let command_pool = CommandPool::new(
+			&self.queue,
+			vk::CommandPoolCreateFlags::TRANSIENT,
+			HostMemoryAllocator::Unspecified()
+		)?;
+		let [command_buffer] = CommandBuffer::new(command_pool, false)?;
+		{
+			let recording = command_buffer.begin_recording(CommandBufferBeginInfo::OneTime)?;
+			recording.bind_compute_pipeline(&pipeline);
+			recording.bind_descriptor_sets(
+				vk::PipelineBindPoint::COMPUTE,
+				&pipeline_layout,
+				0,
+				[descriptor_set.deref()],
+				[] as [u32; 0]
+			)?;
+			recording.dispatch(group_count);
+			recording.pipeline_barrier(
+				vk::PipelineStageFlags::COMPUTE_SHADER,
+				vk::PipelineStageFlags::HOST,
+				[MemoryBarrier::new(
+					vk::AccessFlags::SHADER_WRITE,
+					vk::AccessFlags::HOST_READ
+				)],
+				[] as [BufferMemoryBarrier; 0],
+				[] as [ImageMemoryBarrier; 0]
+			);
+			recording.end()?;
+		}
+
+		let fence = Fence::new(
+			self.device.clone(),
+			false,
+			HostMemoryAllocator::Unspecified()
+		)?;
+		self.queue.submit(
+			[],
+			[],
+			[command_buffer.deref()],
+			[],
+			Some(&fence)
+		)?;
+		fence.wait(Default::default())?;
+
+		Ok(())
+	}
+}
+
+/// Compares `actual` and `expected` element-wise with an absolute `tolerance`.
+///
+/// Returns the index and values of the first mismatching element, or `None` if all elements match.
+/// A length mismatch is reported at index `min(actual.len(), expected.len())` with `NAN` standing in for the missing value.
+pub fn compare_approx(actual: &[f32], expected: &[f32], tolerance: f32) -> Option<(usize, f32, f32)> {
+	for (index, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+		let diff = (a - e).abs();
+		if diff.is_nan() || diff > tolerance {
+			return Some((index, *a, *e))
+		}
+	}
+
+	if actual.len() != expected.len() {
+		let index = actual.len().min(expected.len());
+		return Some((
+			index,
+			actual.get(index).copied().unwrap_or(f32::NAN),
+			expected.get(index).copied().unwrap_or(f32::NAN)
+		))
+	}
+
+	None
+}
+
+#[cfg(all(test, feature = "integration_tests"))]
+mod integration;
+
+#[cfg(test)]
+mod test {
+	use super::compare_approx;
+
+	#[test]
+	fn compare_approx_within_tolerance() {
+		assert_eq!(
+			compare_approx(&[1.0, 2.0005], &[1.0, 2.0], 0.001),
+			None
+		);
+	}
+
+	#[test]
+	fn compare_approx_reports_first_mismatch() {
+		assert_eq!(
+			compare_approx(&[1.0, 2.5, 3.0], &[1.0, 2.0, 4.0], 0.1),
+			Some((1, 2.5, 2.0))
+		);
+		assert_eq!(
+			compare_approx(&[1.0], &[1.0, 2.0], 0.1).map(|m| m.0),
+			Some(1)
+		);
+	}
+}
@@ -0,0 +1,191 @@
+//! Runtime diagnostics of objects that outlive their intended lifetime.
+//!
+//! Parents (`Instance`, `Device`) keep a registry of their children when the `debug_destruction_order` feature is enabled.
+//! Children are registered when created and unregistered when destroyed.
+//!
+//! Every child keeps its parent alive, so a leaked child (for example forgotten using `std::mem::forget` or kept alive
+//! by a reference cycle) also leaks its parent and the parent is never dropped. Such leaks are found by calling `report_live`
+//! at teardown, through `Device::report_live_children` or `Instance::report_live_children`, after the application dropped
+//! all objects it owns: every child still registered at that point was leaked or is still referenced somewhere.
+//!
+//! When the parent is dropped while some children are still registered, a child was destroyed without being unregistered,
+//! and an error listing those children is logged.
+//!
+//! Children are keyed by their object type and raw handle. Non-dispatchable handles of distinct objects may be equal,
+//! so each key counts its registrations.
+//!
+//! With the feature disabled all methods are no-ops and the registry is zero-sized.
+
+use std::fmt::Debug;
+
+use ash::vk;
+
+#[cfg(feature = "debug_destruction_order")]
+use crate::util::{hash::VHashMap, sync::Vutex};
+
+#[cfg(feature = "debug_destruction_order")]
+#[derive(Debug)]
+struct RegisteredChild {
+	name: String,
+	count: usize
+}
+
+pub struct ChildRegistry {
+	#[cfg(feature = "debug_destruction_order")]
+	children: Vutex<VHashMap<(vk::ObjectType, u64), RegisteredChild>>
+}
+impl ChildRegistry {
+	pub fn new() -> Self {
+		ChildRegistry {
+			#[cfg(feature = "debug_destruction_order")]
+			children: Vutex::new(VHashMap::default())
+		}
+	}
+
+	/// Registers a new child identified by `handle`.
+	///
+	/// This also assigns an id to `handle` when the `deterministic_handle_ids` feature is enabled.
+	#[allow(unused_variables)]
+	pub fn register<H: vk::Handle + Copy>(&self, handle: H) {
+		crate::util::handle_id::assign(handle);
+
+		#[cfg(feature = "debug_destruction_order")]
+		{
+			let key = (H::TYPE, handle.as_raw());
+
+			self.children
+				.lock()
+				.expect("vutex poisoned")
+				.entry(key)
+				.or_insert_with(|| RegisteredChild { name: crate::util::fmt::format_handle(handle).to_string(), count: 0 })
+				.count += 1;
+		}
+	}
+
+	/// Unregisters a child identified by `handle`.
+	///
+	/// Unregistering a child that was never registered is a no-op.
+	#[allow(unused_variables)]
+	pub fn unregister<H: vk::Handle + Copy>(&self, handle: H) {
+		#[cfg(feature = "debug_destruction_order")]
+		{
+			let key = (H::TYPE, handle.as_raw());
+
+			let mut children = self.children.lock().expect("vutex poisoned");
+			if let Some(child) = children.get_mut(&key) {
+				child.count -= 1;
+				if child.count == 0 {
+					children.remove(&key);
+				}
+			}
+		}
+
+		crate::util::handle_id::release(handle);
+	}
+
+	/// Returns the number of currently registered children.
+	///
+	/// Always returns `0` when the `debug_destruction_order` feature is disabled.
+	pub fn count(&self) -> usize {
+		#[cfg(feature = "debug_destruction_order")]
+		{
+			self.children.lock().expect("vutex poisoned").values().map(|child| child.count).sum()
+		}
+		#[cfg(not(feature = "debug_destruction_order"))]
+		{
+			0
+		}
+	}
+
+	/// Returns the sorted names of all currently registered children, each repeated by the number of its registrations.
+	///
+	/// Always returns an empty vector when the `debug_destruction_order` feature is disabled.
+	pub fn live(&self) -> Vec<String> {
+		#[allow(unused_mut)]
+		let mut names = Vec::new();
+
+		#[cfg(feature = "debug_destruction_order")]
+		{
+			for child in self.children.lock().expect("vutex poisoned").values() {
+				for _ in 0 .. child.count {
+					names.push(child.name.clone());
+				}
+			}
+			names.sort_unstable();
+		}
+
+		names
+	}
+
+	/// Logs a warning listing all currently registered children of `parent` and returns their number.
+	///
+	/// This is intended to be called at teardown, after all children should have been dropped.
+	pub fn report_live(&self, parent: impl Debug) -> usize {
+		let names = self.live();
+		if !names.is_empty() {
+			log::warn!(
+				"{:?} still has {} live children: {:?}",
+				parent,
+				names.len(),
+				names
+			);
+		}
+
+		names.len()
+	}
+
+	/// Logs an error listing all children of `parent` that are still registered.
+	///
+	/// This is intended to be called from the `Drop` implementation of the parent. Since children keep their parent alive,
+	/// any child registered at that point was destroyed without being unregistered.
+	pub fn report_surviving(&self, parent: impl Debug) {
+		let names = self.live();
+		if !names.is_empty() {
+			log::error!(
+				"{:?} is being destroyed while {} of its children were never unregistered: {:?}",
+				parent,
+				names.len(),
+				names
+			);
+		}
+	}
+}
+impl Default for ChildRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl Debug for ChildRegistry {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("ChildRegistry").field("count", &self.count()).finish()
+	}
+}
+
+#[cfg(all(test, feature = "debug_destruction_order"))]
+mod test {
+	use ash::vk::{self, Handle};
+
+	use super::ChildRegistry;
+
+	#[test]
+	fn registrations_are_counted_per_type() {
+		let registry = ChildRegistry::new();
+
+		// Distinct objects with equal non-dispatchable handles
+		registry.register(vk::Sampler::from_raw(7));
+		registry.register(vk::Sampler::from_raw(7));
+		// Equal raw value of another type
+		registry.register(vk::Buffer::from_raw(7));
+		assert_eq!(registry.count(), 3);
+
+		registry.unregister(vk::Sampler::from_raw(7));
+		assert_eq!(registry.count(), 2);
+		assert_eq!(registry.live().len(), 2);
+
+		registry.unregister(vk::Sampler::from_raw(7));
+		registry.unregister(vk::Buffer::from_raw(7));
+		registry.unregister(vk::Buffer::from_raw(7));
+		assert_eq!(registry.count(), 0);
+		assert_eq!(registry.report_live("parent"), 0);
+	}
+}
@@ -196,6 +196,20 @@ pub fn log_vulkayes_debug_info() {
 	multi_thread: {}
 	insecure_hash: {}
 	runtime_implicit_validations: {}
+	debug_destruction_order: {}
+	deterministic_handle_ids: {}
+	raw_window_handle: {}
+	testing: {}
+	shader_reflection: {}
+	render_pass_analysis: {}
+	sync2: {}
+	shader_compile: {}
+	shader_object: {}
+	renderdoc: {}
+	smallvec: {}
+	profiles: {}
+	ray_tracing: {}
+	integration_tests: {}
 	vulkan1_1: {}
 	vulkan1_2: {}
 ",
@@ -205,12 +219,28 @@ pub fn log_vulkayes_debug_info() {
 		cfg!(feature = "multi_thread"),
 		cfg!(feature = "insecure_hash"),
 		cfg!(feature = "runtime_implicit_validations"),
+		cfg!(feature = "debug_destruction_order"),
+		cfg!(feature = "deterministic_handle_ids"),
+		cfg!(feature = "raw_window_handle"),
+		cfg!(feature = "testing"),
+		cfg!(feature = "shader_reflection"),
+		cfg!(feature = "render_pass_analysis"),
+		cfg!(feature = "sync2"),
+		cfg!(feature = "shader_compile"),
+		cfg!(feature = "shader_object"),
+		cfg!(feature = "renderdoc"),
+		cfg!(feature = "smallvec"),
+		cfg!(feature = "profiles"),
+		cfg!(feature = "ray_tracing"),
+		cfg!(feature = "integration_tests"),
 		cfg!(feature = "vulkan1_1"),
 		cfg!(feature = "vulkan1_2"),
 	);
 }
 
 /// Formats Vulkan handle as `<ObjectType $raw>`.
+///
+/// With the `deterministic_handle_ids` feature, non-null handles are formatted as `<ObjectType #$id>` instead, see `util::handle_id`.
 pub fn format_handle<H: ash::vk::Handle>(handle: H) -> impl Debug + Display {
 	struct Inner {
 		ty: ash::vk::ObjectType,
@@ -218,11 +248,23 @@ pub fn format_handle<H: ash::vk::Handle>(handle: H) -> impl Debug + Display {
 	}
 	impl Debug for Inner {
 		fn fmt(&self, f: &mut Formatter) -> Result {
-			write!(f, "<{:?} 0x{:x}>", self.ty, self.raw)
+			<Inner as Display>::fmt(self, f)
 		}
 	}
 	impl Display for Inner {
 		fn fmt(&self, f: &mut Formatter) -> Result {
+			#[cfg(feature = "deterministic_handle_ids")]
+			{
+				if self.raw != 0 {
+					return write!(
+						f,
+						"<{:?} #{}>",
+						self.ty,
+						crate::util::handle_id::id_of(self.ty, self.raw)
+					)
+				}
+			}
+
 			write!(f, "<{:?} 0x{:x}>", self.ty, self.raw)
 		}
 	}
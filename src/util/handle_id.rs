@@ -0,0 +1,105 @@
+//! Deterministic ids of handles for logs, enabled by the `deterministic_handle_ids` feature.
+//!
+//! Raw handle values are chosen by the driver and differ between runs, which makes logs of two runs hard to compare.
+//! With the feature enabled, each object registered with the `ChildRegistry` of its parent on creation is assigned the next id
+//! of its object type, starting from zero, and `format_handle` shows the id instead of the raw value.
+//!
+//! Ids are released when the object is destroyed, so that a raw handle reused by the driver gets a new id.
+//! Handles of objects that are not registered with a parent, such as instances, queues or command buffers,
+//! are assigned an id the first time they are formatted instead, and keep it even if the driver reuses the raw value.
+//!
+//! With the feature disabled all functions are no-ops.
+
+use ash::vk;
+
+#[cfg(feature = "deterministic_handle_ids")]
+use crate::util::hash::VHashMap;
+
+#[cfg(feature = "deterministic_handle_ids")]
+#[derive(Default)]
+struct HandleIds {
+	next: VHashMap<vk::ObjectType, u64>,
+	assigned: VHashMap<(vk::ObjectType, u64), u64>
+}
+#[cfg(feature = "deterministic_handle_ids")]
+impl HandleIds {
+	fn assign(&mut self, ty: vk::ObjectType, raw: u64) -> u64 {
+		let next = self.next.entry(ty).or_insert(0);
+		let id = *next;
+		*next += 1;
+
+		self.assigned.insert((ty, raw), id);
+		id
+	}
+}
+
+// Global because handles are formatted without access to their parent. Uses the std mutex because `Vutex` is not `Sync` without `multi_thread`.
+#[cfg(feature = "deterministic_handle_ids")]
+static HANDLE_IDS: std::sync::Mutex<Option<HandleIds>> = std::sync::Mutex::new(None);
+
+#[cfg(feature = "deterministic_handle_ids")]
+fn with_ids<R>(f: impl FnOnce(&mut HandleIds) -> R) -> R {
+	// The map is never left in an inconsistent state, so a poisoned lock can be recovered
+	let mut lock = HANDLE_IDS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+	f(lock.get_or_insert_with(Default::default))
+}
+
+/// Assigns the next id of its object type to `handle`, replacing any id it had.
+#[allow(unused_variables)]
+pub fn assign<H: vk::Handle + Copy>(handle: H) {
+	#[cfg(feature = "deterministic_handle_ids")]
+	{
+		with_ids(|ids| ids.assign(H::TYPE, handle.as_raw()));
+	}
+}
+
+/// Releases the id of `handle`.
+///
+/// Releasing a handle that has no id is a no-op.
+#[allow(unused_variables)]
+pub fn release<H: vk::Handle + Copy>(handle: H) {
+	#[cfg(feature = "deterministic_handle_ids")]
+	{
+		with_ids(|ids| ids.assigned.remove(&(H::TYPE, handle.as_raw())));
+	}
+}
+
+/// Returns the id of the handle with type `ty` and raw value `raw`, assigning a new one if it has none.
+#[cfg(feature = "deterministic_handle_ids")]
+pub fn id_of(ty: vk::ObjectType, raw: u64) -> u64 {
+	with_ids(|ids| match ids.assigned.get(&(ty, raw)) {
+		Some(&id) => id,
+		None => ids.assign(ty, raw)
+	})
+}
+
+#[cfg(all(test, feature = "deterministic_handle_ids"))]
+mod test {
+	use ash::vk::{self, Handle};
+
+	use super::*;
+
+	#[test]
+	fn ids_count_per_type() {
+		// Raw values unlikely to collide with handles of other tests
+		let first = vk::Fence::from_raw(0xdead_0001);
+		let second = vk::Fence::from_raw(0xdead_0002);
+
+		assign(first);
+		assign(second);
+		// Other tests may assign fence ids concurrently, so only the order of the ids is checked
+		let first_id = id_of(vk::ObjectType::FENCE, first.as_raw());
+		let second_id = id_of(vk::ObjectType::FENCE, second.as_raw());
+		assert!(second_id > first_id);
+		assert_eq!(
+			crate::util::fmt::format_handle(first).to_string(),
+			format!("<FENCE #{}>", first_id)
+		);
+
+		// A reused raw value gets a new id
+		release(first);
+		assign(first);
+		assert!(id_of(vk::ObjectType::FENCE, first.as_raw()) > second_id);
+	}
+}
@@ -315,6 +315,10 @@ macro_rules! vk_builder_wrap {
 /// 	#[allow(non_camel_case_types)]
 /// 	ERROR_OUT_OF_DEVICE_MEMORY,
 ///
+/// 	#[error("Unexpected result {0}")]
+/// 	#[allow(non_camel_case_types)]
+/// 	UNKNOWN(ash::vk::Result),
+///
 /// 	#[error("Description")]
 /// 	Other(#[from] A)
 /// }
@@ -325,11 +329,7 @@ macro_rules! vk_builder_wrap {
 /// 			ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
 /// 				ImageError::ERROR_OUT_OF_DEVICE_MEMORY
 /// 			}
-/// 			_ => unreachable!(
-/// 				"Cannot create {} from {}",
-/// 				stringify!(ImageError),
-/// 				err
-/// 			)
+/// 			_ => ImageError::UNKNOWN(err)
 /// 		}
 /// 	}
 /// }
@@ -361,15 +361,33 @@ macro_rules! vk_result_error {
 				$vk_error,
 			)+
 
+			/// Result code that is not expected by this error, for example one introduced by a newer driver or extension.
+			#[error("Unexpected result {0}")]
+			#[allow(non_camel_case_types)]
+			UNKNOWN(ash::vk::Result),
+
 			$( $other )*
 		}
+		impl $(< $($generic_bounds)+ >)? $name $(< $($generic_params)+ >)? {
+			/// Returns the `vk::Result` this error was created from, or `None` if it is not a Vulkan error.
+			#[allow(unreachable_patterns)]
+			pub fn vk_result(&self) -> Option<ash::vk::Result> {
+				match self {
+					$(
+						$name::$vk_error => Some(ash::vk::Result::$vk_error),
+					)+
+					$name::UNKNOWN(result) => Some(*result),
+					_ => None
+				}
+			}
+		}
 		impl $(< $($generic_bounds)+ >)? From<ash::vk::Result> for $name $(< $($generic_params)+ >)?  {
 			fn from(err: ash::vk::Result) -> Self {
 				match err {
 					$(
 						ash::vk::Result::$vk_error => $name::$vk_error,
 					)+
-					_ => unreachable!("Cannot create {} from {}", stringify!($name), err)
+					_ => $name::UNKNOWN(err)
 				}
 			}
 		}
@@ -1116,13 +1134,24 @@ macro_rules! collect_iter_faster {
 			$iter: expr, $static_size_hint: expr $(, $global_state_access: expr)?
 		),+
 	) => {
-		// TODO: Benchmark the best solution
-		collect_iter_faster!(
-			@vec
-			$(
-				$iter, $static_size_hint
-			),+
-		)
+		{
+			#[cfg(feature = "smallvec")]
+			let collected = collect_iter_faster!(
+				@smallvec
+				$(
+					$iter, $static_size_hint
+				),+
+			);
+			#[cfg(not(feature = "smallvec"))]
+			let collected = collect_iter_faster!(
+				@vec
+				$(
+					$iter, $static_size_hint
+				),+
+			);
+
+			collected
+		}
 	};
 }
 
@@ -1143,4 +1172,15 @@ mod test {
 		assert_eq!(Foo::offsets().b, 8);
 		assert_eq!(Foo::offsets().c, 16);
 	}
+
+	#[test]
+	#[cfg(feature = "smallvec")]
+	fn collect_iter_faster_inline() {
+		let small = collect_iter_faster!((0 .. 4).map(|i| i * 2), 4);
+		assert!(!small.spilled());
+		assert_eq!(small.as_slice(), [0, 2, 4, 6]);
+
+		let large = collect_iter_faster!(0 .. 5, 4);
+		assert!(large.spilled());
+	}
 }
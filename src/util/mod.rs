@@ -9,8 +9,11 @@ pub mod fmt;
 #[macro_use]
 pub mod sync;
 
+pub mod destruction;
 pub mod handle;
+pub mod handle_id;
 pub mod hash;
+pub mod result;
 pub mod string;
 pub mod transparent;
 pub mod validations;
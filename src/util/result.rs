@@ -0,0 +1,71 @@
+//! Categorization of `vk::Result` codes.
+
+use ash::vk;
+
+/// Extension methods of `vk::Result`.
+pub trait VkResultExt: Sized {
+	/// Returns whether the code is a success code, including status codes such as `SUBOPTIMAL_KHR` or `TIMEOUT`.
+	fn is_success(self) -> bool;
+
+	/// Returns whether the code is an error code.
+	fn is_error(self) -> bool;
+
+	/// Returns whether the code signals that the swapchain no longer matches the surface
+	/// and that recreating the swapchain resolves the issue.
+	///
+	/// These are `SUBOPTIMAL_KHR`, `ERROR_OUT_OF_DATE_KHR` and `ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT`.
+	fn is_recoverable_swapchain_issue(self) -> bool;
+
+	/// Returns success codes as `Ok` and converts error codes into crate error `E`.
+	fn to_crate_error<E: From<vk::Result>>(self) -> Result<vk::Result, E>;
+}
+impl VkResultExt for vk::Result {
+	fn is_success(self) -> bool {
+		self.as_raw() >= 0
+	}
+
+	fn is_error(self) -> bool {
+		self.as_raw() < 0
+	}
+
+	fn is_recoverable_swapchain_issue(self) -> bool {
+		matches!(
+			self,
+			vk::Result::SUBOPTIMAL_KHR | vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT
+		)
+	}
+
+	fn to_crate_error<E: From<vk::Result>>(self) -> Result<vk::Result, E> {
+		if self.is_success() {
+			Ok(self)
+		} else {
+			Err(E::from(self))
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use ash::vk;
+
+	use super::VkResultExt;
+
+	#[test]
+	fn result_categories() {
+		assert!(vk::Result::SUCCESS.is_success());
+		assert!(vk::Result::TIMEOUT.is_success());
+		assert!(vk::Result::ERROR_DEVICE_LOST.is_error());
+
+		assert!(vk::Result::SUBOPTIMAL_KHR.is_recoverable_swapchain_issue());
+		assert!(vk::Result::ERROR_OUT_OF_DATE_KHR.is_recoverable_swapchain_issue());
+		assert!(!vk::Result::ERROR_SURFACE_LOST_KHR.is_recoverable_swapchain_issue());
+	}
+
+	#[test]
+	fn unknown_result() {
+		let error = crate::queue::error::QueueWaitError::from(vk::Result::ERROR_FRAGMENTED_POOL);
+
+		assert!(matches!(error, crate::queue::error::QueueWaitError::UNKNOWN(_)));
+		assert_eq!(error.vk_result(), Some(vk::Result::ERROR_FRAGMENTED_POOL));
+	}
+}
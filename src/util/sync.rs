@@ -21,8 +21,12 @@ mod inner {
 
 	/// A type alias to `Arc`.
 	pub type Vrc<T> = std::sync::Arc<T>;
+	/// A type alias to `sync::Weak`, created using `Vrc::downgrade`.
+	pub type Vweak<T> = std::sync::Weak<T>;
 	/// A type alias to `AtomicBool`.
 	pub type AtomicVool = std::sync::atomic::AtomicBool;
+	/// A type alias to `OnceLock`.
+	pub type VOnce<T> = std::sync::OnceLock<T>;
 
 	#[cfg(feature = "parking_lot_vutex")]
 	mod vutex {
@@ -61,6 +65,10 @@ mod inner {
 
 	/// A type alias to `Rc`.
 	pub type Vrc<T> = std::rc::Rc<T>;
+	/// A type alias to `rc::Weak`, created using `Vrc::downgrade`.
+	pub type Vweak<T> = std::rc::Weak<T>;
+	/// A type alias to `OnceCell`.
+	pub type VOnce<T> = std::cell::OnceCell<T>;
 	/// A type that is interface-compatible with `AtomicBool` to be used in single-threaded context.
 	pub struct AtomicVool(pub std::cell::Cell<bool>);
 	impl AtomicVool {